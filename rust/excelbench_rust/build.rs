@@ -1,40 +1,111 @@
+use std::fmt::Write as _;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-fn emit_version(env_key: &str, version: &str) {
-    // Expose resolved dependency versions to the Rust code.
-    println!("cargo:rustc-env={env_key}={version}");
+/// Backends reported by `build_info()`: (cargo feature, report key, crate name).
+const BACKEND_CRATES: &[(&str, &str, &str)] = &[
+    ("calamine", "calamine", "calamine"),
+    ("rust_xlsxwriter", "rust_xlsxwriter", "rust_xlsxwriter"),
+    ("umya", "umya-spreadsheet", "umya-spreadsheet"),
+];
+
+/// A resolved dependency: version plus where it came from.
+struct ResolvedCrate {
+    version: String,
+    source: String,
 }
 
-fn parse_lock_version(lock: &str, name: &str) -> Option<String> {
+fn parse_lock_package(lock: &str, name: &str) -> Option<ResolvedCrate> {
     // Very small Cargo.lock parser: find a [[package]] entry with matching name.
     // This is stable enough for our "what version did we build against?" reporting.
     let needle = format!("name = \"{}\"", name);
-    let mut in_pkg = false;
     let mut saw_name = false;
-    for line in lock.lines() {
-        let line = line.trim();
+    let mut version: Option<String> = None;
+    let mut source: Option<String> = None;
+    for line in lock
+        .lines()
+        .map(str::trim)
+        .chain(std::iter::once("[[package]]"))
+    {
         if line == "[[package]]" {
-            in_pkg = true;
+            if let Some(version) = version.take().filter(|_| saw_name) {
+                return Some(ResolvedCrate {
+                    version,
+                    source: describe_source(source.as_deref()),
+                });
+            }
             saw_name = false;
-            continue;
-        }
-        if !in_pkg {
+            source = None;
             continue;
         }
         if line.starts_with("name = ") {
             saw_name = line == needle;
-        }
-        if saw_name && line.starts_with("version = ") {
-            // version = "0.25.0"
-            if let Some(v) = line.split('"').nth(1) {
-                return Some(v.to_string());
-            }
+        } else if let Some(v) = quoted_value(line, "version") {
+            version = Some(v);
+        } else if let Some(v) = quoted_value(line, "source") {
+            source = Some(v);
         }
     }
     None
 }
 
+/// `key = "value"` → `value`.
+fn quoted_value(line: &str, key: &str) -> Option<String> {
+    let rest = line.strip_prefix(key)?.trim_start().strip_prefix('=')?;
+    rest.split('"').nth(1).map(str::to_string)
+}
+
+/// Shorten a Cargo.lock `source` (registry URL or `git+url#commit`).
+fn describe_source(source: Option<&str>) -> String {
+    match source {
+        None => "path".to_string(),
+        Some(s) if s.starts_with("registry+") => "crates.io".to_string(),
+        Some(s) if s.starts_with("git+") => {
+            let (repo, commit) = s.split_once('#').unwrap_or((s, ""));
+            let repo = repo.split('?').next().unwrap_or(repo);
+            let short = &commit[..commit.len().min(12)];
+            format!("{repo}#{short}")
+        }
+        Some(s) => s.to_string(),
+    }
+}
+
+/// Fallback when no lockfile is reachable: the version requirement declared
+/// in Cargo.toml (e.g. `0.79`), or `git` for git dependencies.
+fn manifest_requirement(manifest: &str, name: &str) -> Option<ResolvedCrate> {
+    // The same key also names a feature (`calamine = ["dep:calamine", ...]`);
+    // only dependency specs parse.
+    let version = manifest.lines().map(str::trim).find_map(|line| {
+        let spec = line
+            .strip_prefix(name)?
+            .trim_start()
+            .strip_prefix('=')?
+            .trim();
+        if spec.starts_with('"') {
+            spec.split('"').nth(1).map(str::to_string)
+        } else if spec.starts_with('{') && spec.contains("git =") {
+            Some("git".to_string())
+        } else if spec.starts_with('{') {
+            quoted_value(&spec[spec.find("version")?..], "version")
+        } else {
+            None
+        }
+    })?;
+    Some(ResolvedCrate {
+        version,
+        source: "Cargo.toml requirement".to_string(),
+    })
+}
+
+/// Nearest Cargo.lock at or above the manifest dir (workspace builds keep it
+/// at the workspace root).
+fn find_lock(manifest_dir: &Path) -> Option<PathBuf> {
+    manifest_dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|p| p.is_file())
+}
+
 fn main() {
     // This crate uses PyO3 and also has a custom build script. When a crate has its own
     // `build.rs`, we must forward PyO3's configuration + linker args ourselves.
@@ -43,24 +114,40 @@ fn main() {
 
     println!("cargo:rerun-if-changed=Cargo.toml");
     println!("cargo:rerun-if-changed=build.rs");
-    println!("cargo:rerun-if-changed=Cargo.lock");
 
-    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").ok();
-    let Some(manifest_dir) = manifest_dir else {
-        return;
-    };
-    let lock_path = PathBuf::from(manifest_dir).join("Cargo.lock");
-    let Ok(lock) = fs::read_to_string(&lock_path) else {
-        return;
-    };
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+    let lock = find_lock(&manifest_dir).and_then(|path| {
+        println!("cargo:rerun-if-changed={}", path.display());
+        fs::read_to_string(path).ok()
+    });
+    let manifest = fs::read_to_string(manifest_dir.join("Cargo.toml")).unwrap_or_default();
 
-    if let Some(v) = parse_lock_version(&lock, "calamine") {
-        emit_version("EXCELBENCH_DEP_CALAMINE_VERSION", &v);
-    }
-    if let Some(v) = parse_lock_version(&lock, "rust_xlsxwriter") {
-        emit_version("EXCELBENCH_DEP_RUST_XLSXWRITER_VERSION", &v);
-    }
-    if let Some(v) = parse_lock_version(&lock, "umya-spreadsheet") {
-        emit_version("EXCELBENCH_DEP_UMYA_VERSION", &v);
+    // Generated into OUT_DIR and `include!`d by lib.rs, so versions are known
+    // without any environment set up by the caller.
+    let mut out = String::from(
+        "// @generated by build.rs\n\
+         /// (report key, resolved version, source) for each backend; None when the\n\
+         /// backend's feature is disabled.\n\
+         pub(crate) const BACKEND_VERSIONS: &[(&str, Option<&str>, Option<&str>)] = &[\n",
+    );
+    for (feature, key, crate_name) in BACKEND_CRATES {
+        let env_key = format!("CARGO_FEATURE_{}", feature.to_uppercase());
+        let resolved = std::env::var_os(env_key).and_then(|_| {
+            lock.as_deref()
+                .and_then(|l| parse_lock_package(l, crate_name))
+                .or_else(|| manifest_requirement(&manifest, crate_name))
+        });
+        let (version, source) = match resolved {
+            Some(r) => (
+                format!("Some({:?})", r.version),
+                format!("Some({:?})", r.source),
+            ),
+            None => ("None".to_string(), "None".to_string()),
+        };
+        let _ = writeln!(out, "    ({key:?}, {version}, {source}),");
     }
+    out.push_str("];\n");
+
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    fs::write(out_dir.join("dep_versions.rs"), out).expect("write dep_versions.rs");
 }
@@ -0,0 +1,86 @@
+//! Approximate column autofit shared by the write backends.
+//!
+//! Widths are in Excel character units (the width of `0` in the default
+//! font). Text is measured with a coarse per-character table rather than
+//! real font metrics, which is close enough for Calibri/Arial 11 reports.
+
+/// Padding Excel adds around the widest value in a column.
+const PADDING: f64 = 1.0;
+/// Excel's own ceiling on column width.
+pub(crate) const MAX_COLUMN_WIDTH: f64 = 255.0;
+
+/// Estimated display width of one character.
+fn char_width(c: char) -> f64 {
+    match c {
+        'i' | 'j' | 'l' | 'I' | '.' | ',' | ':' | ';' | '\'' | '!' | '|' => 0.5,
+        'f' | 'r' | 't' | ' ' | '(' | ')' | '[' | ']' | '-' => 0.7,
+        'm' | 'w' | 'M' | 'W' | '@' | '%' => 1.4,
+        'A'..='Z' => 1.15,
+        // CJK, Hangul and fullwidth forms render at double width.
+        '\u{1100}'..='\u{115F}'
+        | '\u{2E80}'..='\u{A4CF}'
+        | '\u{AC00}'..='\u{D7A3}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF00}'..='\u{FF60}'
+        | '\u{FFE0}'..='\u{FFE6}' => 2.0,
+        _ => 1.0,
+    }
+}
+
+/// Estimated width of `text`; multi-line text is as wide as its longest line.
+pub(crate) fn text_width(text: &str) -> f64 {
+    text.lines()
+        .map(|line| line.chars().map(char_width).sum::<f64>())
+        .fold(0.0, f64::max)
+}
+
+/// Column width for content of the given text width, padded and clamped to
+/// `[min_width, max_width]` (and Excel's 255 limit).
+pub(crate) fn fit_width(content: f64, min_width: Option<f64>, max_width: Option<f64>) -> f64 {
+    let mut width = content + PADDING;
+    if let Some(max) = max_width {
+        width = width.min(max);
+    }
+    if let Some(min) = min_width {
+        width = width.max(min);
+    }
+    width.min(MAX_COLUMN_WIDTH)
+}
+
+/// Convert a character width to pixels at the default font (7px per `0`
+/// plus 5px of cell margin), as rust_xlsxwriter expects.
+pub(crate) fn width_to_pixels(width: f64) -> u16 {
+    if width < 1.0 {
+        (width * 12.0).round() as u16
+    } else {
+        (width * 7.0 + 5.0).round() as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_width() {
+        assert_eq!(text_width(""), 0.0);
+        assert_eq!(text_width("0000"), 4.0);
+        assert!(text_width("WWW") > text_width("iii") * 2.0);
+        assert_eq!(text_width("ab\nabcd\nc"), 4.0);
+        assert_eq!(text_width("日本"), 4.0);
+    }
+
+    #[test]
+    fn test_fit_width_clamps() {
+        assert_eq!(fit_width(10.0, None, None), 11.0);
+        assert_eq!(fit_width(10.0, None, Some(8.0)), 8.0);
+        assert_eq!(fit_width(2.0, Some(6.0), None), 6.0);
+        assert_eq!(fit_width(400.0, None, None), MAX_COLUMN_WIDTH);
+    }
+
+    #[test]
+    fn test_width_to_pixels() {
+        assert_eq!(width_to_pixels(8.43), 64);
+        assert_eq!(width_to_pixels(0.5), 6);
+    }
+}
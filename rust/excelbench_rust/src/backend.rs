@@ -0,0 +1,163 @@
+//! Common Rust-side interface over the backend classes.
+//!
+//! Every backend module implements `Backend` (plus `ExcelReadBackend` and/or
+//! `ExcelWriteBackend`) next to its pyclass, so generic Rust code can drive
+//! any backend without its own per-class glue. `open_backend()` exposes the
+//! registry of compiled-in backends to Python.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Value-level read access shared by every reader class.
+#[allow(dead_code)] // Driven by generic Rust callers rather than Python
+pub(crate) trait ExcelReadBackend {
+    fn sheet_names(&self) -> PyResult<Vec<String>>;
+    /// Same payload dict as the class's Python `read_cell_value`.
+    fn read_cell_value(&mut self, py: Python<'_>, sheet: &str, a1: &str) -> PyResult<PyObject>;
+    /// 0-based (row, col) of every non-empty or formula cell, row-major.
+    fn used_cells(&mut self, py: Python<'_>, sheet: &str) -> PyResult<Vec<(u32, u32)>>;
+    /// Format dict for the cell, or None when the class does not read styles.
+    fn read_cell_format(
+        &mut self,
+        _py: Python<'_>,
+        _sheet: &str,
+        _a1: &str,
+    ) -> PyResult<Option<PyObject>> {
+        Ok(None)
+    }
+    /// Border dict for the cell, or None when the class does not read styles.
+    fn read_cell_border(
+        &mut self,
+        _py: Python<'_>,
+        _sheet: &str,
+        _a1: &str,
+    ) -> PyResult<Option<PyObject>> {
+        Ok(None)
+    }
+}
+
+/// Cell-level write access shared by every writer/modifier class.
+///
+/// Payload, format and border dicts use the ExcelBench shapes accepted by the
+/// classes' Python `write_cell_*` methods.
+#[allow(dead_code)] // Driven by generic Rust callers rather than Python
+pub(crate) trait ExcelWriteBackend {
+    fn add_sheet(&mut self, name: &str) -> PyResult<()>;
+    fn write_cell_value(
+        &mut self,
+        sheet: &str,
+        a1: &str,
+        payload: &Bound<'_, PyAny>,
+    ) -> PyResult<()>;
+    fn write_cell_format(
+        &mut self,
+        sheet: &str,
+        a1: &str,
+        format_dict: &Bound<'_, PyAny>,
+    ) -> PyResult<()>;
+    fn write_cell_border(
+        &mut self,
+        sheet: &str,
+        a1: &str,
+        border_dict: &Bound<'_, PyAny>,
+    ) -> PyResult<()>;
+    fn save(&mut self, py: Python<'_>, path: &str) -> PyResult<()>;
+}
+
+/// An opened backend instance, before it is handed to Python.
+///
+/// `as_reader` / `as_writer` give generic Rust code typed access to it.
+#[allow(dead_code)] // The typed views are unused by the Python factory
+pub(crate) trait Backend {
+    fn into_py_object(self: Box<Self>, py: Python<'_>) -> PyResult<PyObject>;
+    fn as_reader(&mut self) -> Option<&mut dyn ExcelReadBackend> {
+        None
+    }
+    fn as_writer(&mut self) -> Option<&mut dyn ExcelWriteBackend> {
+        None
+    }
+}
+
+/// Wrap a pyclass value as a Python object (shared by the `Backend` impls).
+pub(crate) fn pyclass_object<T>(py: Python<'_>, value: T) -> PyResult<PyObject>
+where
+    T: pyo3::PyClass + Into<pyo3::PyClassInitializer<T>>,
+{
+    Ok(Py::new(py, value)?.into_any())
+}
+
+/// One registry entry: the adapter name and how to open it.
+///
+/// `path` is the workbook to open; None asks for a new, empty workbook.
+pub(crate) struct BackendEntry {
+    pub name: &'static str,
+    pub open: fn(Python<'_>, Option<&str>) -> PyResult<Box<dyn Backend>>,
+}
+
+fn registry() -> Vec<BackendEntry> {
+    #[allow(unused_mut)]
+    let mut out: Vec<BackendEntry> = Vec::new();
+    #[cfg(feature = "calamine")]
+    {
+        out.push(crate::calamine_backend::BACKEND);
+        out.push(crate::calamine_styled_backend::BACKEND);
+    }
+    #[cfg(feature = "rust_xlsxwriter")]
+    out.push(crate::rust_xlsxwriter_backend::BACKEND);
+    #[cfg(feature = "umya")]
+    out.push(crate::umya::BACKEND);
+    #[cfg(feature = "wolfxl")]
+    {
+        out.push(crate::wolfxl::BACKEND);
+        out.push(crate::wolfxl::reader::BACKEND);
+    }
+    out
+}
+
+/// Open `name` from the registry as a boxed `Backend`.
+pub(crate) fn open_named(
+    py: Python<'_>,
+    name: &str,
+    path: Option<&str>,
+) -> PyResult<Box<dyn Backend>> {
+    let entries = registry();
+    match entries.iter().find(|e| e.name == name) {
+        Some(entry) => (entry.open)(py, path),
+        None => {
+            let known: Vec<&str> = entries.iter().map(|e| e.name).collect();
+            Err(PyErr::new::<PyValueError, _>(format!(
+                "Unknown backend '{name}' (available: {})",
+                known.join(", ")
+            )))
+        }
+    }
+}
+
+/// `path` for backends that can only open existing files.
+pub(crate) fn require_path<'a>(name: &str, path: Option<&'a str>) -> PyResult<&'a str> {
+    path.ok_or_else(|| {
+        PyErr::new::<PyValueError, _>(format!("Backend '{name}' requires a path to open"))
+    })
+}
+
+/// Reject `path` for write-only backends that always start from scratch.
+pub(crate) fn reject_path(name: &str, path: Option<&str>) -> PyResult<()> {
+    match path {
+        Some(_) => Err(PyErr::new::<PyValueError, _>(format!(
+            "Backend '{name}' only creates new workbooks; pass path=None"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Open a backend class by adapter name.
+///
+/// Names match the Python adapters (`"calamine"`, `"calamine-styled"`,
+/// `"rust_xlsxwriter"`, `"umya-spreadsheet"`, `"wolfxl"`, `"wolfxl-read"`).
+/// With `path`, the workbook is opened; without it, writers start a new
+/// workbook. Returns the same object as calling the class directly.
+#[pyfunction]
+#[pyo3(signature = (name, path=None))]
+pub(crate) fn open_backend(py: Python<'_>, name: &str, path: Option<&str>) -> PyResult<PyObject> {
+    open_named(py, name, path)?.into_py_object(py)
+}
@@ -0,0 +1,444 @@
+//! Columnar exports for `CalamineBook`: column dicts, Arrow RecordBatch and numpy arrays.
+//!
+//! These bypass the dict-per-cell payload entirely so pandas/polars ingestion
+//! pays one FFI crossing per sheet instead of one per cell.
+
+use std::collections::HashMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDate, PyDateTime, PyDict, PyList};
+
+use calamine::{Data, Range};
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Timelike};
+
+use crate::calamine_backend::CalamineBook;
+use crate::cell_ref::col_to_letters;
+use crate::util::{excel_serial_to_datetime, parse_iso_date, parse_iso_datetime};
+
+fn data_as_f64(value: &Data) -> Option<f64> {
+    match value {
+        Data::Float(f) => Some(*f),
+        Data::Int(i) => Some(*i as f64),
+        Data::DateTime(dt) => Some(dt.as_f64()),
+        _ => None,
+    }
+}
+
+/// Date/time value of a cell (Excel serials and ISO strings alike).
+fn cell_datetime(value: &Data, date1904: bool) -> Option<NaiveDateTime> {
+    match value {
+        Data::DateTime(dt) => excel_serial_to_datetime(dt.as_f64(), date1904),
+        Data::DateTimeIso(s) => parse_iso_date(s)
+            .map(|d| d.and_time(NaiveTime::MIN))
+            .or_else(|| parse_iso_datetime(s)),
+        _ => None,
+    }
+}
+
+/// Text rendering for string columns and header cells (dates as ISO, not serials).
+fn cell_text(value: &Data, date1904: bool) -> String {
+    match cell_datetime(value, date1904) {
+        Some(ndt) if ndt.time() == NaiveTime::MIN => ndt.date().format("%Y-%m-%d").to_string(),
+        Some(ndt) => ndt.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// Inferred type for one worksheet column.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ColKind {
+    Empty,
+    Bool,
+    Int,
+    Float,
+    Date,
+    DateTime,
+    Utf8,
+}
+
+fn kind_of(value: &Data, date1904: bool) -> ColKind {
+    match value {
+        Data::Empty => ColKind::Empty,
+        Data::Bool(_) => ColKind::Bool,
+        Data::Int(_) => ColKind::Int,
+        Data::Float(_) => ColKind::Float,
+        other => match cell_datetime(other, date1904) {
+            Some(ndt) if ndt.time() == NaiveTime::MIN => ColKind::Date,
+            Some(_) => ColKind::DateTime,
+            None => ColKind::Utf8,
+        },
+    }
+}
+
+fn merge(a: ColKind, b: ColKind) -> ColKind {
+    use ColKind::*;
+    match (a, b) {
+        (x, Empty) | (Empty, x) => x,
+        (x, y) if x == y => x,
+        (Int, Float) | (Float, Int) => Float,
+        (Date, DateTime) | (DateTime, Date) => DateTime,
+        _ => Utf8,
+    }
+}
+
+fn infer_kind(cells: &[&Data], date1904: bool) -> ColKind {
+    cells
+        .iter()
+        .fold(ColKind::Empty, |acc, v| merge(acc, kind_of(v, date1904)))
+}
+
+fn dtype_name(kind: ColKind) -> &'static str {
+    match kind {
+        ColKind::Empty => "empty",
+        ColKind::Bool => "bool",
+        ColKind::Int => "int",
+        ColKind::Float => "float",
+        ColKind::Date => "date",
+        ColKind::DateTime => "datetime",
+        ColKind::Utf8 => "string",
+    }
+}
+
+/// Split a worksheet range into named columns.
+///
+/// `header_row` is a 0-based absolute row whose cells name the columns; data
+/// starts on the following row. Without it, columns are named by letter and
+/// data starts at the first row of the used range. Repeated header names get
+/// pandas-style `.1`, `.2` suffixes.
+fn split_columns(
+    range: &Range<Data>,
+    header_row: Option<u32>,
+    date1904: bool,
+) -> Vec<(String, Vec<&Data>)> {
+    static EMPTY: Data = Data::Empty;
+
+    let (h, w) = range.get_size();
+    if h == 0 || w == 0 {
+        return Vec::new();
+    }
+    let (r0, c0) = range.start().unwrap_or((0, 0));
+    let last_row = r0 + h as u32;
+    let first_data_row = header_row.map_or(r0, |hr| (hr + 1).max(r0));
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    (0..w as u32)
+        .map(|j| {
+            let col = c0 + j;
+            let base = match header_row.and_then(|hr| range.get_value((hr, col))) {
+                Some(Data::Empty) | None => col_to_letters(col),
+                Some(v) => cell_text(v, date1904),
+            };
+            let n = seen.entry(base.clone()).or_insert(0);
+            let name = if *n == 0 { base } else { format!("{base}.{n}") };
+            *n += 1;
+
+            let cells = (first_data_row..last_row)
+                .map(|r| range.get_value((r, col)).unwrap_or(&EMPTY))
+                .collect();
+            (name, cells)
+        })
+        .collect()
+}
+
+fn column_value_to_py(
+    py: Python<'_>,
+    kind: ColKind,
+    value: &Data,
+    date1904: bool,
+) -> PyResult<PyObject> {
+    if matches!(value, Data::Empty) {
+        return Ok(py.None());
+    }
+    let obj = match kind {
+        ColKind::Bool => match value {
+            Data::Bool(b) => b.into_pyobject(py)?.to_owned().into_any().unbind(),
+            _ => py.None(),
+        },
+        ColKind::Int => match value {
+            Data::Int(i) => i.into_pyobject(py)?.into_any().unbind(),
+            _ => py.None(),
+        },
+        ColKind::Float => match data_as_f64(value) {
+            Some(f) => f.into_pyobject(py)?.into_any().unbind(),
+            None => py.None(),
+        },
+        ColKind::Date => match cell_datetime(value, date1904) {
+            Some(ndt) => {
+                let d = ndt.date();
+                PyDate::new(py, d.year(), d.month() as u8, d.day() as u8)?
+                    .into_any()
+                    .unbind()
+            }
+            None => py.None(),
+        },
+        ColKind::DateTime => match cell_datetime(value, date1904) {
+            Some(ndt) => PyDateTime::new(
+                py,
+                ndt.year(),
+                ndt.month() as u8,
+                ndt.day() as u8,
+                ndt.hour() as u8,
+                ndt.minute() as u8,
+                ndt.second() as u8,
+                ndt.nanosecond() / 1_000,
+                None,
+            )?
+            .into_any()
+            .unbind(),
+            None => py.None(),
+        },
+        ColKind::Empty | ColKind::Utf8 => cell_text(value, date1904)
+            .into_pyobject(py)?
+            .into_any()
+            .unbind(),
+    };
+    Ok(obj)
+}
+
+#[pymethods]
+impl CalamineBook {
+    /// Read a sheet as pandas-friendly columns with per-column type inference.
+    ///
+    /// Returns `{"columns": {name: [values]}, "dtypes": {name: dtype}}`. Each
+    /// list is homogeneous with `None` for blanks; dtype is one of "int",
+    /// "float", "bool", "date", "datetime", "string" or "empty". Int/float
+    /// columns widen to float, date/datetime to datetime, anything else mixed
+    /// to string. `header_row` is 0-based; pass None to name columns by letter.
+    #[pyo3(signature = (sheet, header_row=Some(0)))]
+    pub fn read_sheet_columns(
+        &mut self,
+        py: Python<'_>,
+        sheet: &str,
+        header_row: Option<u32>,
+    ) -> PyResult<PyObject> {
+        let date1904 = self.date1904();
+        let range = self.cached_range(sheet)?;
+
+        let columns = PyDict::new(py);
+        let dtypes = PyDict::new(py);
+        for (name, cells) in split_columns(range, header_row, date1904) {
+            let kind = infer_kind(&cells, date1904);
+            let values = PyList::empty(py);
+            for v in &cells {
+                values.append(column_value_to_py(py, kind, v, date1904)?)?;
+            }
+            columns.set_item(&name, values)?;
+            dtypes.set_item(&name, dtype_name(kind))?;
+        }
+
+        let out = PyDict::new(py);
+        out.set_item("columns", columns)?;
+        out.set_item("dtypes", dtypes)?;
+        Ok(out.into())
+    }
+}
+
+#[cfg(feature = "arrow")]
+mod arrow_export {
+    use std::sync::Arc;
+
+    use arrow::array::{
+        ArrayRef, BooleanBuilder, Float64Builder, Int64Builder, StringBuilder,
+        TimestampMillisecondBuilder,
+    };
+    use arrow::datatypes::{DataType as ArrowType, Field, Schema, TimeUnit};
+    use arrow::record_batch::RecordBatch;
+    use calamine::DataType;
+
+    use super::*;
+
+    fn build_column(kind: ColKind, cells: &[&Data], date1904: bool) -> ArrayRef {
+        match kind {
+            ColKind::Bool => {
+                let mut b = BooleanBuilder::with_capacity(cells.len());
+                for v in cells {
+                    b.append_option(v.get_bool());
+                }
+                Arc::new(b.finish())
+            }
+            ColKind::Int => {
+                let mut b = Int64Builder::with_capacity(cells.len());
+                for v in cells {
+                    b.append_option(v.get_int());
+                }
+                Arc::new(b.finish())
+            }
+            ColKind::Float => {
+                let mut b = Float64Builder::with_capacity(cells.len());
+                for v in cells {
+                    b.append_option(data_as_f64(v));
+                }
+                Arc::new(b.finish())
+            }
+            ColKind::Date | ColKind::DateTime => {
+                let mut b = TimestampMillisecondBuilder::with_capacity(cells.len());
+                for v in cells {
+                    b.append_option(
+                        cell_datetime(v, date1904).map(|ndt| ndt.and_utc().timestamp_millis()),
+                    );
+                }
+                Arc::new(b.finish())
+            }
+            ColKind::Empty | ColKind::Utf8 => {
+                let mut b = StringBuilder::with_capacity(cells.len(), cells.len() * 8);
+                for v in cells {
+                    match v {
+                        Data::Empty => b.append_null(),
+                        other => b.append_value(cell_text(other, date1904)),
+                    }
+                }
+                Arc::new(b.finish())
+            }
+        }
+    }
+
+    fn arrow_type(kind: ColKind) -> ArrowType {
+        match kind {
+            ColKind::Bool => ArrowType::Boolean,
+            ColKind::Int => ArrowType::Int64,
+            ColKind::Float => ArrowType::Float64,
+            ColKind::Date | ColKind::DateTime => ArrowType::Timestamp(TimeUnit::Millisecond, None),
+            ColKind::Empty | ColKind::Utf8 => ArrowType::Utf8,
+        }
+    }
+
+    /// Build a RecordBatch from a worksheet range; `header_row` as in
+    /// `split_columns`.
+    pub(crate) fn range_to_record_batch(
+        range: &Range<Data>,
+        header_row: Option<u32>,
+        date1904: bool,
+    ) -> PyResult<RecordBatch> {
+        let columns = split_columns(range, header_row, date1904);
+        let mut fields = Vec::with_capacity(columns.len());
+        let mut arrays: Vec<ArrayRef> = Vec::with_capacity(columns.len());
+        for (name, cells) in columns {
+            let kind = infer_kind(&cells, date1904);
+            fields.push(Field::new(name, arrow_type(kind), true));
+            arrays.push(build_column(kind, &cells, date1904));
+        }
+
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+            .map_err(|e| PyErr::new::<PyValueError, _>(format!("Arrow error: {e}")))
+    }
+}
+
+#[cfg(feature = "arrow")]
+#[pymethods]
+impl CalamineBook {
+    /// Read a sheet as a `pyarrow.RecordBatch` with per-column type inference.
+    ///
+    /// Columns holding only ints/floats/bools/dates get a matching Arrow type;
+    /// mixed columns fall back to strings. Empty cells become nulls.
+    /// `header_row` is 0-based, as in `read_sheet_columns()`; pass None to
+    /// name columns by letter.
+    #[pyo3(signature = (sheet, header_row=Some(0)))]
+    pub fn read_sheet_arrow(
+        &mut self,
+        py: Python<'_>,
+        sheet: &str,
+        header_row: Option<u32>,
+    ) -> PyResult<PyObject> {
+        use arrow::pyarrow::ToPyArrow;
+
+        let date1904 = self.date1904();
+        let range = self.cached_range(sheet)?;
+        let batch = arrow_export::range_to_record_batch(range, header_row, date1904)?;
+        batch.to_pyarrow(py)
+    }
+}
+
+#[cfg(feature = "parquet")]
+#[pymethods]
+impl CalamineBook {
+    /// Write a sheet to a Snappy-compressed Parquet file.
+    ///
+    /// Column names and types follow `read_sheet_columns()`: `header_row` is
+    /// 0-based (None names columns by letter), homogeneous columns keep their
+    /// type and mixed ones become strings. The batch is built and written with
+    /// the GIL released. Returns the number of data rows written.
+    #[pyo3(signature = (sheet, path, header_row=Some(0)))]
+    pub fn export_parquet(
+        &mut self,
+        py: Python<'_>,
+        sheet: &str,
+        path: &str,
+        header_row: Option<u32>,
+    ) -> PyResult<usize> {
+        use parquet::arrow::ArrowWriter;
+        use parquet::basic::Compression;
+        use parquet::file::properties::WriterProperties;
+        use pyo3::exceptions::PyIOError;
+
+        let parquet_err = |e: parquet::errors::ParquetError| {
+            PyErr::new::<PyIOError, _>(format!("Parquet error: {e}"))
+        };
+
+        let date1904 = self.date1904();
+        let range = self.cached_range(sheet)?;
+        let file = std::fs::File::create(path).map_err(|e| {
+            PyErr::new::<PyIOError, _>(format!("Failed to create Parquet file: {e}"))
+        })?;
+        py.allow_threads(|| {
+            let batch = arrow_export::range_to_record_batch(range, header_row, date1904)?;
+            let props = WriterProperties::builder()
+                .set_compression(Compression::SNAPPY)
+                .build();
+            let mut writer =
+                ArrowWriter::try_new(file, batch.schema(), Some(props)).map_err(parquet_err)?;
+            writer.write(&batch).map_err(parquet_err)?;
+            writer.close().map_err(parquet_err)?;
+            Ok(batch.num_rows())
+        })
+    }
+}
+
+#[cfg(feature = "numpy")]
+#[pymethods]
+impl CalamineBook {
+    /// Read a numeric block as a 2-D float64 numpy array.
+    ///
+    /// Covers the sheet's used range, or `cell_range` when given. Non-numeric
+    /// and empty cells become NaN; dates are reported as Excel serials.
+    #[pyo3(signature = (sheet, cell_range=None))]
+    pub fn read_sheet_numpy<'py>(
+        &mut self,
+        py: Python<'py>,
+        sheet: &str,
+        cell_range: Option<&str>,
+    ) -> PyResult<Bound<'py, numpy::PyArray2<f64>>> {
+        use numpy::ndarray::Array2;
+        use numpy::IntoPyArray;
+
+        let range = self.cached_range(sheet)?;
+        let (r0, c0, r1, c1) = match cell_range {
+            Some(cr) if !cr.is_empty() => crate::calamine_backend::parse_a1_bounds(cr)?,
+            _ => {
+                let (h, w) = range.get_size();
+                if h == 0 || w == 0 {
+                    return Ok(Array2::<f64>::zeros((0, 0)).into_pyarray(py));
+                }
+                let (r0, c0) = range.start().unwrap_or((0, 0));
+                (r0, c0, r0 + h as u32 - 1, c0 + w as u32 - 1)
+            }
+        };
+
+        let rows = (r1 - r0 + 1) as usize;
+        let cols = (c1 - c0 + 1) as usize;
+        let mut values = Vec::with_capacity(rows * cols);
+        for r in r0..=r1 {
+            for c in c0..=c1 {
+                values.push(
+                    range
+                        .get_value((r, c))
+                        .and_then(data_as_f64)
+                        .unwrap_or(f64::NAN),
+                );
+            }
+        }
+        let arr = Array2::from_shape_vec((rows, cols), values)
+            .map_err(|e| PyErr::new::<PyValueError, _>(format!("numpy shape error: {e}")))?;
+        Ok(arr.into_pyarray(py))
+    }
+}
@@ -2,7 +2,7 @@ use pyo3::exceptions::{PyIOError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs::File;
 use std::io::BufReader;
 
@@ -14,12 +14,24 @@ use calamine::{
 use calamine::{Data, Range, Reader, Xlsx};
 use chrono::NaiveTime;
 
-use quick_xml::events::Event;
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader as XmlReader;
 use zip::ZipArchive;
 
-use crate::ooxml_util;
-use crate::util::{a1_to_row_col, cell_blank, cell_with_value, parse_iso_date, parse_iso_datetime};
+use crate::backend::{self, pyclass_object, Backend, BackendEntry, ExcelReadBackend};
+use crate::capabilities::BackendCapabilities;
+use crate::cell_ref::letters_to_col;
+use crate::color::{ColorSpec, Palette};
+use crate::errors;
+use crate::formula;
+use crate::numfmt;
+use crate::ooxml_util::fills::{FillDef, StyleFills};
+use crate::ooxml_util::{self, CommentInfo};
+use crate::profile;
+use crate::util::{
+    a1_to_row_col, cell_blank, cell_with_value, excel_serial_to_datetime, parse_iso_date,
+    parse_iso_datetime,
+};
 
 fn map_error_value(err_str: &str) -> &'static str {
     let e = err_str.to_ascii_uppercase();
@@ -133,7 +145,7 @@ fn strip_excel_padding(raw: f64) -> f64 {
     (raw * 10000.0).round() / 10000.0
 }
 
-fn data_to_py(py: Python<'_>, value: &Data) -> PyResult<PyObject> {
+fn data_to_py(py: Python<'_>, value: &Data, date1904: bool) -> PyResult<PyObject> {
     match value {
         Data::Empty => cell_blank(py),
         Data::String(s) => cell_with_value(py, "string", s.clone()),
@@ -141,7 +153,7 @@ fn data_to_py(py: Python<'_>, value: &Data) -> PyResult<PyObject> {
         Data::Int(i) => cell_with_value(py, "number", *i as f64),
         Data::Bool(b) => cell_with_value(py, "boolean", *b),
         Data::DateTime(dt) => {
-            if let Some(ndt) = dt.as_datetime() {
+            if let Some(ndt) = excel_serial_to_datetime(dt.as_f64(), date1904) {
                 let midnight = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
                 if ndt.time() == midnight {
                     let s = ndt.date().format("%Y-%m-%d").to_string();
@@ -207,14 +219,6 @@ struct HyperlinkNode {
     tooltip: Option<String>,
 }
 
-#[derive(Clone, Debug)]
-struct CommentInfo {
-    cell: String,
-    text: String,
-    author: String,
-    threaded: bool,
-}
-
 #[derive(Clone, Debug)]
 struct FreezePaneInfo {
     mode: String,
@@ -251,7 +255,11 @@ struct DataValidationInfo {
 struct NamedRangeInfo {
     name: String,
     scope: String,
+    /// Owning sheet for sheet-scoped names (from `localSheetId`).
+    sheet: Option<String>,
     refers_to: String,
+    /// `_xlnm.*` names Excel reserves for itself (print area, filter range).
+    builtin: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -286,9 +294,41 @@ struct Tier2SheetCache {
     cell_style_ids: Option<HashMap<(u32, u32), u32>>,
 }
 
-#[pyclass(unsendable)]
+pub(crate) const CAPABILITIES: BackendCapabilities = BackendCapabilities {
+    class: "CalamineStyledBook",
+    backend: "calamine",
+    extensions: &[".xlsx", ".xlsm"],
+    read: &[
+        "cell_values",
+        "formulas",
+        "text_formatting",
+        "background_colors",
+        "number_formats",
+        "alignment",
+        "borders",
+        "dimensions",
+        "multiple_sheets",
+        "merged_cells",
+        "conditional_formatting",
+        "data_validation",
+        "hyperlinks",
+        "comments",
+        "freeze_panes",
+        "named_ranges",
+        "tables",
+    ],
+    write: &[],
+    modify: false,
+};
+
+/// The reader owns its file handle and caches outright, so the class is
+/// `Send + Sync`. Most read methods take `&mut self` to fill the lazy
+/// caches, which means PyO3's borrow flag admits one caller at a time; share
+/// a book across threads behind a Python lock, or open one per thread.
+#[pyclass]
 pub struct CalamineStyledBook {
-    workbook: XlsxReader,
+    /// None once `close()` has released the reader and its file handle.
+    workbook: Option<XlsxReader>,
     sheet_names: Vec<String>,
     /// Cache of StyleRange per sheet name, populated lazily on first format/border read.
     style_cache: HashMap<String, SheetCache>,
@@ -300,8 +340,17 @@ pub struct CalamineStyledBook {
     tier2_cache: HashMap<String, Tier2SheetCache>,
     /// Lazy cache: background fill colors for styles.xml <dxfs> list (by index).
     dxfs_bg_colors: Option<Vec<Option<String>>>,
+    /// Lazy cache: theme + indexed colors for resolving styles.xml colors.
+    palette: Option<Palette>,
+    /// Lazy cache: pattern/gradient fills by cellXfs style_id.
+    style_fills: Option<StyleFills>,
     /// Lazy cache: named ranges parsed from workbook.xml definedNames.
     named_ranges: Option<Vec<NamedRangeInfo>>,
+    /// Lazy cache: numFmtId per cellXfs entry (by style_id).
+    cellxfs_num_fmt_ids: Option<Vec<u32>>,
+    /// Lazy cache: (locked, hidden) per cellXfs entry, None for entries
+    /// without a `<protection>` element.
+    cellxfs_protection: Option<Vec<Option<(bool, bool)>>>,
     /// Lazy cache: diagonal border definitions (by cellXfs style_id).
     diagonal_borders: Option<HashMap<u32, DiagonalBorderInfo>>,
     /// Cache: worksheet value ranges (avoids re-cloning on every per-cell read).
@@ -311,31 +360,108 @@ pub struct CalamineStyledBook {
     formula_map_cache: HashMap<String, HashMap<(u32, u32), String>>,
     /// Cache: raw sheet XML content (avoids re-opening zip for Tier 2 + formula parsing).
     sheet_xml_content_cache: HashMap<String, String>,
+    /// Lazy cache: workbook uses the 1904 date system (`workbookPr date1904`).
+    date1904: Option<bool>,
+}
+
+pub(crate) const BACKEND: BackendEntry = BackendEntry {
+    name: "calamine-styled",
+    open: |py, path| {
+        let path = backend::require_path("calamine-styled", path)?;
+        Ok(Box::new(CalamineStyledBook::open(py, path)?))
+    },
+};
+
+impl Backend for CalamineStyledBook {
+    fn into_py_object(self: Box<Self>, py: Python<'_>) -> PyResult<PyObject> {
+        pyclass_object(py, *self)
+    }
+
+    fn as_reader(&mut self) -> Option<&mut dyn ExcelReadBackend> {
+        Some(self)
+    }
+}
+
+impl ExcelReadBackend for CalamineStyledBook {
+    fn sheet_names(&self) -> PyResult<Vec<String>> {
+        Ok(self.sheet_names.clone())
+    }
+
+    fn read_cell_value(&mut self, py: Python<'_>, sheet: &str, a1: &str) -> PyResult<PyObject> {
+        CalamineStyledBook::read_cell_value(self, py, sheet, a1)
+    }
+
+    fn used_cells(&mut self, _py: Python<'_>, sheet: &str) -> PyResult<Vec<(u32, u32)>> {
+        self.ensure_sheet_exists(sheet)?;
+        self.ensure_value_caches(sheet)?;
+        let range = &self.range_cache[sheet];
+        let (r0, c0) = range.start().unwrap_or((0, 0));
+        let mut cells: BTreeSet<(u32, u32)> = range
+            .used_cells()
+            .map(|(r, c, _)| (r0 + r as u32, c0 + c as u32))
+            .collect();
+        cells.extend(self.formula_map_cache[sheet].keys().copied());
+        Ok(cells.into_iter().collect())
+    }
+
+    fn read_cell_format(
+        &mut self,
+        py: Python<'_>,
+        sheet: &str,
+        a1: &str,
+    ) -> PyResult<Option<PyObject>> {
+        CalamineStyledBook::read_cell_format(self, py, sheet, a1).map(Some)
+    }
+
+    fn read_cell_border(
+        &mut self,
+        py: Python<'_>,
+        sheet: &str,
+        a1: &str,
+    ) -> PyResult<Option<PyObject>> {
+        CalamineStyledBook::read_cell_border(self, py, sheet, a1).map(Some)
+    }
 }
 
 #[pymethods]
 impl CalamineStyledBook {
     #[staticmethod]
-    pub fn open(path: &str) -> PyResult<Self> {
-        let file = File::open(path)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to open file: {e}")))?;
+    pub fn open(py: Python<'_>, path: &str) -> PyResult<Self> {
+        let _span = profile::span("calamine_styled.parse");
+        let file = File::open(path).map_err(|e| {
+            errors::file_format(
+                CAPABILITIES.backend,
+                path,
+                format!("Failed to open file: {e}"),
+            )
+        })?;
         let reader = BufReader::new(file);
-        let wb: XlsxReader = Xlsx::new(reader)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to parse xlsx: {e}")))?;
+        let wb: XlsxReader = py.allow_threads(|| Xlsx::new(reader)).map_err(|e| {
+            errors::file_format(
+                CAPABILITIES.backend,
+                path,
+                format!("Failed to parse xlsx: {e}"),
+            )
+        })?;
         let names = wb.sheet_names().to_vec();
         Ok(Self {
-            workbook: wb,
+            workbook: Some(wb),
             sheet_names: names,
             style_cache: HashMap::new(),
             file_path: path.to_string(),
             sheet_xml_paths: None,
             tier2_cache: HashMap::new(),
             dxfs_bg_colors: None,
+            palette: None,
+            style_fills: None,
             named_ranges: None,
+            cellxfs_num_fmt_ids: None,
+            cellxfs_protection: None,
             diagonal_borders: None,
             range_cache: HashMap::new(),
             formula_map_cache: HashMap::new(),
             sheet_xml_content_cache: HashMap::new(),
+            date1904: None,
         })
     }
 
@@ -343,11 +469,52 @@ impl CalamineStyledBook {
         self.sheet_names.clone()
     }
 
+    /// Release the file handle and every lazy cache. Later reads raise
+    /// ValueError; closing twice is a no-op.
+    pub fn close(&mut self) {
+        self.workbook = None;
+        self.style_cache.clear();
+        self.sheet_xml_paths = None;
+        self.tier2_cache.clear();
+        self.dxfs_bg_colors = None;
+        self.palette = None;
+        self.style_fills = None;
+        self.named_ranges = None;
+        self.cellxfs_num_fmt_ids = None;
+        self.cellxfs_protection = None;
+        self.diagonal_borders = None;
+        self.range_cache.clear();
+        self.formula_map_cache.clear();
+        self.sheet_xml_content_cache.clear();
+    }
+
+    pub fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (_exc_type=None, _exc_value=None, _traceback=None))]
+    pub fn __exit__(
+        &mut self,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> bool {
+        self.close();
+        false
+    }
+
+    /// True if the workbook uses the 1904 date system.
+    pub fn date1904(&mut self) -> PyResult<bool> {
+        self.ensure_date1904()
+    }
+
     pub fn read_cell_value(&mut self, py: Python<'_>, sheet: &str, a1: &str) -> PyResult<PyObject> {
-        let (row, col) = a1_to_row_col(a1).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
+        let (row, col) =
+            a1_to_row_col(a1).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a1, msg))?;
 
         self.ensure_sheet_exists(sheet)?;
         self.ensure_value_caches(sheet)?;
+        let date1904 = self.ensure_date1904()?;
 
         let range = self.range_cache.get(sheet).unwrap();
 
@@ -359,11 +526,7 @@ impl CalamineStyledBook {
         // Check the fast formula map (parsed from worksheet XML in a single pass).
         if let Some(fmap) = self.formula_map_cache.get(sheet) {
             if let Some(f) = fmap.get(&(row, col)) {
-                let formula = if f.starts_with('=') {
-                    f.clone()
-                } else {
-                    format!("={f}")
-                };
+                let formula = formula::with_equals(f);
 
                 if let Some(err_val) = map_error_formula(&formula) {
                     let d = PyDict::new(py);
@@ -380,7 +543,58 @@ impl CalamineStyledBook {
             }
         }
 
-        data_to_py(py, value)
+        data_to_py(py, value, date1904)
+    }
+
+    /// The cell's value as Excel displays it through its number format
+    /// (`"1,234.50"`, `"(12.5%)"`, `"Mar 5, 2024"`); `""` for an empty cell.
+    /// Formula cells show their cached result. `locale` (`"de-DE"`, `"fr"`)
+    /// sets the decimal and thousands separators.
+    #[pyo3(signature = (sheet, a1, locale=None))]
+    pub fn format_cell_display(
+        &mut self,
+        sheet: &str,
+        a1: &str,
+        locale: Option<&str>,
+    ) -> PyResult<String> {
+        let locale = locale
+            .map(numfmt::Locale::from_tag)
+            .transpose()
+            .map_err(PyErr::new::<PyValueError, _>)?
+            .unwrap_or_default();
+        let (row, col) =
+            a1_to_row_col(a1).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a1, msg))?;
+
+        self.ensure_sheet_exists(sheet)?;
+        self.ensure_value_caches(sheet)?;
+        let date1904 = self.ensure_date1904()?;
+        let code = self.cell_number_format(sheet, row, col)?;
+
+        let range = self.range_cache.get(sheet).unwrap();
+        let Some(value) = range.get_value((row, col)) else {
+            return Ok(String::new());
+        };
+        let text: String;
+        let value = match value {
+            Data::Empty => return Ok(String::new()),
+            Data::String(s) | Data::DurationIso(s) => numfmt::CellValue::Text(s),
+            Data::RichText(rt) => {
+                text = rt.plain_text().to_string();
+                numfmt::CellValue::Text(&text)
+            }
+            Data::Float(f) => numfmt::CellValue::Number(*f),
+            Data::Int(i) => numfmt::CellValue::Number(*i as f64),
+            Data::Bool(b) => numfmt::CellValue::Bool(*b),
+            Data::DateTime(dt) => {
+                numfmt::CellValue::Number(numfmt::serial_1900(dt.as_f64(), date1904))
+            }
+            Data::DateTimeIso(s) => match numfmt::iso_serial(s) {
+                Some(serial) => numfmt::CellValue::Number(serial),
+                None => numfmt::CellValue::Text(s),
+            },
+            Data::Error(e) => numfmt::CellValue::Error(map_error_value(&format!("{e:?}"))),
+        };
+        Ok(numfmt::format_value(value, &code, &locale))
     }
 
     /// Bulk-read all cell values from a sheet (or a rectangular sub-range).
@@ -396,7 +610,9 @@ impl CalamineStyledBook {
     ) -> PyResult<PyObject> {
         self.ensure_sheet_exists(sheet)?;
         self.ensure_value_caches(sheet)?;
+        let date1904 = self.ensure_date1904()?;
 
+        let _span = profile::span("calamine_styled.convert");
         let range = self.range_cache.get(sheet).unwrap();
 
         let (start_row, start_col, end_row, end_col) = if let Some(cr) = cell_range {
@@ -406,10 +622,10 @@ impl CalamineStyledBook {
                 let parts: Vec<&str> = clean.split(':').collect();
                 let a = parts[0];
                 let b = if parts.len() > 1 { parts[1] } else { a };
-                let (r0, c0) =
-                    a1_to_row_col(a).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
-                let (r1, c1) =
-                    a1_to_row_col(b).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
+                let (r0, c0) = a1_to_row_col(a)
+                    .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a, msg))?;
+                let (r1, c1) = a1_to_row_col(b)
+                    .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, b, msg))?;
                 (r0.min(r1), c0.min(c1), r0.max(r1), c0.max(c1))
             } else {
                 let (h, w) = range.get_size();
@@ -445,11 +661,7 @@ impl CalamineStyledBook {
                 // Check fast formula map first.
                 if let Some(ref fm) = fmap {
                     if let Some(f) = fm.get(&(row, col)) {
-                        let formula = if f.starts_with('=') {
-                            f.clone()
-                        } else {
-                            format!("={f}")
-                        };
+                        let formula = formula::with_equals(f);
                         if let Some(err_val) = map_error_formula(&formula) {
                             let d = PyDict::new(py);
                             d.set_item("type", "error")?;
@@ -468,7 +680,7 @@ impl CalamineStyledBook {
                 // Fall back to data value.
                 match range.get_value((row, col)) {
                     None => inner.append(cell_blank(py)?)?,
-                    Some(v) => inner.append(data_to_py(py, v)?)?,
+                    Some(v) => inner.append(data_to_py(py, v, date1904)?)?,
                 }
             }
             outer.append(inner)?;
@@ -483,7 +695,8 @@ impl CalamineStyledBook {
         sheet: &str,
         a1: &str,
     ) -> PyResult<PyObject> {
-        let (row, col) = a1_to_row_col(a1).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
+        let (row, col) =
+            a1_to_row_col(a1).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a1, msg))?;
         self.ensure_sheet_exists(sheet)?;
         self.ensure_value_caches(sheet)?;
 
@@ -494,11 +707,7 @@ impl CalamineStyledBook {
         };
         match fmap.get(&(row, col)) {
             Some(f) => {
-                let formula = if f.starts_with('=') {
-                    f.clone()
-                } else {
-                    format!("={f}")
-                };
+                let formula = formula::with_equals(f);
                 let d = PyDict::new(py);
                 d.set_item("type", "formula")?;
                 d.set_item("formula", &formula)?;
@@ -515,32 +724,40 @@ impl CalamineStyledBook {
         sheet: &str,
         a1: &str,
     ) -> PyResult<PyObject> {
-        let (row, col) = a1_to_row_col(a1).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
-        let style = self.get_style(sheet, row, col)?;
-        let d = PyDict::new(py);
+        let (row, col) =
+            a1_to_row_col(a1).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a1, msg))?;
+        self.cell_format_to_py(py, sheet, row, col)
+    }
 
-        if let Some(style) = style {
-            // Font
-            if let Some(font) = &style.font {
-                Self::populate_font(py, &d, font)?;
-            }
-            // Fill
-            if let Some(fill) = &style.fill {
-                Self::populate_fill(py, &d, fill)?;
-            }
-            // NumberFormat
-            if let Some(nf) = &style.number_format {
-                if nf.format_code != "General" {
-                    d.set_item("number_format", &nf.format_code)?;
-                }
-            }
-            // Alignment
-            if let Some(align) = &style.alignment {
-                Self::populate_alignment(py, &d, align)?;
+    /// Batch variant of `read_cell_format()` over a rectangular A1 range.
+    ///
+    /// Returns `list[list[dict]]` in row-major order, reusing the cached
+    /// StyleRange so large verification passes avoid per-cell FFI overhead.
+    pub fn read_formats_range(
+        &mut self,
+        py: Python<'_>,
+        sheet: &str,
+        a1_range: &str,
+    ) -> PyResult<PyObject> {
+        self.ensure_sheet_exists(sheet)?;
+        let clean = a1_range.replace('$', "").to_ascii_uppercase();
+        let (a, b) = clean
+            .split_once(':')
+            .unwrap_or((clean.as_str(), clean.as_str()));
+        let (r0, c0) =
+            a1_to_row_col(a).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a, msg))?;
+        let (r1, c1) =
+            a1_to_row_col(b).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, b, msg))?;
+
+        let outer = PyList::empty(py);
+        for row in r0.min(r1)..=r0.max(r1) {
+            let inner = PyList::empty(py);
+            for col in c0.min(c1)..=c0.max(c1) {
+                inner.append(self.cell_format_to_py(py, sheet, row, col)?)?;
             }
+            outer.append(inner)?;
         }
-
-        Ok(d.into())
+        Ok(outer.into())
     }
 
     pub fn read_cell_border(
@@ -549,7 +766,8 @@ impl CalamineStyledBook {
         sheet: &str,
         a1: &str,
     ) -> PyResult<PyObject> {
-        let (row, col) = a1_to_row_col(a1).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
+        let (row, col) =
+            a1_to_row_col(a1).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a1, msg))?;
         let style = self.get_style(sheet, row, col)?;
         let d = PyDict::new(py);
 
@@ -673,7 +891,7 @@ impl CalamineStyledBook {
         self.ensure_sheet_exists(sheet)?;
 
         if let Some(comments) = self.tier2_cache.get(sheet).and_then(|c| c.comments.clone()) {
-            return Self::comments_to_py(py, &comments);
+            return ooxml_util::comments_to_py(py, &comments);
         }
 
         let comments = self.compute_comments(sheet)?;
@@ -681,7 +899,7 @@ impl CalamineStyledBook {
             .entry(sheet.to_string())
             .or_default()
             .comments = Some(comments.clone());
-        Self::comments_to_py(py, &comments)
+        ooxml_util::comments_to_py(py, &comments)
     }
 
     pub fn read_freeze_panes(&mut self, py: Python<'_>, sheet: &str) -> PyResult<PyObject> {
@@ -741,6 +959,8 @@ impl CalamineStyledBook {
         Self::data_validations_to_py(py, &items)
     }
 
+    /// Names visible from `sheet`, reported as `UmyaBook.read_named_ranges()`
+    /// does: built-in `_xlnm.*` names are flagged `builtin: True`.
     pub fn read_named_ranges(&mut self, py: Python<'_>, sheet: &str) -> PyResult<PyObject> {
         self.ensure_sheet_exists(sheet)?;
         self.ensure_named_ranges()?;
@@ -771,7 +991,30 @@ impl CalamineStyledBook {
             let d = PyDict::new(py);
             d.set_item("name", &nr.name)?;
             d.set_item("scope", &nr.scope)?;
+            d.set_item("sheet", &nr.sheet)?;
+            d.set_item("refers_to", &nr.refers_to)?;
+            d.set_item("builtin", nr.builtin)?;
+            result.append(d)?;
+        }
+        Ok(result.into())
+    }
+
+    /// All defined names in the workbook (both scopes), in workbook order.
+    ///
+    /// Sheet-scoped names carry their owning sheet in `sheet`; workbook-scoped
+    /// names report `sheet=None`. Reserved `_xlnm.*` names (print area, print
+    /// titles, filter range) are flagged `builtin: True`.
+    pub fn read_defined_names(&mut self, py: Python<'_>) -> PyResult<PyObject> {
+        self.ensure_named_ranges()?;
+
+        let result = PyList::empty(py);
+        for nr in self.named_ranges.as_deref().unwrap_or_default() {
+            let d = PyDict::new(py);
+            d.set_item("name", &nr.name)?;
+            d.set_item("scope", &nr.scope)?;
+            d.set_item("sheet", &nr.sheet)?;
             d.set_item("refers_to", &nr.refers_to)?;
+            d.set_item("builtin", nr.builtin)?;
             result.append(d)?;
         }
         Ok(result.into())
@@ -795,17 +1038,69 @@ impl CalamineStyledBook {
 
 // Non-Python helper methods.
 impl CalamineStyledBook {
+    /// Build the `read_cell_format()` dict for a 0-based (row, col).
+    fn cell_format_to_py(
+        &mut self,
+        py: Python<'_>,
+        sheet: &str,
+        row: u32,
+        col: u32,
+    ) -> PyResult<PyObject> {
+        let style = self.get_style(sheet, row, col)?;
+        let d = PyDict::new(py);
+
+        // Number format id + tokenizer-based date detection (explicitly styled cells only).
+        if let Some(fmt_id) = self.cell_num_fmt_id(sheet, row, col)? {
+            let code = style
+                .as_ref()
+                .and_then(|s| s.number_format.as_ref())
+                .map(|nf| nf.format_code.as_str());
+            d.set_item("number_format_id", fmt_id)?;
+            d.set_item("is_date_format", numfmt::is_date_format_id(fmt_id, code))?;
+        }
+
+        if let Some(style) = style {
+            // Font
+            if let Some(font) = &style.font {
+                Self::populate_font(py, &d, font)?;
+            }
+            // Fill
+            if let Some(fill) = &style.fill {
+                Self::populate_fill(py, &d, fill)?;
+            }
+            self.populate_fill_detail(py, &d, sheet, row, col)?;
+            // NumberFormat
+            if let Some(nf) = &style.number_format {
+                if nf.format_code != "General" {
+                    d.set_item("number_format", &nf.format_code)?;
+                }
+            }
+            // Alignment
+            if let Some(align) = &style.alignment {
+                Self::populate_alignment(py, &d, align)?;
+            }
+        }
+
+        // Protection: both flags whenever the cell's xf carries the element.
+        if let Some((locked, hidden)) = self.cell_protection(sheet, row, col)? {
+            d.set_item("locked", locked)?;
+            d.set_item("hidden", hidden)?;
+        }
+
+        Ok(d.into())
+    }
+
     /// Ensure the StyleRange + WorksheetLayout are cached for this sheet.
     fn ensure_cache(&mut self, sheet: &str) -> PyResult<()> {
         if self.style_cache.contains_key(sheet) {
             return Ok(());
         }
         let styles = self
-            .workbook
+            .workbook_mut()?
             .worksheet_style(sheet)
             .map_err(|e| PyErr::new::<PyIOError, _>(format!("Style error for {sheet}: {e}")))?;
         let layout = self
-            .workbook
+            .workbook_mut()?
             .worksheet_layout(sheet)
             .map_err(|e| PyErr::new::<PyIOError, _>(format!("Layout error for {sheet}: {e}")))?;
         let origin = styles.start().unwrap_or((0, 0));
@@ -846,7 +1141,7 @@ impl CalamineStyledBook {
         // 2. Parse cell values via calamine (handles shared strings, dates, etc.)
         //    Calamine opens the zip internally; the OS disk cache will serve
         //    the sheet XML from memory since we just read it above.
-        let range = self.workbook.worksheet_range(sheet).map_err(|e| {
+        let range = self.workbook_mut()?.worksheet_range(sheet).map_err(|e| {
             PyErr::new::<PyIOError, _>(format!("Failed to read sheet {sheet}: {e}"))
         })?;
         self.range_cache.insert(sheet.to_string(), range);
@@ -900,6 +1195,72 @@ impl CalamineStyledBook {
         Ok(())
     }
 
+    /// Pattern type, pattern colors and gradients, which calamine's `Fill`
+    /// does not carry, read from styles.xml. For solid fills `bg_color` stays
+    /// the visible color; for other patterns it is the pattern background.
+    fn populate_fill_detail(
+        &mut self,
+        py: Python<'_>,
+        d: &Bound<'_, PyDict>,
+        sheet: &str,
+        row: u32,
+        col: u32,
+    ) -> PyResult<()> {
+        let Some(style_id) = self.cell_style_id(sheet, row, col)? else {
+            return Ok(());
+        };
+        self.ensure_style_fills()?;
+        self.ensure_palette()?;
+        let (Some(fills), Some(palette)) = (&self.style_fills, &self.palette) else {
+            return Ok(());
+        };
+        let resolve = |c: &Option<ColorSpec>| c.as_ref().and_then(|c| c.resolve(palette));
+
+        match fills.for_xf(style_id) {
+            None => {}
+            Some(FillDef::Pattern(p)) if p.pattern_type == "none" => {}
+            Some(FillDef::Pattern(p)) => {
+                d.set_item("pattern_type", &p.pattern_type)?;
+                let (fg, bg) = (resolve(&p.fg), resolve(&p.bg));
+                if let Some(fg) = &fg {
+                    d.set_item("fg_color", fg)?;
+                }
+                let shown = if p.pattern_type == "solid" {
+                    fg.or(bg)
+                } else {
+                    bg
+                };
+                match shown {
+                    Some(color) => d.set_item("bg_color", color)?,
+                    None if d.contains("bg_color")? => d.del_item("bg_color")?,
+                    None => {}
+                }
+            }
+            Some(FillDef::Gradient(g)) => {
+                let gradient = PyDict::new(py);
+                gradient.set_item("type", &g.kind)?;
+                if g.kind == "path" {
+                    gradient.set_item("left", g.left)?;
+                    gradient.set_item("right", g.right)?;
+                    gradient.set_item("top", g.top)?;
+                    gradient.set_item("bottom", g.bottom)?;
+                } else {
+                    gradient.set_item("degree", g.degree)?;
+                }
+                let stops = PyList::empty(py);
+                for stop in &g.stops {
+                    let s = PyDict::new(py);
+                    s.set_item("position", stop.position)?;
+                    s.set_item("color", resolve(&stop.color))?;
+                    stops.append(s)?;
+                }
+                gradient.set_item("stops", stops)?;
+                d.set_item("gradient", gradient)?;
+            }
+        }
+        Ok(())
+    }
+
     fn populate_alignment(
         _py: Python<'_>,
         d: &Bound<'_, PyDict>,
@@ -972,25 +1333,29 @@ impl CalamineStyledBook {
     }
 
     fn col_letter_to_index(col: &str) -> PyResult<u32> {
-        let mut idx: u32 = 0;
-        for ch in col.chars() {
-            if !ch.is_ascii_alphabetic() {
-                return Err(PyErr::new::<PyValueError, _>(format!(
-                    "Invalid column letter: {col}"
-                )));
-            }
-            idx = idx * 26 + (ch.to_ascii_uppercase() as u8 - b'A' + 1) as u32;
-        }
-        Ok(idx - 1)
+        letters_to_col(col).ok_or_else(|| {
+            errors::cell_ref(
+                CAPABILITIES.backend,
+                col,
+                format!("Invalid column letter: {col}"),
+            )
+        })
+    }
+
+    fn workbook_mut(&mut self) -> PyResult<&mut XlsxReader> {
+        self.workbook
+            .as_mut()
+            .ok_or_else(|| errors::closed(CAPABILITIES.backend))
     }
 
     fn ensure_sheet_exists(&self, sheet: &str) -> PyResult<()> {
+        if self.workbook.is_none() {
+            return Err(errors::closed(CAPABILITIES.backend));
+        }
         if self.sheet_names.iter().any(|name| name == sheet) {
             Ok(())
         } else {
-            Err(PyErr::new::<PyValueError, _>(format!(
-                "Unknown sheet: {sheet}"
-            )))
+            Err(errors::sheet_not_found(CAPABILITIES.backend, sheet))
         }
     }
 
@@ -1001,6 +1366,17 @@ impl CalamineStyledBook {
             .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read xlsx zip: {e}")))
     }
 
+    fn ensure_date1904(&mut self) -> PyResult<bool> {
+        if let Some(flag) = self.date1904 {
+            return Ok(flag);
+        }
+        let mut zip = self.open_zip()?;
+        let workbook_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml")?;
+        let flag = ooxml_util::workbook_is_date1904(&workbook_xml);
+        self.date1904 = Some(flag);
+        Ok(flag)
+    }
+
     fn ensure_sheet_xml_paths(&mut self) -> PyResult<()> {
         if self.sheet_xml_paths.is_some() {
             return Ok(());
@@ -1009,17 +1385,9 @@ impl CalamineStyledBook {
         let mut zip = self.open_zip()?;
         let workbook_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml")?;
         let rels_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/_rels/workbook.xml.rels")?;
-
-        let sheet_rids = ooxml_util::parse_workbook_sheet_rids(&workbook_xml)?;
-        let rel_targets = ooxml_util::parse_relationship_targets(&rels_xml)?;
-
-        let mut map: HashMap<String, String> = HashMap::new();
-        for (name, rid) in sheet_rids {
-            if let Some(target) = rel_targets.get(&rid) {
-                let full = ooxml_util::join_and_normalize("xl/", target);
-                map.insert(name, full);
-            }
-        }
+        let map: HashMap<String, String> = ooxml_util::sheet_part_paths(&workbook_xml, &rels_xml)?
+            .into_iter()
+            .collect();
 
         self.sheet_xml_paths = Some(map);
         Ok(())
@@ -1069,37 +1437,6 @@ impl CalamineStyledBook {
         }
     }
 
-    fn find_relationship_target_by_type(xml: &str, type_suffix: &str) -> PyResult<Option<String>> {
-        let mut reader = XmlReader::from_str(xml);
-        reader.config_mut().trim_text(true);
-        let mut buf: Vec<u8> = Vec::new();
-
-        loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
-                    if e.name().as_ref() == b"Relationship" {
-                        let rel_type = ooxml_util::attr_value(&e, b"Type").unwrap_or_default();
-                        if rel_type.ends_with(type_suffix)
-                            || rel_type.ends_with(&format!("/{type_suffix}"))
-                        {
-                            return Ok(ooxml_util::attr_value(&e, b"Target"));
-                        }
-                    }
-                }
-                Ok(Event::Eof) => break,
-                Err(e) => {
-                    return Err(PyErr::new::<PyIOError, _>(format!(
-                        "Failed to parse rels: {e}"
-                    )))
-                }
-                _ => {}
-            }
-            buf.clear();
-        }
-
-        Ok(None)
-    }
-
     fn compute_merged_ranges(&mut self, sheet: &str) -> PyResult<Vec<String>> {
         let xml = self.sheet_xml_content(sheet)?;
         Self::parse_merged_ranges_from_sheet_xml(&xml)
@@ -1246,8 +1583,9 @@ impl CalamineStyledBook {
     }
 
     fn cell_display_text(&mut self, sheet: &str, a1: &str) -> PyResult<String> {
-        let (row, col) = a1_to_row_col(a1).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
-        let range = self.workbook.worksheet_range(sheet).map_err(|e| {
+        let (row, col) =
+            a1_to_row_col(a1).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a1, msg))?;
+        let range = self.workbook_mut()?.worksheet_range(sheet).map_err(|e| {
             PyErr::new::<PyIOError, _>(format!("Failed to read sheet {sheet}: {e}"))
         })?;
         let Some(v) = range.get_value((row, col)) else {
@@ -1385,112 +1723,8 @@ impl CalamineStyledBook {
 
     fn compute_comments(&mut self, sheet: &str) -> PyResult<Vec<CommentInfo>> {
         let sheet_path = self.sheet_xml_path(sheet)?;
-        let sheet_dir = Self::dir_of_path(&sheet_path);
-
-        let Some(rels_xml) = self.sheet_rels_content(sheet)? else {
-            return Ok(Vec::new());
-        };
-
-        let target = match Self::find_relationship_target_by_type(&rels_xml, "comments")? {
-            Some(t) => t,
-            None => return Ok(Vec::new()),
-        };
-        let comments_path = ooxml_util::join_and_normalize(&sheet_dir, &target);
-
         let mut zip = self.open_zip()?;
-        let comments_xml = match ooxml_util::zip_read_to_string_opt(&mut zip, &comments_path)? {
-            Some(s) => s,
-            None => return Ok(Vec::new()),
-        };
-
-        Self::parse_comments_xml(&comments_xml)
-    }
-
-    fn parse_comments_xml(xml: &str) -> PyResult<Vec<CommentInfo>> {
-        let mut reader = XmlReader::from_str(xml);
-        reader.config_mut().trim_text(true);
-        let mut buf: Vec<u8> = Vec::new();
-
-        let mut authors: Vec<String> = Vec::new();
-        let mut out: Vec<CommentInfo> = Vec::new();
-
-        let mut in_author = false;
-        let mut in_comment = false;
-        let mut in_t = false;
-
-        let mut cur_cell: String = String::new();
-        let mut cur_author_id: usize = 0;
-        let mut cur_text: String = String::new();
-
-        loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(e)) => {
-                    let name = e.name();
-                    let name = name.as_ref();
-                    if name == b"author" {
-                        in_author = true;
-                    } else if name == b"comment" {
-                        in_comment = true;
-                        cur_text.clear();
-                        cur_cell = ooxml_util::attr_value(&e, b"ref").unwrap_or_default();
-                        cur_author_id = ooxml_util::attr_value(&e, b"authorId")
-                            .and_then(|s| s.parse::<usize>().ok())
-                            .unwrap_or(0);
-                    } else if name == b"t" {
-                        in_t = true;
-                    }
-                }
-                Ok(Event::End(e)) => {
-                    let name = e.name();
-                    let name = name.as_ref();
-                    if name == b"author" {
-                        in_author = false;
-                    } else if name == b"comment" {
-                        in_comment = false;
-                        let author = authors.get(cur_author_id).cloned().unwrap_or_default();
-                        out.push(CommentInfo {
-                            cell: cur_cell.clone(),
-                            text: cur_text.clone(),
-                            author,
-                            threaded: false,
-                        });
-                    } else if name == b"t" {
-                        in_t = false;
-                    }
-                }
-                Ok(Event::Text(e)) => {
-                    let text = e.unescape().unwrap_or_default().to_string();
-                    if in_author {
-                        authors.push(text);
-                    } else if in_comment && in_t {
-                        cur_text.push_str(&text);
-                    }
-                }
-                Ok(Event::Eof) => break,
-                Err(e) => {
-                    return Err(PyErr::new::<PyIOError, _>(format!(
-                        "Failed to parse comments XML: {e}"
-                    )))
-                }
-                _ => {}
-            }
-            buf.clear();
-        }
-
-        Ok(out)
-    }
-
-    fn comments_to_py(py: Python<'_>, comments: &[CommentInfo]) -> PyResult<PyObject> {
-        let result = PyList::empty(py);
-        for c in comments {
-            let d = PyDict::new(py);
-            d.set_item("cell", &c.cell)?;
-            d.set_item("text", &c.text)?;
-            d.set_item("author", &c.author)?;
-            d.set_item("threaded", c.threaded)?;
-            result.append(d)?;
-        }
-        Ok(result.into())
+        ooxml_util::read_sheet_comments(&mut zip, &sheet_path)
     }
 
     fn compute_freeze_panes(&mut self, sheet: &str) -> PyResult<FreezePaneInfo> {
@@ -1573,19 +1807,36 @@ impl CalamineStyledBook {
         Ok(d.into())
     }
 
-    fn normalize_ooxml_rgb(rgb: &str) -> Option<String> {
-        let mut s = rgb.trim().to_string();
-        if s.starts_with('#') {
-            s = s.trim_start_matches('#').to_string();
-        }
-        // Excel OOXML colors are usually ARGB. Drop alpha if present.
-        if s.len() == 8 {
-            s = s[2..].to_string();
+    fn ensure_style_fills(&mut self) -> PyResult<()> {
+        if self.style_fills.is_some() {
+            return Ok(());
         }
-        if s.len() != 6 {
-            return None;
+        let mut zip = self.open_zip()?;
+        let fills = match ooxml_util::zip_read_to_string_opt(&mut zip, "xl/styles.xml")? {
+            Some(xml) => ooxml_util::fills::read_fills(&xml).map_err(PyErr::new::<PyIOError, _>)?,
+            None => StyleFills::default(),
+        };
+        self.style_fills = Some(fills);
+        Ok(())
+    }
+
+    fn ensure_palette(&mut self) -> PyResult<&Palette> {
+        if self.palette.is_none() {
+            let mut zip = self.open_zip()?;
+            let theme_xml = ooxml_util::zip_read_to_string_opt(&mut zip, "xl/theme/theme1.xml")?;
+            let styles_xml = ooxml_util::zip_read_to_string_opt(&mut zip, "xl/styles.xml")?;
+            self.palette = Some(Palette::from_parts(
+                theme_xml.as_deref(),
+                styles_xml.as_deref(),
+            ));
         }
-        Some(format!("#{}", s.to_ascii_uppercase()))
+        Ok(self.palette.as_ref().unwrap())
+    }
+
+    /// `#RRGGBB` for a `<color>`/`<fgColor>`/`<bgColor>` element, resolving
+    /// theme, indexed and tinted colors.
+    fn element_color(e: &BytesStart<'_>, palette: &Palette) -> Option<String> {
+        ColorSpec::from_attrs(|k| ooxml_util::attr_value(e, k))?.resolve(palette)
     }
 
     fn ensure_dxfs_bg_colors(&mut self) -> PyResult<()> {
@@ -1602,6 +1853,7 @@ impl CalamineStyledBook {
             }
         };
 
+        let palette = self.ensure_palette()?.clone();
         let mut reader = XmlReader::from_str(&styles_xml);
         reader.config_mut().trim_text(true);
         let mut buf: Vec<u8> = Vec::new();
@@ -1632,9 +1884,7 @@ impl CalamineStyledBook {
                     } else {
                         if e.name().as_ref() == b"fgColor" || e.name().as_ref() == b"bgColor" {
                             if cur_bg.is_none() {
-                                if let Some(rgb) = ooxml_util::attr_value(&e, b"rgb") {
-                                    cur_bg = Self::normalize_ooxml_rgb(&rgb);
-                                }
+                                cur_bg = Self::element_color(&e, &palette);
                             }
                         }
                     }
@@ -1692,6 +1942,161 @@ impl CalamineStyledBook {
             .and_then(|m| m.get(&(row, col)).copied()))
     }
 
+    fn ensure_cellxfs_num_fmt_ids(&mut self) -> PyResult<()> {
+        if self.cellxfs_num_fmt_ids.is_some() {
+            return Ok(());
+        }
+
+        let mut zip = self.open_zip()?;
+        let styles_xml = match ooxml_util::zip_read_to_string_opt(&mut zip, "xl/styles.xml")? {
+            Some(s) => s,
+            None => {
+                self.cellxfs_num_fmt_ids = Some(Vec::new());
+                return Ok(());
+            }
+        };
+
+        let mut reader = XmlReader::from_str(&styles_xml);
+        reader.config_mut().trim_text(true);
+        let mut buf: Vec<u8> = Vec::new();
+        let mut in_cellxfs = false;
+        let mut out: Vec<u32> = Vec::new();
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                    if e.name().as_ref() == b"cellXfs" {
+                        in_cellxfs = true;
+                    } else if in_cellxfs && e.name().as_ref() == b"xf" {
+                        let id = ooxml_util::attr_value(&e, b"numFmtId")
+                            .and_then(|v| v.parse::<u32>().ok())
+                            .unwrap_or(0);
+                        out.push(id);
+                    }
+                }
+                Ok(Event::End(e)) => {
+                    if e.name().as_ref() == b"cellXfs" {
+                        in_cellxfs = false;
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => {
+                    return Err(PyErr::new::<PyIOError, _>(format!(
+                        "Failed to parse styles.xml: {e}"
+                    )))
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        self.cellxfs_num_fmt_ids = Some(out);
+        Ok(())
+    }
+
+    /// numFmtId for a cell with an explicit `s` attribute, or None if unstyled.
+    fn cell_num_fmt_id(&mut self, sheet: &str, row: u32, col: u32) -> PyResult<Option<u32>> {
+        let Some(style_id) = self.cell_style_id(sheet, row, col)? else {
+            return Ok(None);
+        };
+        self.ensure_cellxfs_num_fmt_ids()?;
+        Ok(self
+            .cellxfs_num_fmt_ids
+            .as_ref()
+            .and_then(|ids| ids.get(style_id as usize).copied()))
+    }
+
+    fn ensure_cellxfs_protection(&mut self) -> PyResult<()> {
+        if self.cellxfs_protection.is_some() {
+            return Ok(());
+        }
+
+        let mut zip = self.open_zip()?;
+        let styles_xml = match ooxml_util::zip_read_to_string_opt(&mut zip, "xl/styles.xml")? {
+            Some(s) => s,
+            None => {
+                self.cellxfs_protection = Some(Vec::new());
+                return Ok(());
+            }
+        };
+
+        let mut reader = XmlReader::from_str(&styles_xml);
+        reader.config_mut().trim_text(true);
+        let mut buf: Vec<u8> = Vec::new();
+        let mut in_cellxfs = false;
+        let mut out: Vec<Option<(bool, bool)>> = Vec::new();
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.name().as_ref() {
+                    b"cellXfs" => in_cellxfs = true,
+                    b"xf" if in_cellxfs => out.push(None),
+                    b"protection" if in_cellxfs => {
+                        // Excel's defaults: locked, not hidden.
+                        let flag = |name: &[u8], default: bool| {
+                            ooxml_util::attr_value(&e, name)
+                                .map_or(default, |v| v == "1" || v.eq_ignore_ascii_case("true"))
+                        };
+                        if let Some(last) = out.last_mut() {
+                            *last = Some((flag(b"locked", true), flag(b"hidden", false)));
+                        }
+                    }
+                    _ => {}
+                },
+                Ok(Event::End(e)) => {
+                    if e.name().as_ref() == b"cellXfs" {
+                        in_cellxfs = false;
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => {
+                    return Err(PyErr::new::<PyIOError, _>(format!(
+                        "Failed to parse styles.xml: {e}"
+                    )))
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        self.cellxfs_protection = Some(out);
+        Ok(())
+    }
+
+    /// (locked, hidden) of a cell whose xf has a `<protection>` element.
+    fn cell_protection(
+        &mut self,
+        sheet: &str,
+        row: u32,
+        col: u32,
+    ) -> PyResult<Option<(bool, bool)>> {
+        let Some(style_id) = self.cell_style_id(sheet, row, col)? else {
+            return Ok(None);
+        };
+        self.ensure_cellxfs_protection()?;
+        Ok(self
+            .cellxfs_protection
+            .as_ref()
+            .and_then(|xfs| xfs.get(style_id as usize).copied().flatten()))
+    }
+
+    /// Number format code of a cell: built-in ids through the ECMA table,
+    /// custom ones as the style carries them.
+    fn cell_number_format(&mut self, sheet: &str, row: u32, col: u32) -> PyResult<String> {
+        let builtin = self
+            .cell_num_fmt_id(sheet, row, col)?
+            .and_then(numfmt::builtin_format_code);
+        if let Some(code) = builtin {
+            return Ok(code.to_string());
+        }
+        Ok(self
+            .get_style(sheet, row, col)?
+            .and_then(|style| style.number_format)
+            .map(|nf| nf.format_code)
+            .filter(|code| !code.is_empty())
+            .unwrap_or_else(|| "General".to_string()))
+    }
+
     fn ensure_diagonal_borders(&mut self) -> PyResult<()> {
         if self.diagonal_borders.is_some() {
             return Ok(());
@@ -1727,6 +2132,7 @@ impl CalamineStyledBook {
 
         let mut cur_border: Option<BorderDef> = None;
 
+        let palette = self.ensure_palette()?.clone();
         let mut reader = XmlReader::from_str(&styles_xml);
         reader.config_mut().trim_text(true);
         let mut buf: Vec<u8> = Vec::new();
@@ -1757,8 +2163,8 @@ impl CalamineStyledBook {
                     }
                     b"color" if in_diagonal => {
                         if let Some(def) = cur_border.as_mut() {
-                            if let Some(rgb) = ooxml_util::attr_value(&e, b"rgb") {
-                                def.color = Self::normalize_ooxml_rgb(&rgb);
+                            if let Some(color) = Self::element_color(&e, &palette) {
+                                def.color = Some(color);
                             }
                         }
                     }
@@ -1795,8 +2201,8 @@ impl CalamineStyledBook {
                         }
                         b"color" if in_diagonal => {
                             if let Some(def) = cur_border.as_mut() {
-                                if let Some(rgb) = ooxml_util::attr_value(&e, b"rgb") {
-                                    def.color = Self::normalize_ooxml_rgb(&rgb);
+                                if let Some(color) = Self::element_color(&e, &palette) {
+                                    def.color = Some(color);
                                 }
                             }
                         }
@@ -1872,6 +2278,7 @@ impl CalamineStyledBook {
         sheet: &str,
     ) -> PyResult<Vec<ConditionalFormatRuleInfo>> {
         let xml = self.sheet_xml_content(sheet)?;
+        let palette = self.ensure_palette()?.clone();
         let mut reader = XmlReader::from_str(&xml);
         reader.config_mut().trim_text(true);
         let mut buf: Vec<u8> = Vec::new();
@@ -1958,9 +2365,7 @@ impl CalamineStyledBook {
                         if e.name().as_ref() == b"fgColor" || e.name().as_ref() == b"bgColor" {
                             if let Some(ref mut rule) = cur_rule {
                                 if rule.bg_color.is_none() {
-                                    if let Some(rgb) = ooxml_util::attr_value(&e, b"rgb") {
-                                        rule.bg_color = Self::normalize_ooxml_rgb(&rgb);
-                                    }
+                                    rule.bg_color = Self::element_color(&e, &palette);
                                 }
                             }
                         }
@@ -1981,11 +2386,7 @@ impl CalamineStyledBook {
                             let f = formula_buf.trim().to_string();
                             if let Some(ref mut rule) = cur_rule {
                                 if rule.formula.is_none() && !f.is_empty() {
-                                    let formula = if f.starts_with('=') {
-                                        f
-                                    } else {
-                                        format!("={f}")
-                                    };
+                                    let formula = formula::with_equals(&f);
                                     rule.formula = Some(formula);
                                 }
                             }
@@ -2143,11 +2544,7 @@ impl CalamineStyledBook {
                         if let Some(ref mut dv) = cur {
                             let f = formula1_buf.trim().to_string();
                             if !f.is_empty() {
-                                let formula = if f.starts_with('=') {
-                                    f
-                                } else {
-                                    format!("={f}")
-                                };
+                                let formula = formula::with_equals(&f);
                                 dv.formula1 = Some(formula);
                             }
                         }
@@ -2156,11 +2553,7 @@ impl CalamineStyledBook {
                         if let Some(ref mut dv) = cur {
                             let f = formula2_buf.trim().to_string();
                             if !f.is_empty() {
-                                let formula = if f.starts_with('=') {
-                                    f
-                                } else {
-                                    format!("={f}")
-                                };
+                                let formula = formula::with_equals(&f);
                                 dv.formula2 = Some(formula);
                             }
                         }
@@ -2260,16 +2653,22 @@ impl CalamineStyledBook {
                             continue;
                         }
 
-                        // Skip Excel reserved/system names.
-                        if n.starts_with("_xlnm.") {
-                            continue;
-                        }
+                        // Excel reserved/system names always belong to a sheet:
+                        // without localSheetId, take the one the address points at.
+                        let builtin = n.get(..6).is_some_and(|p| p.eq_ignore_ascii_case("_xlnm."));
 
                         let (scope, sheet_name) = match cur_local_id.take() {
                             Some(idx) => {
                                 let sname = self.sheet_names.get(idx).cloned();
                                 ("sheet".to_string(), sname)
                             }
+                            None if builtin => {
+                                let sname = refers_to
+                                    .trim_start_matches('=')
+                                    .split_once('!')
+                                    .map(|(s, _)| s.trim_matches('\'').to_string());
+                                ("sheet".to_string(), sname)
+                            }
                             None => ("workbook".to_string(), None),
                         };
 
@@ -2277,7 +2676,7 @@ impl CalamineStyledBook {
                         let refers_to = if scope == "sheet" {
                             if refers_to.contains('!') {
                                 refers_to
-                            } else if let Some(sn) = sheet_name {
+                            } else if let Some(sn) = &sheet_name {
                                 format!("{sn}!{refers_to}")
                             } else {
                                 refers_to
@@ -2289,7 +2688,9 @@ impl CalamineStyledBook {
                         out.push(NamedRangeInfo {
                             name: n,
                             scope,
+                            sheet: sheet_name,
                             refers_to,
+                            builtin,
                         });
                     }
                 }
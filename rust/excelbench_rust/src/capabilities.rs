@@ -0,0 +1,92 @@
+//! Per-class capability matrix reported by `capabilities()`.
+//!
+//! Each backend module declares a `CAPABILITIES` const next to its pyclass, so
+//! the matrix is updated in the same change that adds or removes a method.
+//! Feature names match the ExcelBench fixture features.
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// Every harness feature, in fixture-manifest order.
+pub(crate) const FEATURES: &[&str] = &[
+    "cell_values",
+    "formulas",
+    "text_formatting",
+    "background_colors",
+    "number_formats",
+    "alignment",
+    "borders",
+    "dimensions",
+    "multiple_sheets",
+    "merged_cells",
+    "conditional_formatting",
+    "data_validation",
+    "hyperlinks",
+    "images",
+    "pivot_tables",
+    "comments",
+    "freeze_panes",
+    "named_ranges",
+    "tables",
+];
+
+/// What one exported class can do.
+pub(crate) struct BackendCapabilities {
+    /// Python class name in `wolfxl._rust`.
+    pub class: &'static str,
+    /// Backend key as listed in `build_info()["enabled_backends"]`.
+    pub backend: &'static str,
+    /// File extensions the class can open (readers/modifiers) or write.
+    pub extensions: &'static [&'static str],
+    pub read: &'static [&'static str],
+    pub write: &'static [&'static str],
+    /// Saves edits back into an existing file rather than writing a new one.
+    pub modify: bool,
+}
+
+fn compiled_in() -> Vec<&'static BackendCapabilities> {
+    #[allow(unused_mut)]
+    let mut out: Vec<&'static BackendCapabilities> = Vec::new();
+    #[cfg(feature = "calamine")]
+    {
+        out.push(&crate::calamine_backend::CAPABILITIES);
+        out.push(&crate::calamine_styled_backend::CAPABILITIES);
+    }
+    #[cfg(feature = "rust_xlsxwriter")]
+    out.push(&crate::rust_xlsxwriter_backend::CAPABILITIES);
+    #[cfg(feature = "umya")]
+    out.push(&crate::umya::CAPABILITIES);
+    #[cfg(feature = "wolfxl")]
+    {
+        out.push(&crate::wolfxl::CAPABILITIES);
+        out.push(&crate::wolfxl::reader::CAPABILITIES);
+    }
+    out
+}
+
+fn feature_flags<'py>(py: Python<'py>, supported: &[&str]) -> PyResult<Bound<'py, PyDict>> {
+    let d = PyDict::new(py);
+    for feature in FEATURES {
+        d.set_item(*feature, supported.contains(feature))?;
+    }
+    Ok(d)
+}
+
+/// Capability matrix for every backend class compiled into this build.
+///
+/// Returns `{class_name: {"backend", "extensions", "read", "write", "modify"}}`
+/// where `read` / `write` map every harness feature name to a bool.
+#[pyfunction]
+pub(crate) fn capabilities(py: Python<'_>) -> PyResult<PyObject> {
+    let out = PyDict::new(py);
+    for caps in compiled_in() {
+        let d = PyDict::new(py);
+        d.set_item("backend", caps.backend)?;
+        d.set_item("extensions", caps.extensions.to_vec())?;
+        d.set_item("read", feature_flags(py, caps.read)?)?;
+        d.set_item("write", feature_flags(py, caps.write)?)?;
+        d.set_item("modify", caps.modify)?;
+        out.set_item(caps.class, d)?;
+    }
+    Ok(out.into())
+}
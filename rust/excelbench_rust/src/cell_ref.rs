@@ -0,0 +1,511 @@
+//! A1 / R1C1 cell and range references shared by every backend.
+//!
+//! Rows and columns are 0-based internally. `$` markers are kept as
+//! `row_abs` / `col_abs` so references round-trip, and ranges may carry a
+//! sheet qualifier (`Sheet1!A1:B2`, `'My Sheet'!A:A`) or span whole
+//! columns/rows.
+
+use std::fmt;
+
+/// Rows in an xlsx worksheet.
+pub(crate) const MAX_ROWS: u32 = 1_048_576;
+/// Columns in an xlsx worksheet (A..XFD).
+pub(crate) const MAX_COLS: u32 = 16_384;
+
+/// Convert a 0-based column index to letters (0 -> "A", 26 -> "AA").
+pub(crate) fn col_to_letters(col: u32) -> String {
+    let mut n = col + 1;
+    let mut out = Vec::new();
+    while n > 0 {
+        let rem = ((n - 1) % 26) as u8;
+        out.push(b'A' + rem);
+        n = (n - 1) / 26;
+    }
+    out.reverse();
+    String::from_utf8(out).unwrap()
+}
+
+/// Convert column letters (case-insensitive) to a 0-based index.
+pub(crate) fn letters_to_col(letters: &str) -> Option<u32> {
+    if letters.is_empty() || letters.len() > 3 {
+        return None;
+    }
+    let mut col: u32 = 0;
+    for ch in letters.chars() {
+        if !ch.is_ascii_alphabetic() {
+            return None;
+        }
+        col = col * 26 + (ch.to_ascii_uppercase() as u8 - b'A' + 1) as u32;
+    }
+    if col > MAX_COLS {
+        return None;
+    }
+    Some(col - 1)
+}
+
+/// Split `$AB$12` into (col_abs, letters, row_abs, digits); either half may be empty.
+fn split_ref(s: &str) -> Option<(bool, &str, bool, &str)> {
+    let (col_abs, rest) = match s.strip_prefix('$') {
+        Some(r) => (true, r),
+        None => (false, s),
+    };
+    let letters_end = rest
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+    let (letters, rest) = rest.split_at(letters_end);
+    let (row_abs, digits) = match rest.strip_prefix('$') {
+        Some(r) => (true, r),
+        None => (false, rest),
+    };
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // A lone `$` belongs to whichever half is present.
+    if letters.is_empty() && col_abs && !row_abs {
+        return Some((false, letters, true, digits));
+    }
+    Some((col_abs, letters, row_abs, digits))
+}
+
+fn parse_row(digits: &str) -> Option<u32> {
+    let row: u32 = digits.parse().ok()?;
+    if row == 0 || row > MAX_ROWS {
+        return None;
+    }
+    Some(row - 1)
+}
+
+/// A single cell reference such as `B3` or `$B$3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct CellRef {
+    pub row: u32,
+    pub col: u32,
+    pub row_abs: bool,
+    pub col_abs: bool,
+}
+
+impl CellRef {
+    pub fn new(row: u32, col: u32) -> Self {
+        Self {
+            row,
+            col,
+            row_abs: false,
+            col_abs: false,
+        }
+    }
+
+    /// Parse an A1 reference (`b3`, `$B$3`, `B$3`).
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let err = || format!("Invalid cell reference: {s}");
+        let (col_abs, letters, row_abs, digits) = split_ref(s.trim()).ok_or_else(err)?;
+        Ok(Self {
+            row: parse_row(digits).ok_or_else(err)?,
+            col: letters_to_col(letters).ok_or_else(err)?,
+            row_abs,
+            col_abs,
+        })
+    }
+
+    /// Relative A1 form without `$` markers.
+    pub fn to_a1(&self) -> String {
+        format!("{}{}", col_to_letters(self.col), self.row + 1)
+    }
+
+    /// Shift by a row/column delta; None when the result leaves the sheet.
+    pub fn offset(&self, rows: i64, cols: i64) -> Option<Self> {
+        let row = i64::from(self.row) + rows;
+        let col = i64::from(self.col) + cols;
+        if !(0..i64::from(MAX_ROWS)).contains(&row) || !(0..i64::from(MAX_COLS)).contains(&col) {
+            return None;
+        }
+        Some(Self {
+            row: row as u32,
+            col: col as u32,
+            ..*self
+        })
+    }
+
+    /// R1C1 form relative to `base` (0-based row, col): absolute components
+    /// become `R5`/`C2`, relative ones `R[-1]`/`C[3]` (or bare `R`/`C` for 0).
+    pub fn to_r1c1(&self, base: (u32, u32)) -> String {
+        fn part(axis: char, abs: bool, value: u32, base: u32) -> String {
+            if abs {
+                return format!("{axis}{}", value + 1);
+            }
+            match i64::from(value) - i64::from(base) {
+                0 => axis.to_string(),
+                d => format!("{axis}[{d}]"),
+            }
+        }
+        format!(
+            "{}{}",
+            part('R', self.row_abs, self.row, base.0),
+            part('C', self.col_abs, self.col, base.1)
+        )
+    }
+
+    /// Parse an R1C1 reference (`R2C3`, `R[-1]C`, `RC[2]`) relative to `base`.
+    pub fn from_r1c1(s: &str, base: (u32, u32)) -> Result<Self, String> {
+        let err = || format!("Invalid R1C1 reference: {s}");
+        fn part(s: &str, axis: char, base: u32) -> Option<(u32, bool, &str)> {
+            let rest = s
+                .strip_prefix(axis)
+                .or_else(|| s.strip_prefix(axis.to_ascii_lowercase()))?;
+            if let Some(inner) = rest.strip_prefix('[') {
+                let close = inner.find(']')?;
+                let delta: i64 = inner[..close].parse().ok()?;
+                let value = u32::try_from(i64::from(base) + delta).ok()?;
+                return Some((value, false, &inner[close + 1..]));
+            }
+            let digits_end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            if digits_end == 0 {
+                return Some((base, false, rest));
+            }
+            let n: u32 = rest[..digits_end].parse().ok()?;
+            Some((n.checked_sub(1)?, true, &rest[digits_end..]))
+        }
+        let (row, row_abs, rest) = part(s.trim(), 'R', base.0).ok_or_else(err)?;
+        let (col, col_abs, rest) = part(rest, 'C', base.1).ok_or_else(err)?;
+        if !rest.is_empty() || row >= MAX_ROWS || col >= MAX_COLS {
+            return Err(err());
+        }
+        Ok(Self {
+            row,
+            col,
+            row_abs,
+            col_abs,
+        })
+    }
+}
+
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = if self.col_abs { "$" } else { "" };
+        let r = if self.row_abs { "$" } else { "" };
+        write!(f, "{c}{}{r}{}", col_to_letters(self.col), self.row + 1)
+    }
+}
+
+/// How a range was written, so whole-column/row forms format back unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RangeKind {
+    Cells,
+    /// `A:C`
+    Columns,
+    /// `1:3`
+    Rows,
+}
+
+/// A (possibly sheet-qualified) rectangular reference.
+///
+/// `start` is always the top-left corner and `end` the bottom-right, so
+/// `B5:A1` parses to `A1:B5`. Whole columns span every row and whole rows
+/// every column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RangeRef {
+    pub sheet: Option<String>,
+    pub start: CellRef,
+    pub end: CellRef,
+    pub kind: RangeKind,
+}
+
+/// Split `'It''s'!A1` / `Sheet1!A1` into (sheet, reference).
+fn split_sheet(s: &str) -> Result<(Option<String>, &str), String> {
+    let Some(bang) = s.rfind('!') else {
+        return Ok((None, s));
+    };
+    let (sheet, reference) = (&s[..bang], &s[bang + 1..]);
+    let sheet = match sheet.strip_prefix('\'') {
+        Some(quoted) => quoted
+            .strip_suffix('\'')
+            .ok_or_else(|| format!("Invalid sheet name in reference: {s}"))?
+            .replace("''", "'"),
+        None => sheet.to_string(),
+    };
+    if sheet.is_empty() {
+        return Err(format!("Invalid sheet name in reference: {s}"));
+    }
+    Ok((Some(sheet), reference))
+}
+
+/// Quote a sheet name for use in a reference when it needs it.
+pub(crate) fn quote_sheet_name(name: &str) -> String {
+    let plain = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        && !name.starts_with(|c: char| c.is_ascii_digit());
+    if plain {
+        name.to_string()
+    } else {
+        format!("'{}'", name.replace('\'', "''"))
+    }
+}
+
+impl RangeRef {
+    /// Parse `A1`, `A1:B2`, `$A$1:$B$2`, `A:C`, `1:3`, optionally `Sheet!`-qualified.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let err = || format!("Invalid range reference: {s}");
+        let (sheet, reference) = split_sheet(s.trim())?;
+        let (a, b) = reference.split_once(':').unwrap_or((reference, reference));
+        let (a_col_abs, a_letters, a_row_abs, a_digits) = split_ref(a).ok_or_else(err)?;
+        let (b_col_abs, b_letters, b_row_abs, b_digits) = split_ref(b).ok_or_else(err)?;
+
+        let (kind, start, end) = if a_digits.is_empty() && b_digits.is_empty() {
+            let c0 = letters_to_col(a_letters).ok_or_else(err)?;
+            let c1 = letters_to_col(b_letters).ok_or_else(err)?;
+            let start = CellRef {
+                row: 0,
+                col: c0,
+                row_abs: false,
+                col_abs: a_col_abs,
+            };
+            let end = CellRef {
+                row: MAX_ROWS - 1,
+                col: c1,
+                row_abs: false,
+                col_abs: b_col_abs,
+            };
+            (RangeKind::Columns, start, end)
+        } else if a_letters.is_empty() && b_letters.is_empty() {
+            let r0 = parse_row(a_digits).ok_or_else(err)?;
+            let r1 = parse_row(b_digits).ok_or_else(err)?;
+            let start = CellRef {
+                row: r0,
+                col: 0,
+                row_abs: a_row_abs,
+                col_abs: false,
+            };
+            let end = CellRef {
+                row: r1,
+                col: MAX_COLS - 1,
+                row_abs: b_row_abs,
+                col_abs: false,
+            };
+            (RangeKind::Rows, start, end)
+        } else {
+            (
+                RangeKind::Cells,
+                CellRef::parse(a).map_err(|_| err())?,
+                CellRef::parse(b).map_err(|_| err())?,
+            )
+        };
+
+        let (top, bottom) = if start.row <= end.row {
+            ((start.row, start.row_abs), (end.row, end.row_abs))
+        } else {
+            ((end.row, end.row_abs), (start.row, start.row_abs))
+        };
+        let (left, right) = if start.col <= end.col {
+            ((start.col, start.col_abs), (end.col, end.col_abs))
+        } else {
+            ((end.col, end.col_abs), (start.col, start.col_abs))
+        };
+        Ok(Self {
+            sheet,
+            start: CellRef {
+                row: top.0,
+                col: left.0,
+                row_abs: top.1,
+                col_abs: left.1,
+            },
+            end: CellRef {
+                row: bottom.0,
+                col: right.0,
+                row_abs: bottom.1,
+                col_abs: right.1,
+            },
+            kind,
+        })
+    }
+
+    /// 0-based inclusive `(first_row, first_col, last_row, last_col)`.
+    pub fn bounds(&self) -> (u32, u32, u32, u32) {
+        (self.start.row, self.start.col, self.end.row, self.end.col)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.end.row - self.start.row + 1
+    }
+
+    pub fn width(&self) -> u32 {
+        self.end.col - self.start.col + 1
+    }
+
+    pub fn contains(&self, row: u32, col: u32) -> bool {
+        (self.start.row..=self.end.row).contains(&row)
+            && (self.start.col..=self.end.col).contains(&col)
+    }
+
+    /// Shift the whole range; None when any corner leaves the sheet.
+    /// Whole-column/row ranges only move along their bounded axis.
+    pub fn offset(&self, rows: i64, cols: i64) -> Option<Self> {
+        let (rows, cols) = match self.kind {
+            RangeKind::Cells => (rows, cols),
+            RangeKind::Columns => (0, cols),
+            RangeKind::Rows => (rows, 0),
+        };
+        Some(Self {
+            sheet: self.sheet.clone(),
+            start: self.start.offset(rows, cols)?,
+            end: self.end.offset(rows, cols)?,
+            kind: self.kind,
+        })
+    }
+
+    /// Relative A1 form without sheet or `$` markers (`A1:B2`, or `A1` for one cell).
+    pub fn to_a1(&self) -> String {
+        match self.kind {
+            RangeKind::Columns => format!(
+                "{}:{}",
+                col_to_letters(self.start.col),
+                col_to_letters(self.end.col)
+            ),
+            RangeKind::Rows => format!("{}:{}", self.start.row + 1, self.end.row + 1),
+            RangeKind::Cells if self.start == self.end => self.start.to_a1(),
+            RangeKind::Cells => format!("{}:{}", self.start.to_a1(), self.end.to_a1()),
+        }
+    }
+}
+
+impl fmt::Display for RangeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(sheet) = &self.sheet {
+            write!(f, "{}!", quote_sheet_name(sheet))?;
+        }
+        let dollar = |abs: bool| if abs { "$" } else { "" };
+        match self.kind {
+            RangeKind::Columns => write!(
+                f,
+                "{}{}:{}{}",
+                dollar(self.start.col_abs),
+                col_to_letters(self.start.col),
+                dollar(self.end.col_abs),
+                col_to_letters(self.end.col)
+            ),
+            RangeKind::Rows => write!(
+                f,
+                "{}{}:{}{}",
+                dollar(self.start.row_abs),
+                self.start.row + 1,
+                dollar(self.end.row_abs),
+                self.end.row + 1
+            ),
+            RangeKind::Cells if self.start == self.end => write!(f, "{}", self.start),
+            RangeKind::Cells => write!(f, "{}:{}", self.start, self.end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_letters_round_trip() {
+        for (col, letters) in [
+            (0, "A"),
+            (25, "Z"),
+            (26, "AA"),
+            (701, "ZZ"),
+            (16_383, "XFD"),
+        ] {
+            assert_eq!(col_to_letters(col), letters);
+            assert_eq!(letters_to_col(letters), Some(col));
+        }
+        assert_eq!(letters_to_col("xfd"), Some(16_383));
+        assert_eq!(letters_to_col("XFE"), None);
+        assert_eq!(letters_to_col(""), None);
+    }
+
+    #[test]
+    fn test_cell_ref_parse_and_format() {
+        let c = CellRef::parse("$B$3").unwrap();
+        assert_eq!((c.row, c.col, c.row_abs, c.col_abs), (2, 1, true, true));
+        assert_eq!(c.to_string(), "$B$3");
+        assert_eq!(c.to_a1(), "B3");
+
+        let mixed = CellRef::parse("b$10").unwrap();
+        assert_eq!(mixed.to_string(), "B$10");
+
+        for bad in ["", "A", "1", "A0", "1A", "A1B", "A1048577", "A-1"] {
+            assert!(CellRef::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_cell_ref_offset() {
+        let c = CellRef::parse("B2").unwrap();
+        assert_eq!(c.offset(1, 2).unwrap().to_a1(), "D3");
+        assert!(c.offset(-2, 0).is_none());
+        assert!(CellRef::parse("XFD1").unwrap().offset(0, 1).is_none());
+    }
+
+    #[test]
+    fn test_r1c1_round_trip() {
+        let base = (4, 2); // C5
+        let abs = CellRef::parse("$A$1").unwrap();
+        assert_eq!(abs.to_r1c1(base), "R1C1");
+        let rel = CellRef::parse("B4").unwrap();
+        assert_eq!(rel.to_r1c1(base), "R[-1]C[-1]");
+        let same = CellRef::parse("C5").unwrap();
+        assert_eq!(same.to_r1c1(base), "RC");
+        let mixed = CellRef::parse("$E6").unwrap();
+        assert_eq!(mixed.to_r1c1(base), "R[1]C5");
+
+        for c in [abs, rel, same, mixed] {
+            assert_eq!(CellRef::from_r1c1(&c.to_r1c1(base), base).unwrap(), c);
+        }
+        assert!(CellRef::from_r1c1("R[-9]C", base).is_err());
+        assert!(CellRef::from_r1c1("R0C1", base).is_err());
+        assert!(CellRef::from_r1c1("C1R1", base).is_err());
+    }
+
+    #[test]
+    fn test_range_ref_cells() {
+        let r = RangeRef::parse("B5:A1").unwrap();
+        assert_eq!(r.bounds(), (0, 0, 4, 1));
+        assert_eq!(r.to_string(), "A1:B5");
+        assert_eq!((r.height(), r.width()), (5, 2));
+        assert!(r.contains(4, 1));
+        assert!(!r.contains(5, 1));
+
+        let single = RangeRef::parse("C3").unwrap();
+        assert_eq!(single.bounds(), (2, 2, 2, 2));
+        assert_eq!(single.to_string(), "C3");
+
+        let abs = RangeRef::parse("$A$1:$B$2").unwrap();
+        assert_eq!(abs.to_string(), "$A$1:$B$2");
+        assert_eq!(abs.to_a1(), "A1:B2");
+    }
+
+    #[test]
+    fn test_range_ref_sheet_qualified() {
+        let r = RangeRef::parse("Sheet1!A1:B2").unwrap();
+        assert_eq!(r.sheet.as_deref(), Some("Sheet1"));
+        assert_eq!(r.to_string(), "Sheet1!A1:B2");
+
+        let quoted = RangeRef::parse("'It''s Q1'!$C$3").unwrap();
+        assert_eq!(quoted.sheet.as_deref(), Some("It's Q1"));
+        assert_eq!(quoted.to_string(), "'It''s Q1'!$C$3");
+
+        assert!(RangeRef::parse("!A1").is_err());
+        assert!(RangeRef::parse("'Open!A1").is_err());
+    }
+
+    #[test]
+    fn test_range_ref_whole_columns_and_rows() {
+        let cols = RangeRef::parse("A:C").unwrap();
+        assert_eq!(cols.kind, RangeKind::Columns);
+        assert_eq!(cols.bounds(), (0, 0, MAX_ROWS - 1, 2));
+        assert_eq!(cols.to_string(), "A:C");
+        assert_eq!(cols.offset(5, 1).unwrap().to_string(), "B:D");
+
+        let rows = RangeRef::parse("$2:$4").unwrap();
+        assert_eq!(rows.kind, RangeKind::Rows);
+        assert_eq!(rows.bounds(), (1, 0, 3, MAX_COLS - 1));
+        assert_eq!(rows.to_string(), "$2:$4");
+        assert_eq!(rows.to_a1(), "2:4");
+    }
+}
@@ -0,0 +1,447 @@
+//! Spreadsheet colors: RGB, theme + tint and legacy indexed colors.
+//!
+//! Readers resolve every form to `"#RRGGBB"` against the workbook's theme
+//! and indexed palette; writers accept the same forms as strings
+//! (`"#RRGGBB"`, `"theme:4"`, `"theme:4:-0.25"`, `"indexed:10"`) and emit
+//! the matching `<color>` attributes.
+
+use quick_xml::events::Event;
+use quick_xml::Reader as XmlReader;
+
+use crate::ooxml_util::attr_value;
+
+/// The legacy 64-color palette (`indexed="0"`..`"63"`).
+const DEFAULT_INDEXED: [u32; 64] = [
+    0x000000, 0xFFFFFF, 0xFF0000, 0x00FF00, 0x0000FF, 0xFFFF00, 0xFF00FF, 0x00FFFF, //
+    0x000000, 0xFFFFFF, 0xFF0000, 0x00FF00, 0x0000FF, 0xFFFF00, 0xFF00FF, 0x00FFFF, //
+    0x800000, 0x008000, 0x000080, 0x808000, 0x800080, 0x008080, 0xC0C0C0, 0x808080, //
+    0x9999FF, 0x993366, 0xFFFFCC, 0xCCFFFF, 0x660066, 0xFF8080, 0x0066CC, 0xCCCCFF, //
+    0x000080, 0xFF00FF, 0xFFFF00, 0x00FFFF, 0x800080, 0x800000, 0x008080, 0x0000FF, //
+    0x00CCFF, 0xCCFFFF, 0xCCFFCC, 0xFFFF99, 0x99CCFF, 0xFF99CC, 0xCC99FF, 0xFFCC99, //
+    0x3366FF, 0x33CCCC, 0x99CC00, 0xFFCC00, 0xFF9900, 0xFF6600, 0x666699, 0x969696, //
+    0x003366, 0x339966, 0x003300, 0x333300, 0x993300, 0x993366, 0x333399, 0x333333, //
+];
+
+/// Office 2013+ theme colors in `theme=` index order: lt1, dk1, lt2, dk2,
+/// accent1..accent6, hlink, folHlink.
+const DEFAULT_THEME: [u32; 12] = [
+    0xFFFFFF, 0x000000, 0xE7E6E6, 0x44546A, 0x4472C4, 0xED7D31, 0xA5A5A5, 0xFFC000, 0x5B9BD5,
+    0x70AD47, 0x0563C1, 0x954F72,
+];
+
+/// `<a:clrScheme>` children in document order, with their `theme=` index.
+/// Excel swaps the dark/light pairs: `theme="0"` is lt1, `theme="1"` is dk1.
+const SCHEME_SLOTS: [(&[u8], usize); 12] = [
+    (b"dk1", 1),
+    (b"lt1", 0),
+    (b"dk2", 3),
+    (b"lt2", 2),
+    (b"accent1", 4),
+    (b"accent2", 5),
+    (b"accent3", 6),
+    (b"accent4", 7),
+    (b"accent5", 8),
+    (b"accent6", 9),
+    (b"hlink", 10),
+    (b"folHlink", 11),
+];
+
+pub(crate) type Rgb = [u8; 3];
+
+fn rgb_from_u32(v: u32) -> Rgb {
+    [(v >> 16) as u8, (v >> 8) as u8, v as u8]
+}
+
+/// `"RRGGBB"`, `"AARRGGBB"` or either with a leading `#`; alpha is dropped.
+pub(crate) fn parse_hex(s: &str) -> Option<Rgb> {
+    let s = s.trim();
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    let hex = match hex.len() {
+        6 => hex,
+        8 => &hex[2..],
+        _ => return None,
+    };
+    u32::from_str_radix(hex, 16).ok().map(rgb_from_u32)
+}
+
+/// `"#RRGGBB"` for an RGB triple.
+pub(crate) fn to_hex(rgb: Rgb) -> String {
+    format!("#{:02X}{:02X}{:02X}", rgb[0], rgb[1], rgb[2])
+}
+
+// ---------------------------------------------------------------------------
+// Tint
+// ---------------------------------------------------------------------------
+
+fn rgb_to_hls(rgb: Rgb) -> (f64, f64, f64) {
+    let [r, g, b] = rgb.map(|c| c as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    if max == min {
+        return (0.0, l, 0.0);
+    }
+    let d = max - min;
+    let s = if l > 0.5 {
+        d / (2.0 - max - min)
+    } else {
+        d / (max + min)
+    };
+    let h = if max == r {
+        (g - b) / d + if g < b { 6.0 } else { 0.0 }
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+    (h / 6.0, l, s)
+}
+
+fn hls_to_rgb(h: f64, l: f64, s: f64) -> Rgb {
+    let channel = |p: f64, q: f64, mut t: f64| {
+        if t < 0.0 {
+            t += 1.0;
+        }
+        if t > 1.0 {
+            t -= 1.0;
+        }
+        if t < 1.0 / 6.0 {
+            p + (q - p) * 6.0 * t
+        } else if t < 0.5 {
+            q
+        } else if t < 2.0 / 3.0 {
+            p + (q - p) * (2.0 / 3.0 - t) * 6.0
+        } else {
+            p
+        }
+    };
+    let (r, g, b) = if s == 0.0 {
+        (l, l, l)
+    } else {
+        let q = if l < 0.5 {
+            l * (1.0 + s)
+        } else {
+            l + s - l * s
+        };
+        let p = 2.0 * l - q;
+        (
+            channel(p, q, h + 1.0 / 3.0),
+            channel(p, q, h),
+            channel(p, q, h - 1.0 / 3.0),
+        )
+    };
+    [r, g, b].map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8)
+}
+
+/// Lighten (`tint > 0`) or darken (`tint < 0`) a color the way Excel does,
+/// by scaling its HLS luminance.
+pub(crate) fn apply_tint(rgb: Rgb, tint: f64) -> Rgb {
+    if tint == 0.0 {
+        return rgb;
+    }
+    let (h, l, s) = rgb_to_hls(rgb);
+    let l = if tint < 0.0 {
+        l * (1.0 + tint)
+    } else {
+        l * (1.0 - tint) + tint
+    };
+    hls_to_rgb(h, l.clamp(0.0, 1.0), s)
+}
+
+// ---------------------------------------------------------------------------
+// Palette
+// ---------------------------------------------------------------------------
+
+/// A workbook's theme colors and indexed palette.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Palette {
+    theme: Vec<Rgb>,
+    indexed: Vec<Rgb>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            theme: DEFAULT_THEME.iter().copied().map(rgb_from_u32).collect(),
+            indexed: DEFAULT_INDEXED.iter().copied().map(rgb_from_u32).collect(),
+        }
+    }
+}
+
+impl Palette {
+    /// Palette from `xl/theme/theme1.xml` and `xl/styles.xml`; missing parts
+    /// (or colors they do not define) keep the Office defaults.
+    pub(crate) fn from_parts(theme_xml: Option<&str>, styles_xml: Option<&str>) -> Self {
+        let mut palette = Self::default();
+        if let Some(xml) = theme_xml {
+            for (idx, rgb) in theme_colors(xml) {
+                palette.theme[idx] = rgb;
+            }
+        }
+        if let Some(colors) = styles_xml.map(indexed_colors).filter(|c| !c.is_empty()) {
+            palette.indexed = colors;
+        }
+        palette
+    }
+
+    fn indexed(&self, idx: u32) -> Option<Rgb> {
+        match idx {
+            // System foreground / background.
+            64 => Some([0, 0, 0]),
+            65 => Some([0xFF, 0xFF, 0xFF]),
+            _ => self.indexed.get(idx as usize).copied(),
+        }
+    }
+}
+
+/// `(theme index, color)` pairs from a theme part's `<a:clrScheme>`.
+fn theme_colors(xml: &str) -> Vec<(usize, Rgb)> {
+    let mut reader = XmlReader::from_str(xml);
+    let mut out = Vec::new();
+    let mut in_scheme = false;
+    let mut slot: Option<usize> = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                let name = e.local_name();
+                if name.as_ref() == b"clrScheme" {
+                    in_scheme = true;
+                } else if in_scheme {
+                    if let Some((_, idx)) = SCHEME_SLOTS.iter().find(|(n, _)| *n == name.as_ref()) {
+                        slot = Some(*idx);
+                        continue;
+                    }
+                    let value = match name.as_ref() {
+                        b"srgbClr" => attr_value(&e, b"val"),
+                        b"sysClr" => attr_value(&e, b"lastClr"),
+                        _ => None,
+                    };
+                    if let (Some(idx), Some(rgb)) = (slot, value.as_deref().and_then(parse_hex)) {
+                        out.push((idx, rgb));
+                        slot = None;
+                    }
+                }
+            }
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"clrScheme" => break,
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    out
+}
+
+/// A custom `<colors><indexedColors>` palette from styles.xml, if any.
+fn indexed_colors(xml: &str) -> Vec<Rgb> {
+    let mut reader = XmlReader::from_str(xml);
+    let mut out = Vec::new();
+    let mut inside = false;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"indexedColors" => inside = true,
+            Ok(Event::Start(e)) | Ok(Event::Empty(e))
+                if inside && e.local_name().as_ref() == b"rgbColor" =>
+            {
+                out.push(
+                    attr_value(&e, b"rgb")
+                        .as_deref()
+                        .and_then(parse_hex)
+                        .unwrap_or([0, 0, 0]),
+                );
+            }
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"indexedColors" => break,
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Color references
+// ---------------------------------------------------------------------------
+
+/// What a `<color>`-like element points at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ColorBase {
+    Rgb(Rgb),
+    Theme(u32),
+    Indexed(u32),
+    /// `auto="1"`: the system foreground color.
+    Auto,
+}
+
+/// A color as stored in styles.xml: a base plus an optional tint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ColorSpec {
+    pub base: ColorBase,
+    pub tint: f64,
+}
+
+impl ColorSpec {
+    pub(crate) fn rgb(rgb: Rgb) -> Self {
+        Self {
+            base: ColorBase::Rgb(rgb),
+            tint: 0.0,
+        }
+    }
+
+    /// Parse a user-facing color string: `"#RRGGBB"` (or bare/ARGB hex),
+    /// `"theme:N[:tint]"` or `"indexed:N"`.
+    pub(crate) fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let bad = || format!("Invalid color '{s}' (expected #RRGGBB, theme:N[:tint] or indexed:N)");
+        let index = |v: &str| v.trim().parse::<u32>().map_err(|_| bad());
+        let mut parts = s.splitn(3, ':');
+        let kind = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let (base, tint) = match (kind.as_str(), parts.next(), parts.next()) {
+            ("theme", Some(idx), tint) => {
+                let idx = index(idx)?;
+                if idx as usize >= DEFAULT_THEME.len() {
+                    return Err(format!("Theme color index {idx} out of range (0-11)"));
+                }
+                let tint = match tint {
+                    Some(t) => t.trim().parse::<f64>().map_err(|_| bad())?,
+                    None => 0.0,
+                };
+                if !(-1.0..=1.0).contains(&tint) {
+                    return Err(format!("Tint {tint} out of range (-1.0 to 1.0)"));
+                }
+                (ColorBase::Theme(idx), tint)
+            }
+            ("indexed", Some(idx), None) => (ColorBase::Indexed(index(idx)?), 0.0),
+            (_, None, None) => (ColorBase::Rgb(parse_hex(s).ok_or_else(bad)?), 0.0),
+            _ => return Err(bad()),
+        };
+        Ok(Self { base, tint })
+    }
+
+    /// Read `rgb` / `theme` / `indexed` / `auto` / `tint` attributes through
+    /// `attr` (e.g. `|k| attr_value(&e, k)`). None when none are present.
+    pub(crate) fn from_attrs(attr: impl Fn(&[u8]) -> Option<String>) -> Option<Self> {
+        let index = |key: &[u8]| attr(key).and_then(|v| v.trim().parse::<u32>().ok());
+        let base = if let Some(rgb) = attr(b"rgb").as_deref().and_then(parse_hex) {
+            ColorBase::Rgb(rgb)
+        } else if let Some(idx) = index(b"theme") {
+            ColorBase::Theme(idx)
+        } else if let Some(idx) = index(b"indexed") {
+            ColorBase::Indexed(idx)
+        } else if matches!(attr(b"auto").as_deref(), Some("1") | Some("true")) {
+            ColorBase::Auto
+        } else {
+            return None;
+        };
+        let tint = attr(b"tint")
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .unwrap_or(0.0);
+        Some(Self { base, tint })
+    }
+
+    /// The color as `"#RRGGBB"`, or None for an unknown theme/indexed slot.
+    pub(crate) fn resolve(&self, palette: &Palette) -> Option<String> {
+        let rgb = match self.base {
+            ColorBase::Rgb(rgb) => rgb,
+            ColorBase::Theme(idx) => *palette.theme.get(idx as usize)?,
+            ColorBase::Indexed(idx) => palette.indexed(idx)?,
+            ColorBase::Auto => [0, 0, 0],
+        };
+        Some(to_hex(apply_tint(rgb, self.tint)))
+    }
+
+    /// Attributes for a styles.xml `<color>` / `<fgColor>` element.
+    pub(crate) fn xml_attrs(&self) -> String {
+        let mut out = match self.base {
+            ColorBase::Rgb([r, g, b]) => format!("rgb=\"FF{r:02X}{g:02X}{b:02X}\""),
+            ColorBase::Theme(idx) => format!("theme=\"{idx}\""),
+            ColorBase::Indexed(idx) => format!("indexed=\"{idx}\""),
+            ColorBase::Auto => "auto=\"1\"".to_string(),
+        };
+        if self.tint != 0.0 {
+            out.push_str(&format!(" tint=\"{}\"", self.tint));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_xml_attrs() {
+        let attrs = |s: &str| ColorSpec::parse(s).unwrap().xml_attrs();
+        assert_eq!(attrs("#ff0000"), r#"rgb="FFFF0000""#);
+        assert_eq!(attrs("80FF0000"), r#"rgb="FFFF0000""#);
+        assert_eq!(attrs("theme:4"), r#"theme="4""#);
+        assert_eq!(attrs("Theme:4:-0.25"), r#"theme="4" tint="-0.25""#);
+        assert_eq!(attrs("indexed:10"), r#"indexed="10""#);
+        for bad in ["#GG0000", "theme:12", "theme:1:2", "indexed:1:0.5", "red"] {
+            assert!(ColorSpec::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_resolve_theme_indexed_and_tint() {
+        let palette = Palette::default();
+        let resolve = |s: &str| ColorSpec::parse(s).unwrap().resolve(&palette).unwrap();
+        assert_eq!(resolve("theme:0"), "#FFFFFF");
+        assert_eq!(resolve("theme:1"), "#000000");
+        assert_eq!(resolve("theme:4"), "#4472C4");
+        assert_eq!(resolve("theme:4:-0.25"), "#2F5597");
+        assert_eq!(resolve("theme:4:0.3999755851924192"), "#8FAADC");
+        assert_eq!(resolve("theme:0:-0.1499984740745262"), "#D9D9D9");
+        assert_eq!(resolve("indexed:10"), "#FF0000");
+        assert_eq!(resolve("indexed:64"), "#000000");
+    }
+
+    #[test]
+    fn test_from_attrs() {
+        let spec = |pairs: &[(&str, &str)]| {
+            ColorSpec::from_attrs(|k| {
+                pairs
+                    .iter()
+                    .find(|(n, _)| n.as_bytes() == k)
+                    .map(|(_, v)| v.to_string())
+            })
+        };
+        assert_eq!(spec(&[]), None);
+        assert_eq!(
+            spec(&[("theme", "5"), ("tint", "0.5")]),
+            Some(ColorSpec {
+                base: ColorBase::Theme(5),
+                tint: 0.5
+            })
+        );
+        assert_eq!(
+            spec(&[("rgb", "FF00FF00")]),
+            Some(ColorSpec::rgb([0, 0xFF, 0]))
+        );
+        assert_eq!(spec(&[("auto", "1")]).unwrap().base, ColorBase::Auto);
+    }
+
+    #[test]
+    fn test_palette_from_parts() {
+        let theme = concat!(
+            r#"<a:theme xmlns:a="x"><a:themeElements><a:clrScheme name="Custom">"#,
+            r#"<a:dk1><a:sysClr val="windowText" lastClr="111111"/></a:dk1>"#,
+            r#"<a:lt1><a:sysClr val="window" lastClr="FEFEFE"/></a:lt1>"#,
+            r#"<a:dk2><a:srgbClr val="222222"/></a:dk2>"#,
+            r#"<a:accent1><a:srgbClr val="ABCDEF"/></a:accent1>"#,
+            r#"</a:clrScheme></a:themeElements></a:theme>"#
+        );
+        let styles = concat!(
+            r#"<styleSheet><colors><indexedColors>"#,
+            r#"<rgbColor rgb="FF010203"/><rgbColor rgb="FF040506"/>"#,
+            r#"</indexedColors></colors></styleSheet>"#
+        );
+        let palette = Palette::from_parts(Some(theme), Some(styles));
+        let resolve = |s: &str| ColorSpec::parse(s).unwrap().resolve(&palette);
+        assert_eq!(resolve("theme:0").as_deref(), Some("#FEFEFE"));
+        assert_eq!(resolve("theme:1").as_deref(), Some("#111111"));
+        assert_eq!(resolve("theme:3").as_deref(), Some("#222222"));
+        assert_eq!(resolve("theme:4").as_deref(), Some("#ABCDEF"));
+        // Slots the theme leaves out keep the defaults.
+        assert_eq!(resolve("theme:5").as_deref(), Some("#ED7D31"));
+        assert_eq!(resolve("indexed:1").as_deref(), Some("#040506"));
+        assert_eq!(resolve("indexed:2"), None);
+    }
+}
@@ -0,0 +1,347 @@
+//! CSV export shared by the read backends' `export_csv()`.
+//!
+//! Backends map their cells onto `CsvCell` and feed rows to `CsvWriter`,
+//! which handles quoting and number/date rendering. Writing happens without
+//! Python objects, so callers can release the GIL around it.
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{NaiveDate, NaiveDateTime};
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// One cell value as exported to CSV.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CsvCell<'a> {
+    Empty,
+    Text(Cow<'a, str>),
+    Number(f64),
+    Bool(bool),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+/// When fields are wrapped in quotes (mirrors Python's `csv.QUOTE_*`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Quoting {
+    /// Only fields containing the delimiter, quote or a line break.
+    Minimal,
+    /// Every non-empty field.
+    All,
+    /// Every non-empty field that is not a number.
+    NonNumeric,
+    /// Never; a field that would need quoting is an error.
+    Never,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CsvExportOptions {
+    pub delimiter: char,
+    pub quote: char,
+    pub quoting: Quoting,
+    pub line_terminator: String,
+    /// strftime format for date cells.
+    pub date_format: String,
+    /// strftime format for datetime cells.
+    pub datetime_format: String,
+}
+
+impl Default for CsvExportOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: '"',
+            quoting: Quoting::Minimal,
+            line_terminator: "\n".to_string(),
+            date_format: "%Y-%m-%d".to_string(),
+            datetime_format: "%Y-%m-%d %H:%M:%S".to_string(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Python-facing helpers
+// ---------------------------------------------------------------------------
+
+fn option_str(d: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<String>> {
+    match d.get_item(key)? {
+        Some(v) if !v.is_none() => v.extract().map(Some).map_err(|_| {
+            PyErr::new::<PyValueError, _>(format!("CSV option '{key}' must be a str"))
+        }),
+        _ => Ok(None),
+    }
+}
+
+fn option_char(d: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<char>> {
+    let Some(s) = option_str(d, key)? else {
+        return Ok(None);
+    };
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(Some(c)),
+        _ => Err(PyErr::new::<PyValueError, _>(format!(
+            "CSV option '{key}' must be a single character"
+        ))),
+    }
+}
+
+fn check_strftime(key: &str, fmt: &str) -> PyResult<()> {
+    if StrftimeItems::new(fmt).any(|item| matches!(item, Item::Error)) {
+        return Err(PyErr::new::<PyValueError, _>(format!(
+            "CSV option '{key}' is not a valid strftime format: {fmt}"
+        )));
+    }
+    Ok(())
+}
+
+/// Parse the `options` dict: `delimiter`, `quote`, `quoting` (`"minimal"`,
+/// `"all"`, `"nonnumeric"`, `"none"`), `line_terminator`, `date_format` and
+/// `datetime_format`.
+pub(crate) fn parse_export_options(
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<CsvExportOptions> {
+    let mut opts = CsvExportOptions::default();
+    let Some(d) = options else {
+        return Ok(opts);
+    };
+    for key in d.keys() {
+        let key: String = key.extract()?;
+        if !matches!(
+            key.as_str(),
+            "delimiter"
+                | "quote"
+                | "quoting"
+                | "line_terminator"
+                | "date_format"
+                | "datetime_format"
+        ) {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "Unknown CSV option: {key}"
+            )));
+        }
+    }
+    if let Some(c) = option_char(d, "delimiter")? {
+        opts.delimiter = c;
+    }
+    if let Some(c) = option_char(d, "quote")? {
+        opts.quote = c;
+    }
+    if let Some(q) = option_str(d, "quoting")? {
+        opts.quoting = match q.as_str() {
+            "minimal" => Quoting::Minimal,
+            "all" => Quoting::All,
+            "nonnumeric" => Quoting::NonNumeric,
+            "none" => Quoting::Never,
+            other => {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "Unknown CSV quoting: {other}"
+                )))
+            }
+        };
+    }
+    if let Some(t) = option_str(d, "line_terminator")? {
+        opts.line_terminator = t;
+    }
+    if let Some(f) = option_str(d, "date_format")? {
+        check_strftime("date_format", &f)?;
+        opts.date_format = f;
+    }
+    if let Some(f) = option_str(d, "datetime_format")? {
+        check_strftime("datetime_format", &f)?;
+        opts.datetime_format = f;
+    }
+    if opts.quote == opts.delimiter {
+        return Err(PyErr::new::<PyValueError, _>(
+            "CSV delimiter and quote must differ",
+        ));
+    }
+    Ok(opts)
+}
+
+/// Create `path` and wrap it in a `CsvWriter`.
+pub(crate) fn create(path: &str, opts: CsvExportOptions) -> PyResult<CsvWriter<File>> {
+    let file = File::create(path)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to create CSV: {e}")))?;
+    Ok(CsvWriter::new(file, opts))
+}
+
+/// Map a write failure onto the Python error callers raise.
+pub(crate) fn write_error(e: io::Error) -> PyErr {
+    match e.kind() {
+        io::ErrorKind::InvalidData => PyErr::new::<PyValueError, _>(e.to_string()),
+        _ => PyErr::new::<PyIOError, _>(format!("Failed to write CSV: {e}")),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Writer
+// ---------------------------------------------------------------------------
+
+pub(crate) struct CsvWriter<W: Write> {
+    out: BufWriter<W>,
+    opts: CsvExportOptions,
+    field: String,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub(crate) fn new(out: W, opts: CsvExportOptions) -> Self {
+        Self {
+            out: BufWriter::new(out),
+            opts,
+            field: String::new(),
+        }
+    }
+
+    pub(crate) fn write_row<'a>(
+        &mut self,
+        cells: impl IntoIterator<Item = CsvCell<'a>>,
+    ) -> io::Result<()> {
+        for (i, cell) in cells.into_iter().enumerate() {
+            if i > 0 {
+                write!(self.out, "{}", self.opts.delimiter)?;
+            }
+            self.write_field(&cell)?;
+        }
+        self.out.write_all(self.opts.line_terminator.as_bytes())
+    }
+
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    fn write_field(&mut self, cell: &CsvCell<'_>) -> io::Result<()> {
+        use std::fmt::Write as _;
+
+        self.field.clear();
+        let numeric = match cell {
+            CsvCell::Empty => return Ok(()),
+            CsvCell::Text(s) => {
+                self.field.push_str(s);
+                false
+            }
+            CsvCell::Number(n) => {
+                self.field.push_str(&render_number(*n));
+                true
+            }
+            CsvCell::Bool(b) => {
+                self.field.push_str(if *b { "TRUE" } else { "FALSE" });
+                false
+            }
+            CsvCell::Date(d) => {
+                let _ = write!(self.field, "{}", d.format(&self.opts.date_format));
+                false
+            }
+            CsvCell::DateTime(dt) => {
+                let _ = write!(self.field, "{}", dt.format(&self.opts.datetime_format));
+                false
+            }
+        };
+
+        let special = self.field.contains(|c: char| {
+            c == self.opts.delimiter || c == self.opts.quote || c == '\n' || c == '\r'
+        });
+        let quote = match self.opts.quoting {
+            Quoting::Minimal => special,
+            Quoting::All => true,
+            Quoting::NonNumeric => !numeric || special,
+            Quoting::Never if special => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("CSV field needs quoting but quoting='none': {}", self.field),
+                ));
+            }
+            Quoting::Never => false,
+        };
+
+        if !quote {
+            return self.out.write_all(self.field.as_bytes());
+        }
+        let q = self.opts.quote;
+        let escaped = self.field.replace(q, &format!("{q}{q}"));
+        write!(self.out, "{q}{escaped}{q}")
+    }
+}
+
+/// Whole numbers render without a fractional part (`3`, not `3.0`).
+pub(crate) fn render_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(opts: CsvExportOptions, rows: Vec<Vec<CsvCell<'static>>>) -> String {
+        let mut buf = Vec::new();
+        let mut w = CsvWriter::new(&mut buf, opts);
+        for row in rows {
+            w.write_row(row).unwrap();
+        }
+        w.finish().unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_minimal_quoting_and_rendering() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let out = render(
+            CsvExportOptions::default(),
+            vec![vec![
+                CsvCell::Text("a,b".into()),
+                CsvCell::Text("say \"hi\"".into()),
+                CsvCell::Empty,
+                CsvCell::Number(3.0),
+                CsvCell::Number(1.25),
+                CsvCell::Bool(true),
+                CsvCell::Date(date),
+                CsvCell::DateTime(date.and_hms_opt(10, 30, 0).unwrap()),
+            ]],
+        );
+        assert_eq!(
+            out,
+            "\"a,b\",\"say \"\"hi\"\"\",,3,1.25,TRUE,2024-06-15,2024-06-15 10:30:00\n"
+        );
+    }
+
+    #[test]
+    fn test_nonnumeric_quoting_and_custom_formats() {
+        let opts = CsvExportOptions {
+            delimiter: ';',
+            quoting: Quoting::NonNumeric,
+            line_terminator: "\r\n".to_string(),
+            date_format: "%d/%m/%Y".to_string(),
+            ..CsvExportOptions::default()
+        };
+        let date = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let out = render(
+            opts,
+            vec![vec![
+                CsvCell::Text("x".into()),
+                CsvCell::Number(-2.5),
+                CsvCell::Date(date),
+            ]],
+        );
+        assert_eq!(out, "\"x\";-2.5;\"15/06/2024\"\r\n");
+    }
+
+    #[test]
+    fn test_never_quoting_rejects_special_fields() {
+        let opts = CsvExportOptions {
+            quoting: Quoting::Never,
+            ..CsvExportOptions::default()
+        };
+        let mut buf = Vec::new();
+        let mut w = CsvWriter::new(&mut buf, opts);
+        w.write_row(vec![CsvCell::Text("plain".into())]).unwrap();
+        let err = w.write_row(vec![CsvCell::Text("a,b".into())]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
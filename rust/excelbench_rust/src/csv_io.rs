@@ -0,0 +1,375 @@
+//! CSV import shared by the write backends' `load_csv()`.
+//!
+//! A small RFC 4180 reader (configurable delimiter and quote character) plus
+//! per-field type inference onto `CellPayload`. Quoted fields always stay
+//! strings, so `"007"` survives as text.
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use crate::payload::CellPayload;
+use crate::util::{parse_iso_date, parse_iso_datetime};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CsvEncoding {
+    /// UTF-8; a leading byte-order mark is dropped.
+    Utf8,
+    /// ISO-8859-1: every byte maps to the code point of the same value.
+    Latin1,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CsvOptions {
+    pub delimiter: char,
+    /// None disables quote handling entirely.
+    pub quote: Option<char>,
+    pub encoding: CsvEncoding,
+    /// Infer numbers, booleans and ISO dates; otherwise every field is a string.
+    pub infer_types: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: Some('"'),
+            encoding: CsvEncoding::Utf8,
+            infer_types: true,
+        }
+    }
+}
+
+/// One parsed field; `quoted` records whether it was written in quotes.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CsvField {
+    pub text: String,
+    pub quoted: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Python-facing helpers
+// ---------------------------------------------------------------------------
+
+fn single_char(d: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<Option<char>>> {
+    let Some(v) = d.get_item(key)? else {
+        return Ok(None);
+    };
+    if v.is_none() {
+        return Ok(Some(None));
+    }
+    let s: String = v.extract().map_err(|_| bad_char(key))?;
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(Some(Some(c))),
+        _ => Err(bad_char(key)),
+    }
+}
+
+fn bad_char(key: &str) -> PyErr {
+    PyErr::new::<PyValueError, _>(format!("CSV option '{key}' must be a single character"))
+}
+
+/// Parse the `options` dict: `delimiter`, `quote` (None disables quoting),
+/// `encoding` (`utf-8`/`utf-8-sig`/`latin-1`) and `infer_types`.
+pub(crate) fn parse_options(options: Option<&Bound<'_, PyDict>>) -> PyResult<CsvOptions> {
+    let mut opts = CsvOptions::default();
+    let Some(d) = options else {
+        return Ok(opts);
+    };
+    for key in d.keys() {
+        let key: String = key.extract()?;
+        if !matches!(
+            key.as_str(),
+            "delimiter" | "quote" | "encoding" | "infer_types"
+        ) {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "Unknown CSV option: {key}"
+            )));
+        }
+    }
+    if let Some(delim) = single_char(d, "delimiter")? {
+        opts.delimiter = delim.ok_or_else(|| bad_char("delimiter"))?;
+    }
+    if let Some(quote) = single_char(d, "quote")? {
+        opts.quote = quote;
+    }
+    if let Some(enc) = d.get_item("encoding")?.filter(|v| !v.is_none()) {
+        let enc: String = enc.extract()?;
+        opts.encoding = parse_encoding(&enc).ok_or_else(|| {
+            PyErr::new::<PyValueError, _>(format!("Unsupported CSV encoding: {enc}"))
+        })?;
+    }
+    if let Some(infer) = d.get_item("infer_types")?.filter(|v| !v.is_none()) {
+        opts.infer_types = infer.extract()?;
+    }
+    if opts.quote == Some(opts.delimiter) {
+        return Err(PyErr::new::<PyValueError, _>(
+            "CSV delimiter and quote must differ",
+        ));
+    }
+    Ok(opts)
+}
+
+fn parse_encoding(name: &str) -> Option<CsvEncoding> {
+    match name.to_ascii_lowercase().replace('_', "-").as_str() {
+        "utf-8" | "utf8" | "utf-8-sig" => Some(CsvEncoding::Utf8),
+        "latin-1" | "latin1" | "iso-8859-1" => Some(CsvEncoding::Latin1),
+        _ => None,
+    }
+}
+
+/// Load CSV cells from a path (`str`) or in-memory `bytes`.
+///
+/// Decoding, parsing and inference run with the GIL released. Returns rows of
+/// payloads; empty unquoted fields are `CellPayload::Blank`.
+pub(crate) fn load(
+    py: Python<'_>,
+    path_or_bytes: &Bound<'_, PyAny>,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Vec<Vec<CellPayload>>> {
+    let opts = parse_options(options)?;
+    let bytes = if let Ok(b) = path_or_bytes.downcast::<PyBytes>() {
+        b.as_bytes().to_vec()
+    } else {
+        let path: String = path_or_bytes
+            .extract()
+            .map_err(|_| PyErr::new::<PyValueError, _>("path_or_bytes must be a str or bytes"))?;
+        py.allow_threads(|| std::fs::read(&path))
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read CSV: {e}")))?
+    };
+    py.allow_threads(|| {
+        let text = decode(&bytes, opts.encoding)?;
+        let records = parse_records(&text, &opts)?;
+        Ok(records
+            .into_iter()
+            .map(|row| {
+                row.iter()
+                    .map(|f| infer_cell(f, opts.infer_types))
+                    .collect()
+            })
+            .collect())
+    })
+    .map_err(PyErr::new::<PyValueError, _>)
+}
+
+// ---------------------------------------------------------------------------
+// Decoding, parsing and inference
+// ---------------------------------------------------------------------------
+
+pub(crate) fn decode(bytes: &[u8], encoding: CsvEncoding) -> Result<String, String> {
+    match encoding {
+        CsvEncoding::Utf8 => {
+            let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+            String::from_utf8(bytes.to_vec()).map_err(|e| format!("CSV is not valid UTF-8: {e}"))
+        }
+        CsvEncoding::Latin1 => Ok(bytes.iter().map(|&b| b as char).collect()),
+    }
+}
+
+/// Split CSV text into records. `\n`, `\r\n` and `\r` all end a record, and a
+/// trailing newline does not produce an empty final record.
+pub(crate) fn parse_records(text: &str, opts: &CsvOptions) -> Result<Vec<Vec<CsvField>>, String> {
+    let mut records = Vec::new();
+    let mut record: Vec<CsvField> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    let mut quote_line = 0;
+    let mut line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if Some(c) == opts.quote {
+                if chars.peek().copied() == opts.quote {
+                    chars.next();
+                    field.push(c);
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+            continue;
+        }
+
+        if Some(c) == opts.quote && field.is_empty() && !quoted {
+            in_quotes = true;
+            quoted = true;
+            quote_line = line;
+        } else if c == opts.delimiter {
+            record.push(CsvField {
+                text: std::mem::take(&mut field),
+                quoted: std::mem::take(&mut quoted),
+            });
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            line += 1;
+            record.push(CsvField {
+                text: std::mem::take(&mut field),
+                quoted: std::mem::take(&mut quoted),
+            });
+            records.push(std::mem::take(&mut record));
+        } else {
+            field.push(c);
+        }
+    }
+
+    if in_quotes {
+        return Err(format!(
+            "Unterminated quoted field starting on line {quote_line}"
+        ));
+    }
+    if !field.is_empty() || quoted || !record.is_empty() {
+        record.push(CsvField {
+            text: field,
+            quoted,
+        });
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Infer a cell from one field: booleans (`true`/`false`, any case), numbers
+/// (no leading zeros, so IDs like `007` stay text), ISO dates and datetimes.
+pub(crate) fn infer_cell(field: &CsvField, infer_types: bool) -> CellPayload {
+    let s = field.text.as_str();
+    if s.is_empty() && !field.quoted {
+        return CellPayload::Blank;
+    }
+    if field.quoted || !infer_types {
+        return CellPayload::String(field.text.clone());
+    }
+    if s.eq_ignore_ascii_case("true") {
+        return CellPayload::Boolean(true);
+    }
+    if s.eq_ignore_ascii_case("false") {
+        return CellPayload::Boolean(false);
+    }
+    if let Some(n) = parse_number(s) {
+        return CellPayload::Number(n);
+    }
+    if let Some(d) = parse_iso_date(s) {
+        return CellPayload::Date(d);
+    }
+    if let Some(dt) = parse_iso_datetime(s) {
+        return CellPayload::DateTime(dt);
+    }
+    CellPayload::String(field.text.clone())
+}
+
+fn parse_number(s: &str) -> Option<f64> {
+    let unsigned = s.strip_prefix('-').unwrap_or(s);
+    let digits_end = unsigned
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(unsigned.len());
+    let int_part = &unsigned[..digits_end];
+    if int_part.is_empty() && !unsigned.starts_with('.') {
+        return None;
+    }
+    if int_part.len() > 1 && int_part.starts_with('0') {
+        return None;
+    }
+    if !unsigned[digits_end..]
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'))
+    {
+        return None;
+    }
+    s.parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(text: &str) -> Vec<Vec<String>> {
+        parse_records(text, &CsvOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|r| r.into_iter().map(|f| f.text).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_records_handles_quotes_and_line_endings() {
+        assert_eq!(
+            fields("a,b\r\n\"x,y\",\"say \"\"hi\"\"\"\n\"multi\nline\",\n"),
+            vec![
+                vec!["a".to_string(), "b".to_string()],
+                vec!["x,y".to_string(), "say \"hi\"".to_string()],
+                vec!["multi\nline".to_string(), String::new()],
+            ]
+        );
+        assert_eq!(fields("a\rb"), vec![vec!["a"], vec!["b"]]);
+        assert!(parse_records("\"open", &CsvOptions::default())
+            .unwrap_err()
+            .contains("line 1"));
+    }
+
+    #[test]
+    fn test_parse_records_custom_delimiter_without_quotes() {
+        let opts = CsvOptions {
+            delimiter: ';',
+            quote: None,
+            ..CsvOptions::default()
+        };
+        let rows = parse_records("\"a\";b", &opts).unwrap();
+        assert_eq!(rows[0][0].text, "\"a\"");
+        assert_eq!(rows[0][1].text, "b");
+    }
+
+    #[test]
+    fn test_infer_cell_types() {
+        let field = |text: &str, quoted: bool| CsvField {
+            text: text.to_string(),
+            quoted,
+        };
+        assert_eq!(infer_cell(&field("", false), true), CellPayload::Blank);
+        assert_eq!(
+            infer_cell(&field("-1.5e3", false), true),
+            CellPayload::Number(-1500.0)
+        );
+        assert_eq!(
+            infer_cell(&field(".5", false), true),
+            CellPayload::Number(0.5)
+        );
+        assert_eq!(
+            infer_cell(&field("TRUE", false), true),
+            CellPayload::Boolean(true)
+        );
+        assert_eq!(
+            infer_cell(&field("007", false), true),
+            CellPayload::String("007".to_string())
+        );
+        assert_eq!(
+            infer_cell(&field("inf", false), true),
+            CellPayload::String("inf".to_string())
+        );
+        assert_eq!(
+            infer_cell(&field("2024-06-15", false), true),
+            CellPayload::Date(parse_iso_date("2024-06-15").unwrap())
+        );
+        assert_eq!(
+            infer_cell(&field("42", true), true),
+            CellPayload::String("42".to_string())
+        );
+        assert_eq!(
+            infer_cell(&field("42", false), false),
+            CellPayload::String("42".to_string())
+        );
+    }
+
+    #[test]
+    fn test_decode_strips_bom_and_maps_latin1() {
+        assert_eq!(decode(b"\xEF\xBB\xBFa", CsvEncoding::Utf8).unwrap(), "a");
+        assert_eq!(decode(b"caf\xE9", CsvEncoding::Latin1).unwrap(), "café");
+        assert!(decode(b"caf\xE9", CsvEncoding::Utf8).is_err());
+    }
+}
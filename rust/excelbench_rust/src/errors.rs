@@ -0,0 +1,210 @@
+//! Structured exceptions raised by the Rust backends.
+//!
+//! Every exception carries `backend`, `path`, `sheet` and `cell` attributes
+//! (None when not applicable) so the harness can classify failures by type
+//! instead of matching message text.
+//!
+//! ```text
+//! ExcelBenchError(Exception)
+//! ├── FileFormatError(ExcelBenchError, OSError)
+//! ├── SheetNotFound(ExcelBenchError, ValueError)
+//! ├── CellRefError(ExcelBenchError, ValueError)
+//! └── UnsupportedFeature(ExcelBenchError, NotImplementedError)
+//! ```
+//!
+//! The leaves also derive from the builtin the backends raised before, so
+//! existing `except ValueError` / `except OSError` callers keep working.
+//! `create_exception!` only supports a single base, so the leaves are built
+//! at runtime with `type(name, bases, namespace)` and cached per interpreter.
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyNotImplementedError, PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyDict, PyModule, PyTuple, PyType};
+
+create_exception!(
+    _rust,
+    ExcelBenchError,
+    PyException,
+    "Base class for errors raised by the Rust backends."
+);
+
+/// Leaf exception classes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ErrorKind {
+    FileFormat,
+    SheetNotFound,
+    CellRef,
+    Unsupported,
+}
+
+impl ErrorKind {
+    const ALL: [ErrorKind; 4] = [
+        ErrorKind::FileFormat,
+        ErrorKind::SheetNotFound,
+        ErrorKind::CellRef,
+        ErrorKind::Unsupported,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            ErrorKind::FileFormat => "FileFormatError",
+            ErrorKind::SheetNotFound => "SheetNotFound",
+            ErrorKind::CellRef => "CellRefError",
+            ErrorKind::Unsupported => "UnsupportedFeature",
+        }
+    }
+
+    fn doc(self) -> &'static str {
+        match self {
+            ErrorKind::FileFormat => "The file could not be opened or is not a valid workbook.",
+            ErrorKind::SheetNotFound => "The requested sheet does not exist in the workbook.",
+            ErrorKind::CellRef => "A cell or range reference could not be parsed.",
+            ErrorKind::Unsupported => "The backend does not support the requested feature.",
+        }
+    }
+
+    fn builtin<'py>(self, py: Python<'py>) -> Bound<'py, PyType> {
+        match self {
+            ErrorKind::FileFormat => py.get_type::<PyOSError>(),
+            ErrorKind::SheetNotFound | ErrorKind::CellRef => py.get_type::<PyValueError>(),
+            ErrorKind::Unsupported => py.get_type::<PyNotImplementedError>(),
+        }
+    }
+
+    fn cell(self) -> &'static GILOnceCell<Py<PyType>> {
+        static FILE_FORMAT: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+        static SHEET_NOT_FOUND: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+        static CELL_REF: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+        static UNSUPPORTED: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+        match self {
+            ErrorKind::FileFormat => &FILE_FORMAT,
+            ErrorKind::SheetNotFound => &SHEET_NOT_FOUND,
+            ErrorKind::CellRef => &CELL_REF,
+            ErrorKind::Unsupported => &UNSUPPORTED,
+        }
+    }
+
+    /// The Python class for this kind, created on first use.
+    fn type_object<'py>(self, py: Python<'py>) -> PyResult<Bound<'py, PyType>> {
+        let ty = self
+            .cell()
+            .get_or_try_init(py, || -> PyResult<Py<PyType>> {
+                let base = py.get_type::<ExcelBenchError>();
+                let bases = PyTuple::new(py, [base.clone(), self.builtin(py)])?;
+                let ns = PyDict::new(py);
+                ns.set_item("__module__", base.getattr("__module__")?)?;
+                ns.set_item("__doc__", self.doc())?;
+                let ty = py
+                    .get_type::<PyType>()
+                    .call1((self.name(), bases, ns))?
+                    .downcast_into::<PyType>()?;
+                Ok(ty.unbind())
+            })?;
+        Ok(ty.bind(py).clone())
+    }
+}
+
+/// Where an error happened; absent fields become None on the exception.
+#[derive(Default)]
+pub(crate) struct ErrorContext<'a> {
+    pub backend: Option<&'a str>,
+    pub path: Option<&'a str>,
+    pub sheet: Option<&'a str>,
+    pub cell: Option<&'a str>,
+}
+
+/// Build a `kind` exception carrying `ctx` as attributes.
+pub(crate) fn raise(kind: ErrorKind, message: impl Into<String>, ctx: ErrorContext<'_>) -> PyErr {
+    let message = message.into();
+    Python::with_gil(|py| {
+        let build = || -> PyResult<PyErr> {
+            let exc = kind.type_object(py)?.call1((message.as_str(),))?;
+            exc.setattr("backend", ctx.backend)?;
+            exc.setattr("path", ctx.path)?;
+            exc.setattr("sheet", ctx.sheet)?;
+            exc.setattr("cell", ctx.cell)?;
+            Ok(PyErr::from_value(exc))
+        };
+        build().unwrap_or_else(|e| e)
+    })
+}
+
+/// `SheetNotFound` for `sheet`.
+pub(crate) fn sheet_not_found(backend: &str, sheet: &str) -> PyErr {
+    raise(
+        ErrorKind::SheetNotFound,
+        format!("Unknown sheet: {sheet}"),
+        ErrorContext {
+            backend: Some(backend),
+            sheet: Some(sheet),
+            ..Default::default()
+        },
+    )
+}
+
+/// `CellRefError` for an unparseable reference `cell`.
+pub(crate) fn cell_ref(backend: &str, cell: &str, message: impl Into<String>) -> PyErr {
+    raise(
+        ErrorKind::CellRef,
+        message,
+        ErrorContext {
+            backend: Some(backend),
+            cell: Some(cell),
+            ..Default::default()
+        },
+    )
+}
+
+/// `FileFormatError` for a file that cannot be opened or parsed.
+pub(crate) fn file_format(backend: &str, path: &str, message: impl Into<String>) -> PyErr {
+    raise(
+        ErrorKind::FileFormat,
+        message,
+        ErrorContext {
+            backend: Some(backend),
+            path: Some(path),
+            ..Default::default()
+        },
+    )
+}
+
+/// `UnsupportedFeature` for something `backend` cannot do.
+pub(crate) fn unsupported(backend: &str, message: impl Into<String>) -> PyErr {
+    raise(
+        ErrorKind::Unsupported,
+        message,
+        ErrorContext {
+            backend: Some(backend),
+            ..Default::default()
+        },
+    )
+}
+
+/// Input `backend` cannot represent. Strict books raise `UnsupportedFeature`;
+/// others log a warning to the `excelbench_rust.<backend>` logger and the
+/// caller carries on with its fallback.
+pub(crate) fn degrade(strict: bool, backend: &str, message: impl Into<String>) -> PyResult<()> {
+    let message = message.into();
+    if strict {
+        return Err(unsupported(backend, message));
+    }
+    log::warn!(target: &format!("excelbench_rust::{backend}"), "{message}");
+    Ok(())
+}
+
+/// `ValueError` for a method called after `close()`, as Python file objects do.
+pub(crate) fn closed(backend: &str) -> PyErr {
+    PyErr::new::<PyValueError, _>(format!("I/O operation on closed {backend} workbook"))
+}
+
+/// Add the exception classes to the extension module.
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("ExcelBenchError", py.get_type::<ExcelBenchError>())?;
+    for kind in ErrorKind::ALL {
+        m.add(kind.name(), kind.type_object(py)?)?;
+    }
+    Ok(())
+}
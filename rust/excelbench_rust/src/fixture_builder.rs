@@ -0,0 +1,361 @@
+//! Synthetic workbook generator for benchmark corpora.
+//!
+//! `FixtureBuilder` writes parameterized grids (size, value mix, style density,
+//! merge pattern) straight through rust_xlsxwriter, which is far faster than
+//! building the same files from Python. Output is deterministic for a given
+//! seed, and every build returns a manifest describing what was written.
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use rust_xlsxwriter::{Color, Format, FormatAlign, FormatBorder, Workbook};
+
+use crate::cell_ref::{col_to_letters, MAX_COLS, MAX_ROWS};
+
+// ---------------------------------------------------------------------------
+// Spec
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MergePattern {
+    None,
+    /// Two cells across: (r, A)-(r, B).
+    Horizontal,
+    /// Two cells down: (r, A)-(r+1, A).
+    Vertical,
+    /// 2x2 block: (r, A)-(r+1, B).
+    Block,
+}
+
+impl MergePattern {
+    fn parse(s: &str) -> PyResult<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "horizontal" => Ok(Self::Horizontal),
+            "vertical" => Ok(Self::Vertical),
+            "block" => Ok(Self::Block),
+            other => Err(PyErr::new::<PyValueError, _>(format!(
+                "Unknown merge_pattern: {other} (expected none|horizontal|vertical|block)"
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Horizontal => "horizontal",
+            Self::Vertical => "vertical",
+            Self::Block => "block",
+        }
+    }
+
+    /// (extra rows, extra cols) covered beyond the anchor cell.
+    fn span(self) -> (u32, u16) {
+        match self {
+            Self::None => (0, 0),
+            Self::Horizontal => (0, 1),
+            Self::Vertical => (1, 0),
+            Self::Block => (1, 1),
+        }
+    }
+}
+
+/// Everything needed to generate a workbook; plain data so it can cross into
+/// `allow_threads`.
+#[derive(Clone, Debug)]
+struct FixtureSpec {
+    rows: u32,
+    cols: u16,
+    sheets: u32,
+    header: bool,
+    string_ratio: f64,
+    formula_ratio: f64,
+    style_density: f64,
+    merge_pattern: MergePattern,
+    merge_every: u32,
+    seed: u64,
+}
+
+/// Per-workbook tallies reported in the manifest.
+#[derive(Default)]
+struct BuildCounts {
+    numbers: u64,
+    strings: u64,
+    formulas: u64,
+    styled: u64,
+    merges: u64,
+}
+
+/// SplitMix64: tiny, fast and reproducible across platforms.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Generation
+// ---------------------------------------------------------------------------
+
+/// Formats applied to styled cells, picked uniformly.
+fn style_palette() -> Vec<Format> {
+    vec![
+        Format::new().set_bold(),
+        Format::new().set_num_format("#,##0.00"),
+        Format::new().set_background_color(Color::RGB(0xFFF2CC)),
+        Format::new().set_border(FormatBorder::Thin),
+    ]
+}
+
+fn xlsx_err(what: &str, e: rust_xlsxwriter::XlsxError) -> String {
+    format!("{what} failed: {e}")
+}
+
+fn build_workbook(spec: &FixtureSpec, path: &str) -> Result<BuildCounts, String> {
+    let mut rng = SplitMix64(spec.seed);
+    let mut counts = BuildCounts::default();
+    let palette = style_palette();
+    let merge_format = Format::new().set_align(FormatAlign::Center);
+    let (merge_rows, merge_cols) = spec.merge_pattern.span();
+    let first_data_row = u32::from(spec.header);
+    let mut wb = Workbook::new();
+
+    for s in 0..spec.sheets {
+        let ws = wb.add_worksheet();
+        ws.set_name(format!("Sheet{}", s + 1))
+            .map_err(|e| xlsx_err("set_name", e))?;
+
+        if spec.header {
+            for c in 0..spec.cols {
+                ws.write_string(0, c, format!("col_{}", col_to_letters(u32::from(c))))
+                    .map_err(|e| xlsx_err("write_string", e))?;
+                counts.strings += 1;
+            }
+        }
+
+        let merging = spec.merge_pattern != MergePattern::None;
+        for r in first_data_row..spec.rows {
+            // Merge anchors sit on every `merge_every`-th data row, column A.
+            let anchor = merging
+                && (r - first_data_row) % spec.merge_every == 0
+                && r + merge_rows < spec.rows;
+            if anchor {
+                let text = format!("merged_{}", counts.merges);
+                ws.merge_range(r, 0, r + merge_rows, merge_cols, &text, &merge_format)
+                    .map_err(|e| xlsx_err("merge_range", e))?;
+                counts.merges += 1;
+                counts.strings += 1;
+            }
+            // Rows below a vertical/block anchor are covered in column A (and B).
+            let covered_below = merging
+                && merge_rows > 0
+                && r > first_data_row
+                && (r - 1 - first_data_row) % spec.merge_every == 0;
+
+            for c in 0..spec.cols {
+                if (anchor || covered_below) && c <= merge_cols {
+                    continue;
+                }
+                let style = (rng.next_f64() < spec.style_density)
+                    .then(|| &palette[(rng.next_u64() % palette.len() as u64) as usize]);
+                let roll = rng.next_f64();
+
+                if roll < spec.formula_ratio && c > 0 {
+                    // SUM skips text, so the result is well-defined whatever the row holds.
+                    let formula = format!(
+                        "=SUM(A{row}:{left}{row})",
+                        row = r + 1,
+                        left = col_to_letters(u32::from(c) - 1)
+                    );
+                    let written = match style {
+                        Some(f) => ws.write_formula_with_format(r, c, formula.as_str(), f),
+                        None => ws.write_formula(r, c, formula.as_str()),
+                    };
+                    written.map_err(|e| xlsx_err("write_formula", e))?;
+                    counts.formulas += 1;
+                } else if roll < spec.formula_ratio + spec.string_ratio {
+                    // A bounded vocabulary keeps the shared-string table realistic.
+                    let text = format!("str_{}", rng.next_u64() % 1000);
+                    let written = match style {
+                        Some(f) => ws.write_string_with_format(r, c, &text, f),
+                        None => ws.write_string(r, c, &text),
+                    };
+                    written.map_err(|e| xlsx_err("write_string", e))?;
+                    counts.strings += 1;
+                } else {
+                    let value = (rng.next_f64() * 100_000.0).round() / 100.0;
+                    let written = match style {
+                        Some(f) => ws.write_number_with_format(r, c, value, f),
+                        None => ws.write_number(r, c, value),
+                    };
+                    written.map_err(|e| xlsx_err("write_number", e))?;
+                    counts.numbers += 1;
+                }
+                if style.is_some() {
+                    counts.styled += 1;
+                }
+            }
+        }
+    }
+
+    wb.save(path).map_err(|e| xlsx_err("save", e))?;
+    Ok(counts)
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 class
+// ---------------------------------------------------------------------------
+
+#[pyclass]
+pub struct FixtureBuilder {
+    spec: FixtureSpec,
+}
+
+#[pymethods]
+impl FixtureBuilder {
+    /// Configure a generator for `rows` x `cols` grids on `sheets` worksheets.
+    ///
+    /// `string_ratio` / `formula_ratio` are the share of data cells holding
+    /// text / a formula (the rest are numbers); `style_density` is the share of
+    /// data cells given a style. `merge_pattern` ("none" | "horizontal" |
+    /// "vertical" | "block") places a merge in columns A:B every `merge_every`
+    /// data rows. `rows` includes the header row when `header` is True.
+    #[new]
+    #[pyo3(signature = (
+        rows=1000,
+        cols=10,
+        sheets=1,
+        header=true,
+        string_ratio=0.0,
+        formula_ratio=0.0,
+        style_density=0.0,
+        merge_pattern="none",
+        merge_every=10,
+        seed=0
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        rows: u32,
+        cols: u32,
+        sheets: u32,
+        header: bool,
+        string_ratio: f64,
+        formula_ratio: f64,
+        style_density: f64,
+        merge_pattern: &str,
+        merge_every: u32,
+        seed: u64,
+    ) -> PyResult<Self> {
+        if rows == 0 || rows > MAX_ROWS {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "rows must be in 1..={MAX_ROWS}, got {rows}"
+            )));
+        }
+        if cols == 0 || cols > MAX_COLS {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "cols must be in 1..={MAX_COLS}, got {cols}"
+            )));
+        }
+        if sheets == 0 {
+            return Err(PyErr::new::<PyValueError, _>("sheets must be at least 1"));
+        }
+        for (name, ratio) in [
+            ("string_ratio", string_ratio),
+            ("formula_ratio", formula_ratio),
+            ("style_density", style_density),
+        ] {
+            if !(0.0..=1.0).contains(&ratio) {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "{name} must be between 0 and 1, got {ratio}"
+                )));
+            }
+        }
+        if string_ratio + formula_ratio > 1.0 {
+            return Err(PyErr::new::<PyValueError, _>(
+                "string_ratio + formula_ratio must not exceed 1",
+            ));
+        }
+        let merge_pattern = MergePattern::parse(merge_pattern)?;
+        if merge_pattern != MergePattern::None {
+            if cols < 2 && merge_pattern != MergePattern::Vertical {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "merge_pattern '{}' needs at least 2 columns",
+                    merge_pattern.name()
+                )));
+            }
+            let min_every = merge_pattern.span().0 + 1;
+            if merge_every < min_every {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "merge_every must be at least {min_every} for '{}' merges",
+                    merge_pattern.name()
+                )));
+            }
+        }
+
+        Ok(FixtureBuilder {
+            spec: FixtureSpec {
+                rows,
+                cols: cols as u16,
+                sheets,
+                header,
+                string_ratio,
+                formula_ratio,
+                style_density,
+                merge_pattern,
+                merge_every,
+                seed,
+            },
+        })
+    }
+
+    /// Generate the workbook at `path` and return its manifest.
+    ///
+    /// The manifest holds the output `path`, file `bytes`, the generation
+    /// parameters, the `sheet_names`, and per-workbook tallies of `cells`,
+    /// `numbers`, `strings` (header and merge anchors included), `formulas`,
+    /// `styled` cells and `merges`. Generation runs without the GIL.
+    pub fn build(&self, py: Python<'_>, path: &str) -> PyResult<PyObject> {
+        let spec = self.spec.clone();
+        let counts = py
+            .allow_threads(|| build_workbook(&spec, path))
+            .map_err(PyErr::new::<PyIOError, _>)?;
+        let bytes = std::fs::metadata(path)
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Cannot stat '{path}': {e}")))?
+            .len();
+
+        let spec = &self.spec;
+        let d = PyDict::new(py);
+        d.set_item("path", path)?;
+        d.set_item("bytes", bytes)?;
+        d.set_item("rows", spec.rows)?;
+        d.set_item("cols", spec.cols)?;
+        d.set_item("header", spec.header)?;
+        d.set_item("string_ratio", spec.string_ratio)?;
+        d.set_item("formula_ratio", spec.formula_ratio)?;
+        d.set_item("style_density", spec.style_density)?;
+        d.set_item("merge_pattern", spec.merge_pattern.name())?;
+        d.set_item("merge_every", spec.merge_every)?;
+        d.set_item("seed", spec.seed)?;
+        let names: Vec<String> = (1..=spec.sheets).map(|i| format!("Sheet{i}")).collect();
+        d.set_item("sheet_names", names)?;
+        d.set_item("cells", counts.numbers + counts.strings + counts.formulas)?;
+        d.set_item("numbers", counts.numbers)?;
+        d.set_item("strings", counts.strings)?;
+        d.set_item("formulas", counts.formulas)?;
+        d.set_item("styled", counts.styled)?;
+        d.set_item("merges", counts.merges)?;
+        Ok(d.into())
+    }
+}
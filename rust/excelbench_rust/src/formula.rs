@@ -0,0 +1,960 @@
+//! Excel formula tokenizer, AST and renderer shared by the backends.
+//!
+//! [`tokenize`] is lossless: concatenating the token texts gives back the
+//! input, so callers can rewrite individual references and leave the rest
+//! of a formula byte-for-byte intact. [`parse`] builds an [`Expr`] tree from
+//! the tokens for analysis; [`Expr::render`] writes it back in canonical form
+//! (no whitespace, canonical sheet quoting).
+//!
+//! Formulas are handled without their leading `=`; [`strip_equals`] and
+//! [`with_equals`] convert between the stored and displayed forms.
+
+use crate::cell_ref::{quote_sheet_name, CellRef, RangeKind, RangeRef};
+
+// ---------------------------------------------------------------------------
+// Leading `=`
+// ---------------------------------------------------------------------------
+
+/// Formula body without its leading `=` (and any whitespace before it).
+pub(crate) fn strip_equals(formula: &str) -> &str {
+    let trimmed = formula.trim_start();
+    trimmed.strip_prefix('=').unwrap_or(formula)
+}
+
+/// Formula with exactly one leading `=`, as Excel displays it.
+pub(crate) fn with_equals(formula: &str) -> String {
+    format!("={}", strip_equals(formula))
+}
+
+// ---------------------------------------------------------------------------
+// Tokens
+// ---------------------------------------------------------------------------
+
+/// A cell, range or name reference, possibly qualified by sheet and workbook.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Reference {
+    /// External workbook index or name, without brackets (`1` in `[1]Sheet1!A1`).
+    pub workbook: Option<String>,
+    /// Sheet name with quoting removed.
+    pub sheet: Option<String>,
+    /// Last sheet of a 3-D span (`Sheet3` in `Sheet1:Sheet3!A1`).
+    pub last_sheet: Option<String>,
+    /// The part after `!`: `A1`, `$A$1:B2`, `A:C`, `1:3`, `R[1]C2`, or a
+    /// sheet-scoped / external defined name.
+    pub target: String,
+    /// `target` is in R1C1 notation.
+    pub r1c1: bool,
+}
+
+impl Reference {
+    fn local(target: String, r1c1: bool) -> Self {
+        Self {
+            workbook: None,
+            sheet: None,
+            last_sheet: None,
+            target,
+            r1c1,
+        }
+    }
+
+    /// Points into another workbook.
+    pub fn is_external(&self) -> bool {
+        self.workbook.is_some()
+    }
+
+    /// Spans several sheets (`Sheet1:Sheet3!A1`).
+    pub fn is_3d(&self) -> bool {
+        self.last_sheet.is_some()
+    }
+
+    /// The target as an A1 cell or range; None for R1C1 targets and names.
+    pub fn range(&self) -> Option<RangeRef> {
+        if self.r1c1 {
+            return None;
+        }
+        let range = RangeRef::parse(&self.target).ok()?;
+        // Bare `A` / `1` parse as whole columns/rows but are names or numbers.
+        (range.kind == RangeKind::Cells || self.target.contains(':')).then_some(range)
+    }
+
+    /// Text form with canonical sheet quoting.
+    pub fn render(&self) -> String {
+        let Some(sheet) = &self.sheet else {
+            return match &self.workbook {
+                Some(wb) => format!("[{wb}]!{}", self.target),
+                None => self.target.clone(),
+            };
+        };
+        let mut plain = true;
+        let mut name = String::new();
+        if let Some(wb) = &self.workbook {
+            name.push_str(&format!("[{wb}]"));
+        }
+        for (i, part) in std::iter::once(sheet).chain(&self.last_sheet).enumerate() {
+            plain &= quote_sheet_name(part) == *part;
+            if i > 0 {
+                name.push(':');
+            }
+            name.push_str(part);
+        }
+        if plain {
+            format!("{name}!{}", self.target)
+        } else {
+            format!("'{}'!{}", name.replace('\'', "''"), self.target)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TokenKind {
+    Number,
+    /// String literal, quotes included in the text.
+    Text,
+    Bool,
+    /// Error literal such as `#N/A` or `#REF!`.
+    Error,
+    Reference(Reference),
+    /// Defined name (or anything else identifier-like that is not a reference).
+    Name,
+    /// Function name; the `(` that follows is a separate [`TokenKind::Open`].
+    Function,
+    /// Table reference such as `Table1[Col]` or `[@Col]`.
+    StructuredRef,
+    /// `+ - * / ^ & = <> < > <= >= % : @`
+    Operator,
+    Open,
+    Close,
+    /// `,` between arguments or array items.
+    Separator,
+    ArrayOpen,
+    ArrayClose,
+    /// `;` between array rows.
+    ArrayRowSep,
+    Whitespace,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Token {
+    pub kind: TokenKind,
+    /// Exact source text of the token.
+    pub text: String,
+}
+
+/// Concatenate token texts; the inverse of [`tokenize`].
+pub(crate) fn render_tokens(tokens: &[Token]) -> String {
+    tokens.iter().map(|t| t.text.as_str()).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tokenizer
+// ---------------------------------------------------------------------------
+
+/// Split a formula body (no leading `=`) into tokens.
+pub(crate) fn tokenize(formula: &str) -> Vec<Token> {
+    let chars: Vec<char> = formula.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (kind, end) = next_token(&chars, i);
+        // Every branch consumes at least one char; guard against a stall anyway.
+        let end = end.max(i + 1);
+        tokens.push(Token {
+            kind,
+            text: chars[i..end].iter().collect(),
+        });
+        i = end;
+    }
+    tokens
+}
+
+fn next_token(chars: &[char], i: usize) -> (TokenKind, usize) {
+    let c = chars[i];
+    match c {
+        c if c.is_whitespace() => {
+            let mut j = i;
+            while j < chars.len() && chars[j].is_whitespace() {
+                j += 1;
+            }
+            (TokenKind::Whitespace, j)
+        }
+        '"' => (TokenKind::Text, quoted_end(chars, i)),
+        '\'' => {
+            let end = quoted_end(chars, i);
+            if chars.get(end) != Some(&'!') || end < i + 2 {
+                return (TokenKind::Unknown, end);
+            }
+            let quoted: String = chars[i + 1..end - 1].iter().collect();
+            let (workbook, sheets) = split_workbook(&quoted.replace("''", "'"));
+            qualified_reference(chars, end + 1, workbook, sheets)
+        }
+        '[' => {
+            let end = bracket_end(chars, i);
+            let inner: String = chars[i + 1..end.saturating_sub(1).max(i + 1)]
+                .iter()
+                .collect();
+            let run = ident_end(chars, end);
+            match chars.get(run) {
+                // `[1]Sheet1!A1`
+                Some('!') if run > end => {
+                    let sheets: String = chars[end..run].iter().collect();
+                    qualified_reference(chars, run + 1, Some(inner), sheets)
+                }
+                // `[1]!Name`: a defined name in another workbook.
+                Some('!') => {
+                    let name_end = ident_end(chars, run + 1);
+                    let mut r = Reference::local(chars[run + 1..name_end].iter().collect(), false);
+                    r.workbook = Some(inner);
+                    (TokenKind::Reference(r), name_end)
+                }
+                _ => (TokenKind::StructuredRef, end),
+            }
+        }
+        '#' => {
+            let mut j = i + 1;
+            while j < chars.len()
+                && (chars[j].is_ascii_alphanumeric() || matches!(chars[j], '/' | '_'))
+            {
+                j += 1;
+            }
+            if matches!(chars.get(j), Some('!') | Some('?')) {
+                j += 1;
+            }
+            (TokenKind::Error, j)
+        }
+        '{' => (TokenKind::ArrayOpen, i + 1),
+        '}' => (TokenKind::ArrayClose, i + 1),
+        ';' => (TokenKind::ArrayRowSep, i + 1),
+        ',' => (TokenKind::Separator, i + 1),
+        '(' => (TokenKind::Open, i + 1),
+        ')' => (TokenKind::Close, i + 1),
+        '<' if matches!(chars.get(i + 1), Some('=') | Some('>')) => (TokenKind::Operator, i + 2),
+        '>' if chars.get(i + 1) == Some(&'=') => (TokenKind::Operator, i + 2),
+        '+' | '-' | '*' | '/' | '^' | '&' | '=' | '<' | '>' | '%' | ':' | '@' => {
+            (TokenKind::Operator, i + 1)
+        }
+        c if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) =>
+        {
+            // `1:3` is a row range, not a number.
+            let end = target_end(chars, i);
+            let text: String = chars[i..end].iter().collect();
+            if text.contains(':') && RangeRef::parse(&text).is_ok() {
+                return (TokenKind::Reference(Reference::local(text, false)), end);
+            }
+            (TokenKind::Number, number_end(chars, i))
+        }
+        c if is_ident_start(c) => ident_token(chars, i),
+        _ => (TokenKind::Unknown, i + 1),
+    }
+}
+
+fn ident_token(chars: &[char], i: usize) -> (TokenKind, usize) {
+    if let Some(end) = r1c1_target_end(chars, i) {
+        let text: String = chars[i..end].iter().collect();
+        return (TokenKind::Reference(Reference::local(text, true)), end);
+    }
+    let run = ident_end(chars, i);
+    let word: String = chars[i..run].iter().collect();
+    match chars.get(run) {
+        Some('(') => return (TokenKind::Function, run),
+        Some('!') => return qualified_reference(chars, run + 1, None, word),
+        Some('[') => return (TokenKind::StructuredRef, bracket_end(chars, run)),
+        // `Sheet1:Sheet3!A1`
+        Some(':') => {
+            let run2 = ident_end(chars, run + 1);
+            if run2 > run + 1 && chars.get(run2) == Some(&'!') {
+                let sheets: String = chars[i..run2].iter().collect();
+                return qualified_reference(chars, run2 + 1, None, sheets);
+            }
+        }
+        _ => {}
+    }
+    if word.eq_ignore_ascii_case("TRUE") || word.eq_ignore_ascii_case("FALSE") {
+        return (TokenKind::Bool, run);
+    }
+    let end = target_end(chars, i);
+    let reference = Reference::local(chars[i..end].iter().collect(), false);
+    if reference.range().is_some() {
+        return (TokenKind::Reference(reference), end);
+    }
+    (TokenKind::Name, run)
+}
+
+/// The reference after a `Sheet!` qualifier starting at `i`; `sheets` may be
+/// a `First:Last` span.
+fn qualified_reference(
+    chars: &[char],
+    i: usize,
+    workbook: Option<String>,
+    sheets: String,
+) -> (TokenKind, usize) {
+    let r1c1 = r1c1_target_end(chars, i);
+    let end = r1c1.unwrap_or_else(|| target_end(chars, i));
+    let (sheet, last_sheet) = match sheets.split_once(':') {
+        Some((a, b)) => (a.to_string(), Some(b.to_string())),
+        None => (sheets, None),
+    };
+    let reference = Reference {
+        workbook,
+        sheet: (!sheet.is_empty()).then_some(sheet),
+        last_sheet,
+        target: chars[i..end].iter().collect(),
+        r1c1: r1c1.is_some(),
+    };
+    (TokenKind::Reference(reference), end)
+}
+
+/// Split `[1]Sheet1` into (`1`, `Sheet1`).
+fn split_workbook(name: &str) -> (Option<String>, String) {
+    if let Some(rest) = name.strip_prefix('[') {
+        if let Some((wb, sheet)) = rest.split_once(']') {
+            return (Some(wb.to_string()), sheet.to_string());
+        }
+    }
+    (None, name.to_string())
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || matches!(c, '$' | '_' | '\\')
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '$' | '_' | '.' | '\\' | '?')
+}
+
+fn ident_end(chars: &[char], mut i: usize) -> usize {
+    while i < chars.len() && is_ident_char(chars[i]) {
+        i += 1;
+    }
+    i
+}
+
+/// End of an A1 target at `i`: one identifier run, extended over `:` when
+/// the extension still parses as a range (`A1:B2`, `A:C`, `1:3`).
+fn target_end(chars: &[char], i: usize) -> usize {
+    let run = ident_end(chars, i);
+    if chars.get(run) == Some(&':') {
+        let run2 = ident_end(chars, run + 1);
+        if run2 > run + 1 {
+            let text: String = chars[i..run2].iter().collect();
+            if RangeRef::parse(&text).is_ok() {
+                return run2;
+            }
+        }
+    }
+    run
+}
+
+fn number_end(chars: &[char], i: usize) -> usize {
+    let digits = |mut j: usize| {
+        while chars.get(j).is_some_and(char::is_ascii_digit) {
+            j += 1;
+        }
+        j
+    };
+    let mut j = digits(i);
+    if chars.get(j) == Some(&'.') {
+        j = digits(j + 1);
+    }
+    if matches!(chars.get(j), Some('e') | Some('E')) {
+        let mut k = j + 1;
+        if matches!(chars.get(k), Some('+') | Some('-')) {
+            k += 1;
+        }
+        if chars.get(k).is_some_and(char::is_ascii_digit) {
+            j = digits(k);
+        }
+    }
+    j
+}
+
+/// End (exclusive) of a `"..."` / `'...'` run starting at `i`, honouring
+/// doubled-quote escapes.
+fn quoted_end(chars: &[char], i: usize) -> usize {
+    let q = chars[i];
+    let mut j = i + 1;
+    while j < chars.len() {
+        if chars[j] == q {
+            if chars.get(j + 1) == Some(&q) {
+                j += 2;
+                continue;
+            }
+            return j + 1;
+        }
+        j += 1;
+    }
+    chars.len()
+}
+
+/// End (exclusive) of a possibly nested `[...]` group starting at `i`.
+fn bracket_end(chars: &[char], i: usize) -> usize {
+    let mut depth = 0;
+    for (j, &c) in chars.iter().enumerate().skip(i) {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return j + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    chars.len()
+}
+
+/// End of an R1C1 axis (`R`, `R5`, `R[-2]`) starting at `i`.
+fn r1c1_axis_end(chars: &[char], i: usize, axis: char) -> Option<usize> {
+    if !chars.get(i)?.eq_ignore_ascii_case(&axis) {
+        return None;
+    }
+    let mut j = i + 1;
+    if chars.get(j) == Some(&'[') {
+        j += 1;
+        if chars.get(j) == Some(&'-') {
+            j += 1;
+        }
+        let digits = j;
+        while chars.get(j).is_some_and(char::is_ascii_digit) {
+            j += 1;
+        }
+        if j == digits || chars.get(j) != Some(&']') {
+            return None;
+        }
+        return Some(j + 1);
+    }
+    while chars.get(j).is_some_and(char::is_ascii_digit) {
+        j += 1;
+    }
+    Some(j)
+}
+
+/// End of an R1C1 cell (`R2C3`, `R[-1]C`, `RC[2]`) starting at `i`.
+fn r1c1_cell_end(chars: &[char], i: usize) -> Option<usize> {
+    let j = r1c1_axis_end(chars, i, 'R')?;
+    let j = r1c1_axis_end(chars, j, 'C')?;
+    if chars
+        .get(j)
+        .is_some_and(|&c| is_ident_char(c) || matches!(c, '(' | '[' | '!'))
+    {
+        return None;
+    }
+    Some(j)
+}
+
+/// End of an R1C1 cell or range at `i`. Tokens that are also valid A1 cells
+/// (`RC1`, column RC) are left to the A1 reader.
+fn r1c1_target_end(chars: &[char], i: usize) -> Option<usize> {
+    let end = r1c1_cell_end(chars, i)?;
+    let run = ident_end(chars, i);
+    let a1: String = chars[i..run].iter().collect();
+    if end == run && CellRef::parse(&a1).is_ok() {
+        return None;
+    }
+    if chars.get(end) == Some(&':') {
+        if let Some(end2) = r1c1_cell_end(chars, end + 1) {
+            return Some(end2);
+        }
+    }
+    Some(end)
+}
+
+// ---------------------------------------------------------------------------
+// Token-level rewrites
+// ---------------------------------------------------------------------------
+
+/// Rewrite every reference token with `f`, which gets the parsed reference
+/// and its source text; None keeps the original text.
+pub(crate) fn map_references(
+    formula: &str,
+    mut f: impl FnMut(&Reference, &str) -> Option<String>,
+) -> String {
+    let mut tokens = tokenize(formula);
+    for token in &mut tokens {
+        if let TokenKind::Reference(r) = &token.kind {
+            if let Some(text) = f(r, &token.text) {
+                token.text = text;
+            }
+        }
+    }
+    render_tokens(&tokens)
+}
+
+/// Reference token `text` with its target replaced by `target`, keeping the
+/// sheet qualifier exactly as written.
+pub(crate) fn replace_target(text: &str, reference: &Reference, target: &str) -> String {
+    let qualifier = &text[..text.len() - reference.target.len()];
+    format!("{qualifier}{target}")
+}
+
+/// Shift the relative A1 references in `formula` by `rows`/`cols`, as
+/// copying the formula that far would.
+///
+/// `$`-anchored components stay put, R1C1 references are already relative,
+/// and references pushed off the sheet become `#REF!`. Whole column/row
+/// ranges (`A:A`, `1:1`) are not shifted.
+pub(crate) fn shift_references(formula: &str, rows: i64, cols: i64) -> String {
+    map_references(formula, |r, text| {
+        let range = r.range()?;
+        if r.is_external() || range.kind != RangeKind::Cells {
+            return None;
+        }
+        let shift = |c: &CellRef| {
+            let rows = if c.row_abs { 0 } else { rows };
+            let cols = if c.col_abs { 0 } else { cols };
+            c.offset(rows, cols)
+        };
+        let target = match (shift(&range.start), shift(&range.end)) {
+            (Some(s), Some(e)) if r.target.contains(':') => format!("{s}:{e}"),
+            (Some(s), Some(_)) => s.to_string(),
+            _ => "#REF!".to_string(),
+        };
+        Some(replace_target(text, r, &target))
+    })
+}
+
+/// Re-quote every sheet qualifier canonically: quoted only when the name
+/// needs it (`'My Sheet'!A1`, `Sheet1!A1`).
+pub(crate) fn normalize_sheet_quoting(formula: &str) -> String {
+    map_references(formula, |r, _| r.sheet.as_ref().map(|_| r.render()))
+}
+
+// ---------------------------------------------------------------------------
+// AST
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    /// Number literal as written.
+    Number(String),
+    /// String literal, unescaped.
+    Text(String),
+    Bool(bool),
+    Error(String),
+    Reference(Reference),
+    Name(String),
+    StructuredRef(String),
+    Function {
+        name: String,
+        args: Vec<Expr>,
+    },
+    /// Prefix `+`, `-` or `@`.
+    Unary {
+        op: String,
+        operand: Box<Expr>,
+    },
+    Percent(Box<Expr>),
+    Binary {
+        op: String,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    /// Parenthesized expression; several items form a union `(A1,B2)`.
+    Group(Vec<Expr>),
+    Array(Vec<Vec<Expr>>),
+    /// Omitted argument, as in `IF(A1,,1)`.
+    Missing,
+}
+
+/// Parse a formula body (no leading `=`) into an expression tree.
+///
+/// Whitespace is dropped, so the space intersection operator (`A1:B2 B1:C1`)
+/// is not supported.
+pub(crate) fn parse(formula: &str) -> Result<Expr, String> {
+    let tokens: Vec<Token> = tokenize(formula)
+        .into_iter()
+        .filter(|t| t.kind != TokenKind::Whitespace)
+        .collect();
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+    };
+    let expr = parser.expr(0)?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(t) => Err(format!("Unexpected '{}' in formula: {formula}", t.text)),
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+/// Binding powers (left, right) of the infix operators; higher binds tighter.
+/// `^` is left-associative in Excel, and unary minus binds tighter than it.
+fn infix_power(op: &str) -> Option<(u8, u8)> {
+    Some(match op {
+        ":" => (90, 91),
+        "^" => (70, 71),
+        "*" | "/" => (60, 61),
+        "+" | "-" => (50, 51),
+        "&" => (40, 41),
+        "=" | "<>" | "<" | ">" | "<=" | ">=" => (30, 31),
+        _ => return None,
+    })
+}
+
+const PERCENT_POWER: u8 = 80;
+const PREFIX_POWER: u8 = 85;
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let t = self.tokens.get(self.pos);
+        self.pos += 1;
+        t
+    }
+
+    fn expect(&mut self, kind: TokenKind, what: &str) -> Result<(), String> {
+        match self.next() {
+            Some(t) if t.kind == kind => Ok(()),
+            Some(t) => Err(format!("Expected {what}, found '{}'", t.text)),
+            None => Err(format!("Expected {what}, found end of formula")),
+        }
+    }
+
+    fn expr(&mut self, min_power: u8) -> Result<Expr, String> {
+        let mut lhs = self.prefix()?;
+        while let Some(t) = self.peek() {
+            if t.kind != TokenKind::Operator {
+                break;
+            }
+            if t.text == "%" {
+                if PERCENT_POWER < min_power {
+                    break;
+                }
+                self.pos += 1;
+                lhs = Expr::Percent(Box::new(lhs));
+                continue;
+            }
+            let Some((left, right)) = infix_power(&t.text) else {
+                break;
+            };
+            if left < min_power {
+                break;
+            }
+            self.pos += 1;
+            let rhs = self.expr(right)?;
+            lhs = Expr::Binary {
+                op: t.text.clone(),
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            };
+        }
+        Ok(lhs)
+    }
+
+    /// Items separated by `,` up to `close`; empty items are [`Expr::Missing`].
+    fn list(&mut self, close: TokenKind, what: &str) -> Result<Vec<Expr>, String> {
+        let mut items = Vec::new();
+        if self.peek().is_some_and(|t| t.kind == close) {
+            self.pos += 1;
+            return Ok(items);
+        }
+        loop {
+            let item = match self.peek() {
+                Some(t) if t.kind == TokenKind::Separator || t.kind == close => Expr::Missing,
+                _ => self.expr(0)?,
+            };
+            items.push(item);
+            match self.next() {
+                Some(t) if t.kind == TokenKind::Separator => continue,
+                Some(t) if t.kind == close => return Ok(items),
+                Some(t) => return Err(format!("Expected ',' or {what}, found '{}'", t.text)),
+                None => return Err(format!("Expected {what}, found end of formula")),
+            }
+        }
+    }
+
+    fn prefix(&mut self) -> Result<Expr, String> {
+        let Some(t) = self.next() else {
+            return Err("Unexpected end of formula".to_string());
+        };
+        Ok(match &t.kind {
+            TokenKind::Number => Expr::Number(t.text.clone()),
+            TokenKind::Text => {
+                let inner = &t.text[1..t.text.len().saturating_sub(1).max(1)];
+                Expr::Text(inner.replace("\"\"", "\""))
+            }
+            TokenKind::Bool => Expr::Bool(t.text.eq_ignore_ascii_case("TRUE")),
+            TokenKind::Error => Expr::Error(t.text.clone()),
+            TokenKind::Reference(r) => Expr::Reference(r.clone()),
+            TokenKind::Name => Expr::Name(t.text.clone()),
+            TokenKind::StructuredRef => Expr::StructuredRef(t.text.clone()),
+            TokenKind::Function => {
+                self.expect(TokenKind::Open, "'('")?;
+                let args = self.list(TokenKind::Close, "')'")?;
+                Expr::Function {
+                    name: t.text.clone(),
+                    args,
+                }
+            }
+            TokenKind::Open => Expr::Group(self.list(TokenKind::Close, "')'")?),
+            TokenKind::ArrayOpen => {
+                let mut rows = vec![Vec::new()];
+                loop {
+                    rows.last_mut().unwrap().push(self.expr(0)?);
+                    match self.next() {
+                        Some(t) if t.kind == TokenKind::Separator => {}
+                        Some(t) if t.kind == TokenKind::ArrayRowSep => rows.push(Vec::new()),
+                        Some(t) if t.kind == TokenKind::ArrayClose => break,
+                        Some(t) => return Err(format!("Unexpected '{}' in array", t.text)),
+                        None => return Err("Unterminated array constant".to_string()),
+                    }
+                }
+                Expr::Array(rows)
+            }
+            TokenKind::Operator if matches!(t.text.as_str(), "+" | "-" | "@") => Expr::Unary {
+                op: t.text.clone(),
+                operand: Box::new(self.expr(PREFIX_POWER)?),
+            },
+            _ => return Err(format!("Unexpected '{}' in formula", t.text)),
+        })
+    }
+}
+
+impl Expr {
+    /// Canonical text (no leading `=`, no whitespace).
+    pub fn render(&self) -> String {
+        let join = |items: &[Expr]| items.iter().map(Expr::render).collect::<Vec<_>>().join(",");
+        match self {
+            Expr::Number(n) => n.clone(),
+            Expr::Text(s) => format!("\"{}\"", s.replace('"', "\"\"")),
+            Expr::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+            Expr::Error(e) | Expr::Name(e) | Expr::StructuredRef(e) => e.clone(),
+            Expr::Reference(r) => r.render(),
+            Expr::Function { name, args } => format!("{name}({})", join(args)),
+            Expr::Unary { op, operand } => format!("{op}{}", operand.render()),
+            Expr::Percent(e) => format!("{}%", e.render()),
+            Expr::Binary { op, lhs, rhs } => format!("{}{op}{}", lhs.render(), rhs.render()),
+            Expr::Group(items) => format!("({})", join(items)),
+            Expr::Array(rows) => {
+                let rows: Vec<String> = rows.iter().map(|r| join(r)).collect();
+                format!("{{{}}}", rows.join(";"))
+            }
+            Expr::Missing => String::new(),
+        }
+    }
+
+    /// Visit this node and every descendant, parents first.
+    pub fn walk<'e>(&'e self, f: &mut impl FnMut(&'e Expr)) {
+        f(self);
+        match self {
+            Expr::Function { args: items, .. } | Expr::Group(items) => {
+                items.iter().for_each(|e| e.walk(f));
+            }
+            Expr::Unary { operand: e, .. } | Expr::Percent(e) => e.walk(f),
+            Expr::Binary { lhs, rhs, .. } => {
+                lhs.walk(f);
+                rhs.walk(f);
+            }
+            Expr::Array(rows) => rows.iter().flatten().for_each(|e| e.walk(f)),
+            _ => {}
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Analysis
+// ---------------------------------------------------------------------------
+
+/// Functions Excel recalculates on every change, whatever their inputs.
+pub(crate) const VOLATILE_FUNCTIONS: &[&str] = &[
+    "NOW",
+    "TODAY",
+    "RAND",
+    "RANDBETWEEN",
+    "RANDARRAY",
+    "OFFSET",
+    "INDIRECT",
+    "INFO",
+    "CELL",
+];
+
+/// Function name without the `_xlfn.` / `_xlws.` prefixes newer functions
+/// are stored with, uppercased.
+pub(crate) fn canonical_function_name(name: &str) -> String {
+    let upper = name.to_ascii_uppercase();
+    upper
+        .trim_start_matches("_XLFN.")
+        .trim_start_matches("_XLWS.")
+        .to_string()
+}
+
+pub(crate) fn is_volatile_function(name: &str) -> bool {
+    VOLATILE_FUNCTIONS.contains(&canonical_function_name(name).as_str())
+}
+
+/// Canonical names of the volatile functions `formula` calls, in order of
+/// appearance (with repeats).
+pub(crate) fn volatile_functions(formula: &str) -> Vec<String> {
+    tokenize(formula)
+        .iter()
+        .filter(|t| t.kind == TokenKind::Function && is_volatile_function(&t.text))
+        .map(|t| canonical_function_name(&t.text))
+        .collect()
+}
+
+/// References into other workbooks, as written in `formula`.
+pub(crate) fn external_references(formula: &str) -> Vec<String> {
+    tokenize(formula)
+        .into_iter()
+        .filter(|t| matches!(&t.kind, TokenKind::Reference(r) if r.is_external()))
+        .map(|t| t.text)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(formula: &str) -> Vec<String> {
+        tokenize(formula)
+            .into_iter()
+            .map(|t| match t.kind {
+                TokenKind::Reference(_) => format!("ref:{}", t.text),
+                TokenKind::Whitespace => " ".to_string(),
+                k => format!("{k:?}:{}", t.text),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_equals_prefix() {
+        assert_eq!(strip_equals("=SUM(A1)"), "SUM(A1)");
+        assert_eq!(strip_equals("  =A1"), "A1");
+        assert_eq!(strip_equals("A1"), "A1");
+        assert_eq!(with_equals("A1"), "=A1");
+        assert_eq!(with_equals("=A1"), "=A1");
+    }
+
+    #[test]
+    fn test_tokenize_is_lossless() {
+        for f in [
+            "SUM(A1:B2, 'My Sheet'!$C$3) * 2",
+            "IF(A1>=1,\"a\"\"b\",#N/A)",
+            "[1]Sheet1!A1+Sheet1:Sheet3!B2",
+            "Table1[[#This Row],[Col]]+{1,2;3,4}",
+            "R[-1]C+RC[2]",
+            "_xlfn.XLOOKUP(1,A:A,1:1)",
+        ] {
+            assert_eq!(render_tokens(&tokenize(f)), f);
+        }
+    }
+
+    #[test]
+    fn test_tokenize_kinds() {
+        assert_eq!(
+            kinds("SUM(A1:B2,x)"),
+            [
+                "Function:SUM",
+                "Open:(",
+                "ref:A1:B2",
+                "Separator:,",
+                "Name:x",
+                "Close:)"
+            ]
+        );
+        assert_eq!(
+            kinds("LOG10(1.5E3)&TRUE"),
+            [
+                "Function:LOG10",
+                "Open:(",
+                "Number:1.5E3",
+                "Close:)",
+                "Operator:&",
+                "Bool:TRUE"
+            ]
+        );
+        assert_eq!(
+            kinds("A1:INDEX(B:B,2)")[..3],
+            ["ref:A1", "Operator::", "Function:INDEX"]
+        );
+        assert_eq!(kinds("Tbl1[Col]"), ["StructuredRef:Tbl1[Col]"]);
+    }
+
+    #[test]
+    fn test_qualified_references() {
+        let refs: Vec<Reference> = tokenize("'It''s'!A1+[2]Data!B1:B2+S1:S3!C1+[1]!Rate")
+            .into_iter()
+            .filter_map(|t| match t.kind {
+                TokenKind::Reference(r) => Some(r),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(refs[0].sheet.as_deref(), Some("It's"));
+        assert_eq!(refs[1].workbook.as_deref(), Some("2"));
+        assert_eq!(refs[1].target, "B1:B2");
+        assert!(refs[2].is_3d());
+        assert_eq!(refs[3].workbook.as_deref(), Some("1"));
+        assert_eq!(refs[3].target, "Rate");
+    }
+
+    #[test]
+    fn test_normalize_sheet_quoting() {
+        assert_eq!(
+            normalize_sheet_quoting("'Data'!A1+My!B1+'My Sheet'!C1"),
+            "Data!A1+My!B1+'My Sheet'!C1"
+        );
+    }
+
+    #[test]
+    fn test_shift_references() {
+        assert_eq!(shift_references("A1*2", 3, 0), "A4*2");
+        assert_eq!(shift_references("$A1+A$1+$A$1", 2, 1), "$A3+B$1+$A$1");
+        assert_eq!(shift_references("SUM(B2:B10)/C2", 1, 1), "SUM(C3:C11)/D3");
+        assert_eq!(shift_references("SUM(A:A)+1:1", 1, 1), "SUM(A:A)+1:1");
+        assert_eq!(shift_references("A1", -1, 0), "#REF!");
+        assert_eq!(shift_references("'Data'!A1", 1, 0), "'Data'!A2");
+    }
+
+    #[test]
+    fn test_parse_and_render() {
+        let e = parse("-2^2+SUM(A1:A3, 5%) & \"x\"").unwrap();
+        assert_eq!(e.render(), "-2^2+SUM(A1:A3,5%)&\"x\"");
+        match &e {
+            Expr::Binary { op, .. } => assert_eq!(op, "&"),
+            other => panic!("{other:?}"),
+        }
+        assert_eq!(
+            parse("IF(A1,,{1,2;3,4})").unwrap().render(),
+            "IF(A1,,{1,2;3,4})"
+        );
+        assert_eq!(parse("(A1,B2)").unwrap(), parse("( A1 , B2 )").unwrap());
+        assert!(parse("SUM(1").is_err());
+        assert!(parse("1+").is_err());
+    }
+
+    #[test]
+    fn test_volatile_functions() {
+        assert_eq!(
+            volatile_functions("IF(RAND()>0.5,NOW(),OFFSET(A1,1,1))+LEN(\"RAND()\")"),
+            ["RAND", "NOW", "OFFSET"]
+        );
+        assert!(is_volatile_function("_xlfn.RANDARRAY"));
+        assert!(!is_volatile_function("SUM"));
+        let mut calls = Vec::new();
+        let expr = parse("SUM(TODAY(),1)").unwrap();
+        expr.walk(&mut |e| {
+            if let Expr::Function { name, .. } = e {
+                calls.push(name.as_str());
+            }
+        });
+        assert_eq!(calls, ["SUM", "TODAY"]);
+    }
+
+    #[test]
+    fn test_external_references() {
+        assert_eq!(
+            external_references("[1]Sheet1!A1+'[Other.xlsx]My Data'!B2+Sheet1!C3+[2]!Rate"),
+            ["[1]Sheet1!A1", "'[Other.xlsx]My Data'!B2", "[2]!Rate"]
+        );
+        assert!(external_references("SUM(A1:A3)").is_empty());
+    }
+}
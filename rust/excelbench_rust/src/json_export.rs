@@ -0,0 +1,307 @@
+//! Typed cell payloads shared by `read_cell_value` and `export_json()`.
+//!
+//! `TypedCell` is the `{"type", "value", ...}` payload built without Python
+//! objects: readers type a cell once, then render it either as a Python dict
+//! or as JSON, so both outputs follow the same typing rules.
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::cell_ref::col_to_letters;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsonValue<'a> {
+    Str(Cow<'a, str>),
+    Num(f64),
+    Bool(bool),
+}
+
+/// One `read_cell_value` payload. Optional keys are emitted only when set.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TypedCell<'a> {
+    pub kind: &'static str,
+    pub value: Option<JsonValue<'a>>,
+    pub formula: Option<Cow<'a, str>>,
+    /// Payload type of a formula's cached `value`.
+    pub value_type: Option<&'static str>,
+    pub cached: Option<bool>,
+}
+
+impl<'a> TypedCell<'a> {
+    pub(crate) fn blank() -> Self {
+        Self {
+            kind: "blank",
+            value: None,
+            formula: None,
+            value_type: None,
+            cached: None,
+        }
+    }
+
+    pub(crate) fn new(kind: &'static str, value: JsonValue<'a>) -> Self {
+        Self {
+            kind,
+            value: Some(value),
+            ..Self::blank()
+        }
+    }
+
+    pub(crate) fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let d = PyDict::new(py);
+        d.set_item("type", self.kind)?;
+        match &self.value {
+            Some(JsonValue::Str(s)) => d.set_item("value", s.as_ref())?,
+            Some(JsonValue::Num(n)) => d.set_item("value", *n)?,
+            Some(JsonValue::Bool(b)) => d.set_item("value", *b)?,
+            None => {}
+        }
+        if let Some(f) = &self.formula {
+            d.set_item("formula", f.as_ref())?;
+        }
+        if let Some(t) = self.value_type {
+            d.set_item("value_type", t)?;
+        }
+        if let Some(c) = self.cached {
+            d.set_item("cached", c)?;
+        }
+        Ok(d.into())
+    }
+
+    /// Append this payload as a JSON object.
+    pub(crate) fn write_json(&self, out: &mut String) {
+        out.push_str("{\"type\":");
+        push_json_str(out, self.kind);
+        if let Some(v) = &self.value {
+            out.push_str(",\"value\":");
+            push_json_value(out, v);
+        }
+        if let Some(f) = &self.formula {
+            out.push_str(",\"formula\":");
+            push_json_str(out, f);
+        }
+        if let Some(t) = self.value_type {
+            out.push_str(",\"value_type\":");
+            push_json_str(out, t);
+        }
+        if let Some(c) = self.cached {
+            out.push_str(if c {
+                ",\"cached\":true"
+            } else {
+                ",\"cached\":false"
+            });
+        }
+        out.push('}');
+    }
+
+    /// Plain-text rendering used for `records` header names.
+    fn header_text(&self) -> Option<String> {
+        match &self.value {
+            Some(JsonValue::Str(s)) if !s.is_empty() => Some(s.to_string()),
+            Some(JsonValue::Num(n)) => Some(n.to_string()),
+            Some(JsonValue::Bool(b)) => Some(if *b { "TRUE" } else { "FALSE" }.to_string()),
+            _ => None,
+        }
+    }
+}
+
+fn push_json_value(out: &mut String, v: &JsonValue<'_>) {
+    match v {
+        JsonValue::Str(s) => push_json_str(out, s),
+        JsonValue::Num(n) if n.is_finite() => out.push_str(&n.to_string()),
+        JsonValue::Num(_) => out.push_str("null"),
+        JsonValue::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+    }
+}
+
+fn push_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// ---------------------------------------------------------------------------
+// Sheet export
+// ---------------------------------------------------------------------------
+
+/// Output layout of `export_json()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Orient {
+    /// NDJSON: the first row is the header; each later row is one object
+    /// mapping header names to payloads.
+    Records,
+    /// One JSON array of rows, each an array of payloads.
+    Rows,
+}
+
+pub(crate) fn parse_orient(orient: &str) -> PyResult<Orient> {
+    match orient {
+        "records" => Ok(Orient::Records),
+        "rows" => Ok(Orient::Rows),
+        other => Err(PyErr::new::<PyValueError, _>(format!(
+            "orient must be 'records' or 'rows', got '{other}'"
+        ))),
+    }
+}
+
+/// Create `path` for `write_sheet`.
+pub(crate) fn create(path: &str) -> PyResult<File> {
+    File::create(path)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to create JSON file: {e}")))
+}
+
+pub(crate) fn write_error(e: io::Error) -> PyErr {
+    PyErr::new::<PyIOError, _>(format!("Failed to write JSON: {e}"))
+}
+
+/// Write rows of payloads in the given layout. Returns the number of rows
+/// (records: data rows, excluding the header) written.
+pub(crate) fn write_sheet<'a, W: Write>(
+    out: W,
+    orient: Orient,
+    rows: impl IntoIterator<Item = Vec<TypedCell<'a>>>,
+) -> io::Result<usize> {
+    let mut out = BufWriter::new(out);
+    let mut buf = String::new();
+    let mut count = 0;
+    let mut rows = rows.into_iter();
+
+    match orient {
+        Orient::Rows => {
+            out.write_all(b"[")?;
+            for row in rows {
+                buf.clear();
+                if count > 0 {
+                    buf.push(',');
+                }
+                buf.push('[');
+                for (i, cell) in row.iter().enumerate() {
+                    if i > 0 {
+                        buf.push(',');
+                    }
+                    cell.write_json(&mut buf);
+                }
+                buf.push(']');
+                out.write_all(buf.as_bytes())?;
+                count += 1;
+            }
+            out.write_all(b"]\n")?;
+        }
+        Orient::Records => {
+            let header = match rows.next() {
+                Some(row) => header_names(&row),
+                None => Vec::new(),
+            };
+            for row in rows {
+                buf.clear();
+                buf.push('{');
+                for (i, (name, cell)) in header.iter().zip(&row).enumerate() {
+                    if i > 0 {
+                        buf.push(',');
+                    }
+                    push_json_str(&mut buf, name);
+                    buf.push(':');
+                    cell.write_json(&mut buf);
+                }
+                buf.push_str("}\n");
+                out.write_all(buf.as_bytes())?;
+                count += 1;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(count)
+}
+
+/// Header names from the first row. Blank headers fall back to the column
+/// letter, and repeated names get a `_2`, `_3`, ... suffix.
+fn header_names(row: &[TypedCell<'_>]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(row.len());
+    for (col, cell) in row.iter().enumerate() {
+        let base = cell
+            .header_text()
+            .unwrap_or_else(|| col_to_letters(col as u32));
+        let mut name = base.clone();
+        let mut n = 2;
+        while names.contains(&name) {
+            name = format!("{base}_{n}");
+            n += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> TypedCell<'_> {
+        TypedCell::new("string", JsonValue::Str(Cow::Borrowed(s)))
+    }
+
+    #[test]
+    fn test_write_json_escapes_and_optional_keys() {
+        let mut out = String::new();
+        text("a\"b\\\n\u{1}").write_json(&mut out);
+        assert_eq!(out, r#"{"type":"string","value":"a\"b\\\n\u0001"}"#);
+
+        out.clear();
+        TypedCell {
+            formula: Some(Cow::Borrowed("=A1*2")),
+            value_type: Some("number"),
+            cached: Some(true),
+            ..TypedCell::new("formula", JsonValue::Num(2.5))
+        }
+        .write_json(&mut out);
+        assert_eq!(
+            out,
+            r#"{"type":"formula","value":2.5,"formula":"=A1*2","value_type":"number","cached":true}"#
+        );
+
+        out.clear();
+        TypedCell::blank().write_json(&mut out);
+        assert_eq!(out, r#"{"type":"blank"}"#);
+    }
+
+    #[test]
+    fn test_write_sheet_orients() {
+        let rows = || {
+            vec![
+                vec![text("id"), TypedCell::blank(), text("id")],
+                vec![
+                    TypedCell::new("number", JsonValue::Num(1.0)),
+                    TypedCell::new("boolean", JsonValue::Bool(true)),
+                    TypedCell::blank(),
+                ],
+            ]
+        };
+
+        let mut buf = Vec::new();
+        assert_eq!(write_sheet(&mut buf, Orient::Records, rows()).unwrap(), 1);
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "{\"id\":{\"type\":\"number\",\"value\":1},\"B\":{\"type\":\"boolean\",\"value\":true},\"id_2\":{\"type\":\"blank\"}}\n"
+        );
+
+        let mut buf = Vec::new();
+        assert_eq!(write_sheet(&mut buf, Orient::Rows, rows()).unwrap(), 2);
+        let out = String::from_utf8(buf).unwrap();
+        assert!(out.starts_with("[[{\"type\":\"string\",\"value\":\"id\"},"));
+        assert!(out.ends_with("{\"type\":\"blank\"}]]\n"));
+    }
+}
@@ -0,0 +1,80 @@
+//! Lazy registration of the backend classes (`load_backend()`).
+//!
+//! Module init only adds the core functions and exception types. Each
+//! backend's classes and functions are added to the module the first time
+//! one of them is looked up (a PEP 562 module `__getattr__`), or up front
+//! with `load_backend(name)`. A wheel built with every backend
+//! (`--features full`) then imports as fast as a single-backend build, and
+//! `wolfxl._rust.CalamineBook` keeps working without an explicit load.
+
+use pyo3::exceptions::{PyAttributeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+
+/// The module attributes one backend adds, and how to add them.
+pub(crate) struct BackendGroup {
+    /// Backend key, as in `build_info()["enabled_backends"]`.
+    pub name: &'static str,
+    pub attrs: &'static [&'static str],
+    pub register: fn(&Bound<'_, PyModule>) -> PyResult<()>,
+}
+
+/// Register `group` unless an earlier lookup already did.
+fn ensure_loaded(m: &Bound<'_, PyModule>, group: &BackendGroup) -> PyResult<()> {
+    // Checked on the dict: `hasattr` would come back through `__getattr__`.
+    if !m.dict().contains(group.attrs[0])? {
+        (group.register)(m)?;
+    }
+    Ok(())
+}
+
+/// Register a compiled-in backend's classes now instead of on first use.
+/// Returns the attribute names it provides; loading twice is a no-op.
+#[pyfunction]
+#[pyo3(pass_module)]
+pub(crate) fn load_backend(m: &Bound<'_, PyModule>, name: &str) -> PyResult<Vec<&'static str>> {
+    let groups = crate::backend_groups();
+    let Some(group) = groups.iter().find(|g| g.name == name) else {
+        let known: Vec<&str> = groups.iter().map(|g| g.name).collect();
+        return Err(PyErr::new::<PyValueError, _>(format!(
+            "Unknown backend '{name}' (available: {})",
+            known.join(", ")
+        )));
+    };
+    ensure_loaded(m, group)?;
+    Ok(group.attrs.to_vec())
+}
+
+/// Module `__getattr__`: load the backend owning `name`, then return it.
+#[pyfunction]
+#[pyo3(pass_module, name = "__getattr__")]
+pub(crate) fn module_getattr(m: &Bound<'_, PyModule>, name: &str) -> PyResult<PyObject> {
+    let groups = crate::backend_groups();
+    if let Some(group) = groups.iter().find(|g| g.attrs.contains(&name)) {
+        ensure_loaded(m, group)?;
+        if let Some(value) = m.dict().get_item(name)? {
+            return Ok(value.unbind());
+        }
+    }
+    Err(PyErr::new::<PyAttributeError, _>(format!(
+        "module 'wolfxl._rust' has no attribute '{name}'"
+    )))
+}
+
+/// Module `__dir__`: loaded attributes plus the ones still to be loaded.
+#[pyfunction]
+#[pyo3(pass_module, name = "__dir__")]
+pub(crate) fn module_dir(m: &Bound<'_, PyModule>) -> PyResult<Vec<String>> {
+    let mut names: Vec<String> = m
+        .dict()
+        .keys()
+        .iter()
+        .map(|k| k.extract())
+        .collect::<PyResult<_>>()?;
+    for group in crate::backend_groups() {
+        names.extend(group.attrs.iter().map(|a| a.to_string()));
+    }
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
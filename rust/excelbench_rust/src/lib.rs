@@ -1,6 +1,18 @@
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 
+mod backend;
+mod capabilities;
+#[allow(dead_code)] // Each backend raises a different subset of the exception helpers
+mod errors;
+mod lazy;
+mod logging;
+mod profile;
+
+mod dep_versions {
+    include!(concat!(env!("OUT_DIR"), "/dep_versions.rs"));
+}
+
 #[cfg(any(
     feature = "calamine",
     feature = "rust_xlsxwriter",
@@ -9,18 +21,102 @@ use pyo3::types::{PyDict, PyList};
 ))]
 mod util;
 
-#[cfg(any(feature = "calamine", feature = "rust_xlsxwriter", feature = "wolfxl"))]
+#[cfg(any(
+    feature = "calamine",
+    feature = "rust_xlsxwriter",
+    feature = "umya",
+    feature = "wolfxl"
+))]
+#[allow(dead_code)] // Each feature set uses a different subset of the reference helpers
+mod cell_ref;
+
+#[cfg(any(
+    feature = "calamine",
+    feature = "rust_xlsxwriter",
+    feature = "umya",
+    feature = "wolfxl"
+))]
+#[allow(dead_code)] // Each feature set uses a different subset of the formula helpers
+mod formula;
+
+#[cfg(any(
+    feature = "calamine",
+    feature = "rust_xlsxwriter",
+    feature = "umya",
+    feature = "wolfxl"
+))]
 mod ooxml_util;
 
+#[cfg(any(
+    feature = "calamine",
+    feature = "rust_xlsxwriter",
+    feature = "umya",
+    feature = "wolfxl"
+))]
+#[allow(dead_code)] // Readers resolve colors, writers only parse them
+mod color;
+
+#[cfg(any(feature = "calamine", feature = "wolfxl"))]
+mod numfmt;
+
+#[cfg(any(feature = "rust_xlsxwriter", feature = "umya", feature = "wolfxl"))]
+mod payload;
+
+#[cfg(any(feature = "rust_xlsxwriter", feature = "umya"))]
+mod csv_io;
+
+#[cfg(any(feature = "rust_xlsxwriter", feature = "umya"))]
+#[allow(dead_code)] // width_to_pixels is only used by rust_xlsxwriter
+mod autofit;
+
+#[cfg(any(feature = "calamine", feature = "umya"))]
+mod csv_export;
+
+#[cfg(any(feature = "calamine", feature = "umya"))]
+mod json_export;
+
+#[cfg(any(
+    feature = "calamine",
+    feature = "rust_xlsxwriter",
+    feature = "umya",
+    feature = "wolfxl"
+))]
+mod transcode;
+
+#[cfg(any(
+    feature = "calamine",
+    feature = "rust_xlsxwriter",
+    feature = "umya",
+    feature = "wolfxl"
+))]
+mod roundtrip;
+
+#[cfg(any(
+    feature = "calamine",
+    feature = "rust_xlsxwriter",
+    feature = "umya",
+    feature = "wolfxl"
+))]
+mod structured_ref;
+
 #[cfg(feature = "calamine")]
 mod calamine_backend;
 
 #[cfg(feature = "calamine")]
 mod calamine_styled_backend;
 
+#[cfg(feature = "calamine")]
+mod calamine_columnar;
+
+#[cfg(feature = "encryption")]
+mod office_crypto;
+
 #[cfg(feature = "rust_xlsxwriter")]
 mod rust_xlsxwriter_backend;
 
+#[cfg(feature = "rust_xlsxwriter")]
+mod fixture_builder;
+
 #[cfg(feature = "umya")]
 mod umya;
 
@@ -44,6 +140,70 @@ fn enabled_backends() -> Vec<&'static str> {
     out
 }
 
+/// Backend classes and functions, registered on first use (see `lazy`).
+fn backend_groups() -> Vec<lazy::BackendGroup> {
+    #[allow(unused_mut)]
+    let mut out: Vec<lazy::BackendGroup> = Vec::new();
+    #[cfg(feature = "calamine")]
+    out.push(lazy::BackendGroup {
+        name: "calamine",
+        attrs: &["CalamineBook", "CalamineRowIter", "CalamineStyledBook"],
+        register: |m| {
+            m.add_class::<calamine_backend::CalamineBook>()?;
+            m.add_class::<calamine_backend::CalamineRowIter>()?;
+            m.add_class::<calamine_styled_backend::CalamineStyledBook>()
+        },
+    });
+    #[cfg(feature = "rust_xlsxwriter")]
+    out.push(lazy::BackendGroup {
+        name: "rust_xlsxwriter",
+        attrs: &["RustXlsxWriterBook", "FixtureBuilder"],
+        register: |m| {
+            m.add_class::<rust_xlsxwriter_backend::RustXlsxWriterBook>()?;
+            m.add_class::<fixture_builder::FixtureBuilder>()
+        },
+    });
+    #[cfg(feature = "umya")]
+    out.push(lazy::BackendGroup {
+        name: "umya-spreadsheet",
+        attrs: &["UmyaBook"],
+        register: |m| m.add_class::<umya::UmyaBook>(),
+    });
+    #[cfg(feature = "wolfxl")]
+    out.push(lazy::BackendGroup {
+        name: "wolfxl",
+        attrs: &[
+            "XlsxPatcher",
+            "XlsxReader",
+            "XlsxRowIter",
+            "transform",
+            "patch_hyperlink_attrs",
+            "anonymize",
+            "sample",
+            "extract_print_preview_metadata",
+            "render_html",
+        ],
+        register: |m| {
+            m.add_class::<wolfxl::XlsxPatcher>()?;
+            m.add_class::<wolfxl::reader::XlsxReader>()?;
+            m.add_class::<wolfxl::reader::XlsxRowIter>()?;
+            m.add_function(wrap_pyfunction!(wolfxl::transform::transform, m)?)?;
+            m.add_function(wrap_pyfunction!(
+                wolfxl::hyperlinks::patch_hyperlink_attrs,
+                m
+            )?)?;
+            m.add_function(wrap_pyfunction!(wolfxl::anonymize::anonymize, m)?)?;
+            m.add_function(wrap_pyfunction!(wolfxl::preview::sample, m)?)?;
+            m.add_function(wrap_pyfunction!(
+                wolfxl::preview::extract_print_preview_metadata,
+                m
+            )?)?;
+            m.add_function(wrap_pyfunction!(wolfxl::render::render_html, m)?)
+        },
+    });
+    out
+}
+
 #[pyfunction]
 fn build_info(py: Python<'_>) -> PyResult<PyObject> {
     // Stable keys so Python adapters can depend on this shape.
@@ -54,8 +214,7 @@ fn build_info(py: Python<'_>) -> PyResult<PyObject> {
     let enabled = enabled_backends();
     info.set_item("enabled_backends", PyList::new(py, enabled)?)?;
 
-    // Backend version reporting can be filled in later.
-    // Keep a dict in place now so consumers can read it unconditionally.
+    // Enabled/disabled per backend; versions are in `backend_versions` below.
     let backends = PyDict::new(py);
     backends.set_item(
         "calamine",
@@ -83,17 +242,16 @@ fn build_info(py: Python<'_>) -> PyResult<PyObject> {
     )?;
     info.set_item("backends", backends)?;
 
+    // Resolved at build time from Cargo.lock (see build.rs); None when the
+    // backend is compiled out.
     let versions = PyDict::new(py);
-    versions.set_item("calamine", option_env!("EXCELBENCH_DEP_CALAMINE_VERSION"))?;
-    versions.set_item(
-        "rust_xlsxwriter",
-        option_env!("EXCELBENCH_DEP_RUST_XLSXWRITER_VERSION"),
-    )?;
-    versions.set_item(
-        "umya-spreadsheet",
-        option_env!("EXCELBENCH_DEP_UMYA_VERSION"),
-    )?;
+    let sources = PyDict::new(py);
+    for (key, version, source) in dep_versions::BACKEND_VERSIONS {
+        versions.set_item(*key, *version)?;
+        sources.set_item(*key, *source)?;
+    }
     info.set_item("backend_versions", versions)?;
+    info.set_item("backend_sources", sources)?;
 
     Ok(info.into())
 }
@@ -101,28 +259,48 @@ fn build_info(py: Python<'_>) -> PyResult<PyObject> {
 #[pymodule]
 fn _rust(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    errors::register(m)?;
     m.add_function(wrap_pyfunction!(build_info, m)?)?;
+    m.add_function(wrap_pyfunction!(capabilities::capabilities, m)?)?;
+    m.add_function(wrap_pyfunction!(backend::open_backend, m)?)?;
+    m.add_function(wrap_pyfunction!(profile::enable_profiling, m)?)?;
+    m.add_function(wrap_pyfunction!(profile::get_profile, m)?)?;
+    logging::init();
+    m.add_function(wrap_pyfunction!(logging::flush_logs, m)?)?;
+    #[cfg(any(
+        feature = "calamine",
+        feature = "rust_xlsxwriter",
+        feature = "umya",
+        feature = "wolfxl"
+    ))]
+    m.add_function(wrap_pyfunction!(transcode::transcode, m)?)?;
+    #[cfg(any(
+        feature = "calamine",
+        feature = "rust_xlsxwriter",
+        feature = "umya",
+        feature = "wolfxl"
+    ))]
+    m.add_function(wrap_pyfunction!(roundtrip::roundtrip, m)?)?;
 
-    #[cfg(feature = "calamine")]
-    {
-        m.add_class::<calamine_backend::CalamineBook>()?;
-        m.add_class::<calamine_styled_backend::CalamineStyledBook>()?;
-    }
-
-    #[cfg(feature = "rust_xlsxwriter")]
-    {
-        m.add_class::<rust_xlsxwriter_backend::RustXlsxWriterBook>()?;
-    }
+    #[cfg(any(
+        feature = "calamine",
+        feature = "rust_xlsxwriter",
+        feature = "umya",
+        feature = "wolfxl"
+    ))]
+    m.add_function(wrap_pyfunction!(ooxml_util::validate_xlsx, m)?)?;
+    #[cfg(any(
+        feature = "calamine",
+        feature = "rust_xlsxwriter",
+        feature = "umya",
+        feature = "wolfxl"
+    ))]
+    m.add_function(wrap_pyfunction!(structured_ref::resolve_structured_ref, m)?)?;
 
-    #[cfg(feature = "umya")]
-    {
-        m.add_class::<umya::UmyaBook>()?;
-    }
-
-    #[cfg(feature = "wolfxl")]
-    {
-        m.add_class::<wolfxl::XlsxPatcher>()?;
-    }
+    // Backend classes are added on first lookup or by load_backend().
+    m.add_function(wrap_pyfunction!(lazy::load_backend, m)?)?;
+    m.add_function(wrap_pyfunction!(lazy::module_getattr, m)?)?;
+    m.add_function(wrap_pyfunction!(lazy::module_dir, m)?)?;
 
     Ok(())
 }
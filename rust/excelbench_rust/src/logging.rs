@@ -0,0 +1,127 @@
+//! Forwards `log` records to Python's `logging` module.
+//!
+//! Backends report silent degradation (an unknown border style written as
+//! thin, a conditional rule the writer cannot express, a best-effort
+//! post-save patch that failed) with `log::warn!` / `log::debug!`. A record
+//! from `excelbench_rust::umya::util` goes to the `excelbench_rust.umya.util`
+//! logger, so `logging.getLogger("excelbench_rust")` controls them all.
+//! Records from dependency crates are not forwarded.
+//!
+//! Emitting takes the GIL. A record from a thread that does not hold it
+//! (a rayon worker while the caller waits with the GIL held) is queued
+//! instead and emitted with the next record logged under the GIL, or by
+//! `flush_logs()`.
+
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use pyo3::prelude::*;
+
+const ROOT: &str = "excelbench_rust";
+
+/// (logger name, Python level, message) waiting for a thread with the GIL.
+static PENDING: Mutex<Vec<(String, u32, String)>> = Mutex::new(Vec::new());
+
+struct PyLogger;
+
+static LOGGER: PyLogger = PyLogger;
+
+/// `logging` level number for a `log` level.
+fn py_level(level: Level) -> u32 {
+    match level {
+        Level::Error => 40,
+        Level::Warn => 30,
+        Level::Info => 20,
+        Level::Debug => 10,
+        Level::Trace => 5,
+    }
+}
+
+/// `excelbench_rust::umya::util` → `excelbench_rust.umya.util`.
+fn logger_name(target: &str) -> String {
+    target.replace("::", ".")
+}
+
+fn emit(py: Python<'_>, records: Vec<(String, u32, String)>) -> PyResult<()> {
+    let logging = py.import("logging")?;
+    for (name, level, message) in records {
+        logging
+            .call_method1("getLogger", (name,))?
+            .call_method1("log", (level, message))?;
+    }
+    Ok(())
+}
+
+/// Emit queued records plus `extra` on a thread that holds the GIL.
+fn drain(py: Python<'_>, extra: Option<(String, u32, String)>) {
+    let mut records = std::mem::take(&mut *PENDING.lock().unwrap_or_else(|e| e.into_inner()));
+    records.extend(extra);
+    if records.is_empty() {
+        return;
+    }
+    // A failing handler must not turn a warning into a crash.
+    if let Err(e) = emit(py, records) {
+        e.print(py);
+    }
+}
+
+impl Log for PyLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target().starts_with(ROOT)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let entry = (
+            logger_name(record.target()),
+            py_level(record.level()),
+            record.args().to_string(),
+        );
+        // SAFETY: PyGILState_Check only reads the calling thread's state.
+        if unsafe { pyo3::ffi::PyGILState_Check() } == 1 {
+            Python::with_gil(|py| drain(py, Some(entry)));
+        } else {
+            PENDING
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(entry);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the forwarder. Python's logger levels do the filtering, so
+/// everything down to debug is passed on; a logger installed earlier by an
+/// embedding program is left in place.
+pub(crate) fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Debug);
+    }
+}
+
+/// Emit records queued by worker threads since the last call that logged.
+#[pyfunction]
+pub(crate) fn flush_logs(py: Python<'_>) {
+    drain(py, None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logger_name_and_level() {
+        assert_eq!(
+            logger_name("excelbench_rust::umya::util"),
+            "excelbench_rust.umya.util"
+        );
+        assert_eq!(py_level(Level::Warn), 30);
+        let dep = Metadata::builder()
+            .target("umya_spreadsheet::reader")
+            .build();
+        assert!(!LOGGER.enabled(&dep));
+    }
+}
@@ -0,0 +1,292 @@
+//! Excel number-format helpers shared by the reader backends.
+//!
+//! Format codes are tokenized rather than substring-matched, so quoted text,
+//! escapes, colors/conditions (`[Red]`, `[>100]`), locale tags (`[$-409]`) and
+//! fill/padding directives never masquerade as date tokens, while elapsed-time
+//! brackets (`[h]`, `[mm]`, `[ss]`) are recognized. [`format_value`] renders
+//! a value through a code the way a cell displays it.
+
+mod format;
+
+pub(crate) use format::{format_value, iso_serial, serial_1900, CellValue, Locale};
+
+/// Built-in number format codes (ECMA-376 Part 1, 18.8.30).
+pub(crate) fn builtin_format_code(id: u32) -> Option<&'static str> {
+    Some(match id {
+        0 => "General",
+        1 => "0",
+        2 => "0.00",
+        3 => "#,##0",
+        4 => "#,##0.00",
+        9 => "0%",
+        10 => "0.00%",
+        11 => "0.00E+00",
+        12 => "# ?/?",
+        13 => "# ??/??",
+        14 => "mm-dd-yy",
+        15 => "d-mmm-yy",
+        16 => "d-mmm",
+        17 => "mmm-yy",
+        18 => "h:mm AM/PM",
+        19 => "h:mm:ss AM/PM",
+        20 => "h:mm",
+        21 => "h:mm:ss",
+        22 => "m/d/yy h:mm",
+        37 => "#,##0 ;(#,##0)",
+        38 => "#,##0 ;[Red](#,##0)",
+        39 => "#,##0.00;(#,##0.00)",
+        40 => "#,##0.00;[Red](#,##0.00)",
+        45 => "mm:ss",
+        46 => "[h]:mm:ss",
+        47 => "mmss.0",
+        48 => "##0.0E+0",
+        49 => "@",
+        _ => return None,
+    })
+}
+
+/// Built-in ids that Excel always treats as dates/times, whatever the code says.
+pub(crate) fn is_builtin_date_id(id: u32) -> bool {
+    matches!(id, 14..=22 | 45..=47)
+}
+
+/// Token kinds produced by [`tokenize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Token {
+    /// Date/time placeholder run, lowercased (`yyyy`, `mm`, `d`, `h`, `ss`, `am/pm`).
+    DateTime(String),
+    /// Elapsed-time bracket, lowercased without brackets (`h`, `mm`, `ss`).
+    Elapsed(String),
+    /// Digit placeholders and numeric punctuation (`0`, `#`, `?`, `.`, `,`, `%`, `E+`).
+    Numeric(String),
+    /// `@` text placeholder.
+    TextPlaceholder,
+    /// Literal text: quoted strings, escapes and pass-through characters.
+    Literal(String),
+    /// Non-elapsed bracket content (colors, conditions, locale tags).
+    Bracket(String),
+    /// `General` keyword.
+    General,
+    /// `;` section separator.
+    SectionSep,
+}
+
+/// Tokenize a number format code.
+pub(crate) fn tokenize(code: &str) -> Vec<Token> {
+    let chars: Vec<char> = code.chars().collect();
+    let mut out: Vec<Token> = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        match ch {
+            '"' => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && chars[i] != '"' {
+                    i += 1;
+                }
+                out.push(Token::Literal(
+                    chars[start..i.min(chars.len())].iter().collect(),
+                ));
+                i += 1;
+            }
+            '\\' => {
+                if let Some(c) = chars.get(i + 1) {
+                    out.push(Token::Literal(c.to_string()));
+                }
+                i += 2;
+            }
+            // `_x` pads with the width of x (one space here); `*x` repeats x
+            // to fill the cell.
+            '_' => {
+                out.push(Token::Literal(" ".to_string()));
+                i += 2;
+            }
+            '*' => {
+                i += 2;
+            }
+            '[' => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && chars[i] != ']' {
+                    i += 1;
+                }
+                let inner: String = chars[start..i.min(chars.len())].iter().collect();
+                let lower = inner.to_ascii_lowercase();
+                let is_elapsed = !lower.is_empty()
+                    && lower.chars().all(|c| c == lower.chars().next().unwrap())
+                    && matches!(lower.chars().next(), Some('h' | 'm' | 's'));
+                if is_elapsed {
+                    out.push(Token::Elapsed(lower));
+                } else {
+                    out.push(Token::Bracket(inner));
+                }
+                i += 1;
+            }
+            ';' => {
+                out.push(Token::SectionSep);
+                i += 1;
+            }
+            '@' => {
+                out.push(Token::TextPlaceholder);
+                i += 1;
+            }
+            '0' | '#' | '?' | '.' | ',' | '%' => {
+                let start = i;
+                while i < chars.len() && matches!(chars[i], '0' | '#' | '?' | '.' | ',' | '%') {
+                    i += 1;
+                }
+                out.push(Token::Numeric(chars[start..i].iter().collect()));
+            }
+            'E' | 'e' if matches!(chars.get(i + 1), Some('+' | '-')) => {
+                out.push(Token::Numeric(chars[i..i + 2].iter().collect()));
+                i += 2;
+            }
+            _ => {
+                let rest: String = chars[i..].iter().collect();
+                let lower_rest = rest.to_ascii_lowercase();
+                if lower_rest.starts_with("general") {
+                    out.push(Token::General);
+                    i += "general".len();
+                } else if lower_rest.starts_with("am/pm") {
+                    out.push(Token::DateTime("am/pm".to_string()));
+                    i += "am/pm".len();
+                } else if lower_rest.starts_with("a/p") {
+                    out.push(Token::DateTime("a/p".to_string()));
+                    i += "a/p".len();
+                } else if matches!(ch.to_ascii_lowercase(), 'y' | 'm' | 'd' | 'h' | 's') {
+                    let lc = ch.to_ascii_lowercase();
+                    let start = i;
+                    while i < chars.len() && chars[i].to_ascii_lowercase() == lc {
+                        i += 1;
+                    }
+                    out.push(Token::DateTime(
+                        chars[start..i]
+                            .iter()
+                            .collect::<String>()
+                            .to_ascii_lowercase(),
+                    ));
+                } else {
+                    out.push(Token::Literal(ch.to_string()));
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    out
+}
+
+/// True if a format code renders its value as a date and/or time.
+///
+/// Only the first section decides (that's the section Excel applies to
+/// positive numbers, which is what a stored serial is).
+pub(crate) fn is_date_format(code: &str) -> bool {
+    tokenize(code)
+        .into_iter()
+        .take_while(|t| *t != Token::SectionSep)
+        .any(|t| matches!(t, Token::DateTime(_) | Token::Elapsed(_)))
+}
+
+/// Resolve a cell's effective format: id-based built-ins win for date detection.
+pub(crate) fn is_date_format_id(id: u32, code: Option<&str>) -> bool {
+    if is_builtin_date_id(id) {
+        return true;
+    }
+    match code.or_else(|| builtin_format_code(id)) {
+        Some(c) => is_date_format(c),
+        None => false,
+    }
+}
+
+/// True if a format code shows only a time of day (`h:mm`, `mm:ss`,
+/// `h:mm AM/PM`): hour or second tokens and no year, day or month name.
+/// Elapsed formats (`[h]:mm`) count as durations, not times.
+pub(crate) fn is_time_format(code: &str) -> bool {
+    let mut has_time = false;
+    for t in tokenize(code)
+        .into_iter()
+        .take_while(|t| *t != Token::SectionSep)
+    {
+        match t {
+            Token::Elapsed(_) => return false,
+            Token::DateTime(s) if s.starts_with(['y', 'd']) || s.starts_with("mmm") => {
+                return false
+            }
+            Token::DateTime(s) if s.starts_with(['h', 's']) => has_time = true,
+            _ => {}
+        }
+    }
+    has_time
+}
+
+/// [`is_time_format`] for a cell's format id, with the built-in time ids
+/// decided by id as in [`is_date_format_id`].
+pub(crate) fn is_time_format_id(id: u32, code: Option<&str>) -> bool {
+    match id {
+        18..=21 | 45 | 47 => true,
+        14..=17 | 22 | 46 => false,
+        _ => code
+            .or_else(|| builtin_format_code(id))
+            .is_some_and(is_time_format),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_dates_and_times() {
+        assert!(is_date_format("yyyy-mm-dd"));
+        assert!(is_date_format("mmm-yy"));
+        assert!(is_date_format("h:mm AM/PM"));
+        assert!(is_date_format("[h]:mm"));
+        assert!(is_date_format("[$-409]d-mmm-yyyy;@"));
+    }
+
+    #[test]
+    fn test_non_dates() {
+        assert!(!is_date_format("General"));
+        assert!(!is_date_format("0.00E+00"));
+        assert!(!is_date_format("#,##0.00;[Red](#,##0.00)"));
+        assert!(!is_date_format("\"days\" 0"));
+        assert!(!is_date_format("0_);[Red]\\(0\\)"));
+        assert!(!is_date_format("@"));
+    }
+
+    #[test]
+    fn test_elapsed_bracket_token() {
+        let toks = tokenize("[mm]:ss");
+        assert_eq!(toks[0], Token::Elapsed("mm".to_string()));
+        assert_eq!(toks[2], Token::DateTime("ss".to_string()));
+    }
+
+    #[test]
+    fn test_time_formats() {
+        assert!(is_time_format("h:mm"));
+        assert!(is_time_format("hh:mm:ss AM/PM"));
+        assert!(is_time_format("mm:ss.0"));
+        assert!(is_time_format("[$-409]h:mm:ss;@"));
+        assert!(!is_time_format("[h]:mm:ss"));
+        assert!(!is_time_format("yyyy-mm-dd hh:mm"));
+        assert!(!is_time_format("d-mmm"));
+        assert!(!is_time_format("mmm"));
+        assert!(!is_time_format("0.00"));
+        assert!(!is_time_format("\"h\" 0"));
+        assert!(is_time_format_id(21, None));
+        assert!(!is_time_format_id(22, None));
+        assert!(!is_time_format_id(46, None));
+        assert!(is_time_format_id(165, Some("h:mm;@")));
+        assert!(!is_time_format_id(166, Some("dd/mm/yyyy")));
+    }
+
+    #[test]
+    fn test_builtin_ids() {
+        assert!(is_date_format_id(14, None));
+        assert!(is_date_format_id(46, Some("[h]:mm:ss")));
+        assert!(!is_date_format_id(4, None));
+        assert!(is_date_format_id(165, Some("dd/mm/yyyy")));
+    }
+}
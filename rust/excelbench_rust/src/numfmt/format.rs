@@ -0,0 +1,877 @@
+//! Excel number-format rendering: the text a cell shows for its value and
+//! format code (`format_cell_display()` on the readers).
+//!
+//! Sections (`positive;negative;zero;text`) and `[>100]` conditions pick the
+//! pattern; digit placeholders `0 # ?`, thousands and scaling commas, `%`,
+//! `E+00`, fractions (`# ?/?`, `# ??/16`), literal text, `[$€-407]` currency
+//! tags, dates, times and elapsed `[h]` are rendered the way Excel does.
+//! Colors are dropped, `_x` pads with one space and `*x` fills nothing, so
+//! `#,##0.00_);[Red](#,##0.00)` shows `1,234.50 ` and `(1,234.50)`. Month
+//! and day names are English; a [`Locale`] sets the decimal and thousands
+//! separators.
+
+use std::fmt::Write as _;
+
+use chrono::{Datelike, NaiveDateTime};
+
+use super::{tokenize, Token};
+use crate::util::{excel_serial_to_datetime, parse_iso_date, parse_iso_datetime};
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const DAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+/// What Excel shows for a date outside 1900-01-00 ..= 9999-12-31.
+const OUT_OF_RANGE: &str = "########";
+/// Serial of 9999-12-31 plus one day.
+const MAX_SERIAL: f64 = 2_958_466.0;
+
+/// A cell value to render. Dates are serials in the 1900 date system (see
+/// [`serial_1900`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CellValue<'a> {
+    Number(f64),
+    Text(&'a str),
+    Bool(bool),
+    Error(&'a str),
+}
+
+/// Decimal and thousands separators used for the rendered digits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Locale {
+    pub decimal: char,
+    pub thousands: char,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            decimal: '.',
+            thousands: ',',
+        }
+    }
+}
+
+impl Locale {
+    /// Separators for a language tag such as `en-US`, `de-DE` or `fr`.
+    pub(crate) fn from_tag(tag: &str) -> Result<Self, String> {
+        let tag_lower = tag.trim().replace('_', "-").to_ascii_lowercase();
+        let lang = tag_lower.split('-').next().unwrap_or_default();
+        let (decimal, thousands) = match (lang, tag_lower.as_str()) {
+            (_, "de-ch" | "it-ch") => ('.', '\''),
+            ("en" | "ja" | "zh" | "ko" | "he" | "th" | "hi", _) => ('.', ','),
+            ("de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el", _) => (',', '.'),
+            ("fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "nb" | "fi" | "uk" | "hu", _) => {
+                (',', '\u{a0}')
+            }
+            _ => return Err(format!("Unsupported locale: {tag}")),
+        };
+        Ok(Self { decimal, thousands })
+    }
+}
+
+/// A serial from a workbook's date system as a 1900-system serial.
+pub(crate) fn serial_1900(serial: f64, date1904: bool) -> f64 {
+    if date1904 {
+        serial + 1462.0
+    } else {
+        serial
+    }
+}
+
+/// 1900-system serial of a `t="d"` ISO date or datetime.
+pub(crate) fn iso_serial(s: &str) -> Option<f64> {
+    let dt = parse_iso_datetime(s)
+        .or_else(|| parse_iso_date(s.trim_end_matches('Z')).and_then(|d| d.and_hms_opt(0, 0, 0)))?;
+    Some(datetime_to_serial(dt))
+}
+
+/// 1900-system serial of a datetime, counting Excel's phantom 1900-02-29.
+fn datetime_to_serial(dt: NaiveDateTime) -> f64 {
+    let epoch = chrono::NaiveDate::from_ymd_opt(1899, 12, 30)
+        .unwrap_or_default()
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default();
+    let serial = (dt - epoch).num_milliseconds() as f64 / 86_400_000.0;
+    if serial < 61.0 {
+        serial - 1.0
+    } else {
+        serial
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Sections
+// ---------------------------------------------------------------------------
+
+/// A `[>100]`-style section condition.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Condition {
+    Lt(f64),
+    Le(f64),
+    Gt(f64),
+    Ge(f64),
+    Eq(f64),
+    Ne(f64),
+}
+
+impl Condition {
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let split = s.find(|c: char| !matches!(c, '<' | '>' | '='))?;
+        let x: f64 = s[split..].trim().parse().ok()?;
+        Some(match &s[..split] {
+            "<" => Self::Lt(x),
+            "<=" => Self::Le(x),
+            ">" => Self::Gt(x),
+            ">=" => Self::Ge(x),
+            "=" => Self::Eq(x),
+            "<>" => Self::Ne(x),
+            _ => return None,
+        })
+    }
+
+    fn matches(self, v: f64) -> bool {
+        match self {
+            Self::Lt(x) => v < x,
+            Self::Le(x) => v <= x,
+            Self::Gt(x) => v > x,
+            Self::Ge(x) => v >= x,
+            Self::Eq(x) => v == x,
+            Self::Ne(x) => v != x,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Section {
+    tokens: Vec<Token>,
+    condition: Option<Condition>,
+}
+
+impl Section {
+    fn is_text(&self) -> bool {
+        self.tokens.contains(&Token::TextPlaceholder)
+    }
+
+    fn is_date(&self) -> bool {
+        self.tokens
+            .iter()
+            .any(|t| matches!(t, Token::DateTime(_) | Token::Elapsed(_)))
+    }
+}
+
+/// Split a format code into sections. Conditions are kept, currency tags
+/// become literals, colors and locale ids are dropped.
+fn sections(code: &str) -> Vec<Section> {
+    let mut out = vec![Section::default()];
+    for token in tokenize(code) {
+        let section = out.last_mut().expect("at least one section");
+        match token {
+            Token::SectionSep => out.push(Section::default()),
+            Token::Bracket(inner) => {
+                if let Some(cond) = Condition::parse(&inner) {
+                    section.condition = Some(cond);
+                } else if let Some(symbol) = inner
+                    .strip_prefix('$')
+                    .map(|s| s.split('-').next().unwrap_or_default())
+                    .filter(|s| !s.is_empty())
+                {
+                    section.tokens.push(Token::Literal(symbol.to_string()));
+                }
+            }
+            token => section.tokens.push(token),
+        }
+    }
+    out
+}
+
+/// Render `value` through the format `code` as Excel displays it.
+pub(crate) fn format_value(value: CellValue<'_>, code: &str, locale: &Locale) -> String {
+    let sections = sections(code);
+    match value {
+        CellValue::Number(v) => format_number(v, &sections, locale),
+        CellValue::Text(text) => {
+            let section = sections
+                .get(3)
+                .or_else(|| sections.iter().find(|s| s.is_text()));
+            let Some(section) = section else {
+                return text.to_string();
+            };
+            let mut out = String::new();
+            for token in &section.tokens {
+                match token {
+                    Token::TextPlaceholder => out.push_str(text),
+                    Token::Literal(s) => out.push_str(s),
+                    _ => {}
+                }
+            }
+            out
+        }
+        CellValue::Bool(b) => (if b { "TRUE" } else { "FALSE" }).to_string(),
+        CellValue::Error(e) => e.to_string(),
+    }
+}
+
+fn format_number(v: f64, sections: &[Section], locale: &Locale) -> String {
+    let numeric: Vec<&Section> = sections.iter().take(3).filter(|s| !s.is_text()).collect();
+    let conditional = numeric.iter().take(2).any(|s| s.condition.is_some());
+    let (section, value) = if conditional {
+        // The first section whose condition holds, or an unconditional one.
+        match numeric
+            .iter()
+            .find(|s| s.condition.is_none_or(|c| c.matches(v)))
+        {
+            Some(s) => (*s, v),
+            None => return OUT_OF_RANGE.to_string(),
+        }
+    } else {
+        match numeric.len() {
+            0 => return general(v, locale),
+            1 => (numeric[0], v),
+            _ if v < 0.0 => (numeric[1], -v),
+            n if v == 0.0 && n >= 3 => (numeric[2], v),
+            _ => (numeric[0], v),
+        }
+    };
+
+    if section.is_date() {
+        if !(0.0..MAX_SERIAL).contains(&value) {
+            return OUT_OF_RANGE.to_string();
+        }
+        return format_date(value, &section.tokens, locale);
+    }
+    let parts = parts(&section.tokens);
+    let body = format_parts(value.abs(), &parts, locale);
+    let shows_number = parts
+        .iter()
+        .any(|p| matches!(p, Part::Digit(_) | Part::General));
+    // `-0.00` is shown as `0.00`.
+    if value < 0.0 && shows_number && body.chars().any(|c| matches!(c, '1'..='9')) {
+        format!("-{body}")
+    } else {
+        body
+    }
+}
+
+// ---------------------------------------------------------------------------
+// General
+// ---------------------------------------------------------------------------
+
+/// Round half away from zero after dropping float noise beyond 15
+/// significant digits (so 1.005 rounds to 1.01, as in Excel).
+fn round_to(v: f64, decimals: usize) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    let scaled = v * factor;
+    let clean: f64 = format!("{scaled:.14e}").parse().unwrap_or(scaled);
+    clean.round() / factor
+}
+
+fn trim_fraction(s: &str) -> &str {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        s
+    }
+}
+
+/// Excel's General format: up to 11 characters, scientific for magnitudes
+/// of 1E+11 and above or below 1E-04.
+fn general(v: f64, locale: &Locale) -> String {
+    if v == 0.0 || !v.is_finite() {
+        return "0".to_string();
+    }
+    let sign = if v < 0.0 { "-" } else { "" };
+    let abs = v.abs();
+    let text = if !(1e-4..1e11).contains(&abs) {
+        let s = format!("{:.5e}", round_to(abs, 20));
+        let (mantissa, exp) = s.split_once('e').unwrap_or((&s, "0"));
+        let exp: i32 = exp.parse().unwrap_or(0);
+        let exp_sign = if exp < 0 { '-' } else { '+' };
+        format!("{}E{exp_sign}{:02}", trim_fraction(mantissa), exp.abs())
+    } else {
+        let int_digits = if abs < 1.0 {
+            1
+        } else {
+            abs.log10().floor() as usize + 1
+        };
+        let decimals = 10usize.saturating_sub(int_digits);
+        let s = format!("{:.*}", decimals, round_to(abs, decimals));
+        trim_fraction(&s).to_string()
+    };
+    format!("{sign}{}", text.replace('.', &locale.decimal.to_string()))
+}
+
+// ---------------------------------------------------------------------------
+// Numbers
+// ---------------------------------------------------------------------------
+
+/// One piece of a numeric section.
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    /// `0`, `#` or `?`.
+    Digit(char),
+    Point,
+    /// `,` between digit placeholders: thousands separators.
+    Group,
+    /// `,` after the last digit placeholder: divide by 1000.
+    Scale,
+    Percent,
+    /// `E+` (true) or `E-`.
+    Exp(bool),
+    Literal(String),
+    General,
+}
+
+fn parts(tokens: &[Token]) -> Vec<Part> {
+    let mut parts = Vec::new();
+    let mut seen_point = false;
+    for token in tokens {
+        match token {
+            Token::Numeric(s) if s.eq_ignore_ascii_case("e+") || s.eq_ignore_ascii_case("e-") => {
+                parts.push(Part::Exp(s.ends_with('+')));
+            }
+            Token::Numeric(s) => {
+                for ch in s.chars() {
+                    parts.push(match ch {
+                        '0' | '#' | '?' => Part::Digit(ch),
+                        '.' if !seen_point => {
+                            seen_point = true;
+                            Part::Point
+                        }
+                        ',' => Part::Group,
+                        '%' => Part::Percent,
+                        c => Part::Literal(c.to_string()),
+                    });
+                }
+            }
+            Token::Literal(s) => parts.push(Part::Literal(s.clone())),
+            Token::General => parts.push(Part::General),
+            _ => {}
+        }
+    }
+    for i in 0..parts.len() {
+        if parts[i] != Part::Group {
+            continue;
+        }
+        let digit_before = parts[..i].iter().any(|p| matches!(p, Part::Digit(_)));
+        let digit_after = matches!(
+            parts[i + 1..]
+                .iter()
+                .find(|p| **p != Part::Group && **p != Part::Scale),
+            Some(Part::Digit(_))
+        );
+        parts[i] = match (digit_before, digit_after) {
+            (true, true) => Part::Group,
+            (true, false) => Part::Scale,
+            _ => Part::Literal(",".to_string()),
+        };
+    }
+    parts
+}
+
+/// Spread `digits` over integer placeholders right to left; the leftmost
+/// placeholder takes any overflow.
+fn fill_integer(digits: &str, placeholders: &[char]) -> (Vec<String>, String) {
+    let mut digits: Vec<char> = digits.chars().collect();
+    let mut slots = vec![String::new(); placeholders.len()];
+    for (slot, p) in slots.iter_mut().zip(placeholders).rev() {
+        *slot = match digits.pop() {
+            Some(d) => d.to_string(),
+            None => match p {
+                '0' => "0".to_string(),
+                '?' => " ".to_string(),
+                _ => String::new(),
+            },
+        };
+    }
+    let overflow: String = digits.into_iter().collect();
+    match slots.first_mut() {
+        Some(first) => {
+            first.insert_str(0, &overflow);
+            (slots, String::new())
+        }
+        None => (slots, overflow),
+    }
+}
+
+/// Insert `sep` every three digits of the digit run ending `s`.
+fn group_thousands(s: &str, sep: char) -> String {
+    let start = s.find(|c: char| c.is_ascii_digit()).unwrap_or(s.len());
+    let (pad, digits) = s.split_at(start);
+    let mut out = pad.to_string();
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(sep);
+        }
+        out.push(ch);
+    }
+    out
+}
+
+fn placeholders(parts: &[Part]) -> Vec<char> {
+    parts
+        .iter()
+        .filter_map(|p| match p {
+            Part::Digit(c) => Some(*c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Render a non-negative value through a numeric section.
+fn format_parts(v: f64, parts: &[Part], locale: &Locale) -> String {
+    let is_slash = |p: &Part| matches!(p, Part::Literal(s) if s == "/");
+    let slash = parts.iter().position(is_slash).filter(|&i| {
+        matches!(parts[..i].last(), Some(Part::Digit(_)))
+            && !parts
+                .iter()
+                .any(|p| matches!(p, Part::Point | Part::Exp(_)))
+    });
+    if let Some(slash) = slash {
+        return format_fraction(v, parts, slash, locale);
+    }
+
+    let percents = parts.iter().filter(|p| **p == Part::Percent).count() as i32;
+    let scales = parts.iter().filter(|p| **p == Part::Scale).count() as i32;
+    let v = v * 100f64.powi(percents) / 1000f64.powi(scales);
+
+    let exp_at = parts.iter().position(|p| matches!(p, Part::Exp(_)));
+    let mantissa = &parts[..exp_at.unwrap_or(parts.len())];
+    let point_at = mantissa.iter().position(|p| *p == Part::Point);
+    let int_ph = placeholders(&mantissa[..point_at.unwrap_or(mantissa.len())]);
+    let frac_ph = placeholders(&mantissa[point_at.map_or(mantissa.len(), |p| p + 1)..]);
+    let exp_ph = placeholders(&parts[exp_at.map_or(parts.len(), |e| e + 1)..]);
+
+    let (mut value, mut exponent) = (v, 0i32);
+    if exp_at.is_some() && v != 0.0 {
+        let k = int_ph.len().max(1) as i32;
+        let engineering = k > 1 && int_ph.contains(&'#');
+        let e = v.log10().floor() as i32;
+        exponent = if engineering {
+            e.div_euclid(k) * k
+        } else {
+            e - (k - 1)
+        };
+        value = v / 10f64.powi(exponent);
+        if round_to(value, frac_ph.len()) >= 10f64.powi(k) {
+            exponent += if engineering { k } else { 1 };
+            value = v / 10f64.powi(exponent);
+        }
+    }
+
+    let rounded = format!("{:.*}", frac_ph.len(), round_to(value, frac_ph.len()));
+    let (int_digits, frac_digits) = rounded.split_once('.').unwrap_or((&rounded, ""));
+    let int_digits = if int_digits == "0" { "" } else { int_digits };
+    let (mut int_slots, overflow) = fill_integer(int_digits, &int_ph);
+    if parts.contains(&Part::Group) && !int_slots.is_empty() {
+        let joined = group_thousands(&int_slots.concat(), locale.thousands);
+        int_slots.iter_mut().for_each(String::clear);
+        int_slots[0] = joined;
+    }
+    let mut frac_slots: Vec<String> = frac_digits.chars().map(String::from).collect();
+    for (slot, p) in frac_slots.iter_mut().zip(&frac_ph).rev() {
+        if slot != "0" || *p == '0' {
+            break;
+        }
+        *slot = if *p == '?' { " ".into() } else { String::new() };
+    }
+    let exp_digits = exponent.abs().to_string();
+    let (exp_slots, _) = fill_integer(&exp_digits, &exp_ph);
+
+    let mut out = String::new();
+    let (mut int_i, mut frac_i, mut exp_i) = (0, 0, 0);
+    let mut zone = 0; // 0 integer, 1 fraction, 2 exponent
+    for part in parts {
+        match part {
+            Part::Digit(_) => {
+                let (slots, i) = match zone {
+                    0 => (&int_slots, &mut int_i),
+                    1 => (&frac_slots, &mut frac_i),
+                    _ => (&exp_slots, &mut exp_i),
+                };
+                if let Some(s) = slots.get(*i) {
+                    out.push_str(s);
+                }
+                *i += 1;
+            }
+            Part::Point => {
+                out.push_str(&overflow);
+                out.push(locale.decimal);
+                zone = 1;
+            }
+            Part::Exp(plus) => {
+                out.push('E');
+                if exponent < 0 {
+                    out.push('-');
+                } else if *plus {
+                    out.push('+');
+                }
+                zone = 2;
+            }
+            Part::Percent => out.push('%'),
+            Part::Literal(s) => out.push_str(s),
+            Part::General => out.push_str(&general(v, locale)),
+            Part::Group | Part::Scale => {}
+        }
+    }
+    out
+}
+
+/// The fraction `n/d` (d ≤ `max_den`) closest to `x`.
+fn best_fraction(x: f64, max_den: u64) -> (u64, u64) {
+    let mut best = (x.round() as u64, 1);
+    let mut best_err = (x - best.0 as f64).abs();
+    for d in 2..=max_den.max(1) {
+        let n = (x * d as f64).round();
+        let err = (x - n / d as f64).abs();
+        if err < best_err - 1e-12 {
+            best = (n as u64, d);
+            best_err = err;
+        }
+    }
+    best
+}
+
+/// Render `# ?/?`-style sections; `slash` indexes the `/` literal.
+fn format_fraction(v: f64, parts: &[Part], slash: usize, locale: &Locale) -> String {
+    let num_start = parts[..slash]
+        .iter()
+        .rposition(|p| !matches!(p, Part::Digit(_)))
+        .map_or(0, |i| i + 1);
+    let whole_ph = placeholders(&parts[..num_start]);
+    let num_ph = placeholders(&parts[num_start..slash]);
+    // Denominator: placeholders, or literal digits for a fixed one.
+    let den_len = parts[slash + 1..]
+        .iter()
+        .take_while(|p| match p {
+            Part::Digit(_) => true,
+            Part::Literal(s) => s.chars().all(|c| c.is_ascii_digit()),
+            _ => false,
+        })
+        .count();
+    let den_text: String = parts[slash + 1..slash + 1 + den_len]
+        .iter()
+        .map(|p| match p {
+            Part::Digit(c) => c.to_string(),
+            Part::Literal(s) => s.clone(),
+            _ => String::new(),
+        })
+        .collect();
+    let fixed = den_text
+        .parse::<u64>()
+        .ok()
+        .filter(|d| *d > 0 && !den_text.starts_with('0'));
+
+    let has_whole = !whole_ph.is_empty();
+    let (mut whole, frac) = if has_whole {
+        (v.trunc() as u64, v.fract())
+    } else {
+        (0, v)
+    };
+    let (mut num, den) = match fixed {
+        Some(d) => ((frac * d as f64).round() as u64, d),
+        None => best_fraction(frac, 10u64.pow(den_text.len().min(4) as u32) - 1),
+    };
+    if has_whole && num == den {
+        whole += 1;
+        num = 0;
+    }
+    let blank = has_whole && num == 0;
+
+    let whole_digits = if whole == 0 {
+        String::new()
+    } else {
+        whole.to_string()
+    };
+    let (mut whole_slots, _) = fill_integer(&whole_digits, &whole_ph);
+    if parts[..num_start].contains(&Part::Group) && !whole_slots.is_empty() {
+        let joined = group_thousands(&whole_slots.concat(), locale.thousands);
+        whole_slots.iter_mut().for_each(String::clear);
+        whole_slots[0] = joined;
+    }
+    if has_whole && whole == 0 && blank {
+        // Zero shows as `0`, not as an empty cell.
+        if let Some(last) = whole_slots.last_mut() {
+            *last = "0".to_string();
+        }
+    }
+    let (num_slots, _) = fill_integer(&num.to_string(), &num_ph);
+    let width = den_text.len().max(den.to_string().len());
+    let mut den_str = if fixed.is_some() {
+        den_text.clone()
+    } else {
+        den.to_string()
+    };
+    while den_str.len() < width {
+        den_str.push(' ');
+    }
+
+    let mut out = String::new();
+    let (mut whole_i, mut num_i) = (0, 0);
+    for (i, part) in parts.iter().enumerate() {
+        if i == slash {
+            out.push(if blank { ' ' } else { '/' });
+        } else if i == slash + 1 && den_len > 0 {
+            if blank {
+                out.push_str(&" ".repeat(den_str.len()));
+            } else {
+                out.push_str(&den_str);
+            }
+        } else if i > slash && i <= slash + den_len {
+            continue;
+        } else if let Part::Digit(_) = part {
+            if i < num_start {
+                out.push_str(whole_slots.get(whole_i).map_or("", String::as_str));
+                whole_i += 1;
+            } else {
+                let slot = num_slots.get(num_i).map_or("", String::as_str);
+                if blank {
+                    out.push_str(&" ".repeat(slot.len()));
+                } else {
+                    out.push_str(slot);
+                }
+                num_i += 1;
+            }
+        } else {
+            match part {
+                Part::Literal(s) => out.push_str(s),
+                Part::Percent => out.push('%'),
+                _ => {}
+            }
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Dates and times
+// ---------------------------------------------------------------------------
+
+/// Is the `m`/`mm` token at `i` minutes (next to hours or seconds)?
+fn is_minute(tokens: &[Token], i: usize) -> bool {
+    let first = |t: &Token| match t {
+        Token::DateTime(s) | Token::Elapsed(s) => s.chars().next(),
+        _ => None,
+    };
+    let prev = tokens[..i].iter().rev().find_map(first);
+    let next = tokens[i + 1..].iter().find_map(first);
+    prev == Some('h') || next == Some('s')
+}
+
+fn format_date(serial: f64, tokens: &[Token], locale: &Locale) -> String {
+    let has_ampm = tokens
+        .iter()
+        .any(|t| matches!(t, Token::DateTime(s) if s == "am/pm" || s == "a/p"));
+    // Fractional seconds: `ss.00`.
+    let sub_at = tokens.windows(2).position(|w| {
+        matches!(w, [Token::DateTime(s) | Token::Elapsed(s), Token::Numeric(n)]
+            if s.starts_with('s') && n.starts_with('.'))
+    });
+    let sub_digits = sub_at.map_or(0, |i| match &tokens[i + 1] {
+        Token::Numeric(n) => n.chars().filter(|c| *c == '0').count().min(3),
+        _ => 0,
+    });
+
+    let units = 10i64.pow(sub_digits as u32);
+    let total = (serial * 86_400.0 * units as f64).round() as i64;
+    let (days, in_day) = (
+        total.div_euclid(86_400 * units),
+        total.rem_euclid(86_400 * units),
+    );
+    let (secs, sub) = (in_day / units, in_day % units);
+    let (hour, minute, second) = (secs / 3600, secs / 60 % 60, secs % 60);
+    let elapsed_secs = days * 86_400 + secs;
+    let (year, month, day) = match days {
+        0 => (1900, 1, 0),
+        60 => (1900, 2, 29),
+        _ => match excel_serial_to_datetime(days as f64, false) {
+            Some(dt) => (dt.year(), dt.month(), dt.day()),
+            None => return OUT_OF_RANGE.to_string(),
+        },
+    };
+    // Excel's calendar makes serial 1 a Sunday.
+    let weekday = ((days + 6) % 7) as usize;
+    let month_name = MONTHS[(month as usize).saturating_sub(1) % 12];
+
+    let mut out = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::DateTime(s) => {
+                let n = s.len();
+                match s.chars().next() {
+                    _ if s == "am/pm" => out.push_str(if hour < 12 { "AM" } else { "PM" }),
+                    _ if s == "a/p" => out.push(if hour < 12 { 'A' } else { 'P' }),
+                    Some('y') if n <= 2 => {
+                        let _ = write!(out, "{:02}", year % 100);
+                    }
+                    Some('y') => {
+                        let _ = write!(out, "{year:04}");
+                    }
+                    Some('m') if n <= 2 && is_minute(tokens, i) => {
+                        let _ = write!(out, "{minute:0n$}");
+                    }
+                    Some('m') => match n {
+                        1 | 2 => {
+                            let _ = write!(out, "{month:0n$}");
+                        }
+                        3 => out.push_str(&month_name[..3]),
+                        5 => out.push_str(&month_name[..1]),
+                        _ => out.push_str(month_name),
+                    },
+                    Some('d') => match n {
+                        1 | 2 => {
+                            let _ = write!(out, "{day:0n$}");
+                        }
+                        3 => out.push_str(&DAYS[weekday][..3]),
+                        _ => out.push_str(DAYS[weekday]),
+                    },
+                    Some('h') => {
+                        let h = if has_ampm { (hour + 11) % 12 + 1 } else { hour };
+                        let _ = write!(out, "{h:0w$}", w = n.min(2));
+                    }
+                    Some('s') => {
+                        let _ = write!(out, "{second:0w$}", w = n.min(2));
+                    }
+                    _ => {}
+                }
+            }
+            Token::Elapsed(s) => {
+                let n = match s.chars().next() {
+                    Some('h') => elapsed_secs / 3600,
+                    Some('m') => elapsed_secs / 60,
+                    _ => elapsed_secs,
+                };
+                let _ = write!(out, "{n:0w$}", w = s.len());
+            }
+            Token::Numeric(n) if sub_at.is_some_and(|at| at + 1 == i) => {
+                out.push(locale.decimal);
+                if sub_digits > 0 {
+                    let _ = write!(out, "{sub:0sub_digits$}");
+                }
+                out.push_str(n.trim_start_matches('.').trim_start_matches('0'));
+            }
+            Token::Numeric(n) => out.push_str(n),
+            Token::Literal(s) => out.push_str(s),
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(v: f64, code: &str) -> String {
+        format_value(CellValue::Number(v), code, &Locale::default())
+    }
+
+    #[test]
+    fn test_accounting_sections() {
+        let code = "#,##0.00;[Red](#,##0.00)";
+        assert_eq!(fmt(1234.5, code), "1,234.50");
+        assert_eq!(fmt(-1234.5, code), "(1,234.50)");
+        assert_eq!(fmt(0.0, code), "0.00");
+        assert_eq!(fmt(-5.0, "0;-0;\"zero\""), "-5");
+        assert_eq!(fmt(0.0, "0;-0;\"zero\""), "zero");
+        assert_eq!(fmt(1234.5, "#,##0.00_);(#,##0.00)"), "1,234.50 ");
+        assert_eq!(fmt(-3.0, "0.0"), "-3.0");
+        assert_eq!(fmt(-0.001, "0.00"), "0.00");
+    }
+
+    #[test]
+    fn test_digits_percent_scaling_and_scientific() {
+        assert_eq!(fmt(1.005, "0.00"), "1.01");
+        assert_eq!(fmt(0.5, "#.##"), ".5");
+        assert_eq!(fmt(2.5, "0.0?"), "2.5 ");
+        assert_eq!(fmt(7.0, "000"), "007");
+        assert_eq!(fmt(0.125, "0.0%"), "12.5%");
+        assert_eq!(fmt(1234567.0, "#,##0,\"K\""), "1,235K");
+        assert_eq!(fmt(12345.678, "0.00E+00"), "1.23E+04");
+        assert_eq!(fmt(0.00012, "0.0E+0"), "1.2E-4");
+        assert_eq!(fmt(12345.0, "##0.0E+0"), "12.3E+3");
+        assert_eq!(fmt(1234.5, "[$€-407]#,##0.00"), "€1,234.50");
+        assert_eq!(fmt(150.0, "[>100]\"big\" 0;0"), "big 150");
+        assert_eq!(fmt(50.0, "[>100]\"big\" 0;0"), "50");
+    }
+
+    #[test]
+    fn test_general() {
+        let en = Locale::default();
+        assert_eq!(general(42.0, &en), "42");
+        assert_eq!(general(1.0 / 3.0, &en), "0.333333333");
+        assert_eq!(general(-123.456789012345, &en), "-123.456789");
+        assert_eq!(general(123456789012.0, &en), "1.23457E+11");
+        assert_eq!(general(0.00001, &en), "1E-05");
+        assert_eq!(fmt(0.1 + 0.2, "General"), "0.3");
+        assert_eq!(fmt(3.5, "\"Total: \"General"), "Total: 3.5");
+    }
+
+    #[test]
+    fn test_fractions() {
+        assert_eq!(fmt(1.25, "# ?/?"), "1 1/4");
+        assert_eq!(fmt(0.333, "?/??"), "1/3 ");
+        assert_eq!(fmt(2.0, "# ?/?"), "2    ");
+        assert_eq!(fmt(1.4, "# ?/8"), "1 3/8");
+    }
+
+    #[test]
+    fn test_dates_and_times() {
+        // 2024-03-05 14:07:09.25
+        let serial = 45356.0 + (14.0 * 3600.0 + 7.0 * 60.0 + 9.25) / 86400.0;
+        assert_eq!(fmt(serial, "yyyy-mm-dd hh:mm:ss"), "2024-03-05 14:07:09");
+        assert_eq!(fmt(serial, "m/d/yy h:mm AM/PM"), "3/5/24 2:07 PM");
+        assert_eq!(fmt(serial, "dddd, mmmm d"), "Tuesday, March 5");
+        assert_eq!(fmt(serial, "ddd mmm"), "Tue Mar");
+        assert_eq!(fmt(serial, "mm:ss.00"), "07:09.25");
+        assert_eq!(fmt(1.5, "[h]:mm"), "36:00");
+        assert_eq!(fmt(60.0, "yyyy-mm-dd"), "1900-02-29");
+        assert_eq!(fmt(-1.0, "yyyy-mm-dd"), OUT_OF_RANGE);
+        assert_eq!(fmt(serial_1900(0.0, true), "yyyy-mm-dd"), "1904-01-01");
+        let dt = chrono::NaiveDate::from_ymd_opt(2024, 3, 5)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        assert_eq!(datetime_to_serial(dt), 45356.5);
+    }
+
+    #[test]
+    fn test_text_bool_error_and_locale() {
+        let en = Locale::default();
+        assert_eq!(
+            format_value(CellValue::Text("abc"), "0;-0;0;\"<\"@\">\"", &en),
+            "<abc>"
+        );
+        assert_eq!(format_value(CellValue::Text("abc"), "0.00", &en), "abc");
+        assert_eq!(format_value(CellValue::Bool(true), "0.00", &en), "TRUE");
+        assert_eq!(format_value(CellValue::Error("#N/A"), "0", &en), "#N/A");
+
+        let de = Locale::from_tag("de-DE").unwrap();
+        assert_eq!(
+            format_value(CellValue::Number(1234.5), "#,##0.00", &de),
+            "1.234,50"
+        );
+        assert_eq!(format_value(CellValue::Number(0.5), "General", &de), "0,5");
+        assert!(Locale::from_tag("xx").is_err());
+    }
+}
@@ -0,0 +1,348 @@
+//! Decryption of password-protected OOXML workbooks (MS-OFFCRYPTO).
+//!
+//! An encrypted xlsx/xlsb is an OLE compound document holding an
+//! `EncryptionInfo` stream (key derivation parameters) and an
+//! `EncryptedPackage` stream (the AES-encrypted zip). Both the Standard
+//! (Office 2007: AES-ECB, SHA-1) and Agile (Office 2010+: AES-CBC, SHA-1/2)
+//! schemes are handled; legacy RC4 CryptoAPI encryption is rejected.
+
+use std::io::{Cursor, Read};
+
+use aes::cipher::{BlockDecrypt, KeyInit};
+use aes::{Aes128, Aes192, Aes256, Block};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use quick_xml::events::Event;
+use quick_xml::Reader as XmlReader;
+use sha2::Digest;
+
+use crate::ooxml_util::attr_value;
+
+const OLE_MAGIC: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// Agile block keys (MS-OFFCRYPTO 2.3.4.13).
+const BLOCK_VERIFIER_INPUT: [u8; 8] = [0xfe, 0xa7, 0xd2, 0x76, 0x3b, 0x4b, 0x9e, 0x79];
+const BLOCK_VERIFIER_VALUE: [u8; 8] = [0xd7, 0xaa, 0x0f, 0x6d, 0x30, 0x61, 0x34, 0x4e];
+const BLOCK_KEY_VALUE: [u8; 8] = [0x14, 0x6e, 0x0b, 0xe7, 0xab, 0xac, 0xd0, 0xd6];
+
+/// `fAES` in the EncryptionInfo flags (MS-OFFCRYPTO 2.3.1).
+const FLAG_AES: u32 = 0x20;
+
+/// Agile packages are encrypted in independent 4096-byte segments.
+const SEGMENT_LEN: usize = 4096;
+
+/// True if `bytes` is an OLE container carrying an encrypted OOXML package.
+pub(crate) fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(&OLE_MAGIC)
+        && cfb::CompoundFile::open(Cursor::new(bytes))
+            .map(|c| c.exists("/EncryptionInfo") && c.exists("/EncryptedPackage"))
+            .unwrap_or(false)
+}
+
+/// Decrypt an encrypted OOXML container, returning the plain zip bytes.
+pub(crate) fn decrypt(bytes: &[u8], password: &str) -> Result<Vec<u8>, String> {
+    let mut comp = cfb::CompoundFile::open(Cursor::new(bytes))
+        .map_err(|e| format!("Not an encrypted workbook: {e}"))?;
+    let info = read_stream(&mut comp, "/EncryptionInfo")?;
+    let package = read_stream(&mut comp, "/EncryptedPackage")?;
+    if info.len() < 8 || package.len() < 8 {
+        return Err("Truncated encryption streams".to_string());
+    }
+
+    let major = u16::from_le_bytes([info[0], info[1]]);
+    let minor = u16::from_le_bytes([info[2], info[3]]);
+    let flags = read_u32(&info, 4)?;
+    let password: Vec<u8> = password.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    match (major, minor) {
+        (4, 4) => decrypt_agile(&info[8..], &package, &password),
+        // CryptoAPI headers without fAES describe RC4, not Standard AES.
+        (2..=4, 2) if flags & FLAG_AES == 0 => Err("Unsupported RC4 encryption".to_string()),
+        (2..=4, 2) => decrypt_standard(&info[8..], &package, &password),
+        _ => Err(format!("Unsupported encryption version {major}.{minor}")),
+    }
+}
+
+fn read_stream(comp: &mut cfb::CompoundFile<Cursor<&[u8]>>, name: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    comp.open_stream(name)
+        .and_then(|mut s| s.read_to_end(&mut out))
+        .map_err(|e| format!("Failed to read {name}: {e}"))?;
+    Ok(out)
+}
+
+fn read_u32(buf: &[u8], at: usize) -> Result<u32, String> {
+    buf.get(at..at + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "Truncated EncryptionInfo".to_string())
+}
+
+fn package_size(package: &[u8]) -> usize {
+    let mut size = [0u8; 8];
+    size.copy_from_slice(&package[..8]);
+    u64::from_le_bytes(size) as usize
+}
+
+// =========================================================================
+// Primitives
+// =========================================================================
+
+#[derive(Clone, Copy)]
+enum HashAlg {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlg {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_uppercase().replace('-', "").as_str() {
+            "SHA1" => Ok(HashAlg::Sha1),
+            "SHA256" => Ok(HashAlg::Sha256),
+            "SHA384" => Ok(HashAlg::Sha384),
+            "SHA512" => Ok(HashAlg::Sha512),
+            other => Err(format!("Unsupported hash algorithm: {other}")),
+        }
+    }
+
+    fn digest(self, parts: &[&[u8]]) -> Vec<u8> {
+        fn run<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+            let mut h = D::new();
+            for p in parts {
+                h.update(p);
+            }
+            h.finalize().to_vec()
+        }
+        match self {
+            HashAlg::Sha1 => run::<sha1::Sha1>(parts),
+            HashAlg::Sha256 => run::<sha2::Sha256>(parts),
+            HashAlg::Sha384 => run::<sha2::Sha384>(parts),
+            HashAlg::Sha512 => run::<sha2::Sha512>(parts),
+        }
+    }
+
+    /// H0 = H(salt + password), then `spin` rounds of H(iterator + H).
+    fn spin(self, salt: &[u8], password: &[u8], spin: u32) -> Vec<u8> {
+        let mut h = self.digest(&[salt, password]);
+        for i in 0..spin {
+            h = self.digest(&[&i.to_le_bytes()[..], &h[..]]);
+        }
+        h
+    }
+}
+
+enum AesKey {
+    A128(Aes128),
+    A192(Aes192),
+    A256(Aes256),
+}
+
+impl AesKey {
+    fn new(key: &[u8]) -> Result<Self, String> {
+        let bad = |_| format!("Invalid AES key length: {}", key.len());
+        match key.len() {
+            16 => Aes128::new_from_slice(key).map(AesKey::A128).map_err(bad),
+            24 => Aes192::new_from_slice(key).map(AesKey::A192).map_err(bad),
+            32 => Aes256::new_from_slice(key).map(AesKey::A256).map_err(bad),
+            n => Err(format!("Invalid AES key length: {n}")),
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut Block) {
+        match self {
+            AesKey::A128(c) => c.decrypt_block(block),
+            AesKey::A192(c) => c.decrypt_block(block),
+            AesKey::A256(c) => c.decrypt_block(block),
+        }
+    }
+
+    fn decrypt_ecb(&self, data: &[u8]) -> Vec<u8> {
+        let mut out = data[..data.len() / 16 * 16].to_vec();
+        for chunk in out.chunks_exact_mut(16) {
+            self.decrypt_block(Block::from_mut_slice(chunk));
+        }
+        out
+    }
+
+    fn decrypt_cbc(&self, iv: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut out = data[..data.len() / 16 * 16].to_vec();
+        let mut prev = [0u8; 16];
+        prev.copy_from_slice(&iv[..16]);
+        for chunk in out.chunks_exact_mut(16) {
+            let mut ciphertext = [0u8; 16];
+            ciphertext.copy_from_slice(chunk);
+            self.decrypt_block(Block::from_mut_slice(chunk));
+            for (b, p) in chunk.iter_mut().zip(prev.iter()) {
+                *b ^= p;
+            }
+            prev = ciphertext;
+        }
+        out
+    }
+}
+
+// =========================================================================
+// Standard encryption (ECMA-376 2.3.4.5)
+// =========================================================================
+
+fn standard_key(password: &[u8], salt: &[u8], key_len: usize) -> Vec<u8> {
+    let h = HashAlg::Sha1.spin(salt, password, 50_000);
+    let h_final = HashAlg::Sha1.digest(&[&h[..], &0u32.to_le_bytes()[..]]);
+
+    let mut inner = [0x36u8; 64];
+    let mut outer = [0x5cu8; 64];
+    for (i, b) in h_final.iter().enumerate() {
+        inner[i] ^= b;
+        outer[i] ^= b;
+    }
+    let mut key = HashAlg::Sha1.digest(&[&inner[..]]);
+    key.extend(HashAlg::Sha1.digest(&[&outer[..]]));
+    key.truncate(key_len);
+    key
+}
+
+fn decrypt_standard(info: &[u8], package: &[u8], password: &[u8]) -> Result<Vec<u8>, String> {
+    let truncated = || "Truncated EncryptionInfo".to_string();
+
+    let header_size = read_u32(info, 0)? as usize;
+    let header = info.get(4..4 + header_size).ok_or_else(truncated)?;
+    let key_bits = read_u32(header, 16)? as usize;
+
+    let verifier = info.get(4 + header_size..).ok_or_else(truncated)?;
+    let salt_size = read_u32(verifier, 0)? as usize;
+    let salt = verifier.get(4..4 + salt_size).ok_or_else(truncated)?;
+    let enc_verifier = verifier
+        .get(4 + salt_size..20 + salt_size)
+        .ok_or_else(truncated)?;
+    let hash_size = read_u32(verifier, 20 + salt_size)? as usize;
+    let enc_hash = verifier
+        .get(24 + salt_size..56 + salt_size)
+        .ok_or_else(truncated)?;
+
+    let cipher = AesKey::new(&standard_key(password, salt, key_bits / 8))?;
+    let plain_verifier = cipher.decrypt_ecb(enc_verifier);
+    let plain_hash = cipher.decrypt_ecb(enc_hash);
+    if HashAlg::Sha1.digest(&[&plain_verifier[..]]) != plain_hash[..hash_size.min(plain_hash.len())]
+    {
+        return Err("Incorrect password".to_string());
+    }
+
+    let mut out = cipher.decrypt_ecb(&package[8..]);
+    out.truncate(package_size(package));
+    Ok(out)
+}
+
+// =========================================================================
+// Agile encryption (MS-OFFCRYPTO 2.3.4.10)
+// =========================================================================
+
+#[derive(Default)]
+struct AgileParams {
+    salt: Vec<u8>,
+    block_size: usize,
+    key_bits: usize,
+    hash: Option<String>,
+}
+
+#[derive(Default)]
+struct AgileInfo {
+    key_data: AgileParams,
+    password_key: AgileParams,
+    spin_count: u32,
+    verifier_input: Vec<u8>,
+    verifier_value: Vec<u8>,
+    key_value: Vec<u8>,
+}
+
+fn parse_agile_info(xml: &str) -> Result<AgileInfo, String> {
+    let b64 = |v: Option<String>| -> Result<Vec<u8>, String> {
+        BASE64
+            .decode(v.unwrap_or_default())
+            .map_err(|e| format!("Invalid EncryptionInfo: {e}"))
+    };
+    let num = |v: Option<String>| v.and_then(|s| s.parse::<usize>().ok()).unwrap_or(0);
+
+    let mut reader = XmlReader::from_str(xml);
+    let mut buf: Vec<u8> = Vec::new();
+    let mut info = AgileInfo::default();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                b"keyData" => {
+                    info.key_data = AgileParams {
+                        salt: b64(attr_value(&e, b"saltValue"))?,
+                        block_size: num(attr_value(&e, b"blockSize")),
+                        key_bits: num(attr_value(&e, b"keyBits")),
+                        hash: attr_value(&e, b"hashAlgorithm"),
+                    };
+                }
+                b"encryptedKey" => {
+                    info.password_key = AgileParams {
+                        salt: b64(attr_value(&e, b"saltValue"))?,
+                        block_size: num(attr_value(&e, b"blockSize")),
+                        key_bits: num(attr_value(&e, b"keyBits")),
+                        hash: attr_value(&e, b"hashAlgorithm"),
+                    };
+                    info.spin_count = num(attr_value(&e, b"spinCount")) as u32;
+                    info.verifier_input = b64(attr_value(&e, b"encryptedVerifierHashInput"))?;
+                    info.verifier_value = b64(attr_value(&e, b"encryptedVerifierHashValue"))?;
+                    info.key_value = b64(attr_value(&e, b"encryptedKeyValue"))?;
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Invalid EncryptionInfo XML: {e}")),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    if info.key_value.is_empty() || info.key_data.salt.is_empty() {
+        return Err("EncryptionInfo has no password key encryptor".to_string());
+    }
+    Ok(info)
+}
+
+/// Resize a hash to `len` bytes, padding with 0x36 as the spec requires.
+fn fit(mut bytes: Vec<u8>, len: usize) -> Vec<u8> {
+    bytes.resize(len, 0x36);
+    bytes
+}
+
+fn decrypt_agile(info: &[u8], package: &[u8], password: &[u8]) -> Result<Vec<u8>, String> {
+    let xml = String::from_utf8_lossy(info);
+    let info = parse_agile_info(xml.trim_start_matches('\u{feff}'))?;
+
+    let pk = &info.password_key;
+    let hash = HashAlg::parse(pk.hash.as_deref().unwrap_or("SHA1"))?;
+    let key_len = pk.key_bits / 8;
+    let iv = fit(pk.salt.clone(), pk.block_size.max(16));
+    let h = hash.spin(&pk.salt, password, info.spin_count);
+    let derive = |block_key: &[u8]| fit(hash.digest(&[&h[..], block_key]), key_len);
+
+    let verifier_input =
+        AesKey::new(&derive(&BLOCK_VERIFIER_INPUT))?.decrypt_cbc(&iv, &info.verifier_input);
+    let verifier_hash =
+        AesKey::new(&derive(&BLOCK_VERIFIER_VALUE))?.decrypt_cbc(&iv, &info.verifier_value);
+    let expected = hash.digest(&[&verifier_input[..pk.salt.len().min(verifier_input.len())]]);
+    if verifier_hash.get(..expected.len()) != Some(&expected[..]) {
+        return Err("Incorrect password".to_string());
+    }
+
+    let mut secret = AesKey::new(&derive(&BLOCK_KEY_VALUE))?.decrypt_cbc(&iv, &info.key_value);
+    secret.truncate(key_len);
+    let cipher = AesKey::new(&secret)?;
+
+    let kd = &info.key_data;
+    let kd_hash = HashAlg::parse(kd.hash.as_deref().unwrap_or("SHA1"))?;
+    let mut out = Vec::with_capacity(package.len());
+    for (i, segment) in package[8..].chunks(SEGMENT_LEN).enumerate() {
+        let seg_iv = fit(
+            kd_hash.digest(&[&kd.salt[..], &(i as u32).to_le_bytes()[..]]),
+            kd.block_size.max(16),
+        );
+        out.extend(cipher.decrypt_cbc(&seg_iv, segment));
+    }
+    out.truncate(package_size(package));
+    Ok(out)
+}
@@ -1,13 +1,40 @@
 use pyo3::exceptions::PyIOError;
 use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
 
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Seek, Write};
 
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader as XmlReader;
-use zip::ZipArchive;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+#[cfg(feature = "umya")]
+pub mod auto_filter;
+pub mod calc_pr;
+#[cfg(feature = "umya")]
+pub mod data_validations;
+#[cfg(feature = "calamine")]
+pub mod defined_names;
+#[cfg(any(feature = "calamine", feature = "wolfxl"))]
+pub mod drawings;
+#[allow(dead_code)] // Readers use read_fills, writers replace_fills
+pub mod fills;
+#[allow(dead_code)] // Sheet ids and visibility state are parsed ahead of their consumers
+pub mod parts;
+#[cfg(feature = "calamine")]
+pub mod repair;
+#[cfg(any(feature = "rust_xlsxwriter", feature = "umya"))]
+pub mod sheet_format;
+#[cfg(feature = "calamine")]
+pub mod sheet_stats;
+#[cfg(feature = "calamine")]
+pub mod sheet_view;
+#[allow(dead_code)] // Only the calamine reader reports table styles and filters
+pub mod tables;
+pub mod validate;
 
 pub fn normalize_zip_path(path: &str) -> String {
     let mut stack: Vec<&str> = Vec::new();
@@ -46,27 +73,322 @@ pub fn attr_value(e: &BytesStart<'_>, key: &[u8]) -> Option<String> {
     None
 }
 
-pub fn parse_workbook_sheet_rids(xml: &str) -> PyResult<Vec<(String, String)>> {
+/// Sheet name → worksheet zip path (`xl/worksheets/sheet1.xml`), in workbook order.
+pub fn sheet_part_paths(workbook_xml: &str, rels_xml: &str) -> PyResult<Vec<(String, String)>> {
+    parts::sheet_part_paths(workbook_xml, rels_xml).map_err(PyErr::new::<PyIOError, _>)
+}
+
+/// Read `date1904` from `<workbookPr>` in workbook.xml.
+pub fn workbook_is_date1904(workbook_xml: &str) -> bool {
+    let mut reader = XmlReader::from_str(workbook_xml);
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e))
+                if e.local_name().as_ref() == b"workbookPr" =>
+            {
+                return matches!(
+                    attr_value(&e, b"date1904").as_deref(),
+                    Some("1") | Some("true")
+                );
+            }
+            // workbookPr precedes <sheets>; stop once we're past it.
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"sheets" => return false,
+            Ok(Event::Eof) | Err(_) => return false,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Relationship id → `Target` for a `.rels` part.
+pub fn parse_relationship_targets(xml: &str) -> PyResult<HashMap<String, String>> {
+    Ok(parts::parse_relationships(xml)
+        .map_err(PyErr::new::<PyIOError, _>)?
+        .into_iter()
+        .map(|r| (r.id, r.target))
+        .collect())
+}
+
+pub fn zip_read_to_string<R: Read + Seek>(zip: &mut ZipArchive<R>, name: &str) -> PyResult<String> {
+    let mut f = zip
+        .by_name(name)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Missing zip entry {name}: {e}")))?;
+    let mut out = String::new();
+    f.read_to_string(&mut out)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read {name}: {e}")))?;
+    Ok(out)
+}
+
+pub fn zip_read_to_string_opt<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    name: &str,
+) -> PyResult<Option<String>> {
+    match zip.by_name(name) {
+        Ok(mut f) => {
+            let mut out = String::new();
+            f.read_to_string(&mut out)
+                .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read {name}: {e}")))?;
+            Ok(Some(out))
+        }
+        Err(zip::result::ZipError::FileNotFound) => Ok(None),
+        Err(e) => Err(PyErr::new::<PyIOError, _>(format!(
+            "Zip error reading {name}: {e}"
+        ))),
+    }
+}
+
+/// Path of a part's relationships file (`xl/worksheets/_rels/sheet1.xml.rels`).
+pub fn part_rels_path(part_path: &str) -> String {
+    match part_path.rfind('/') {
+        Some(i) => format!("{}_rels/{}.rels", &part_path[..i + 1], &part_path[i + 1..]),
+        None => format!("_rels/{part_path}.rels"),
+    }
+}
+
+/// First relationship `Target` whose `Type` ends with `/{type_suffix}`.
+pub fn relationship_target_by_type(xml: &str, type_suffix: &str) -> PyResult<Option<String>> {
+    Ok(parts::parse_relationships(xml)
+        .map_err(PyErr::new::<PyIOError, _>)?
+        .into_iter()
+        .find(|r| r.is_type(type_suffix))
+        .map(|r| r.target))
+}
+
+/// Rewrite a saved xlsx in place, replacing the entries in `file_patches`.
+pub fn rewrite_zip_entries(path: &str, file_patches: &HashMap<String, Vec<u8>>) -> PyResult<()> {
+    let src = File::open(path)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to open '{path}': {e}")))?;
+    let mut zip = ZipArchive::new(src)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read xlsx zip: {e}")))?;
+
+    let tmp_path = format!("{path}.tmp");
+    let dst = File::create(&tmp_path)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to create '{tmp_path}': {e}")))?;
+    let mut out = ZipWriter::new(dst);
+
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i).map_err(|e| {
+            PyErr::new::<PyIOError, _>(format!("Failed to read zip entry {i}: {e}"))
+        })?;
+        let name = file.name().to_string();
+
+        let mut opts = SimpleFileOptions::default().compression_method(file.compression());
+        if let Some(dt) = file.last_modified() {
+            opts = opts.last_modified_time(dt);
+        }
+        if let Some(mode) = file.unix_mode() {
+            opts = opts.unix_permissions(mode);
+        }
+
+        if file.is_dir() {
+            out.add_directory(name, opts).map_err(|e| {
+                PyErr::new::<PyIOError, _>(format!("Failed to add directory to zip: {e}"))
+            })?;
+            continue;
+        }
+
+        let mut data: Vec<u8> = Vec::new();
+        file.read_to_end(&mut data)
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read zip entry: {e}")))?;
+        if let Some(patched) = file_patches.get(&name) {
+            data = patched.clone();
+        }
+
+        out.start_file(name, opts)
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to write zip entry: {e}")))?;
+        out.write_all(&data)
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to write zip entry: {e}")))?;
+    }
+
+    out.finish()
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to finalize zip: {e}")))?;
+
+    if let Err(e) = std::fs::rename(&tmp_path, path) {
+        // On some platforms rename() may not replace; retry with explicit remove.
+        let _ = std::fs::remove_file(path);
+        std::fs::rename(&tmp_path, path).map_err(|e2| {
+            PyErr::new::<PyIOError, _>(format!("Failed to replace file: {e}; {e2}"))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Edits to the parts of a saved xlsx, collected in memory and written back
+/// with a single `rewrite_zip_entries` pass.
+pub struct PackagePatches {
+    path: String,
+    zip: ZipArchive<File>,
+    parts: HashMap<String, String>,
+}
+
+impl PackagePatches {
+    pub fn open(path: &str) -> PyResult<Self> {
+        let f = File::open(path)
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to open '{path}': {e}")))?;
+        let zip = ZipArchive::new(f)
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read xlsx zip: {e}")))?;
+        Ok(Self {
+            path: path.to_string(),
+            zip,
+            parts: HashMap::new(),
+        })
+    }
+
+    /// The part `name`, with the edits collected so far.
+    pub fn read(&mut self, name: &str) -> PyResult<String> {
+        match self.parts.get(name) {
+            Some(xml) => Ok(xml.clone()),
+            None => zip_read_to_string(&mut self.zip, name),
+        }
+    }
+
+    pub fn set(&mut self, name: &str, xml: String) {
+        self.parts.insert(name.to_string(), xml);
+    }
+
+    /// Entry names of the saved package.
+    pub fn names(&self) -> Vec<String> {
+        self.zip.file_names().map(str::to_string).collect()
+    }
+
+    /// Worksheet part of each sheet, by sheet name.
+    pub fn sheet_paths(&mut self) -> PyResult<HashMap<String, String>> {
+        let workbook_xml = self.read("xl/workbook.xml")?;
+        let rels_xml = self.read("xl/_rels/workbook.xml.rels")?;
+        Ok(sheet_part_paths(&workbook_xml, &rels_xml)?
+            .into_iter()
+            .collect())
+    }
+
+    /// Apply `<calcPr>` attribute edits to workbook.xml.
+    pub fn calc_pr(&mut self, edits: &[calc_pr::CalcPrEdit]) -> PyResult<()> {
+        if edits.is_empty() {
+            return Ok(());
+        }
+        let workbook_xml = self.read("xl/workbook.xml")?;
+        let patched =
+            calc_pr::patch_calc_pr(&workbook_xml, edits).map_err(PyErr::new::<PyIOError, _>)?;
+        self.set("xl/workbook.xml", patched);
+        Ok(())
+    }
+
+    /// Apply `<sheetFormatPr>` attribute edits to worksheets, by sheet name.
+    /// Sheets the workbook doesn't have are skipped.
+    #[cfg(any(feature = "rust_xlsxwriter", feature = "umya"))]
+    pub fn sheet_format_pr(
+        &mut self,
+        sheet_edits: &[(String, Vec<sheet_format::SheetFormatEdit>)],
+    ) -> PyResult<()> {
+        if sheet_edits.is_empty() {
+            return Ok(());
+        }
+        let sheet_paths = self.sheet_paths()?;
+        for (sheet, edits) in sheet_edits {
+            let Some(part) = sheet_paths.get(sheet) else {
+                continue;
+            };
+            let xml = self.read(part)?;
+            let patched = sheet_format::patch_sheet_format_pr(&xml, edits)
+                .map_err(PyErr::new::<PyIOError, _>)?;
+            self.set(part, patched);
+        }
+        Ok(())
+    }
+
+    /// Write the collected edits back; without any the file is left as is.
+    pub fn finish(self) -> PyResult<()> {
+        let Self { path, zip, parts } = self;
+        drop(zip);
+        if parts.is_empty() {
+            return Ok(());
+        }
+        let file_patches: HashMap<String, Vec<u8>> = parts
+            .into_iter()
+            .map(|(name, xml)| (name, xml.into_bytes()))
+            .collect();
+        rewrite_zip_entries(&path, &file_patches)
+    }
+}
+
+// =========================================================================
+// Cell comments (legacy notes + threaded comments)
+// =========================================================================
+
+#[derive(Clone, Debug)]
+pub struct CommentInfo {
+    pub cell: String,
+    pub text: String,
+    pub author: String,
+    pub threaded: bool,
+}
+
+/// Parse a legacy `commentsN.xml` part.
+pub fn parse_comments_xml(xml: &str) -> PyResult<Vec<CommentInfo>> {
     let mut reader = XmlReader::from_str(xml);
     reader.config_mut().trim_text(true);
     let mut buf: Vec<u8> = Vec::new();
-    let mut out: Vec<(String, String)> = Vec::new();
+
+    let mut authors: Vec<String> = Vec::new();
+    let mut out: Vec<CommentInfo> = Vec::new();
+
+    let mut in_author = false;
+    let mut in_comment = false;
+    let mut in_t = false;
+
+    let mut cur_cell: String = String::new();
+    let mut cur_author_id: usize = 0;
+    let mut cur_text: String = String::new();
 
     loop {
         match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
-                if e.name().as_ref() == b"sheet" {
-                    let name = attr_value(&e, b"name");
-                    let rid = attr_value(&e, b"r:id");
-                    if let (Some(n), Some(r)) = (name, rid) {
-                        out.push((n, r));
-                    }
+            Ok(Event::Start(e)) => {
+                let name = e.name();
+                let name = name.as_ref();
+                if name == b"author" {
+                    in_author = true;
+                } else if name == b"comment" {
+                    in_comment = true;
+                    cur_text.clear();
+                    cur_cell = attr_value(&e, b"ref").unwrap_or_default();
+                    cur_author_id = attr_value(&e, b"authorId")
+                        .and_then(|s| s.parse::<usize>().ok())
+                        .unwrap_or(0);
+                } else if name == b"t" {
+                    in_t = true;
+                }
+            }
+            Ok(Event::End(e)) => {
+                let name = e.name();
+                let name = name.as_ref();
+                if name == b"author" {
+                    in_author = false;
+                } else if name == b"comment" {
+                    in_comment = false;
+                    let author = authors.get(cur_author_id).cloned().unwrap_or_default();
+                    out.push(CommentInfo {
+                        cell: cur_cell.clone(),
+                        text: cur_text.clone(),
+                        author,
+                        threaded: false,
+                    });
+                } else if name == b"t" {
+                    in_t = false;
+                }
+            }
+            Ok(Event::Text(e)) => {
+                let text = e.unescape().unwrap_or_default().to_string();
+                if in_author {
+                    authors.push(text);
+                } else if in_comment && in_t {
+                    cur_text.push_str(&text);
                 }
             }
             Ok(Event::Eof) => break,
             Err(e) => {
                 return Err(PyErr::new::<PyIOError, _>(format!(
-                    "Failed to parse workbook.xml: {e}"
+                    "Failed to parse comments XML: {e}"
                 )))
             }
             _ => {}
@@ -77,7 +399,8 @@ pub fn parse_workbook_sheet_rids(xml: &str) -> PyResult<Vec<(String, String)>> {
     Ok(out)
 }
 
-pub fn parse_relationship_targets(xml: &str) -> PyResult<HashMap<String, String>> {
+/// Map person ids to display names from `xl/persons/person.xml`.
+pub fn parse_persons_xml(xml: &str) -> PyResult<HashMap<String, String>> {
     let mut reader = XmlReader::from_str(xml);
     reader.config_mut().trim_text(true);
     let mut buf: Vec<u8> = Vec::new();
@@ -86,18 +409,18 @@ pub fn parse_relationship_targets(xml: &str) -> PyResult<HashMap<String, String>
     loop {
         match reader.read_event_into(&mut buf) {
             Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
-                if e.name().as_ref() == b"Relationship" {
-                    let id = attr_value(&e, b"Id");
-                    let target = attr_value(&e, b"Target");
-                    if let (Some(i), Some(t)) = (id, target) {
-                        out.insert(i, t);
+                if e.name().as_ref() == b"person" {
+                    if let (Some(id), Some(name)) =
+                        (attr_value(&e, b"id"), attr_value(&e, b"displayName"))
+                    {
+                        out.insert(id, name);
                     }
                 }
             }
             Ok(Event::Eof) => break,
             Err(e) => {
                 return Err(PyErr::new::<PyIOError, _>(format!(
-                    "Failed to parse rels: {e}"
+                    "Failed to parse persons XML: {e}"
                 )))
             }
             _ => {}
@@ -108,27 +431,243 @@ pub fn parse_relationship_targets(xml: &str) -> PyResult<HashMap<String, String>
     Ok(out)
 }
 
-pub fn zip_read_to_string(zip: &mut ZipArchive<File>, name: &str) -> PyResult<String> {
-    let mut f = zip
-        .by_name(name)
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Missing zip entry {name}: {e}")))?;
-    let mut out = String::new();
-    f.read_to_string(&mut out)
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read {name}: {e}")))?;
+/// Parse a `threadedCommentsN.xml` part.
+///
+/// Only thread roots are returned (replies carry a `parentId`); authors are
+/// resolved through `persons`, falling back to the raw person id.
+pub fn parse_threaded_comments_xml(
+    xml: &str,
+    persons: &HashMap<String, String>,
+) -> PyResult<Vec<CommentInfo>> {
+    let mut reader = XmlReader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut buf: Vec<u8> = Vec::new();
+    let mut out: Vec<CommentInfo> = Vec::new();
+
+    let mut current: Option<CommentInfo> = None;
+    let mut in_text = false;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => match e.name().as_ref() {
+                b"threadedComment" => {
+                    if attr_value(&e, b"parentId").is_none() {
+                        let person = attr_value(&e, b"personId").unwrap_or_default();
+                        current = Some(CommentInfo {
+                            cell: attr_value(&e, b"ref").unwrap_or_default(),
+                            text: String::new(),
+                            author: persons.get(&person).cloned().unwrap_or(person),
+                            threaded: true,
+                        });
+                    }
+                }
+                b"text" => in_text = true,
+                _ => {}
+            },
+            Ok(Event::End(e)) => match e.name().as_ref() {
+                b"threadedComment" => {
+                    if let Some(c) = current.take() {
+                        out.push(c);
+                    }
+                }
+                b"text" => in_text = false,
+                _ => {}
+            },
+            Ok(Event::Text(e)) => {
+                if let (true, Some(c)) = (in_text, current.as_mut()) {
+                    c.text.push_str(&e.unescape().unwrap_or_default());
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(PyErr::new::<PyIOError, _>(format!(
+                    "Failed to parse threaded comments XML: {e}"
+                )))
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+
     Ok(out)
 }
 
-pub fn zip_read_to_string_opt(zip: &mut ZipArchive<File>, name: &str) -> PyResult<Option<String>> {
-    match zip.by_name(name) {
-        Ok(mut f) => {
-            let mut out = String::new();
-            f.read_to_string(&mut out)
-                .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read {name}: {e}")))?;
-            Ok(Some(out))
+/// Read all comments attached to a worksheet part.
+///
+/// Threaded comments replace the legacy placeholder note Excel writes for the
+/// same cell; plain notes are returned as-is, in document order.
+pub fn read_sheet_comments<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    sheet_path: &str,
+) -> PyResult<Vec<CommentInfo>> {
+    let Some(rels_xml) = zip_read_to_string_opt(zip, &part_rels_path(sheet_path))? else {
+        return Ok(Vec::new());
+    };
+    let sheet_dir = match sheet_path.rfind('/') {
+        Some(i) => &sheet_path[..i + 1],
+        None => "",
+    };
+
+    let mut comments = match relationship_target_by_type(&rels_xml, "comments")? {
+        Some(target) => match zip_read_to_string_opt(zip, &join_and_normalize(sheet_dir, &target))?
+        {
+            Some(xml) => parse_comments_xml(&xml)?,
+            None => Vec::new(),
+        },
+        None => Vec::new(),
+    };
+
+    let threaded_xml = match relationship_target_by_type(&rels_xml, "threadedComment")? {
+        Some(target) => zip_read_to_string_opt(zip, &join_and_normalize(sheet_dir, &target))?,
+        None => None,
+    };
+    if let Some(xml) = threaded_xml {
+        let persons = match zip_read_to_string_opt(zip, "xl/_rels/workbook.xml.rels")? {
+            Some(wb_rels) => match relationship_target_by_type(&wb_rels, "person")? {
+                Some(target) => zip_read_to_string_opt(zip, &join_and_normalize("xl/", &target))?
+                    .map(|x| parse_persons_xml(&x))
+                    .transpose()?
+                    .unwrap_or_default(),
+                None => HashMap::new(),
+            },
+            None => HashMap::new(),
+        };
+
+        let mut threaded = parse_threaded_comments_xml(&xml, &persons)?;
+        for c in comments.iter_mut() {
+            if let Some(pos) = threaded.iter().position(|t| t.cell == c.cell) {
+                *c = threaded.remove(pos);
+            }
         }
-        Err(zip::result::ZipError::FileNotFound) => Ok(None),
-        Err(e) => Err(PyErr::new::<PyIOError, _>(format!(
-            "Zip error reading {name}: {e}"
-        ))),
+        comments.extend(threaded);
+    }
+
+    Ok(comments)
+}
+
+/// A worksheet picture with its blip resolved to a zip entry.
+#[cfg(any(feature = "calamine", feature = "wolfxl"))]
+pub struct SheetImage {
+    pub image: drawings::DrawingImage,
+    /// `xl/media/image1.png`; None for linked (external) images.
+    pub media: Option<String>,
+}
+
+/// Pictures on a worksheet part, from every drawing it references.
+#[cfg(any(feature = "calamine", feature = "wolfxl"))]
+pub fn read_sheet_images<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    sheet_path: &str,
+) -> PyResult<Vec<SheetImage>> {
+    let Some(rels_xml) = zip_read_to_string_opt(zip, &part_rels_path(sheet_path))? else {
+        return Ok(Vec::new());
+    };
+    let dir_of = |path: &str| match path.rfind('/') {
+        Some(i) => path[..i + 1].to_string(),
+        None => String::new(),
+    };
+    let sheet_dir = dir_of(sheet_path);
+
+    let mut out = Vec::new();
+    for rel in parts::parse_relationships(&rels_xml).map_err(PyErr::new::<PyIOError, _>)? {
+        if !rel.is_type("drawing") || rel.external {
+            continue;
+        }
+        let drawing_path = join_and_normalize(&sheet_dir, &rel.target);
+        let Some(xml) = zip_read_to_string_opt(zip, &drawing_path)? else {
+            continue;
+        };
+        let media_rels = match zip_read_to_string_opt(zip, &part_rels_path(&drawing_path))? {
+            Some(x) => parts::parse_relationships(&x).map_err(PyErr::new::<PyIOError, _>)?,
+            None => Vec::new(),
+        };
+        let drawing_dir = dir_of(&drawing_path);
+        for image in drawings::parse_drawing_images(&xml).map_err(PyErr::new::<PyIOError, _>)? {
+            let media = image
+                .embed
+                .as_deref()
+                .and_then(|id| media_rels.iter().find(|r| r.id == id && !r.external))
+                .map(|r| join_and_normalize(&drawing_dir, &r.target));
+            out.push(SheetImage { image, media });
+        }
+    }
+    Ok(out)
+}
+
+/// Table definitions of a worksheet part, in `<tableParts>` order.
+pub fn read_sheet_tables<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    sheet_path: &str,
+) -> PyResult<Vec<tables::TableDef>> {
+    let Some(rels_xml) = zip_read_to_string_opt(zip, &part_rels_path(sheet_path))? else {
+        return Ok(Vec::new());
+    };
+    let sheet_dir = match sheet_path.rfind('/') {
+        Some(i) => &sheet_path[..i + 1],
+        None => "",
+    };
+
+    let mut out = Vec::new();
+    for rel in parts::parse_relationships(&rels_xml).map_err(PyErr::new::<PyIOError, _>)? {
+        if !rel.is_type("table") || rel.external {
+            continue;
+        }
+        let Some(xml) = zip_read_to_string_opt(zip, &join_and_normalize(sheet_dir, &rel.target))?
+        else {
+            continue;
+        };
+        out.push(tables::parse_table_xml(&xml).map_err(PyErr::new::<PyIOError, _>)?);
+    }
+    Ok(out)
+}
+
+/// Comments as `list[dict]` with `cell`, `text`, `author` and `threaded` keys.
+pub fn comments_to_py(py: Python<'_>, comments: &[CommentInfo]) -> PyResult<PyObject> {
+    let result = PyList::empty(py);
+    for c in comments {
+        let d = PyDict::new(py);
+        d.set_item("cell", &c.cell)?;
+        d.set_item("text", &c.text)?;
+        d.set_item("author", &c.author)?;
+        d.set_item("threaded", c.threaded)?;
+        result.append(d)?;
+    }
+    Ok(result.into())
+}
+
+// =========================================================================
+// Package validation
+// =========================================================================
+
+/// Structurally validate an xlsx file without going through any backend.
+///
+/// Returns `{"valid", "errors", "warnings", "findings"}`; each finding is a
+/// dict with `severity` ("error" | "warning"), `code` (e.g.
+/// "rels.missing_target"), `part` and `message`. A file that isn't a ZIP is
+/// reported as a `zip.invalid` finding rather than raised.
+#[pyfunction]
+pub fn validate_xlsx(py: Python<'_>, path: &str) -> PyResult<PyObject> {
+    let f = std::fs::File::open(path)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Cannot open '{path}': {e}")))?;
+    let findings = py.allow_threads(|| validate::validate_bytes(std::io::BufReader::new(f)));
+
+    let errors = findings
+        .iter()
+        .filter(|f| f.severity == validate::Severity::Error)
+        .count();
+    let items = PyList::empty(py);
+    for f in &findings {
+        let d = PyDict::new(py);
+        d.set_item("severity", f.severity.as_str())?;
+        d.set_item("code", f.code)?;
+        d.set_item("part", &f.part)?;
+        d.set_item("message", &f.message)?;
+        items.append(d)?;
     }
+    let report = PyDict::new(py);
+    report.set_item("valid", errors == 0)?;
+    report.set_item("errors", errors)?;
+    report.set_item("warnings", findings.len() - errors)?;
+    report.set_item("findings", items)?;
+    Ok(report.into())
 }
@@ -0,0 +1,281 @@
+//! Worksheet auto filters (`<autoFilter>` in a worksheet part).
+//!
+//! umya keeps only the filter range, so the per-column criteria are read
+//! from the worksheet XML. Errors are plain strings; PyO3 callers wrap them.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader as XmlReader;
+
+use super::attr_value;
+
+/// The criteria of one `<filterColumn>`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterCriteria {
+    /// `<filters>`: show rows matching any listed value. Date groups are
+    /// rendered as `2024`, `2024-06`, `2024-06-15` and so on down to the
+    /// grouping level.
+    Values {
+        values: Vec<String>,
+        dates: Vec<String>,
+        blank: bool,
+    },
+    /// `<customFilters>`: one or two `(operator, value)` conditions.
+    Custom {
+        and: bool,
+        conditions: Vec<(String, String)>,
+    },
+    /// `<top10>`: top or bottom N items or percent.
+    Top10 {
+        top: bool,
+        percent: bool,
+        value: f64,
+    },
+    /// `<dynamicFilter>`, e.g. `aboveAverage` or `thisMonth`.
+    Dynamic { kind: String },
+    /// `<colorFilter>`: cell fill (or font color) from a differential format.
+    Color {
+        dxf_id: Option<u32>,
+        cell_color: bool,
+    },
+    /// `<iconFilter>`: a conditional-format icon.
+    Icon {
+        icon_set: String,
+        icon_id: Option<u32>,
+    },
+}
+
+/// One `<filterColumn>`; `col_id` is relative to the filter range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterColumn {
+    pub col_id: u32,
+    pub criteria: Option<FilterCriteria>,
+}
+
+/// A worksheet's `<autoFilter>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoFilterDef {
+    pub range: String,
+    pub columns: Vec<FilterColumn>,
+}
+
+fn flag(e: &BytesStart<'_>, key: &[u8], default: bool) -> bool {
+    match attr_value(e, key).as_deref() {
+        Some("1") | Some("true") => true,
+        Some("0") | Some("false") => false,
+        _ => default,
+    }
+}
+
+/// `<dateGroupItem>` as `year[-month[-day[ hh[:mm[:ss]]]]]`.
+fn date_group(e: &BytesStart<'_>) -> String {
+    let grouping = attr_value(e, b"dateTimeGrouping").unwrap_or_default();
+    let level = ["year", "month", "day", "hour", "minute", "second"]
+        .iter()
+        .position(|g| *g == grouping)
+        .unwrap_or(0);
+    let part = |key: &[u8]| attr_value(e, key).unwrap_or_default();
+    let mut out = part(b"year");
+    for (i, (sep, key)) in [
+        ("-", &b"month"[..]),
+        ("-", b"day"),
+        (" ", b"hour"),
+        (":", b"minute"),
+        (":", b"second"),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        if i >= level {
+            break;
+        }
+        out.push_str(sep);
+        out.push_str(&format!("{:0>2}", part(key)));
+    }
+    out
+}
+
+/// The sheet's auto filter, or None when it has none.
+pub fn read_auto_filter(sheet_xml: &str) -> Result<Option<AutoFilterDef>, String> {
+    let mut reader = XmlReader::from_str(sheet_xml);
+    let mut def: Option<AutoFilterDef> = None;
+    loop {
+        let (e, empty) = match reader.read_event() {
+            Ok(Event::Start(e)) => (e, false),
+            Ok(Event::Empty(e)) => (e, true),
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"autoFilter" => return Ok(def),
+            Ok(Event::Eof) => return Ok(def),
+            Err(e) => return Err(format!("XML parse error: {e}")),
+            _ => continue,
+        };
+
+        let name = e.local_name();
+        if name.as_ref() == b"autoFilter" {
+            let range = attr_value(&e, b"ref").unwrap_or_default();
+            let found = AutoFilterDef {
+                range,
+                columns: Vec::new(),
+            };
+            if empty {
+                return Ok(Some(found));
+            }
+            def = Some(found);
+            continue;
+        }
+        let Some(def) = def.as_mut() else {
+            continue;
+        };
+
+        if name.as_ref() == b"filterColumn" {
+            let col_id = attr_value(&e, b"colId")
+                .and_then(|v| v.parse().ok())
+                .ok_or("filterColumn without a valid colId")?;
+            def.columns.push(FilterColumn {
+                col_id,
+                criteria: None,
+            });
+            continue;
+        }
+        let Some(column) = def.columns.last_mut() else {
+            continue;
+        };
+        let criteria = match name.as_ref() {
+            b"filters" => FilterCriteria::Values {
+                values: Vec::new(),
+                dates: Vec::new(),
+                blank: flag(&e, b"blank", false),
+            },
+            b"customFilters" => FilterCriteria::Custom {
+                and: flag(&e, b"and", false),
+                conditions: Vec::new(),
+            },
+            b"top10" => FilterCriteria::Top10 {
+                top: flag(&e, b"top", true),
+                percent: flag(&e, b"percent", false),
+                value: attr_value(&e, b"val")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0.0),
+            },
+            b"dynamicFilter" => FilterCriteria::Dynamic {
+                kind: attr_value(&e, b"type").unwrap_or_default(),
+            },
+            b"colorFilter" => FilterCriteria::Color {
+                dxf_id: attr_value(&e, b"dxfId").and_then(|v| v.parse().ok()),
+                cell_color: flag(&e, b"cellColor", true),
+            },
+            b"iconFilter" => FilterCriteria::Icon {
+                icon_set: attr_value(&e, b"iconSet").unwrap_or_default(),
+                icon_id: attr_value(&e, b"iconId").and_then(|v| v.parse().ok()),
+            },
+            // Items of the enclosing `<filters>` / `<customFilters>`.
+            _ => {
+                match (name.as_ref(), column.criteria.as_mut()) {
+                    (b"filter", Some(FilterCriteria::Values { values, .. })) => {
+                        values.push(attr_value(&e, b"val").unwrap_or_default());
+                    }
+                    (b"dateGroupItem", Some(FilterCriteria::Values { dates, .. })) => {
+                        dates.push(date_group(&e));
+                    }
+                    (b"customFilter", Some(FilterCriteria::Custom { conditions, .. })) => {
+                        let op = attr_value(&e, b"operator").unwrap_or_else(|| "equal".to_string());
+                        conditions.push((op, attr_value(&e, b"val").unwrap_or_default()));
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+        };
+        column.criteria = Some(criteria);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_and_custom_filters() {
+        let xml = r#"<worksheet><sheetData/><autoFilter ref="A1:C9">
+            <filterColumn colId="0"><filters blank="1"><filter val="East"/><filter val="West"/>
+              <dateGroupItem year="2024" month="6" dateTimeGrouping="month"/>
+            </filters></filterColumn>
+            <filterColumn colId="2"><customFilters and="1">
+              <customFilter operator="greaterThan" val="5"/><customFilter val="9"/>
+            </customFilters></filterColumn>
+          </autoFilter></worksheet>"#;
+        let def = read_auto_filter(xml).unwrap().unwrap();
+        assert_eq!(def.range, "A1:C9");
+        assert_eq!(
+            def.columns,
+            vec![
+                FilterColumn {
+                    col_id: 0,
+                    criteria: Some(FilterCriteria::Values {
+                        values: vec!["East".to_string(), "West".to_string()],
+                        dates: vec!["2024-06".to_string()],
+                        blank: true,
+                    }),
+                },
+                FilterColumn {
+                    col_id: 2,
+                    criteria: Some(FilterCriteria::Custom {
+                        and: true,
+                        conditions: vec![
+                            ("greaterThan".to_string(), "5".to_string()),
+                            ("equal".to_string(), "9".to_string()),
+                        ],
+                    }),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_other_criteria() {
+        let xml = r#"<worksheet><autoFilter ref="B2:E20">
+            <filterColumn colId="0"><top10 top="0" percent="1" val="10"/></filterColumn>
+            <filterColumn colId="1"><dynamicFilter type="aboveAverage"/></filterColumn>
+            <filterColumn colId="2"><colorFilter dxfId="3" cellColor="0"/></filterColumn>
+            <filterColumn colId="3"><iconFilter iconSet="3Arrows" iconId="1"/></filterColumn>
+          </autoFilter></worksheet>"#;
+        let criteria: Vec<_> = read_auto_filter(xml)
+            .unwrap()
+            .unwrap()
+            .columns
+            .into_iter()
+            .map(|c| c.criteria.unwrap())
+            .collect();
+        assert_eq!(
+            criteria,
+            vec![
+                FilterCriteria::Top10 {
+                    top: false,
+                    percent: true,
+                    value: 10.0,
+                },
+                FilterCriteria::Dynamic {
+                    kind: "aboveAverage".to_string(),
+                },
+                FilterCriteria::Color {
+                    dxf_id: Some(3),
+                    cell_color: false,
+                },
+                FilterCriteria::Icon {
+                    icon_set: "3Arrows".to_string(),
+                    icon_id: Some(1),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_or_empty_filter() {
+        assert_eq!(
+            read_auto_filter("<worksheet><sheetData/></worksheet>").unwrap(),
+            None
+        );
+        let def = read_auto_filter(r#"<worksheet><autoFilter ref="A1:B2"/></worksheet>"#)
+            .unwrap()
+            .unwrap();
+        assert!(def.columns.is_empty());
+    }
+}
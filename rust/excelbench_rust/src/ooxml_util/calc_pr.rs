@@ -0,0 +1,202 @@
+//! Workbook calculation properties (`<calcPr>` in `xl/workbook.xml`).
+//!
+//! Neither writer backend models every calcPr attribute, so the element is
+//! patched into the saved package afterwards. Edits are attribute-level:
+//! attributes that are not edited keep whatever the writer emitted. Errors
+//! are plain strings; PyO3 callers wrap them.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader as XmlReader, Writer as XmlWriter};
+
+/// One attribute edit: `Some` sets the value, `None` removes the attribute.
+pub type CalcPrEdit = (String, Option<String>);
+
+/// OOXML `calcMode` for the user-facing names "auto", "manual" and
+/// "auto_except_tables".
+pub fn calc_mode_value(mode: &str) -> Result<&'static str, String> {
+    match mode {
+        "auto" => Ok("auto"),
+        "manual" => Ok("manual"),
+        "auto_except_tables" => Ok("autoNoTable"),
+        other => Err(format!(
+            "Unknown calc mode: {other} (expected auto, manual or auto_except_tables)"
+        )),
+    }
+}
+
+/// User-facing name for an OOXML `calcMode`; the inverse of [`calc_mode_value`].
+pub fn calc_mode_name(value: &str) -> Option<&'static str> {
+    match value {
+        "auto" => Some("auto"),
+        "manual" => Some("manual"),
+        "autoNoTable" => Some("auto_except_tables"),
+        _ => None,
+    }
+}
+
+/// Replace the edit for `key`, keeping the order edits were first made in.
+pub fn set_edit(edits: &mut Vec<CalcPrEdit>, key: &str, value: Option<String>) {
+    match edits.iter_mut().find(|(k, _)| k == key) {
+        Some(edit) => edit.1 = value,
+        None => edits.push((key.to_string(), value)),
+    }
+}
+
+/// Attributes of the workbook's `<calcPr>`, or None when it has none.
+pub fn read_calc_pr(workbook_xml: &str) -> Result<Option<Vec<(String, String)>>, String> {
+    let mut reader = XmlReader::from_str(workbook_xml);
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.local_name().as_ref() == b"calcPr" => {
+                let mut attrs = Vec::new();
+                for a in e.attributes().with_checks(false) {
+                    let a = a.map_err(|err| format!("XML attr parse error: {err}"))?;
+                    let key = String::from_utf8_lossy(a.key.as_ref()).into_owned();
+                    let value = a
+                        .unescape_value()
+                        .map_err(|err| format!("XML attr parse error: {err}"))?
+                        .into_owned();
+                    attrs.push((key, value));
+                }
+                return Ok(Some(attrs));
+            }
+            Ok(Event::Eof) => return Ok(None),
+            Err(e) => return Err(format!("XML parse error: {e}")),
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Copy of a `<calcPr>` tag with `edits` applied.
+fn edited_elem(e: &BytesStart<'_>, edits: &[CalcPrEdit]) -> Result<BytesStart<'static>, String> {
+    let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+    let mut elem = BytesStart::new(name);
+    for a in e.attributes().with_checks(false) {
+        let a = a.map_err(|err| format!("XML attr parse error: {err}"))?;
+        if !edits.iter().any(|(k, _)| k.as_bytes() == a.key.as_ref()) {
+            elem.push_attribute(a);
+        }
+    }
+    for (key, value) in edits {
+        if let Some(value) = value {
+            elem.push_attribute((key.as_str(), value.as_str()));
+        }
+    }
+    Ok(elem)
+}
+
+/// Apply `edits` to workbook.xml, adding a `<calcPr>` if there is none.
+///
+/// A new element goes last in `<workbook>`, which keeps the schema order
+/// for the parts both writers emit (calcPr is followed only by optional
+/// elements they don't write).
+pub fn patch_calc_pr(workbook_xml: &str, edits: &[CalcPrEdit]) -> Result<String, String> {
+    let mut reader = XmlReader::from_str(workbook_xml);
+    reader.config_mut().trim_text(false);
+    let mut writer = XmlWriter::new(Vec::new());
+    let mut buf: Vec<u8> = Vec::new();
+    let write_err = |err: std::io::Error| format!("XML write error: {err}");
+
+    let mut found = false;
+    loop {
+        let event = match reader.read_event_into(&mut buf) {
+            Ok(Event::Eof) => break,
+            Ok(Event::Empty(e)) if e.local_name().as_ref() == b"calcPr" => {
+                found = true;
+                Event::Empty(edited_elem(&e, edits)?)
+            }
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"calcPr" => {
+                found = true;
+                Event::Start(edited_elem(&e, edits)?)
+            }
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"workbook" && !found => {
+                let prefix = match e.name().prefix() {
+                    Some(p) => format!("{}:", String::from_utf8_lossy(p.as_ref())),
+                    None => String::new(),
+                };
+                let calc_pr = edited_elem(&BytesStart::new(format!("{prefix}calcPr")), edits)?;
+                writer
+                    .write_event(Event::Empty(calc_pr))
+                    .map_err(write_err)?;
+                Event::End(e.into_owned())
+            }
+            Ok(e) => e.into_owned(),
+            Err(e) => return Err(format!("XML parse error: {e}")),
+        };
+        writer.write_event(event).map_err(write_err)?;
+        buf.clear();
+    }
+
+    String::from_utf8(writer.into_inner()).map_err(|e| format!("Workbook XML not UTF-8: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(key: &str, value: Option<&str>) -> CalcPrEdit {
+        (key.to_string(), value.map(str::to_string))
+    }
+
+    #[test]
+    fn test_patch_existing_calc_pr() {
+        let xml = r#"<workbook><sheets/><calcPr calcId="191029" calcMode="auto"/></workbook>"#;
+        let out = patch_calc_pr(
+            xml,
+            &[edit("calcMode", Some("manual")), edit("iterate", Some("1"))],
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            r#"<workbook><sheets/><calcPr calcId="191029" calcMode="manual" iterate="1"/></workbook>"#
+        );
+    }
+
+    #[test]
+    fn test_patch_inserts_and_removes() {
+        let xml = r#"<workbook><sheets/></workbook>"#;
+        let out = patch_calc_pr(xml, &[edit("fullCalcOnLoad", Some("1"))]).unwrap();
+        assert_eq!(
+            out,
+            r#"<workbook><sheets/><calcPr fullCalcOnLoad="1"/></workbook>"#
+        );
+
+        let out = patch_calc_pr(&out, &[edit("fullCalcOnLoad", None)]).unwrap();
+        assert_eq!(out, r#"<workbook><sheets/><calcPr/></workbook>"#);
+    }
+
+    #[test]
+    fn test_read_calc_pr() {
+        let xml = r#"<x:workbook><x:calcPr calcId="1" iterateCount="50"/></x:workbook>"#;
+        assert_eq!(
+            read_calc_pr(xml).unwrap(),
+            Some(vec![
+                ("calcId".to_string(), "1".to_string()),
+                ("iterateCount".to_string(), "50".to_string()),
+            ])
+        );
+        assert_eq!(read_calc_pr("<workbook/>").unwrap(), None);
+    }
+
+    #[test]
+    fn test_set_edit_replaces() {
+        let mut edits = Vec::new();
+        set_edit(&mut edits, "calcMode", Some("manual".to_string()));
+        set_edit(&mut edits, "iterate", Some("1".to_string()));
+        set_edit(&mut edits, "calcMode", None);
+        assert_eq!(
+            edits,
+            vec![edit("calcMode", None), edit("iterate", Some("1"))]
+        );
+    }
+
+    #[test]
+    fn test_calc_mode_names_round_trip() {
+        for name in ["auto", "manual", "auto_except_tables"] {
+            assert_eq!(calc_mode_name(calc_mode_value(name).unwrap()), Some(name));
+        }
+        assert!(calc_mode_value("sometimes").is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! `operator` attributes of a worksheet's `<dataValidations>`.
+//!
+//! umya reports `lessThan` both for an explicit `lessThan` and for a rule
+//! without an operator, so the attribute is read from the part directly.
+//! Errors are plain strings; PyO3 callers wrap them.
+
+use quick_xml::events::Event;
+use quick_xml::Reader as XmlReader;
+
+use super::attr_value;
+
+/// The `operator` of each `<dataValidation>`, in document order; None where
+/// the attribute is absent. Rules under `<extLst>` (x14 validations) are not
+/// part of the main list and are skipped.
+pub fn read_dv_operators(sheet_xml: &str) -> Result<Vec<Option<String>>, String> {
+    let mut reader = XmlReader::from_str(sheet_xml);
+    let mut out = Vec::new();
+    let mut in_ext = false;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"extLst" => in_ext = true,
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"extLst" => in_ext = false,
+            Ok(Event::Start(e)) | Ok(Event::Empty(e))
+                if !in_ext && e.local_name().as_ref() == b"dataValidation" =>
+            {
+                out.push(attr_value(&e, b"operator"));
+            }
+            Ok(Event::Eof) => return Ok(out),
+            Err(e) => return Err(format!("Failed to parse dataValidations: {e}")),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_dv_operators() {
+        let xml = concat!(
+            r#"<worksheet><sheetData/><dataValidations count="3">"#,
+            r#"<dataValidation type="whole" sqref="A1"><formula1>5</formula1></dataValidation>"#,
+            r#"<dataValidation type="whole" operator="lessThan" sqref="B1">"#,
+            r#"<formula1>1</formula1><formula2>9</formula2></dataValidation>"#,
+            r#"<dataValidation type="list" sqref="C1"/></dataValidations>"#,
+            r#"<extLst><ext><x14:dataValidations><x14:dataValidation operator="equal"/>"#,
+            r#"</x14:dataValidations></ext></extLst></worksheet>"#
+        );
+        assert_eq!(
+            read_dv_operators(xml).unwrap(),
+            vec![None, Some("lessThan".to_string()), None]
+        );
+        assert!(read_dv_operators("<worksheet/>").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+//! `<definedNames>` of workbook.xml.
+//!
+//! calamine reports defined names without their scope, so the sheet a name
+//! belongs to (`localSheetId`) is read from the part directly. Errors are
+//! plain strings; PyO3 callers wrap them.
+
+use quick_xml::events::Event;
+use quick_xml::Reader as XmlReader;
+
+use super::attr_value;
+
+/// One `<definedName>`, in workbook order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DefinedNameDef {
+    pub name: String,
+    /// Index of the owning sheet for a sheet-scoped name.
+    pub local_sheet_id: Option<usize>,
+    pub hidden: bool,
+    /// The formula text, without a leading `=`.
+    pub refers_to: String,
+}
+
+impl DefinedNameDef {
+    /// `_xlnm.Print_Area` and the other names Excel reserves for itself.
+    pub fn is_builtin(&self) -> bool {
+        self.name
+            .get(..6)
+            .is_some_and(|p| p.eq_ignore_ascii_case("_xlnm."))
+    }
+}
+
+/// Every `<definedName>` of a workbook part; names without a formula are
+/// skipped.
+pub fn parse_defined_names(workbook_xml: &str) -> Result<Vec<DefinedNameDef>, String> {
+    let mut reader = XmlReader::from_str(workbook_xml);
+    let mut current: Option<DefinedNameDef> = None;
+    let mut out = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"definedName" => {
+                current = Some(DefinedNameDef {
+                    name: attr_value(&e, b"name").unwrap_or_default(),
+                    local_sheet_id: attr_value(&e, b"localSheetId").and_then(|v| v.parse().ok()),
+                    hidden: attr_value(&e, b"hidden").is_some_and(|v| v == "1" || v == "true"),
+                    refers_to: String::new(),
+                });
+            }
+            Ok(Event::Text(t)) => {
+                if let Some(dn) = current.as_mut() {
+                    let text = t
+                        .unescape()
+                        .map_err(|e| format!("Failed to parse definedName: {e}"))?;
+                    dn.refers_to.push_str(&text);
+                }
+            }
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"definedName" => {
+                if let Some(mut dn) = current.take() {
+                    dn.refers_to = dn.refers_to.trim().trim_start_matches('=').to_string();
+                    if !dn.name.is_empty() && !dn.refers_to.is_empty() {
+                        out.push(dn);
+                    }
+                }
+            }
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"definedNames" => break,
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Failed to parse workbook.xml definedNames: {e}")),
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_defined_names() {
+        let xml = concat!(
+            r#"<workbook><sheets><sheet name="A" sheetId="1" r:id="rId1"/></sheets>"#,
+            r#"<definedNames><definedName name="Rate">A!$B$1</definedName>"#,
+            r#"<definedName name="Local" localSheetId="1">'B &amp; C'!$A$1:$A$3</definedName>"#,
+            r#"<definedName name="_xlnm._FilterDatabase" localSheetId="0" hidden="1">"#,
+            r#"A!$A$1:$C$9</definedName><definedName name="Empty"/>"#,
+            r#"<definedName name="Const">=0.2</definedName></definedNames></workbook>"#
+        );
+        let names = parse_defined_names(xml).unwrap();
+        assert_eq!(names.len(), 4);
+        assert_eq!(
+            names[1],
+            DefinedNameDef {
+                name: "Local".to_string(),
+                local_sheet_id: Some(1),
+                hidden: false,
+                refers_to: "'B & C'!$A$1:$A$3".to_string(),
+            }
+        );
+        assert!(names[2].hidden && names[2].is_builtin());
+        assert!(!names[0].is_builtin());
+        assert_eq!(names[3].refers_to, "0.2");
+    }
+}
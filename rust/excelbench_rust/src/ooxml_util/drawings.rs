@@ -0,0 +1,232 @@
+//! Pictures anchored in a drawing part (`xl/drawings/drawingN.xml`).
+//!
+//! Only `<xdr:pic>` anchors are collected; charts and shapes are skipped.
+//! Errors are plain strings; PyO3 callers wrap them.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader as XmlReader;
+
+use super::parts::local_attr;
+use crate::cell_ref::col_to_letters;
+
+/// EMUs per pixel at 96 dpi.
+pub const EMU_PER_PIXEL: i64 = 9525;
+
+/// An `<xdr:from>` / `<xdr:to>` marker (0-based cell plus EMU offsets).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnchorMarker {
+    pub col: u32,
+    pub row: u32,
+    pub col_off: i64,
+    pub row_off: i64,
+}
+
+impl AnchorMarker {
+    /// The anchor cell in A1 form.
+    pub fn a1(&self) -> String {
+        format!("{}{}", col_to_letters(self.col), self.row + 1)
+    }
+}
+
+/// One picture from a drawing part.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DrawingImage {
+    /// `oneCell`, `twoCell` or `absolute`.
+    pub anchor: String,
+    pub from: Option<AnchorMarker>,
+    pub to: Option<AnchorMarker>,
+    /// Extent in EMU: the anchor's `<xdr:ext>` or the picture's `<a:xfrm>`.
+    pub cx: Option<i64>,
+    pub cy: Option<i64>,
+    pub name: Option<String>,
+    /// `descr` on `<xdr:cNvPr>` (Excel's alt text).
+    pub description: Option<String>,
+    /// Relationship id of the blip (`r:embed`, or `r:link` for linked images).
+    pub embed: Option<String>,
+}
+
+fn anchor_kind(local: &[u8]) -> Option<&'static str> {
+    match local {
+        b"oneCellAnchor" => Some("oneCell"),
+        b"twoCellAnchor" => Some("twoCell"),
+        b"absoluteAnchor" => Some("absolute"),
+        _ => None,
+    }
+}
+
+fn set_extent(img: &mut DrawingImage, e: &BytesStart<'_>) {
+    if img.cx.is_some() {
+        return;
+    }
+    // `<a:ext uri=...>` inside extension lists has no cx/cy.
+    if let (Some(cx), Some(cy)) = (local_attr(e, b"cx"), local_attr(e, b"cy")) {
+        img.cx = cx.parse().ok();
+        img.cy = cy.parse().ok();
+    }
+}
+
+/// Parse the pictures of a drawing part, in document order.
+pub fn parse_drawing_images(xml: &str) -> Result<Vec<DrawingImage>, String> {
+    let mut reader = XmlReader::from_str(xml);
+    let mut out = Vec::new();
+    // The anchor being read and whether it holds a picture.
+    let mut cur: Option<(DrawingImage, bool)> = None;
+    // Which marker (`from`/`to`) and field (`col`, `rowOff`, ...) is open.
+    let mut marker: Option<AnchorMarker> = None;
+    let mut field: Option<Vec<u8>> = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let local = e.local_name();
+                if let Some(kind) = anchor_kind(local.as_ref()) {
+                    let img = DrawingImage {
+                        anchor: kind.to_string(),
+                        ..Default::default()
+                    };
+                    cur = Some((img, false));
+                    continue;
+                }
+                let Some((img, has_pic)) = cur.as_mut() else {
+                    continue;
+                };
+                match local.as_ref() {
+                    b"from" | b"to" => marker = Some(AnchorMarker::default()),
+                    name @ (b"col" | b"row" | b"colOff" | b"rowOff") if marker.is_some() => {
+                        field = Some(name.to_vec())
+                    }
+                    b"pic" => *has_pic = true,
+                    b"ext" => set_extent(img, &e),
+                    b"cNvPr" => {
+                        img.name = local_attr(&e, b"name");
+                        img.description = local_attr(&e, b"descr");
+                    }
+                    b"blip" => {
+                        img.embed = local_attr(&e, b"embed").or_else(|| local_attr(&e, b"link"))
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Empty(e)) => {
+                let Some((img, _)) = cur.as_mut() else {
+                    continue;
+                };
+                match e.local_name().as_ref() {
+                    b"ext" => set_extent(img, &e),
+                    b"cNvPr" => {
+                        img.name = local_attr(&e, b"name");
+                        img.description = local_attr(&e, b"descr");
+                    }
+                    b"blip" => {
+                        img.embed = local_attr(&e, b"embed").or_else(|| local_attr(&e, b"link"))
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Text(t)) => {
+                if let (Some(m), Some(name)) = (marker.as_mut(), field.as_deref()) {
+                    let text = t.unescape().map_err(|e| format!("Bad drawing text: {e}"))?;
+                    let text = text.trim();
+                    match name {
+                        b"col" => m.col = text.parse().unwrap_or(0),
+                        b"row" => m.row = text.parse().unwrap_or(0),
+                        b"colOff" => m.col_off = text.parse().unwrap_or(0),
+                        _ => m.row_off = text.parse().unwrap_or(0),
+                    }
+                }
+            }
+            Ok(Event::End(e)) => {
+                let local = e.local_name();
+                match local.as_ref() {
+                    b"col" | b"row" | b"colOff" | b"rowOff" => field = None,
+                    name @ (b"from" | b"to") => {
+                        if let (Some((img, _)), Some(m)) = (cur.as_mut(), marker.take()) {
+                            if name == b"from" {
+                                img.from = Some(m);
+                            } else {
+                                img.to = Some(m);
+                            }
+                        }
+                    }
+                    name if anchor_kind(name).is_some() => {
+                        if let Some((img, true)) = cur.take() {
+                            out.push(img);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => return Ok(out),
+            Err(e) => return Err(format!("Failed to parse drawing: {e}")),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DRAWING: &str = r#"<xdr:wsDr
+      xmlns:xdr="http://schemas.openxmlformats.org/drawingml/2006/spreadsheetDrawing"
+      xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"
+      xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+      <xdr:oneCellAnchor>
+        <xdr:from><xdr:col>1</xdr:col><xdr:colOff>9525</xdr:colOff>
+          <xdr:row>2</xdr:row><xdr:rowOff>19050</xdr:rowOff></xdr:from>
+        <xdr:ext cx="381000" cy="285750"/>
+        <xdr:pic>
+          <xdr:nvPicPr><xdr:cNvPr id="2" name="Logo" descr="Company logo"/></xdr:nvPicPr>
+          <xdr:blipFill><a:blip r:embed="rId1"/></xdr:blipFill>
+        </xdr:pic>
+        <xdr:clientData/>
+      </xdr:oneCellAnchor>
+      <xdr:twoCellAnchor>
+        <xdr:from><xdr:col>0</xdr:col><xdr:colOff>0</xdr:colOff>
+          <xdr:row>5</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:from>
+        <xdr:to><xdr:col>4</xdr:col><xdr:colOff>0</xdr:colOff>
+          <xdr:row>15</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:to>
+        <xdr:graphicFrame><xdr:nvGraphicFramePr><xdr:cNvPr id="3" name="Chart"/>
+        </xdr:nvGraphicFramePr></xdr:graphicFrame>
+        <xdr:clientData/>
+      </xdr:twoCellAnchor>
+      <xdr:twoCellAnchor editAs="oneCell">
+        <xdr:from><xdr:col>3</xdr:col><xdr:colOff>0</xdr:colOff>
+          <xdr:row>0</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:from>
+        <xdr:to><xdr:col>5</xdr:col><xdr:colOff>0</xdr:colOff>
+          <xdr:row>4</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:to>
+        <xdr:pic>
+          <xdr:nvPicPr><xdr:cNvPr id="4" name="Picture 2"/></xdr:nvPicPr>
+          <xdr:blipFill><a:blip r:embed="rId2"><a:extLst><a:ext uri="{28A0}"/></a:extLst>
+          </a:blip></xdr:blipFill>
+          <xdr:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="952500" cy="762000"/></a:xfrm>
+          </xdr:spPr>
+        </xdr:pic>
+        <xdr:clientData/>
+      </xdr:twoCellAnchor>
+    </xdr:wsDr>"#;
+
+    #[test]
+    fn test_parse_drawing_images() {
+        let images = parse_drawing_images(DRAWING).unwrap();
+        assert_eq!(images.len(), 2, "the chart frame is skipped");
+
+        let logo = &images[0];
+        assert_eq!(logo.anchor, "oneCell");
+        let from = logo.from.unwrap();
+        assert_eq!(from.a1(), "B3");
+        assert_eq!((from.col_off, from.row_off), (9525, 19050));
+        assert_eq!((logo.cx, logo.cy), (Some(381000), Some(285750)));
+        assert_eq!(logo.name.as_deref(), Some("Logo"));
+        assert_eq!(logo.description.as_deref(), Some("Company logo"));
+        assert_eq!(logo.embed.as_deref(), Some("rId1"));
+
+        let pic = &images[1];
+        assert_eq!(pic.anchor, "twoCell");
+        assert_eq!(pic.from.unwrap().a1(), "D1");
+        assert_eq!(pic.to.unwrap().a1(), "F5");
+        assert_eq!((pic.cx, pic.cy), (Some(952500), Some(762000)));
+        assert_eq!(pic.description, None);
+        assert_eq!(pic.embed.as_deref(), Some("rId2"));
+    }
+}
@@ -0,0 +1,350 @@
+//! Cell fills from `xl/styles.xml` (`<fills>` and the cellXfs `fillId`s).
+//!
+//! Colors are kept as `ColorSpec`s so callers resolve them against the
+//! workbook palette. Errors are plain strings; PyO3 callers wrap them.
+
+use std::io::Write;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader as XmlReader, Writer as XmlWriter};
+
+use super::attr_value;
+use crate::color::ColorSpec;
+
+/// A `<patternFill>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternFillDef {
+    /// `patternType`; absent means `none`.
+    pub pattern_type: String,
+    pub fg: Option<ColorSpec>,
+    pub bg: Option<ColorSpec>,
+}
+
+/// One `<stop>` of a gradient.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientStop {
+    pub position: f64,
+    pub color: Option<ColorSpec>,
+}
+
+/// A `<gradientFill>`: `linear` at `degree`, or `path` toward the
+/// `left`/`right`/`top`/`bottom` rectangle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientFillDef {
+    pub kind: String,
+    pub degree: f64,
+    pub left: f64,
+    pub right: f64,
+    pub top: f64,
+    pub bottom: f64,
+    pub stops: Vec<GradientStop>,
+}
+
+impl GradientFillDef {
+    /// The `<fill><gradientFill>` element for styles.xml.
+    pub fn to_xml(&self) -> String {
+        let mut out = if self.kind == "path" {
+            format!(
+                "<fill><gradientFill type=\"path\" left=\"{}\" right=\"{}\" \
+                 top=\"{}\" bottom=\"{}\">",
+                self.left, self.right, self.top, self.bottom
+            )
+        } else {
+            format!("<fill><gradientFill degree=\"{}\">", self.degree)
+        };
+        for stop in &self.stops {
+            out.push_str(&format!("<stop position=\"{}\">", stop.position));
+            if let Some(color) = &stop.color {
+                out.push_str(&format!("<color {}/>", color.xml_attrs()));
+            }
+            out.push_str("</stop>");
+        }
+        out.push_str("</gradientFill></fill>");
+        out
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FillDef {
+    Pattern(PatternFillDef),
+    Gradient(GradientFillDef),
+}
+
+/// The fills table plus each cellXfs entry's `fillId`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StyleFills {
+    pub fills: Vec<FillDef>,
+    pub xf_fill_ids: Vec<usize>,
+}
+
+impl StyleFills {
+    /// The fill a cellXfs entry uses.
+    pub fn for_xf(&self, xf: u32) -> Option<&FillDef> {
+        let id = *self.xf_fill_ids.get(xf as usize)?;
+        self.fills.get(id)
+    }
+}
+
+fn no_fill() -> FillDef {
+    FillDef::Pattern(PatternFillDef {
+        pattern_type: "none".to_string(),
+        fg: None,
+        bg: None,
+    })
+}
+
+fn color(e: &BytesStart<'_>) -> Option<ColorSpec> {
+    ColorSpec::from_attrs(|k| attr_value(e, k))
+}
+
+fn num(e: &BytesStart<'_>, key: &[u8]) -> f64 {
+    attr_value(e, key)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0.0)
+}
+
+/// Parse the fills and cellXfs fill ids of a styles part.
+pub fn read_fills(styles_xml: &str) -> Result<StyleFills, String> {
+    let mut reader = XmlReader::from_str(styles_xml);
+    let mut out = StyleFills::default();
+    // Only the top-level tables count; `<dxfs>` carries fills too.
+    let mut in_fills = false;
+    let mut in_cellxfs = false;
+    let mut cur: Option<FillDef> = None;
+    let mut in_stop = false;
+
+    loop {
+        let (e, empty) = match reader.read_event() {
+            Ok(Event::Start(e)) => (e, false),
+            Ok(Event::Empty(e)) => (e, true),
+            Ok(Event::End(e)) => {
+                match e.local_name().as_ref() {
+                    b"fills" => in_fills = false,
+                    b"cellXfs" => in_cellxfs = false,
+                    b"fill" if in_fills => out.fills.push(cur.take().unwrap_or_else(no_fill)),
+                    b"stop" => in_stop = false,
+                    _ => {}
+                }
+                continue;
+            }
+            Ok(Event::Eof) => return Ok(out),
+            Err(e) => return Err(format!("Failed to parse styles.xml fills: {e}")),
+            _ => continue,
+        };
+
+        match e.local_name().as_ref() {
+            b"fills" => in_fills = !empty,
+            b"cellXfs" => in_cellxfs = !empty,
+            b"fill" if in_fills => {
+                cur = None;
+                if empty {
+                    out.fills.push(no_fill());
+                }
+            }
+            b"xf" if in_cellxfs => {
+                let id = attr_value(&e, b"fillId")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0);
+                out.xf_fill_ids.push(id);
+            }
+            b"patternFill" if in_fills => {
+                cur = Some(FillDef::Pattern(PatternFillDef {
+                    pattern_type: attr_value(&e, b"patternType")
+                        .unwrap_or_else(|| "none".to_string()),
+                    fg: None,
+                    bg: None,
+                }));
+            }
+            b"gradientFill" if in_fills => {
+                cur = Some(FillDef::Gradient(GradientFillDef {
+                    kind: attr_value(&e, b"type").unwrap_or_else(|| "linear".to_string()),
+                    degree: num(&e, b"degree"),
+                    left: num(&e, b"left"),
+                    right: num(&e, b"right"),
+                    top: num(&e, b"top"),
+                    bottom: num(&e, b"bottom"),
+                    stops: Vec::new(),
+                }));
+            }
+            b"stop" if in_fills => {
+                if let Some(FillDef::Gradient(g)) = cur.as_mut() {
+                    g.stops.push(GradientStop {
+                        position: num(&e, b"position"),
+                        color: None,
+                    });
+                    in_stop = !empty;
+                }
+            }
+            name @ (b"fgColor" | b"bgColor" | b"color") if in_fills => match cur.as_mut() {
+                Some(FillDef::Pattern(p)) if name == b"fgColor" => p.fg = color(&e),
+                Some(FillDef::Pattern(p)) if name == b"bgColor" => p.bg = color(&e),
+                Some(FillDef::Gradient(g)) if in_stop && name == b"color" => {
+                    if let Some(stop) = g.stops.last_mut() {
+                        stop.color = color(&e);
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Replace each top-level `<fill>` for which `replace` returns new XML,
+/// passing everything else through unchanged.
+pub fn replace_fills(
+    styles_xml: &str,
+    replace: impl Fn(&FillDef) -> Option<String>,
+) -> Result<String, String> {
+    let fills = read_fills(styles_xml)?.fills;
+    let mut reader = XmlReader::from_str(styles_xml);
+    let mut writer = XmlWriter::new(Vec::new());
+    let mut in_fills = false;
+    let mut index = 0usize;
+    // Depth inside a `<fill>` being replaced; its events are dropped.
+    let mut skip_depth = 0usize;
+
+    loop {
+        let event = match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(e) => e,
+            Err(e) => return Err(format!("Failed to parse styles.xml fills: {e}")),
+        };
+        if skip_depth > 0 {
+            match &event {
+                Event::Start(_) => skip_depth += 1,
+                Event::End(_) => skip_depth -= 1,
+                _ => {}
+            }
+            continue;
+        }
+        let fill_start = match &event {
+            Event::Start(e) | Event::Empty(e) => {
+                let name = e.local_name();
+                if name.as_ref() == b"fills" {
+                    in_fills = matches!(event, Event::Start(_));
+                }
+                in_fills && name.as_ref() == b"fill"
+            }
+            Event::End(e) if e.local_name().as_ref() == b"fills" => {
+                in_fills = false;
+                false
+            }
+            _ => false,
+        };
+        if fill_start {
+            let replacement = fills.get(index).and_then(&replace);
+            index += 1;
+            if let Some(xml) = replacement {
+                if matches!(event, Event::Start(_)) {
+                    skip_depth = 1;
+                }
+                writer
+                    .get_mut()
+                    .write_all(xml.as_bytes())
+                    .map_err(|e| format!("XML write error: {e}"))?;
+                continue;
+            }
+        }
+        writer
+            .write_event(event)
+            .map_err(|e| format!("XML write error: {e}"))?;
+    }
+
+    String::from_utf8(writer.into_inner()).map_err(|e| format!("styles.xml not UTF-8: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{ColorBase, Palette};
+
+    const STYLES: &str = r#"<styleSheet>
+      <fills count="4">
+        <fill><patternFill patternType="none"/></fill>
+        <fill><patternFill patternType="gray125"/></fill>
+        <fill><patternFill patternType="darkGrid">
+          <fgColor rgb="FFFF0000"/><bgColor theme="4" tint="-0.25"/>
+        </patternFill></fill>
+        <fill><gradientFill degree="90">
+          <stop position="0"><color rgb="FFFFFFFF"/></stop>
+          <stop position="1"><color theme="4"/></stop>
+        </gradientFill></fill>
+      </fills>
+      <cellXfs count="3">
+        <xf fillId="0"/><xf fillId="2"/><xf fillId="3"/>
+      </cellXfs>
+      <dxfs count="1"><dxf><fill>
+        <patternFill><bgColor rgb="FF00FF00"/></patternFill>
+      </fill></dxf></dxfs>
+    </styleSheet>"#;
+
+    #[test]
+    fn test_pattern_fills() {
+        let fills = read_fills(STYLES).unwrap();
+        assert_eq!(fills.fills.len(), 4);
+        assert_eq!(fills.xf_fill_ids, vec![0, 2, 3]);
+        let Some(FillDef::Pattern(p)) = fills.for_xf(1) else {
+            panic!("expected a pattern fill");
+        };
+        assert_eq!(p.pattern_type, "darkGrid");
+        let palette = Palette::default();
+        assert_eq!(p.fg.unwrap().resolve(&palette).as_deref(), Some("#FF0000"));
+        assert_eq!(p.bg.unwrap().resolve(&palette).as_deref(), Some("#2F5597"));
+        assert_eq!(
+            fills.fills[1],
+            FillDef::Pattern(PatternFillDef {
+                pattern_type: "gray125".to_string(),
+                fg: None,
+                bg: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_gradient_fill() {
+        let fills = read_fills(STYLES).unwrap();
+        let Some(FillDef::Gradient(g)) = fills.for_xf(2) else {
+            panic!("expected a gradient fill");
+        };
+        assert_eq!((g.kind.as_str(), g.degree), ("linear", 90.0));
+        assert_eq!(g.stops.len(), 2);
+        assert_eq!(g.stops[1].position, 1.0);
+        assert_eq!(g.stops[1].color.unwrap().base, ColorBase::Theme(4));
+        assert!(fills.for_xf(3).is_none());
+    }
+
+    #[test]
+    fn test_replace_fills_with_gradient() {
+        let gradient = GradientFillDef {
+            kind: "linear".to_string(),
+            degree: 45.0,
+            left: 0.0,
+            right: 0.0,
+            top: 0.0,
+            bottom: 0.0,
+            stops: vec![
+                GradientStop {
+                    position: 0.0,
+                    color: Some(ColorSpec::rgb([0xFF, 0xFF, 0xFF])),
+                },
+                GradientStop {
+                    position: 1.0,
+                    color: ColorSpec::parse("theme:4:-0.5").ok(),
+                },
+            ],
+        };
+        let out = replace_fills(STYLES, |fill| match fill {
+            FillDef::Pattern(p) if p.pattern_type == "darkGrid" => Some(gradient.to_xml()),
+            _ => None,
+        })
+        .unwrap();
+        let fills = read_fills(&out).unwrap();
+        assert_eq!(fills.fills.len(), 4);
+        assert_eq!(fills.fills[2], FillDef::Gradient(gradient));
+        assert!(out.contains(r#"<stop position="1"><color theme="4" tint="-0.5"/></stop>"#));
+        // Untouched fills and the dxf fill pass through.
+        assert!(out.contains(r#"<patternFill patternType="gray125"/>"#));
+        assert!(out.contains(r#"<bgColor rgb="FF00FF00"/>"#));
+    }
+}
@@ -0,0 +1,290 @@
+//! Typed parsers for the OPC package parts every reader needs first:
+//! relationships (`*.rels`), the workbook's `<sheets>` list and
+//! `[Content_Types].xml`.
+//!
+//! Elements and attributes are matched by local name, so namespace-prefixed
+//! documents (`<x:sheet>`, `<pr:Relationship>`) parse the same as the default
+//! namespace form. Errors are plain strings; PyO3 callers wrap them.
+
+use std::collections::HashMap;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader as XmlReader;
+
+/// One `<Relationship>` from a `.rels` part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relationship {
+    pub id: String,
+    pub rel_type: String,
+    pub target: String,
+    /// `TargetMode="External"` (hyperlinks, linked images).
+    pub external: bool,
+}
+
+impl Relationship {
+    /// True when `Type` ends with `/{suffix}` (e.g. `"worksheet"`, `"comments"`).
+    pub fn is_type(&self, suffix: &str) -> bool {
+        self.rel_type
+            .rsplit('/')
+            .next()
+            .is_some_and(|last| last == suffix)
+    }
+}
+
+/// One `<sheet>` entry from workbook.xml, in workbook order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SheetRef {
+    pub name: String,
+    pub sheet_id: Option<u32>,
+    /// Relationship id (`r:id`) into `xl/_rels/workbook.xml.rels`.
+    pub rid: String,
+    /// `state` attribute: None (visible), "hidden" or "veryHidden".
+    pub state: Option<String>,
+}
+
+/// Parsed `[Content_Types].xml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentTypes {
+    /// Lower-cased extension → content type.
+    pub defaults: HashMap<String, String>,
+    /// Part name without the leading `/` → content type.
+    pub overrides: HashMap<String, String>,
+}
+
+impl ContentTypes {
+    /// Content type for a zip entry, preferring an override over the extension default.
+    pub fn content_type(&self, part: &str) -> Option<&str> {
+        let part = part.trim_start_matches('/');
+        if let Some(ct) = self.overrides.get(part) {
+            return Some(ct);
+        }
+        let ext = part.rsplit_once('.')?.1.to_ascii_lowercase();
+        self.defaults.get(&ext).map(String::as_str)
+    }
+}
+
+/// Attribute value by local name, ignoring any namespace prefix.
+pub(crate) fn local_attr(e: &BytesStart<'_>, local: &[u8]) -> Option<String> {
+    for a in e.attributes().with_checks(false).flatten() {
+        if a.key.local_name().as_ref() == local {
+            if let Ok(v) = a.unescape_value() {
+                return Some(v.to_string());
+            }
+            return Some(String::from_utf8_lossy(a.value.as_ref()).into_owned());
+        }
+    }
+    None
+}
+
+/// The relationship id on `<sheet>`: a prefixed `id` attribute (`r:id`).
+fn relationship_id_attr(e: &BytesStart<'_>) -> Option<String> {
+    for a in e.attributes().with_checks(false).flatten() {
+        if a.key.prefix().is_some() && a.key.local_name().as_ref() == b"id" {
+            if let Ok(v) = a.unescape_value() {
+                return Some(v.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Visit every start/empty element with the given local name.
+fn for_each_element(
+    xml: &str,
+    part: &str,
+    local: &[u8],
+    mut f: impl FnMut(&BytesStart<'_>),
+) -> Result<(), String> {
+    let mut reader = XmlReader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.local_name().as_ref() == local => f(&e),
+            Ok(Event::Eof) => return Ok(()),
+            Err(e) => return Err(format!("Failed to parse {part}: {e}")),
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Parse a `.rels` part. Entries without `Id` or `Target` are skipped.
+pub fn parse_relationships(xml: &str) -> Result<Vec<Relationship>, String> {
+    let mut out = Vec::new();
+    for_each_element(xml, "rels", b"Relationship", |e| {
+        let (Some(id), Some(target)) = (local_attr(e, b"Id"), local_attr(e, b"Target")) else {
+            return;
+        };
+        out.push(Relationship {
+            id,
+            rel_type: local_attr(e, b"Type").unwrap_or_default(),
+            target,
+            external: local_attr(e, b"TargetMode").is_some_and(|m| m == "External"),
+        });
+    })?;
+    Ok(out)
+}
+
+/// Parse the `<sheets>` list of workbook.xml.
+pub fn parse_workbook_sheets(xml: &str) -> Result<Vec<SheetRef>, String> {
+    let mut out = Vec::new();
+    for_each_element(xml, "workbook.xml", b"sheet", |e| {
+        let (Some(name), Some(rid)) = (local_attr(e, b"name"), relationship_id_attr(e)) else {
+            return;
+        };
+        out.push(SheetRef {
+            name,
+            sheet_id: local_attr(e, b"sheetId").and_then(|v| v.parse().ok()),
+            rid,
+            state: local_attr(e, b"state"),
+        });
+    })?;
+    Ok(out)
+}
+
+/// Parse `[Content_Types].xml`.
+pub fn parse_content_types(xml: &str) -> Result<ContentTypes, String> {
+    let mut out = ContentTypes::default();
+    for_each_element(xml, "[Content_Types].xml", b"Default", |e| {
+        if let (Some(ext), Some(ct)) = (local_attr(e, b"Extension"), local_attr(e, b"ContentType"))
+        {
+            out.defaults.insert(ext.to_ascii_lowercase(), ct);
+        }
+    })?;
+    for_each_element(xml, "[Content_Types].xml", b"Override", |e| {
+        if let (Some(part), Some(ct)) = (local_attr(e, b"PartName"), local_attr(e, b"ContentType"))
+        {
+            out.overrides
+                .insert(part.trim_start_matches('/').to_string(), ct);
+        }
+    })?;
+    Ok(out)
+}
+
+/// Resolve workbook sheets to their zip entry paths (`xl/worksheets/sheet1.xml`),
+/// in workbook order. Sheets whose relationship is missing are skipped.
+pub fn sheet_part_paths(
+    workbook_xml: &str,
+    rels_xml: &str,
+) -> Result<Vec<(String, String)>, String> {
+    let rels: HashMap<String, String> = parse_relationships(rels_xml)?
+        .into_iter()
+        .map(|r| (r.id, r.target))
+        .collect();
+    Ok(parse_workbook_sheets(workbook_xml)?
+        .into_iter()
+        .filter_map(|s| {
+            let target = rels.get(&s.rid)?;
+            Some((s.name, super::join_and_normalize("xl/", target)))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKBOOK_DEFAULT_NS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"
+          xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+  <sheets>
+    <sheet name="Data" sheetId="1" r:id="rId1"/>
+    <sheet name="Q&amp;A" sheetId="2" state="hidden" r:id="rId2"/>
+  </sheets>
+</workbook>"#;
+
+    const WORKBOOK_PREFIXED: &str = r#"<x:workbook xmlns:x='http://schemas.openxmlformats.org/spreadsheetml/2006/main'
+            xmlns:rel='http://schemas.openxmlformats.org/officeDocument/2006/relationships'>
+  <x:sheets>
+    <x:sheet name='Data' sheetId='1' rel:id='rId1'/>
+    <x:sheet name='Q&amp;A' sheetId='2' state='hidden' rel:id='rId2'/>
+  </x:sheets>
+</x:workbook>"#;
+
+    const RELS_DEFAULT_NS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/>
+  <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="/xl/worksheets/sheet2.xml"/>
+  <Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/?a=1&amp;b=2" TargetMode="External"/>
+</Relationships>"#;
+
+    const RELS_PREFIXED: &str = r#"<pr:Relationships xmlns:pr='http://schemas.openxmlformats.org/package/2006/relationships'>
+  <pr:Relationship Id='rId1' Type='http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet' Target='worksheets/sheet1.xml'/>
+  <pr:Relationship Id='rId2' Type='http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet' Target='/xl/worksheets/sheet2.xml'/>
+  <pr:Relationship Id='rId3' Type='http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink' Target='https://example.com/?a=1&amp;b=2' TargetMode='External'/>
+</pr:Relationships>"#;
+
+    #[test]
+    fn test_workbook_sheets_default_and_prefixed() {
+        for xml in [WORKBOOK_DEFAULT_NS, WORKBOOK_PREFIXED] {
+            let sheets = parse_workbook_sheets(xml).unwrap();
+            assert_eq!(sheets.len(), 2);
+            assert_eq!(sheets[0].name, "Data");
+            assert_eq!(sheets[0].rid, "rId1");
+            assert_eq!(sheets[0].sheet_id, Some(1));
+            assert_eq!(sheets[0].state, None);
+            assert_eq!(sheets[1].name, "Q&A");
+            assert_eq!(sheets[1].state.as_deref(), Some("hidden"));
+        }
+    }
+
+    #[test]
+    fn test_relationships_default_and_prefixed() {
+        for xml in [RELS_DEFAULT_NS, RELS_PREFIXED] {
+            let rels = parse_relationships(xml).unwrap();
+            assert_eq!(rels.len(), 3);
+            assert!(rels[0].is_type("worksheet"));
+            assert!(!rels[0].external);
+            assert_eq!(rels[2].target, "https://example.com/?a=1&b=2");
+            assert!(rels[2].is_type("hyperlink"));
+            assert!(rels[2].external);
+        }
+    }
+
+    #[test]
+    fn test_is_type_matches_whole_segment() {
+        let rel = Relationship {
+            id: "rId1".into(),
+            rel_type: "http://schemas.microsoft.com/office/2017/10/relationships/threadedComment"
+                .into(),
+            target: "../threadedComments/threadedComment1.xml".into(),
+            external: false,
+        };
+        assert!(rel.is_type("threadedComment"));
+        assert!(!rel.is_type("Comment"));
+    }
+
+    #[test]
+    fn test_sheet_part_paths_resolves_relative_and_absolute() {
+        let paths = sheet_part_paths(WORKBOOK_PREFIXED, RELS_DEFAULT_NS).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                ("Data".to_string(), "xl/worksheets/sheet1.xml".to_string()),
+                ("Q&A".to_string(), "xl/worksheets/sheet2.xml".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_content_types() {
+        let xml = r#"<Types xmlns='http://schemas.openxmlformats.org/package/2006/content-types'>
+  <Default Extension='XML' ContentType='application/xml'/>
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+  <Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>
+</Types>"#;
+        let ct = parse_content_types(xml).unwrap();
+        assert_eq!(
+            ct.content_type("/xl/workbook.xml"),
+            Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml")
+        );
+        assert_eq!(ct.content_type("xl/styles.xml"), Some("application/xml"));
+        assert_eq!(ct.content_type("xl/media/image1.png"), None);
+    }
+
+    #[test]
+    fn test_malformed_xml_is_an_error() {
+        assert!(parse_workbook_sheets("<workbook><sheets></workbook>").is_err());
+    }
+}
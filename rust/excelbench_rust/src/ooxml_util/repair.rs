@@ -0,0 +1,457 @@
+//! Best-effort repair of xlsx packages for lenient opening.
+//!
+//! Third-party writers often produce packages Excel opens but strict readers
+//! reject. This fixes the package-level defects without touching cell data:
+//! - bytes appended after the ZIP end-of-central-directory record,
+//! - entry names with backslashes, a leading `/`, or a case that differs from
+//!   the relationship targets that point at them,
+//! - a missing `[Content_Types].xml`, or parts it does not cover.
+//!
+//! Every fix is reported as an [`Anomaly`] so callers can surface it.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Cursor, Read, Write};
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::normalize_zip_path;
+use super::parts::{parse_content_types, parse_relationships, ContentTypes};
+
+const CONTENT_TYPES: &str = "[Content_Types].xml";
+
+/// One repaired defect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anomaly {
+    /// Stable dotted identifier, e.g. `zip.trailing_data`.
+    pub code: &'static str,
+    /// Zip entry the anomaly is about (empty for package-level ones).
+    pub part: String,
+    pub message: String,
+}
+
+/// Repair result: the (possibly rewritten) package bytes plus what changed.
+#[derive(Debug)]
+pub struct Repaired {
+    pub bytes: Vec<u8>,
+    pub anomalies: Vec<Anomaly>,
+}
+
+/// Repair `bytes` if it is a ZIP package. Non-ZIP input (e.g. `.xls`) and
+/// non-spreadsheet packages (e.g. `.ods`) only get the trailing-data fix.
+pub fn repair_package(mut bytes: Vec<u8>) -> Result<Repaired, String> {
+    let mut anomalies = Vec::new();
+    if !bytes.starts_with(b"PK\x03\x04") {
+        return Ok(Repaired { bytes, anomalies });
+    }
+
+    if let Some(end) = zip_end(&bytes) {
+        if end < bytes.len() {
+            anomalies.push(Anomaly {
+                code: "zip.trailing_data",
+                part: String::new(),
+                message: format!(
+                    "Dropped {} bytes after the ZIP central directory",
+                    bytes.len() - end
+                ),
+            });
+            bytes.truncate(end);
+        }
+    }
+
+    let mut zip = ZipArchive::new(Cursor::new(bytes.as_slice()))
+        .map_err(|e| format!("Not a valid ZIP: {e}"))?;
+    let names: Vec<String> = zip.file_names().map(str::to_string).collect();
+    let canonical: Vec<String> = names.iter().map(|n| canonical_name(n)).collect();
+    if !canonical
+        .iter()
+        .any(|n| n.eq_ignore_ascii_case("xl/workbook.xml"))
+    {
+        drop(zip);
+        return Ok(Repaired { bytes, anomalies });
+    }
+
+    // Relationship parts, read by index since their names may be the broken ones.
+    let mut rels: Vec<(String, String)> = Vec::new();
+    for (i, name) in canonical.iter().enumerate() {
+        if name.ends_with(".rels") {
+            let mut xml = String::new();
+            zip.by_index(i)
+                .map_err(|e| format!("Cannot read {name}: {e}"))?
+                .read_to_string(&mut xml)
+                .map_err(|e| format!("Cannot read {name}: {e}"))?;
+            rels.push((name.clone(), xml));
+        }
+    }
+
+    // Part name → relationship type suffix, for every internal target.
+    let mut referenced: BTreeMap<String, String> = BTreeMap::new();
+    for fixed in [CONTENT_TYPES, "_rels/.rels", "xl/_rels/workbook.xml.rels"] {
+        referenced.insert(fixed.to_string(), String::new());
+    }
+    for (rels_name, xml) in &rels {
+        let source_dir = match rels_name.rfind("_rels/") {
+            Some(i) => &rels_name[..i],
+            None => continue,
+        };
+        for rel in parse_relationships(xml).unwrap_or_default() {
+            if rel.external {
+                continue;
+            }
+            let target = rel.target.split('#').next().unwrap_or_default();
+            let part = match target.strip_prefix('/') {
+                Some(abs) => normalize_zip_path(abs),
+                None => normalize_zip_path(&format!("{source_dir}{target}")),
+            };
+            let kind = rel.rel_type.rsplit('/').next().unwrap_or_default();
+            referenced.insert(part, kind.to_string());
+        }
+    }
+    let by_lower: HashMap<String, &String> = referenced
+        .keys()
+        .map(|k| (k.to_ascii_lowercase(), k))
+        .collect();
+
+    // Final entry names, renaming nonstandard ones.
+    let mut taken: HashSet<String> = canonical.iter().cloned().collect();
+    let mut final_names: Vec<String> = Vec::with_capacity(names.len());
+    for (raw, name) in names.iter().zip(&canonical) {
+        let mut name = name.clone();
+        if let Some(expected) = by_lower.get(&name.to_ascii_lowercase()) {
+            if **expected != name && !taken.contains(*expected) {
+                taken.insert((*expected).clone());
+                name = (*expected).clone();
+            }
+        }
+        if name != *raw {
+            anomalies.push(Anomaly {
+                code: "part.nonstandard_name",
+                part: name.clone(),
+                message: format!("Renamed entry '{raw}' to '{name}'"),
+            });
+        }
+        final_names.push(name);
+    }
+
+    // Content types: keep what is declared, fill in what is missing.
+    let existing = match final_names.iter().position(|n| n == CONTENT_TYPES) {
+        Some(i) => {
+            let mut xml = String::new();
+            zip.by_index(i)
+                .map_err(|e| format!("Cannot read {CONTENT_TYPES}: {e}"))?
+                .read_to_string(&mut xml)
+                .map_err(|e| format!("Cannot read {CONTENT_TYPES}: {e}"))?;
+            Some(parse_content_types(&xml).unwrap_or_default())
+        }
+        None => {
+            anomalies.push(Anomaly {
+                code: "content_types.missing",
+                part: CONTENT_TYPES.to_string(),
+                message: "Generated a missing [Content_Types].xml".to_string(),
+            });
+            None
+        }
+    };
+    let mut types = existing.clone().unwrap_or_default();
+    let has_vba = final_names.iter().any(|n| n == "xl/vbaProject.bin");
+    for name in &final_names {
+        if name == CONTENT_TYPES || name.ends_with('/') || types.content_type(name).is_some() {
+            continue;
+        }
+        let kind = referenced.get(name).map(String::as_str).unwrap_or_default();
+        let Some(ct) = guess_content_type(name, kind, has_vba) else {
+            continue;
+        };
+        if existing.is_some() {
+            anomalies.push(Anomaly {
+                code: "content_types.uncovered",
+                part: name.clone(),
+                message: format!("Declared missing content type {ct}"),
+            });
+        }
+        types.overrides.insert(name.clone(), ct);
+    }
+    let types_changed = existing.as_ref() != Some(&types);
+
+    let renamed = names != final_names;
+    if !renamed && !types_changed {
+        drop(zip);
+        return Ok(Repaired { bytes, anomalies });
+    }
+
+    let mut out = ZipWriter::new(Cursor::new(Vec::with_capacity(bytes.len())));
+    let opts = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut wrote_types = false;
+    for (i, name) in final_names.iter().enumerate() {
+        if name == CONTENT_TYPES && types_changed {
+            write_content_types(&mut out, &types, opts)?;
+            wrote_types = true;
+            continue;
+        }
+        let file = zip
+            .by_index_raw(i)
+            .map_err(|e| format!("Cannot read {name}: {e}"))?;
+        out.raw_copy_file_rename(file, name)
+            .map_err(|e| format!("ZIP write error: {e}"))?;
+    }
+    if types_changed && !wrote_types {
+        write_content_types(&mut out, &types, opts)?;
+    }
+    let bytes = out
+        .finish()
+        .map_err(|e| format!("ZIP finalize error: {e}"))?
+        .into_inner();
+    Ok(Repaired { bytes, anomalies })
+}
+
+/// End offset of the end-of-central-directory record (including its
+/// comment), or None when no consistent record is found.
+fn zip_end(bytes: &[u8]) -> Option<usize> {
+    const EOCD_LEN: usize = 22;
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]) as usize;
+    let u32_at =
+        |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+
+    let mut pos = bytes.len().checked_sub(EOCD_LEN)?;
+    loop {
+        if &bytes[pos..pos + 4] == b"PK\x05\x06" {
+            let end = pos + EOCD_LEN + u16_at(pos + 20);
+            let cd_size = u32_at(pos + 12);
+            let cd_offset = u32_at(pos + 16);
+            // ZIP64 archives store 0xFFFFFFFF here and the real values elsewhere.
+            let zip64 = cd_size == u32::MAX || cd_offset == u32::MAX;
+            if end <= bytes.len() && (zip64 || cd_offset as usize + cd_size as usize == pos) {
+                return Some(end);
+            }
+        }
+        pos = pos.checked_sub(1)?;
+    }
+}
+
+/// `xl\worksheets\sheet1.xml` / `/xl/worksheets/sheet1.xml` → `xl/worksheets/sheet1.xml`.
+fn canonical_name(raw: &str) -> String {
+    raw.replace('\\', "/").trim_start_matches('/').to_string()
+}
+
+/// Content type for an undeclared part, from the relationship that targets
+/// it or, failing that, its extension.
+fn guess_content_type(part: &str, rel_kind: &str, has_vba: bool) -> Option<String> {
+    const SML: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml";
+    const OFFICE: &str = "application/vnd.openxmlformats-officedocument";
+    Some(match rel_kind {
+        "officeDocument" if has_vba => {
+            "application/vnd.ms-excel.sheet.macroEnabled.main+xml".into()
+        }
+        "officeDocument" => format!("{SML}.sheet.main+xml"),
+        "worksheet" | "chartsheet" | "styles" | "sharedStrings" | "table" | "comments" => {
+            format!("{SML}.{rel_kind}+xml")
+        }
+        "theme" | "drawing" | "extended-properties" => format!("{OFFICE}.{rel_kind}+xml"),
+        "core-properties" => "application/vnd.openxmlformats-package.core-properties+xml".into(),
+        _ => {
+            let ext = part.rsplit_once('.')?.1.to_ascii_lowercase();
+            let ct = match ext.as_str() {
+                "rels" => "application/vnd.openxmlformats-package.relationships+xml",
+                "xml" => "application/xml",
+                "vml" => "application/vnd.openxmlformats-officedocument.vmlDrawing",
+                "bin" if part == "xl/vbaProject.bin" => "application/vnd.ms-office.vbaProject",
+                "png" => "image/png",
+                "jpg" | "jpeg" => "image/jpeg",
+                "gif" => "image/gif",
+                "emf" => "image/x-emf",
+                "wmf" => "image/x-wmf",
+                _ => return None,
+            };
+            ct.to_string()
+        }
+    })
+}
+
+fn write_content_types<W: Write + std::io::Seek>(
+    out: &mut ZipWriter<W>,
+    types: &ContentTypes,
+    opts: SimpleFileOptions,
+) -> Result<(), String> {
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('"', "&quot;")
+    };
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">",
+    );
+    let defaults: BTreeMap<_, _> = types.defaults.iter().collect();
+    for (ext, ct) in defaults {
+        xml.push_str(&format!(
+            "<Default Extension=\"{}\" ContentType=\"{}\"/>",
+            escape(ext),
+            escape(ct)
+        ));
+    }
+    let overrides: BTreeMap<_, _> = types.overrides.iter().collect();
+    for (part, ct) in overrides {
+        xml.push_str(&format!(
+            "<Override PartName=\"/{}\" ContentType=\"{}\"/>",
+            escape(part),
+            escape(ct)
+        ));
+    }
+    xml.push_str("</Types>");
+
+    out.start_file(CONTENT_TYPES, opts)
+        .map_err(|e| format!("ZIP write error: {e}"))?;
+    out.write_all(xml.as_bytes())
+        .map_err(|e| format!("ZIP write error: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOT_RELS: &str = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>
+</Relationships>"#;
+
+    const WORKBOOK_RELS: &str = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/>
+</Relationships>"#;
+
+    const CONTENT_TYPES_XML: &str = r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+  <Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>
+</Types>"#;
+
+    fn package(parts: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in parts {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn entries(bytes: &[u8]) -> Vec<String> {
+        let zip = ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut names: Vec<String> = zip.file_names().map(str::to_string).collect();
+        names.sort();
+        names
+    }
+
+    fn content_types(bytes: &[u8]) -> ContentTypes {
+        let mut zip = ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut xml = String::new();
+        zip.by_name(CONTENT_TYPES)
+            .unwrap()
+            .read_to_string(&mut xml)
+            .unwrap();
+        parse_content_types(&xml).unwrap()
+    }
+
+    fn codes(repaired: &Repaired) -> Vec<&'static str> {
+        repaired.anomalies.iter().map(|a| a.code).collect()
+    }
+
+    #[test]
+    fn test_clean_package_is_untouched() {
+        let bytes = package(&[
+            (CONTENT_TYPES, CONTENT_TYPES_XML),
+            ("_rels/.rels", ROOT_RELS),
+            ("xl/workbook.xml", "<workbook/>"),
+        ]);
+        let repaired = repair_package(bytes.clone()).unwrap();
+        assert!(repaired.anomalies.is_empty());
+        assert_eq!(repaired.bytes, bytes);
+    }
+
+    #[test]
+    fn test_non_zip_is_passed_through() {
+        let repaired = repair_package(b"\xD0\xCF\x11\xE0 not a zip".to_vec()).unwrap();
+        assert!(repaired.anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_trailing_data_is_dropped() {
+        let clean = package(&[
+            (CONTENT_TYPES, CONTENT_TYPES_XML),
+            ("_rels/.rels", ROOT_RELS),
+            ("xl/workbook.xml", "<workbook/>"),
+        ]);
+        let mut bytes = clean.clone();
+        bytes.extend_from_slice(b"\0\0garbage PK\x05\x06 trailer");
+        let repaired = repair_package(bytes).unwrap();
+        assert_eq!(codes(&repaired), vec!["zip.trailing_data"]);
+        assert_eq!(repaired.bytes, clean);
+    }
+
+    #[test]
+    fn test_nonstandard_names_are_renamed() {
+        let bytes = package(&[
+            (CONTENT_TYPES, CONTENT_TYPES_XML),
+            ("_rels/.rels", ROOT_RELS),
+            ("/xl/workbook.xml", "<workbook/>"),
+            ("xl\\_rels\\workbook.xml.rels", WORKBOOK_RELS),
+            ("xl/Worksheets/Sheet1.xml", "<worksheet/>"),
+        ]);
+        let repaired = repair_package(bytes).unwrap();
+        assert_eq!(
+            entries(&repaired.bytes),
+            vec![
+                CONTENT_TYPES,
+                "_rels/.rels",
+                "xl/_rels/workbook.xml.rels",
+                "xl/workbook.xml",
+                "xl/worksheets/sheet1.xml",
+            ]
+        );
+        let renamed = codes(&repaired)
+            .iter()
+            .filter(|c| **c == "part.nonstandard_name")
+            .count();
+        assert_eq!(renamed, 3);
+    }
+
+    #[test]
+    fn test_missing_content_types_are_generated() {
+        let bytes = package(&[
+            ("_rels/.rels", ROOT_RELS),
+            ("xl/workbook.xml", "<workbook/>"),
+            ("xl/_rels/workbook.xml.rels", WORKBOOK_RELS),
+            ("xl/worksheets/sheet1.xml", "<worksheet/>"),
+        ]);
+        let repaired = repair_package(bytes).unwrap();
+        assert_eq!(codes(&repaired), vec!["content_types.missing"]);
+        let types = content_types(&repaired.bytes);
+        assert_eq!(
+            types.content_type("xl/workbook.xml"),
+            Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml")
+        );
+        assert_eq!(
+            types.content_type("xl/worksheets/sheet1.xml"),
+            Some("application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml")
+        );
+        assert_eq!(
+            types.content_type("_rels/.rels"),
+            Some("application/vnd.openxmlformats-package.relationships+xml")
+        );
+    }
+
+    #[test]
+    fn test_uncovered_parts_are_declared() {
+        let bytes = package(&[
+            (CONTENT_TYPES, CONTENT_TYPES_XML),
+            ("_rels/.rels", ROOT_RELS),
+            ("xl/workbook.xml", "<workbook/>"),
+            ("xl/_rels/workbook.xml.rels", WORKBOOK_RELS),
+            ("xl/worksheets/sheet1.xml", "<worksheet/>"),
+        ]);
+        let repaired = repair_package(bytes).unwrap();
+        assert_eq!(codes(&repaired), vec!["content_types.uncovered"]);
+        assert_eq!(repaired.anomalies[0].part, "xl/worksheets/sheet1.xml");
+        let types = content_types(&repaired.bytes);
+        assert_eq!(
+            types.content_type("xl/worksheets/sheet1.xml"),
+            Some("application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml")
+        );
+    }
+}
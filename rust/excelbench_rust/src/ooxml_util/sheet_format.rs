@@ -0,0 +1,190 @@
+//! Worksheet default row/column properties (`<sheetFormatPr>`).
+//!
+//! The writers model only some of its attributes (rust_xlsxwriter has no
+//! `defaultColWidth`, umya no `zeroHeight`), so the element is patched into
+//! the saved worksheet parts. Edits work like the calcPr ones: attributes
+//! that are not edited keep whatever the writer emitted. Errors are plain
+//! strings; PyO3 callers wrap them.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader as XmlReader, Writer as XmlWriter};
+
+/// One attribute edit: `Some` sets the value, `None` removes the attribute.
+pub type SheetFormatEdit = (String, Option<String>);
+
+/// Attributes of a worksheet's `<sheetFormatPr>`, or None when it has none.
+/// Parsing stops at `<sheetData>`, so large sheets are not scanned.
+pub fn read_sheet_format_pr(sheet_xml: &str) -> Result<Option<Vec<(String, String)>>, String> {
+    let mut reader = XmlReader::from_str(sheet_xml);
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                b"sheetFormatPr" => {
+                    let mut attrs = Vec::new();
+                    for a in e.attributes().with_checks(false) {
+                        let a = a.map_err(|err| format!("XML attr parse error: {err}"))?;
+                        let key = String::from_utf8_lossy(a.key.as_ref()).into_owned();
+                        let value = a
+                            .unescape_value()
+                            .map_err(|err| format!("XML attr parse error: {err}"))?
+                            .into_owned();
+                        attrs.push((key, value));
+                    }
+                    return Ok(Some(attrs));
+                }
+                b"sheetData" => return Ok(None),
+                _ => {}
+            },
+            Ok(Event::Eof) => return Ok(None),
+            Err(e) => return Err(format!("XML parse error: {e}")),
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Copy of a `<sheetFormatPr>` tag with `edits` applied.
+fn edited_elem(
+    e: &BytesStart<'_>,
+    edits: &[SheetFormatEdit],
+) -> Result<BytesStart<'static>, String> {
+    let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+    let mut elem = BytesStart::new(name);
+    for a in e.attributes().with_checks(false) {
+        let a = a.map_err(|err| format!("XML attr parse error: {err}"))?;
+        if !edits.iter().any(|(k, _)| k.as_bytes() == a.key.as_ref()) {
+            elem.push_attribute(a);
+        }
+    }
+    for (key, value) in edits {
+        if let Some(value) = value {
+            elem.push_attribute((key.as_str(), value.as_str()));
+        }
+    }
+    Ok(elem)
+}
+
+/// The first elements that come after `<sheetFormatPr>` in schema order.
+fn follows_format_pr(e: &BytesStart<'_>) -> bool {
+    matches!(e.local_name().as_ref(), b"cols" | b"sheetData")
+}
+
+/// A new `<sheetFormatPr>` with the namespace prefix of its sibling `e`.
+fn new_elem(e: &BytesStart<'_>, edits: &[SheetFormatEdit]) -> Result<BytesStart<'static>, String> {
+    let prefix = match e.name().prefix() {
+        Some(p) => format!("{}:", String::from_utf8_lossy(p.as_ref())),
+        None => String::new(),
+    };
+    edited_elem(&BytesStart::new(format!("{prefix}sheetFormatPr")), edits)
+}
+
+/// Apply `edits` to a worksheet part, adding a `<sheetFormatPr>` in schema
+/// position (before `<cols>` / `<sheetData>`) if there is none.
+pub fn patch_sheet_format_pr(sheet_xml: &str, edits: &[SheetFormatEdit]) -> Result<String, String> {
+    let mut reader = XmlReader::from_str(sheet_xml);
+    reader.config_mut().trim_text(false);
+    let mut writer = XmlWriter::new(Vec::new());
+    let mut buf: Vec<u8> = Vec::new();
+    let write_err = |err: std::io::Error| format!("XML write error: {err}");
+
+    let mut done = false;
+    loop {
+        let event = match reader.read_event_into(&mut buf) {
+            Ok(Event::Eof) => break,
+            Ok(Event::Empty(e)) if !done && e.local_name().as_ref() == b"sheetFormatPr" => {
+                done = true;
+                Event::Empty(edited_elem(&e, edits)?)
+            }
+            Ok(Event::Start(e)) if !done && e.local_name().as_ref() == b"sheetFormatPr" => {
+                done = true;
+                Event::Start(edited_elem(&e, edits)?)
+            }
+            Ok(Event::Start(e)) if !done && follows_format_pr(&e) => {
+                done = true;
+                let format_pr = new_elem(&e, edits)?;
+                writer
+                    .write_event(Event::Empty(format_pr))
+                    .map_err(write_err)?;
+                Event::Start(e.into_owned())
+            }
+            Ok(Event::Empty(e)) if !done && follows_format_pr(&e) => {
+                done = true;
+                let format_pr = new_elem(&e, edits)?;
+                writer
+                    .write_event(Event::Empty(format_pr))
+                    .map_err(write_err)?;
+                Event::Empty(e.into_owned())
+            }
+            Ok(e) => e.into_owned(),
+            Err(e) => return Err(format!("XML parse error: {e}")),
+        };
+        writer.write_event(event).map_err(write_err)?;
+        buf.clear();
+    }
+
+    String::from_utf8(writer.into_inner()).map_err(|e| format!("Worksheet XML not UTF-8: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(key: &str, value: Option<&str>) -> SheetFormatEdit {
+        (key.to_string(), value.map(str::to_string))
+    }
+
+    #[test]
+    fn test_patch_existing_sheet_format_pr() {
+        let xml = r#"<worksheet><sheetFormatPr defaultRowHeight="15"/><sheetData/></worksheet>"#;
+        let out = patch_sheet_format_pr(
+            xml,
+            &[
+                edit("defaultColWidth", Some("12.5")),
+                edit("defaultRowHeight", Some("20")),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            concat!(
+                r#"<worksheet><sheetFormatPr defaultColWidth="12.5" defaultRowHeight="20"/>"#,
+                r#"<sheetData/></worksheet>"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_patch_inserts_before_cols() {
+        let xml = concat!(
+            r#"<worksheet><dimension ref="A1"/>"#,
+            r#"<cols><col min="1"/></cols><sheetData/></worksheet>"#
+        );
+        let out = patch_sheet_format_pr(xml, &[edit("zeroHeight", Some("1"))]).unwrap();
+        assert_eq!(
+            out,
+            concat!(
+                r#"<worksheet><dimension ref="A1"/><sheetFormatPr zeroHeight="1"/>"#,
+                r#"<cols><col min="1"/></cols><sheetData/></worksheet>"#
+            )
+        );
+
+        let out = patch_sheet_format_pr(&out, &[edit("zeroHeight", None)]).unwrap();
+        assert!(out.contains("<sheetFormatPr/><cols>"));
+    }
+
+    #[test]
+    fn test_read_sheet_format_pr() {
+        let xml = r#"<x:worksheet><x:sheetFormatPr defaultRowHeight="15" zeroHeight="1"/>
+            <x:sheetData/></x:worksheet>"#;
+        assert_eq!(
+            read_sheet_format_pr(xml).unwrap(),
+            Some(vec![
+                ("defaultRowHeight".to_string(), "15".to_string()),
+                ("zeroHeight".to_string(), "1".to_string()),
+            ])
+        );
+        let late = r#"<worksheet><sheetData/><sheetFormatPr zeroHeight="1"/></worksheet>"#;
+        assert_eq!(read_sheet_format_pr(late).unwrap(), None);
+    }
+}
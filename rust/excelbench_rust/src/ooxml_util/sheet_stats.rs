@@ -0,0 +1,362 @@
+//! Cell statistics of a worksheet part in one streaming pass
+//! (`CalamineBook.sheet_stats()`).
+//!
+//! The part is read straight from the archive, so a sheet is never
+//! materialized as a range: memory stays flat however large it is. Numeric
+//! cells count as dates when their `cellXfs` number format is a date or time
+//! format, the same rule calamine applies when it reads values.
+//! [`styled_cells`] makes the same pass to locate the cells that use given
+//! styles (`CalamineBook` uses it to find time-of-day cells).
+
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader as XmlReader;
+
+use super::attr_value;
+use crate::cell_ref::CellRef;
+use crate::numfmt;
+
+/// Type of a cell's (cached) value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ValueKind {
+    String,
+    Number,
+    Date,
+    Boolean,
+    Error,
+}
+
+/// Counts over the non-empty cells of a sheet.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SheetStats {
+    /// Cells with a value or a formula.
+    pub cells: u64,
+    pub formulas: u64,
+    pub strings: u64,
+    pub numbers: u64,
+    pub dates: u64,
+    pub booleans: u64,
+    pub errors: u64,
+    /// 0-based (first row, first col, last row, last col) of those cells.
+    pub bounds: Option<(u32, u32, u32, u32)>,
+}
+
+impl SheetStats {
+    /// Record one non-empty cell. A formula without a cached value has no
+    /// `kind`.
+    pub fn add(&mut self, row: u32, col: u32, kind: Option<ValueKind>, formula: bool) {
+        self.cells += 1;
+        self.formulas += u64::from(formula);
+        match kind {
+            Some(ValueKind::String) => self.strings += 1,
+            Some(ValueKind::Number) => self.numbers += 1,
+            Some(ValueKind::Date) => self.dates += 1,
+            Some(ValueKind::Boolean) => self.booleans += 1,
+            Some(ValueKind::Error) => self.errors += 1,
+            None => {}
+        }
+        self.bounds = Some(match self.bounds {
+            Some((r0, c0, r1, c1)) => (r0.min(row), c0.min(col), r1.max(row), c1.max(col)),
+            None => (row, col, row, col),
+        });
+    }
+
+    /// Used range in A1 form (`"B2:D10"`), None for an empty sheet.
+    pub fn used_range(&self) -> Option<String> {
+        let (r0, c0, r1, c1) = self.bounds?;
+        let (start, end) = (CellRef::new(r0, c0).to_a1(), CellRef::new(r1, c1).to_a1());
+        Some(if start == end {
+            start
+        } else {
+            format!("{start}:{end}")
+        })
+    }
+}
+
+/// For each `cellXfs` entry of `styles.xml`, whether its number format shows
+/// a date or time.
+pub(crate) fn date_styles(styles_xml: &str) -> Result<Vec<bool>, String> {
+    xf_styles(styles_xml, numfmt::is_date_format_id)
+}
+
+/// For each `cellXfs` entry of `styles.xml`, whether its number format shows
+/// only a time of day.
+pub(crate) fn time_styles(styles_xml: &str) -> Result<Vec<bool>, String> {
+    xf_styles(styles_xml, numfmt::is_time_format_id)
+}
+
+/// `matches(numFmtId, formatCode)` for each `cellXfs` entry.
+fn xf_styles(
+    styles_xml: &str,
+    matches: fn(u32, Option<&str>) -> bool,
+) -> Result<Vec<bool>, String> {
+    let mut reader = XmlReader::from_str(styles_xml);
+    let mut codes: HashMap<u32, String> = HashMap::new();
+    let mut xf_ids: Vec<u32> = Vec::new();
+    let mut in_cell_xfs = false;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                b"numFmt" => {
+                    let id = attr_value(&e, b"numFmtId").and_then(|v| v.parse().ok());
+                    if let (Some(id), Some(code)) = (id, attr_value(&e, b"formatCode")) {
+                        codes.insert(id, code);
+                    }
+                }
+                b"cellXfs" => in_cell_xfs = true,
+                b"xf" if in_cell_xfs => xf_ids.push(
+                    attr_value(&e, b"numFmtId")
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(0),
+                ),
+                _ => {}
+            },
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"cellXfs" => in_cell_xfs = false,
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Failed to parse styles.xml: {e}")),
+            _ => {}
+        }
+    }
+    Ok(xf_ids
+        .into_iter()
+        .map(|id| matches(id, codes.get(&id).map(String::as_str)))
+        .collect())
+}
+
+/// The `<c>` being read.
+#[derive(Default)]
+struct OpenCell {
+    row: u32,
+    col: u32,
+    t: Option<String>,
+    style: usize,
+    has_value: bool,
+    formula: bool,
+}
+
+impl OpenCell {
+    fn start(e: &BytesStart<'_>, row: u32, next_col: u32) -> Self {
+        let pos = attr_value(e, b"r").and_then(|r| CellRef::parse(&r).ok());
+        Self {
+            row: pos.map_or(row, |p| p.row),
+            col: pos.map_or(next_col, |p| p.col),
+            t: attr_value(e, b"t"),
+            style: attr_value(e, b"s")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            ..Self::default()
+        }
+    }
+
+    fn kind(&self, date_styles: &[bool]) -> Option<ValueKind> {
+        if !self.has_value {
+            return None;
+        }
+        Some(match self.t.as_deref() {
+            Some("s" | "str" | "inlineStr") => ValueKind::String,
+            Some("b") => ValueKind::Boolean,
+            Some("e") => ValueKind::Error,
+            Some("d") => ValueKind::Date,
+            _ if date_styles.get(self.style).copied().unwrap_or(false) => ValueKind::Date,
+            _ => ValueKind::Number,
+        })
+    }
+}
+
+/// Stream a worksheet part and count its cells. `date_styles` comes from
+/// [`date_styles`]; cells without an `r` attribute follow the previous one.
+pub(crate) fn scan_sheet<R: BufRead>(xml: R, date_styles: &[bool]) -> Result<SheetStats, String> {
+    let mut reader = XmlReader::from_reader(xml);
+    let mut buf: Vec<u8> = Vec::new();
+    let mut stats = SheetStats::default();
+    // 0-based position the next `<row>` / `<c>` defaults to.
+    let (mut row, mut next_col) = (0u32, 0u32);
+    let mut cell: Option<OpenCell> = None;
+    let mut in_v = false;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => match e.local_name().as_ref() {
+                b"row" => {
+                    row = attr_value(&e, b"r")
+                        .and_then(|r| r.parse::<u32>().ok())
+                        .map_or(row, |r| r.saturating_sub(1));
+                    next_col = 0;
+                }
+                b"c" => cell = Some(OpenCell::start(&e, row, next_col)),
+                b"f" => {
+                    if let Some(c) = cell.as_mut() {
+                        c.formula = true;
+                    }
+                }
+                b"v" => in_v = true,
+                b"is" => {
+                    if let Some(c) = cell.as_mut() {
+                        c.has_value = true;
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                // An empty row still takes its place in the numbering.
+                b"row" => {
+                    row = attr_value(&e, b"r")
+                        .and_then(|r| r.parse::<u32>().ok())
+                        .unwrap_or(row + 1);
+                }
+                b"c" => {
+                    // Style-only cell: not counted, but it holds a column.
+                    next_col = OpenCell::start(&e, row, next_col).col + 1;
+                }
+                b"f" => {
+                    if let Some(c) = cell.as_mut() {
+                        c.formula = true;
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Text(t)) if in_v => {
+                if let Some(c) = cell
+                    .as_mut()
+                    .filter(|_| !t.iter().all(u8::is_ascii_whitespace))
+                {
+                    c.has_value = true;
+                }
+            }
+            Ok(Event::End(e)) => match e.local_name().as_ref() {
+                b"v" => in_v = false,
+                b"c" => {
+                    if let Some(c) = cell.take() {
+                        if c.has_value || c.formula {
+                            stats.add(c.row, c.col, c.kind(date_styles), c.formula);
+                        }
+                        next_col = c.col + 1;
+                    }
+                }
+                b"row" => row += 1,
+                b"sheetData" => break,
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Failed to parse worksheet: {e}")),
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(stats)
+}
+
+/// 0-based (row, col) of every `<c>` whose style is flagged in `styles`
+/// (from [`date_styles`] or [`time_styles`]), in document order. Positions
+/// follow the same rules as [`scan_sheet`].
+pub(crate) fn styled_cells<R: BufRead>(xml: R, styles: &[bool]) -> Result<Vec<(u32, u32)>, String> {
+    let mut reader = XmlReader::from_reader(xml);
+    let mut buf: Vec<u8> = Vec::new();
+    let mut out = Vec::new();
+    let (mut row, mut next_col) = (0u32, 0u32);
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"row" => {
+                row = attr_value(&e, b"r")
+                    .and_then(|r| r.parse::<u32>().ok())
+                    .map_or(row, |r| r.saturating_sub(1));
+                next_col = 0;
+            }
+            Ok(Event::Empty(e)) if e.local_name().as_ref() == b"row" => {
+                row = attr_value(&e, b"r")
+                    .and_then(|r| r.parse::<u32>().ok())
+                    .unwrap_or(row + 1);
+            }
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.local_name().as_ref() == b"c" => {
+                let c = OpenCell::start(&e, row, next_col);
+                if styles.get(c.style).copied().unwrap_or(false) {
+                    out.push((c.row, c.col));
+                }
+                next_col = c.col + 1;
+            }
+            Ok(Event::End(e)) => match e.local_name().as_ref() {
+                b"row" => row += 1,
+                b"sheetData" => break,
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Failed to parse worksheet: {e}")),
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STYLES: &str = concat!(
+        r#"<styleSheet><numFmts count="2"><numFmt numFmtId="164" formatCode="0.000"/>"#,
+        r#"<numFmt numFmtId="165" formatCode="yyyy\-mm\-dd"/></numFmts>"#,
+        r#"<cellStyleXfs count="1"><xf numFmtId="14"/></cellStyleXfs>"#,
+        r#"<cellXfs count="4"><xf numFmtId="0"/><xf numFmtId="164"/><xf numFmtId="165"/>"#,
+        r#"<xf numFmtId="22"/></cellXfs></styleSheet>"#
+    );
+
+    #[test]
+    fn test_date_styles() {
+        assert_eq!(date_styles(STYLES).unwrap(), vec![false, false, true, true]);
+        assert_eq!(time_styles(STYLES).unwrap(), vec![false; 4]);
+        let times =
+            r#"<styleSheet><cellXfs><xf numFmtId="0"/><xf numFmtId="21"/></cellXfs></styleSheet>"#;
+        assert_eq!(time_styles(times).unwrap(), vec![false, true]);
+    }
+
+    #[test]
+    fn test_styled_cells() {
+        let xml = concat!(
+            r#"<worksheet><sheetData><row r="2"><c r="B2" s="1"><v>0.5</v></c>"#,
+            r#"<c s="1"/><c r="E2" s="2"><v>1</v></c></row><row><c s="1"><v>0.25</v></c></row>"#,
+            r#"</sheetData></worksheet>"#
+        );
+        let cells = styled_cells(xml.as_bytes(), &[false, true, false]).unwrap();
+        assert_eq!(cells, vec![(1, 1), (1, 2), (2, 0)]);
+        assert!(styled_cells(xml.as_bytes(), &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_scan_sheet_counts_types_and_bounds() {
+        let xml = concat!(
+            r#"<worksheet><dimension ref="A1:Z99"/><sheetData>"#,
+            r#"<row r="2"><c r="B2" t="s"><v>0</v></c><c r="C2"><v>1.5</v></c>"#,
+            r#"<c r="D2" s="2"><v>45000</v></c><c r="E2" s="1"/></row>"#,
+            r#"<row r="4"><c r="B4" t="b"><v>1</v></c><c t="e"><v>#N/A</v></c>"#,
+            r#"<c r="E4"><f>C2*2</f></c><c r="F4" t="str"><f>"x"</f><v>x</v></c>"#,
+            r#"<c r="G4" t="inlineStr"><is><t>hi</t></is></c><c r="H4" t="s"><v></v></c></row>"#,
+            r#"</sheetData></worksheet>"#
+        );
+        let stats = scan_sheet(xml.as_bytes(), &date_styles(STYLES).unwrap()).unwrap();
+        assert_eq!(
+            stats,
+            SheetStats {
+                cells: 8,
+                formulas: 2,
+                strings: 3,
+                numbers: 1,
+                dates: 1,
+                booleans: 1,
+                errors: 1,
+                bounds: Some((1, 1, 3, 6)),
+            }
+        );
+        assert_eq!(stats.used_range().as_deref(), Some("B2:G4"));
+    }
+
+    #[test]
+    fn test_scan_sheet_without_cell_refs() {
+        let xml = r#"<worksheet><sheetData><row><c><v>1</v></c><c/><c><v>2</v></c></row><row/><row><c t="s"><v>0</v></c></row></sheetData></worksheet>"#;
+        let stats = scan_sheet(xml.as_bytes(), &[]).unwrap();
+        assert_eq!((stats.cells, stats.numbers, stats.strings), (3, 2, 1));
+        assert_eq!(stats.used_range().as_deref(), Some("A1:C3"));
+        assert_eq!(SheetStats::default().used_range(), None);
+    }
+}
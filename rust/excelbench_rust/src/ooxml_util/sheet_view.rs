@@ -0,0 +1,180 @@
+//! The first `<sheetView>` of a worksheet: zoom, gridlines, scroll
+//! position, panes and selection.
+//!
+//! Only the part before `<sheetData>` is parsed, so reading a view costs the
+//! same for any sheet size. Errors are plain strings; PyO3 callers wrap them.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader as XmlReader;
+
+use super::attr_value;
+
+/// A `<pane>` (freeze or split).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PaneInfo {
+    /// `split`, `frozen` or `frozenSplit`; absent means `split`.
+    pub state: String,
+    pub x_split: f64,
+    pub y_split: f64,
+    pub top_left_cell: Option<String>,
+    pub active_pane: Option<String>,
+}
+
+impl PaneInfo {
+    pub fn is_frozen(&self) -> bool {
+        self.state == "frozen" || self.state == "frozenSplit"
+    }
+}
+
+/// One `<selection>`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SelectionInfo {
+    pub pane: Option<String>,
+    pub active_cell: Option<String>,
+    pub sqref: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetViewInfo {
+    pub zoom: u32,
+    pub show_gridlines: bool,
+    pub tab_selected: bool,
+    pub right_to_left: bool,
+    /// `normal`, `pageBreakPreview` or `pageLayout`.
+    pub view: String,
+    pub top_left_cell: Option<String>,
+    pub pane: Option<PaneInfo>,
+    pub selections: Vec<SelectionInfo>,
+}
+
+impl Default for SheetViewInfo {
+    fn default() -> Self {
+        Self {
+            zoom: 100,
+            show_gridlines: true,
+            tab_selected: false,
+            right_to_left: false,
+            view: "normal".to_string(),
+            top_left_cell: None,
+            pane: None,
+            selections: Vec::new(),
+        }
+    }
+}
+
+fn flag(e: &BytesStart<'_>, key: &[u8], default: bool) -> bool {
+    match attr_value(e, key).as_deref() {
+        Some("1") | Some("true") => true,
+        Some("0") | Some("false") => false,
+        _ => default,
+    }
+}
+
+fn num(e: &BytesStart<'_>, key: &[u8]) -> f64 {
+    attr_value(e, key)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0.0)
+}
+
+/// Parse the first sheet view of a worksheet, or None when it has none.
+pub fn parse_sheet_view(sheet_xml: &str) -> Result<Option<SheetViewInfo>, String> {
+    let mut reader = XmlReader::from_str(sheet_xml);
+    let mut buf: Vec<u8> = Vec::new();
+    let mut view: Option<SheetViewInfo> = None;
+
+    loop {
+        buf.clear();
+        let (e, empty) = match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => (e, false),
+            Ok(Event::Empty(e)) => (e, true),
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"sheetView" && view.is_some() => {
+                return Ok(view);
+            }
+            Ok(Event::Eof) => return Ok(view),
+            Err(e) => return Err(format!("Failed to parse sheet view: {e}")),
+            _ => continue,
+        };
+        match e.local_name().as_ref() {
+            b"sheetView" => {
+                let v = SheetViewInfo {
+                    zoom: attr_value(&e, b"zoomScale")
+                        .and_then(|z| z.parse().ok())
+                        .unwrap_or(100),
+                    show_gridlines: flag(&e, b"showGridLines", true),
+                    tab_selected: flag(&e, b"tabSelected", false),
+                    right_to_left: flag(&e, b"rightToLeft", false),
+                    view: attr_value(&e, b"view").unwrap_or_else(|| "normal".to_string()),
+                    top_left_cell: attr_value(&e, b"topLeftCell"),
+                    ..Default::default()
+                };
+                if empty {
+                    return Ok(Some(v));
+                }
+                view = Some(v);
+            }
+            b"pane" => {
+                if let Some(v) = view.as_mut() {
+                    v.pane = Some(PaneInfo {
+                        state: attr_value(&e, b"state").unwrap_or_else(|| "split".to_string()),
+                        x_split: num(&e, b"xSplit"),
+                        y_split: num(&e, b"ySplit"),
+                        top_left_cell: attr_value(&e, b"topLeftCell"),
+                        active_pane: attr_value(&e, b"activePane"),
+                    });
+                }
+            }
+            b"selection" => {
+                if let Some(v) = view.as_mut() {
+                    v.selections.push(SelectionInfo {
+                        pane: attr_value(&e, b"pane"),
+                        active_cell: attr_value(&e, b"activeCell"),
+                        sqref: attr_value(&e, b"sqref"),
+                    });
+                }
+            }
+            b"sheetData" => return Ok(view),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frozen_view_with_selection() {
+        let xml = concat!(
+            r#"<worksheet><sheetViews>"#,
+            r#"<sheetView showGridLines="0" tabSelected="1" zoomScale="85" workbookViewId="0">"#,
+            r#"<pane xSplit="1" ySplit="2" topLeftCell="B3" activePane="bottomRight" "#,
+            r#"state="frozen"/><selection pane="topRight"/>"#,
+            r#"<selection pane="bottomRight" activeCell="C5" sqref="C5:D6"/></sheetView>"#,
+            r#"<sheetView workbookViewId="1" zoomScale="50"/></sheetViews>"#,
+            r#"<sheetData/></worksheet>"#
+        );
+        let v = parse_sheet_view(xml).unwrap().unwrap();
+        assert_eq!(v.zoom, 85);
+        assert!(!v.show_gridlines && v.tab_selected);
+        assert_eq!(v.view, "normal");
+        let pane = v.pane.unwrap();
+        assert!(pane.is_frozen());
+        assert_eq!((pane.x_split, pane.y_split), (1.0, 2.0));
+        assert_eq!(pane.top_left_cell.as_deref(), Some("B3"));
+        assert_eq!(v.selections.len(), 2);
+        assert_eq!(v.selections[1].active_cell.as_deref(), Some("C5"));
+        assert_eq!(v.selections[1].sqref.as_deref(), Some("C5:D6"));
+    }
+
+    #[test]
+    fn test_defaults_and_missing_view() {
+        let v = parse_sheet_view(r#"<worksheet><sheetViews><sheetView workbookViewId="0"/>"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(v, SheetViewInfo::default());
+        assert_eq!(
+            parse_sheet_view("<worksheet><sheetData/></worksheet>").unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Table parts (`xl/tables/tableN.xml`): name, range, header/totals rows
+//! and column names, which structured references (`Table1[Amount]`) resolve
+//! against. Errors are plain strings; PyO3 callers wrap them.
+
+use quick_xml::events::Event;
+use quick_xml::Reader as XmlReader;
+
+use super::attr_value;
+
+/// One table definition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableDef {
+    pub name: String,
+    /// `displayName`, the name formulas use; usually equal to `name`.
+    pub display_name: String,
+    /// A1 range the table covers, header and totals rows included.
+    pub ref_range: String,
+    pub header_row: bool,
+    pub totals_row: bool,
+    pub style: Option<String>,
+    pub columns: Vec<String>,
+    pub autofilter: bool,
+}
+
+/// Parse a table part.
+pub fn parse_table_xml(xml: &str) -> Result<TableDef, String> {
+    let mut reader = XmlReader::from_str(xml);
+    let mut table = TableDef {
+        name: String::new(),
+        display_name: String::new(),
+        ref_range: String::new(),
+        header_row: true,
+        totals_row: false,
+        style: None,
+        columns: Vec::new(),
+        autofilter: false,
+    };
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                b"table" => {
+                    let name = attr_value(&e, b"name");
+                    let display_name = attr_value(&e, b"displayName");
+                    table.name = name.clone().or(display_name.clone()).unwrap_or_default();
+                    table.display_name = display_name.or(name).unwrap_or_default();
+                    table.ref_range = attr_value(&e, b"ref").unwrap_or_default();
+                    table.header_row = attr_value(&e, b"headerRowCount").is_none_or(|v| v != "0");
+                    table.totals_row = attr_value(&e, b"totalsRowCount").is_some_and(|v| v != "0");
+                }
+                b"tableColumn" => {
+                    if let Some(name) = attr_value(&e, b"name") {
+                        table.columns.push(name);
+                    }
+                }
+                b"autoFilter" => table.autofilter = true,
+                b"tableStyleInfo" => {
+                    table.style = attr_value(&e, b"name").filter(|s| !s.is_empty());
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Failed to parse table XML: {e}")),
+            _ => {}
+        }
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_table_xml() {
+        let xml = concat!(
+            r#"<table xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" id="1" "#,
+            r#"name="Sales" displayName="Sales" ref="B2:D6" totalsRowCount="1">"#,
+            r#"<autoFilter ref="B2:D5"/><tableColumns count="3"><tableColumn id="1" name="Region"/>"#,
+            r#"<tableColumn id="2" name="Q1"/><tableColumn id="3" name="Q2"/></tableColumns>"#,
+            r#"<tableStyleInfo name="TableStyleMedium2" showRowStripes="1"/></table>"#
+        );
+        let table = parse_table_xml(xml).unwrap();
+        assert_eq!(
+            table,
+            TableDef {
+                name: "Sales".to_string(),
+                display_name: "Sales".to_string(),
+                ref_range: "B2:D6".to_string(),
+                header_row: true,
+                totals_row: true,
+                style: Some("TableStyleMedium2".to_string()),
+                columns: vec!["Region".to_string(), "Q1".to_string(), "Q2".to_string()],
+                autofilter: true,
+            }
+        );
+
+        let bare =
+            parse_table_xml(r#"<table displayName="T" ref="A1:A3" headerRowCount="0"/>"#).unwrap();
+        assert_eq!((bare.name.as_str(), bare.header_row), ("T", false));
+        assert_eq!((bare.style, bare.autofilter), (None, false));
+    }
+}
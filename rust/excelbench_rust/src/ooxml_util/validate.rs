@@ -0,0 +1,975 @@
+//! Structural validation of xlsx packages.
+//!
+//! A neutral referee for "is this file corrupt, or is Excel just lenient?":
+//! every check works on the raw package (ZIP entries and part XML) without
+//! going through any backend's object model. Findings are machine-readable
+//! (`code`, `part`, `message`) so test harnesses can assert on them.
+//!
+//! Checks, in order:
+//! - ZIP integrity: every entry can be read and passes its CRC check.
+//! - Content types: every part is covered by a Default or Override, and
+//!   overrides point at parts that exist.
+//! - Relationships: every internal target of every `.rels` part exists.
+//! - styles.xml: cellXfs font/fill/border/numFmt/xfId indices are in bounds.
+//! - sharedStrings.xml: `uniqueCount` matches the number of `<si>` items.
+//! - Worksheets: rows and cells are in ascending order, cell refs match their
+//!   row, and shared-string and style indices are in bounds.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{Read, Seek};
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader as XmlReader;
+use zip::ZipArchive;
+
+use super::normalize_zip_path;
+use super::parts::{local_attr, parse_content_types, parse_relationships, ContentTypes};
+use crate::cell_ref::CellRef;
+
+/// Findings recorded per (code, part) before the rest are only counted.
+const MAX_FINDINGS_PER_CHECK: usize = 25;
+
+/// First custom number format id; lower ids are built in.
+const FIRST_CUSTOM_NUM_FMT: u32 = 164;
+
+// ---------------------------------------------------------------------------
+// Findings
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The package is structurally broken.
+    Error,
+    /// Tolerated by Excel but inconsistent.
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Stable dotted identifier, e.g. `rels.missing_target`.
+    pub code: &'static str,
+    /// Zip entry the finding is about (empty for package-level findings).
+    pub part: String,
+    pub message: String,
+}
+
+/// Collects findings, capping repeats of the same check on the same part.
+#[derive(Default)]
+struct Findings {
+    items: Vec<Finding>,
+    seen: HashMap<(&'static str, String), usize>,
+}
+
+impl Findings {
+    fn push(&mut self, severity: Severity, code: &'static str, part: &str, message: String) {
+        let n = self.seen.entry((code, part.to_string())).or_insert(0);
+        *n += 1;
+        if *n <= MAX_FINDINGS_PER_CHECK {
+            self.items.push(Finding {
+                severity,
+                code,
+                part: part.to_string(),
+                message,
+            });
+        }
+    }
+
+    fn error(&mut self, code: &'static str, part: &str, message: String) {
+        self.push(Severity::Error, code, part, message);
+    }
+
+    fn warning(&mut self, code: &'static str, part: &str, message: String) {
+        self.push(Severity::Warning, code, part, message);
+    }
+
+    /// Append a note for every check whose repeats were dropped.
+    fn finish(mut self) -> Vec<Finding> {
+        let mut truncated: Vec<(&'static str, String, usize)> = self
+            .seen
+            .iter()
+            .filter(|(_, n)| **n > MAX_FINDINGS_PER_CHECK)
+            .map(|((code, part), n)| (*code, part.clone(), n - MAX_FINDINGS_PER_CHECK))
+            .collect();
+        truncated.sort();
+        for (code, part, extra) in truncated {
+            self.items.push(Finding {
+                severity: Severity::Warning,
+                code: "report.truncated",
+                part,
+                message: format!("{extra} more '{code}' findings omitted"),
+            });
+        }
+        self.items
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Package
+// ---------------------------------------------------------------------------
+
+/// Readable entries of the archive, by name.
+struct Package {
+    parts: HashMap<String, Vec<u8>>,
+    /// Lower-cased part names (OPC part names are case-insensitive).
+    names_ci: HashSet<String>,
+}
+
+impl Package {
+    fn exists(&self, part: &str) -> bool {
+        self.names_ci.contains(&part.to_ascii_lowercase())
+    }
+
+    fn text(&self, part: &str) -> Option<String> {
+        self.parts
+            .get(part)
+            .map(|b| String::from_utf8_lossy(b).into_owned())
+    }
+
+    /// Parts whose content type ends with `suffix`, in name order.
+    fn parts_of_type(&self, types: &ContentTypes, suffix: &str) -> Vec<String> {
+        let mut out: Vec<String> = self
+            .parts
+            .keys()
+            .filter(|p| types.content_type(p).is_some_and(|ct| ct.ends_with(suffix)))
+            .cloned()
+            .collect();
+        out.sort();
+        out
+    }
+}
+
+/// Read every entry, recording unreadable and CRC-failing ones.
+fn read_package<R: Read + Seek>(zip: &mut ZipArchive<R>, findings: &mut Findings) -> Package {
+    let mut parts = HashMap::new();
+    for i in 0..zip.len() {
+        let mut entry = match zip.by_index(i) {
+            Ok(entry) => entry,
+            Err(e) => {
+                findings.error("zip.bad_entry", "", format!("Entry #{i} unreadable: {e}"));
+                continue;
+            }
+        };
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        match entry.read_to_end(&mut bytes) {
+            Ok(_) => {
+                parts.insert(name, bytes);
+            }
+            Err(e) => findings.error("zip.corrupt_entry", &name, format!("Cannot read: {e}")),
+        }
+    }
+    let names_ci = parts.keys().map(|n| n.to_ascii_lowercase()).collect();
+    Package { parts, names_ci }
+}
+
+/// Source directory of a `.rels` part (`xl/_rels/workbook.xml.rels` → `xl/`).
+fn rels_source_dir(rels_part: &str) -> Option<&str> {
+    let idx = rels_part.rfind("_rels/")?;
+    Some(&rels_part[..idx])
+}
+
+/// Decode `%XX` escapes in a relationship target.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Resolve a relationship target against the source part's directory.
+fn resolve_target(source_dir: &str, target: &str) -> String {
+    let target = target.split('#').next().unwrap_or_default();
+    let joined = match target.strip_prefix('/') {
+        Some(abs) => abs.to_string(),
+        None => format!("{source_dir}{target}"),
+    };
+    normalize_zip_path(&percent_decode(&joined))
+}
+
+// ---------------------------------------------------------------------------
+// Checks
+// ---------------------------------------------------------------------------
+
+fn check_content_types(pkg: &Package, findings: &mut Findings) -> Option<ContentTypes> {
+    const PART: &str = "[Content_Types].xml";
+    let Some(xml) = pkg.text(PART) else {
+        findings.error(
+            "content_types.missing",
+            PART,
+            "Package has no [Content_Types].xml".into(),
+        );
+        return None;
+    };
+    let types = match parse_content_types(&xml) {
+        Ok(types) => types,
+        Err(e) => {
+            findings.error("xml.malformed", PART, e);
+            return None;
+        }
+    };
+
+    let mut names: Vec<&String> = pkg.parts.keys().collect();
+    names.sort();
+    for name in names {
+        if name != PART && types.content_type(name).is_none() {
+            findings.error(
+                "content_types.uncovered",
+                name,
+                "No Default or Override content type".into(),
+            );
+        }
+    }
+    let mut overrides: Vec<&String> = types.overrides.keys().collect();
+    overrides.sort();
+    for part in overrides {
+        if !pkg.exists(part) {
+            findings.warning(
+                "content_types.dangling_override",
+                PART,
+                format!("Override for missing part /{part}"),
+            );
+        }
+    }
+    Some(types)
+}
+
+fn check_relationships(pkg: &Package, findings: &mut Findings) {
+    let mut rels_parts: Vec<&String> = pkg.parts.keys().filter(|n| n.ends_with(".rels")).collect();
+    rels_parts.sort();
+    for rels_part in rels_parts {
+        let Some(source_dir) = rels_source_dir(rels_part) else {
+            continue;
+        };
+        let xml = pkg.text(rels_part).unwrap_or_default();
+        let rels = match parse_relationships(&xml) {
+            Ok(rels) => rels,
+            Err(e) => {
+                findings.error("xml.malformed", rels_part, e);
+                continue;
+            }
+        };
+        let mut ids = HashSet::new();
+        for rel in rels {
+            if !ids.insert(rel.id.clone()) {
+                findings.error(
+                    "rels.duplicate_id",
+                    rels_part,
+                    format!("Relationship id {} is used more than once", rel.id),
+                );
+            }
+            if rel.external {
+                continue;
+            }
+            let target = resolve_target(source_dir, &rel.target);
+            if !pkg.exists(&target) {
+                findings.error(
+                    "rels.missing_target",
+                    rels_part,
+                    format!("{} targets missing part {target}", rel.id),
+                );
+            }
+        }
+    }
+}
+
+/// Element counts and cellXfs references from styles.xml.
+#[derive(Default)]
+struct StyleTables {
+    fonts: usize,
+    fills: usize,
+    borders: usize,
+    cell_style_xfs: usize,
+    num_fmt_ids: HashSet<u32>,
+    /// (fontId, fillId, borderId, numFmtId, xfId) per cellXfs entry.
+    cell_xfs: Vec<[Option<u32>; 5]>,
+    /// Declared `count` attributes by section.
+    declared: Vec<(&'static str, usize, usize)>,
+}
+
+fn parse_style_tables(xml: &str) -> Result<StyleTables, String> {
+    const SECTIONS: [(&str, &[u8]); 6] = [
+        ("numFmts", b"numFmt"),
+        ("fonts", b"font"),
+        ("fills", b"fill"),
+        ("borders", b"border"),
+        ("cellStyleXfs", b"xf"),
+        ("cellXfs", b"xf"),
+    ];
+
+    let mut reader = XmlReader::from_str(xml);
+    let mut buf: Vec<u8> = Vec::new();
+    let mut out = StyleTables::default();
+    let mut depth = 0usize;
+    // (section index, declared count, children seen)
+    let mut section: Option<(usize, Option<usize>, usize)> = None;
+
+    let id = |e: &BytesStart<'_>, key: &[u8]| local_attr(e, key).and_then(|v| v.parse().ok());
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| format!("Failed to parse styles XML: {e}"))?;
+        let (e, is_start) = match &event {
+            Event::Start(e) => (e, true),
+            Event::Empty(e) => (e, false),
+            Event::End(_) => {
+                depth = depth.saturating_sub(1);
+                if depth == 1 {
+                    if let Some((idx, declared, seen)) = section.take() {
+                        let name = SECTIONS[idx].0;
+                        match name {
+                            "fonts" => out.fonts = seen,
+                            "fills" => out.fills = seen,
+                            "borders" => out.borders = seen,
+                            "cellStyleXfs" => out.cell_style_xfs = seen,
+                            _ => {}
+                        }
+                        if let Some(declared) = declared {
+                            out.declared.push((name, declared, seen));
+                        }
+                    }
+                }
+                buf.clear();
+                continue;
+            }
+            Event::Eof => break,
+            _ => {
+                buf.clear();
+                continue;
+            }
+        };
+
+        let local = e.local_name();
+        if depth == 1 {
+            if let Some(idx) = SECTIONS
+                .iter()
+                .position(|(s, _)| s.as_bytes() == local.as_ref())
+            {
+                let declared = local_attr(e, b"count").and_then(|v| v.parse().ok());
+                if is_start {
+                    section = Some((idx, declared, 0));
+                } else if let Some(declared) = declared {
+                    out.declared.push((SECTIONS[idx].0, declared, 0));
+                }
+            }
+        } else if depth == 2 {
+            if let Some((idx, _, seen)) = section.as_mut() {
+                let (name, child) = SECTIONS[*idx];
+                if local.as_ref() == child {
+                    *seen += 1;
+                    match name {
+                        "numFmts" => {
+                            if let Some(n) = id(e, b"numFmtId") {
+                                out.num_fmt_ids.insert(n);
+                            }
+                        }
+                        "cellXfs" => out.cell_xfs.push([
+                            id(e, b"fontId"),
+                            id(e, b"fillId"),
+                            id(e, b"borderId"),
+                            id(e, b"numFmtId"),
+                            id(e, b"xfId"),
+                        ]),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        if is_start {
+            depth += 1;
+        }
+        buf.clear();
+    }
+    Ok(out)
+}
+
+/// Check styles.xml and return the number of cellXfs entries.
+fn check_styles(part: &str, xml: &str, findings: &mut Findings) -> Option<usize> {
+    let tables = match parse_style_tables(xml) {
+        Ok(tables) => tables,
+        Err(e) => {
+            findings.error("xml.malformed", part, e);
+            return None;
+        }
+    };
+
+    for (section, declared, seen) in &tables.declared {
+        if declared != seen {
+            findings.warning(
+                "styles.count_mismatch",
+                part,
+                format!("<{section}> declares count={declared} but has {seen} entries"),
+            );
+        }
+    }
+
+    let limits = [
+        ("fontId", tables.fonts),
+        ("fillId", tables.fills),
+        ("borderId", tables.borders),
+    ];
+    for (i, xf) in tables.cell_xfs.iter().enumerate() {
+        for (slot, (attr, limit)) in limits.iter().enumerate() {
+            if let Some(v) = xf[slot] {
+                if v as usize >= *limit {
+                    findings.error(
+                        "styles.xf_index",
+                        part,
+                        format!("cellXfs[{i}] {attr}={v} but only {limit} defined"),
+                    );
+                }
+            }
+        }
+        if let Some(fmt) = xf[3] {
+            if fmt >= FIRST_CUSTOM_NUM_FMT && !tables.num_fmt_ids.contains(&fmt) {
+                findings.error(
+                    "styles.num_fmt",
+                    part,
+                    format!("cellXfs[{i}] numFmtId={fmt} is not defined in <numFmts>"),
+                );
+            }
+        }
+        if let Some(xf_id) = xf[4] {
+            if xf_id as usize >= tables.cell_style_xfs {
+                findings.error(
+                    "styles.xf_index",
+                    part,
+                    format!(
+                        "cellXfs[{i}] xfId={xf_id} but only {} cellStyleXfs defined",
+                        tables.cell_style_xfs
+                    ),
+                );
+            }
+        }
+    }
+    Some(tables.cell_xfs.len())
+}
+
+/// Check sharedStrings.xml and return the number of `<si>` items.
+fn check_shared_strings(part: &str, xml: &str, findings: &mut Findings) -> Option<usize> {
+    let mut reader = XmlReader::from_str(xml);
+    let mut buf: Vec<u8> = Vec::new();
+    let mut depth = 0usize;
+    let mut count = 0usize;
+    let mut unique_count: Option<usize> = None;
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                if depth == 0 {
+                    unique_count = local_attr(&e, b"uniqueCount").and_then(|v| v.parse().ok());
+                } else if depth == 1 && e.local_name().as_ref() == b"si" {
+                    count += 1;
+                }
+                depth += 1;
+            }
+            Ok(Event::Empty(e)) if depth == 1 && e.local_name().as_ref() == b"si" => count += 1,
+            Ok(Event::End(_)) => depth = depth.saturating_sub(1),
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                findings.error("xml.malformed", part, format!("Failed to parse: {e}"));
+                return None;
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+    if let Some(declared) = unique_count {
+        if declared != count {
+            findings.warning(
+                "sst.count_mismatch",
+                part,
+                format!("uniqueCount={declared} but {count} <si> items present"),
+            );
+        }
+    }
+    Some(count)
+}
+
+/// Check `<sheetData>` ordering and index bounds of one worksheet.
+fn check_worksheet(
+    part: &str,
+    xml: &str,
+    sst_len: Option<usize>,
+    xf_len: Option<usize>,
+    findings: &mut Findings,
+) {
+    let mut reader = XmlReader::from_str(xml);
+    let mut buf: Vec<u8> = Vec::new();
+
+    let mut in_sheet_data = false;
+    // 1-based number of the current row, and the highest row seen so far.
+    let mut row_num: u32 = 0;
+    let mut last_row: u32 = 0;
+    let mut next_col: u32 = 0;
+    // Cell being read: its A1 (for messages) and whether it is shared-string typed.
+    let mut cell: Option<(String, bool)> = None;
+    let mut in_value = false;
+    let mut value = String::new();
+
+    let check_style = |e: &BytesStart<'_>, what: &str, findings: &mut Findings| {
+        let (Some(s), Some(xf_len)) = (local_attr(e, b"s"), xf_len) else {
+            return;
+        };
+        match s.parse::<usize>() {
+            Ok(idx) if idx < xf_len => {}
+            _ => findings.error(
+                "sheet.style_index",
+                part,
+                format!("{what} style s={s} but styles.xml has {xf_len} cellXfs"),
+            ),
+        }
+    };
+
+    loop {
+        let event = match reader.read_event_into(&mut buf) {
+            Ok(event) => event,
+            Err(e) => {
+                findings.error("xml.malformed", part, format!("Failed to parse: {e}"));
+                return;
+            }
+        };
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let is_empty = matches!(event, Event::Empty(_));
+                match e.local_name().as_ref() {
+                    b"sheetData" => in_sheet_data = !is_empty,
+                    b"row" if in_sheet_data => {
+                        let explicit = local_attr(e, b"r");
+                        let r = match explicit.as_deref().map(str::parse::<u32>) {
+                            None => row_num + 1,
+                            Some(Ok(r)) if r >= 1 => r,
+                            Some(_) => {
+                                findings.error(
+                                    "sheet.bad_ref",
+                                    part,
+                                    format!(
+                                        "Invalid row number r={}",
+                                        explicit.as_deref().unwrap_or_default()
+                                    ),
+                                );
+                                row_num + 1
+                            }
+                        };
+                        if r <= last_row {
+                            findings.error(
+                                "sheet.row_order",
+                                part,
+                                format!("Row {r} follows row {last_row}"),
+                            );
+                        }
+                        last_row = last_row.max(r);
+                        row_num = r;
+                        next_col = 0;
+                        if local_attr(e, b"customFormat").is_some_and(|v| v == "1" || v == "true") {
+                            check_style(e, &format!("Row {r}"), findings);
+                        }
+                    }
+                    b"c" if in_sheet_data => {
+                        let col = match local_attr(e, b"r") {
+                            None => next_col,
+                            Some(a1) => match CellRef::parse(&a1) {
+                                Ok(c) => {
+                                    if c.row + 1 != row_num {
+                                        findings.error(
+                                            "sheet.cell_row_mismatch",
+                                            part,
+                                            format!("Cell {a1} is inside row {row_num}"),
+                                        );
+                                    }
+                                    if c.col < next_col {
+                                        findings.error(
+                                            "sheet.cell_order",
+                                            part,
+                                            format!("Cell {a1} is out of column order"),
+                                        );
+                                    }
+                                    c.col
+                                }
+                                Err(_) => {
+                                    findings.error(
+                                        "sheet.bad_ref",
+                                        part,
+                                        format!("Invalid cell reference r={a1}"),
+                                    );
+                                    next_col
+                                }
+                            },
+                        };
+                        next_col = next_col.max(col + 1);
+                        let a1 = CellRef::new(row_num.saturating_sub(1), col).to_a1();
+                        check_style(e, &format!("Cell {a1}"), findings);
+                        if !is_empty {
+                            let shared = local_attr(e, b"t").is_some_and(|t| t == "s");
+                            cell = Some((a1, shared));
+                        }
+                    }
+                    b"v" if cell.as_ref().is_some_and(|(_, shared)| *shared) && !is_empty => {
+                        in_value = true;
+                        value.clear();
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(ref t) if in_value => {
+                if let Ok(s) = t.unescape() {
+                    value.push_str(&s);
+                }
+            }
+            Event::End(ref e) => match e.local_name().as_ref() {
+                b"sheetData" => in_sheet_data = false,
+                b"v" if in_value => {
+                    in_value = false;
+                    let a1 = cell.as_ref().map(|(a1, _)| a1.as_str()).unwrap_or_default();
+                    match (value.trim().parse::<usize>(), sst_len) {
+                        (Ok(idx), Some(n)) if idx < n => {}
+                        (Ok(idx), Some(n)) => findings.error(
+                            "sheet.sst_index",
+                            part,
+                            format!("Cell {a1} references shared string {idx} of {n}"),
+                        ),
+                        (Ok(_), None) => findings.error(
+                            "sheet.sst_missing",
+                            part,
+                            format!(
+                                "Cell {a1} references a shared string but the package has none"
+                            ),
+                        ),
+                        (Err(_), _) => findings.error(
+                            "sheet.sst_index",
+                            part,
+                            format!("Cell {a1} has a non-numeric shared string index"),
+                        ),
+                    }
+                }
+                b"c" => cell = None,
+                _ => {}
+            },
+            Event::Eof => return,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Entry point
+// ---------------------------------------------------------------------------
+
+/// Validate an opened xlsx package. An empty result means no problems found.
+pub fn validate_archive<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Vec<Finding> {
+    let mut findings = Findings::default();
+    let pkg = read_package(zip, &mut findings);
+
+    let types = check_content_types(&pkg, &mut findings);
+    check_relationships(&pkg, &mut findings);
+
+    // Without content types, fall back to the conventional part names.
+    let find = |suffix: &str, default: &str| -> Vec<String> {
+        match &types {
+            Some(types) => pkg.parts_of_type(types, suffix),
+            None if pkg.parts.contains_key(default) => vec![default.to_string()],
+            None => Vec::new(),
+        }
+    };
+
+    let mut xf_len = None;
+    for part in find("styles+xml", "xl/styles.xml") {
+        let xml = pkg.text(&part).unwrap_or_default();
+        xf_len = check_styles(&part, &xml, &mut findings);
+    }
+    let mut sst_len = None;
+    for part in find("sharedStrings+xml", "xl/sharedStrings.xml") {
+        let xml = pkg.text(&part).unwrap_or_default();
+        sst_len = check_shared_strings(&part, &xml, &mut findings);
+    }
+
+    let sheets: Vec<String> = match &types {
+        Some(types) => pkg.parts_of_type(types, "worksheet+xml"),
+        None => {
+            let mut names: BTreeSet<String> = BTreeSet::new();
+            for name in pkg.parts.keys() {
+                if name.starts_with("xl/worksheets/") && name.ends_with(".xml") {
+                    names.insert(name.clone());
+                }
+            }
+            names.into_iter().collect()
+        }
+    };
+    for part in sheets {
+        let xml = pkg.text(&part).unwrap_or_default();
+        check_worksheet(&part, &xml, sst_len, xf_len, &mut findings);
+    }
+
+    findings.finish()
+}
+
+/// Validate raw package bytes; a non-ZIP input yields a single `zip.invalid` error.
+pub fn validate_bytes<R: Read + Seek>(source: R) -> Vec<Finding> {
+    match ZipArchive::new(source) {
+        Ok(mut zip) => validate_archive(&mut zip),
+        Err(e) => vec![Finding {
+            severity: Severity::Error,
+            code: "zip.invalid",
+            part: String::new(),
+            message: format!("Not a valid ZIP archive: {e}"),
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+  <Default Extension="xml" ContentType="application/xml"/>
+  <Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>
+  <Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>
+  <Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>
+  <Override PartName="/xl/sharedStrings.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sharedStrings+xml"/>
+</Types>"#;
+
+    const ROOT_RELS: &str = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>
+</Relationships>"#;
+
+    const WORKBOOK: &str = r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+  <sheets><sheet name="Data" sheetId="1" r:id="rId1"/></sheets>
+</workbook>"#;
+
+    const WORKBOOK_RELS: &str = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/>
+  <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+  <Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/sharedStrings" Target="/xl/sharedStrings.xml"/>
+  <Relationship Id="rId4" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/" TargetMode="External"/>
+</Relationships>"#;
+
+    const STYLES: &str = r#"<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+  <numFmts count="1"><numFmt numFmtId="164" formatCode="0.000"/></numFmts>
+  <fonts count="1"><font><sz val="11"/><name val="Calibri"/></font></fonts>
+  <fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills>
+  <borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders>
+  <cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>
+  <cellXfs count="2">
+    <xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/>
+    <xf numFmtId="164" fontId="0" fillId="1" borderId="0" xfId="0" applyNumberFormat="1"/>
+  </cellXfs>
+</styleSheet>"#;
+
+    const SST: &str = r#"<sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" count="2" uniqueCount="2">
+  <si><t>alpha</t></si><si><r><t>be</t></r><r><t>ta</t></r></si>
+</sst>"#;
+
+    const SHEET: &str = r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+  <sheetData>
+    <row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" s="1"><v>1.5</v></c></row>
+    <row r="3"><c t="s"><v>1</v></c><c r="C3"><f>A1</f><v>0</v></c></row>
+  </sheetData>
+</worksheet>"#;
+
+    /// Build a package from the valid baseline, replacing (Some) or dropping
+    /// (None) the given parts.
+    fn package(changes: &[(&str, Option<&str>)]) -> Vec<u8> {
+        let base = [
+            ("[Content_Types].xml", CONTENT_TYPES),
+            ("_rels/.rels", ROOT_RELS),
+            ("xl/workbook.xml", WORKBOOK),
+            ("xl/_rels/workbook.xml.rels", WORKBOOK_RELS),
+            ("xl/styles.xml", STYLES),
+            ("xl/sharedStrings.xml", SST),
+            ("xl/worksheets/sheet1.xml", SHEET),
+        ];
+        let mut parts: Vec<(&str, &str)> = base.to_vec();
+        for (name, content) in changes {
+            parts.retain(|(n, _)| n != name);
+            if let Some(content) = content {
+                parts.push((name, content));
+            }
+        }
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in parts {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn codes(changes: &[(&str, Option<&str>)]) -> Vec<&'static str> {
+        validate_bytes(Cursor::new(package(changes)))
+            .into_iter()
+            .map(|f| f.code)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_package_has_no_findings() {
+        assert_eq!(validate_bytes(Cursor::new(package(&[]))), vec![]);
+    }
+
+    #[test]
+    fn test_not_a_zip() {
+        let findings = validate_bytes(Cursor::new(b"PK not really".to_vec()));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].code, "zip.invalid");
+        assert_eq!(findings[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_missing_relationship_target() {
+        let found = codes(&[("xl/sharedStrings.xml", None)]);
+        assert!(found.contains(&"rels.missing_target"));
+        assert!(found.contains(&"content_types.dangling_override"));
+        // Shared-string cells now point at a table that isn't there.
+        assert!(found.contains(&"sheet.sst_missing"));
+    }
+
+    #[test]
+    fn test_uncovered_content_type() {
+        let types = CONTENT_TYPES.replace(
+            r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+            "",
+        );
+        let found = codes(&[("[Content_Types].xml", Some(&types))]);
+        // workbook.xml.rels and .rels are still covered by the rels default.
+        assert!(found.is_empty());
+        let found = codes(&[
+            ("[Content_Types].xml", Some(&types)),
+            ("xl/extra.xml", Some("<x/>")),
+        ]);
+        assert_eq!(found, vec!["content_types.uncovered"]);
+    }
+
+    #[test]
+    fn test_sheet_ordering_and_bounds() {
+        let sheet = r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>
+  <row r="2"><c r="B2"/><c r="A2"/></row>
+  <row r="1"><c r="A5" t="s"><v>7</v></c><c r="B1" s="9"/></row>
+</sheetData></worksheet>"#;
+        let found = codes(&[("xl/worksheets/sheet1.xml", Some(sheet))]);
+        assert_eq!(
+            found,
+            vec![
+                "sheet.cell_order",
+                "sheet.row_order",
+                "sheet.cell_row_mismatch",
+                "sheet.sst_index",
+                "sheet.style_index",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_styles_index_bounds() {
+        let styles = STYLES
+            .replace(
+                r#"fillId="1" borderId="0" xfId="0""#,
+                r#"fillId="5" borderId="0" xfId="3""#,
+            )
+            .replace(
+                r#"<xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/>"#,
+                r#"<xf numFmtId="170" fontId="0" fillId="0" borderId="0" xfId="0"/>"#,
+            );
+        let found = codes(&[("xl/styles.xml", Some(&styles))]);
+        assert_eq!(
+            found,
+            vec!["styles.num_fmt", "styles.xf_index", "styles.xf_index"]
+        );
+    }
+
+    #[test]
+    fn test_sst_count_mismatch_is_warning() {
+        let sst = SST.replace(r#"uniqueCount="2""#, r#"uniqueCount="3""#);
+        let findings = validate_bytes(Cursor::new(package(&[(
+            "xl/sharedStrings.xml",
+            Some(&sst),
+        )])));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].code, "sst.count_mismatch");
+        assert_eq!(findings[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_repeated_findings_are_capped() {
+        let rows: String = (0..40)
+            .map(|_| r#"<row r="1"><c r="A1" s="99"/></row>"#)
+            .collect();
+        let sheet = format!(
+            r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>{rows}</sheetData></worksheet>"#
+        );
+        let findings = validate_bytes(Cursor::new(package(&[(
+            "xl/worksheets/sheet1.xml",
+            Some(&sheet),
+        )])));
+        let style = findings
+            .iter()
+            .filter(|f| f.code == "sheet.style_index")
+            .count();
+        let order = findings
+            .iter()
+            .filter(|f| f.code == "sheet.row_order")
+            .count();
+        assert_eq!(
+            (style, order),
+            (MAX_FINDINGS_PER_CHECK, MAX_FINDINGS_PER_CHECK)
+        );
+        let notes: Vec<&str> = findings
+            .iter()
+            .filter(|f| f.code == "report.truncated")
+            .map(|f| f.message.as_str())
+            .collect();
+        assert_eq!(
+            notes,
+            vec![
+                "14 more 'sheet.row_order' findings omitted",
+                "15 more 'sheet.style_index' findings omitted",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_target() {
+        assert_eq!(
+            resolve_target("xl/", "worksheets/sheet1.xml"),
+            "xl/worksheets/sheet1.xml"
+        );
+        assert_eq!(
+            resolve_target("xl/worksheets/", "../media/My%20Image.png"),
+            "xl/media/My Image.png"
+        );
+        assert_eq!(resolve_target("xl/", "/xl/styles.xml"), "xl/styles.xml");
+        assert_eq!(
+            resolve_target("", "xl/workbook.xml#frag"),
+            "xl/workbook.xml"
+        );
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
@@ -0,0 +1,553 @@
+//! Python dict → Rust parsing for the cell payload, format and border dicts
+//! accepted by every write backend.
+//!
+//! This is the single definition of which keys (and aliases) are accepted and
+//! what errors a malformed dict raises; backends map the parsed structs onto
+//! their own representations. A key set to None counts as absent.
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDate, PyDateTime, PyDelta, PyDict, PyFloat, PyInt, PyString, PyTime};
+
+use crate::color::ColorSpec;
+use crate::formula;
+use crate::ooxml_util::fills::{GradientFillDef, GradientStop};
+use crate::util::{parse_iso_date, parse_iso_datetime, parse_iso_time};
+
+// ---------------------------------------------------------------------------
+// Cell payloads
+// ---------------------------------------------------------------------------
+
+/// A parsed `{"type": ..., "value": ...}` cell payload.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CellPayload {
+    Blank,
+    String(String),
+    Number(f64),
+    Boolean(bool),
+    /// Formula text without the leading `=`. `result` is the optional cached
+    /// value (`"result"` key) rendered as Excel stores it (`TRUE`, `1.5`, ...).
+    Formula {
+        formula: String,
+        result: Option<String>,
+    },
+    /// Error token such as `#DIV/0!`.
+    Error(String),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    /// Time of day without a date.
+    Time(NaiveTime),
+    /// Elapsed time, written as a day count with an `[h]:mm:ss` format.
+    Duration(TimeDelta),
+}
+
+impl CellPayload {
+    /// Canonical type name, as used in the payload dict.
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            CellPayload::Blank => "blank",
+            CellPayload::String(_) => "string",
+            CellPayload::Number(_) => "number",
+            CellPayload::Boolean(_) => "boolean",
+            CellPayload::Formula { .. } => "formula",
+            CellPayload::Error(_) => "error",
+            CellPayload::Date(_) => "date",
+            CellPayload::DateTime(_) => "datetime",
+            CellPayload::Time(_) => "time",
+            CellPayload::Duration(_) => "duration",
+        }
+    }
+}
+
+/// Error for a payload type a backend cannot write.
+pub(crate) fn unsupported_type(type_name: &str) -> PyErr {
+    PyErr::new::<PyValueError, _>(format!("Unsupported cell type: {type_name}"))
+}
+
+/// Parse a cell payload dict.
+///
+/// `type` is required; `str`, `int`/`integer`/`float` and `bool` are accepted
+/// as aliases of `string`, `number` and `boolean`. Formulas are read from
+/// `formula`, falling back to `value`. Dates, datetimes, times and durations
+/// take either `datetime` module objects or ISO strings (`h:mm:ss[.ffffff]`
+/// for durations).
+pub(crate) fn parse_cell_payload(payload: &Bound<'_, PyAny>) -> PyResult<CellPayload> {
+    let dict = as_dict(payload, "payload")?;
+    let type_str: String = item(dict, "type")?
+        .ok_or_else(|| PyErr::new::<PyValueError, _>("payload missing 'type'"))?
+        .extract()
+        .map_err(|_| PyErr::new::<PyValueError, _>("payload 'type' must be a str"))?;
+
+    let parsed = match type_str.as_str() {
+        "blank" => CellPayload::Blank,
+        "string" | "str" => match item(dict, "value")? {
+            Some(v) => {
+                CellPayload::String(v.extract().map_err(|_| wrong_value("string", "a str"))?)
+            }
+            None => CellPayload::String(String::new()),
+        },
+        "number" | "int" | "integer" | "float" => {
+            let v = required_value(dict, "number")?;
+            let n = v
+                .extract::<f64>()
+                .ok()
+                .or_else(|| {
+                    v.extract::<String>()
+                        .ok()
+                        .and_then(|s| s.trim().parse::<f64>().ok())
+                })
+                .ok_or_else(|| wrong_value("number", "a number"))?;
+            CellPayload::Number(n)
+        }
+        "boolean" | "bool" => {
+            let v = required_value(dict, "boolean")?;
+            let b = v
+                .extract::<bool>()
+                .ok()
+                .or_else(|| v.extract::<String>().ok().and_then(|s| parse_bool(&s)))
+                .ok_or_else(|| wrong_value("boolean", "a bool"))?;
+            CellPayload::Boolean(b)
+        }
+        "formula" => {
+            let v = match item(dict, "formula")? {
+                Some(v) => v,
+                None => item(dict, "value")?.ok_or_else(|| {
+                    PyErr::new::<PyValueError, _>("formula payload missing 'formula'")
+                })?,
+            };
+            let text: String = v.extract().map_err(|_| {
+                PyErr::new::<PyValueError, _>("formula payload 'formula' must be a str")
+            })?;
+            let formula = formula::strip_equals(&text).to_string();
+            let result = item(dict, "result")?
+                .map(|r| cached_result(&r))
+                .transpose()?;
+            CellPayload::Formula { formula, result }
+        }
+        "error" => CellPayload::Error(
+            required_value(dict, "error")?
+                .extract()
+                .map_err(|_| wrong_value("error", "a str"))?,
+        ),
+        "date" => {
+            let v = required_value(dict, "date")?;
+            let d = match v.extract::<String>() {
+                Ok(s) => parse_iso_date(&s).ok_or_else(|| {
+                    PyErr::new::<PyValueError, _>(format!("Invalid ISO date: {s}"))
+                })?,
+                // A datetime is a date subclass; its time part is dropped.
+                Err(_) => v
+                    .extract::<NaiveDate>()
+                    .map_err(|_| wrong_value("date", "a date or ISO date str"))?,
+            };
+            CellPayload::Date(d)
+        }
+        "datetime" => {
+            let v = required_value(dict, "datetime")?;
+            let dt = match v.extract::<String>() {
+                Ok(s) => parse_iso_datetime(&s).ok_or_else(|| {
+                    PyErr::new::<PyValueError, _>(format!("Invalid ISO datetime: {s}"))
+                })?,
+                // Timezone-aware datetimes are rejected: Excel has no zones.
+                Err(_) => v
+                    .extract::<NaiveDateTime>()
+                    .or_else(|_| v.extract::<NaiveDate>().map(|d| d.and_time(NaiveTime::MIN)))
+                    .map_err(|_| wrong_value("datetime", "a naive datetime or ISO datetime str"))?,
+            };
+            CellPayload::DateTime(dt)
+        }
+        "time" => {
+            let v = required_value(dict, "time")?;
+            let t = match v.extract::<String>() {
+                Ok(s) => parse_iso_time(&s).ok_or_else(|| {
+                    PyErr::new::<PyValueError, _>(format!("Invalid ISO time: {s}"))
+                })?,
+                Err(_) => v
+                    .extract::<NaiveTime>()
+                    .map_err(|_| wrong_value("time", "a time or ISO time str"))?,
+            };
+            CellPayload::Time(t)
+        }
+        "duration" => {
+            let v = required_value(dict, "duration")?;
+            let d = match v.extract::<String>() {
+                Ok(s) => parse_duration(&s).ok_or_else(|| {
+                    PyErr::new::<PyValueError, _>(format!("Invalid duration: {s}"))
+                })?,
+                Err(_) => v
+                    .extract::<TimeDelta>()
+                    .map_err(|_| wrong_value("duration", "a timedelta or h:mm:ss str"))?,
+            };
+            CellPayload::Duration(d)
+        }
+        other => return Err(unsupported_type(other)),
+    };
+    Ok(parsed)
+}
+
+/// The payload for a plain Python value, as `write_records` writes it:
+/// bool, int/float, str, naive datetime, date, time and timedelta. None for
+/// anything else (including timezone-aware datetimes, which Excel can't
+/// store). Callers skip Python None before calling this.
+pub(crate) fn infer_cell_payload(value: &Bound<'_, PyAny>) -> Option<CellPayload> {
+    // bool before the numbers: True is an int (and extracts as 1.0).
+    if value.is_instance_of::<PyBool>() {
+        return value.extract().ok().map(CellPayload::Boolean);
+    }
+    if value.is_instance_of::<PyInt>() || value.is_instance_of::<PyFloat>() {
+        return value.extract().ok().map(CellPayload::Number);
+    }
+    if let Ok(s) = value.downcast::<PyString>() {
+        return s.to_str().ok().map(|s| CellPayload::String(s.to_string()));
+    }
+    // datetime before date: a datetime is a date subclass.
+    if value.is_instance_of::<PyDateTime>() {
+        return value.extract().ok().map(CellPayload::DateTime);
+    }
+    if value.is_instance_of::<PyDate>() {
+        return value.extract().ok().map(CellPayload::Date);
+    }
+    if value.is_instance_of::<PyTime>() {
+        return value.extract().ok().map(CellPayload::Time);
+    }
+    if value.is_instance_of::<PyDelta>() {
+        return value.extract().ok().map(CellPayload::Duration);
+    }
+    None
+}
+
+fn required_value<'py>(dict: &Bound<'py, PyDict>, type_name: &str) -> PyResult<Bound<'py, PyAny>> {
+    item(dict, "value")?.ok_or_else(|| {
+        PyErr::new::<PyValueError, _>(format!("{type_name} payload missing 'value'"))
+    })
+}
+
+fn wrong_value(type_name: &str, expected: &str) -> PyErr {
+    PyErr::new::<PyValueError, _>(format!("{type_name} payload 'value' must be {expected}"))
+}
+
+/// `[-]h:mm:ss[.ffffff]`, with any number of hours.
+fn parse_duration(s: &str) -> Option<TimeDelta> {
+    let s = s.trim();
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let mut parts = s.split(':');
+    let (h, m, sec) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let all_digits = |p: &str| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit());
+    let (whole, frac) = sec.split_once('.').unwrap_or((sec, ""));
+    if !all_digits(h) || !all_digits(m) || !all_digits(whole) || m.len() != 2 || whole.len() != 2 {
+        return None;
+    }
+    if !frac.bytes().all(|b| b.is_ascii_digit()) || frac.len() > 6 {
+        return None;
+    }
+    let (m, whole): (i64, i64) = (m.parse().ok()?, whole.parse().ok()?);
+    if m > 59 || whole > 59 {
+        return None;
+    }
+    let micros: i64 = format!("{frac:0<6}").parse().ok()?;
+    let d = TimeDelta::try_hours(h.parse().ok()?)?
+        .checked_add(&TimeDelta::minutes(m))?
+        .checked_add(&TimeDelta::seconds(whole))?
+        .checked_add(&TimeDelta::microseconds(micros))?;
+    Some(if negative { -d } else { d })
+}
+
+/// A duration as Excel stores it: a (fractional) number of days.
+pub(crate) fn duration_to_days(d: TimeDelta) -> f64 {
+    match d.num_microseconds() {
+        Some(us) => us as f64 / 86_400_000_000.0,
+        None => d.num_milliseconds() as f64 / 86_400_000.0,
+    }
+}
+
+fn parse_bool(s: &str) -> Option<bool> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "t" | "yes" | "y" => Some(true),
+        "false" | "0" | "f" | "no" | "n" => Some(false),
+        _ => None,
+    }
+}
+
+/// A formula's cached result as stored in `<v>`.
+fn cached_result(r: &Bound<'_, PyAny>) -> PyResult<String> {
+    if let Ok(b) = r.extract::<bool>() {
+        return Ok(if b { "TRUE" } else { "FALSE" }.to_string());
+    }
+    if let Ok(n) = r.extract::<f64>() {
+        return Ok(n.to_string());
+    }
+    r.extract::<String>().map_err(|_| {
+        PyErr::new::<PyValueError, _>("formula payload 'result' must be a bool, number or str")
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Format dicts
+// ---------------------------------------------------------------------------
+
+/// A parsed cell format dict. Every field is optional; absent fields leave
+/// the cell's existing (or default) formatting alone.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct FormatPayload {
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    /// Underline style (`single`, `double`, ...); `true` means `single` and
+    /// `false` means `none`.
+    pub underline: Option<String>,
+    pub strikethrough: Option<bool>,
+    pub font_name: Option<String>,
+    pub font_size: Option<f64>,
+    /// `#RRGGBB`.
+    pub font_color: Option<String>,
+    /// Fill background. With no `pattern_type` (or `solid`) this is the
+    /// visible fill color.
+    pub bg_color: Option<String>,
+    /// Key `pattern_type` (alias `pattern`): `solid`, `gray125`, `darkGrid`,
+    /// ... in OOXML spelling (matched case-insensitively).
+    pub pattern_type: Option<String>,
+    /// Pattern foreground (the color of the pattern's dots or lines).
+    pub fg_color: Option<String>,
+    /// Gradient fill; takes precedence over the pattern keys.
+    pub gradient: Option<GradientFillDef>,
+    pub number_format: Option<String>,
+    /// Key `h_align` (alias `horizontal`).
+    pub h_align: Option<String>,
+    /// Key `v_align` (alias `vertical`).
+    pub v_align: Option<String>,
+    /// Key `wrap` (alias `wrap_text`).
+    pub wrap: Option<bool>,
+    /// Key `rotation` (alias `text_rotation`).
+    pub rotation: Option<i32>,
+    pub indent: Option<i32>,
+    /// Cell protection; only enforced once the sheet is protected.
+    pub locked: Option<bool>,
+    pub hidden: Option<bool>,
+}
+
+/// Parse a format dict (`format_dict` in the backends' write methods).
+pub(crate) fn parse_format(format_dict: &Bound<'_, PyAny>) -> PyResult<FormatPayload> {
+    let d = as_dict(format_dict, "format_dict")?;
+    let underline = match item(d, "underline")? {
+        None => None,
+        Some(v) => match v.extract::<bool>() {
+            Ok(true) => Some("single".to_string()),
+            Ok(false) => Some("none".to_string()),
+            Err(_) => Some(v.extract::<String>().map_err(|_| {
+                PyErr::new::<PyValueError, _>("format key 'underline' must be a str or bool")
+            })?),
+        },
+    };
+    Ok(FormatPayload {
+        bold: typed(d, "format", &["bold"], "a bool")?,
+        italic: typed(d, "format", &["italic"], "a bool")?,
+        underline,
+        strikethrough: typed(d, "format", &["strikethrough"], "a bool")?,
+        font_name: typed(d, "format", &["font_name"], "a str")?,
+        font_size: typed(d, "format", &["font_size"], "a number")?,
+        font_color: typed(d, "format", &["font_color"], "a str")?,
+        bg_color: typed(d, "format", &["bg_color"], "a str")?,
+        pattern_type: typed::<String>(d, "format", &["pattern_type", "pattern"], "a str")?
+            .map(|p| canonical_pattern(&p))
+            .transpose()?,
+        fg_color: typed(d, "format", &["fg_color"], "a str")?,
+        gradient: item(d, "gradient")?
+            .map(|g| parse_gradient(&g))
+            .transpose()?,
+        number_format: typed(d, "format", &["number_format"], "a str")?,
+        h_align: typed(d, "format", &["h_align", "horizontal"], "a str")?,
+        v_align: typed(d, "format", &["v_align", "vertical"], "a str")?,
+        wrap: typed(d, "format", &["wrap", "wrap_text"], "a bool")?,
+        rotation: typed(d, "format", &["rotation", "text_rotation"], "an int")?,
+        indent: typed(d, "format", &["indent"], "an int")?,
+        locked: typed(d, "format", &["locked"], "a bool")?,
+        hidden: typed(d, "format", &["hidden"], "a bool")?,
+    })
+}
+
+/// OOXML `patternType` names.
+const FILL_PATTERNS: &[&str] = &[
+    "none",
+    "solid",
+    "mediumGray",
+    "darkGray",
+    "lightGray",
+    "darkHorizontal",
+    "darkVertical",
+    "darkDown",
+    "darkUp",
+    "darkGrid",
+    "darkTrellis",
+    "lightHorizontal",
+    "lightVertical",
+    "lightDown",
+    "lightUp",
+    "lightGrid",
+    "lightTrellis",
+    "gray125",
+    "gray0625",
+];
+
+/// A fill pattern name in its OOXML spelling, matched case-insensitively.
+fn canonical_pattern(name: &str) -> PyResult<String> {
+    FILL_PATTERNS
+        .iter()
+        .find(|p| p.eq_ignore_ascii_case(name))
+        .map(|p| p.to_string())
+        .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown fill pattern '{name}'")))
+}
+
+/// Parse `{"type": "linear"|"path", "degree": .., "stops": [..]}`. Stops
+/// are `{"position": 0..1, "color": ..}` dicts, or bare colors spaced evenly;
+/// `path` gradients take `left`/`right`/`top`/`bottom` instead of `degree`.
+fn parse_gradient(obj: &Bound<'_, PyAny>) -> PyResult<GradientFillDef> {
+    let d = as_dict(obj, "format key 'gradient'")?;
+    let err = |msg: String| PyErr::new::<PyValueError, _>(msg);
+    let kind =
+        typed::<String>(d, "gradient", &["type"], "a str")?.unwrap_or_else(|| "linear".to_string());
+    if kind != "linear" && kind != "path" {
+        return Err(err(format!(
+            "Unknown gradient type '{kind}' (expected 'linear' or 'path')"
+        )));
+    }
+    let num = |key: &str| -> PyResult<f64> {
+        Ok(typed(d, "gradient", &[key], "a number")?.unwrap_or(0.0))
+    };
+    let color = |s: String| ColorSpec::parse(&s).map(Some).map_err(err);
+
+    let raw_stops: Vec<Bound<'_, PyAny>> = match item(d, "stops")? {
+        Some(v) => v
+            .extract()
+            .map_err(|_| err("gradient key 'stops' must be a list".to_string()))?,
+        None => Vec::new(),
+    };
+    if raw_stops.len() < 2 {
+        return Err(err("A gradient needs at least two stops".to_string()));
+    }
+    let last = (raw_stops.len() - 1) as f64;
+    let mut stops = Vec::with_capacity(raw_stops.len());
+    for (i, stop) in raw_stops.iter().enumerate() {
+        let stop = match stop.downcast::<PyDict>() {
+            Ok(sd) => {
+                let position: f64 = typed(sd, "gradient stop", &["position"], "a number")?
+                    .ok_or_else(|| err("gradient stop needs a 'position'".to_string()))?;
+                if !(0.0..=1.0).contains(&position) {
+                    return Err(err(format!(
+                        "Gradient stop position {position} out of range (0-1)"
+                    )));
+                }
+                let c: String = typed(sd, "gradient stop", &["color"], "a str")?
+                    .ok_or_else(|| err("gradient stop needs a 'color'".to_string()))?;
+                GradientStop {
+                    position,
+                    color: color(c)?,
+                }
+            }
+            Err(_) => {
+                let c: String = stop.extract().map_err(|_| {
+                    err("gradient stops must be dicts or color strings".to_string())
+                })?;
+                GradientStop {
+                    position: i as f64 / last,
+                    color: color(c)?,
+                }
+            }
+        };
+        stops.push(stop);
+    }
+
+    Ok(GradientFillDef {
+        degree: num("degree")?,
+        left: num("left")?,
+        right: num("right")?,
+        top: num("top")?,
+        bottom: num("bottom")?,
+        kind,
+        stops,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Border dicts
+// ---------------------------------------------------------------------------
+
+/// One border edge: `{"style": "thin", "color": "#RRGGBB"}`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct BorderEdge {
+    pub style: Option<String>,
+    pub color: Option<String>,
+}
+
+/// A parsed border dict; edges that are absent are left untouched.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct BorderPayload {
+    pub top: Option<BorderEdge>,
+    pub bottom: Option<BorderEdge>,
+    pub left: Option<BorderEdge>,
+    pub right: Option<BorderEdge>,
+    pub diagonal_up: Option<BorderEdge>,
+    pub diagonal_down: Option<BorderEdge>,
+}
+
+/// Parse a border dict (`border_dict` in the backends' write methods).
+pub(crate) fn parse_border(border_dict: &Bound<'_, PyAny>) -> PyResult<BorderPayload> {
+    let d = as_dict(border_dict, "border_dict")?;
+    let edge = |key: &str| -> PyResult<Option<BorderEdge>> {
+        let Some(v) = item(d, key)? else {
+            return Ok(None);
+        };
+        let sub = v.downcast::<PyDict>().map_err(|_| {
+            PyErr::new::<PyValueError, _>(format!("border key '{key}' must be a dict"))
+        })?;
+        Ok(Some(BorderEdge {
+            style: typed(sub, "border", &["style"], "a str")?,
+            color: typed(sub, "border", &["color"], "a str")?,
+        }))
+    };
+    Ok(BorderPayload {
+        top: edge("top")?,
+        bottom: edge("bottom")?,
+        left: edge("left")?,
+        right: edge("right")?,
+        diagonal_up: edge("diagonal_up")?,
+        diagonal_down: edge("diagonal_down")?,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Dict helpers
+// ---------------------------------------------------------------------------
+
+fn as_dict<'a, 'py>(obj: &'a Bound<'py, PyAny>, what: &str) -> PyResult<&'a Bound<'py, PyDict>> {
+    obj.downcast::<PyDict>()
+        .map_err(|_| PyErr::new::<PyValueError, _>(format!("{what} must be a dict")))
+}
+
+/// `dict[key]`, treating a None value like a missing key.
+fn item<'py>(d: &Bound<'py, PyDict>, key: &str) -> PyResult<Option<Bound<'py, PyAny>>> {
+    Ok(d.get_item(key)?.filter(|v| !v.is_none()))
+}
+
+/// First present key among `keys` (canonical name first, then aliases),
+/// extracted as `T`. `what` names the dict in the error message.
+fn typed<'py, T: FromPyObject<'py>>(
+    d: &Bound<'py, PyDict>,
+    what: &str,
+    keys: &[&str],
+    expected: &str,
+) -> PyResult<Option<T>> {
+    for key in keys {
+        if let Some(v) = item(d, key)? {
+            return v.extract().map(Some).map_err(|_| {
+                PyErr::new::<PyValueError, _>(format!("{what} key '{key}' must be {expected}"))
+            });
+        }
+    }
+    Ok(None)
+}
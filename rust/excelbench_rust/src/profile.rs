@@ -0,0 +1,85 @@
+//! Opt-in per-phase timing counters (`enable_profiling()` / `get_profile()`).
+//!
+//! Backends wrap their hot phases in `profile::span("<backend>.<phase>")`;
+//! phases are `parse` (XML/ZIP → Rust), `convert` (Rust → Python objects),
+//! `style_build` (format/style assembly), `patch` (wolfxl worksheet rewrites)
+//! and `zip_write` (serialization).
+//! Disabled spans cost one atomic load. Counters are thread-local, so work
+//! done on rayon workers is not attributed to the calling thread.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Default, Clone, Copy)]
+struct PhaseStats {
+    calls: u64,
+    total: Duration,
+}
+
+thread_local! {
+    static COUNTERS: RefCell<BTreeMap<&'static str, PhaseStats>> =
+        const { RefCell::new(BTreeMap::new()) };
+}
+
+/// Times one phase until dropped; inert unless profiling is enabled.
+pub(crate) struct Span {
+    phase: &'static str,
+    start: Option<Instant>,
+}
+
+/// Start timing `phase` (e.g. `"calamine.parse"`).
+pub(crate) fn span(phase: &'static str) -> Span {
+    let start = ENABLED.load(Ordering::Relaxed).then(Instant::now);
+    Span { phase, start }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            let elapsed = start.elapsed();
+            COUNTERS.with(|c| {
+                let mut counters = c.borrow_mut();
+                let stats = counters.entry(self.phase).or_default();
+                stats.calls += 1;
+                stats.total += elapsed;
+            });
+        }
+    }
+}
+
+/// Turn per-phase timing on or off. Existing counters are kept.
+#[pyfunction]
+pub(crate) fn enable_profiling(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Timings recorded on the calling thread.
+///
+/// Returns `{"<backend>.<phase>": {"calls": int, "seconds": float}}`. With
+/// `reset`, the counters are cleared after reading.
+#[pyfunction]
+#[pyo3(signature = (reset=false))]
+pub(crate) fn get_profile(py: Python<'_>, reset: bool) -> PyResult<PyObject> {
+    let snapshot = COUNTERS.with(|c| {
+        if reset {
+            std::mem::take(&mut *c.borrow_mut())
+        } else {
+            c.borrow().clone()
+        }
+    });
+    let out = PyDict::new(py);
+    for (phase, stats) in snapshot {
+        let d = PyDict::new(py);
+        d.set_item("calls", stats.calls)?;
+        d.set_item("seconds", stats.total.as_secs_f64())?;
+        out.set_item(phase, d)?;
+    }
+    Ok(out.into())
+}
@@ -0,0 +1,344 @@
+//! Write → save → read → compare in one call (`roundtrip()`).
+//!
+//! The case is written through one backend's `ExcelWriteBackend` view, saved,
+//! reopened through another backend's `ExcelReadBackend` view and compared
+//! field by field, so per-case Python orchestration is limited to a single
+//! call. Comparison follows the harness's `compare_results`.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyFloat, PyInt, PyList, PyString};
+
+use crate::backend::{self, ExcelReadBackend, ExcelWriteBackend};
+use crate::profile;
+
+/// Default sheet for cells that don't name one.
+const DEFAULT_SHEET: &str = "Sheet1";
+
+/// Same tolerance as the harness's `_deep_compare`.
+const NUMBER_TOLERANCE: f64 = 0.0001;
+
+/// One cell of a roundtrip case.
+struct CaseCell<'py> {
+    sheet: String,
+    cell: String,
+    value: Option<Bound<'py, PyAny>>,
+    format: Option<Bound<'py, PyAny>>,
+    border: Option<Bound<'py, PyAny>>,
+    /// `{"value", "format", "border"}` to compare against; each defaults to
+    /// what was written.
+    expected: Option<Bound<'py, PyDict>>,
+}
+
+/// One mismatch: `field` is a dotted path such as `format.bold`.
+struct FieldDiff {
+    sheet: String,
+    cell: String,
+    field: String,
+    expected: PyObject,
+    actual: Option<PyObject>,
+}
+
+/// Run one write→save→read→compare cycle.
+///
+/// `case` is `{"cells": [...]}`, or a single cell dict. Each cell is
+/// `{"cell": "A1", "sheet"?, "value"?, "format"?, "border"?, "expected"?}`
+/// using the payload, format and border dicts of the `write_cell_*` methods;
+/// `expected` may override `value`, `format` and/or `border` for the
+/// comparison (None skips that field). Expected dicts only need the keys
+/// that matter, and a None expected key matches a missing one. The workbook
+/// is saved to `path`, or to a temporary file that is removed afterwards.
+///
+/// Returns `{"passed", "diffs", "error"}`; each diff is `{"sheet", "cell",
+/// "field", "expected", "actual"}`. A backend exception fails the case with
+/// its message in `error` instead of raising.
+#[pyfunction]
+#[pyo3(signature = (write_backend, read_backend, case, path=None))]
+pub(crate) fn roundtrip(
+    py: Python<'_>,
+    write_backend: &str,
+    read_backend: &str,
+    case: &Bound<'_, PyAny>,
+    path: Option<&str>,
+) -> PyResult<PyObject> {
+    let cells = parse_case(case)?;
+    let (path, temporary) = match path {
+        Some(p) => (PathBuf::from(p), false),
+        None => (temp_path(), true),
+    };
+    let path_str = path.to_string_lossy().into_owned();
+
+    let outcome = run_case(py, write_backend, read_backend, &cells, &path_str);
+    if temporary {
+        let _ = std::fs::remove_file(&path);
+    }
+
+    let out = PyDict::new(py);
+    let diffs_out = PyList::empty(py);
+    match outcome {
+        Ok(diffs) => {
+            out.set_item("passed", diffs.is_empty())?;
+            for diff in diffs {
+                let d = PyDict::new(py);
+                d.set_item("sheet", diff.sheet)?;
+                d.set_item("cell", diff.cell)?;
+                d.set_item("field", diff.field)?;
+                d.set_item("expected", diff.expected)?;
+                d.set_item("actual", diff.actual)?;
+                diffs_out.append(d)?;
+            }
+            out.set_item("error", py.None())?;
+        }
+        Err(e) => {
+            out.set_item("passed", false)?;
+            out.set_item("error", e.to_string())?;
+        }
+    }
+    out.set_item("diffs", diffs_out)?;
+    Ok(out.into())
+}
+
+fn temp_path() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "excelbench-roundtrip-{}-{n}.xlsx",
+        std::process::id()
+    ))
+}
+
+fn parse_case<'py>(case: &Bound<'py, PyAny>) -> PyResult<Vec<CaseCell<'py>>> {
+    let case = case
+        .downcast::<PyDict>()
+        .map_err(|_| PyErr::new::<PyValueError, _>("case must be a dict"))?;
+    let default_sheet = match case.get_item("sheet")? {
+        Some(s) if !s.is_none() => s.extract::<String>()?,
+        _ => DEFAULT_SHEET.to_string(),
+    };
+    let entries: Vec<Bound<'py, PyAny>> = match case.get_item("cells")? {
+        Some(cells) => cells
+            .extract()
+            .map_err(|_| PyErr::new::<PyValueError, _>("case key 'cells' must be a list"))?,
+        None => vec![case.clone().into_any()],
+    };
+
+    let mut out = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let d = entry
+            .downcast::<PyDict>()
+            .map_err(|_| PyErr::new::<PyValueError, _>("case cells must be dicts"))?;
+        let get = |key: &str| -> PyResult<Option<Bound<'py, PyAny>>> {
+            Ok(d.get_item(key)?.filter(|v| !v.is_none()))
+        };
+        let cell: String = get("cell")?
+            .ok_or_else(|| PyErr::new::<PyValueError, _>("case cell needs a 'cell' key"))?
+            .extract()?;
+        let sheet = match get("sheet")? {
+            Some(s) => s.extract()?,
+            None => default_sheet.clone(),
+        };
+        let expected = match get("expected")? {
+            Some(e) => Some(e.downcast_into::<PyDict>().map_err(|_| {
+                PyErr::new::<PyValueError, _>(format!("'expected' for {cell} must be a dict"))
+            })?),
+            None => None,
+        };
+        out.push(CaseCell {
+            value: get("value")?,
+            format: get("format")?,
+            border: get("border")?,
+            expected,
+            sheet,
+            cell,
+        });
+    }
+    Ok(out)
+}
+
+fn run_case(
+    py: Python<'_>,
+    write_backend: &str,
+    read_backend: &str,
+    cells: &[CaseCell<'_>],
+    path: &str,
+) -> PyResult<Vec<FieldDiff>> {
+    {
+        let _span = profile::span("roundtrip.write");
+        let mut dst = backend::open_named(py, write_backend, None)?;
+        let dst = dst.as_writer().ok_or_else(|| {
+            PyErr::new::<PyValueError, _>(format!(
+                "Backend '{write_backend}' cannot write workbooks"
+            ))
+        })?;
+        write_cells(dst, cells)?;
+        dst.save(py, path)?;
+    }
+
+    let _span = profile::span("roundtrip.read");
+    let mut src = backend::open_named(py, read_backend, Some(path))?;
+    let src = src.as_reader().ok_or_else(|| {
+        PyErr::new::<PyValueError, _>(format!("Backend '{read_backend}' cannot read workbooks"))
+    })?;
+    let mut diffs = Vec::new();
+    for c in cells {
+        if let Some(exp) = expected_field(c, "value")? {
+            let actual = src.read_cell_value(py, &c.sheet, &c.cell)?;
+            compare(c, "value", &exp, Some(actual.bind(py)), &mut diffs)?;
+        }
+        if let Some(exp) = expected_field(c, "format")? {
+            let actual = src.read_cell_format(py, &c.sheet, &c.cell)?;
+            compare(
+                c,
+                "format",
+                &exp,
+                actual.as_ref().map(|a| a.bind(py)),
+                &mut diffs,
+            )?;
+        }
+        if let Some(exp) = expected_field(c, "border")? {
+            let actual = src.read_cell_border(py, &c.sheet, &c.cell)?;
+            compare(
+                c,
+                "border",
+                &exp,
+                actual.as_ref().map(|a| a.bind(py)),
+                &mut diffs,
+            )?;
+        }
+    }
+    Ok(diffs)
+}
+
+/// What to compare `key` against: the case's `expected` entry when it has
+/// one (None skips the field), otherwise what was written.
+fn expected_field<'py>(c: &CaseCell<'py>, key: &str) -> PyResult<Option<Bound<'py, PyAny>>> {
+    if let Some(exp) = &c.expected {
+        if let Some(v) = exp.get_item(key)? {
+            return Ok(Some(v).filter(|v| !v.is_none()));
+        }
+    }
+    Ok(match key {
+        "value" => c.value.clone(),
+        "format" => c.format.clone(),
+        _ => c.border.clone(),
+    })
+}
+
+fn write_cells(dst: &mut dyn ExcelWriteBackend, cells: &[CaseCell<'_>]) -> PyResult<()> {
+    let mut sheets: Vec<&str> = Vec::new();
+    for c in cells {
+        if !sheets.contains(&c.sheet.as_str()) {
+            dst.add_sheet(&c.sheet)?;
+            sheets.push(&c.sheet);
+        }
+    }
+    for c in cells {
+        if let Some(value) = &c.value {
+            dst.write_cell_value(&c.sheet, &c.cell, value)?;
+        }
+        if let Some(format) = &c.format {
+            dst.write_cell_format(&c.sheet, &c.cell, format)?;
+        }
+        if let Some(border) = &c.border {
+            dst.write_cell_border(&c.sheet, &c.cell, border)?;
+        }
+    }
+    Ok(())
+}
+
+/// Record a diff for every expected field `actual` doesn't match.
+fn compare(
+    c: &CaseCell<'_>,
+    field: &str,
+    expected: &Bound<'_, PyAny>,
+    actual: Option<&Bound<'_, PyAny>>,
+    diffs: &mut Vec<FieldDiff>,
+) -> PyResult<()> {
+    if let (Ok(exp), Some(Ok(act))) = (
+        expected.downcast::<PyDict>(),
+        actual.map(|a| a.downcast::<PyDict>()),
+    ) {
+        for (key, exp_value) in exp.iter() {
+            let sub = format!("{field}.{}", key.str()?);
+            match act.get_item(&key)? {
+                Some(act_value) => compare(c, &sub, &exp_value, Some(&act_value), diffs)?,
+                None if exp_value.is_none() => {}
+                None => compare(c, &sub, &exp_value, None, diffs)?,
+            }
+        }
+        return Ok(());
+    }
+    let matched = match actual {
+        Some(act) => values_match(expected, act)?,
+        None => false,
+    };
+    if !matched {
+        diffs.push(FieldDiff {
+            sheet: c.sheet.clone(),
+            cell: c.cell.clone(),
+            field: field.to_string(),
+            expected: expected.clone().unbind(),
+            actual: actual.map(|a| a.clone().unbind()),
+        });
+    }
+    Ok(())
+}
+
+/// `_deep_compare` semantics: dicts match on the expected keys, lists when
+/// every expected item matches some actual item, `#` colors ignore case and
+/// numbers match within `NUMBER_TOLERANCE`.
+fn values_match(expected: &Bound<'_, PyAny>, actual: &Bound<'_, PyAny>) -> PyResult<bool> {
+    if let Ok(exp) = expected.downcast::<PyDict>() {
+        let Ok(act) = actual.downcast::<PyDict>() else {
+            return Ok(false);
+        };
+        for (key, exp_value) in exp.iter() {
+            let ok = match act.get_item(&key)? {
+                Some(act_value) => values_match(&exp_value, &act_value)?,
+                None => exp_value.is_none(),
+            };
+            if !ok {
+                return Ok(false);
+            }
+        }
+        return Ok(true);
+    }
+    if let Ok(exp) = expected.downcast::<PyList>() {
+        let Ok(act) = actual.downcast::<PyList>() else {
+            return Ok(false);
+        };
+        for exp_item in exp.iter() {
+            let mut found = false;
+            for act_item in act.iter() {
+                if values_match(&exp_item, &act_item)? {
+                    found = true;
+                    break;
+                }
+            }
+            if !found {
+                return Ok(false);
+            }
+        }
+        return Ok(true);
+    }
+    if let Ok(exp) = expected.downcast::<PyString>() {
+        let exp = exp.to_str()?;
+        if exp.starts_with('#') {
+            return Ok(match actual.downcast::<PyString>() {
+                Ok(act) => act.to_str()?.eq_ignore_ascii_case(exp),
+                Err(_) => false,
+            });
+        }
+    }
+    // As in Python, bools count as numbers.
+    let is_number =
+        |v: &Bound<'_, PyAny>| v.is_instance_of::<PyInt>() || v.is_instance_of::<PyFloat>();
+    if is_number(expected) && is_number(actual) {
+        let (e, a): (f64, f64) = (expected.extract()?, actual.extract()?);
+        return Ok((e - a).abs() <= NUMBER_TOLERANCE);
+    }
+    expected.eq(actual)
+}
@@ -3,72 +3,40 @@ use pyo3::prelude::*;
 use pyo3::types::PyDict;
 
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{Read, Write};
 
 use indexmap::IndexMap;
+use rayon::prelude::*;
 
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::{Reader as XmlReader, Writer as XmlWriter};
 
 use rust_xlsxwriter::{
-    Color, ConditionalFormat3ColorScale, ConditionalFormatCell, ConditionalFormatCellRule,
-    ConditionalFormatDataBar, ConditionalFormatFormula, DataValidation, DataValidationRule, Format,
-    FormatAlign, FormatBorder, FormatPattern, Formula, Note, Table, TableColumn, TableStyle, Url,
-    Workbook, Worksheet,
+    Color, ConditionalFormat2ColorScale, ConditionalFormat3ColorScale, ConditionalFormatCell,
+    ConditionalFormatCellRule, ConditionalFormatDataBar, ConditionalFormatFormula,
+    ConditionalFormatType, ConditionalFormatValue, DataValidation, DataValidationRule, Format,
+    FormatAlign, FormatBorder, FormatPattern, Formula, HeaderImagePosition, IgnoreError, Image,
+    Note, Table, TableColumn, TableStyle, Url, Workbook, Worksheet,
 };
 
-use zip::write::SimpleFileOptions;
-use zip::{ZipArchive, ZipWriter};
-
-use crate::ooxml_util;
-use crate::util::{a1_to_row_col, parse_iso_date, parse_iso_datetime};
+use crate::autofit;
+use crate::backend::{self, pyclass_object, Backend, BackendEntry, ExcelWriteBackend};
+use crate::capabilities::BackendCapabilities;
+use crate::cell_ref::{letters_to_col, RangeRef, MAX_COLS};
+use crate::color::ColorSpec;
+use crate::csv_io;
+use crate::errors;
+use crate::formula;
+use crate::ooxml_util::fills::{self, FillDef, GradientFillDef};
+use crate::ooxml_util::sheet_format::SheetFormatEdit;
+use crate::ooxml_util::{self, calc_pr, calc_pr::CalcPrEdit, PackagePatches};
+use crate::payload::{self, BorderEdge, BorderPayload, CellPayload, FormatPayload};
+use crate::profile;
+use crate::util::a1_to_row_col;
 
 // ---------------------------------------------------------------------------
 // Queued operation types
 // ---------------------------------------------------------------------------
 
-/// Stored cell value payload (mirrors the Python dict contract).
-struct CellPayload {
-    type_str: String,
-    value: Option<String>,
-    formula: Option<String>,
-}
-
-/// Queued per-cell format dict fields.
-struct FormatFields {
-    bold: Option<bool>,
-    italic: Option<bool>,
-    underline: Option<String>,
-    strikethrough: Option<bool>,
-    font_name: Option<String>,
-    font_size: Option<f64>,
-    font_color: Option<String>,
-    bg_color: Option<String>,
-    number_format: Option<String>,
-    h_align: Option<String>,
-    v_align: Option<String>,
-    wrap: Option<bool>,
-    rotation: Option<i32>,
-    indent: Option<i32>,
-}
-
-/// Queued per-cell border dict fields.
-struct BorderFields {
-    top_style: Option<String>,
-    top_color: Option<String>,
-    bottom_style: Option<String>,
-    bottom_color: Option<String>,
-    left_style: Option<String>,
-    left_color: Option<String>,
-    right_style: Option<String>,
-    right_color: Option<String>,
-    diagonal_up_style: Option<String>,
-    diagonal_up_color: Option<String>,
-    diagonal_down_style: Option<String>,
-    diagonal_down_color: Option<String>,
-}
-
 struct MergeRange {
     sheet: String,
     first_row: u32,
@@ -92,6 +60,12 @@ struct CommentPayload {
     col: u16,
     text: String,
     author: Option<String>,
+    /// Note box size in pixels.
+    width: Option<u32>,
+    height: Option<u32>,
+    /// Show the note permanently instead of on hover.
+    visible: Option<bool>,
+    bg_color: Option<String>,
 }
 
 struct ConditionalFormatPayload {
@@ -102,6 +76,30 @@ struct ConditionalFormatPayload {
     formula: Option<String>,
     stop_if_true: bool,
     bg_color: Option<String>,
+    /// dataBar / colorScale points; None keeps the library default.
+    min: Option<ScalePoint>,
+    mid: Option<ScalePoint>,
+    max: Option<ScalePoint>,
+    /// dataBar fill, or colorScale min/(mid/)max colors.
+    colors: Vec<String>,
+    bar_border_color: Option<String>,
+    negative_fill: Option<String>,
+}
+
+/// A dataBar / colorScale cfvo; `value` is a number or formula as text.
+struct ScalePoint {
+    kind: ConditionalFormatType,
+    value: Option<String>,
+}
+
+impl ScalePoint {
+    fn value(&self) -> ConditionalFormatValue {
+        let raw = self.value.as_deref().unwrap_or("0");
+        match raw.parse::<f64>() {
+            Ok(n) => ConditionalFormatValue::from(n),
+            Err(_) => ConditionalFormatValue::from(raw),
+        }
+    }
 }
 
 struct DataValidationPayload {
@@ -134,6 +132,63 @@ struct TablePayload {
     header_row: bool,
 }
 
+/// One `ignore_errors()` range; `range` is (first_row, first_col, last_row, last_col).
+struct IgnoreErrorPayload {
+    sheet: String,
+    kind: IgnoreError,
+    range: (u32, u16, u32, u16),
+}
+
+/// Map an `ignore_errors()` key to its warning type (openpyxl attribute names
+/// in snake_case).
+fn ignore_error_kind(key: &str) -> PyResult<IgnoreError> {
+    Ok(match key {
+        "number_stored_as_text" => IgnoreError::NumberStoredAsText,
+        "eval_error" => IgnoreError::EvalError,
+        "formula" => IgnoreError::FormulaDiffers,
+        "formula_range" => IgnoreError::FormulaRange,
+        "unlocked_formula" => IgnoreError::FormulaUnlocked,
+        "empty_cell_reference" => IgnoreError::EmptyCellReference,
+        "list_data_validation" => IgnoreError::ListDataValidation,
+        "calculated_column" => IgnoreError::CalculatedColumn,
+        "two_digit_text_year" => IgnoreError::TwoDigitTextYear,
+        other => {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "Unknown ignore_errors key: {other}"
+            )))
+        }
+    })
+}
+
+/// Bounds for `autofit_columns()`, in character units.
+#[derive(Clone, Copy)]
+struct AutofitSetting {
+    min_width: Option<f64>,
+    max_width: Option<f64>,
+}
+
+/// Sheet-wide row/column defaults and outline placement.
+#[derive(Clone, Copy)]
+struct SheetDefaults {
+    row_height: Option<f64>,
+    col_width: Option<f64>,
+    hide_unused_rows: bool,
+    summary_below: bool,
+    summary_right: bool,
+}
+
+impl Default for SheetDefaults {
+    fn default() -> Self {
+        Self {
+            row_height: None,
+            col_width: None,
+            hide_unused_rows: false,
+            summary_below: true,
+            summary_right: true,
+        }
+    }
+}
+
 enum PaneSetting {
     Freeze { row: u32, col: u16 },
     Split { x_split: f64, y_split: f64 },
@@ -141,23 +196,73 @@ enum PaneSetting {
 
 type CellKey = (String, u32, u16); // (sheet, row, col)
 
-#[pyclass(unsendable)]
+pub(crate) const CAPABILITIES: BackendCapabilities = BackendCapabilities {
+    class: "RustXlsxWriterBook",
+    backend: "rust_xlsxwriter",
+    extensions: &[".xlsx", ".xlsm"],
+    read: &[],
+    write: &[
+        "cell_values",
+        "formulas",
+        "text_formatting",
+        "background_colors",
+        "number_formats",
+        "alignment",
+        "borders",
+        "dimensions",
+        "multiple_sheets",
+        "merged_cells",
+        "conditional_formatting",
+        "data_validation",
+        "hyperlinks",
+        "comments",
+        "freeze_panes",
+        "named_ranges",
+        "tables",
+    ],
+    modify: false,
+};
+
+/// Buffers every write and builds the workbook in `save()`.
+///
+/// All fields are plain owned data, so the class is `Send + Sync` and may be
+/// passed to worker threads. PyO3's per-object borrow flag serializes calls:
+/// a method entered while another thread holds the book mutably raises
+/// `RuntimeError` instead of racing.
+#[pyclass]
 pub struct RustXlsxWriterBook {
     sheet_names: Vec<String>,
     values: IndexMap<CellKey, CellPayload>,
-    formats: HashMap<CellKey, FormatFields>,
-    borders: HashMap<CellKey, BorderFields>,
+    formats: HashMap<CellKey, FormatPayload>,
+    borders: HashMap<CellKey, BorderPayload>,
     row_heights: HashMap<(String, u32), f64>,
     col_widths: HashMap<(String, u16), f64>,
     merge_ranges: Vec<MergeRange>,
     hyperlinks: Vec<HyperlinkPayload>,
     comments: Vec<CommentPayload>,
     panes: HashMap<String, PaneSetting>,
+    autofit: HashMap<String, AutofitSetting>,
+    sheet_defaults: HashMap<String, SheetDefaults>,
     conditional_formats: Vec<ConditionalFormatPayload>,
     data_validations: Vec<DataValidationPayload>,
     named_ranges: Vec<NamedRangePayload>,
     tables: Vec<TablePayload>,
+    ignore_errors: Vec<IgnoreErrorPayload>,
+    calc_pr: Vec<CalcPrEdit>,
+    /// Sheet → image path for `set_background()`.
+    backgrounds: HashMap<String, String>,
+    /// Sheet → image path for `set_watermark()`.
+    watermarks: HashMap<String, String>,
+    /// `vbaProject.bin` for `add_vba_project()`; the book saves as `.xlsm`.
+    vba_project: Option<String>,
+    /// VBA code names: the workbook's (None key) and per sheet.
+    vba_names: HashMap<Option<String>, String>,
     saved: bool,
+    /// Written by `__exit__` when the `with` block ends without an exception.
+    save_path: Option<String>,
+    /// Raise `UnsupportedFeature` instead of logging and dropping input
+    /// rust_xlsxwriter cannot write.
+    strict: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -166,6 +271,8 @@ pub struct RustXlsxWriterBook {
 
 fn parse_hex_color(hex: &str) -> Color {
     let s = hex.strip_prefix('#').unwrap_or(hex);
+    // ARGB: the writer has no alpha channel.
+    let s = if s.len() == 8 { &s[2..] } else { s };
     if let Ok(n) = u32::from_str_radix(s, 16) {
         Color::RGB(n)
     } else {
@@ -173,31 +280,84 @@ fn parse_hex_color(hex: &str) -> Color {
     }
 }
 
-fn map_h_align(s: &str) -> FormatAlign {
-    match s.to_ascii_lowercase().as_str() {
+fn map_pattern(s: &str) -> PyResult<FormatPattern> {
+    Ok(match s.to_ascii_lowercase().as_str() {
+        "none" => FormatPattern::None,
+        "solid" => FormatPattern::Solid,
+        "mediumgray" => FormatPattern::MediumGray,
+        "darkgray" => FormatPattern::DarkGray,
+        "lightgray" => FormatPattern::LightGray,
+        "darkhorizontal" => FormatPattern::DarkHorizontal,
+        "darkvertical" => FormatPattern::DarkVertical,
+        "darkdown" => FormatPattern::DarkDown,
+        "darkup" => FormatPattern::DarkUp,
+        "darkgrid" => FormatPattern::DarkGrid,
+        "darktrellis" => FormatPattern::DarkTrellis,
+        "lighthorizontal" => FormatPattern::LightHorizontal,
+        "lightvertical" => FormatPattern::LightVertical,
+        "lightdown" => FormatPattern::LightDown,
+        "lightup" => FormatPattern::LightUp,
+        "lightgrid" => FormatPattern::LightGrid,
+        "lighttrellis" => FormatPattern::LightTrellis,
+        "gray125" => FormatPattern::Gray125,
+        "gray0625" => FormatPattern::Gray0625,
+        _ => {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "Unknown fill pattern '{s}'"
+            )))
+        }
+    })
+}
+
+/// rust_xlsxwriter can't write gradient fills, so a gradient cell gets a
+/// `gray0625` placeholder whose fg/bg colors key the gradient; after saving,
+/// `patch_gradient_fills` swaps each placeholder fill for the gradient.
+/// Returns the placeholder's (fg, bg) RGB values.
+fn gradient_placeholder(gradient: &GradientFillDef) -> (u32, u32) {
+    // FNV-1a folded to 24 bits.
+    let mut h: u32 = 0x811c_9dc5;
+    for b in gradient.to_xml().bytes() {
+        h = (h ^ b as u32).wrapping_mul(0x0100_0193);
+    }
+    let fg = (h >> 24 ^ h) & 0xFF_FFFF;
+    (fg, fg ^ 0xFF_FFFF)
+}
+
+/// Load an image file, failing on unreadable or unsupported files.
+fn load_image(path: &str) -> PyResult<Image> {
+    Image::new(path)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Cannot load image '{path}': {e}")))
+}
+
+// The style mappers return None for names rust_xlsxwriter has no value for;
+// `check_format`/`check_border` report those when they are queued and
+// `build_format` falls back to Excel's default.
+
+fn map_h_align(s: &str) -> Option<FormatAlign> {
+    Some(match s.to_ascii_lowercase().as_str() {
         "left" => FormatAlign::Left,
         "center" | "centre" => FormatAlign::Center,
         "right" => FormatAlign::Right,
         "fill" => FormatAlign::Fill,
         "justify" => FormatAlign::Justify,
         "distributed" | "centercontinuous" => FormatAlign::CenterAcross,
-        _ => FormatAlign::Left,
-    }
+        _ => return None,
+    })
 }
 
-fn map_v_align(s: &str) -> FormatAlign {
-    match s.to_ascii_lowercase().as_str() {
+fn map_v_align(s: &str) -> Option<FormatAlign> {
+    Some(match s.to_ascii_lowercase().as_str() {
         "top" => FormatAlign::Top,
         "center" | "centre" => FormatAlign::VerticalCenter,
         "bottom" => FormatAlign::Bottom,
         "justify" => FormatAlign::VerticalJustify,
         "distributed" => FormatAlign::VerticalDistributed,
-        _ => FormatAlign::Bottom,
-    }
+        _ => return None,
+    })
 }
 
-fn map_border_style(s: &str) -> FormatBorder {
-    match s.to_ascii_lowercase().as_str() {
+fn map_border_style(s: &str) -> Option<FormatBorder> {
+    Some(match s.to_ascii_lowercase().as_str() {
         "thin" => FormatBorder::Thin,
         "medium" => FormatBorder::Medium,
         "thick" => FormatBorder::Thick,
@@ -212,25 +372,26 @@ fn map_border_style(s: &str) -> FormatBorder {
         "mediumdashdotdot" => FormatBorder::MediumDashDotDot,
         "slantdashdot" => FormatBorder::SlantDashDot,
         "none" | "" => FormatBorder::None,
-        _ => FormatBorder::Thin,
-    }
+        _ => return None,
+    })
 }
 
-fn map_underline(s: &str) -> rust_xlsxwriter::FormatUnderline {
-    match s.to_ascii_lowercase().as_str() {
+fn map_underline(s: &str) -> Option<rust_xlsxwriter::FormatUnderline> {
+    Some(match s.to_ascii_lowercase().as_str() {
         "single" => rust_xlsxwriter::FormatUnderline::Single,
         "double" => rust_xlsxwriter::FormatUnderline::Double,
         "singleaccounting" => rust_xlsxwriter::FormatUnderline::SingleAccounting,
         "doubleaccounting" => rust_xlsxwriter::FormatUnderline::DoubleAccounting,
-        _ => rust_xlsxwriter::FormatUnderline::Single,
-    }
+        _ => return None,
+    })
 }
 
 // ---------------------------------------------------------------------------
-// Build a Format from optional FormatFields + BorderFields
+// Build a Format from optional format + border payloads
 // ---------------------------------------------------------------------------
 
-fn build_format(fmt: Option<&FormatFields>, bdr: Option<&BorderFields>) -> PyResult<Format> {
+fn build_format(fmt: Option<&FormatPayload>, bdr: Option<&BorderPayload>) -> PyResult<Format> {
+    let _span = profile::span("rust_xlsxwriter.style_build");
     let mut f = Format::new();
 
     if let Some(ff) = fmt {
@@ -241,7 +402,8 @@ fn build_format(fmt: Option<&FormatFields>, bdr: Option<&BorderFields>) -> PyRes
             f = f.set_italic();
         }
         if let Some(ref ul) = ff.underline {
-            f = f.set_underline(map_underline(ul));
+            let underline = map_underline(ul).unwrap_or(rust_xlsxwriter::FormatUnderline::Single);
+            f = f.set_underline(underline);
         }
         if ff.strikethrough == Some(true) {
             f = f.set_font_strikethrough();
@@ -255,17 +417,31 @@ fn build_format(fmt: Option<&FormatFields>, bdr: Option<&BorderFields>) -> PyRes
         if let Some(ref color) = ff.font_color {
             f = f.set_font_color(parse_hex_color(color));
         }
-        if let Some(ref bg) = ff.bg_color {
-            f = f.set_background_color(parse_hex_color(bg));
+        if let Some(ref gradient) = ff.gradient {
+            let (fg, bg) = gradient_placeholder(gradient);
+            f = f
+                .set_pattern(FormatPattern::Gray0625)
+                .set_foreground_color(Color::RGB(fg))
+                .set_background_color(Color::RGB(bg));
+        } else {
+            if let Some(ref pattern) = ff.pattern_type {
+                f = f.set_pattern(map_pattern(pattern)?);
+            }
+            if let Some(ref fg) = ff.fg_color {
+                f = f.set_foreground_color(parse_hex_color(fg));
+            }
+            if let Some(ref bg) = ff.bg_color {
+                f = f.set_background_color(parse_hex_color(bg));
+            }
         }
         if let Some(ref nf) = ff.number_format {
             f = f.set_num_format(nf);
         }
         if let Some(ref h) = ff.h_align {
-            f = f.set_align(map_h_align(h));
+            f = f.set_align(map_h_align(h).unwrap_or(FormatAlign::Left));
         }
         if let Some(ref v) = ff.v_align {
-            f = f.set_align(map_v_align(v));
+            f = f.set_align(map_v_align(v).unwrap_or(FormatAlign::Bottom));
         }
         if ff.wrap == Some(true) {
             f = f.set_text_wrap();
@@ -281,49 +457,67 @@ fn build_format(fmt: Option<&FormatFields>, bdr: Option<&BorderFields>) -> PyRes
                 f = f.set_indent(indent as u8);
             }
         }
+        match ff.locked {
+            Some(true) => f = f.set_locked(),
+            Some(false) => f = f.set_unlocked(),
+            None => {}
+        }
+        if ff.hidden == Some(true) {
+            f = f.set_hidden();
+        }
     }
 
     if let Some(bb) = bdr {
-        if let Some(ref s) = bb.top_style {
-            f = f.set_border_top(map_border_style(s));
-            if let Some(ref c) = bb.top_color {
-                f = f.set_border_top_color(parse_hex_color(c));
+        let edge_style = |edge: &Option<BorderEdge>| {
+            edge.as_ref()
+                .and_then(|e| e.style.as_deref())
+                .map(|s| map_border_style(s).unwrap_or(FormatBorder::Thin))
+        };
+        let edge_color = |edge: &Option<BorderEdge>| {
+            edge.as_ref()
+                .and_then(|e| e.color.as_deref())
+                .map(parse_hex_color)
+        };
+        if let Some(style) = edge_style(&bb.top) {
+            f = f.set_border_top(style);
+            if let Some(color) = edge_color(&bb.top) {
+                f = f.set_border_top_color(color);
             }
         }
-        if let Some(ref s) = bb.bottom_style {
-            f = f.set_border_bottom(map_border_style(s));
-            if let Some(ref c) = bb.bottom_color {
-                f = f.set_border_bottom_color(parse_hex_color(c));
+        if let Some(style) = edge_style(&bb.bottom) {
+            f = f.set_border_bottom(style);
+            if let Some(color) = edge_color(&bb.bottom) {
+                f = f.set_border_bottom_color(color);
             }
         }
-        if let Some(ref s) = bb.left_style {
-            f = f.set_border_left(map_border_style(s));
-            if let Some(ref c) = bb.left_color {
-                f = f.set_border_left_color(parse_hex_color(c));
+        if let Some(style) = edge_style(&bb.left) {
+            f = f.set_border_left(style);
+            if let Some(color) = edge_color(&bb.left) {
+                f = f.set_border_left_color(color);
             }
         }
-        if let Some(ref s) = bb.right_style {
-            f = f.set_border_right(map_border_style(s));
-            if let Some(ref c) = bb.right_color {
-                f = f.set_border_right_color(parse_hex_color(c));
+        if let Some(style) = edge_style(&bb.right) {
+            f = f.set_border_right(style);
+            if let Some(color) = edge_color(&bb.right) {
+                f = f.set_border_right_color(color);
             }
         }
 
         // Diagonal borders: if both up+down are present, use BorderUpDown.
-        let has_up = bb.diagonal_up_style.is_some();
-        let has_down = bb.diagonal_down_style.is_some();
+        let has_up = edge_style(&bb.diagonal_up).is_some();
+        let has_down = edge_style(&bb.diagonal_down).is_some();
         if has_up || has_down {
             // Use whichever is set (prefer down if both, since it's applied second).
-            let (style_ref, color_ref) = if has_down {
-                (&bb.diagonal_down_style, &bb.diagonal_down_color)
+            let edge = if has_down {
+                &bb.diagonal_down
             } else {
-                (&bb.diagonal_up_style, &bb.diagonal_up_color)
+                &bb.diagonal_up
             };
-            if let Some(ref s) = style_ref {
-                f = f.set_border_diagonal(map_border_style(s));
+            if let Some(style) = edge_style(edge) {
+                f = f.set_border_diagonal(style);
             }
-            if let Some(ref c) = color_ref {
-                f = f.set_border_diagonal_color(parse_hex_color(c));
+            if let Some(color) = edge_color(edge) {
+                f = f.set_border_diagonal_color(color);
             }
             let diag_type = if has_up && has_down {
                 rust_xlsxwriter::FormatDiagonalBorder::BorderUpDown
@@ -339,70 +533,9 @@ fn build_format(fmt: Option<&FormatFields>, bdr: Option<&BorderFields>) -> PyRes
     Ok(f)
 }
 
-// ---------------------------------------------------------------------------
-// Extract fields from Python dicts
-// ---------------------------------------------------------------------------
-
-fn extract_format_fields(dict: &Bound<'_, PyDict>) -> PyResult<FormatFields> {
-    Ok(FormatFields {
-        bold: dict.get_item("bold")?.and_then(|v| v.extract().ok()),
-        italic: dict.get_item("italic")?.and_then(|v| v.extract().ok()),
-        underline: dict.get_item("underline")?.and_then(|v| v.extract().ok()),
-        strikethrough: dict
-            .get_item("strikethrough")?
-            .and_then(|v| v.extract().ok()),
-        font_name: dict.get_item("font_name")?.and_then(|v| v.extract().ok()),
-        font_size: dict.get_item("font_size")?.and_then(|v| v.extract().ok()),
-        font_color: dict.get_item("font_color")?.and_then(|v| v.extract().ok()),
-        bg_color: dict.get_item("bg_color")?.and_then(|v| v.extract().ok()),
-        number_format: dict
-            .get_item("number_format")?
-            .and_then(|v| v.extract().ok()),
-        h_align: dict.get_item("h_align")?.and_then(|v| v.extract().ok()),
-        v_align: dict.get_item("v_align")?.and_then(|v| v.extract().ok()),
-        wrap: dict.get_item("wrap")?.and_then(|v| v.extract().ok()),
-        rotation: dict.get_item("rotation")?.and_then(|v| v.extract().ok()),
-        indent: dict.get_item("indent")?.and_then(|v| v.extract().ok()),
-    })
-}
-
-fn extract_border_fields(dict: &Bound<'_, PyDict>) -> PyResult<BorderFields> {
-    fn edge(dict: &Bound<'_, PyDict>, key: &str) -> PyResult<(Option<String>, Option<String>)> {
-        if let Some(sub) = dict.get_item(key)? {
-            if let Ok(d) = sub.downcast::<PyDict>() {
-                let style: Option<String> = d.get_item("style")?.and_then(|v| v.extract().ok());
-                let color: Option<String> = d.get_item("color")?.and_then(|v| v.extract().ok());
-                return Ok((style, color));
-            }
-        }
-        Ok((None, None))
-    }
-
-    let (ts, tc) = edge(dict, "top")?;
-    let (bs, bc) = edge(dict, "bottom")?;
-    let (ls, lc) = edge(dict, "left")?;
-    let (rs, rc) = edge(dict, "right")?;
-    let (dus, duc) = edge(dict, "diagonal_up")?;
-    let (dds, ddc) = edge(dict, "diagonal_down")?;
-
-    Ok(BorderFields {
-        top_style: ts,
-        top_color: tc,
-        bottom_style: bs,
-        bottom_color: bc,
-        left_style: ls,
-        left_color: lc,
-        right_style: rs,
-        right_color: rc,
-        diagonal_up_style: dus,
-        diagonal_up_color: duc,
-        diagonal_down_style: dds,
-        diagonal_down_color: ddc,
-    })
-}
-
 fn resolve_key(sheet: &str, a1: &str) -> PyResult<CellKey> {
-    let (row, col0) = a1_to_row_col(a1).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
+    let (row, col0) =
+        a1_to_row_col(a1).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a1, msg))?;
     let col: u16 = col0.try_into().map_err(|_| {
         PyErr::new::<PyValueError, _>(format!("Column out of range for Excel: {a1}"))
     })?;
@@ -410,45 +543,23 @@ fn resolve_key(sheet: &str, a1: &str) -> PyResult<CellKey> {
 }
 
 fn parse_a1_range(range_str: &str) -> PyResult<(u32, u16, u32, u16)> {
-    let clean = range_str.replace('$', "");
-    let mut parts = clean.split(':');
-    let a = parts.next().unwrap_or("");
-    let b = parts.next().unwrap_or(a);
-
-    let (r1, c1_32) = a1_to_row_col(a).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
-    let (r2, c2_32) = a1_to_row_col(b).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
-
-    let c1: u16 = c1_32.try_into().map_err(|_| {
-        PyErr::new::<PyValueError, _>(format!("Column out of range for Excel: {a}"))
-    })?;
-    let c2: u16 = c2_32.try_into().map_err(|_| {
-        PyErr::new::<PyValueError, _>(format!("Column out of range for Excel: {b}"))
-    })?;
-
-    let (first_row, last_row) = if r1 <= r2 { (r1, r2) } else { (r2, r1) };
-    let (first_col, last_col) = if c1 <= c2 { (c1, c2) } else { (c2, c1) };
-
-    Ok((first_row, first_col, last_row, last_col))
+    let (first_row, first_col, last_row, last_col) = RangeRef::parse(range_str)
+        .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, range_str, msg))?
+        .bounds();
+    // Columns are bounded by MAX_COLS (16,384), so they always fit in u16.
+    Ok((first_row, first_col as u16, last_row, last_col as u16))
 }
 
 fn col_letter_to_index(col_str: &str) -> PyResult<u16> {
-    let mut col: u32 = 0;
-    for ch in col_str.chars() {
-        if !ch.is_ascii_alphabetic() {
-            return Err(PyErr::new::<PyValueError, _>(format!(
-                "Invalid column letter: {col_str}"
-            )));
-        }
-        let uc = ch.to_ascii_uppercase() as u8;
-        col = col * 26 + (uc - b'A' + 1) as u32;
-    }
-    if col == 0 {
-        return Err(PyErr::new::<PyValueError, _>(format!(
-            "Invalid column letter: {col_str}"
-        )));
-    }
-    let idx = (col - 1) as u16;
-    Ok(idx)
+    letters_to_col(col_str)
+        .map(|col| col as u16)
+        .ok_or_else(|| {
+            errors::cell_ref(
+                CAPABILITIES.backend,
+                col_str,
+                format!("Invalid column letter: {col_str}"),
+            )
+        })
 }
 
 // ---------------------------------------------------------------------------
@@ -462,56 +573,31 @@ fn write_cell(
     payload: &CellPayload,
     format: &Format,
 ) -> PyResult<()> {
-    fn parse_bool(s: &str) -> bool {
-        match s.trim().to_ascii_lowercase().as_str() {
-            "true" | "1" | "t" | "yes" | "y" => true,
-            _ => false,
-        }
-    }
-
-    match payload.type_str.as_str() {
-        "blank" => {
+    match payload {
+        CellPayload::Blank => {
             // Write blank with format so the format is preserved.
             ws.write_blank(row, col, format)
                 .map(|_| ())
                 .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_blank failed: {e}")))
         }
-        "string" => {
-            let s = payload.value.as_deref().unwrap_or("");
-            ws.write_string_with_format(row, col, s, format)
-                .map(|_| ())
-                .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_string failed: {e}")))
-        }
-        "number" => {
-            let f_val: f64 = payload
-                .value
-                .as_deref()
-                .unwrap_or("0")
-                .parse()
-                .map_err(|_| PyErr::new::<PyValueError, _>("number parse failed"))?;
-            ws.write_number_with_format(row, col, f_val, format)
-                .map(|_| ())
-                .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_number failed: {e}")))
-        }
-        "boolean" => {
-            let b = payload.value.as_deref().map(parse_bool).unwrap_or(false);
-            ws.write_boolean_with_format(row, col, b, format)
-                .map(|_| ())
-                .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_boolean failed: {e}")))
-        }
-        "formula" => {
-            let formula = payload
-                .formula
-                .as_deref()
-                .or(payload.value.as_deref())
-                .unwrap_or("");
-            ws.write_formula_with_format(row, col, formula, format)
-                .map(|_| ())
-                .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_formula failed: {e}")))
-        }
-        "error" => {
-            let token = payload.value.as_deref().unwrap_or("");
-            let formula = match token {
+        CellPayload::String(s) => ws
+            .write_string_with_format(row, col, s, format)
+            .map(|_| ())
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_string failed: {e}"))),
+        CellPayload::Number(n) => ws
+            .write_number_with_format(row, col, *n, format)
+            .map(|_| ())
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_number failed: {e}"))),
+        CellPayload::Boolean(b) => ws
+            .write_boolean_with_format(row, col, *b, format)
+            .map(|_| ())
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_boolean failed: {e}"))),
+        CellPayload::Formula { formula, .. } => ws
+            .write_formula_with_format(row, col, formula.as_str(), format)
+            .map(|_| ())
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_formula failed: {e}"))),
+        CellPayload::Error(token) => {
+            let formula = match token.as_str() {
                 "#DIV/0!" => Some("=1/0"),
                 "#N/A" => Some("=NA()"),
                 "#VALUE!" => Some("=\"text\"+1"),
@@ -527,33 +613,22 @@ fn write_cell(
                     .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_string failed: {e}")))
             }
         }
-        "date" => {
-            let s = payload.value.as_deref().unwrap_or("");
-            if let Some(d) = parse_iso_date(s) {
-                ws.write_datetime_with_format(row, col, d, format)
-                    .map(|_| ())
-                    .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_datetime failed: {e}")))
-            } else {
-                ws.write_string_with_format(row, col, s, format)
-                    .map(|_| ())
-                    .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_string failed: {e}")))
-            }
-        }
-        "datetime" => {
-            let s = payload.value.as_deref().unwrap_or("");
-            if let Some(dt) = parse_iso_datetime(s) {
-                ws.write_datetime_with_format(row, col, dt, format)
-                    .map(|_| ())
-                    .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_datetime failed: {e}")))
-            } else {
-                ws.write_string_with_format(row, col, s, format)
-                    .map(|_| ())
-                    .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_string failed: {e}")))
-            }
-        }
-        other => Err(PyErr::new::<PyValueError, _>(format!(
-            "Unsupported cell type: {other}"
-        ))),
+        CellPayload::Date(d) => ws
+            .write_datetime_with_format(row, col, *d, format)
+            .map(|_| ())
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_datetime failed: {e}"))),
+        CellPayload::DateTime(dt) => ws
+            .write_datetime_with_format(row, col, *dt, format)
+            .map(|_| ())
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_datetime failed: {e}"))),
+        CellPayload::Time(t) => ws
+            .write_datetime_with_format(row, col, *t, format)
+            .map(|_| ())
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_datetime failed: {e}"))),
+        CellPayload::Duration(d) => ws
+            .write_number_with_format(row, col, payload::duration_to_days(*d), format)
+            .map(|_| ())
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_number failed: {e}"))),
     }
 }
 
@@ -566,11 +641,97 @@ impl RustXlsxWriterBook {
         if self.sheet_names.contains(&sheet.to_string()) {
             Ok(())
         } else {
-            Err(PyErr::new::<PyValueError, _>(format!(
-                "Unknown sheet: {sheet}"
-            )))
+            Err(errors::sheet_not_found(CAPABILITIES.backend, sheet))
+        }
+    }
+
+    /// Report input this writer drops or approximates; see `errors::degrade`.
+    fn degrade(&self, message: impl Into<String>) -> PyResult<()> {
+        errors::degrade(self.strict, CAPABILITIES.backend, message)
+    }
+
+    /// Report format values `build_format` cannot express.
+    fn check_format(&self, cell: &str, fmt: &FormatPayload) -> PyResult<()> {
+        if let Some(ul) = fmt
+            .underline
+            .as_deref()
+            .filter(|u| map_underline(u).is_none())
+        {
+            self.degrade(format!(
+                "Underline style '{ul}' at {cell} written as single"
+            ))?;
+        }
+        if let Some(h) = fmt.h_align.as_deref().filter(|h| map_h_align(h).is_none()) {
+            self.degrade(format!(
+                "Horizontal alignment '{h}' at {cell} written as left"
+            ))?;
+        }
+        if let Some(v) = fmt.v_align.as_deref().filter(|v| map_v_align(v).is_none()) {
+            self.degrade(format!(
+                "Vertical alignment '{v}' at {cell} written as bottom"
+            ))?;
+        }
+        if let Some(indent) = fmt.indent.filter(|i| u8::try_from(*i).is_err()) {
+            self.degrade(format!(
+                "Indent {indent} at {cell} is out of range and was dropped"
+            ))?;
         }
+        Ok(())
+    }
+
+    /// Report border styles `build_format` cannot express.
+    fn check_border(&self, cell: &str, bdr: &BorderPayload) -> PyResult<()> {
+        let edges = [
+            ("top", &bdr.top),
+            ("bottom", &bdr.bottom),
+            ("left", &bdr.left),
+            ("right", &bdr.right),
+            ("diagonal_up", &bdr.diagonal_up),
+            ("diagonal_down", &bdr.diagonal_down),
+        ];
+        for (name, edge) in edges {
+            let style = edge.as_ref().and_then(|e| e.style.as_deref());
+            if let Some(style) = style.filter(|s| map_border_style(s).is_none()) {
+                self.degrade(format!(
+                    "Border style '{style}' on the {name} edge of {cell} written as thin"
+                ))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `(field, value)` pairs of a `write_records` record, in field order.
+fn record_fields<'py>(record: &Bound<'py, PyAny>) -> PyResult<Vec<(String, Bound<'py, PyAny>)>> {
+    if let Ok(dict) = record.downcast::<PyDict>() {
+        return dict
+            .iter()
+            .map(|(k, v)| {
+                let key = k
+                    .extract::<String>()
+                    .map_err(|_| PyErr::new::<PyValueError, _>("record keys must be str"))?;
+                Ok((key, v))
+            })
+            .collect();
     }
+    if record.hasattr("__dataclass_fields__")? {
+        let fields = record
+            .py()
+            .import("dataclasses")?
+            .call_method1("fields", (record,))?;
+        return fields
+            .try_iter()?
+            .map(|field| {
+                let name: String = field?.getattr("name")?.extract()?;
+                let value = record.getattr(name.as_str())?;
+                Ok((name, value))
+            })
+            .collect();
+    }
+    Err(PyErr::new::<PyValueError, _>(format!(
+        "records must be dicts or dataclass instances, got {}",
+        record.get_type().name()?
+    )))
 }
 
 fn quote_sheet_name(sheet: &str) -> String {
@@ -627,6 +788,54 @@ fn map_cf_cell_rule(operator: &str, value_str: &str) -> PyResult<ConditionalForm
     Ok(rule)
 }
 
+/// OOXML cfvo type → writer type. `value_required` is false for min/max.
+fn map_cfvo_type(kind: &str) -> Option<(ConditionalFormatType, bool)> {
+    Some(match kind {
+        "min" => (ConditionalFormatType::Lowest, false),
+        "max" => (ConditionalFormatType::Highest, false),
+        "num" => (ConditionalFormatType::Number, true),
+        "percent" => (ConditionalFormatType::Percent, true),
+        "percentile" => (ConditionalFormatType::Percentile, true),
+        "formula" => (ConditionalFormatType::Formula, true),
+        "autoMin" | "autoMax" => (ConditionalFormatType::Automatic, false),
+        _ => return None,
+    })
+}
+
+/// `min`/`mid`/`max` as a `{"type", "value"}` dict (the umya payload) or as
+/// flat `min_type`/`min_value` keys: (type, value).
+fn extract_scale_point(
+    cfg: &Bound<'_, PyDict>,
+    key: &str,
+) -> PyResult<Option<(String, Option<String>)>> {
+    let value_str = |v: Option<Bound<'_, PyAny>>| -> PyResult<Option<String>> {
+        match v.filter(|v| !v.is_none()) {
+            Some(v) => Ok(Some(v.str()?.to_string())),
+            None => Ok(None),
+        }
+    };
+    if let Some(d) = cfg.get_item(key)?.filter(|v| !v.is_none()) {
+        let d = d.downcast::<PyDict>().map_err(|_| {
+            PyErr::new::<PyValueError, _>(format!("'{key}' must be a {{type, value}} dict"))
+        })?;
+        let kind: String = d
+            .get_item("type")?
+            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("'{key}' missing 'type'")))?
+            .extract()?;
+        return Ok(Some((kind, value_str(d.get_item("value")?)?)));
+    }
+    let Some(kind) = cfg
+        .get_item(format!("{key}_type"))?
+        .filter(|v| !v.is_none())
+    else {
+        return Ok(None);
+    };
+    Ok(Some((
+        kind.extract()?,
+        value_str(cfg.get_item(format!("{key}_value"))?)?,
+    )))
+}
+
 fn map_dv_rule_between_i32(formula1: &str, formula2: &str) -> PyResult<DataValidationRule<i32>> {
     let a: i32 = formula1.trim().parse().map_err(|_| {
         PyErr::new::<PyValueError, _>(format!("Data validation formula1 parse failed: {formula1}"))
@@ -828,101 +1037,31 @@ fn write_default_split_selection(writer: &mut XmlWriter<Vec<u8>>) -> std::io::Re
     writer.write_event(Event::Empty(elem))
 }
 
-fn patch_split_panes_xlsx(path: &str, split_patches: &[(String, i32, i32)]) -> PyResult<()> {
+/// Convert the placeholder freeze panes into split panes. Parts are only
+/// queued once every sheet has been patched.
+fn patch_split_panes(
+    patches: &mut PackagePatches,
+    split_patches: &[(String, i32, i32)],
+) -> PyResult<()> {
     if split_patches.is_empty() {
         return Ok(());
     }
+    let sheet_to_path = patches.sheet_paths()?;
 
-    // Build sheet name -> sheet XML path mapping.
-    let f = File::open(path)
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to open '{path}': {e}")))?;
-    let mut zip = ZipArchive::new(f)
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read xlsx zip: {e}")))?;
-
-    let workbook_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml")?;
-    let rels_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/_rels/workbook.xml.rels")?;
-    let sheet_rids = ooxml_util::parse_workbook_sheet_rids(&workbook_xml)?;
-    let rel_targets = ooxml_util::parse_relationship_targets(&rels_xml)?;
-
-    let mut sheet_to_path: HashMap<String, String> = HashMap::new();
-    for (name, rid) in sheet_rids {
-        if let Some(target) = rel_targets.get(&rid) {
-            sheet_to_path.insert(name, ooxml_util::join_and_normalize("xl/", target));
-        }
-    }
-
-    // Generate patched worksheet XML contents.
-    let mut file_patches: HashMap<String, Vec<u8>> = HashMap::new();
+    let mut patched_parts: Vec<(&str, String)> = Vec::new();
     for (sheet_name, x_split, y_split) in split_patches {
         let Some(sheet_path) = sheet_to_path.get(sheet_name) else {
             continue;
         };
-        let xml = ooxml_util::zip_read_to_string(&mut zip, sheet_path)?;
-        let patched = patch_sheet_xml_split_panes(&xml, *x_split, *y_split)?;
-        file_patches.insert(sheet_path.clone(), patched.into_bytes());
+        let xml = patches.read(sheet_path)?;
+        patched_parts.push((
+            sheet_path,
+            patch_sheet_xml_split_panes(&xml, *x_split, *y_split)?,
+        ));
     }
-    drop(zip);
-
-    if file_patches.is_empty() {
-        return Ok(());
+    for (part, xml) in patched_parts {
+        patches.set(part, xml);
     }
-
-    // Rewrite the zip with patched entries.
-    let src = File::open(path)
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to open '{path}': {e}")))?;
-    let mut zip = ZipArchive::new(src)
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read xlsx zip: {e}")))?;
-
-    let tmp_path = format!("{path}.tmp");
-    let dst = File::create(&tmp_path)
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to create '{tmp_path}': {e}")))?;
-    let mut out = ZipWriter::new(dst);
-
-    for i in 0..zip.len() {
-        let mut file = zip.by_index(i).map_err(|e| {
-            PyErr::new::<PyIOError, _>(format!("Failed to read zip entry {i}: {e}"))
-        })?;
-        let name = file.name().to_string();
-
-        let mut opts = SimpleFileOptions::default().compression_method(file.compression());
-        if let Some(dt) = file.last_modified() {
-            opts = opts.last_modified_time(dt);
-        }
-        if let Some(mode) = file.unix_mode() {
-            opts = opts.unix_permissions(mode);
-        }
-
-        if file.is_dir() {
-            out.add_directory(name, opts).map_err(|e| {
-                PyErr::new::<PyIOError, _>(format!("Failed to add directory to zip: {e}"))
-            })?;
-            continue;
-        }
-
-        let mut data: Vec<u8> = Vec::new();
-        file.read_to_end(&mut data)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read zip entry: {e}")))?;
-        if let Some(patched) = file_patches.get(&name) {
-            data = patched.clone();
-        }
-
-        out.start_file(name, opts)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to write zip entry: {e}")))?;
-        out.write_all(&data)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to write zip entry: {e}")))?;
-    }
-
-    out.finish()
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to finalize zip: {e}")))?;
-
-    if let Err(e) = std::fs::rename(&tmp_path, path) {
-        // On some platforms rename() may not replace; retry with explicit remove.
-        let _ = std::fs::remove_file(path);
-        std::fs::rename(&tmp_path, path).map_err(|e2| {
-            PyErr::new::<PyIOError, _>(format!("Failed to replace file: {e}; {e2}"))
-        })?;
-    }
-
     Ok(())
 }
 
@@ -1078,109 +1217,133 @@ fn patch_table_xml_ref(xml: &str, new_ref: &str) -> PyResult<String> {
         .map_err(|e| PyErr::new::<PyIOError, _>(format!("Table XML not UTF-8: {e}")))
 }
 
-fn patch_tables_xlsx(path: &str, ref_patches: &[(String, String)]) -> PyResult<()> {
+/// Point header-only tables at their real refs. Like split panes, parts
+/// are only queued once every table has been patched.
+fn patch_tables(patches: &mut PackagePatches, ref_patches: &[(String, String)]) -> PyResult<()> {
     if ref_patches.is_empty() {
         return Ok(());
     }
     let patch_map: HashMap<String, String> = ref_patches.iter().cloned().collect();
 
-    let src = File::open(path)
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to open '{path}': {e}")))?;
-    let mut zip = ZipArchive::new(src)
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read xlsx zip: {e}")))?;
-
-    let mut file_patches: HashMap<String, Vec<u8>> = HashMap::new();
-
-    for i in 0..zip.len() {
-        let mut file = zip.by_index(i).map_err(|e| {
-            PyErr::new::<PyIOError, _>(format!("Failed to read zip entry {i}: {e}"))
-        })?;
-        let name = file.name().to_string();
+    let mut patched_parts: Vec<(String, String)> = Vec::new();
+    for name in patches.names() {
         if !name.starts_with("xl/tables/") || !name.ends_with(".xml") {
             continue;
         }
-
-        let mut data: Vec<u8> = Vec::new();
-        file.read_to_end(&mut data)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read zip entry: {e}")))?;
-        let xml = String::from_utf8_lossy(&data).to_string();
+        let xml = patches.read(&name)?;
         let Some(tname) = extract_table_name(&xml) else {
             continue;
         };
         let Some(new_ref) = patch_map.get(&tname) else {
             continue;
         };
-        let patched = patch_table_xml_ref(&xml, new_ref)?;
-        file_patches.insert(name, patched.into_bytes());
+        patched_parts.push((name, patch_table_xml_ref(&xml, new_ref)?));
     }
-
-    drop(zip);
-    if file_patches.is_empty() {
-        return Ok(());
+    for (part, xml) in patched_parts {
+        patches.set(&part, xml);
     }
+    Ok(())
+}
 
-    // Rewrite the zip with patched table entries.
-    let src = File::open(path)
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to open '{path}': {e}")))?;
-    let mut zip = ZipArchive::new(src)
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read xlsx zip: {e}")))?;
+// ---------------------------------------------------------------------------
+// OOXML post-processing (gradient fills)
+// ---------------------------------------------------------------------------
 
-    let tmp_path = format!("{path}.tmp");
-    let dst = File::create(&tmp_path)
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to create '{tmp_path}': {e}")))?;
-    let mut out = ZipWriter::new(dst);
+/// Replace the `gradient_placeholder` fills in styles.xml with the
+/// gradients they stand for.
+fn patch_gradient_fills(
+    patches: &mut PackagePatches,
+    gradients: &[&GradientFillDef],
+) -> PyResult<()> {
+    if gradients.is_empty() {
+        return Ok(());
+    }
+    let spec = |n: u32| Some(ColorSpec::rgb([(n >> 16) as u8, (n >> 8) as u8, n as u8]));
+    let placeholders: Vec<(Option<ColorSpec>, Option<ColorSpec>, String)> = gradients
+        .iter()
+        .map(|g| {
+            let (fg, bg) = gradient_placeholder(g);
+            (spec(fg), spec(bg), g.to_xml())
+        })
+        .collect();
+
+    let styles_xml = patches.read("xl/styles.xml")?;
+    let patched = fills::replace_fills(&styles_xml, |fill| match fill {
+        FillDef::Pattern(p) if p.pattern_type == "gray0625" => placeholders
+            .iter()
+            .find(|(fg, bg, _)| *fg == p.fg && *bg == p.bg)
+            .map(|(_, _, xml)| xml.clone()),
+        _ => None,
+    })
+    .map_err(PyErr::new::<PyIOError, _>)?;
+    patches.set("xl/styles.xml", patched);
+    Ok(())
+}
 
-    for i in 0..zip.len() {
-        let mut file = zip.by_index(i).map_err(|e| {
-            PyErr::new::<PyIOError, _>(format!("Failed to read zip entry {i}: {e}"))
-        })?;
-        let name = file.name().to_string();
+pub(crate) const BACKEND: BackendEntry = BackendEntry {
+    name: "rust_xlsxwriter",
+    open: |_py, path| {
+        backend::reject_path("rust_xlsxwriter", path)?;
+        Ok(Box::new(RustXlsxWriterBook::new(None, false)))
+    },
+};
 
-        let mut opts = SimpleFileOptions::default().compression_method(file.compression());
-        if let Some(dt) = file.last_modified() {
-            opts = opts.last_modified_time(dt);
-        }
-        if let Some(mode) = file.unix_mode() {
-            opts = opts.unix_permissions(mode);
-        }
+impl Backend for RustXlsxWriterBook {
+    fn into_py_object(self: Box<Self>, py: Python<'_>) -> PyResult<PyObject> {
+        pyclass_object(py, *self)
+    }
 
-        if file.is_dir() {
-            out.add_directory(name, opts).map_err(|e| {
-                PyErr::new::<PyIOError, _>(format!("Failed to add directory to zip: {e}"))
-            })?;
-            continue;
-        }
+    fn as_writer(&mut self) -> Option<&mut dyn ExcelWriteBackend> {
+        Some(self)
+    }
+}
 
-        let mut data: Vec<u8> = Vec::new();
-        file.read_to_end(&mut data)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read zip entry: {e}")))?;
-        if let Some(patched) = file_patches.get(&name) {
-            data = patched.clone();
-        }
+impl ExcelWriteBackend for RustXlsxWriterBook {
+    fn add_sheet(&mut self, name: &str) -> PyResult<()> {
+        RustXlsxWriterBook::add_sheet(self, name)
+    }
 
-        out.start_file(name, opts)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to write zip entry: {e}")))?;
-        out.write_all(&data)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to write zip entry: {e}")))?;
+    fn write_cell_value(
+        &mut self,
+        sheet: &str,
+        a1: &str,
+        payload: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        RustXlsxWriterBook::write_cell_value(self, sheet, a1, payload)
     }
 
-    out.finish()
-        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to finalize zip: {e}")))?;
+    fn write_cell_format(
+        &mut self,
+        sheet: &str,
+        a1: &str,
+        format_dict: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        RustXlsxWriterBook::write_cell_format(self, sheet, a1, format_dict)
+    }
 
-    if let Err(e) = std::fs::rename(&tmp_path, path) {
-        let _ = std::fs::remove_file(path);
-        std::fs::rename(&tmp_path, path).map_err(|e2| {
-            PyErr::new::<PyIOError, _>(format!("Failed to replace file: {e}; {e2}"))
-        })?;
+    fn write_cell_border(
+        &mut self,
+        sheet: &str,
+        a1: &str,
+        border_dict: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        RustXlsxWriterBook::write_cell_border(self, sheet, a1, border_dict)
     }
 
-    Ok(())
+    fn save(&mut self, py: Python<'_>, path: &str) -> PyResult<()> {
+        RustXlsxWriterBook::save(self, py, path)
+    }
 }
 
 #[pymethods]
 impl RustXlsxWriterBook {
+    /// `path` is only used by the context manager, which saves there on a
+    /// clean exit. With `strict`, input the writer would otherwise drop or
+    /// approximate (unknown style names, unsupported conditional rule types,
+    /// pane modes) raises `UnsupportedFeature`.
     #[new]
-    pub fn new() -> Self {
+    #[pyo3(signature = (path=None, strict=false))]
+    pub fn new(path: Option<String>, strict: bool) -> Self {
         Self {
             sheet_names: Vec::new(),
             values: IndexMap::new(),
@@ -1192,14 +1355,53 @@ impl RustXlsxWriterBook {
             hyperlinks: Vec::new(),
             comments: Vec::new(),
             panes: HashMap::new(),
+            autofit: HashMap::new(),
+            sheet_defaults: HashMap::new(),
             conditional_formats: Vec::new(),
             data_validations: Vec::new(),
             named_ranges: Vec::new(),
             tables: Vec::new(),
+            ignore_errors: Vec::new(),
+            calc_pr: Vec::new(),
+            backgrounds: HashMap::new(),
+            watermarks: HashMap::new(),
+            vba_project: None,
+            vba_names: HashMap::new(),
             saved: false,
+            save_path: path,
+            strict,
         }
     }
 
+    /// Discard everything buffered without saving; the book cannot be saved
+    /// afterwards.
+    pub fn close(&mut self) {
+        *self = Self::new(None, self.strict);
+        self.saved = true;
+    }
+
+    pub fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Save to the constructor's `path` when the block exits cleanly, then
+    /// close. On an exception nothing is written.
+    #[pyo3(signature = (exc_type=None, _exc_value=None, _traceback=None))]
+    pub fn __exit__(
+        &mut self,
+        py: Python<'_>,
+        exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        let result = match (&exc_type, self.save_path.clone()) {
+            (None, Some(path)) if !self.saved => self.save(py, &path),
+            _ => Ok(()),
+        };
+        self.close();
+        result.map(|_| false)
+    }
+
     pub fn add_sheet(&mut self, name: &str) -> PyResult<()> {
         if self.sheet_names.contains(&name.to_string()) {
             return Ok(());
@@ -1218,34 +1420,8 @@ impl RustXlsxWriterBook {
 
         let key = resolve_key(sheet, a1)?;
 
-        let dict = payload
-            .downcast::<PyDict>()
-            .map_err(|_| PyErr::new::<PyValueError, _>("payload must be a dict"))?;
-        let type_str: String = dict
-            .get_item("type")?
-            .ok_or_else(|| PyErr::new::<PyValueError, _>("payload missing 'type'"))?
-            .extract()?;
-
-        // Store the value for deferred writing.
-        let value_str: Option<String> = dict.get_item("value")?.and_then(|v| {
-            v.extract::<String>().ok().or_else(|| {
-                // Handle numeric/bool values by converting to string.
-                v.extract::<f64>()
-                    .map(|n| n.to_string())
-                    .ok()
-                    .or_else(|| v.extract::<bool>().map(|b| b.to_string()).ok())
-            })
-        });
-        let formula_str: Option<String> = dict.get_item("formula")?.and_then(|v| v.extract().ok());
-
-        self.values.insert(
-            key,
-            CellPayload {
-                type_str,
-                value: value_str,
-                formula: formula_str,
-            },
-        );
+        let parsed = payload::parse_cell_payload(payload)?;
+        self.values.insert(key, parsed);
 
         Ok(())
     }
@@ -1263,8 +1439,8 @@ impl RustXlsxWriterBook {
     ) -> PyResult<()> {
         self.ensure_sheet_exists(sheet)?;
 
-        let (base_row, base_col_32) =
-            a1_to_row_col(start_a1).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
+        let (base_row, base_col_32) = a1_to_row_col(start_a1)
+            .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, start_a1, msg))?;
         let base_col: u16 = base_col_32.try_into().map_err(|_| {
             PyErr::new::<PyValueError, _>(format!("Column out of range: {start_a1}"))
         })?;
@@ -1282,49 +1458,133 @@ impl RustXlsxWriterBook {
 
                 // Infer type from Python object.
                 if let Ok(f) = val.extract::<f64>() {
-                    self.values.insert(
-                        key,
-                        CellPayload {
-                            type_str: "number".to_string(),
-                            value: Some(f.to_string()),
-                            formula: None,
-                        },
-                    );
+                    self.values.insert(key, CellPayload::Number(f));
                 } else if let Ok(i) = val.extract::<i64>() {
-                    self.values.insert(
-                        key,
-                        CellPayload {
-                            type_str: "number".to_string(),
-                            value: Some((i as f64).to_string()),
-                            formula: None,
-                        },
-                    );
+                    self.values.insert(key, CellPayload::Number(i as f64));
                 } else if let Ok(s) = val.extract::<String>() {
-                    self.values.insert(
-                        key,
-                        CellPayload {
-                            type_str: "string".to_string(),
-                            value: Some(s),
-                            formula: None,
-                        },
-                    );
+                    self.values.insert(key, CellPayload::String(s));
                 } else if let Ok(b) = val.extract::<bool>() {
-                    self.values.insert(
-                        key,
-                        CellPayload {
-                            type_str: "boolean".to_string(),
-                            value: Some(b.to_string()),
-                            formula: None,
-                        },
-                    );
+                    self.values.insert(key, CellPayload::Boolean(b));
+                } else {
+                    self.degrade(format!(
+                        "Skipped {} value at row {row}, col {col} of '{sheet}'",
+                        val.get_type().name()?
+                    ))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write `records` (dicts or dataclass instances) as a table starting at
+    /// A1: a header row of field names, unless `header` is False, then one
+    /// typed row per record.
+    ///
+    /// Columns follow the first record's keys; keys that first appear in a
+    /// later record are appended. Missing keys and None values leave the
+    /// cell empty. Values are typed as bool, number, str, naive datetime,
+    /// date, time or timedelta, so dates get date formats on save.
+    #[pyo3(signature = (sheet, records, header=true))]
+    pub fn write_records(
+        &mut self,
+        sheet: &str,
+        records: &Bound<'_, PyAny>,
+        header: bool,
+    ) -> PyResult<()> {
+        self.ensure_sheet_exists(sheet)?;
+
+        let mut columns: IndexMap<String, u16> = IndexMap::new();
+        let mut rows = Vec::new();
+        for record in records.try_iter()? {
+            let fields = record_fields(&record?)?;
+            for (name, _) in &fields {
+                if !columns.contains_key(name) {
+                    let col = u16::try_from(columns.len())
+                        .ok()
+                        .filter(|&col| u32::from(col) < MAX_COLS)
+                        .ok_or_else(|| {
+                            PyErr::new::<PyValueError, _>(format!(
+                                "Records have more than {MAX_COLS} fields"
+                            ))
+                        })?;
+                    columns.insert(name.clone(), col);
                 }
-                // else: skip unsupported types silently.
             }
+            rows.push(fields);
         }
 
+        let first_row = u32::from(header);
+        if header {
+            for (name, &col) in &columns {
+                self.values.insert(
+                    (sheet.to_string(), 0, col),
+                    CellPayload::String(name.clone()),
+                );
+            }
+        }
+        for (i, fields) in rows.iter().enumerate() {
+            let row = first_row + i as u32;
+            for (name, value) in fields {
+                if value.is_none() {
+                    continue;
+                }
+                let col = columns[name];
+                match payload::infer_cell_payload(value) {
+                    Some(cell) => {
+                        self.values.insert((sheet.to_string(), row, col), cell);
+                    }
+                    None => self.degrade(format!(
+                        "Skipped {} value for '{name}' in record {i} of '{sheet}'",
+                        value.get_type().name()?
+                    ))?,
+                }
+            }
+        }
         Ok(())
     }
 
+    /// Load a CSV file (`str` path) or `bytes` into `sheet`, starting at A1.
+    ///
+    /// The sheet is created if missing. `options` keys: `delimiter` (default
+    /// `","`), `quote` (default `'"'`, None disables quoting), `encoding`
+    /// (`"utf-8"`, `"utf-8-sig"` or `"latin-1"`) and `infer_types` (default
+    /// True: unquoted numbers, booleans and ISO dates are typed; quoted fields
+    /// stay strings). Parsing runs with the GIL released. Returns the number
+    /// of rows loaded.
+    #[pyo3(signature = (path_or_bytes, sheet, options=None))]
+    pub fn load_csv(
+        &mut self,
+        py: Python<'_>,
+        path_or_bytes: &Bound<'_, PyAny>,
+        sheet: &str,
+        options: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<usize> {
+        let rows = csv_io::load(py, path_or_bytes, options)?;
+        self.add_sheet(sheet)?;
+
+        let count = rows.len();
+        for (r, row) in rows.into_iter().enumerate() {
+            for (c, value) in row.into_iter().enumerate() {
+                if value == CellPayload::Blank {
+                    continue;
+                }
+                let col: u16 = u16::try_from(c)
+                    .ok()
+                    .filter(|&col| col < 16_384)
+                    .ok_or_else(|| {
+                        PyErr::new::<PyValueError, _>(format!(
+                            "CSV row {} has more columns than Excel allows",
+                            r + 1
+                        ))
+                    })?;
+                self.values
+                    .insert((sheet.to_string(), r as u32, col), value);
+            }
+        }
+        Ok(count)
+    }
+
     pub fn write_cell_format(
         &mut self,
         sheet: &str,
@@ -1333,11 +1593,9 @@ impl RustXlsxWriterBook {
     ) -> PyResult<()> {
         self.ensure_sheet_exists(sheet)?;
         let key = resolve_key(sheet, a1)?;
-        let dict = format_dict
-            .downcast::<PyDict>()
-            .map_err(|_| PyErr::new::<PyValueError, _>("format_dict must be a dict"))?;
-        let fields = extract_format_fields(dict)?;
-        self.formats.insert(key, fields);
+        let fmt = payload::parse_format(format_dict)?;
+        self.check_format(a1, &fmt)?;
+        self.formats.insert(key, fmt);
         Ok(())
     }
 
@@ -1349,11 +1607,9 @@ impl RustXlsxWriterBook {
     ) -> PyResult<()> {
         self.ensure_sheet_exists(sheet)?;
         let key = resolve_key(sheet, a1)?;
-        let dict = border_dict
-            .downcast::<PyDict>()
-            .map_err(|_| PyErr::new::<PyValueError, _>("border_dict must be a dict"))?;
-        let fields = extract_border_fields(dict)?;
-        self.borders.insert(key, fields);
+        let bdr = payload::parse_border(border_dict)?;
+        self.check_border(a1, &bdr)?;
+        self.borders.insert(key, bdr);
         Ok(())
     }
 
@@ -1370,6 +1626,82 @@ impl RustXlsxWriterBook {
         Ok(())
     }
 
+    /// Autofit the used columns of `sheet` on save, via rust_xlsxwriter's
+    /// `autofit()`. `max_width` caps the fitted width; `min_width` widens
+    /// narrow columns. Widths set with `set_column_width` take precedence.
+    #[pyo3(signature = (sheet, min_width=None, max_width=None))]
+    pub fn autofit_columns(
+        &mut self,
+        sheet: &str,
+        min_width: Option<f64>,
+        max_width: Option<f64>,
+    ) -> PyResult<()> {
+        self.ensure_sheet_exists(sheet)?;
+        if let (Some(min), Some(max)) = (min_width, max_width) {
+            if min > max {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "min_width ({min}) exceeds max_width ({max})"
+                )));
+            }
+        }
+        self.autofit.insert(
+            sheet.to_string(),
+            AutofitSetting {
+                min_width,
+                max_width,
+            },
+        );
+        Ok(())
+    }
+
+    /// Height in points of rows without an explicit height.
+    pub fn set_default_row_height(&mut self, sheet: &str, height: f64) -> PyResult<()> {
+        self.ensure_sheet_exists(sheet)?;
+        if !(height > 0.0 && height <= 409.0) {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "Default row height must be in (0, 409], got {height}"
+            )));
+        }
+        self.sheet_defaults_mut(sheet).row_height = Some(height);
+        Ok(())
+    }
+
+    /// Width in characters of columns without an explicit width.
+    pub fn set_default_column_width(&mut self, sheet: &str, width: f64) -> PyResult<()> {
+        self.ensure_sheet_exists(sheet)?;
+        if !(0.0..=255.0).contains(&width) {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "Default column width must be in [0, 255], got {width}"
+            )));
+        }
+        self.sheet_defaults_mut(sheet).col_width = Some(width);
+        Ok(())
+    }
+
+    /// Hide every row that isn't written or given a height (`zeroHeight`).
+    #[pyo3(signature = (sheet, hide=true))]
+    pub fn hide_unused_rows(&mut self, sheet: &str, hide: bool) -> PyResult<()> {
+        self.ensure_sheet_exists(sheet)?;
+        self.sheet_defaults_mut(sheet).hide_unused_rows = hide;
+        Ok(())
+    }
+
+    /// Where outline summary rows and columns sit relative to their detail:
+    /// Excel's defaults are below and to the right.
+    #[pyo3(signature = (sheet, summary_below=true, summary_right=true))]
+    pub fn set_outline_settings(
+        &mut self,
+        sheet: &str,
+        summary_below: bool,
+        summary_right: bool,
+    ) -> PyResult<()> {
+        self.ensure_sheet_exists(sheet)?;
+        let defaults = self.sheet_defaults_mut(sheet);
+        defaults.summary_below = summary_below;
+        defaults.summary_right = summary_right;
+        Ok(())
+    }
+
     // =========================================================================
     // Tier 2 Write Operations
     // =========================================================================
@@ -1427,7 +1759,8 @@ impl RustXlsxWriterBook {
             .and_then(|v| v.extract::<bool>().ok())
             .unwrap_or(false);
 
-        let (row, col0) = a1_to_row_col(&cell).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
+        let (row, col0) = a1_to_row_col(&cell)
+            .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, &cell, msg))?;
         let col: u16 = col0.try_into().map_err(|_| {
             PyErr::new::<PyValueError, _>(format!("Column out of range for Excel: {cell}"))
         })?;
@@ -1449,6 +1782,8 @@ impl RustXlsxWriterBook {
         Ok(())
     }
 
+    /// Queue a note: `{"cell", "text", "author"}` plus optional `width` /
+    /// `height` (pixels), `visible` and `bg_color` (`"#RRGGBB"`).
     pub fn add_comment(&mut self, sheet: &str, comment_dict: &Bound<'_, PyAny>) -> PyResult<()> {
         self.ensure_sheet_exists(sheet)?;
 
@@ -1480,7 +1815,26 @@ impl RustXlsxWriterBook {
             .and_then(|v| v.extract::<String>().ok())
             .and_then(|s| if s.is_empty() { None } else { Some(s) });
 
-        let (row, col0) = a1_to_row_col(&cell).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
+        let pixels = |key: &str| -> PyResult<Option<u32>> {
+            match cfg.get_item(key)? {
+                Some(v) if !v.is_none() => v.extract::<u32>().map(Some).map_err(|_| {
+                    PyErr::new::<PyValueError, _>(format!(
+                        "comment '{key}' must be a non-negative int (pixels)"
+                    ))
+                }),
+                _ => Ok(None),
+            }
+        };
+        let width = pixels("width")?;
+        let height = pixels("height")?;
+        let visible: Option<bool> = cfg.get_item("visible")?.and_then(|v| v.extract().ok());
+        let bg_color: Option<String> = cfg
+            .get_item("bg_color")?
+            .and_then(|v| v.extract::<String>().ok())
+            .and_then(|s| if s.is_empty() { None } else { Some(s) });
+
+        let (row, col0) = a1_to_row_col(&cell)
+            .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, &cell, msg))?;
         let col: u16 = col0.try_into().map_err(|_| {
             PyErr::new::<PyValueError, _>(format!("Column out of range for Excel: {cell}"))
         })?;
@@ -1491,6 +1845,10 @@ impl RustXlsxWriterBook {
             col,
             text,
             author,
+            width,
+            height,
+            visible,
+            bg_color,
         });
         Ok(())
     }
@@ -1519,8 +1877,8 @@ impl RustXlsxWriterBook {
                 .get_item("top_left_cell")?
                 .and_then(|v| v.extract::<String>().ok());
             if let Some(cell) = top_left {
-                let (row, col0) =
-                    a1_to_row_col(&cell).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
+                let (row, col0) = a1_to_row_col(&cell)
+                    .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, &cell, msg))?;
                 let col: u16 = col0.try_into().map_err(|_| {
                     PyErr::new::<PyValueError, _>(format!("Column out of range for Excel: {cell}"))
                 })?;
@@ -1538,6 +1896,8 @@ impl RustXlsxWriterBook {
                 .unwrap_or(0.0);
             self.panes
                 .insert(sheet.to_string(), PaneSetting::Split { x_split, y_split });
+        } else {
+            self.degrade(format!("Pane mode '{mode}' on '{sheet}' ignored"))?;
         }
 
         Ok(())
@@ -1567,11 +1927,28 @@ impl RustXlsxWriterBook {
         let range: Option<String> = cfg.get_item("range")?.and_then(|v| v.extract().ok());
         let rule_type: Option<String> = cfg.get_item("rule_type")?.and_then(|v| v.extract().ok());
         let (Some(range), Some(rule_type)) = (range, rule_type) else {
-            return Ok(());
+            return self.degrade(format!(
+                "Conditional format on '{sheet}' without a range and rule_type ignored"
+            ));
         };
 
         let operator: Option<String> = cfg.get_item("operator")?.and_then(|v| v.extract().ok());
         let formula: Option<String> = cfg.get_item("formula")?.and_then(|v| v.extract().ok());
+        let missing = match rule_type.as_str() {
+            "cellIs" if operator.is_none() || formula.is_none() => Some("an operator and formula"),
+            "expression" if formula.is_none() => Some("a formula"),
+            "cellIs" | "expression" | "dataBar" | "colorScale" => None,
+            other => {
+                return self.degrade(format!(
+                    "Conditional rule type '{other}' on {sheet}!{range} is not supported; skipped"
+                ));
+            }
+        };
+        if let Some(missing) = missing {
+            return self.degrade(format!(
+                "{rule_type} rule on {sheet}!{range} needs {missing}; skipped"
+            ));
+        }
         let stop_if_true: bool = cfg
             .get_item("stop_if_true")?
             .and_then(|v| v.extract::<bool>().ok())
@@ -1584,6 +1961,64 @@ impl RustXlsxWriterBook {
             }
         }
 
+        let scale = matches!(rule_type.as_str(), "dataBar" | "colorScale");
+        let mut points: [Option<ScalePoint>; 3] = [None, None, None];
+        let mut colors: Vec<String> = Vec::new();
+        let mut bar_border_color: Option<String> = None;
+        let mut negative_fill: Option<String> = None;
+        if scale {
+            for (slot, key) in points.iter_mut().zip(["min", "mid", "max"]) {
+                if key == "mid" && rule_type == "dataBar" {
+                    continue;
+                }
+                let Some((kind, value)) = extract_scale_point(cfg, key)? else {
+                    continue;
+                };
+                match map_cfvo_type(&kind) {
+                    Some((_, true)) if value.is_none() => self.degrade(format!(
+                        "{rule_type} {key} of type '{kind}' on {sheet}!{range} has no value; \
+                         using the default"
+                    ))?,
+                    Some((kind, _)) => *slot = Some(ScalePoint { kind, value }),
+                    None => self.degrade(format!(
+                        "{rule_type} {key} type '{kind}' on {sheet}!{range} is not supported; \
+                         using the default"
+                    ))?,
+                }
+            }
+            colors = match (cfg.get_item("colors")?, cfg.get_item("color")?) {
+                (Some(v), _) if !v.is_none() => v.extract().map_err(|_| {
+                    PyErr::new::<PyValueError, _>("'colors' must be a list of hex strings")
+                })?,
+                (_, Some(v)) if !v.is_none() => vec![v
+                    .extract()
+                    .map_err(|_| PyErr::new::<PyValueError, _>("'color' must be a hex string"))?],
+                _ => Vec::new(),
+            };
+            let expected = match (rule_type.as_str(), colors.len()) {
+                ("dataBar", 0 | 1) => None,
+                ("dataBar", _) => Some("exactly one color"),
+                (_, 0 | 3) => None,
+                _ if points[1].is_some() => Some("3 colors (min/mid/max)"),
+                (_, 2) => None,
+                _ => Some("2 colors (min/max) or 3 colors (min/mid/max)"),
+            };
+            if let Some(expected) = expected {
+                return self.degrade(format!(
+                    "{rule_type} on {sheet}!{range} takes {expected}; skipped"
+                ));
+            }
+            if rule_type == "dataBar" {
+                bar_border_color = cfg
+                    .get_item("bar_border_color")?
+                    .and_then(|v| v.extract().ok());
+                negative_fill = cfg
+                    .get_item("negative_fill")?
+                    .and_then(|v| v.extract().ok());
+            }
+        }
+        let [min, mid, max] = points;
+
         self.conditional_formats.push(ConditionalFormatPayload {
             sheet: sheet.to_string(),
             range,
@@ -1592,6 +2027,12 @@ impl RustXlsxWriterBook {
             formula,
             stop_if_true,
             bg_color,
+            min,
+            mid,
+            max,
+            colors,
+            bar_border_color,
+            negative_fill,
         });
         Ok(())
     }
@@ -1744,13 +2185,103 @@ impl RustXlsxWriterBook {
         Ok(())
     }
 
-    pub fn save(&mut self, path: &str) -> PyResult<()> {
+    /// Workbook calculation mode: "auto", "manual" or "auto_except_tables".
+    pub fn set_calc_mode(&mut self, mode: &str) -> PyResult<()> {
+        let value = calc_pr::calc_mode_value(mode).map_err(PyErr::new::<PyValueError, _>)?;
+        calc_pr::set_edit(&mut self.calc_pr, "calcMode", Some(value.to_string()));
+        Ok(())
+    }
+
+    /// Whether Excel recalculates every formula when the file is opened.
+    #[pyo3(signature = (enabled=true))]
+    pub fn set_full_calc_on_load(&mut self, enabled: bool) {
+        let value = enabled.then(|| "1".to_string());
+        calc_pr::set_edit(&mut self.calc_pr, "fullCalcOnLoad", value);
+    }
+
+    /// Suppress Excel's green-triangle warnings, e.g.
+    /// `{"number_stored_as_text": "A2:A100", "formula": "C1"}`.
+    ///
+    /// Keys are `number_stored_as_text`, `eval_error`, `formula`,
+    /// `formula_range`, `unlocked_formula`, `empty_cell_reference`,
+    /// `list_data_validation`, `calculated_column` and `two_digit_text_year`;
+    /// a value may list several space-separated ranges.
+    pub fn ignore_errors(&mut self, sheet: &str, rules: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.ensure_sheet_exists(sheet)?;
+        let dict = rules
+            .downcast::<PyDict>()
+            .map_err(|_| PyErr::new::<PyValueError, _>("rules must be a dict"))?;
+
+        for (key, value) in dict.iter() {
+            let kind = ignore_error_kind(&key.extract::<String>()?)?;
+            let ranges: String = value.extract()?;
+            for range in ranges.split_whitespace() {
+                self.ignore_errors.push(IgnoreErrorPayload {
+                    sheet: sheet.to_string(),
+                    kind,
+                    range: parse_a1_range(range)?,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Tile `image_path` behind the cells of `sheet` (screen only; Excel
+    /// doesn't print sheet backgrounds).
+    pub fn set_background(&mut self, sheet: &str, image_path: &str) -> PyResult<()> {
+        self.ensure_sheet_exists(sheet)?;
+        load_image(image_path)?;
+        self.backgrounds
+            .insert(sheet.to_string(), image_path.to_string());
+        Ok(())
+    }
+
+    /// Watermark `sheet` with `image_path`, Excel-style: the image is the
+    /// centered header picture, so it shows in Page Layout view and on
+    /// every printed page.
+    pub fn set_watermark(&mut self, sheet: &str, image_path: &str) -> PyResult<()> {
+        self.ensure_sheet_exists(sheet)?;
+        load_image(image_path)?;
+        self.watermarks
+            .insert(sheet.to_string(), image_path.to_string());
+        Ok(())
+    }
+
+    /// Embed the macros in `vba_path` (a `vbaProject.bin` extracted from an
+    /// existing `.xlsm`). The workbook must then be saved as `.xlsm`.
+    pub fn add_vba_project(&mut self, vba_path: &str) -> PyResult<()> {
+        std::fs::metadata(vba_path).map_err(|e| {
+            PyErr::new::<PyIOError, _>(format!("Cannot read VBA project '{vba_path}': {e}"))
+        })?;
+        self.vba_project = Some(vba_path.to_string());
+        Ok(())
+    }
+
+    /// Set the VBA code name of the workbook, or of `sheet` when given
+    /// (`ThisWorkbook` / `Sheet1` by default). Macros refer to objects by it.
+    #[pyo3(signature = (name, sheet=None))]
+    pub fn set_vba_name(&mut self, name: &str, sheet: Option<&str>) -> PyResult<()> {
+        if let Some(sheet) = sheet {
+            self.ensure_sheet_exists(sheet)?;
+        }
+        self.vba_names
+            .insert(sheet.map(str::to_string), name.to_string());
+        Ok(())
+    }
+
+    /// Build and write the workbook. The book is consumed only once the file
+    /// is complete; a save that fails can be retried.
+    pub fn save(&mut self, py: Python<'_>, path: &str) -> PyResult<()> {
+        if self.vba_project.is_some() && !path.to_ascii_lowercase().ends_with(".xlsm") {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "A workbook with a VBA project must be saved as .xlsm, not '{path}'"
+            )));
+        }
         if self.saved {
             return Err(PyErr::new::<PyValueError, _>(
                 "Workbook already saved (RustXlsxWriterBook is consumed-on-save)",
             ));
         }
-        self.saved = true;
 
         let mut wb = Workbook::new();
 
@@ -1781,6 +2312,16 @@ impl RustXlsxWriterBook {
             }
         }
 
+        for ie in &self.ignore_errors {
+            if let Some(ws) = ws_map.get_mut(&ie.sheet) {
+                let (r1, c1, r2, c2) = ie.range;
+                ws.ignore_error_range(r1, c1, r2, c2, ie.kind)
+                    .map_err(|e| {
+                        PyErr::new::<PyIOError, _>(format!("ignore_error_range failed: {e}"))
+                    })?;
+            }
+        }
+
         let mut split_patches: Vec<(String, i32, i32)> = Vec::new();
         let mut table_ref_patches: Vec<(String, String)> = Vec::new();
 
@@ -1835,49 +2376,47 @@ impl RustXlsxWriterBook {
             }
         }
 
-        // Merged ranges.
-        let merge_format = Format::new();
+        // Merged ranges. merge_range() only takes a string, so a string
+        // top-left value is written through it; any other payload is left
+        // to the cell pass below, which overwrites the placeholder with the
+        // typed value. Either way the range carries the top-left's format.
+        let mut merged_strings: HashSet<CellKey> = HashSet::new();
         for m in &self.merge_ranges {
             if let Some(ws) = ws_map.get_mut(&m.sheet) {
+                let key = (m.sheet.clone(), m.first_row, m.first_col);
+                let format = build_format(self.formats.get(&key), self.borders.get(&key))?;
+                let text = match self.values.get(&key) {
+                    Some(CellPayload::String(s)) => s.as_str(),
+                    _ => "",
+                };
                 ws.merge_range(
                     m.first_row,
                     m.first_col,
                     m.last_row,
                     m.last_col,
-                    "",
-                    &merge_format,
+                    text,
+                    &format,
                 )
                 .map(|_| ())
                 .map_err(|e| PyErr::new::<PyIOError, _>(format!("merge_range failed: {e}")))?;
+                if matches!(self.values.get(&key), Some(CellPayload::String(_))) {
+                    merged_strings.insert(key);
+                }
             }
         }
 
-        // Write all cells with merged format+border.
-        for (key, payload) in &self.values {
-            let (ref sheet, row, col) = *key;
-            let fmt_fields = self.formats.get(key);
-            let bdr_fields = self.borders.get(key);
-            let mut format = build_format(fmt_fields, bdr_fields)?;
-
-            // Apply default date/datetime number format only if the user
-            // didn't already provide one via write_cell_format.
-            let has_user_nf = fmt_fields.and_then(|f| f.number_format.as_ref()).is_some();
-            if !has_user_nf {
-                if payload.type_str == "date" {
-                    format = format.set_num_format("yyyy-mm-dd");
-                } else if payload.type_str == "datetime" {
-                    format = format.set_num_format("yyyy-mm-dd hh:mm:ss");
-                }
+        // Write all cells with merged format+border. Cells are grouped per
+        // sheet so independent worksheets are filled on the rayon pool.
+        let mut sheet_cells: HashMap<&str, Vec<&CellKey>> = HashMap::new();
+        for key in self.values.keys() {
+            if !ws_map.contains_key(&key.0) {
+                return Err(errors::sheet_not_found(CAPABILITIES.backend, &key.0));
+            }
+            if !merged_strings.contains(key) {
+                sheet_cells.entry(key.0.as_str()).or_default().push(key);
             }
-
-            let ws = ws_map
-                .get_mut(sheet)
-                .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
-
-            write_cell(ws, row, col, payload, &format)?;
         }
-
-        // Write formats for cells that have format/border but no value
+        // Then formats for cells that have format/border but no value
         // (e.g., blank cells with borders).
         let format_only_keys: HashSet<_> = self
             .formats
@@ -1886,15 +2425,16 @@ impl RustXlsxWriterBook {
             .filter(|k| !self.values.contains_key(*k))
             .collect();
         for key in format_only_keys {
-            let (ref sheet, row, col) = *key;
-            let fmt_fields = self.formats.get(key);
-            let bdr_fields = self.borders.get(key);
-            let format = build_format(fmt_fields, bdr_fields)?;
-            if let Some(ws) = ws_map.get_mut(sheet) {
-                ws.write_blank(row, col, &format)
-                    .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_blank failed: {e}")))?;
-            }
+            sheet_cells.entry(key.0.as_str()).or_default().push(key);
         }
+        let jobs: Vec<_> = ws_map
+            .iter_mut()
+            .filter_map(|(name, ws)| Some((ws, sheet_cells.get(name.as_str())?)))
+            .collect();
+        py.allow_threads(|| {
+            jobs.into_par_iter()
+                .try_for_each(|(ws, keys)| self.write_sheet_cells(ws, keys))
+        })?;
 
         // Hyperlinks (apply after cell writes so they win on the final cell record).
         for link in &self.hyperlinks {
@@ -1929,6 +2469,18 @@ impl RustXlsxWriterBook {
                 if let Some(author) = &comment.author {
                     note = note.set_author(author.as_str());
                 }
+                if let Some(width) = comment.width {
+                    note = note.set_width(width);
+                }
+                if let Some(height) = comment.height {
+                    note = note.set_height(height);
+                }
+                if let Some(visible) = comment.visible {
+                    note = note.set_visible(visible);
+                }
+                if let Some(bg) = &comment.bg_color {
+                    note = note.set_background_color(parse_hex_color(bg));
+                }
                 ws.insert_note(comment.row, comment.col, &note)
                     .map(|_| ())
                     .map_err(|e| PyErr::new::<PyIOError, _>(format!("insert_note failed: {e}")))?;
@@ -1944,10 +2496,7 @@ impl RustXlsxWriterBook {
                     name = format!("{quoted}!{}", name);
                 }
             }
-            let mut refers = nr.refers_to.clone();
-            if !refers.trim_start().starts_with('=') {
-                refers = format!("={refers}");
-            }
+            let refers = formula::with_equals(&nr.refers_to);
             wb.define_name(name, &refers)
                 .map_err(|e| PyErr::new::<PyIOError, _>(format!("define_name failed: {e}")))?;
         }
@@ -1962,10 +2511,7 @@ impl RustXlsxWriterBook {
             let rule_type = cf.rule_type.as_str();
 
             if rule_type == "cellIs" {
-                let Some(op) = &cf.operator else {
-                    continue;
-                };
-                let Some(formula) = &cf.formula else {
+                let (Some(op), Some(formula)) = (&cf.operator, &cf.formula) else {
                     continue;
                 };
                 let value_str = formula.trim_start_matches('=');
@@ -1990,11 +2536,7 @@ impl RustXlsxWriterBook {
                 let Some(formula) = &cf.formula else {
                     continue;
                 };
-                let f = if formula.trim_start().starts_with('=') {
-                    formula.clone()
-                } else {
-                    format!("={formula}")
-                };
+                let f = formula::with_equals(formula);
                 let mut fmt = Format::new();
                 if let Some(bg) = &cf.bg_color {
                     let c = parse_hex_color(bg);
@@ -2014,13 +2556,57 @@ impl RustXlsxWriterBook {
                         PyErr::new::<PyIOError, _>(format!("add_conditional_format failed: {e}"))
                     })?;
             } else if rule_type == "dataBar" {
-                let cfmt = ConditionalFormatDataBar::new();
+                let mut cfmt = ConditionalFormatDataBar::new();
+                if let Some(p) = &cf.min {
+                    cfmt = cfmt.set_minimum(p.kind, p.value());
+                }
+                if let Some(p) = &cf.max {
+                    cfmt = cfmt.set_maximum(p.kind, p.value());
+                }
+                if let Some(fill) = cf.colors.first() {
+                    cfmt = cfmt.set_fill_color(parse_hex_color(fill));
+                }
+                if let Some(border) = &cf.bar_border_color {
+                    cfmt = cfmt.set_border_color(parse_hex_color(border));
+                }
+                if let Some(negative) = &cf.negative_fill {
+                    cfmt = cfmt.set_negative_fill_color(parse_hex_color(negative));
+                }
+                ws.add_conditional_format(r1, c1, r2, c2, &cfmt)
+                    .map_err(|e| {
+                        PyErr::new::<PyIOError, _>(format!("add_conditional_format failed: {e}"))
+                    })?;
+            } else if rule_type == "colorScale" && cf.mid.is_none() && cf.colors.len() == 2 {
+                let mut cfmt = ConditionalFormat2ColorScale::new()
+                    .set_minimum_color(parse_hex_color(&cf.colors[0]))
+                    .set_maximum_color(parse_hex_color(&cf.colors[1]));
+                if let Some(p) = &cf.min {
+                    cfmt = cfmt.set_minimum(p.kind, p.value());
+                }
+                if let Some(p) = &cf.max {
+                    cfmt = cfmt.set_maximum(p.kind, p.value());
+                }
                 ws.add_conditional_format(r1, c1, r2, c2, &cfmt)
                     .map_err(|e| {
                         PyErr::new::<PyIOError, _>(format!("add_conditional_format failed: {e}"))
                     })?;
             } else if rule_type == "colorScale" {
-                let cfmt = ConditionalFormat3ColorScale::new();
+                let mut cfmt = ConditionalFormat3ColorScale::new();
+                if let [lo, mid, hi] = cf.colors.as_slice() {
+                    cfmt = cfmt
+                        .set_minimum_color(parse_hex_color(lo))
+                        .set_midpoint_color(parse_hex_color(mid))
+                        .set_maximum_color(parse_hex_color(hi));
+                }
+                if let Some(p) = &cf.min {
+                    cfmt = cfmt.set_minimum(p.kind, p.value());
+                }
+                if let Some(p) = &cf.mid {
+                    cfmt = cfmt.set_midpoint(p.kind, p.value());
+                }
+                if let Some(p) = &cf.max {
+                    cfmt = cfmt.set_maximum(p.kind, p.value());
+                }
                 ws.add_conditional_format(r1, c1, r2, c2, &cfmt)
                     .map_err(|e| {
                         PyErr::new::<PyIOError, _>(format!("add_conditional_format failed: {e}"))
@@ -2130,27 +2716,203 @@ impl RustXlsxWriterBook {
                 .map_err(|e| PyErr::new::<PyIOError, _>(format!("add_table failed: {e}")))?;
         }
 
+        // Autofit last so it measures every written cell.
+        for (sheet, fit) in &self.autofit {
+            if let Some(ws) = ws_map.get_mut(sheet) {
+                self.apply_autofit(sheet, ws, *fit)?;
+            }
+        }
+
+        let mut format_pr_edits: Vec<(String, Vec<SheetFormatEdit>)> = Vec::new();
+        for (sheet, defaults) in &self.sheet_defaults {
+            let Some(ws) = ws_map.get_mut(sheet) else {
+                continue;
+            };
+            if let Some(height) = defaults.row_height {
+                ws.set_default_row_height(height);
+            }
+            if defaults.hide_unused_rows {
+                ws.hide_unused_rows(true);
+            }
+            ws.group_symbols_above(!defaults.summary_below);
+            ws.group_symbols_to_left(!defaults.summary_right);
+            // rust_xlsxwriter has no default column width; patched below.
+            if let Some(width) = defaults.col_width {
+                let edit = ("defaultColWidth".to_string(), Some(width.to_string()));
+                format_pr_edits.push((sheet.clone(), vec![edit]));
+            }
+        }
+
+        for (sheet, image_path) in &self.backgrounds {
+            if let Some(ws) = ws_map.get_mut(sheet) {
+                ws.set_background_image(&load_image(image_path)?);
+            }
+        }
+        for (sheet, image_path) in &self.watermarks {
+            if let Some(ws) = ws_map.get_mut(sheet) {
+                ws.set_header("&C&[Picture]");
+                ws.set_header_image(&load_image(image_path)?, HeaderImagePosition::Center)
+                    .map_err(|e| {
+                        PyErr::new::<PyIOError, _>(format!("set_header_image failed: {e}"))
+                    })?;
+            }
+        }
+
+        if let Some(vba_path) = &self.vba_project {
+            wb.add_vba_project(vba_path)
+                .map_err(|e| PyErr::new::<PyIOError, _>(format!("add_vba_project failed: {e}")))?;
+        }
+        for (sheet, name) in &self.vba_names {
+            match sheet {
+                None => wb.set_vba_name(name).map(|_| ()),
+                Some(sheet) => match ws_map.get_mut(sheet) {
+                    Some(ws) => ws.set_vba_name(name).map(|_| ()),
+                    None => Ok(()),
+                },
+            }
+            .map_err(|e| {
+                PyErr::new::<PyValueError, _>(format!("Invalid VBA name '{name}': {e}"))
+            })?;
+        }
+
         for (_name, ws) in ws_map.drain(..) {
             wb.push_worksheet(ws);
         }
 
-        wb.save(path)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to save workbook: {e}")))?;
+        {
+            let _span = profile::span("rust_xlsxwriter.zip_write");
+            wb.save(path)
+                .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to save workbook: {e}")))?;
+        }
+
+        // OOXML post-processing for what rust_xlsxwriter cannot express. The
+        // edits are collected per part and written back in one rewrite.
+        let mut patches = PackagePatches::open(path)?;
+
+        // Split panes (edge case).
+        if let Err(e) = patch_split_panes(&mut patches, &split_patches) {
+            self.degrade(format!("Failed to patch split panes in {path}: {e}"))?;
+        }
+
+        // Header-only tables.
+        if let Err(e) = patch_tables(&mut patches, &table_ref_patches) {
+            self.degrade(format!("Failed to patch table refs in {path}: {e}"))?;
+        }
+
+        // Unlike the cosmetic patches above, a lost calc mode changes how the
+        // file behaves in Excel, so failures are reported.
+        patches.calc_pr(&self.calc_pr)?;
+
+        // Template-fidelity checks compare the default width, so report too.
+        patches.sheet_format_pr(&format_pr_edits)?;
+
+        // Likewise a gradient left as its placeholder would show the wrong fill.
+        let mut gradients: Vec<&GradientFillDef> = Vec::new();
+        for g in self.formats.values().filter_map(|f| f.gradient.as_ref()) {
+            if !gradients.contains(&g) {
+                gradients.push(g);
+            }
+        }
+        patch_gradient_fills(&mut patches, &gradients)?;
+
+        patches.finish()?;
+        self.saved = true;
+        Ok(())
+    }
+}
+
+impl RustXlsxWriterBook {
+    fn sheet_defaults_mut(&mut self, sheet: &str) -> &mut SheetDefaults {
+        self.sheet_defaults.entry(sheet.to_string()).or_default()
+    }
+
+    /// Write one sheet's queued cells: values with their merged format, and
+    /// format-only keys as styled blanks.
+    fn write_sheet_cells(&self, ws: &mut Worksheet, keys: &[&CellKey]) -> PyResult<()> {
+        for key in keys {
+            let (_, row, col) = **key;
+            let fmt_fields = self.formats.get(*key);
+            let bdr_fields = self.borders.get(*key);
+            let mut format = build_format(fmt_fields, bdr_fields)?;
+
+            let Some(payload) = self.values.get(*key) else {
+                ws.write_blank(row, col, &format)
+                    .map_err(|e| PyErr::new::<PyIOError, _>(format!("write_blank failed: {e}")))?;
+                continue;
+            };
 
-        // Post-process split panes (edge case) by patching OOXML.
-        if !split_patches.is_empty() {
-            if let Err(e) = patch_split_panes_xlsx(path, &split_patches) {
-                eprintln!("Failed to patch split panes in {path}: {e}");
+            // Apply default date/datetime/duration number format only if the
+            // user didn't already provide one via write_cell_format.
+            let has_user_nf = fmt_fields.and_then(|f| f.number_format.as_ref()).is_some();
+            if !has_user_nf {
+                match payload {
+                    CellPayload::Date(_) => format = format.set_num_format("yyyy-mm-dd"),
+                    CellPayload::DateTime(_) => {
+                        format = format.set_num_format("yyyy-mm-dd hh:mm:ss")
+                    }
+                    CellPayload::Time(_) => format = format.set_num_format("hh:mm:ss"),
+                    // `[h]` keeps counting past 24 hours instead of wrapping.
+                    CellPayload::Duration(_) => format = format.set_num_format("[h]:mm:ss"),
+                    _ => {}
+                }
             }
+
+            write_cell(ws, row, col, payload, &format)?;
         }
+        Ok(())
+    }
+
+    /// Autofit one sheet, then enforce `min_width` and explicit widths.
+    fn apply_autofit(&self, sheet: &str, ws: &mut Worksheet, fit: AutofitSetting) -> PyResult<()> {
+        match fit.max_width {
+            Some(max) => ws.autofit_to_max_width(autofit::width_to_pixels(max)),
+            None => ws.autofit(),
+        };
 
-        // Post-process header-only tables by patching table XML refs.
-        if !table_ref_patches.is_empty() {
-            if let Err(e) = patch_tables_xlsx(path, &table_ref_patches) {
-                eprintln!("Failed to patch table refs in {path}: {e}");
+        // rust_xlsxwriter has no lower bound, so widen columns whose
+        // estimated content width falls short of `min_width`.
+        if let Some(min) = fit.min_width {
+            let mut content: HashMap<u16, f64> = HashMap::new();
+            for ((s, _, col), payload) in &self.values {
+                if s != sheet {
+                    continue;
+                }
+                let w = content.entry(*col).or_default();
+                if let Some(text) = payload_text(payload) {
+                    *w = w.max(autofit::text_width(&text));
+                }
+            }
+            for (col, w) in content {
+                if autofit::fit_width(w, None, fit.max_width) < min {
+                    ws.set_column_width(col, min).map_err(|e| {
+                        PyErr::new::<PyIOError, _>(format!("set_column_width failed: {e}"))
+                    })?;
+                }
             }
         }
 
+        for ((s, col), width) in &self.col_widths {
+            if s == sheet {
+                ws.set_column_width(*col, *width).map_err(|e| {
+                    PyErr::new::<PyIOError, _>(format!("set_column_width failed: {e}"))
+                })?;
+            }
+        }
         Ok(())
     }
 }
+
+/// Approximate rendered text of a queued value, for autofit estimates.
+fn payload_text(payload: &CellPayload) -> Option<String> {
+    Some(match payload {
+        CellPayload::Blank => return None,
+        CellPayload::String(s) => s.clone(),
+        CellPayload::Number(n) => n.to_string(),
+        CellPayload::Boolean(b) => (if *b { "TRUE" } else { "FALSE" }).to_string(),
+        CellPayload::Formula { result, .. } => result.clone()?,
+        CellPayload::Error(e) => e.clone(),
+        CellPayload::Date(_) => "yyyy-mm-dd".to_string(),
+        CellPayload::DateTime(_) => "yyyy-mm-dd hh:mm:ss".to_string(),
+        CellPayload::Time(_) | CellPayload::Duration(_) => "hh:mm:ss".to_string(),
+    })
+}
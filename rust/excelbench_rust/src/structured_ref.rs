@@ -0,0 +1,414 @@
+//! Structured references (`Sales[Q1]`, `Sales[[#This Row],[Q1]]`, `[@Q1]`)
+//! resolved to the A1 ranges they cover (`resolve_structured_ref()`).
+//!
+//! Backends store table formulas either way: Excel and openpyxl keep the
+//! structured form, others write the range it stood for. Resolving both
+//! sides to A1 lets the diff engine and the formula analyzer compare them.
+
+use std::fs::File;
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use zip::ZipArchive;
+
+use crate::cell_ref::{CellRef, RangeKind, RangeRef};
+use crate::formula;
+use crate::ooxml_util::{self, tables::TableDef};
+
+/// A table and the sheet it is on.
+#[derive(Debug, Clone)]
+pub(crate) struct SheetTable {
+    pub sheet: String,
+    pub table: TableDef,
+}
+
+/// What a specifier selects, before it is placed on the sheet.
+#[derive(Debug, Default, PartialEq)]
+struct Selection {
+    all: bool,
+    data: bool,
+    headers: bool,
+    totals: bool,
+    this_row: bool,
+    /// First and last column (the same name for a single column).
+    columns: Option<(String, String)>,
+}
+
+impl Selection {
+    /// Record one bracketed item: a `#` special or a column name.
+    fn add(&mut self, item: &str) -> Result<(), String> {
+        let flag = match item.to_ascii_lowercase().as_str() {
+            "#all" => &mut self.all,
+            "#data" => &mut self.data,
+            "#headers" => &mut self.headers,
+            "#totals" => &mut self.totals,
+            "#this row" => &mut self.this_row,
+            special if special.starts_with('#') => {
+                return Err(format!("Unknown structured reference item: {item}"))
+            }
+            _ => return self.add_columns(item, item),
+        };
+        *flag = true;
+        Ok(())
+    }
+
+    fn add_columns(&mut self, first: &str, last: &str) -> Result<(), String> {
+        if self.columns.is_some() {
+            return Err("A structured reference takes one column range".to_string());
+        }
+        if first.starts_with('#') || last.starts_with('#') {
+            return Err(format!("Invalid column range: [{first}]:[{last}]"));
+        }
+        self.columns = Some((first.to_string(), last.to_string()));
+        Ok(())
+    }
+}
+
+/// Read a `[...]` item starting at `chars[i]`; `'` escapes the next char.
+/// Returns the unescaped text and the index after the closing `]`.
+fn bracket_item(chars: &[char], i: usize) -> Result<(String, usize), String> {
+    if chars.get(i) != Some(&'[') {
+        return Err("Expected '[' in structured reference".to_string());
+    }
+    let mut text = String::new();
+    let mut j = i + 1;
+    while let Some(&c) = chars.get(j) {
+        match c {
+            '\'' => {
+                text.extend(chars.get(j + 1));
+                j += 2;
+            }
+            ']' => return Ok((text, j + 1)),
+            _ => {
+                text.push(c);
+                j += 1;
+            }
+        }
+    }
+    Err("Unclosed '[' in structured reference".to_string())
+}
+
+/// `'` escapes in a bare (unbracketed) item.
+fn unescape(item: &str) -> String {
+    let mut out = String::new();
+    let mut chars = item.chars();
+    while let Some(c) = chars.next() {
+        out.extend(if c == '\'' { chars.next() } else { Some(c) });
+    }
+    out
+}
+
+fn skip_whitespace(chars: &[char], mut i: usize) -> usize {
+    while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+        i += 1;
+    }
+    i
+}
+
+/// Parse the text between a table name's outer brackets: `Q1`, `#All`,
+/// `@Q1`, `@[Q 1]`, `[#Headers],[Q1]:[Q2]` or nothing (the data rows).
+fn parse_specifier(body: &str) -> Result<Selection, String> {
+    let mut sel = Selection::default();
+    let mut body = body.trim();
+    if let Some(rest) = body.strip_prefix('@') {
+        sel.this_row = true;
+        body = rest.trim_start();
+    }
+    if !body.starts_with('[') {
+        if !body.is_empty() {
+            sel.add(&unescape(body))?;
+        }
+        return Ok(sel);
+    }
+
+    let chars: Vec<char> = body.chars().collect();
+    let mut i = 0;
+    loop {
+        let (item, end) = bracket_item(&chars, i)?;
+        i = skip_whitespace(&chars, end);
+        if chars.get(i) == Some(&':') {
+            let (last, end) = bracket_item(&chars, skip_whitespace(&chars, i + 1))?;
+            sel.add_columns(&item, &last)?;
+            i = skip_whitespace(&chars, end);
+        } else {
+            sel.add(&item)?;
+        }
+        match chars.get(i) {
+            None => return Ok(sel),
+            Some(',') => i = skip_whitespace(&chars, i + 1),
+            Some(_) => return Err(format!("Invalid structured reference specifier: [{body}]")),
+        }
+    }
+}
+
+/// Split `Sales[Q1]` into the table name (empty for `[@Q1]`) and the text
+/// between the outer brackets (None for a bare `Sales`).
+fn split_reference(reference: &str) -> Result<(&str, Option<&str>), String> {
+    let reference = formula::strip_equals(reference).trim();
+    let Some(open) = reference.find('[') else {
+        return Ok((reference, None));
+    };
+    let body = reference[open + 1..]
+        .strip_suffix(']')
+        .ok_or_else(|| format!("Invalid structured reference: {reference}"))?;
+    Ok((&reference[..open], Some(body)))
+}
+
+/// Resolve `reference` against `tables`. `cell` is the formula's cell and
+/// sheet; `#This Row` and references without a table name need it.
+pub(crate) fn resolve(
+    reference: &str,
+    tables: &[SheetTable],
+    cell: Option<(Option<&str>, CellRef)>,
+) -> Result<RangeRef, String> {
+    let (name, body) = split_reference(reference)?;
+    if name.contains('!') {
+        return Err(format!(
+            "References to other workbooks are not supported: {reference}"
+        ));
+    }
+    let sel = match body {
+        Some(body) => parse_specifier(body)?,
+        None => Selection::default(),
+    };
+
+    let found = if name.is_empty() {
+        let (sheet, at) = cell.ok_or_else(|| {
+            format!("{reference} has no table name; pass the cell the formula is in")
+        })?;
+        tables.iter().find(|t| {
+            sheet.is_none_or(|s| s == t.sheet)
+                && RangeRef::parse(&t.table.ref_range).is_ok_and(|r| r.contains(at.row, at.col))
+        })
+    } else {
+        tables.iter().find(|t| {
+            t.table.display_name.eq_ignore_ascii_case(name)
+                || t.table.name.eq_ignore_ascii_case(name)
+        })
+    };
+    let Some(SheetTable { sheet, table }) = found else {
+        return Err(match name {
+            "" => format!("No table contains the cell of {reference}"),
+            _ => format!("Unknown table: {name}"),
+        });
+    };
+
+    let (r0, c0, r1, c1) = RangeRef::parse(&table.ref_range)?.bounds();
+    let data_first = r0 + u32::from(table.header_row);
+    let data_last = r1 - u32::from(table.totals_row);
+
+    let (top, bottom) = if sel.this_row {
+        if sel.all || sel.headers || sel.totals || sel.data {
+            return Err(format!(
+                "#This Row can't be combined with other items: {reference}"
+            ));
+        }
+        let (_, at) =
+            cell.ok_or_else(|| format!("{reference} uses #This Row; pass the formula's cell"))?;
+        if !(data_first..=data_last).contains(&at.row) {
+            return Err(format!(
+                "Row {} is outside the data rows of table {}",
+                at.row + 1,
+                table.display_name
+            ));
+        }
+        (at.row, at.row)
+    } else if sel.all {
+        (r0, r1)
+    } else {
+        if sel.headers && !table.header_row {
+            return Err(format!("Table {} has no header row", table.display_name));
+        }
+        if sel.totals && !table.totals_row {
+            return Err(format!("Table {} has no totals row", table.display_name));
+        }
+        if sel.headers && sel.totals && !sel.data {
+            return Err(format!(
+                "#Headers and #Totals need #Data between them: {reference}"
+            ));
+        }
+        let data = sel.data || !(sel.headers || sel.totals);
+        let top = match (sel.headers, data) {
+            (true, _) => r0,
+            (false, true) => data_first,
+            (false, false) => r1,
+        };
+        let bottom = match (sel.totals, data) {
+            (true, _) => r1,
+            (false, true) => data_last,
+            (false, false) => r0,
+        };
+        (top, bottom)
+    };
+
+    let (left, right) = match &sel.columns {
+        Some((first, last)) => {
+            let index = |col: &str| {
+                table
+                    .columns
+                    .iter()
+                    .position(|c| c.eq_ignore_ascii_case(col))
+                    .map(|i| c0 + i as u32)
+                    .ok_or_else(|| format!("Table {} has no column {col}", table.display_name))
+            };
+            let (a, b) = (index(first)?, index(last)?);
+            (a.min(b), a.max(b))
+        }
+        None => (c0, c1),
+    };
+
+    Ok(RangeRef {
+        sheet: Some(sheet.clone()),
+        start: CellRef::new(top, left),
+        end: CellRef::new(bottom, right),
+        kind: RangeKind::Cells,
+    })
+}
+
+/// Every table of the xlsx at `path`, with its sheet.
+fn workbook_tables(path: &str) -> PyResult<Vec<SheetTable>> {
+    let file = File::open(path)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to open {path}: {e}")))?;
+    let mut zip = ZipArchive::new(file)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to read xlsx zip: {e}")))?;
+    let workbook_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml")?;
+    let rels_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/_rels/workbook.xml.rels")?;
+
+    let mut out = Vec::new();
+    for (sheet, sheet_path) in ooxml_util::sheet_part_paths(&workbook_xml, &rels_xml)? {
+        for table in ooxml_util::read_sheet_tables(&mut zip, &sheet_path)? {
+            out.push(SheetTable {
+                sheet: sheet.clone(),
+                table,
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// Resolve a structured reference against the tables of the xlsx at
+/// `workbook` and return the range it covers, sheet-qualified
+/// (`"Data!B2:B4"`).
+///
+/// `cell` (`"Data!E3"`) is the cell the formula is in; `#This Row` (`@`)
+/// and references without a table name (`[@Q1]`) need it. Raises
+/// ValueError for an unknown table or column, or an item the table lacks
+/// (`#Totals` without a totals row).
+#[pyfunction]
+#[pyo3(signature = (workbook, reference, cell=None))]
+pub(crate) fn resolve_structured_ref(
+    workbook: &str,
+    reference: &str,
+    cell: Option<&str>,
+) -> PyResult<String> {
+    let cell = cell
+        .map(|c| {
+            let r = RangeRef::parse(c)?;
+            match r.kind {
+                RangeKind::Cells if r.start == r.end => Ok((r.sheet, r.start)),
+                _ => Err(format!("cell must be a single cell, got {c}")),
+            }
+        })
+        .transpose()
+        .map_err(PyErr::new::<PyValueError, _>)?;
+    let tables = workbook_tables(workbook)?;
+    let cell = cell.as_ref().map(|(sheet, at)| (sheet.as_deref(), *at));
+    resolve(reference, &tables, cell)
+        .map(|range| range.to_string())
+        .map_err(PyErr::new::<PyValueError, _>)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `Sales` on `Data!B2:D6`: header row 2, data rows 3-5, totals row 6.
+    fn tables() -> Vec<SheetTable> {
+        let table = |name: &str, ref_range: &str, totals_row: bool, columns: &[&str]| TableDef {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            ref_range: ref_range.to_string(),
+            header_row: true,
+            totals_row,
+            style: None,
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            autofilter: true,
+        };
+        vec![
+            SheetTable {
+                sheet: "Data".to_string(),
+                table: table("Sales", "B2:D6", true, &["Region", "Q1", "Q 2]"]),
+            },
+            SheetTable {
+                sheet: "My Sheet".to_string(),
+                table: table("Costs", "A1:B3", false, &["Item", "Cost"]),
+            },
+        ]
+    }
+
+    fn resolved(reference: &str, cell: Option<&str>) -> Result<String, String> {
+        let cell = cell.map(|c| RangeRef::parse(c).unwrap());
+        let cell = cell.as_ref().map(|r| (r.sheet.as_deref(), r.start));
+        resolve(reference, &tables(), cell).map(|r| r.to_string())
+    }
+
+    #[test]
+    fn test_resolve_areas_and_columns() {
+        for (reference, expected) in [
+            ("Sales", "Data!B3:D5"),
+            ("=sales[]", "Data!B3:D5"),
+            ("Sales[#All]", "Data!B2:D6"),
+            ("Sales[#Headers]", "Data!B2:D2"),
+            ("Sales[#Totals]", "Data!B6:D6"),
+            ("Sales[Q1]", "Data!C3:C5"),
+            ("Sales[[#Headers],[#Data],[Q1]]", "Data!C2:C5"),
+            ("Sales[[#Data], [#Totals], [Region]:[Q1]]", "Data!B3:C6"),
+            ("Sales[[Q 2']]:[Region]]", "Data!B3:D5"),
+            ("Sales[[#All],[Q 2']]]", "Data!D2:D6"),
+            ("Costs[Cost]", "'My Sheet'!B2:B3"),
+        ] {
+            assert_eq!(
+                resolved(reference, None).as_deref(),
+                Ok(expected),
+                "{reference}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_this_row() {
+        for (reference, cell, expected) in [
+            ("Sales[[#This Row],[Q1]]", "Data!E4", "Data!C4"),
+            ("Sales[@Q1]", "E4", "Data!C4"),
+            ("Sales[@[Q 2']]]", "Data!F5", "Data!D5"),
+            ("Sales[@]", "Data!E3", "Data!B3:D3"),
+            ("[@Region]", "Data!D4", "Data!B4"),
+            ("[Q1]", "Data!D4", "Data!C3:C5"),
+        ] {
+            assert_eq!(
+                resolved(reference, Some(cell)).as_deref(),
+                Ok(expected),
+                "{reference}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        for (reference, cell) in [
+            ("Nope[Q1]", None),
+            ("Sales[Q9]", None),
+            ("Sales[#Bogus]", None),
+            ("Costs[#Totals]", None),
+            ("Sales[[#Headers],[#Totals]]", None),
+            ("Sales[[Q1],[Region]]", None),
+            ("Sales[@Q1]", None),
+            ("Sales[@Q1]", Some("Data!E6")),
+            ("[@Q1]", Some("Data!H9")),
+            ("Sales[[Q1]", None),
+            ("[1]!Sales[Q1]", None),
+        ] {
+            assert!(resolved(reference, cell).is_err(), "{reference}");
+        }
+    }
+}
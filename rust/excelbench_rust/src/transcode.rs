@@ -0,0 +1,235 @@
+//! Backend-to-backend workbook copy (`transcode()`).
+//!
+//! Values are read through one backend's `ExcelReadBackend` view and written
+//! through another's `ExcelWriteBackend` view without a round-trip through
+//! Python code; the payload dicts are the same ones the Python adapters see.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+
+use crate::backend::{self, ExcelReadBackend, ExcelWriteBackend};
+use crate::cell_ref::col_to_letters;
+
+/// Features `transcode()` understands, in the ExcelBench feature vocabulary.
+const KNOWN_FEATURES: &[&str] = &[
+    "cell_values",
+    "formulas",
+    "text_formatting",
+    "background_colors",
+    "number_formats",
+    "alignment",
+    "borders",
+];
+
+/// Format dict keys copied for each formatting feature.
+const FORMAT_KEYS: &[(&str, &[&str])] = &[
+    (
+        "text_formatting",
+        &[
+            "bold",
+            "italic",
+            "underline",
+            "strikethrough",
+            "font_name",
+            "font_size",
+            "font_color",
+        ],
+    ),
+    ("background_colors", &["bg_color"]),
+    ("number_formats", &["number_format"]),
+    (
+        "alignment",
+        &["h_align", "v_align", "wrap", "rotation", "indent"],
+    ),
+];
+
+/// Copy a workbook from one backend to another.
+///
+/// `reader` and `writer` are adapter names as accepted by `open_backend()`;
+/// the writer must be able to create a new workbook. `features` selects what
+/// is copied (default `["cell_values", "formulas"]`). Without `"formulas"`,
+/// formula cells are written as their cached results, and skipped when the
+/// reader has none. Formats and borders are only copied from readers that
+/// expose them. Returns `{"sheets", "cells", "formats", "borders"}` counts.
+#[pyfunction]
+#[pyo3(signature = (src_path, dst_path, reader="calamine", writer="rust_xlsxwriter", features=None))]
+pub(crate) fn transcode(
+    py: Python<'_>,
+    src_path: &str,
+    dst_path: &str,
+    reader: &str,
+    writer: &str,
+    features: Option<Vec<String>>,
+) -> PyResult<PyObject> {
+    let features =
+        features.unwrap_or_else(|| vec!["cell_values".to_string(), "formulas".to_string()]);
+    if let Some(unknown) = features
+        .iter()
+        .find(|f| !KNOWN_FEATURES.contains(&f.as_str()))
+    {
+        return Err(PyErr::new::<PyValueError, _>(format!(
+            "Unknown transcode feature '{unknown}' (known: {})",
+            KNOWN_FEATURES.join(", ")
+        )));
+    }
+    let wants = |f: &str| features.iter().any(|x| x == f);
+    let formulas = wants("formulas");
+    let format_keys: Vec<&str> = FORMAT_KEYS
+        .iter()
+        .filter(|(feature, _)| wants(feature))
+        .flat_map(|(_, keys)| keys.iter().copied())
+        .collect();
+    let borders = wants("borders");
+
+    let mut src = backend::open_named(py, reader, Some(src_path))?;
+    let mut dst = backend::open_named(py, writer, None)?;
+    let src = src.as_reader().ok_or_else(|| {
+        PyErr::new::<PyValueError, _>(format!("Backend '{reader}' cannot read workbooks"))
+    })?;
+    let dst = dst.as_writer().ok_or_else(|| {
+        PyErr::new::<PyValueError, _>(format!("Backend '{writer}' cannot write workbooks"))
+    })?;
+
+    let mut stats = TranscodeStats::default();
+    for sheet in src.sheet_names()? {
+        dst.add_sheet(&sheet)?;
+        stats.sheets += 1;
+        for (row, col) in src.used_cells(py, &sheet)? {
+            let a1 = format!("{}{}", col_to_letters(col), row + 1);
+            let value = src.read_cell_value(py, &sheet, &a1)?;
+            if let Some(payload) = write_payload(py, value.bind(py), formulas)? {
+                dst.write_cell_value(&sheet, &a1, &payload)?;
+                stats.cells += 1;
+            }
+            if !format_keys.is_empty() && copy_format(py, src, dst, &sheet, &a1, &format_keys)? {
+                stats.formats += 1;
+            }
+            if borders && copy_border(py, src, dst, &sheet, &a1)? {
+                stats.borders += 1;
+            }
+        }
+    }
+    dst.save(py, dst_path)?;
+
+    let out = PyDict::new(py);
+    out.set_item("sheets", stats.sheets)?;
+    out.set_item("cells", stats.cells)?;
+    out.set_item("formats", stats.formats)?;
+    out.set_item("borders", stats.borders)?;
+    Ok(out.into())
+}
+
+#[derive(Default)]
+struct TranscodeStats {
+    sheets: usize,
+    cells: usize,
+    formats: usize,
+    borders: usize,
+}
+
+/// Turn a read payload into the write payload to emit, if any.
+///
+/// Readers report formula cells as `{"type": "formula", "formula", "value",
+/// "value_type"}` (or as `"error"` with a `"formula"` key); the cached value
+/// becomes the writer's `"result"`, or the whole cell when formulas are off.
+fn write_payload<'py>(
+    py: Python<'py>,
+    read: &Bound<'py, PyAny>,
+    formulas: bool,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let d = read.downcast::<PyDict>()?;
+    let kind: String = match d.get_item("type")? {
+        Some(t) => t.extract()?,
+        None => return Ok(None),
+    };
+    if kind == "blank" {
+        return Ok(None);
+    }
+    let Some(formula) = d.get_item("formula")?.filter(|f| !f.is_none()) else {
+        return Ok(Some(d.clone().into_any()));
+    };
+
+    let cached = if kind == "error" {
+        let is_cached = match d.get_item("cached")? {
+            Some(c) => c.extract::<bool>()?,
+            None => true,
+        };
+        match d.get_item("value")? {
+            Some(v) if is_cached => Some((PyString::new(py, "error").into_any(), v)),
+            _ => None,
+        }
+    } else {
+        match (d.get_item("value_type")?, d.get_item("value")?) {
+            (Some(t), Some(v)) => Some((t, v)),
+            _ => None,
+        }
+    };
+
+    let out = PyDict::new(py);
+    if formulas {
+        out.set_item("type", "formula")?;
+        out.set_item("formula", formula)?;
+        if let Some((_, value)) = cached.filter(|(_, v)| !v.is_none()) {
+            out.set_item("result", value)?;
+        }
+        return Ok(Some(out.into_any()));
+    }
+    let Some((value_type, value)) = cached else {
+        return Ok(None);
+    };
+    out.set_item("type", value_type)?;
+    out.set_item("value", value)?;
+    Ok(Some(out.into_any()))
+}
+
+/// Copy the selected format keys of one cell; true when anything was written.
+fn copy_format(
+    py: Python<'_>,
+    src: &mut dyn ExcelReadBackend,
+    dst: &mut dyn ExcelWriteBackend,
+    sheet: &str,
+    a1: &str,
+    keys: &[&str],
+) -> PyResult<bool> {
+    let Some(fmt) = src.read_cell_format(py, sheet, a1)? else {
+        return Ok(false);
+    };
+    let fmt = fmt.bind(py).downcast::<PyDict>()?.clone();
+    let picked = PyDict::new(py);
+    for key in keys {
+        if let Some(v) = fmt.get_item(*key)?.filter(|v| !v.is_none()) {
+            picked.set_item(*key, v)?;
+        }
+    }
+    if picked.is_empty() {
+        return Ok(false);
+    }
+    dst.write_cell_format(sheet, a1, picked.as_any())?;
+    Ok(true)
+}
+
+/// Copy one cell's border edges; true when the cell had any.
+fn copy_border(
+    py: Python<'_>,
+    src: &mut dyn ExcelReadBackend,
+    dst: &mut dyn ExcelWriteBackend,
+    sheet: &str,
+    a1: &str,
+) -> PyResult<bool> {
+    let Some(bdr) = src.read_cell_border(py, sheet, a1)? else {
+        return Ok(false);
+    };
+    let bdr = bdr.bind(py).downcast::<PyDict>()?.clone();
+    let edges = PyDict::new(py);
+    for (key, edge) in bdr.iter() {
+        if !edge.is_none() {
+            edges.set_item(key, edge)?;
+        }
+    }
+    if edges.is_empty() {
+        return Ok(false);
+    }
+    dst.write_cell_border(sheet, a1, edges.as_any())?;
+    Ok(true)
+}
@@ -1,7 +1,63 @@
-use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
 
-use super::UmyaBook;
+use crate::errors;
+use crate::ooxml_util::auto_filter::FilterCriteria;
+
+use super::{UmyaBook, CAPABILITIES};
+
+fn criteria_to_py(py: Python<'_>, criteria: &FilterCriteria) -> PyResult<Bound<'_, PyDict>> {
+    let d = PyDict::new(py);
+    match criteria {
+        FilterCriteria::Values {
+            values,
+            dates,
+            blank,
+        } => {
+            d.set_item("type", "values")?;
+            d.set_item("values", values)?;
+            d.set_item("dates", dates)?;
+            d.set_item("blank", blank)?;
+        }
+        FilterCriteria::Custom { and, conditions } => {
+            d.set_item("type", "custom")?;
+            d.set_item("and", and)?;
+            let items = PyList::empty(py);
+            for (operator, value) in conditions {
+                let c = PyDict::new(py);
+                c.set_item("operator", operator)?;
+                c.set_item("value", value)?;
+                items.append(c)?;
+            }
+            d.set_item("conditions", items)?;
+        }
+        FilterCriteria::Top10 {
+            top,
+            percent,
+            value,
+        } => {
+            d.set_item("type", "top10")?;
+            d.set_item("top", top)?;
+            d.set_item("percent", percent)?;
+            d.set_item("value", value)?;
+        }
+        FilterCriteria::Dynamic { kind } => {
+            d.set_item("type", "dynamic")?;
+            d.set_item("kind", kind)?;
+        }
+        FilterCriteria::Color { dxf_id, cell_color } => {
+            d.set_item("type", "color")?;
+            d.set_item("dxf_id", dxf_id)?;
+            d.set_item("cell_color", cell_color)?;
+        }
+        FilterCriteria::Icon { icon_set, icon_id } => {
+            d.set_item("type", "icon")?;
+            d.set_item("icon_set", icon_set)?;
+            d.set_item("icon_id", icon_id)?;
+        }
+    }
+    Ok(d)
+}
 
 #[pymethods]
 impl UmyaBook {
@@ -10,19 +66,63 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         Ok(ws
             .get_auto_filter()
             .map(|af| af.get_range().get_range().replace('$', "")))
     }
 
+    /// The sheet's auto filter as `{"range": "A1:D10", "columns": [...]}`,
+    /// or None if not set.
+    ///
+    /// Each column dict has `col_id` (0-based within the range) and `type`:
+    /// "values" (`values`, `dates`, `blank`), "custom" (`and`, `conditions`
+    /// of `operator`/`value`), "top10" (`top`, `percent`, `value`),
+    /// "dynamic" (`kind`), "color" (`dxf_id`, `cell_color`) or "icon"
+    /// (`icon_set`, `icon_id`); "none" for a column with only a button.
+    /// umya keeps just the range, so criteria come from the file the book
+    /// was opened from and are reported only while the range is unchanged.
+    pub fn read_auto_filter(&self, py: Python<'_>, sheet: &str) -> PyResult<PyObject> {
+        let ws = self
+            .book
+            .get_sheet_by_name(sheet)
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
+        let Some(af) = ws.get_auto_filter() else {
+            return Ok(py.None());
+        };
+        let range = af.get_range().get_range().replace('$', "");
+
+        let columns = PyList::empty(py);
+        let source = self
+            .source_auto_filters
+            .get(sheet)
+            .filter(|def| def.range.replace('$', "").eq_ignore_ascii_case(&range));
+        for column in source.iter().flat_map(|def| &def.columns) {
+            let d = match &column.criteria {
+                Some(criteria) => criteria_to_py(py, criteria)?,
+                None => {
+                    let d = PyDict::new(py);
+                    d.set_item("type", "none")?;
+                    d
+                }
+            };
+            d.set_item("col_id", column.col_id)?;
+            columns.append(d)?;
+        }
+
+        let d = PyDict::new(py);
+        d.set_item("range", range)?;
+        d.set_item("columns", columns)?;
+        Ok(d.into())
+    }
+
     /// Set an auto filter on a range (e.g. "A1:D10").
     pub fn set_auto_filter(&mut self, sheet: &str, range: &str) -> PyResult<()> {
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         ws.set_auto_filter(range);
         Ok(())
@@ -33,7 +133,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         ws.remove_auto_filter();
         Ok(())
@@ -44,7 +144,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         Ok(ws.get_auto_filter().is_some())
     }
@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::autofit::{fit_width, text_width};
+use crate::errors;
+
+use super::{UmyaBook, CAPABILITIES};
+
+#[pymethods]
+impl UmyaBook {
+    /// Size every used column of `sheet` to its widest displayed value.
+    ///
+    /// Values are measured as Excel would render them (number formats
+    /// applied) with an approximate character-width table, then clamped to
+    /// `[min_width, max_width]`. Returns the number of columns sized.
+    #[pyo3(signature = (sheet, min_width=None, max_width=None))]
+    pub fn autofit_columns(
+        &mut self,
+        sheet: &str,
+        min_width: Option<f64>,
+        max_width: Option<f64>,
+    ) -> PyResult<usize> {
+        if let (Some(min), Some(max)) = (min_width, max_width) {
+            if min > max {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "min_width ({min}) exceeds max_width ({max})"
+                )));
+            }
+        }
+        let ws = self
+            .book
+            .get_sheet_by_name_mut(sheet)
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
+
+        // 1-based column → widest content.
+        let mut widths: BTreeMap<u32, f64> = BTreeMap::new();
+        for cell in ws.get_cell_collection() {
+            let text = cell.get_formatted_value();
+            if text.is_empty() {
+                continue;
+            }
+            let col = *cell.get_coordinate().get_col_num();
+            let w = widths.entry(col).or_default();
+            *w = w.max(text_width(&text));
+        }
+
+        for (&col, &content) in &widths {
+            ws.get_column_dimension_by_number_mut(&col)
+                .set_width(fit_width(content, min_width, max_width));
+        }
+        Ok(widths.len())
+    }
+}
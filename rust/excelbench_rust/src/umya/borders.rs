@@ -1,11 +1,12 @@
-use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
 
+use crate::errors;
+use crate::payload::{self, BorderEdge};
 use crate::util::a1_to_row_col;
 
-use super::util::{argb_to_hex, hex_to_argb, umya_border_style_to_str};
-use super::UmyaBook;
+use super::util::{resolve_color, set_color, umya_border_style_to_str};
+use super::{UmyaBook, CAPABILITIES};
 
 #[pymethods]
 impl UmyaBook {
@@ -13,9 +14,10 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
-        let (row0, col0) = a1_to_row_col(a1).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
+        let (row0, col0) =
+            a1_to_row_col(a1).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a1, msg))?;
         let coord = (col0 + 1, row0 + 1);
 
         let d = PyDict::new(py);
@@ -26,18 +28,15 @@ impl UmyaBook {
         };
 
         let style = cell.get_style();
+        let theme = self.book.get_theme();
         if let Some(borders) = style.get_borders() {
             let read_edge = |e: &umya_spreadsheet::structs::Border| -> Option<(String, String)> {
                 let style_str = e.get_border_style();
                 if style_str.is_empty() || style_str == "none" {
                     return None;
                 }
-                let argb = e.get_color().get_argb();
-                let color_str = if argb.is_empty() {
-                    "#000000".to_string()
-                } else {
-                    argb_to_hex(argb)
-                };
+                let color_str =
+                    resolve_color(e.get_color(), theme).unwrap_or_else(|| "#000000".to_string());
                 Some((umya_border_style_to_str(style_str).to_string(), color_str))
             };
 
@@ -93,65 +92,40 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
-
-        let dict = border_dict
-            .downcast::<PyDict>()
-            .map_err(|_| PyErr::new::<PyValueError, _>("border_dict must be a dict"))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
+        let bdr = payload::parse_border(border_dict)?;
         let style = ws.get_style_mut(a1);
         let borders = style.get_borders_mut();
 
-        fn apply_edge(
-            edge: &mut umya_spreadsheet::structs::Border,
-            sub: &Bound<'_, PyDict>,
-        ) -> PyResult<()> {
-            if let Some(s) = sub
-                .get_item("style")?
-                .and_then(|v| v.extract::<String>().ok())
-            {
-                edge.set_border_style(s);
+        fn apply_edge(edge: &mut umya_spreadsheet::structs::Border, spec: &BorderEdge) {
+            if let Some(s) = &spec.style {
+                edge.set_border_style(s.as_str());
             }
-            if let Some(c) = sub
-                .get_item("color")?
-                .and_then(|v| v.extract::<String>().ok())
-            {
-                edge.get_color_mut().set_argb(hex_to_argb(&c));
+            if let Some(c) = &spec.color {
+                set_color(edge.get_color_mut(), c);
             }
-            Ok(())
         }
 
-        if let Some(sub) = dict.get_item("top")? {
-            if let Ok(d) = sub.downcast::<PyDict>() {
-                apply_edge(borders.get_top_mut(), d)?;
-            }
+        if let Some(e) = &bdr.top {
+            apply_edge(borders.get_top_mut(), e);
         }
-        if let Some(sub) = dict.get_item("bottom")? {
-            if let Ok(d) = sub.downcast::<PyDict>() {
-                apply_edge(borders.get_bottom_mut(), d)?;
-            }
+        if let Some(e) = &bdr.bottom {
+            apply_edge(borders.get_bottom_mut(), e);
         }
-        if let Some(sub) = dict.get_item("left")? {
-            if let Ok(d) = sub.downcast::<PyDict>() {
-                apply_edge(borders.get_left_mut(), d)?;
-            }
+        if let Some(e) = &bdr.left {
+            apply_edge(borders.get_left_mut(), e);
         }
-        if let Some(sub) = dict.get_item("right")? {
-            if let Ok(d) = sub.downcast::<PyDict>() {
-                apply_edge(borders.get_right_mut(), d)?;
-            }
+        if let Some(e) = &bdr.right {
+            apply_edge(borders.get_right_mut(), e);
         }
-        if let Some(sub) = dict.get_item("diagonal_up")? {
-            if let Ok(d) = sub.downcast::<PyDict>() {
-                apply_edge(borders.get_diagonal_mut(), d)?;
-                borders.set_diagonal_up(true);
-            }
+        if let Some(e) = &bdr.diagonal_up {
+            apply_edge(borders.get_diagonal_mut(), e);
+            borders.set_diagonal_up(true);
         }
-        if let Some(sub) = dict.get_item("diagonal_down")? {
-            if let Ok(d) = sub.downcast::<PyDict>() {
-                apply_edge(borders.get_diagonal_mut(), d)?;
-                borders.set_diagonal_down(true);
-            }
+        if let Some(e) = &bdr.diagonal_down {
+            apply_edge(borders.get_diagonal_mut(), e);
+            borders.set_diagonal_down(true);
         }
 
         Ok(())
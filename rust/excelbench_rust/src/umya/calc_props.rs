@@ -0,0 +1,109 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::ooxml_util::calc_pr;
+
+use super::UmyaBook;
+
+fn flag(value: bool) -> Option<String> {
+    value.then(|| "1".to_string())
+}
+
+fn is_true(value: Option<&str>) -> bool {
+    matches!(value, Some("1") | Some("true"))
+}
+
+#[pymethods]
+impl UmyaBook {
+    /// Workbook calculation properties, e.g.
+    /// `{"calc_mode": "manual", "iterative": True, "max_iterations": 500}`.
+    ///
+    /// Keys are `calc_mode` ("auto", "manual" or "auto_except_tables"),
+    /// `full_calc_on_load`, `iterative`, `max_iterations` and `max_change`.
+    /// A None value resets the property to Excel's default; keys that are
+    /// not given keep their current value.
+    pub fn set_calc_properties(&mut self, props: &Bound<'_, PyDict>) -> PyResult<()> {
+        let mut edits = self.calc_pr.clone();
+        for (key, value) in props.iter() {
+            let key: String = key.extract()?;
+            let value = (!value.is_none()).then_some(value);
+            let (attr, v) = match key.as_str() {
+                "calc_mode" => {
+                    let v = match value {
+                        Some(v) => {
+                            let mode: String = v.extract()?;
+                            let mode = calc_pr::calc_mode_value(&mode)
+                                .map_err(PyErr::new::<PyValueError, _>)?;
+                            Some(mode.to_string())
+                        }
+                        None => None,
+                    };
+                    ("calcMode", v)
+                }
+                "full_calc_on_load" => {
+                    let v = value.map(|v| v.extract::<bool>()).transpose()?;
+                    ("fullCalcOnLoad", v.and_then(flag))
+                }
+                "iterative" => {
+                    let v = value.map(|v| v.extract::<bool>()).transpose()?;
+                    ("iterate", v.and_then(flag))
+                }
+                "max_iterations" => {
+                    let v = value.map(|v| v.extract::<u32>()).transpose()?;
+                    if v == Some(0) {
+                        return Err(PyErr::new::<PyValueError, _>(
+                            "max_iterations must be at least 1",
+                        ));
+                    }
+                    ("iterateCount", v.map(|n| n.to_string()))
+                }
+                "max_change" => {
+                    let v = value.map(|v| v.extract::<f64>()).transpose()?;
+                    if v.is_some_and(|d| !d.is_finite() || d < 0.0) {
+                        return Err(PyErr::new::<PyValueError, _>(
+                            "max_change must be a non-negative number",
+                        ));
+                    }
+                    ("iterateDelta", v.map(|d| d.to_string()))
+                }
+                other => {
+                    return Err(PyErr::new::<PyValueError, _>(format!(
+                        "Unknown calc property: {other}"
+                    )))
+                }
+            };
+            calc_pr::set_edit(&mut edits, attr, v);
+        }
+        self.calc_pr = edits;
+        Ok(())
+    }
+
+    /// The calculation properties the next save will write, with Excel's
+    /// defaults filled in for anything unset.
+    pub fn read_calc_properties(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let get = |key: &str| {
+            self.calc_pr
+                .iter()
+                .find(|(k, _)| k == key)
+                .and_then(|(_, v)| v.as_deref())
+        };
+
+        let d = PyDict::new(py);
+        let mode = get("calcMode")
+            .and_then(calc_pr::calc_mode_name)
+            .unwrap_or("auto");
+        d.set_item("calc_mode", mode)?;
+        d.set_item("full_calc_on_load", is_true(get("fullCalcOnLoad")))?;
+        d.set_item("iterative", is_true(get("iterate")))?;
+        let count = get("iterateCount")
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(100);
+        d.set_item("max_iterations", count)?;
+        let delta = get("iterateDelta")
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.001);
+        d.set_item("max_change", delta)?;
+        Ok(d.into())
+    }
+}
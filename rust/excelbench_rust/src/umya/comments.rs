@@ -4,7 +4,9 @@ use pyo3::types::{PyDict, PyList};
 
 use umya_spreadsheet::structs::Comment;
 
-use super::UmyaBook;
+use crate::errors;
+
+use super::{UmyaBook, CAPABILITIES};
 
 /// Extract plain text from a comment.
 /// umya-spreadsheet's `Text` type is pub(crate), so we can only read text
@@ -20,13 +22,28 @@ fn extract_comment_text(comment: &Comment) -> String {
     String::new()
 }
 
+/// `$b$2` → `B2`, the form `Coordinate::to_string` produces.
+fn normalize_cell(cell: &str) -> String {
+    cell.replace('$', "").to_ascii_uppercase()
+}
+
+/// True unless the note's VML shape is styled `visibility:hidden`, which is
+/// how Excel stores a note that only shows on hover.
+fn comment_visible(comment: &Comment) -> bool {
+    !comment
+        .get_shape()
+        .get_style()
+        .replace(' ', "")
+        .contains("visibility:hidden")
+}
+
 #[pymethods]
 impl UmyaBook {
     pub fn read_comments(&self, py: Python<'_>, sheet: &str) -> PyResult<PyObject> {
         let ws = self
             .book
             .get_sheet_by_name(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         let comments = ws.get_comments();
         let result = PyList::empty(py);
@@ -37,6 +54,23 @@ impl UmyaBook {
             d.set_item("text", extract_comment_text(comment))?;
             d.set_item("author", comment.get_author())?;
             d.set_item("threaded", false)?;
+            d.set_item("visible", comment_visible(comment))?;
+            // The note box is anchored to cells; report its span in cells.
+            let anchor = comment.get_anchor();
+            let size = PyDict::new(py);
+            size.set_item(
+                "cols",
+                anchor
+                    .get_right_column()
+                    .saturating_sub(*anchor.get_left_column()),
+            )?;
+            size.set_item(
+                "rows",
+                anchor
+                    .get_bottom_row()
+                    .saturating_sub(*anchor.get_top_row()),
+            )?;
+            d.set_item("anchor", size)?;
             result.append(d)?;
         }
 
@@ -47,7 +81,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         let dict = comment_dict
             .downcast::<PyDict>()
@@ -83,4 +117,53 @@ impl UmyaBook {
 
         Ok(())
     }
+
+    /// Delete the note on `cell`. Raises ValueError when there is none.
+    pub fn remove_comment(&mut self, sheet: &str, cell: &str) -> PyResult<()> {
+        let ws = self
+            .book
+            .get_sheet_by_name_mut(sheet)
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
+
+        let target = normalize_cell(cell);
+        let comments = ws.get_comments_mut();
+        let before = comments.len();
+        comments.retain(|c| c.get_coordinate().to_string() != target);
+        if comments.len() == before {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "No comment at {sheet}!{target}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Replace the text (and, when given, the author) of the note on `cell`
+    /// in place, keeping its size and visibility.
+    #[pyo3(signature = (sheet, cell, text, author=None))]
+    pub fn update_comment(
+        &mut self,
+        sheet: &str,
+        cell: &str,
+        text: &str,
+        author: Option<&str>,
+    ) -> PyResult<()> {
+        let ws = self
+            .book
+            .get_sheet_by_name_mut(sheet)
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
+
+        let target = normalize_cell(cell);
+        let comment = ws
+            .get_comments_mut()
+            .iter_mut()
+            .find(|c| c.get_coordinate().to_string() == target)
+            .ok_or_else(|| {
+                PyErr::new::<PyValueError, _>(format!("No comment at {sheet}!{target}"))
+            })?;
+        comment.set_text_string(text);
+        if let Some(author) = author {
+            comment.set_author(author);
+        }
+        Ok(())
+    }
 }
@@ -3,18 +3,21 @@ use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 
 use umya_spreadsheet::structs::{
-    Color, ConditionalFormatValues, ConditionalFormatting, ConditionalFormattingOperatorValues,
-    ConditionalFormattingRule, EnumTrait, Formula, Style,
+    Color, ColorScale, ConditionalFormatValueObject, ConditionalFormatValueObjectValues,
+    ConditionalFormatValues, ConditionalFormatting, ConditionalFormattingOperatorValues,
+    ConditionalFormattingRule, DataBar, EnumTrait, Formula, IconSet, Style,
 };
 
-use super::UmyaBook;
+use crate::errors;
+
+use super::{UmyaBook, CAPABILITIES};
 
 fn cf_type_to_str(t: &ConditionalFormatValues) -> &str {
     t.get_value_string()
 }
 
-fn str_to_cf_type(s: &str) -> ConditionalFormatValues {
-    match s {
+fn str_to_cf_type(s: &str) -> Option<ConditionalFormatValues> {
+    Some(match s {
         "cellIs" => ConditionalFormatValues::CellIs,
         "expression" => ConditionalFormatValues::Expression,
         "colorScale" => ConditionalFormatValues::ColorScale,
@@ -33,16 +36,16 @@ fn str_to_cf_type(s: &str) -> ConditionalFormatValues {
         "duplicateValues" => ConditionalFormatValues::DuplicateValues,
         "uniqueValues" => ConditionalFormatValues::UniqueValues,
         "timePeriod" => ConditionalFormatValues::TimePeriod,
-        _ => ConditionalFormatValues::Expression,
-    }
+        _ => return None,
+    })
 }
 
 fn cf_op_to_str(op: &ConditionalFormattingOperatorValues) -> &str {
     op.get_value_string()
 }
 
-fn str_to_cf_op(s: &str) -> ConditionalFormattingOperatorValues {
-    match s {
+fn str_to_cf_op(s: &str) -> Option<ConditionalFormattingOperatorValues> {
+    Some(match s {
         "between" => ConditionalFormattingOperatorValues::Between,
         "notBetween" => ConditionalFormattingOperatorValues::NotBetween,
         "equal" => ConditionalFormattingOperatorValues::Equal,
@@ -55,8 +58,285 @@ fn str_to_cf_op(s: &str) -> ConditionalFormattingOperatorValues {
         "endsWith" => ConditionalFormattingOperatorValues::EndsWith,
         "containsText" => ConditionalFormattingOperatorValues::ContainsText,
         "notContains" => ConditionalFormattingOperatorValues::NotContains,
-        _ => ConditionalFormattingOperatorValues::LessThan,
+        _ => return None,
+    })
+}
+
+/// The only icon set umya can write: `<iconSet>` carries no `iconSet`
+/// attribute, so Excel falls back to its default.
+const DEFAULT_ICON_STYLE: &str = "3TrafficLights1";
+
+fn str_to_cfvo_type(s: &str) -> PyResult<ConditionalFormatValueObjectValues> {
+    Ok(match s {
+        "num" | "number" => ConditionalFormatValueObjectValues::Number,
+        "percent" => ConditionalFormatValueObjectValues::Percent,
+        "percentile" => ConditionalFormatValueObjectValues::Percentile,
+        "formula" => ConditionalFormatValueObjectValues::Formula,
+        "min" => ConditionalFormatValueObjectValues::Min,
+        "max" => ConditionalFormatValueObjectValues::Max,
+        _ => {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "Unknown threshold type '{s}' \
+                 (expected num, percent, percentile, formula, min or max)"
+            )))
+        }
+    })
+}
+
+/// `{"type": "percent", "value": 33}` → cfvo; `type` defaults to `default_type`.
+fn extract_cfvo(
+    v: &Bound<'_, PyAny>,
+    default_type: &str,
+) -> PyResult<ConditionalFormatValueObject> {
+    let d = v
+        .downcast::<PyDict>()
+        .map_err(|_| PyErr::new::<PyValueError, _>("thresholds must be dicts"))?;
+    let kind: String = d
+        .get_item("type")?
+        .map(|t| t.extract())
+        .transpose()?
+        .unwrap_or_else(|| default_type.to_string());
+    let mut cfvo = ConditionalFormatValueObject::default();
+    cfvo.set_type(str_to_cfvo_type(&kind)?);
+    if let Some(val) = d.get_item("value")?.filter(|x| !x.is_none()) {
+        cfvo.set_val(val.str()?.to_string());
     }
+    Ok(cfvo)
+}
+
+/// cfvo used when the payload leaves a point unspecified.
+fn default_cfvo(
+    kind: ConditionalFormatValueObjectValues,
+    val: Option<&str>,
+) -> ConditionalFormatValueObject {
+    let mut cfvo = ConditionalFormatValueObject::default();
+    cfvo.set_type(kind);
+    if let Some(val) = val {
+        cfvo.set_val(val);
+    }
+    cfvo
+}
+
+/// `[{"type": "percent", "value": 33}, ...]` → cfvo list.
+fn extract_thresholds(v: &Bound<'_, PyAny>) -> PyResult<Vec<ConditionalFormatValueObject>> {
+    let list = v
+        .downcast::<PyList>()
+        .map_err(|_| PyErr::new::<PyValueError, _>("'thresholds' must be a list of dicts"))?;
+    list.iter()
+        .map(|item| extract_cfvo(&item, "percent"))
+        .collect()
+}
+
+/// `min`/`mid`/`max` cfvo specs from the payload, falling back to `default`.
+fn scale_point(
+    cfg: &Bound<'_, PyDict>,
+    key: &str,
+    default: ConditionalFormatValueObject,
+) -> PyResult<ConditionalFormatValueObject> {
+    match cfg.get_item(key)?.filter(|v| !v.is_none()) {
+        Some(v) => extract_cfvo(&v, if key == "mid" { "percentile" } else { key }),
+        None => Ok(default),
+    }
+}
+
+/// `colors` (or a single `color`) as ARGB, falling back to `defaults`.
+fn scale_colors(cfg: &Bound<'_, PyDict>, defaults: &[&str]) -> PyResult<Vec<Color>> {
+    let hexes: Vec<String> = match (cfg.get_item("colors")?, cfg.get_item("color")?) {
+        (Some(v), _) if !v.is_none() => v
+            .extract()
+            .map_err(|_| PyErr::new::<PyValueError, _>("'colors' must be a list of hex strings"))?,
+        (_, Some(v)) if !v.is_none() => vec![v
+            .extract()
+            .map_err(|_| PyErr::new::<PyValueError, _>("'color' must be a hex string"))?],
+        _ => defaults.iter().map(|c| c.to_string()).collect(),
+    };
+    Ok(hexes
+        .iter()
+        .map(|hex| {
+            let hex = hex.strip_prefix('#').unwrap_or(hex);
+            let mut color = Color::default();
+            if hex.len() == 6 {
+                color.set_argb(format!("FF{hex}"));
+            } else {
+                color.set_argb(hex);
+            }
+            color
+        })
+        .collect())
+}
+
+/// `[{"type", "value"}, ...]` for read-back; numeric values become floats.
+fn cfvos_to_py<'py>(
+    py: Python<'py>,
+    cfvos: &[ConditionalFormatValueObject],
+) -> PyResult<Bound<'py, PyList>> {
+    let out = PyList::empty(py);
+    for cfvo in cfvos {
+        let d = PyDict::new(py);
+        d.set_item("type", cfvo.get_type().get_value_string())?;
+        let val = cfvo.get_val();
+        if val.is_empty() {
+            d.set_item("value", py.None())?;
+        } else if let Ok(n) = val.parse::<f64>() {
+            d.set_item("value", n)?;
+        } else {
+            d.set_item("value", val)?;
+        }
+        out.append(d)?;
+    }
+    Ok(out)
+}
+
+/// Report a data bar / color scale's cfvos as `min`, (`mid`,) `max`.
+fn set_scale_points(d: &Bound<'_, PyDict>, points: &Bound<'_, PyList>) -> PyResult<()> {
+    let n = points.len();
+    if n >= 2 {
+        d.set_item("min", points.get_item(0)?)?;
+        d.set_item("max", points.get_item(n - 1)?)?;
+    }
+    if n == 3 {
+        d.set_item("mid", points.get_item(1)?)?;
+    }
+    Ok(())
+}
+
+/// Type-specific rule attributes: dataBar/colorScale points and colors,
+/// iconSet thresholds, top10 rank and aboveAverage direction/std-dev.
+fn apply_rule_params(
+    rule: &mut ConditionalFormattingRule,
+    rule_type: &str,
+    cfg: &Bound<'_, PyDict>,
+) -> PyResult<()> {
+    match rule_type {
+        "iconSet" => {
+            if let Some(style) = cfg
+                .get_item("icon_style")?
+                .and_then(|v| v.extract::<String>().ok())
+            {
+                if style != DEFAULT_ICON_STYLE {
+                    return Err(PyErr::new::<PyValueError, _>(format!(
+                        "only the {DEFAULT_ICON_STYLE} icon set can be written, not '{style}'"
+                    )));
+                }
+            }
+            let thresholds = match cfg.get_item("thresholds")? {
+                Some(v) => extract_thresholds(&v)?,
+                None => Vec::new(),
+            };
+            let mut icon_set = IconSet::default();
+            if thresholds.is_empty() {
+                // Excel's defaults for a 3-icon set: 0%, 33%, 67%.
+                for val in ["0", "33", "67"] {
+                    icon_set.add_cfvo_collection(default_cfvo(
+                        ConditionalFormatValueObjectValues::Percent,
+                        Some(val),
+                    ));
+                }
+            } else {
+                for cfvo in thresholds {
+                    icon_set.add_cfvo_collection(cfvo);
+                }
+            }
+            rule.set_icon_set(icon_set);
+        }
+        "dataBar" => {
+            let mut bar = DataBar::default();
+            bar.add_cfvo_collection(scale_point(
+                cfg,
+                "min",
+                default_cfvo(ConditionalFormatValueObjectValues::Min, None),
+            )?);
+            bar.add_cfvo_collection(scale_point(
+                cfg,
+                "max",
+                default_cfvo(ConditionalFormatValueObjectValues::Max, None),
+            )?);
+            let colors = scale_colors(cfg, &["#638EC6"])?;
+            if colors.len() != 1 {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "dataBar takes exactly one color",
+                ));
+            }
+            for color in colors {
+                bar.add_color_collection(color);
+            }
+            rule.set_data_bar(bar);
+        }
+        "colorScale" => {
+            let has_mid = cfg.get_item("mid")?.is_some_and(|v| !v.is_none());
+            let colors = match cfg.get_item("colors")?.filter(|v| !v.is_none()) {
+                Some(_) => scale_colors(cfg, &[])?,
+                None if has_mid => scale_colors(cfg, &["#F8696B", "#FFEB84", "#63BE7B"])?,
+                None => scale_colors(cfg, &["#F8696B", "#63BE7B"])?,
+            };
+            // A two-color list without an explicit mid is a 2-color scale.
+            let three = has_mid || colors.len() == 3;
+            if colors.len() != if three { 3 } else { 2 } {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "colorScale takes 2 colors (min/max) or 3 colors (min/mid/max)",
+                ));
+            }
+            let mut scale = ColorScale::default();
+            scale.add_cfvo_collection(scale_point(
+                cfg,
+                "min",
+                default_cfvo(ConditionalFormatValueObjectValues::Min, None),
+            )?);
+            if three {
+                scale.add_cfvo_collection(scale_point(
+                    cfg,
+                    "mid",
+                    default_cfvo(ConditionalFormatValueObjectValues::Percentile, Some("50")),
+                )?);
+            }
+            scale.add_cfvo_collection(scale_point(
+                cfg,
+                "max",
+                default_cfvo(ConditionalFormatValueObjectValues::Max, None),
+            )?);
+            for color in colors {
+                scale.add_color_collection(color);
+            }
+            rule.set_color_scale(scale);
+        }
+        "top10" => {
+            let rank = cfg
+                .get_item("rank")?
+                .map(|v| v.extract::<u32>())
+                .transpose()
+                .map_err(|_| PyErr::new::<PyValueError, _>("'rank' must be a positive int"))?
+                .unwrap_or(10);
+            rule.set_rank(rank);
+            if let Some(percent) = cfg.get_item("percent")?.and_then(|v| v.extract().ok()) {
+                rule.set_percent(percent);
+            }
+            if let Some(bottom) = cfg.get_item("bottom")?.and_then(|v| v.extract().ok()) {
+                rule.set_bottom(bottom);
+            }
+        }
+        "aboveAverage" => {
+            if let Some(above) = cfg
+                .get_item("above_average")?
+                .and_then(|v| v.extract().ok())
+            {
+                rule.set_above_average(above);
+            }
+            if let Some(equal) = cfg
+                .get_item("equal_average")?
+                .and_then(|v| v.extract().ok())
+            {
+                rule.set_equal_average(equal);
+            }
+            if let Some(std_dev) = cfg.get_item("std_dev")?.filter(|v| !v.is_none()) {
+                let std_dev: i32 = std_dev
+                    .extract()
+                    .map_err(|_| PyErr::new::<PyValueError, _>("'std_dev' must be an int (1-3)"))?;
+                rule.set_std_dev(std_dev);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
 }
 
 fn argb_to_hex(color: &Color) -> Option<String> {
@@ -75,7 +355,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         let result = PyList::empty(py);
 
@@ -138,6 +418,30 @@ impl UmyaBook {
                 }
                 d.set_item("format", fmt)?;
 
+                if let Some(bar) = rule.get_data_bar() {
+                    let points = cfvos_to_py(py, bar.get_cfvo_collection())?;
+                    set_scale_points(&d, &points)?;
+                    let colors: Vec<String> = bar
+                        .get_color_collection()
+                        .iter()
+                        .filter_map(argb_to_hex)
+                        .collect();
+                    d.set_item("colors", colors)?;
+                }
+                if let Some(scale) = rule.get_color_scale() {
+                    let points = cfvos_to_py(py, scale.get_cfvo_collection())?;
+                    set_scale_points(&d, &points)?;
+                    let colors: Vec<String> = scale
+                        .get_color_collection()
+                        .iter()
+                        .filter_map(argb_to_hex)
+                        .collect();
+                    d.set_item("colors", colors)?;
+                }
+                if let Some(icons) = rule.get_icon_set() {
+                    d.set_item("thresholds", cfvos_to_py(py, icons.get_cfvo_collection())?)?;
+                }
+
                 result.append(d)?;
             }
         }
@@ -150,10 +454,11 @@ impl UmyaBook {
         sheet: &str,
         rule_dict: &Bound<'_, PyAny>,
     ) -> PyResult<()> {
+        let strict = self.strict;
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         let dict = rule_dict
             .downcast::<PyDict>()
@@ -194,14 +499,37 @@ impl UmyaBook {
                     .and_then(|x| x.extract::<String>().ok())
             });
 
-        if let Some(rt) = rule_type {
-            rule.set_type(str_to_cf_type(&rt));
+        if let Some(rt) = &rule_type {
+            let cf_type = match str_to_cf_type(rt) {
+                Some(t) => t,
+                None => {
+                    errors::degrade(
+                        strict,
+                        CAPABILITIES.backend,
+                        format!("Conditional rule type '{rt}' written as expression"),
+                    )?;
+                    ConditionalFormatValues::Expression
+                }
+            };
+            rule.set_type(cf_type);
+            apply_rule_params(&mut rule, rt, cfg)?;
         }
         if let Some(op) = cfg
             .get_item("operator")?
             .and_then(|v| v.extract::<String>().ok())
         {
-            rule.set_operator(str_to_cf_op(&op));
+            let cf_op = match str_to_cf_op(&op) {
+                Some(o) => o,
+                None => {
+                    errors::degrade(
+                        strict,
+                        CAPABILITIES.backend,
+                        format!("Conditional operator '{op}' written as lessThan"),
+                    )?;
+                    ConditionalFormattingOperatorValues::LessThan
+                }
+            };
+            rule.set_operator(cf_op);
         }
         if let Some(f) = cfg
             .get_item("formula")?
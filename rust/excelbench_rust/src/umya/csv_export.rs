@@ -0,0 +1,83 @@
+use std::borrow::Cow;
+
+use chrono::NaiveTime;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use umya_spreadsheet::Cell;
+
+use crate::csv_export::{self, CsvCell};
+use crate::errors;
+
+use super::util::{excel_serial_to_naive_datetime, looks_like_date_format};
+use super::{UmyaBook, CAPABILITIES};
+
+#[pymethods]
+impl UmyaBook {
+    /// Write the sheet's values to a CSV file at `path`.
+    ///
+    /// Output runs from A1 to the highest used row/column; formula cells
+    /// export their cached values. `options` are as for
+    /// `CalamineBook.export_csv`. Rows are collected first, then the file is
+    /// written with the GIL released. Returns the number of rows written.
+    #[pyo3(signature = (sheet, path, options=None))]
+    pub fn export_csv(
+        &self,
+        py: Python<'_>,
+        sheet: &str,
+        path: &str,
+        options: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<usize> {
+        let opts = csv_export::parse_export_options(options)?;
+        let ws = self
+            .book
+            .get_sheet_by_name(sheet)
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
+
+        let (max_col, max_row) = ws.get_highest_column_and_row();
+        let rows: Vec<Vec<CsvCell<'static>>> = (1..=max_row)
+            .map(|row| {
+                (1..=max_col)
+                    .map(|col| ws.get_cell((col, row)).map_or(CsvCell::Empty, cell_to_csv))
+                    .collect()
+            })
+            .collect();
+
+        let mut writer = csv_export::create(path, opts)?;
+        py.allow_threads(|| {
+            for row in &rows {
+                writer.write_row(row.iter().cloned())?;
+            }
+            writer.finish()
+        })
+        .map_err(csv_export::write_error)?;
+        Ok(rows.len())
+    }
+}
+
+/// Same typing as `read_cell_value`, minus the formula text.
+fn cell_to_csv(cell: &Cell) -> CsvCell<'static> {
+    if let Some(f) = cell.get_value_number() {
+        let is_date = cell
+            .get_style()
+            .get_number_format()
+            .is_some_and(|nf| looks_like_date_format(nf.get_format_code()));
+        if is_date {
+            if let Some(ndt) = excel_serial_to_naive_datetime(f) {
+                if ndt.time() == NaiveTime::MIN {
+                    return CsvCell::Date(ndt.date());
+                }
+                return CsvCell::DateTime(ndt);
+            }
+        }
+        return CsvCell::Number(f);
+    }
+
+    let raw = cell.get_value();
+    match raw.as_ref() {
+        "" => CsvCell::Empty,
+        "TRUE" => CsvCell::Bool(true),
+        "FALSE" => CsvCell::Bool(false),
+        _ => CsvCell::Text(Cow::Owned(raw.into_owned())),
+    }
+}
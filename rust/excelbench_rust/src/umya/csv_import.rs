@@ -0,0 +1,44 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::cell_ref::col_to_letters;
+use crate::csv_io;
+use crate::errors;
+
+use super::cell_values::write_payload;
+use super::{UmyaBook, CAPABILITIES};
+
+#[pymethods]
+impl UmyaBook {
+    /// Load a CSV file (`str` path) or `bytes` into `sheet`, starting at A1.
+    ///
+    /// The sheet is created if missing. `options` accepts `delimiter`,
+    /// `quote`, `encoding` and `infer_types`; see `RustXlsxWriterBook.load_csv`.
+    /// Returns the number of rows loaded.
+    #[pyo3(signature = (path_or_bytes, sheet, options=None))]
+    pub fn load_csv(
+        &mut self,
+        py: Python<'_>,
+        path_or_bytes: &Bound<'_, PyAny>,
+        sheet: &str,
+        options: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<usize> {
+        let rows = csv_io::load(py, path_or_bytes, options)?;
+        if self.book.get_sheet_by_name(sheet).is_none() {
+            self.add_sheet(sheet)?;
+        }
+        let ws = self
+            .book
+            .get_sheet_by_name_mut(sheet)
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
+
+        let count = rows.len();
+        for (r, row) in rows.into_iter().enumerate() {
+            for (c, value) in row.into_iter().enumerate() {
+                let a1 = format!("{}{}", col_to_letters(c as u32), r + 1);
+                write_payload(ws, &a1, value)?;
+            }
+        }
+        Ok(count)
+    }
+}
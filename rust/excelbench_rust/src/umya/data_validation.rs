@@ -3,10 +3,12 @@ use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 
 use umya_spreadsheet::structs::{
-    DataValidation, DataValidationOperatorValues, DataValidationValues, EnumTrait,
+    DataValidation, DataValidationOperatorValues, DataValidationValues,
 };
 
-use super::UmyaBook;
+use crate::errors;
+
+use super::{UmyaBook, CAPABILITIES};
 
 fn dv_type_to_str(t: &DataValidationValues) -> &'static str {
     match t {
@@ -21,8 +23,8 @@ fn dv_type_to_str(t: &DataValidationValues) -> &'static str {
     }
 }
 
-fn str_to_dv_type(s: &str) -> DataValidationValues {
-    match s {
+fn str_to_dv_type(s: &str) -> Option<DataValidationValues> {
+    Some(match s {
         "whole" => DataValidationValues::Whole,
         "decimal" => DataValidationValues::Decimal,
         "list" => DataValidationValues::List,
@@ -30,8 +32,9 @@ fn str_to_dv_type(s: &str) -> DataValidationValues {
         "time" => DataValidationValues::Time,
         "textLength" => DataValidationValues::TextLength,
         "custom" => DataValidationValues::Custom,
-        _ => DataValidationValues::None,
-    }
+        "none" => DataValidationValues::None,
+        _ => return None,
+    })
 }
 
 fn dv_op_to_str(op: &DataValidationOperatorValues) -> &'static str {
@@ -47,26 +50,31 @@ fn dv_op_to_str(op: &DataValidationOperatorValues) -> &'static str {
     }
 }
 
-fn str_to_dv_op(s: &str) -> DataValidationOperatorValues {
-    match s {
+fn str_to_dv_op(s: &str) -> Option<DataValidationOperatorValues> {
+    Some(match s {
         "between" => DataValidationOperatorValues::Between,
         "notBetween" => DataValidationOperatorValues::NotBetween,
         "equal" => DataValidationOperatorValues::Equal,
         "notEqual" => DataValidationOperatorValues::NotEqual,
         "greaterThan" => DataValidationOperatorValues::GreaterThan,
         "greaterThanOrEqual" => DataValidationOperatorValues::GreaterThanOrEqual,
+        "lessThan" => DataValidationOperatorValues::LessThan,
         "lessThanOrEqual" => DataValidationOperatorValues::LessThanOrEqual,
-        _ => DataValidationOperatorValues::LessThan,
-    }
+        _ => return None,
+    })
 }
 
 #[pymethods]
 impl UmyaBook {
+    /// Data validations of a sheet, one dict per range of each rule.
+    ///
+    /// `operator` is the rule's attribute as written; None when the rule has
+    /// none (Excel then applies `between`).
     pub fn read_data_validations(&self, py: Python<'_>, sheet: &str) -> PyResult<PyObject> {
         let ws = self
             .book
             .get_sheet_by_name(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         let result = PyList::empty(py);
 
@@ -75,39 +83,48 @@ impl UmyaBook {
             None => return Ok(result.into()),
         };
 
-        for dv in dvs.get_data_validation_list() {
-            let d = PyDict::new(py);
-            d.set_item("range", dv.get_sequence_of_references().get_sqref())?;
-            d.set_item("validation_type", dv_type_to_str(dv.get_type()))?;
-
-            let op_str = dv_op_to_str(dv.get_operator());
-            let op_has_value = dv.get_operator().get_value_string() != "lessThan"
-                || *dv.get_type() != DataValidationValues::List;
-            if op_has_value {
-                d.set_item("operator", op_str)?;
-            } else {
-                d.set_item("operator", py.None())?;
-            }
+        let list = dvs.get_data_validation_list();
+        // Positions only line up while every rule is tracked.
+        let tracked = self
+            .dv_operators
+            .get(sheet)
+            .filter(|ops| ops.len() == list.len());
+        for (i, dv) in list.iter().enumerate() {
+            let dv_type = dv.get_type();
+            let operator: Option<&str> = match tracked.and_then(|ops| ops.get(i)) {
+                Some(op) => op.as_deref(),
+                None => Some(dv_op_to_str(dv.get_operator())),
+            };
 
-            let f1 = dv.get_formula1();
-            d.set_item("formula1", if f1.is_empty() { None } else { Some(f1) })?;
             let f2 = dv.get_formula2();
-            d.set_item("formula2", if f2.is_empty() { None } else { Some(f2) })?;
-
-            d.set_item("allow_blank", *dv.get_allow_blank())?;
-            d.set_item("show_input", *dv.get_show_input_message())?;
-            d.set_item("show_error", *dv.get_show_error_message())?;
-
+            let f1 = dv.get_formula1();
             let pt = dv.get_prompt_title();
-            d.set_item("prompt_title", if pt.is_empty() { None } else { Some(pt) })?;
             let p = dv.get_prompt();
-            d.set_item("prompt", if p.is_empty() { None } else { Some(p) })?;
             let et = dv.get_error_title();
-            d.set_item("error_title", if et.is_empty() { None } else { Some(et) })?;
             let em = dv.get_error_message();
-            d.set_item("error", if em.is_empty() { None } else { Some(em) })?;
 
-            result.append(d)?;
+            // Multi-range sqrefs ("A1:A5 C1:C5") become one entry per range.
+            let sqref = dv.get_sequence_of_references().get_sqref();
+            for range in sqref.split_whitespace() {
+                let d = PyDict::new(py);
+                d.set_item("range", range)?;
+                d.set_item("validation_type", dv_type_to_str(dv_type))?;
+                d.set_item("operator", operator)?;
+                d.set_item("formula1", if f1.is_empty() { None } else { Some(f1) })?;
+                d.set_item("formula2", if f2.is_empty() { None } else { Some(f2) })?;
+
+                d.set_item("allow_blank", *dv.get_allow_blank())?;
+                d.set_item("show_input", *dv.get_show_input_message())?;
+                d.set_item("show_error", *dv.get_show_error_message())?;
+                d.set_item("show_dropdown", *dv.get_show_drop_down())?;
+
+                d.set_item("prompt_title", if pt.is_empty() { None } else { Some(pt) })?;
+                d.set_item("prompt", if p.is_empty() { None } else { Some(p) })?;
+                d.set_item("error_title", if et.is_empty() { None } else { Some(et) })?;
+                d.set_item("error", if em.is_empty() { None } else { Some(em) })?;
+
+                result.append(d)?;
+            }
         }
 
         Ok(result.into())
@@ -118,10 +135,12 @@ impl UmyaBook {
         sheet: &str,
         validation_dict: &Bound<'_, PyAny>,
     ) -> PyResult<()> {
+        let strict = self.strict;
+        let mut operator = None;
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         let dict = validation_dict
             .downcast::<PyDict>()
@@ -164,13 +183,36 @@ impl UmyaBook {
             });
 
         if let Some(vt) = validation_type {
-            dv.set_type(str_to_dv_type(&vt));
+            let dv_type = match str_to_dv_type(&vt) {
+                Some(t) => t,
+                None => {
+                    errors::degrade(
+                        strict,
+                        CAPABILITIES.backend,
+                        format!("Validation type '{vt}' written as none"),
+                    )?;
+                    DataValidationValues::None
+                }
+            };
+            dv.set_type(dv_type);
         }
         if let Some(op) = cfg
             .get_item("operator")?
             .and_then(|v| v.extract::<String>().ok())
         {
-            dv.set_operator(str_to_dv_op(&op));
+            let dv_op = match str_to_dv_op(&op) {
+                Some(o) => o,
+                None => {
+                    errors::degrade(
+                        strict,
+                        CAPABILITIES.backend,
+                        format!("Validation operator '{op}' written as lessThan"),
+                    )?;
+                    DataValidationOperatorValues::LessThan
+                }
+            };
+            operator = Some(dv_op_to_str(&dv_op).to_string());
+            dv.set_operator(dv_op);
         }
         if let Some(f1) = cfg
             .get_item("formula1")?
@@ -202,6 +244,12 @@ impl UmyaBook {
         {
             dv.set_show_error_message(se);
         }
+        if let Some(sd) = cfg
+            .get_item("show_dropdown")?
+            .and_then(|v| v.extract::<bool>().ok())
+        {
+            dv.set_show_drop_down(sd);
+        }
         if let Some(pt) = cfg
             .get_item("prompt_title")?
             .and_then(|v| v.extract::<String>().ok())
@@ -246,6 +294,10 @@ impl UmyaBook {
         let mut dvs = ws.get_data_validations().cloned().unwrap_or_default();
         dvs.add_data_validation_list(dv);
         ws.set_data_validations(dvs);
+        self.dv_operators
+            .entry(sheet.to_string())
+            .or_default()
+            .push(operator);
 
         Ok(())
     }
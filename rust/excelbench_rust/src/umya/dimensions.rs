@@ -1,8 +1,9 @@
-use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 
+use crate::errors;
+
 use super::util::col_letter_to_u32;
-use super::UmyaBook;
+use super::{UmyaBook, CAPABILITIES};
 
 #[pymethods]
 impl UmyaBook {
@@ -10,7 +11,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         // umya uses 1-based row index.
         if let Some(rd) = ws.get_row_dimension(&(row + 1)) {
@@ -26,9 +27,10 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
-        let col_idx = col_letter_to_u32(col_str).map_err(|e| PyErr::new::<PyValueError, _>(e))?;
+        let col_idx = col_letter_to_u32(col_str)
+            .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, col_str, msg))?;
 
         if let Some(cd) = ws.get_column_dimension_by_number(&col_idx) {
             let w = cd.get_width();
@@ -43,7 +45,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         // umya uses 1-based row index.
         ws.get_row_dimension_mut(&(row + 1)).set_height(height);
@@ -54,9 +56,10 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
-        let col_idx = col_letter_to_u32(col_str).map_err(|e| PyErr::new::<PyValueError, _>(e))?;
+        let col_idx = col_letter_to_u32(col_str)
+            .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, col_str, msg))?;
 
         ws.get_column_dimension_by_number_mut(&col_idx)
             .set_width(width);
@@ -1,17 +1,20 @@
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyDict, PyList};
 
 use std::str::FromStr;
 
 use umya_spreadsheet::structs::{
-    EnumTrait, HorizontalAlignmentValues, PatternValues, VerticalAlignmentValues,
+    EnumTrait, GradientFill, GradientStop, HorizontalAlignmentValues, PatternValues,
+    VerticalAlignmentValues,
 };
 
+use crate::errors;
+use crate::payload;
 use crate::util::a1_to_row_col;
 
-use super::util::{argb_to_hex, hex_to_argb};
-use super::UmyaBook;
+use super::util::{resolve_color, set_color, set_color_spec};
+use super::{UmyaBook, CAPABILITIES};
 
 #[pymethods]
 impl UmyaBook {
@@ -19,9 +22,10 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
-        let (row0, col0) = a1_to_row_col(a1).map_err(|msg| PyErr::new::<PyValueError, _>(msg))?;
+        let (row0, col0) =
+            a1_to_row_col(a1).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a1, msg))?;
         let coord = (col0 + 1, row0 + 1);
 
         let d = PyDict::new(py);
@@ -32,6 +36,7 @@ impl UmyaBook {
         };
 
         let style = cell.get_style();
+        let theme = self.book.get_theme();
 
         // Font properties
         if let Some(font) = style.get_font() {
@@ -62,27 +67,52 @@ impl UmyaBook {
                     d.set_item("font_size", size)?;
                 }
             }
-            {
-                let argb = font.get_color().get_argb();
-                if !argb.is_empty() {
-                    let hex = argb_to_hex(argb);
-                    if hex != "#000000" {
-                        d.set_item("font_color", hex)?;
-                    }
+            if let Some(hex) = resolve_color(font.get_color(), theme) {
+                if hex != "#000000" {
+                    d.set_item("font_color", hex)?;
                 }
             }
         }
 
-        // Fill / background color
+        // Fill: `bg_color` is the visible color for solid fills and the
+        // background for other patterns (as in CalamineStyledBook).
         if let Some(fill) = style.get_fill() {
             if let Some(pf) = fill.get_pattern_fill() {
-                if let Some(fg) = pf.get_foreground_color() {
-                    let argb = fg.get_argb();
-                    if !argb.is_empty() {
-                        let hex = argb_to_hex(argb);
-                        d.set_item("bg_color", hex)?;
+                let pattern = pf.get_pattern_type().get_value_string();
+                let fg = pf
+                    .get_foreground_color()
+                    .and_then(|c| resolve_color(c, theme));
+                let bg = pf
+                    .get_background_color()
+                    .and_then(|c| resolve_color(c, theme));
+                if pattern != "none" {
+                    d.set_item("pattern_type", pattern)?;
+                    if let Some(fg) = &fg {
+                        d.set_item("fg_color", fg)?;
                     }
                 }
+                let shown = if pattern == "solid" || pattern == "none" {
+                    fg.or(bg)
+                } else {
+                    bg
+                };
+                if let Some(hex) = shown {
+                    d.set_item("bg_color", hex)?;
+                }
+            }
+            if let Some(gf) = fill.get_gradient_fill() {
+                let gradient = PyDict::new(py);
+                gradient.set_item("type", "linear")?;
+                gradient.set_item("degree", *gf.get_degree())?;
+                let stops = PyList::empty(py);
+                for stop in gf.get_gradient_stop() {
+                    let s = PyDict::new(py);
+                    s.set_item("position", *stop.get_position())?;
+                    s.set_item("color", resolve_color(stop.get_color(), theme))?;
+                    stops.append(s)?;
+                }
+                gradient.set_item("stops", stops)?;
+                d.set_item("gradient", gradient)?;
             }
         }
 
@@ -113,6 +143,12 @@ impl UmyaBook {
             }
         }
 
+        // Protection: both flags whenever the style carries the element.
+        if let Some(protection) = style.get_protection() {
+            d.set_item("locked", *protection.get_locked())?;
+            d.set_item("hidden", *protection.get_hidden())?;
+        }
+
         Ok(d.into())
     }
 
@@ -125,113 +161,113 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
-
-        let dict = format_dict
-            .downcast::<PyDict>()
-            .map_err(|_| PyErr::new::<PyValueError, _>("format_dict must be a dict"))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
+        let fmt = payload::parse_format(format_dict)?;
+        if fmt.gradient.as_ref().is_some_and(|g| g.kind != "linear") {
+            return Err(errors::unsupported(
+                CAPABILITIES.backend,
+                "umya-spreadsheet only writes linear gradient fills",
+            ));
+        }
         let style = ws.get_style_mut(a1);
 
         // Font properties
         {
             let font = style.get_font_mut();
 
-            if let Some(bold) = dict
-                .get_item("bold")?
-                .and_then(|v| v.extract::<bool>().ok())
-            {
+            if let Some(bold) = fmt.bold {
                 font.set_bold(bold);
             }
-            if let Some(italic) = dict
-                .get_item("italic")?
-                .and_then(|v| v.extract::<bool>().ok())
-            {
+            if let Some(italic) = fmt.italic {
                 font.set_italic(italic);
             }
-            if let Some(ul) = dict
-                .get_item("underline")?
-                .and_then(|v| v.extract::<String>().ok())
-            {
+            if let Some(ul) = fmt.underline {
                 font.set_underline(ul);
             }
-            if let Some(st) = dict
-                .get_item("strikethrough")?
-                .and_then(|v| v.extract::<bool>().ok())
-            {
+            if let Some(st) = fmt.strikethrough {
                 font.set_strikethrough(st);
             }
-            if let Some(name) = dict
-                .get_item("font_name")?
-                .and_then(|v| v.extract::<String>().ok())
-            {
+            if let Some(name) = fmt.font_name {
                 font.set_name(name);
             }
-            if let Some(size) = dict
-                .get_item("font_size")?
-                .and_then(|v| v.extract::<f64>().ok())
-            {
+            if let Some(size) = fmt.font_size {
                 font.set_size(size);
             }
-            if let Some(color) = dict
-                .get_item("font_color")?
-                .and_then(|v| v.extract::<String>().ok())
-            {
-                font.get_color_mut().set_argb(hex_to_argb(&color));
+            if let Some(color) = fmt.font_color {
+                set_color(font.get_color_mut(), &color);
             }
         }
 
-        // Background color via pattern fill
-        if let Some(bg) = dict
-            .get_item("bg_color")?
-            .and_then(|v| v.extract::<String>().ok())
-        {
-            let fill = style.get_fill_mut();
-            let pf = fill.get_pattern_fill_mut();
-            pf.set_pattern_type(PatternValues::Solid);
-            pf.get_foreground_color_mut().set_argb(hex_to_argb(&bg));
+        // Fill: a gradient, or a pattern fill. A solid fill shows its
+        // fgColor, so a lone `bg_color` is written there.
+        if let Some(g) = fmt.gradient {
+            let mut gf = GradientFill::default();
+            gf.set_degree(g.degree);
+            for stop in &g.stops {
+                let mut gs = GradientStop::default();
+                gs.set_position(stop.position);
+                if let Some(c) = &stop.color {
+                    set_color_spec(gs.get_color_mut(), c);
+                }
+                gf.set_gradient_stop(gs);
+            }
+            style.get_fill_mut().set_gradient_fill(gf);
+        } else if fmt.pattern_type.is_some() || fmt.fg_color.is_some() || fmt.bg_color.is_some() {
+            let pattern = match fmt.pattern_type.as_deref() {
+                Some(p) => PatternValues::from_str(p).map_err(|_| {
+                    PyErr::new::<PyValueError, _>(format!("Unknown fill pattern '{p}'"))
+                })?,
+                None => PatternValues::Solid,
+            };
+            let solid = pattern.get_value_string() == "solid";
+            let (fg, bg) = match (solid, fmt.fg_color, fmt.bg_color) {
+                (true, None, bg) => (bg, None),
+                (_, fg, bg) => (fg, bg),
+            };
+            let pf = style.get_fill_mut().get_pattern_fill_mut();
+            pf.set_pattern_type(pattern);
+            if let Some(fg) = fg {
+                set_color(pf.get_foreground_color_mut(), &fg);
+            }
+            if let Some(bg) = bg {
+                set_color(pf.get_background_color_mut(), &bg);
+            }
         }
 
         // Number format
-        if let Some(nf) = dict
-            .get_item("number_format")?
-            .and_then(|v| v.extract::<String>().ok())
-        {
+        if let Some(nf) = fmt.number_format {
             style.get_number_format_mut().set_format_code(nf);
         }
 
         // Alignment
-        if let Some(h) = dict
-            .get_item("h_align")?
-            .and_then(|v| v.extract::<String>().ok())
-        {
+        if let Some(h) = fmt.h_align {
             if let Ok(ha) = HorizontalAlignmentValues::from_str(&h) {
                 style.get_alignment_mut().set_horizontal(ha);
             }
         }
-        if let Some(v) = dict
-            .get_item("v_align")?
-            .and_then(|v| v.extract::<String>().ok())
-        {
+        if let Some(v) = fmt.v_align {
             if let Ok(va) = VerticalAlignmentValues::from_str(&v) {
                 style.get_alignment_mut().set_vertical(va);
             }
         }
-        if let Some(wrap) = dict
-            .get_item("wrap")?
-            .and_then(|v| v.extract::<bool>().ok())
-        {
+        if let Some(wrap) = fmt.wrap {
             style.get_alignment_mut().set_wrap_text(wrap);
         }
-        if let Some(rot) = dict
-            .get_item("rotation")?
-            .and_then(|v| v.extract::<i64>().ok())
-        {
+        if let Some(rot) = fmt.rotation {
             if let Ok(r) = u32::try_from(rot) {
                 style.get_alignment_mut().set_text_rotation(r);
             }
         }
 
+        // Protection
+        if let Some(locked) = fmt.locked {
+            style.get_protection_mut().set_locked(locked);
+        }
+        if let Some(hidden) = fmt.hidden {
+            style.get_protection_mut().set_hidden(hidden);
+        }
+
         Ok(())
     }
 }
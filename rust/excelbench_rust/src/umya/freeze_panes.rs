@@ -4,7 +4,10 @@ use pyo3::types::PyDict;
 
 use umya_spreadsheet::structs::{EnumTrait, Pane, PaneStateValues, PaneValues, SheetView};
 
-use super::UmyaBook;
+use crate::cell_ref::col_to_letters;
+use crate::errors;
+
+use super::{UmyaBook, CAPABILITIES};
 
 /// Extract a string value from a PyDict, looking in an optional inner dict first.
 fn get_str(dict: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<String>> {
@@ -21,19 +24,6 @@ fn get_i64(dict: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<i64>> {
     Ok(dict.get_item(key)?.and_then(|v| v.extract::<i64>().ok()))
 }
 
-/// Convert a 0-based column index to Excel letters (0 -> A, 25 -> Z, 26 -> AA).
-fn col_to_letters(col0: u32) -> String {
-    // Convert to 1-based for the usual base-26 algorithm.
-    let mut n = col0 + 1;
-    let mut out = String::new();
-    while n > 0 {
-        let rem = ((n - 1) % 26) as u8;
-        out.push((b'A' + rem) as char);
-        n = (n - 1) / 26;
-    }
-    out.chars().rev().collect()
-}
-
 /// Convert split counts (row/column frozen counts) into the first scrollable cell.
 fn split_to_top_left(row: u32, col: u32) -> String {
     let col_letters = col_to_letters(col);
@@ -47,7 +37,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         let d = PyDict::new(py);
 
@@ -95,7 +85,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         let dict = settings
             .downcast::<PyDict>()
@@ -4,7 +4,9 @@ use pyo3::types::{PyDict, PyList};
 
 use umya_spreadsheet::structs::Hyperlink;
 
-use super::UmyaBook;
+use crate::errors;
+
+use super::{UmyaBook, CAPABILITIES};
 
 #[pymethods]
 impl UmyaBook {
@@ -12,7 +14,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         let result = PyList::empty(py);
 
@@ -45,7 +47,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         let dict = link_dict
             .downcast::<PyDict>()
@@ -86,6 +88,8 @@ impl UmyaBook {
         let mut h = Hyperlink::default();
         h.set_url(&target);
         h.set_location(internal);
+        // umya writes the tooltip attribute itself, so saving needs no
+        // post-processing of the worksheet XML.
         if let Some(tt) = &tooltip {
             h.set_tooltip(tt);
         }
@@ -5,7 +5,12 @@ use pyo3::types::{PyDict, PyList};
 use umya_spreadsheet::structs::drawing::spreadsheet::MarkerType;
 use umya_spreadsheet::structs::Image;
 
-use super::UmyaBook;
+use crate::errors;
+
+use super::{UmyaBook, CAPABILITIES};
+
+/// EMUs per pixel at 96 dpi; `width`/`height` are in pixels, offsets in EMU.
+const EMU_PER_PIXEL: i64 = 9525;
 
 #[pymethods]
 impl UmyaBook {
@@ -13,13 +18,15 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         let result = PyList::empty(py);
 
         for img in ws.get_image_collection() {
             let d = PyDict::new(py);
 
+            let mut size = None;
+            let mut alt_text = None;
             // Determine anchor type and cell from the image's anchor
             if let Some(two_cell) = img.get_two_cell_anchor() {
                 let from = two_cell.get_from_marker();
@@ -33,6 +40,17 @@ impl UmyaBook {
                 d.set_item("anchor", "oneCell")?;
                 let offsets = PyList::new(py, [*from.get_col_off(), *from.get_row_off()])?;
                 d.set_item("offset", offsets)?;
+                let extent = one_cell.get_extent();
+                size = Some((
+                    *extent.get_cx() / EMU_PER_PIXEL,
+                    *extent.get_cy() / EMU_PER_PIXEL,
+                ));
+                alt_text = one_cell.get_picture().map(|pic| {
+                    pic.get_non_visual_picture_properties()
+                        .get_non_visual_drawing_properties()
+                        .get_description()
+                        .to_string()
+                });
             } else {
                 d.set_item("cell", py.None())?;
                 d.set_item("anchor", py.None())?;
@@ -41,7 +59,9 @@ impl UmyaBook {
 
             // Path/media reference not directly exposed in umya — set to None
             d.set_item("path", py.None())?;
-            d.set_item("alt_text", py.None())?;
+            d.set_item("width", size.map(|s| s.0))?;
+            d.set_item("height", size.map(|s| s.1))?;
+            d.set_item("alt_text", alt_text.filter(|t| !t.is_empty()))?;
 
             result.append(d)?;
         }
@@ -53,7 +73,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         let dict = image_dict
             .downcast::<PyDict>()
@@ -75,11 +95,57 @@ impl UmyaBook {
             .ok_or_else(|| PyErr::new::<PyValueError, _>("image missing 'cell'"))?
             .extract()?;
 
+        // `offset` is [col_off, row_off] in EMU, as `read_images` reports it.
+        let offset: Option<(usize, usize)> = match cfg.get_item("offset")? {
+            Some(v) if !v.is_none() => Some(v.extract().map_err(|_| {
+                PyErr::new::<PyValueError, _>("image 'offset' must be [col_off, row_off]")
+            })?),
+            _ => None,
+        };
+        let dimension = |key: &str| -> PyResult<Option<f64>> {
+            match cfg.get_item(key)? {
+                Some(v) if !v.is_none() => {
+                    let px: f64 = v.extract()?;
+                    if px <= 0.0 {
+                        return Err(PyErr::new::<PyValueError, _>(format!(
+                            "image '{key}' must be positive"
+                        )));
+                    }
+                    Ok(Some(px))
+                }
+                _ => Ok(None),
+            }
+        };
+        let width = dimension("width")?;
+        let height = dimension("height")?;
+        let alt_text: Option<String> = match cfg.get_item("alt_text")? {
+            Some(v) if !v.is_none() => Some(v.extract()?),
+            _ => None,
+        };
+
         let mut marker = MarkerType::default();
         marker.set_coordinate(cell);
+        if let Some((col_off, row_off)) = offset {
+            marker.set_col_off(col_off);
+            marker.set_row_off(row_off);
+        }
 
         let mut image = Image::default();
         image.new_image(&path, marker);
+        if let Some(anchor) = image.get_one_cell_anchor_mut() {
+            let extent = anchor.get_extent_mut();
+            if let Some(w) = width {
+                extent.set_cx((w * EMU_PER_PIXEL as f64).round() as i64);
+            }
+            if let Some(h) = height {
+                extent.set_cy((h * EMU_PER_PIXEL as f64).round() as i64);
+            }
+            if let (Some(text), Some(pic)) = (&alt_text, anchor.get_picture_mut()) {
+                pic.get_non_visual_picture_properties_mut()
+                    .get_non_visual_drawing_properties_mut()
+                    .set_description(text);
+            }
+        }
         ws.add_image(image);
 
         Ok(())
@@ -0,0 +1,47 @@
+use pyo3::prelude::*;
+
+use crate::errors;
+use crate::json_export::{self, TypedCell};
+
+use super::cell_values::typed_cell;
+use super::{UmyaBook, CAPABILITIES};
+
+#[pymethods]
+impl UmyaBook {
+    /// Write the sheet to `path` as typed JSON, from A1 to the highest used
+    /// row/column.
+    ///
+    /// Payloads and `orient` are as for `CalamineBook.export_json`. Rows are
+    /// collected first, then the file is written with the GIL released.
+    /// Returns the number of rows (records: excluding the header) written.
+    #[pyo3(signature = (sheet, path, orient="records"))]
+    pub fn export_json(
+        &self,
+        py: Python<'_>,
+        sheet: &str,
+        path: &str,
+        orient: &str,
+    ) -> PyResult<usize> {
+        let orient = json_export::parse_orient(orient)?;
+        let ws = self
+            .book
+            .get_sheet_by_name(sheet)
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
+
+        let (max_col, max_row) = ws.get_highest_column_and_row();
+        let rows: Vec<Vec<TypedCell<'static>>> = (1..=max_row)
+            .map(|row| {
+                (1..=max_col)
+                    .map(|col| {
+                        ws.get_cell((col, row))
+                            .map_or(TypedCell::blank(), typed_cell)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let file = json_export::create(path)?;
+        py.allow_threads(|| json_export::write_sheet(file, orient, rows))
+            .map_err(json_export::write_error)
+    }
+}
@@ -1,47 +1,258 @@
 use pyo3::exceptions::{PyIOError, PyValueError};
 use pyo3::prelude::*;
 
+use std::collections::HashMap;
 use std::path::Path;
 
 use umya_spreadsheet::{new_file, reader, writer, Spreadsheet};
 
 mod auto_filter;
+mod autofit;
 mod borders;
+mod calc_props;
 mod cell_values;
 mod comments;
 mod conditional_fmt;
+mod csv_export;
+mod csv_import;
 mod data_validation;
 mod dimensions;
 mod formatting;
 mod freeze_panes;
 mod hyperlinks;
 mod images;
+mod json_export;
 mod merged_cells;
 mod named_ranges;
+mod sheet_format;
+mod source;
 mod tables;
 mod util;
 
-#[pyclass(unsendable)]
+use crate::backend::{pyclass_object, Backend, BackendEntry, ExcelReadBackend, ExcelWriteBackend};
+use crate::capabilities::BackendCapabilities;
+use crate::errors;
+use crate::ooxml_util::{self, auto_filter::AutoFilterDef, calc_pr::CalcPrEdit};
+use crate::profile;
+
+/// umya loads and re-serializes the whole workbook, so every feature it can
+/// write it can also round-trip through `open()` + `save()`.
+pub(crate) const CAPABILITIES: BackendCapabilities = BackendCapabilities {
+    class: "UmyaBook",
+    backend: "umya-spreadsheet",
+    extensions: &[".xlsx", ".xlsm"],
+    read: UMYA_FEATURES,
+    write: UMYA_FEATURES,
+    modify: true,
+};
+
+const UMYA_FEATURES: &[&str] = &[
+    "cell_values",
+    "formulas",
+    "text_formatting",
+    "background_colors",
+    "number_formats",
+    "alignment",
+    "borders",
+    "dimensions",
+    "multiple_sheets",
+    "merged_cells",
+    "conditional_formatting",
+    "data_validation",
+    "hyperlinks",
+    "images",
+    "comments",
+    "freeze_panes",
+    "named_ranges",
+    "tables",
+];
+
+/// `umya_spreadsheet::Spreadsheet` is `Send + Sync`, so the book can move
+/// between threads. Concurrent calls on one book are serialized by PyO3's
+/// borrow flag (the loser gets `RuntimeError`); `open()` and `save()` run
+/// without the GIL so other threads keep working during the parse/write.
+#[pyclass]
 pub struct UmyaBook {
     pub(super) book: Spreadsheet,
     pub(super) saved: bool,
+    /// `<calcPr>` attributes patched into workbook.xml on save; umya itself
+    /// does not model calculation properties.
+    pub(super) calc_pr: Vec<CalcPrEdit>,
+    /// Sheets with `zeroHeight` (rows hidden by default), patched into the
+    /// worksheets on save; umya does not model the attribute.
+    pub(super) zero_height: HashMap<String, bool>,
+    /// `operator` of each sheet's data validations, in list order (None when
+    /// absent); umya reports an absent operator as `lessThan`.
+    pub(super) dv_operators: HashMap<String, Vec<Option<String>>>,
+    /// `<autoFilter>` of each sheet in the file the book was opened from;
+    /// umya keeps only the range.
+    pub(super) source_auto_filters: HashMap<String, AutoFilterDef>,
+    /// Written by `__exit__` when the `with` block ends without an exception.
+    pub(super) save_path: Option<String>,
+    /// Raise `UnsupportedFeature` instead of logging and approximating input
+    /// umya cannot write.
+    pub(super) strict: bool,
+}
+
+/// Fails to compile if a umya upgrade makes `Spreadsheet` thread-bound.
+fn _assert_send<T: Send>() {}
+const _: fn() = _assert_send::<Spreadsheet>;
+
+pub(crate) const BACKEND: BackendEntry = BackendEntry {
+    name: "umya-spreadsheet",
+    open: |py, path| {
+        Ok(Box::new(match path {
+            Some(path) => UmyaBook::open(py, path, false)?,
+            None => UmyaBook::new(None, false),
+        }))
+    },
+};
+
+impl Backend for UmyaBook {
+    fn into_py_object(self: Box<Self>, py: Python<'_>) -> PyResult<PyObject> {
+        pyclass_object(py, *self)
+    }
+
+    fn as_reader(&mut self) -> Option<&mut dyn ExcelReadBackend> {
+        Some(self)
+    }
+
+    fn as_writer(&mut self) -> Option<&mut dyn ExcelWriteBackend> {
+        Some(self)
+    }
+}
+
+impl ExcelReadBackend for UmyaBook {
+    fn sheet_names(&self) -> PyResult<Vec<String>> {
+        UmyaBook::sheet_names(self)
+    }
+
+    fn read_cell_value(&mut self, py: Python<'_>, sheet: &str, a1: &str) -> PyResult<PyObject> {
+        UmyaBook::read_cell_value(self, py, sheet, a1)
+    }
+
+    fn used_cells(&mut self, _py: Python<'_>, sheet: &str) -> PyResult<Vec<(u32, u32)>> {
+        let ws = self
+            .book
+            .get_sheet_by_name(sheet)
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
+        let mut cells: Vec<(u32, u32)> = ws
+            .get_cell_collection()
+            .into_iter()
+            .filter(|c| !c.get_formula().is_empty() || !c.get_value().is_empty())
+            .map(|c| {
+                let coord = c.get_coordinate();
+                (*coord.get_row_num() - 1, *coord.get_col_num() - 1)
+            })
+            .collect();
+        cells.sort_unstable();
+        Ok(cells)
+    }
+
+    fn read_cell_format(
+        &mut self,
+        py: Python<'_>,
+        sheet: &str,
+        a1: &str,
+    ) -> PyResult<Option<PyObject>> {
+        UmyaBook::read_cell_format(self, py, sheet, a1).map(Some)
+    }
+
+    fn read_cell_border(
+        &mut self,
+        py: Python<'_>,
+        sheet: &str,
+        a1: &str,
+    ) -> PyResult<Option<PyObject>> {
+        UmyaBook::read_cell_border(self, py, sheet, a1).map(Some)
+    }
+}
+
+impl ExcelWriteBackend for UmyaBook {
+    fn add_sheet(&mut self, name: &str) -> PyResult<()> {
+        UmyaBook::add_sheet(self, name)
+    }
+
+    fn write_cell_value(
+        &mut self,
+        sheet: &str,
+        a1: &str,
+        payload: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        UmyaBook::write_cell_value(self, sheet, a1, payload)
+    }
+
+    fn write_cell_format(
+        &mut self,
+        sheet: &str,
+        a1: &str,
+        format_dict: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        UmyaBook::write_cell_format(self, sheet, a1, format_dict)
+    }
+
+    fn write_cell_border(
+        &mut self,
+        sheet: &str,
+        a1: &str,
+        border_dict: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        UmyaBook::write_cell_border(self, sheet, a1, border_dict)
+    }
+
+    fn save(&mut self, py: Python<'_>, path: &str) -> PyResult<()> {
+        UmyaBook::save(self, py, path)
+    }
 }
 
 #[pymethods]
 impl UmyaBook {
+    /// `path` is only used by the context manager, which saves there on a
+    /// clean exit. With `strict`, rule and validation types umya would
+    /// otherwise write as a stand-in raise `UnsupportedFeature`.
     #[new]
-    pub fn new() -> Self {
+    #[pyo3(signature = (path=None, strict=false))]
+    pub fn new(path: Option<String>, strict: bool) -> Self {
         let mut book = new_file();
         let _ = book.remove_sheet_by_name("Sheet1");
-        Self { book, saved: false }
+        Self {
+            book,
+            saved: false,
+            calc_pr: Vec::new(),
+            zero_height: HashMap::new(),
+            dv_operators: HashMap::new(),
+            source_auto_filters: HashMap::new(),
+            save_path: path,
+            strict,
+        }
     }
 
     #[staticmethod]
-    pub fn open(path: &str) -> PyResult<Self> {
+    #[pyo3(signature = (path, strict=false))]
+    pub fn open(py: Python<'_>, path: &str, strict: bool) -> PyResult<Self> {
+        let _span = profile::span("umya.parse");
         let p = Path::new(path);
-        let book = reader::xlsx::read(p)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to open workbook: {e}")))?;
-        Ok(Self { book, saved: false })
+        let (book, source) = py
+            .allow_threads(|| {
+                reader::xlsx::read(p).map(|book| (book, source::read_source_parts(path)))
+            })
+            .map_err(|e| {
+                errors::file_format(
+                    CAPABILITIES.backend,
+                    path,
+                    format!("Failed to open workbook: {e}"),
+                )
+            })?;
+        Ok(Self {
+            book,
+            saved: false,
+            calc_pr: source.calc_pr,
+            zero_height: source.zero_height,
+            dv_operators: source.dv_operators,
+            source_auto_filters: source.auto_filters,
+            save_path: None,
+            strict,
+        })
     }
 
     pub fn sheet_names(&self) -> PyResult<Vec<String>> {
@@ -59,7 +270,7 @@ impl UmyaBook {
         Ok(())
     }
 
-    pub fn save(&mut self, path: &str) -> PyResult<()> {
+    pub fn save(&mut self, py: Python<'_>, path: &str) -> PyResult<()> {
         if self.saved {
             return Err(PyErr::new::<PyValueError, _>(
                 "Workbook already saved (UmyaBook is consumed-on-save)",
@@ -68,7 +279,48 @@ impl UmyaBook {
         self.saved = true;
 
         let p = Path::new(path);
-        writer::xlsx::write(&self.book, p)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to save workbook: {e}")))
+        let _span = profile::span("umya.zip_write");
+        let book = &self.book;
+        py.allow_threads(|| writer::xlsx::write(book, p))
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Failed to save workbook: {e}")))?;
+
+        let mut patches = ooxml_util::PackagePatches::open(path)?;
+        patches.calc_pr(&self.calc_pr)?;
+        patches.sheet_format_pr(&sheet_format::zero_height_edits(&self.zero_height))?;
+        patches.finish()
+    }
+
+    /// Release the workbook without saving. Afterwards the book has no
+    /// sheets and cannot be saved.
+    pub fn close(&mut self) {
+        self.book = new_file();
+        let _ = self.book.remove_sheet_by_name("Sheet1");
+        self.calc_pr.clear();
+        self.zero_height.clear();
+        self.dv_operators.clear();
+        self.source_auto_filters.clear();
+        self.saved = true;
+    }
+
+    pub fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Save to the constructor's `path` when the block exits cleanly, then
+    /// close. On an exception nothing is written.
+    #[pyo3(signature = (exc_type=None, _exc_value=None, _traceback=None))]
+    pub fn __exit__(
+        &mut self,
+        py: Python<'_>,
+        exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        let result = match (&exc_type, self.save_path.clone()) {
+            (None, Some(path)) if !self.saved => self.save(py, &path),
+            _ => Ok(()),
+        };
+        self.close();
+        result.map(|_| false)
     }
 }
@@ -2,7 +2,9 @@ use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 
-use super::UmyaBook;
+use crate::errors;
+
+use super::{UmyaBook, CAPABILITIES};
 
 /// Normalize a defined name address for benchmark comparison.
 /// Strips leading `=`, `$` signs, and surrounding quotes from sheet names.
@@ -25,40 +27,99 @@ fn normalize_address(raw: &str) -> String {
     }
 }
 
+/// `_xlnm.Print_Area`, `_xlnm._FilterDatabase` and the other names Excel
+/// reserves for itself.
+fn is_builtin(name: &str) -> bool {
+    name.get(..6)
+        .is_some_and(|p| p.eq_ignore_ascii_case("_xlnm."))
+}
+
+/// Sheet part of a normalized `Sheet!A1` address.
+fn address_sheet(address: &str) -> Option<&str> {
+    address.split_once('!').map(|(sheet, _)| sheet)
+}
+
+/// One `read_named_ranges()` entry; `sheet` is the owner of a sheet-scoped
+/// name.
+fn name_to_py<'py>(
+    py: Python<'py>,
+    name: &str,
+    sheet: Option<&str>,
+    refers_to: String,
+) -> PyResult<Bound<'py, PyDict>> {
+    let d = PyDict::new(py);
+    d.set_item("name", name)?;
+    d.set_item("scope", if sheet.is_some() { "sheet" } else { "workbook" })?;
+    d.set_item("sheet", sheet)?;
+    d.set_item("refers_to", refers_to)?;
+    d.set_item("builtin", is_builtin(name))?;
+    Ok(d)
+}
+
 #[pymethods]
 impl UmyaBook {
+    /// Names visible from `sheet`: workbook-scoped names, names stored on
+    /// the sheet, and the sheet's built-in names (print area, print titles,
+    /// filter range) flagged `builtin: True`. Sheet-scoped entries carry
+    /// their owning `sheet`.
     pub fn read_named_ranges(&self, py: Python<'_>, sheet: &str) -> PyResult<PyObject> {
         let result = PyList::empty(py);
+        let sheet_names: Vec<String> = self
+            .book
+            .get_sheet_collection()
+            .iter()
+            .map(|s| s.get_name().to_string())
+            .collect();
 
-        // 1. Workbook-level defined names (no localSheetId).
+        // 1. Workbook-level defined names (no localSheetId). A built-in name
+        //    always belongs to a sheet: without localSheetId, take the one
+        //    its address points at.
         for dn in self.book.get_defined_names() {
             if dn.has_local_sheet_id() {
                 continue;
             }
-            let d = PyDict::new(py);
-            d.set_item("name", dn.get_name())?;
-            d.set_item("scope", "workbook")?;
-            d.set_item("refers_to", normalize_address(&dn.get_address()))?;
-            result.append(d)?;
+            let refers_to = normalize_address(&dn.get_address());
+            if is_builtin(dn.get_name()) {
+                if address_sheet(&refers_to) == Some(sheet) {
+                    result.append(name_to_py(py, dn.get_name(), Some(sheet), refers_to)?)?;
+                }
+                continue;
+            }
+            result.append(name_to_py(py, dn.get_name(), None, refers_to)?)?;
         }
 
         // 2. Worksheet-level defined names.
         //    umya puts workbook-scoped names that reference a sheet onto the
         //    worksheet, so we include both those and truly sheet-scoped ones.
-        //    We need the sheet index to detect localSheetId.
+        //    localSheetId (a sheet index) tells them apart.
         if let Some(ws) = self.book.get_sheet_by_name(sheet) {
+            let mut has_filter_database = false;
             for dn in ws.get_defined_names() {
-                let d = PyDict::new(py);
-                d.set_item("name", dn.get_name())?;
-                // If localSheetId is set, it's sheet-scoped;
-                // otherwise it's a workbook-scoped name stored on the sheet.
-                let scope = if dn.has_local_sheet_id() {
-                    "sheet"
-                } else {
-                    "workbook"
-                };
-                d.set_item("scope", scope)?;
-                d.set_item("refers_to", normalize_address(&dn.get_address()))?;
+                let name = dn.get_name();
+                let builtin = is_builtin(name);
+                let owner = dn
+                    .has_local_sheet_id()
+                    .then(|| {
+                        let id: u32 = dn.get_local_sheet_id().to_owned();
+                        sheet_names.get(id as usize)
+                    })
+                    .map(|owner| owner.map_or(sheet, String::as_str));
+                // Another sheet's print area must not show up here.
+                if builtin && owner.is_some_and(|o| o != sheet) {
+                    continue;
+                }
+                has_filter_database |= builtin && name[6..].eq_ignore_ascii_case("_FilterDatabase");
+                let owner = owner.or(builtin.then_some(sheet));
+                let refers_to = normalize_address(&dn.get_address());
+                result.append(name_to_py(py, name, owner, refers_to)?)?;
+            }
+
+            // umya keeps the autoFilter but not always its hidden
+            // `_FilterDatabase` name; report it from the filter itself.
+            if let Some(af) = ws.get_auto_filter().filter(|_| !has_filter_database) {
+                let range = af.get_range().get_range().replace('$', "");
+                let refers_to = format!("{sheet}!{range}");
+                let d = name_to_py(py, "_xlnm._FilterDatabase", Some(sheet), refers_to)?;
                 result.append(d)?;
             }
         }
@@ -102,7 +163,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         ws.add_defined_name(&name, &address).map_err(|e| {
             PyErr::new::<PyValueError, _>(format!("Failed to add named range: {e}"))
@@ -0,0 +1,115 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use std::collections::HashMap;
+
+use crate::errors;
+use crate::ooxml_util::sheet_format;
+
+use super::{UmyaBook, CAPABILITIES};
+
+/// `zeroHeight` edits for the sheets that set it, for `save()`.
+pub(super) fn zero_height_edits(
+    zero_height: &HashMap<String, bool>,
+) -> Vec<(String, Vec<sheet_format::SheetFormatEdit>)> {
+    zero_height
+        .iter()
+        .filter(|(_, &hidden)| hidden)
+        .map(|(sheet, _)| {
+            let edit = ("zeroHeight".to_string(), Some("1".to_string()));
+            (sheet.clone(), vec![edit])
+        })
+        .collect()
+}
+
+#[pymethods]
+impl UmyaBook {
+    /// `{"default_row_height", "default_col_width", "zero_height"}` for a
+    /// sheet. Sizes are None when the sheet doesn't set them.
+    pub fn read_sheet_format_properties(&self, py: Python<'_>, sheet: &str) -> PyResult<PyObject> {
+        let ws = self
+            .book
+            .get_sheet_by_name(sheet)
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
+        let props = ws.get_sheet_format_properties();
+        let positive = |v: f64| (v > 0.0).then_some(v);
+
+        let d = PyDict::new(py);
+        d.set_item(
+            "default_row_height",
+            positive(*props.get_default_row_height()),
+        )?;
+        d.set_item(
+            "default_col_width",
+            positive(*props.get_default_column_width()),
+        )?;
+        d.set_item(
+            "zero_height",
+            self.zero_height.get(sheet).copied().unwrap_or(false),
+        )?;
+        Ok(d.into())
+    }
+
+    /// Set sheet-wide row/column defaults, e.g.
+    /// `{"default_row_height": 20, "default_col_width": 12, "zero_height": True}`.
+    ///
+    /// `zero_height` hides every row that has no explicit height, the way
+    /// templates collapse unused rows. Keys that are not given keep their
+    /// current value.
+    pub fn set_sheet_format_properties(
+        &mut self,
+        sheet: &str,
+        props: &Bound<'_, PyDict>,
+    ) -> PyResult<()> {
+        let mut row_height = None;
+        let mut col_width = None;
+        let mut zero_height = None;
+        for (key, value) in props.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "default_row_height" => {
+                    let h: f64 = value.extract()?;
+                    if !(h > 0.0 && h <= 409.0) {
+                        return Err(PyErr::new::<PyValueError, _>(format!(
+                            "default_row_height must be in (0, 409], got {h}"
+                        )));
+                    }
+                    row_height = Some(h);
+                }
+                "default_col_width" => {
+                    let w: f64 = value.extract()?;
+                    if !(w > 0.0 && w <= 255.0) {
+                        return Err(PyErr::new::<PyValueError, _>(format!(
+                            "default_col_width must be in (0, 255], got {w}"
+                        )));
+                    }
+                    col_width = Some(w);
+                }
+                "zero_height" => zero_height = Some(value.extract::<bool>()?),
+                other => {
+                    return Err(PyErr::new::<PyValueError, _>(format!(
+                        "Unknown sheet format property: {other}"
+                    )))
+                }
+            }
+        }
+
+        let ws = self
+            .book
+            .get_sheet_by_name_mut(sheet)
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
+        let format_pr = ws.get_sheet_format_properties_mut();
+        if let Some(h) = row_height {
+            format_pr.set_default_row_height(h);
+            format_pr.set_custom_height(true);
+        }
+        if let Some(w) = col_width {
+            format_pr.set_default_column_width(w);
+        }
+        if let Some(z) = zero_height {
+            self.zero_height.insert(sheet.to_string(), z);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use std::fs::File;
+
+use zip::ZipArchive;
+
+use crate::ooxml_util::{
+    self, auto_filter::AutoFilterDef, calc_pr, calc_pr::CalcPrEdit, data_validations, sheet_format,
+};
+
+/// Details of the source workbook that umya does not model and drops on
+/// load, so a re-save can write them back.
+#[derive(Default)]
+pub(super) struct SourceParts {
+    /// The workbook's `<calcPr>`, as edits.
+    pub(super) calc_pr: Vec<CalcPrEdit>,
+    /// Sheets whose `<sheetFormatPr>` sets `zeroHeight`.
+    pub(super) zero_height: HashMap<String, bool>,
+    /// `operator` of each sheet's data validations, in list order.
+    pub(super) dv_operators: HashMap<String, Vec<Option<String>>>,
+    /// Each sheet's `<autoFilter>`, criteria included; umya keeps the range.
+    pub(super) auto_filters: HashMap<String, AutoFilterDef>,
+}
+
+/// Read the parts above from the file at `path`, opening the package once
+/// and each worksheet part once.
+///
+/// Best effort: umya has already parsed the package, and a detail that
+/// cannot be recovered here only loses what umya would have lost anyway.
+pub(super) fn read_source_parts(path: &str) -> SourceParts {
+    let mut out = SourceParts::default();
+    let Some(mut zip) = File::open(path).ok().and_then(|f| ZipArchive::new(f).ok()) else {
+        return out;
+    };
+    let Ok(workbook_xml) = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml") else {
+        return out;
+    };
+    out.calc_pr = calc_pr::read_calc_pr(&workbook_xml)
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(k, v)| (k, Some(v)))
+        .collect();
+
+    let paths = ooxml_util::zip_read_to_string(&mut zip, "xl/_rels/workbook.xml.rels")
+        .and_then(|rels| ooxml_util::sheet_part_paths(&workbook_xml, &rels))
+        .unwrap_or_default();
+    for (sheet, part) in paths {
+        let Ok(xml) = ooxml_util::zip_read_to_string(&mut zip, &part) else {
+            continue;
+        };
+        let zero_height = sheet_format::read_sheet_format_pr(&xml)
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+            .iter()
+            .any(|(k, v)| k == "zeroHeight" && matches!(v.as_str(), "1" | "true"));
+        if zero_height {
+            out.zero_height.insert(sheet.clone(), true);
+        }
+        if let Ok(operators) = data_validations::read_dv_operators(&xml) {
+            out.dv_operators.insert(sheet.clone(), operators);
+        }
+        if let Some(def) = ooxml_util::auto_filter::read_auto_filter(&xml)
+            .ok()
+            .flatten()
+        {
+            out.auto_filters.insert(sheet, def);
+        }
+    }
+    out
+}
@@ -4,7 +4,9 @@ use pyo3::types::{PyDict, PyList};
 
 use umya_spreadsheet::structs::{Table, TableColumn, TableStyleInfo};
 
-use super::UmyaBook;
+use crate::errors;
+
+use super::{UmyaBook, CAPABILITIES};
 
 /// Convert a Table's area coordinates to a range string like "A1:D10".
 fn area_to_ref(table: &Table) -> String {
@@ -30,7 +32,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         let result = PyList::empty(py);
 
@@ -154,7 +156,7 @@ impl UmyaBook {
         let ws = self
             .book
             .get_sheet_by_name_mut(sheet)
-            .ok_or_else(|| PyErr::new::<PyValueError, _>(format!("Unknown sheet: {sheet}")))?;
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
 
         ws.add_table(table);
 
@@ -1,5 +1,10 @@
 use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
 
+use umya_spreadsheet::structs::{Color, Theme};
+
+use crate::cell_ref;
+use crate::color::{self, ColorBase, ColorSpec};
+
 pub(super) fn looks_like_date_format(code: &str) -> bool {
     // Heuristic: date formats typically include year + day tokens.
     let lc = code.to_ascii_lowercase();
@@ -13,42 +18,85 @@ pub(super) fn excel_serial_to_naive_datetime(serial: f64) -> Option<NaiveDateTim
     if f < 60.0 {
         f += 1.0;
     }
-    let total_ms = (f * 86_400_000.0).round() as i64;
-    epoch.checked_add_signed(Duration::milliseconds(total_ms))
+    // Round the day fraction to the microsecond the write side stores; with
+    // the whole days split off this is exact for dates through 2079.
+    let days = f.floor();
+    let us = ((f - days) * 86_400_000_000.0).round() as i64;
+    epoch
+        .checked_add_signed(Duration::try_days(days as i64)?)?
+        .checked_add_signed(Duration::microseconds(us))
 }
 
 pub(super) fn naive_datetime_to_excel_serial(dt: NaiveDateTime) -> Option<f64> {
     let epoch = NaiveDate::from_ymd_opt(1899, 12, 30)?.and_time(NaiveTime::MIN);
     let delta = dt - epoch;
-    let total_ms = delta.num_milliseconds();
-    Some(total_ms as f64 / 86_400_000.0)
+    let days = delta.num_days();
+    let us = (delta - Duration::days(days)).num_microseconds()?;
+    let mut serial = days as f64 + us as f64 / 86_400_000_000.0;
+    // Undo the leap-year bug adjustment `excel_serial_to_naive_datetime`
+    // applies: days before 1900-03-01 sit one serial lower.
+    if serial < 61.0 {
+        serial -= 1.0;
+    }
+    Some(serial)
+}
+
+/// Fraction of a day for a time of day (Excel's time-only serial).
+pub(super) fn naive_time_to_excel_serial(t: NaiveTime) -> f64 {
+    let us = (t - NaiveTime::MIN).num_microseconds().unwrap_or(0);
+    us as f64 / 86_400_000_000.0
 }
 
 // ---------------------------------------------------------------------------
-// Color helpers: ARGB <-> hex
+// Color helpers
 // ---------------------------------------------------------------------------
 
-/// Convert ARGB "FFRRGGBB" or "RRGGBB" to "#RRGGBB".
-pub(super) fn argb_to_hex(argb: &str) -> String {
-    let s = argb.trim();
-    if s.len() == 8 {
-        // "FFRRGGBB" -> "#RRGGBB"
-        format!("#{}", &s[2..])
-    } else if s.len() == 6 {
-        format!("#{s}")
-    } else if s.starts_with('#') {
-        s.to_string()
-    } else {
-        format!("#{s}")
-    }
-}
-
 /// Convert "#RRGGBB" to "FFRRGGBB" ARGB.
 pub(super) fn hex_to_argb(hex: &str) -> String {
     let s = hex.strip_prefix('#').unwrap_or(hex);
     format!("FF{s}")
 }
 
+/// "#RRGGBB" for a umya color, resolving theme/indexed colors and applying
+/// the tint. None when the color is unset.
+pub(super) fn resolve_color(c: &Color, theme: &Theme) -> Option<String> {
+    let argb = c.get_argb_with_theme(theme);
+    let rgb = color::parse_hex(&argb)?;
+    Some(color::to_hex(color::apply_tint(rgb, *c.get_tint())))
+}
+
+/// Set a umya color from `"#RRGGBB"`, `"theme:N[:tint]"` or `"indexed:N"`.
+/// Other strings are written as ARGB unchanged, as before.
+pub(super) fn set_color(c: &mut Color, spec: &str) {
+    match ColorSpec::parse(spec) {
+        Ok(parsed) => set_color_spec(c, &parsed),
+        Err(_) => {
+            c.set_argb(hex_to_argb(spec));
+        }
+    }
+}
+
+/// Set a umya color from a parsed spec.
+pub(super) fn set_color_spec(c: &mut Color, spec: &ColorSpec) {
+    match spec.base {
+        ColorBase::Rgb(rgb) => {
+            c.set_argb(hex_to_argb(&color::to_hex(rgb)));
+        }
+        ColorBase::Theme(idx) => {
+            c.set_theme_index(idx);
+        }
+        ColorBase::Indexed(idx) => {
+            c.set_indexed(idx);
+        }
+        ColorBase::Auto => {
+            c.set_argb("FF000000");
+        }
+    }
+    if spec.tint != 0.0 {
+        c.set_tint(spec.tint);
+    }
+}
+
 /// Map umya border style string to our canonical style names.
 pub(super) fn umya_border_style_to_str(style: &str) -> &'static str {
     match style.to_ascii_lowercase().as_str() {
@@ -65,21 +113,17 @@ pub(super) fn umya_border_style_to_str(style: &str) -> &'static str {
         "dashdotdot" => "dashDotDot",
         "mediumdashdotdot" => "mediumDashDotDot",
         "slantdashdot" => "slantDashDot",
-        _ => "none",
+        "none" | "" => "none",
+        other => {
+            log::debug!("Unknown border style '{other}' read as none");
+            "none"
+        }
     }
 }
 
+/// 1-based index of column letters, as umya numbers columns; at most XFD.
 pub(super) fn col_letter_to_u32(col_str: &str) -> Result<u32, String> {
-    let mut col: u32 = 0;
-    for ch in col_str.chars() {
-        if !ch.is_ascii_alphabetic() {
-            return Err(format!("Invalid column string: {col_str}"));
-        }
-        let uc = ch.to_ascii_uppercase() as u8;
-        col = col * 26 + (uc - b'A' + 1) as u32;
-    }
-    if col == 0 {
-        return Err(format!("Invalid column string: {col_str}"));
-    }
-    Ok(col)
+    cell_ref::letters_to_col(col_str)
+        .map(|col| col + 1)
+        .ok_or_else(|| format!("Invalid column string: {col_str}"))
 }
@@ -2,37 +2,22 @@ use pyo3::prelude::*;
 use pyo3::types::PyDict;
 use pyo3::IntoPyObject;
 
-#[cfg(any(feature = "calamine", feature = "rust_xlsxwriter", feature = "umya"))]
-use chrono::{NaiveDate, NaiveDateTime};
+use crate::cell_ref::CellRef;
 
-pub fn a1_to_row_col(a1: &str) -> Result<(u32, u32), String> {
-    let mut col: u32 = 0;
-    let mut row_digits = String::new();
-
-    for ch in a1.chars() {
-        if ch.is_ascii_alphabetic() {
-            let uc = ch.to_ascii_uppercase() as u8;
-            let val = (uc - b'A' + 1) as u32;
-            col = col * 26 + val;
-        } else if ch.is_ascii_digit() {
-            row_digits.push(ch);
-        } else {
-            return Err(format!("Invalid cell reference: {a1}"));
-        }
-    }
-
-    if col == 0 || row_digits.is_empty() {
-        return Err(format!("Invalid cell reference: {a1}"));
-    }
+#[cfg(any(
+    feature = "calamine",
+    feature = "rust_xlsxwriter",
+    feature = "umya",
+    feature = "wolfxl"
+))]
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 
-    let row_1: u32 = row_digits
-        .parse()
-        .map_err(|_| format!("Invalid cell reference: {a1}"))?;
-    if row_1 == 0 {
-        return Err(format!("Invalid cell reference: {a1}"));
-    }
+#[cfg(any(feature = "calamine", feature = "wolfxl"))]
+use chrono::Duration;
 
-    Ok((row_1 - 1, col - 1))
+/// Parse an A1 cell reference (`$` markers allowed) into 0-based (row, col).
+pub fn a1_to_row_col(a1: &str) -> Result<(u32, u32), String> {
+    CellRef::parse(a1).map(|c| (c.row, c.col))
 }
 
 pub(crate) fn cell_blank(py: Python<'_>) -> PyResult<PyObject> {
@@ -53,15 +38,51 @@ pub(crate) fn cell_with_value<'py, V: IntoPyObject<'py>>(
     Ok(d.into())
 }
 
-#[cfg(any(feature = "calamine", feature = "rust_xlsxwriter", feature = "umya"))]
+#[cfg(any(
+    feature = "calamine",
+    feature = "rust_xlsxwriter",
+    feature = "umya",
+    feature = "wolfxl"
+))]
 pub(crate) fn parse_iso_date(s: &str) -> Option<NaiveDate> {
     NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
 }
 
-#[cfg(any(feature = "calamine", feature = "rust_xlsxwriter", feature = "umya"))]
+#[cfg(any(
+    feature = "calamine",
+    feature = "rust_xlsxwriter",
+    feature = "umya",
+    feature = "wolfxl"
+))]
 pub(crate) fn parse_iso_datetime(s: &str) -> Option<NaiveDateTime> {
     let raw = s.trim_end_matches('Z');
     NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S")
         .ok()
         .or_else(|| NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S%.f").ok())
 }
+
+#[cfg(any(feature = "rust_xlsxwriter", feature = "umya", feature = "wolfxl"))]
+pub(crate) fn parse_iso_time(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S")
+        .ok()
+        .or_else(|| NaiveTime::parse_from_str(s, "%H:%M:%S%.f").ok())
+}
+
+/// Convert an Excel serial to a datetime in the workbook's date system.
+///
+/// The 1900 system keeps Excel's phantom 1900-02-29 (serials below 60 shift by
+/// a day); the 1904 system counts from 1904-01-01 with no such quirk.
+#[cfg(any(feature = "calamine", feature = "wolfxl"))]
+pub(crate) fn excel_serial_to_datetime(serial: f64, date1904: bool) -> Option<NaiveDateTime> {
+    let (epoch, days) = if date1904 {
+        (NaiveDate::from_ymd_opt(1904, 1, 1)?, serial)
+    } else if serial < 60.0 {
+        (NaiveDate::from_ymd_opt(1899, 12, 31)?, serial)
+    } else {
+        (NaiveDate::from_ymd_opt(1899, 12, 30)?, serial)
+    };
+    let total_ms = (days * 86_400_000.0).round() as i64;
+    epoch
+        .and_time(NaiveTime::MIN)
+        .checked_add_signed(Duration::milliseconds(total_ms))
+}
@@ -0,0 +1,360 @@
+//! Text redaction for publishing workbooks (`anonymize()`).
+//!
+//! Rules rewrite the text of shared strings (`xl/sharedStrings.xml`) and
+//! inline strings (`<is>` in worksheets). Formulas, cached formula results,
+//! numbers, styles and every other part are copied unchanged with the same
+//! raw ZIP copy as `XlsxPatcher.save()`. Rich-text runs are redacted run by
+//! run, so a match split across runs is not seen.
+
+use std::collections::HashMap;
+use std::fs::File;
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use quick_xml::events::{BytesText, Event};
+use quick_xml::{Reader as XmlReader, Writer as XmlWriter};
+use regex::{Captures, Regex, RegexBuilder};
+use zip::ZipArchive;
+
+use crate::errors;
+use crate::ooxml_util;
+
+use super::{rewrite_zip, CAPABILITIES};
+
+const SHARED_STRINGS: &str = "xl/sharedStrings.xml";
+
+/// What a rule puts in place of each match.
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    /// Replacement text; `$1` / `${name}` refer to capture groups.
+    Replace(String),
+    /// `prefix` plus a salted hash of the match, so equal inputs map to
+    /// equal pseudonyms across the workbook.
+    Hash { prefix: String },
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: Regex,
+    action: Action,
+}
+
+/// The compiled rules plus the hash salt.
+#[derive(Debug, Clone)]
+pub(crate) struct Redactor {
+    rules: Vec<Rule>,
+    salt: String,
+}
+
+impl Redactor {
+    /// Apply every rule in order; returns the new text and the match count.
+    fn redact(&self, text: &str) -> (String, usize) {
+        let mut out = text.to_string();
+        let mut count = 0usize;
+        for rule in &self.rules {
+            let n = rule.pattern.find_iter(&out).count();
+            if n == 0 {
+                continue;
+            }
+            count += n;
+            out = match &rule.action {
+                Action::Replace(with) => rule.pattern.replace_all(&out, with.as_str()).into_owned(),
+                Action::Hash { prefix } => rule
+                    .pattern
+                    .replace_all(&out, |caps: &Captures<'_>| {
+                        format!("{prefix}{}", pseudonym(&self.salt, &caps[0]))
+                    })
+                    .into_owned(),
+            };
+        }
+        (out, count)
+    }
+}
+
+/// 8 hex digits of FNV-1a over `salt` and `text`; stable across runs and
+/// platforms so redacted corpora can be regenerated.
+fn pseudonym(salt: &str, text: &str) -> String {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in salt.bytes().chain([0u8]).chain(text.bytes()) {
+        h = (h ^ b as u64).wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:08x}", (h >> 32) ^ (h & 0xFFFF_FFFF))
+}
+
+/// Counts from one redaction pass.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct RedactStats {
+    /// Text nodes that changed.
+    pub strings: usize,
+    /// Rule matches replaced.
+    pub replacements: usize,
+}
+
+/// Redact the text of every `<t>` element in a shared-strings or worksheet
+/// part. Returns None when nothing matched.
+pub(crate) fn redact_part(
+    xml: &str,
+    redactor: &Redactor,
+) -> Result<Option<(String, RedactStats)>, String> {
+    let mut reader = XmlReader::from_str(xml);
+    reader.config_mut().trim_text(false);
+    let mut writer = XmlWriter::new(Vec::new());
+    let mut stats = RedactStats::default();
+    let mut in_t = false;
+
+    loop {
+        let event = match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(Event::Start(e)) => {
+                in_t = e.local_name().as_ref() == b"t";
+                Event::Start(e)
+            }
+            Ok(Event::End(e)) => {
+                in_t = false;
+                Event::End(e)
+            }
+            Ok(Event::Text(e)) if in_t => {
+                let text = e
+                    .unescape()
+                    .map_err(|err| format!("XML text decode error: {err}"))?;
+                let (redacted, n) = redactor.redact(&text);
+                if n == 0 {
+                    Event::Text(e)
+                } else {
+                    stats.strings += 1;
+                    stats.replacements += n;
+                    Event::Text(BytesText::new(&redacted).into_owned())
+                }
+            }
+            Ok(e) => e,
+            Err(e) => return Err(format!("XML parse error: {e}")),
+        };
+        writer
+            .write_event(event)
+            .map_err(|e| format!("XML write error: {e}"))?;
+    }
+
+    if stats.strings == 0 {
+        return Ok(None);
+    }
+    let xml = String::from_utf8(writer.into_inner()).map_err(|e| format!("XML not UTF-8: {e}"))?;
+    Ok(Some((xml, stats)))
+}
+
+/// Parse one rule dict.
+fn parse_rule<'py>(i: usize, value: &Bound<'py, PyAny>) -> PyResult<Rule> {
+    let err = |msg: String| PyErr::new::<PyValueError, _>(format!("anonymize rule {i}: {msg}"));
+    let d = value
+        .downcast::<PyDict>()
+        .map_err(|_| err("must be a dict".to_string()))?;
+    for key in d.keys() {
+        let key: String = key.extract()?;
+        if !matches!(
+            key.as_str(),
+            "pattern" | "literal" | "ignore_case" | "replace" | "hash" | "prefix"
+        ) {
+            return Err(err(format!("unknown key '{key}'")));
+        }
+    }
+    let get = |key: &str| -> PyResult<Option<Bound<'py, PyAny>>> {
+        Ok(d.get_item(key)?.filter(|v| !v.is_none()))
+    };
+    let string = |key: &str| -> PyResult<Option<String>> {
+        get(key)?
+            .map(|v| {
+                v.extract()
+                    .map_err(|_| err(format!("'{key}' must be a str")))
+            })
+            .transpose()
+    };
+
+    let source = match (string("pattern")?, string("literal")?) {
+        (Some(p), None) => p,
+        (None, Some(l)) => regex::escape(&l),
+        _ => {
+            return Err(err(
+                "needs exactly one of 'pattern' or 'literal'".to_string()
+            ))
+        }
+    };
+    let ignore_case = match get("ignore_case")? {
+        Some(v) => v.extract::<bool>()?,
+        None => false,
+    };
+    let pattern = RegexBuilder::new(&source)
+        .case_insensitive(ignore_case)
+        .build()
+        .map_err(|e| err(format!("invalid pattern: {e}")))?;
+    if pattern.is_match("") {
+        return Err(err("pattern must not match the empty string".to_string()));
+    }
+
+    let hash = match get("hash")? {
+        Some(v) => v.extract::<bool>()?,
+        None => false,
+    };
+    let action = match (string("replace")?, hash) {
+        (Some(with), false) => Action::Replace(with),
+        (None, true) => Action::Hash {
+            prefix: string("prefix")?.unwrap_or_default(),
+        },
+        (None, false) => Action::Replace(String::new()),
+        (Some(_), true) => return Err(err("'replace' and 'hash' are exclusive".to_string())),
+    };
+    Ok(Rule { pattern, action })
+}
+
+/// Copy `src` to `dst` with text redacted by `rules`.
+///
+/// Each rule is a dict with `pattern` (a regex) or `literal` (plain text),
+/// optional `ignore_case`, and either `replace` (text, `$1` for groups;
+/// default empty) or `hash=True` with an optional `prefix`, which replaces
+/// each match with `prefix` + 8 hex digits derived from the match and
+/// `salt`. Rules apply in order. Only shared and inline strings change;
+/// structure, formulas and formats are kept. Returns `{"strings",
+/// "replacements", "parts"}`: text nodes changed, matches replaced and parts
+/// rewritten.
+#[pyfunction]
+#[pyo3(signature = (src, dst, rules, salt=None))]
+pub(crate) fn anonymize(
+    py: Python<'_>,
+    src: &str,
+    dst: &str,
+    rules: Vec<Bound<'_, PyAny>>,
+    salt: Option<String>,
+) -> PyResult<PyObject> {
+    let redactor = Redactor {
+        rules: rules
+            .iter()
+            .enumerate()
+            .map(|(i, r)| parse_rule(i, r))
+            .collect::<PyResult<_>>()?,
+        salt: salt.unwrap_or_default(),
+    };
+    if redactor.rules.is_empty() {
+        return Err(PyErr::new::<PyValueError, _>(
+            "anonymize() needs at least one rule",
+        ));
+    }
+
+    let f = File::open(src).map_err(|e| {
+        errors::file_format(
+            CAPABILITIES.backend,
+            src,
+            format!("Cannot open '{src}': {e}"),
+        )
+    })?;
+    let mut zip = ZipArchive::new(f).map_err(|e| {
+        errors::file_format(CAPABILITIES.backend, src, format!("Not a valid ZIP: {e}"))
+    })?;
+    let wb_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml")?;
+    let rels_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/_rels/workbook.xml.rels")?;
+
+    let mut parts: Vec<(String, String)> = Vec::new();
+    if let Some(sst) = ooxml_util::zip_read_to_string_opt(&mut zip, SHARED_STRINGS)? {
+        parts.push((SHARED_STRINGS.to_string(), sst));
+    }
+    for (_, part) in ooxml_util::sheet_part_paths(&wb_xml, &rels_xml)? {
+        let xml = ooxml_util::zip_read_to_string(&mut zip, &part)?;
+        // Only inline-string cells carry text in a worksheet.
+        if xml.contains("inlineStr") {
+            parts.push((part, xml));
+        }
+    }
+    drop(zip);
+
+    let mut file_patches: HashMap<String, Vec<u8>> = HashMap::new();
+    let mut total = RedactStats::default();
+    for (part, xml) in &parts {
+        let redacted = py
+            .allow_threads(|| redact_part(xml, &redactor))
+            .map_err(PyErr::new::<PyIOError, _>)?;
+        if let Some((xml, stats)) = redacted {
+            total.strings += stats.strings;
+            total.replacements += stats.replacements;
+            file_patches.insert(part.clone(), xml.into_bytes());
+        }
+    }
+    rewrite_zip(src, dst, &file_patches)?;
+
+    let out = PyDict::new(py);
+    out.set_item("strings", total.strings)?;
+    out.set_item("replacements", total.replacements)?;
+    out.set_item("parts", file_patches.len())?;
+    Ok(out.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor(rules: Vec<(&str, Action)>) -> Redactor {
+        Redactor {
+            rules: rules
+                .into_iter()
+                .map(|(p, action)| Rule {
+                    pattern: Regex::new(p).unwrap(),
+                    action,
+                })
+                .collect(),
+            salt: "s".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_redact_shared_strings() {
+        let xml = concat!(
+            r#"<sst count="3"><si><t>Contact alice@example.com</t></si>"#,
+            r#"<si><r><rPr><b/></rPr><t xml:space="preserve">Acct 12345678 </t></r>"#,
+            r#"<r><t>ok</t></r></si><si><t>nothing &amp; here</t></si></sst>"#
+        );
+        let r = redactor(vec![
+            (r"[\w.]+@[\w.]+", Action::Replace("<email>".to_string())),
+            (
+                r"\d{8}",
+                Action::Hash {
+                    prefix: "ACCT-".to_string(),
+                },
+            ),
+        ]);
+        let (out, stats) = redact_part(xml, &r).unwrap().unwrap();
+        assert_eq!(
+            stats,
+            RedactStats {
+                strings: 2,
+                replacements: 2
+            }
+        );
+        assert!(out.contains("<t>Contact &lt;email&gt;</t>"));
+        let acct = format!("Acct ACCT-{} ", pseudonym("s", "12345678"));
+        assert!(out.contains(&format!(r#"<t xml:space="preserve">{acct}</t>"#)));
+        assert!(out.contains("<rPr><b/></rPr>"));
+        assert!(out.contains("<t>nothing &amp; here</t>"));
+    }
+
+    #[test]
+    fn test_redact_inline_strings_only() {
+        let xml = concat!(
+            r#"<worksheet><sheetData><row r="1">"#,
+            r#"<c r="A1" t="inlineStr"><is><t>Bob Smith</t></is></c>"#,
+            r#"<c r="B1" t="str"><f>"Bob"&amp;A1</f><v>Bob</v></c>"#,
+            r#"</row></sheetData></worksheet>"#
+        );
+        let r = redactor(vec![("Bob", Action::Replace("X".to_string()))]);
+        let (out, stats) = redact_part(xml, &r).unwrap().unwrap();
+        assert_eq!(stats.strings, 1);
+        assert!(out.contains("<is><t>X Smith</t></is>"));
+        assert!(out.contains(r#"<f>"Bob"&amp;A1</f><v>Bob</v>"#));
+        assert!(redact_part("<sst><si><t>none</t></si></sst>", &r)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_pseudonyms_are_stable_and_salted() {
+        assert_eq!(pseudonym("a", "x"), pseudonym("a", "x"));
+        assert_ne!(pseudonym("a", "x"), pseudonym("b", "x"));
+        assert_eq!(pseudonym("a", "x").len(), 8);
+    }
+}
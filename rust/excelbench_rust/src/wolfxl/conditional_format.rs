@@ -0,0 +1,208 @@
+//! Conditional-format range extension (`XlsxPatcher.extend_conditional_format`).
+//!
+//! Appending rows to a formatted report leaves the new rows outside the
+//! template's rules. Every `<conditionalFormatting>` block whose `sqref`
+//! covers the source range gets the new range added, and so does the Excel
+//! 2010 `<x14:conditionalFormatting>` twin that data bars and icon sets keep
+//! in the worksheet `extLst`. Rules are not copied: a block's formulas are
+//! relative to the top-left of its first range, so growing `sqref` applies
+//! them to the new cells exactly as Excel's fill-down would.
+
+use quick_xml::events::{BytesStart, BytesText, Event};
+use quick_xml::Reader as XmlReader;
+use quick_xml::Writer as XmlWriter;
+
+use crate::cell_ref::{CellRef, RangeKind, RangeRef};
+use crate::ooxml_util::attr_value;
+
+/// One queued `extend_conditional_format` call.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CfExtension {
+    pub sheet: String,
+    pub source: RangeRef,
+    pub target: RangeRef,
+}
+
+/// True when `outer` contains every cell of `inner`.
+fn covers(outer: &RangeRef, inner: &RangeRef) -> bool {
+    let (r0, c0, r1, c1) = inner.bounds();
+    outer.contains(r0, c0) && outer.contains(r1, c1)
+}
+
+/// `part` grown to include `target` when the two form one rectangle that
+/// keeps `part`'s top-left cell (so relative rule formulas stay anchored).
+fn merged(part: &RangeRef, target: &RangeRef) -> Option<RangeRef> {
+    if part.kind != RangeKind::Cells || target.kind != RangeKind::Cells {
+        return None;
+    }
+    let (pr0, pc0, pr1, pc1) = part.bounds();
+    let (tr0, tc0, tr1, tc1) = target.bounds();
+    let same_cols = (pc0, pc1) == (tc0, tc1) && tr0 >= pr0 && tr0 <= pr1 + 1;
+    let same_rows = (pr0, pr1) == (tr0, tr1) && tc0 >= pc0 && tc0 <= pc1 + 1;
+    if !same_cols && !same_rows {
+        return None;
+    }
+    Some(RangeRef {
+        sheet: None,
+        start: CellRef::new(pr0, pc0),
+        end: CellRef::new(pr1.max(tr1), pc1.max(tc1)),
+        kind: RangeKind::Cells,
+    })
+}
+
+/// The extended `sqref`, or None when no range in it covers `source`.
+///
+/// `target` is merged into an adjacent range when possible (`A2:A10` plus
+/// `A11:A20` becomes `A2:A20`) and appended otherwise. Ranges that cannot be
+/// parsed are kept as written.
+pub(crate) fn extend_sqref(sqref: &str, source: &RangeRef, target: &RangeRef) -> Option<String> {
+    let parts: Vec<&str> = sqref.split_whitespace().collect();
+    let ranges: Vec<Option<RangeRef>> = parts.iter().map(|p| RangeRef::parse(p).ok()).collect();
+    if !ranges.iter().flatten().any(|r| covers(r, source)) {
+        return None;
+    }
+    if ranges.iter().flatten().any(|r| covers(r, target)) {
+        return Some(parts.join(" "));
+    }
+
+    let mut out: Vec<String> = parts.iter().map(|p| p.to_string()).collect();
+    let merge = ranges
+        .iter()
+        .enumerate()
+        .find_map(|(i, r)| Some((i, merged(r.as_ref()?, target)?)));
+    match merge {
+        Some((i, range)) => out[i] = range.to_a1(),
+        None => out.push(target.to_a1()),
+    }
+    Some(out.join(" "))
+}
+
+/// Copy of a `<conditionalFormatting>` tag with `sqref` replaced.
+fn with_sqref(e: &BytesStart<'_>, sqref: &str) -> Result<BytesStart<'static>, String> {
+    let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+    let mut elem = BytesStart::new(name);
+    for a in e.attributes().with_checks(false) {
+        let a = a.map_err(|err| format!("XML attr parse error: {err}"))?;
+        if a.key.as_ref() != b"sqref" {
+            elem.push_attribute(a);
+        }
+    }
+    elem.push_attribute(("sqref", sqref));
+    Ok(elem)
+}
+
+/// Extend every conditional-format block of a worksheet that covers
+/// `source` to also cover `target`. Returns the patched XML and the number
+/// of blocks extended (both the classic and the x14 form count).
+pub(crate) fn extend_conditional_formats(
+    sheet_xml: &str,
+    source: &RangeRef,
+    target: &RangeRef,
+) -> Result<(String, usize), String> {
+    let mut reader = XmlReader::from_str(sheet_xml);
+    reader.config_mut().trim_text(false);
+    let mut writer = XmlWriter::new(Vec::new());
+    let mut extended = 0;
+    // Inside `<x14:conditionalFormatting>` / its `<xm:sqref>`.
+    let mut in_x14 = false;
+    let mut in_sqref = false;
+
+    loop {
+        let event = match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"conditionalFormatting" => {
+                // The x14 form carries its ranges in an `<xm:sqref>` child.
+                let sqref = attr_value(&e, b"sqref");
+                in_x14 = sqref.is_none();
+                match sqref.and_then(|s| extend_sqref(&s, source, target)) {
+                    Some(sqref) => {
+                        extended += 1;
+                        Event::Start(with_sqref(&e, &sqref)?)
+                    }
+                    None => Event::Start(e),
+                }
+            }
+            Ok(Event::Start(e)) if in_x14 && e.local_name().as_ref() == b"sqref" => {
+                in_sqref = true;
+                Event::Start(e)
+            }
+            Ok(Event::Text(t)) if in_sqref => {
+                let text = t.unescape().map_err(|e| format!("Bad sqref text: {e}"))?;
+                match extend_sqref(&text, source, target) {
+                    Some(sqref) => {
+                        extended += 1;
+                        Event::Text(BytesText::new(&sqref).into_owned())
+                    }
+                    None => Event::Text(t),
+                }
+            }
+            Ok(Event::End(e)) => {
+                match e.local_name().as_ref() {
+                    b"sqref" => in_sqref = false,
+                    b"conditionalFormatting" => in_x14 = false,
+                    _ => {}
+                }
+                Event::End(e)
+            }
+            Ok(e) => e,
+            Err(e) => return Err(format!("XML parse error: {e}")),
+        };
+        writer
+            .write_event(event)
+            .map_err(|e| format!("XML write error: {e}"))?;
+    }
+
+    let xml = String::from_utf8(writer.into_inner())
+        .map_err(|e| format!("Worksheet XML not UTF-8: {e}"))?;
+    Ok((xml, extended))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(s: &str) -> RangeRef {
+        RangeRef::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_extend_sqref_merges_or_appends() {
+        let (src, new) = (range("A2:A10"), range("A11:A20"));
+        assert_eq!(
+            extend_sqref("A2:A10", &src, &new).as_deref(),
+            Some("A2:A20")
+        );
+        assert_eq!(
+            extend_sqref("A1:C10 E1", &range("B3:B4"), &range("A15:C15")).as_deref(),
+            Some("A1:C10 E1 A15:C15")
+        );
+        assert_eq!(
+            extend_sqref("A2:A30", &src, &new).as_deref(),
+            Some("A2:A30")
+        );
+        assert_eq!(extend_sqref("B2:B10", &src, &new), None);
+    }
+
+    #[test]
+    fn test_extend_conditional_formats_classic_and_x14() {
+        let xml = concat!(
+            r#"<worksheet xmlns:x14="x14" xmlns:xm="xm"><sheetData/>"#,
+            r#"<conditionalFormatting sqref="B2:B5"><cfRule type="dataBar" priority="1">"#,
+            r#"<dataBar><cfvo type="min"/><cfvo type="max"/><color rgb="FF638EC6"/></dataBar>"#,
+            r#"</cfRule></conditionalFormatting>"#,
+            r#"<conditionalFormatting sqref="D2:D5"><cfRule type="cellIs" priority="2"/>"#,
+            r#"</conditionalFormatting><extLst><ext uri="{78C0}"><x14:conditionalFormattings>"#,
+            r#"<x14:conditionalFormatting><x14:cfRule type="dataBar"/>"#,
+            r#"<xm:sqref>B2:B5</xm:sqref></x14:conditionalFormatting>"#,
+            r#"</x14:conditionalFormattings></ext></extLst></worksheet>"#
+        );
+        let (out, n) = extend_conditional_formats(xml, &range("B2:B5"), &range("B6:B9")).unwrap();
+        assert_eq!(n, 2);
+        assert!(out.contains(r#"<conditionalFormatting sqref="B2:B9">"#));
+        assert!(out.contains(r#"<conditionalFormatting sqref="D2:D5">"#));
+        assert!(out.contains("<xm:sqref>B2:B9</xm:sqref>"));
+
+        let (same, n) = extend_conditional_formats(xml, &range("F1"), &range("F2")).unwrap();
+        assert_eq!((same.as_str(), n), (xml, 0));
+    }
+}
@@ -0,0 +1,277 @@
+//! Hyperlink attribute patching (`patch_hyperlink_attrs()`).
+//!
+//! Sets or removes the `tooltip` and `display` attributes of existing
+//! `<hyperlink>` elements. Only the worksheets named in the request are
+//! rewritten; every other part is copied as in `XlsxPatcher.save()`, so any
+//! writer's output (or an external file) can be patched after the fact.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader as XmlReader, Writer as XmlWriter};
+use zip::ZipArchive;
+
+use crate::cell_ref::RangeRef;
+use crate::errors;
+use crate::ooxml_util;
+
+use super::{replace_file, rewrite_zip, CAPABILITIES};
+
+/// Attribute edits for one hyperlink: `Some(Some(v))` sets, `Some(None)`
+/// removes, `None` leaves the attribute alone.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct HyperlinkAttrs {
+    pub tooltip: Option<Option<String>>,
+    pub display: Option<Option<String>>,
+}
+
+impl HyperlinkAttrs {
+    fn edits(&self) -> [(&'static str, &Option<Option<String>>); 2] {
+        [("tooltip", &self.tooltip), ("display", &self.display)]
+    }
+}
+
+/// Canonical `ref` for matching: upper case without `$` markers.
+fn normalize_ref(r: &str) -> String {
+    r.replace('$', "").to_ascii_uppercase()
+}
+
+fn edited_hyperlink(
+    e: &BytesStart<'_>,
+    attrs: &HyperlinkAttrs,
+) -> Result<BytesStart<'static>, String> {
+    let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+    let mut elem = BytesStart::new(name);
+    let edits = attrs.edits();
+    for a in e.attributes().with_checks(false) {
+        let a = a.map_err(|err| format!("XML attr parse error: {err}"))?;
+        let edited = edits
+            .iter()
+            .any(|(key, edit)| edit.is_some() && key.as_bytes() == a.key.as_ref());
+        if !edited {
+            elem.push_attribute(a);
+        }
+    }
+    for (key, edit) in edits {
+        if let Some(Some(value)) = edit {
+            elem.push_attribute((key, value.as_str()));
+        }
+    }
+    Ok(elem)
+}
+
+/// The edit for a `<hyperlink>`, recording its `ref` in `found`.
+fn matching_edit<'a>(
+    e: &BytesStart<'_>,
+    attrs: &'a HashMap<String, HyperlinkAttrs>,
+    found: &mut HashSet<String>,
+) -> Option<&'a HyperlinkAttrs> {
+    let key = normalize_ref(&ooxml_util::attr_value(e, b"ref")?);
+    let (key, edit) = attrs.get_key_value(&key)?;
+    found.insert(key.clone());
+    Some(edit)
+}
+
+/// Apply `attrs` (keyed by normalized `ref`) to a worksheet's hyperlinks.
+/// Returns the patched XML and the refs that were found.
+pub(crate) fn patch_sheet_hyperlinks(
+    sheet_xml: &str,
+    attrs: &HashMap<String, HyperlinkAttrs>,
+) -> Result<(String, HashSet<String>), String> {
+    let mut reader = XmlReader::from_str(sheet_xml);
+    reader.config_mut().trim_text(false);
+    let mut writer = XmlWriter::new(Vec::new());
+    let mut found = HashSet::new();
+
+    loop {
+        let event = match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(Event::Empty(e)) if e.local_name().as_ref() == b"hyperlink" => {
+                match matching_edit(&e, attrs, &mut found) {
+                    Some(edit) => Event::Empty(edited_hyperlink(&e, edit)?),
+                    None => Event::Empty(e),
+                }
+            }
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"hyperlink" => {
+                match matching_edit(&e, attrs, &mut found) {
+                    Some(edit) => Event::Start(edited_hyperlink(&e, edit)?),
+                    None => Event::Start(e),
+                }
+            }
+            Ok(e) => e,
+            Err(e) => return Err(format!("XML parse error: {e}")),
+        };
+        writer
+            .write_event(event)
+            .map_err(|e| format!("XML write error: {e}"))?;
+    }
+
+    let xml = String::from_utf8(writer.into_inner())
+        .map_err(|e| format!("Worksheet XML not UTF-8: {e}"))?;
+    Ok((xml, found))
+}
+
+/// Parse one `{"tooltip": ..., "display": ...}` dict.
+fn parse_attrs(cell: &str, value: &Bound<'_, PyAny>) -> PyResult<HyperlinkAttrs> {
+    let dict = value.downcast::<PyDict>().map_err(|_| {
+        PyErr::new::<PyValueError, _>(format!("Hyperlink attrs for {cell} must be a dict"))
+    })?;
+    let mut attrs = HyperlinkAttrs::default();
+    for (key, value) in dict.iter() {
+        let key: String = key.extract()?;
+        let value: Option<String> = value.extract().map_err(|_| {
+            PyErr::new::<PyValueError, _>(format!("Hyperlink {key} for {cell} must be a str"))
+        })?;
+        match key.as_str() {
+            "tooltip" => attrs.tooltip = Some(value),
+            "display" => attrs.display = Some(value),
+            other => {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "Unknown hyperlink attribute '{other}' (expected 'tooltip' or 'display')"
+                )))
+            }
+        }
+    }
+    Ok(attrs)
+}
+
+/// Set hyperlink tooltips and display text in a saved workbook.
+///
+/// `attrs` maps sheet → cell (or the range a link covers) → a dict with
+/// `tooltip` and/or `display`; a None value removes the attribute. The
+/// cells must already carry hyperlinks. The file is patched in place
+/// unless `output` is given. Returns the number of hyperlinks patched.
+#[pyfunction]
+#[pyo3(signature = (path, attrs, output=None))]
+pub(crate) fn patch_hyperlink_attrs(
+    py: Python<'_>,
+    path: &str,
+    attrs: &Bound<'_, PyDict>,
+    output: Option<&str>,
+) -> PyResult<usize> {
+    let mut requests: Vec<(String, HashMap<String, HyperlinkAttrs>)> = Vec::new();
+    for (sheet, cells) in attrs.iter() {
+        let sheet: String = sheet.extract()?;
+        let cells = cells.downcast::<PyDict>().map_err(|_| {
+            PyErr::new::<PyValueError, _>(format!("Hyperlinks for sheet {sheet} must be a dict"))
+        })?;
+        let mut by_ref = HashMap::new();
+        for (cell, value) in cells.iter() {
+            let cell: String = cell.extract()?;
+            RangeRef::parse(&cell)
+                .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, &cell, msg))?;
+            by_ref.insert(normalize_ref(&cell), parse_attrs(&cell, &value)?);
+        }
+        requests.push((sheet, by_ref));
+    }
+
+    let f = File::open(path).map_err(|e| {
+        errors::file_format(
+            CAPABILITIES.backend,
+            path,
+            format!("Cannot open '{path}': {e}"),
+        )
+    })?;
+    let mut zip = ZipArchive::new(f).map_err(|e| {
+        errors::file_format(CAPABILITIES.backend, path, format!("Not a valid ZIP: {e}"))
+    })?;
+    let wb_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml")?;
+    let rels_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/_rels/workbook.xml.rels")?;
+    let sheet_paths: HashMap<String, String> = ooxml_util::sheet_part_paths(&wb_xml, &rels_xml)?
+        .into_iter()
+        .collect();
+
+    let mut file_patches: HashMap<String, Vec<u8>> = HashMap::new();
+    let mut patched = 0usize;
+    for (sheet, by_ref) in &requests {
+        if by_ref.is_empty() {
+            continue;
+        }
+        let part = sheet_paths
+            .get(sheet)
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
+        let xml = ooxml_util::zip_read_to_string(&mut zip, part)?;
+        let (xml, found) = py
+            .allow_threads(|| patch_sheet_hyperlinks(&xml, by_ref))
+            .map_err(PyErr::new::<PyIOError, _>)?;
+        let mut missing: Vec<&str> = by_ref
+            .keys()
+            .filter(|k| !found.contains(*k))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            missing.sort_unstable();
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "No hyperlink at {sheet}!{}",
+                missing.join(", ")
+            )));
+        }
+        patched += found.len();
+        file_patches.insert(part.clone(), xml.into_bytes());
+    }
+    drop(zip);
+
+    match output {
+        Some(output) => rewrite_zip(path, output, &file_patches)?,
+        None if file_patches.is_empty() => {}
+        None => {
+            let tmp_path = format!("{path}.wolfxl.tmp");
+            rewrite_zip(path, &tmp_path, &file_patches)?;
+            replace_file(&tmp_path, path)?;
+        }
+    }
+    Ok(patched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attrs(tooltip: Option<Option<&str>>, display: Option<Option<&str>>) -> HyperlinkAttrs {
+        HyperlinkAttrs {
+            tooltip: tooltip.map(|t| t.map(str::to_string)),
+            display: display.map(|d| d.map(str::to_string)),
+        }
+    }
+
+    #[test]
+    fn test_patch_sets_and_removes_attrs() {
+        let xml = concat!(
+            r#"<worksheet><sheetData/><hyperlinks>"#,
+            r#"<hyperlink ref="A1" r:id="rId1" tooltip="old"/>"#,
+            r#"<hyperlink ref="B2:C3" location="S!A1" display="Go"/>"#,
+            r#"<hyperlink ref="D4" r:id="rId2"/>"#,
+            r#"</hyperlinks></worksheet>"#
+        );
+        let edits = HashMap::from([
+            ("A1".to_string(), attrs(Some(Some("New & <tip>")), None)),
+            ("B2:C3".to_string(), attrs(None, Some(None))),
+        ]);
+        let (out, found) = patch_sheet_hyperlinks(xml, &edits).unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(
+            out.contains(r#"<hyperlink ref="A1" r:id="rId1" tooltip="New &amp; &lt;tip&gt;"/>"#)
+        );
+        assert!(out.contains(r#"<hyperlink ref="B2:C3" location="S!A1"/>"#));
+        assert!(out.contains(r#"<hyperlink ref="D4" r:id="rId2"/>"#));
+    }
+
+    #[test]
+    fn test_unmatched_refs_are_not_found() {
+        let xml = concat!(
+            r#"<worksheet><hyperlinks><hyperlink ref="$A$1" r:id="rId1"/>"#,
+            r#"</hyperlinks></worksheet>"#
+        );
+        let edits = HashMap::from([
+            ("A1".to_string(), attrs(Some(Some("t")), None)),
+            ("Z9".to_string(), attrs(Some(Some("t")), None)),
+        ]);
+        let (out, found) = patch_sheet_hyperlinks(xml, &edits).unwrap();
+        assert_eq!(found, HashSet::from(["A1".to_string()]));
+        assert!(out.contains(r#"tooltip="t""#));
+    }
+}
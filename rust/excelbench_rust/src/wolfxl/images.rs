@@ -0,0 +1,138 @@
+//! Image media swaps for `XlsxPatcher.replace_image()`.
+//!
+//! Only the `xl/media/*` entry is rewritten; drawings and relationships keep
+//! pointing at the same part name. When the new file has a different format,
+//! `[Content_Types].xml` gets an `<Override>` for that part so readers still
+//! see the right MIME type.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader as XmlReader, Writer as XmlWriter};
+
+use crate::ooxml_util::parts::local_attr;
+
+/// New bytes for a media entry, plus its content type when the format
+/// changes.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ImagePatch {
+    pub data: Vec<u8>,
+    pub content_type: Option<&'static str>,
+}
+
+/// Lower-cased extension of a path or part name (empty when there is none).
+pub(crate) fn extension(path: &str) -> String {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    match name.rsplit_once('.') {
+        Some((_, ext)) => ext.to_ascii_lowercase(),
+        None => String::new(),
+    }
+}
+
+/// MIME type Excel uses for an image extension.
+pub(crate) fn image_content_type(ext: &str) -> Option<&'static str> {
+    Some(match ext {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        "emf" => "image/x-emf",
+        "wmf" => "image/x-wmf",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    })
+}
+
+/// Set the `<Override>` content type of `part` (a zip entry name without the
+/// leading `/`), adding the element when the part has none.
+pub(crate) fn set_content_type_override(
+    xml: &str,
+    part: &str,
+    content_type: &str,
+) -> Result<String, String> {
+    let part_name = format!("/{}", part.trim_start_matches('/'));
+    let mut reader = XmlReader::from_str(xml);
+    let mut writer = XmlWriter::new(Vec::new());
+    let mut found = false;
+
+    let override_elem = || {
+        let mut e = BytesStart::new("Override");
+        e.push_attribute(("PartName", part_name.as_str()));
+        e.push_attribute(("ContentType", content_type));
+        e
+    };
+
+    loop {
+        let event = match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(e) => e,
+            Err(e) => return Err(format!("Failed to parse [Content_Types].xml: {e}")),
+        };
+        match &event {
+            Event::Empty(e)
+                if e.local_name().as_ref() == b"Override"
+                    && local_attr(e, b"PartName").as_deref() == Some(part_name.as_str()) =>
+            {
+                found = true;
+                writer
+                    .write_event(Event::Empty(override_elem()))
+                    .map_err(|e| format!("XML write error: {e}"))?;
+                continue;
+            }
+            Event::End(e) if e.local_name().as_ref() == b"Types" && !found => {
+                writer
+                    .write_event(Event::Empty(override_elem()))
+                    .map_err(|e| format!("XML write error: {e}"))?;
+            }
+            _ => {}
+        }
+        writer
+            .write_event(event)
+            .map_err(|e| format!("XML write error: {e}"))?;
+    }
+
+    String::from_utf8(writer.into_inner())
+        .map_err(|e| format!("[Content_Types].xml not UTF-8: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ooxml_util::parts::parse_content_types;
+
+    const TYPES: &str = concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+        r#"<Default Extension="png" ContentType="image/png"/>"#,
+        r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+        r#"<Override PartName="/xl/workbook.xml" ContentType="application/xml"/>"#,
+        r#"</Types>"#,
+    );
+
+    #[test]
+    fn test_extension_and_content_type() {
+        assert_eq!(extension("logos/New.JPG"), "jpg");
+        assert_eq!(extension(r"C:\img\a.b\logo"), "");
+        assert_eq!(image_content_type("jpeg"), Some("image/jpeg"));
+        assert_eq!(image_content_type("txt"), None);
+    }
+
+    #[test]
+    fn test_set_content_type_override_adds_then_replaces() {
+        let once = set_content_type_override(TYPES, "xl/media/image1.png", "image/jpeg").unwrap();
+        let types = parse_content_types(&once).unwrap();
+        assert_eq!(
+            types.content_type("xl/media/image1.png"),
+            Some("image/jpeg")
+        );
+        assert_eq!(types.content_type("xl/media/image2.png"), Some("image/png"));
+        assert_eq!(
+            types.content_type("xl/workbook.xml"),
+            Some("application/xml")
+        );
+
+        let twice = set_content_type_override(&once, "xl/media/image1.png", "image/gif").unwrap();
+        assert_eq!(twice.matches("/xl/media/image1.png").count(), 1);
+        let types = parse_content_types(&twice).unwrap();
+        assert_eq!(types.content_type("xl/media/image1.png"), Some("image/gif"));
+    }
+}
@@ -2,17 +2,31 @@
 //!
 //! Instead of parsing the entire workbook into a DOM (like openpyxl or umya),
 //! WolfXL opens the xlsx ZIP, queues cell changes in memory, and on save:
-//!   1. Patches only the worksheet XMLs that have dirty cells
+//!   1. Patches only the worksheet XMLs that have dirty cells (one rayon task
+//!      per sheet)
 //!   2. Patches sharedStrings/styles only if needed
 //!   3. Raw-copies all other ZIP entries unchanged
 //!
 //! This makes modify-and-save O(modified data) instead of O(entire file).
 
+pub mod anonymize;
+pub mod conditional_format;
+pub mod hyperlinks;
+pub mod images;
+pub mod pivot;
+pub mod preview;
+pub mod reader;
+pub mod remap;
+pub mod render;
+pub mod shared_formula;
 #[allow(dead_code)] // SST parser used in Phase 3 (format patching reads existing styles)
 pub mod shared_strings;
 pub mod sheet_patcher;
+pub mod sheet_reader;
 #[allow(dead_code)] // Styles parser/appender used in Phase 3 (format patching)
 pub mod styles;
+pub mod transform;
+pub mod workbook_patcher;
 
 use std::collections::HashMap;
 use std::fs::File;
@@ -21,18 +35,46 @@ use std::io::{Read, Write};
 use pyo3::exceptions::{PyIOError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
-
+use rayon::prelude::*;
 use zip::write::SimpleFileOptions;
 use zip::{ZipArchive, ZipWriter};
 
+use crate::backend::{self, pyclass_object, Backend, BackendEntry, ExcelWriteBackend};
+use crate::capabilities::BackendCapabilities;
+use crate::cell_ref::{CellRef, RangeRef};
+use crate::color::ColorSpec;
+use crate::errors;
 use crate::ooxml_util;
-use sheet_patcher::{CellPatch, CellValue};
+use crate::payload::{self, BorderEdge, BorderPayload, CellPayload, FormatPayload};
+use crate::profile;
+use conditional_format::CfExtension;
+use images::ImagePatch;
+use remap::{RangeMove, Remap};
+use sheet_patcher::{CellPatch, CellValue, RowSpans};
 use styles::FormatSpec;
+use workbook_patcher::SheetState;
 
 // ---------------------------------------------------------------------------
 // PyO3 class
 // ---------------------------------------------------------------------------
 
+pub(crate) const CAPABILITIES: BackendCapabilities = BackendCapabilities {
+    class: "XlsxPatcher",
+    backend: "wolfxl",
+    extensions: &[".xlsx", ".xlsm"],
+    read: &[],
+    write: &[
+        "cell_values",
+        "formulas",
+        "text_formatting",
+        "background_colors",
+        "number_formats",
+        "alignment",
+        "borders",
+    ],
+    modify: true,
+};
+
 #[pyclass]
 pub struct XlsxPatcher {
     file_path: String,
@@ -42,6 +84,82 @@ pub struct XlsxPatcher {
     value_patches: HashMap<(String, String), CellPatch>,
     /// Queued cell format changes: (sheet, "A1") → FormatSpec.
     format_patches: HashMap<(String, String), FormatSpec>,
+    /// Queued sheet visibility changes, validated together on save.
+    sheet_states: HashMap<String, SheetState>,
+    /// Queued active (selected) sheet.
+    active_sheet: Option<String>,
+    /// Queued reference remaps, applied in order to every formula on save.
+    remaps: Vec<Remap>,
+    /// Queued media swaps: `xl/media/*` entry → new bytes.
+    image_patches: HashMap<String, ImagePatch>,
+    /// Queued conditional-format range extensions, applied in order.
+    cf_extensions: Vec<CfExtension>,
+    /// `spans` handling for rows that gain or change cells.
+    row_spans: RowSpans,
+    /// Queued pivot cache sources: `pivotCacheDefinition` part → new range.
+    pivot_sources: HashMap<String, RangeRef>,
+}
+
+pub(crate) const BACKEND: BackendEntry = BackendEntry {
+    name: "wolfxl",
+    open: |_py, path| {
+        let path = backend::require_path("wolfxl", path)?;
+        Ok(Box::new(XlsxPatcher::open(path)?))
+    },
+};
+
+impl Backend for XlsxPatcher {
+    fn into_py_object(self: Box<Self>, py: Python<'_>) -> PyResult<PyObject> {
+        pyclass_object(py, *self)
+    }
+
+    fn as_writer(&mut self) -> Option<&mut dyn ExcelWriteBackend> {
+        Some(self)
+    }
+}
+
+impl ExcelWriteBackend for XlsxPatcher {
+    /// The patcher edits existing sheets only; adding one that is already
+    /// present is a no-op.
+    fn add_sheet(&mut self, name: &str) -> PyResult<()> {
+        if self.sheet_paths.contains_key(name) {
+            return Ok(());
+        }
+        Err(PyErr::new::<PyValueError, _>(format!(
+            "wolfxl cannot add sheets (no sheet '{name}' in workbook)"
+        )))
+    }
+
+    fn write_cell_value(
+        &mut self,
+        sheet: &str,
+        a1: &str,
+        payload: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        self.queue_value(sheet, a1, payload)
+    }
+
+    fn write_cell_format(
+        &mut self,
+        sheet: &str,
+        a1: &str,
+        format_dict: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        self.queue_format(sheet, a1, format_dict)
+    }
+
+    fn write_cell_border(
+        &mut self,
+        sheet: &str,
+        a1: &str,
+        border_dict: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        self.queue_border(sheet, a1, border_dict)
+    }
+
+    fn save(&mut self, _py: Python<'_>, path: &str) -> PyResult<()> {
+        self.do_save(path)
+    }
 }
 
 #[pymethods]
@@ -49,29 +167,37 @@ impl XlsxPatcher {
     /// Open an xlsx file for surgical patching.
     #[staticmethod]
     fn open(path: &str) -> PyResult<Self> {
-        let f = File::open(path)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Cannot open '{path}': {e}")))?;
-        let mut zip = ZipArchive::new(f)
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Not a valid ZIP: {e}")))?;
+        let f = File::open(path).map_err(|e| {
+            errors::file_format(
+                CAPABILITIES.backend,
+                path,
+                format!("Cannot open '{path}': {e}"),
+            )
+        })?;
+        let mut zip = ZipArchive::new(f).map_err(|e| {
+            errors::file_format(CAPABILITIES.backend, path, format!("Not a valid ZIP: {e}"))
+        })?;
 
         // Parse workbook.xml + rels to build sheet name → XML path mapping.
         let wb_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml")?;
         let rels_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/_rels/workbook.xml.rels")?;
-        let sheet_rids = ooxml_util::parse_workbook_sheet_rids(&wb_xml)?;
-        let rel_targets = ooxml_util::parse_relationship_targets(&rels_xml)?;
-
-        let mut sheet_paths: HashMap<String, String> = HashMap::new();
-        for (name, rid) in sheet_rids {
-            if let Some(target) = rel_targets.get(&rid) {
-                sheet_paths.insert(name, ooxml_util::join_and_normalize("xl/", target));
-            }
-        }
+        let sheet_paths: HashMap<String, String> =
+            ooxml_util::sheet_part_paths(&wb_xml, &rels_xml)?
+                .into_iter()
+                .collect();
 
         Ok(XlsxPatcher {
             file_path: path.to_string(),
             sheet_paths,
             value_patches: HashMap::new(),
             format_patches: HashMap::new(),
+            sheet_states: HashMap::new(),
+            active_sheet: None,
+            remaps: Vec::new(),
+            image_patches: HashMap::new(),
+            cf_extensions: Vec::new(),
+            row_spans: RowSpans::default(),
+            pivot_sources: HashMap::new(),
         })
     }
 
@@ -79,63 +205,11 @@ impl XlsxPatcher {
     ///
     /// `payload` is a dict matching the ExcelBench cell payload format:
     ///   {"type": "string"|"number"|"boolean"|"formula"|"blank", "value": ...}
-    fn queue_value(
-        &mut self,
-        sheet: &str,
-        cell: &str,
-        payload: &Bound<'_, PyDict>,
-    ) -> PyResult<()> {
-        let cell_type = payload
-            .get_item("type")?
-            .map(|v| v.extract::<String>())
-            .transpose()?
-            .unwrap_or_default();
-
-        let value = match cell_type.as_str() {
-            "blank" => CellValue::Blank,
-            "string" | "str" => {
-                let v = payload
-                    .get_item("value")?
-                    .map(|v| v.extract::<String>())
-                    .transpose()?
-                    .unwrap_or_default();
-                CellValue::String(v)
-            }
-            "number" | "float" | "int" | "integer" => {
-                let v = payload
-                    .get_item("value")?
-                    .map(|v| v.extract::<f64>())
-                    .transpose()?
-                    .unwrap_or(0.0);
-                CellValue::Number(v)
-            }
-            "boolean" | "bool" => {
-                let v = payload
-                    .get_item("value")?
-                    .map(|v| v.extract::<bool>())
-                    .transpose()?
-                    .unwrap_or(false);
-                CellValue::Boolean(v)
-            }
-            "formula" => {
-                let v = payload
-                    .get_item("value")?
-                    .map(|v| v.extract::<String>())
-                    .transpose()?
-                    .unwrap_or_default();
-                // Strip leading '=' if present (openpyxl convention)
-                let formula = v.strip_prefix('=').unwrap_or(&v).to_string();
-                CellValue::Formula(formula)
-            }
-            other => {
-                return Err(PyErr::new::<PyValueError, _>(format!(
-                    "Unknown cell type: '{other}'"
-                )));
-            }
-        };
+    fn queue_value(&mut self, sheet: &str, cell: &str, payload: &Bound<'_, PyAny>) -> PyResult<()> {
+        let value = cell_value(payload)?;
 
-        let (row, col) =
-            crate::util::a1_to_row_col(cell).map_err(|e| PyErr::new::<PyValueError, _>(e))?;
+        let (row, col) = crate::util::a1_to_row_col(cell)
+            .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, cell, msg))?;
 
         let patch = CellPatch {
             row: row + 1, // a1_to_row_col returns 0-based, patcher uses 1-based
@@ -158,9 +232,9 @@ impl XlsxPatcher {
         &mut self,
         sheet: &str,
         cell: &str,
-        format_dict: &Bound<'_, PyDict>,
+        format_dict: &Bound<'_, PyAny>,
     ) -> PyResult<()> {
-        let spec = dict_to_format_spec(format_dict)?;
+        let spec = format_spec(&payload::parse_format(format_dict)?)?;
         self.format_patches
             .insert((sheet.to_string(), cell.to_string()), spec);
         Ok(())
@@ -171,9 +245,9 @@ impl XlsxPatcher {
         &mut self,
         sheet: &str,
         cell: &str,
-        border_dict: &Bound<'_, PyDict>,
+        border_dict: &Bound<'_, PyAny>,
     ) -> PyResult<()> {
-        let border = dict_to_border_spec(border_dict)?;
+        let border = border_spec(&payload::parse_border(border_dict)?)?;
         // Merge with existing format patch or create new one
         let key = (sheet.to_string(), cell.to_string());
         let spec = self.format_patches.entry(key).or_default();
@@ -181,6 +255,163 @@ impl XlsxPatcher {
         Ok(())
     }
 
+    /// Queue a sheet visibility change: `"visible"`, `"hidden"` or
+    /// `"veryHidden"`.
+    ///
+    /// Visibility and the active sheet are validated together on save: at
+    /// least one sheet must stay visible, and an active tab that ends up
+    /// hidden moves to the first visible sheet.
+    fn set_sheet_visibility(&mut self, sheet: &str, state: &str) -> PyResult<()> {
+        self.require_sheet(sheet)?;
+        let state = SheetState::parse(state).ok_or_else(|| {
+            PyErr::new::<PyValueError, _>(format!(
+                "Invalid sheet state '{state}' (expected 'visible', 'hidden' or 'veryHidden')"
+            ))
+        })?;
+        self.sheet_states.insert(sheet.to_string(), state);
+        Ok(())
+    }
+
+    /// How patched rows treat their optional `spans` attribute:
+    /// `"recompute"` (the default) rewrites it to cover the row's cells,
+    /// `"strip"` removes it.
+    fn set_row_spans(&mut self, mode: &str) -> PyResult<()> {
+        self.row_spans = RowSpans::parse(mode).ok_or_else(|| {
+            PyErr::new::<PyValueError, _>(format!(
+                "Invalid row spans mode '{mode}' (expected 'recompute' or 'strip')"
+            ))
+        })?;
+        Ok(())
+    }
+
+    /// Queue `sheet` as the active (selected) tab. Saving fails if the sheet
+    /// is hidden once all visibility changes are applied.
+    fn set_active_sheet(&mut self, sheet: &str) -> PyResult<()> {
+        self.require_sheet(sheet)?;
+        self.active_sheet = Some(sheet.to_string());
+        Ok(())
+    }
+
+    /// Queue a move of cell ranges on `sheet`: `mapping` maps each source
+    /// range to its new top-left cell (or a destination range of the same
+    /// size), e.g. `{"A1:B10": "D1"}`.
+    ///
+    /// On save every formula in the workbook that references a moved cell,
+    /// A1 or R1C1, from `sheet` itself or qualified from another sheet, is
+    /// rewritten to follow it. Only references change; cell contents are
+    /// moved with `queue_value`. Queued values are remapped too, so formulas
+    /// written into the destination keep pointing at the moved block.
+    ///
+    /// When sources overlap, the first entry containing a reference wins.
+    fn remap_references(&mut self, sheet: &str, mapping: &Bound<'_, PyDict>) -> PyResult<()> {
+        self.require_sheet(sheet)?;
+        let mut moves = Vec::with_capacity(mapping.len());
+        for (src, dst) in mapping.iter() {
+            let (src, dst): (String, String) = (src.extract()?, dst.extract()?);
+            let source = RangeRef::parse(&src)
+                .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, &src, msg))?;
+            let dest = RangeRef::parse(&dst)
+                .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, &dst, msg))?;
+            if source.sheet.is_some() || dest.sheet.is_some() {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "remap_references moves ranges within '{sheet}'; \
+                     drop the sheet from {src} -> {dst}"
+                )));
+            }
+            let single_cell = dest.start == dest.end;
+            if !single_cell && (dest.height(), dest.width()) != (source.height(), source.width()) {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "Destination {dst} is not the same size as {src}"
+                )));
+            }
+            let top_left = CellRef::new(dest.start.row, dest.start.col);
+            moves.push(RangeMove::new(source, top_left));
+        }
+        self.remaps.push(Remap {
+            sheet: sheet.to_string(),
+            moves,
+        });
+        Ok(())
+    }
+
+    /// Queue a swap of an existing image's bytes for the file at `new_path`.
+    ///
+    /// `target` names a media entry (`"image1.png"` or `"xl/media/image1.png"`)
+    /// or the anchor cell of a picture, sheet-qualified (`"Sheet1!B2"`). Only
+    /// the media entry is replaced, so every drawing sharing it shows the new
+    /// image. When `new_path` has a different extension the part keeps its
+    /// name and gets a content-type override instead.
+    fn replace_image(&mut self, target: &str, new_path: &str) -> PyResult<()> {
+        let media = self.resolve_media(target)?;
+        let new_ext = images::extension(new_path);
+        let content_type = if new_ext == images::extension(&media) {
+            None
+        } else {
+            Some(images::image_content_type(&new_ext).ok_or_else(|| {
+                PyErr::new::<PyValueError, _>(format!(
+                    "Unsupported image type '.{new_ext}' for {new_path}"
+                ))
+            })?)
+        };
+        let data = std::fs::read(new_path)
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Cannot read '{new_path}': {e}")))?;
+        self.image_patches
+            .insert(media, ImagePatch { data, content_type });
+        Ok(())
+    }
+
+    /// Queue an extension of the conditional formatting on `source_range` to
+    /// `new_range`, e.g. `("Report", "B2:B10", "B11:B20")` after appending
+    /// rows.
+    ///
+    /// On save every block whose ranges cover `source_range` (data bars,
+    /// color scales, icon sets and plain rules alike, including their Excel
+    /// 2010 extension entries) also covers `new_range`; an adjacent range is
+    /// grown in place. Saving fails if no block covers `source_range`.
+    fn extend_conditional_format(
+        &mut self,
+        sheet: &str,
+        source_range: &str,
+        new_range: &str,
+    ) -> PyResult<()> {
+        self.require_sheet(sheet)?;
+        let parse = |r: &str| {
+            let range =
+                RangeRef::parse(r).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, r, msg))?;
+            if range.sheet.is_some() {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "extend_conditional_format works within '{sheet}'; drop the sheet from {r}"
+                )));
+            }
+            Ok(range)
+        };
+        self.cf_extensions.push(CfExtension {
+            sheet: sheet.to_string(),
+            source: parse(source_range)?,
+            target: parse(new_range)?,
+        });
+        Ok(())
+    }
+
+    /// Queue a new source range for a pivot table's cache, e.g.
+    /// `("Summary", "Data!A1:F500")` after appending rows.
+    ///
+    /// `pivot` is the pivot table's name or its 0-based index in workbook
+    /// order (sheets in tab order). Without a sheet, `new_range` stays on
+    /// the cache's current source sheet. The cache is marked to refresh on
+    /// load, and every pivot table sharing it follows the new range. Saving
+    /// fails if the cache doesn't read a worksheet range of this workbook.
+    fn repoint_pivot_source(&mut self, pivot: &Bound<'_, PyAny>, new_range: &str) -> PyResult<()> {
+        let range = RangeRef::parse(new_range)
+            .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, new_range, msg))?;
+        if let Some(sheet) = &range.sheet {
+            self.require_sheet(sheet)?;
+        }
+        let cache = self.resolve_pivot_cache(pivot)?;
+        self.pivot_sources.insert(cache, range);
+        Ok(())
+    }
+
     /// Return the list of sheet names discovered in the workbook.
     fn sheet_names(&self) -> Vec<String> {
         self.sheet_paths.keys().cloned().collect()
@@ -191,20 +422,53 @@ impl XlsxPatcher {
         self.do_save(path)
     }
 
+    /// Discard queued patches. The source file is only opened by `open()` and
+    /// `save()`, so no handle is held in between.
+    fn close(&mut self) {
+        self.value_patches.clear();
+        self.format_patches.clear();
+        self.sheet_states.clear();
+        self.active_sheet = None;
+        self.remaps.clear();
+        self.image_patches.clear();
+        self.cf_extensions.clear();
+        self.pivot_sources.clear();
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Queued patches are not saved implicitly; call `save()` or
+    /// `save_in_place()` inside the block.
+    #[pyo3(signature = (_exc_type=None, _exc_value=None, _traceback=None))]
+    fn __exit__(
+        &mut self,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> bool {
+        self.close();
+        false
+    }
+
     /// Save in-place (atomic tmp+rename).
     fn save_in_place(&self) -> PyResult<()> {
         let tmp_path = format!("{}.wolfxl.tmp", self.file_path);
         self.do_save(&tmp_path)?;
+        replace_file(&tmp_path, &self.file_path)
+    }
+}
 
-        // Atomic rename
-        if let Err(e) = std::fs::rename(&tmp_path, &self.file_path) {
-            let _ = std::fs::remove_file(&self.file_path);
-            std::fs::rename(&tmp_path, &self.file_path).map_err(|e2| {
-                PyErr::new::<PyIOError, _>(format!("Failed to replace file: {e}; {e2}"))
-            })?;
-        }
-        Ok(())
+/// Move `tmp_path` over `path` (atomic where the platform allows).
+fn replace_file(tmp_path: &str, path: &str) -> PyResult<()> {
+    if let Err(e) = std::fs::rename(tmp_path, path) {
+        let _ = std::fs::remove_file(path);
+        std::fs::rename(tmp_path, path).map_err(|e2| {
+            PyErr::new::<PyIOError, _>(format!("Failed to replace file: {e}; {e2}"))
+        })?;
     }
+    Ok(())
 }
 
 // ---------------------------------------------------------------------------
@@ -213,7 +477,15 @@ impl XlsxPatcher {
 
 impl XlsxPatcher {
     fn do_save(&self, output_path: &str) -> PyResult<()> {
-        if self.value_patches.is_empty() && self.format_patches.is_empty() {
+        if self.value_patches.is_empty()
+            && self.format_patches.is_empty()
+            && self.sheet_states.is_empty()
+            && self.active_sheet.is_none()
+            && self.remaps.is_empty()
+            && self.image_patches.is_empty()
+            && self.cf_extensions.is_empty()
+            && self.pivot_sources.is_empty()
+        {
             // No changes — just copy
             std::fs::copy(&self.file_path, output_path)
                 .map_err(|e| PyErr::new::<PyIOError, _>(format!("Copy failed: {e}")))?;
@@ -231,6 +503,7 @@ impl XlsxPatcher {
         let mut style_assignments: HashMap<String, u32> = HashMap::new(); // "sheet:cell" → xf_index
 
         if !self.format_patches.is_empty() {
+            let _span = profile::span("wolfxl.style_build");
             let raw = ooxml_util::zip_read_to_string_opt(&mut zip, "xl/styles.xml")?
                 .unwrap_or_else(|| minimal_styles_xml());
             let mut xml = raw;
@@ -277,7 +550,7 @@ impl XlsxPatcher {
             let key = format!("{sheet}:{cell}");
             if let Some(&xf_idx) = style_assignments.get(&key) {
                 let (row, col) = crate::util::a1_to_row_col(cell)
-                    .map_err(|e| PyErr::new::<PyValueError, _>(e))?;
+                    .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, cell, msg))?;
                 let patch = CellPatch {
                     row: row + 1,
                     col: col + 1,
@@ -292,13 +565,35 @@ impl XlsxPatcher {
         }
 
         // --- Phase 3: Patch worksheet XMLs ---
-        let mut file_patches: HashMap<String, Vec<u8>> = HashMap::new();
+        let mut file_patches = patch_parts(&mut zip, &sheet_cell_patches, self.row_spans)?;
 
-        for (sheet_path, patches) in &sheet_cell_patches {
-            let xml = ooxml_util::zip_read_to_string(&mut zip, sheet_path)?;
-            let patched = sheet_patcher::patch_worksheet(&xml, patches)
-                .map_err(|e| PyErr::new::<PyIOError, _>(format!("Patch failed: {e}")))?;
-            file_patches.insert(sheet_path.clone(), patched.into_bytes());
+        // --- Phase 3a: Reference remaps (every worksheet's formulas) ---
+        if !self.remaps.is_empty() {
+            self.remap_parts(&mut zip, &mut file_patches)?;
+        }
+
+        // --- Phase 3b: Sheet visibility / active tab (workbook.xml) ---
+        if !self.sheet_states.is_empty() || self.active_sheet.is_some() {
+            self.patch_workbook_view(&mut zip, &mut file_patches)?;
+        }
+
+        // --- Phase 3c: Image media swaps ---
+        if !self.image_patches.is_empty() {
+            self.patch_images(&mut zip, &mut file_patches)?;
+        }
+
+        // --- Phase 3d: Conditional-format range extensions ---
+        if !self.cf_extensions.is_empty() {
+            self.extend_cf_parts(&mut zip, &mut file_patches)?;
+        }
+
+        // --- Phase 3e: Pivot cache sources ---
+        for (part, range) in &self.pivot_sources {
+            let xml = ooxml_util::zip_read_to_string(&mut zip, part)?;
+            let patched = pivot::repoint_cache_source(&xml, range).map_err(|e| {
+                PyErr::new::<PyValueError, _>(format!("Cannot repoint {part}: {e}"))
+            })?;
+            file_patches.insert(part.clone(), patched.into_bytes());
         }
 
         // Add styles.xml patch if modified
@@ -309,176 +604,458 @@ impl XlsxPatcher {
         drop(zip);
 
         // --- Phase 4: Rewrite ZIP ---
-        let src = File::open(&self.file_path).map_err(|e| {
+        rewrite_zip(&self.file_path, output_path, &file_patches)
+    }
+}
+
+impl XlsxPatcher {
+    fn require_sheet(&self, sheet: &str) -> PyResult<()> {
+        if self.sheet_paths.contains_key(sheet) {
+            Ok(())
+        } else {
+            Err(errors::sheet_not_found(CAPABILITIES.backend, sheet))
+        }
+    }
+
+    /// The `xl/media` entry `replace_image` should swap for `target`.
+    fn resolve_media(&self, target: &str) -> PyResult<String> {
+        let f = File::open(&self.file_path).map_err(|e| {
             PyErr::new::<PyIOError, _>(format!("Cannot open '{}': {e}", self.file_path))
         })?;
-        let mut zip = ZipArchive::new(src)
+        let mut zip = ZipArchive::new(f)
             .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP read error: {e}")))?;
 
-        let dst = File::create(output_path).map_err(|e| {
-            PyErr::new::<PyIOError, _>(format!("Cannot create '{output_path}': {e}"))
-        })?;
-        let mut out = ZipWriter::new(dst);
+        if target.contains('!') {
+            let range = RangeRef::parse(target)
+                .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, target, msg))?;
+            let sheet = range.sheet.as_deref().unwrap_or_default();
+            self.require_sheet(sheet)?;
+            let cell = range.start.to_a1();
+            return ooxml_util::read_sheet_images(&mut zip, &self.sheet_paths[sheet])?
+                .into_iter()
+                .find(|i| i.image.from.is_some_and(|m| m.a1() == cell))
+                .and_then(|i| i.media)
+                .ok_or_else(|| {
+                    PyErr::new::<PyValueError, _>(format!("No embedded image anchored at {target}"))
+                });
+        }
 
-        for i in 0..zip.len() {
-            let mut file = zip
-                .by_index(i)
-                .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP entry read error: {e}")))?;
-            let name = file.name().to_string();
+        let name = target.trim_start_matches('/');
+        let part = if name.starts_with("xl/media/") {
+            name.to_string()
+        } else {
+            format!("xl/media/{name}")
+        };
+        if zip.by_name(&part).is_err() {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "No media entry '{part}' in {}",
+                self.file_path
+            )));
+        }
+        Ok(part)
+    }
+
+    /// The `pivotCacheDefinition` part behind the pivot table `pivot` (a
+    /// name or a 0-based index in workbook order).
+    fn resolve_pivot_cache(&self, pivot: &Bound<'_, PyAny>) -> PyResult<String> {
+        let f = File::open(&self.file_path).map_err(|e| {
+            PyErr::new::<PyIOError, _>(format!("Cannot open '{}': {e}", self.file_path))
+        })?;
+        let mut zip = ZipArchive::new(f)
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP read error: {e}")))?;
 
-            let mut opts = SimpleFileOptions::default().compression_method(file.compression());
-            if let Some(dt) = file.last_modified() {
-                opts = opts.last_modified_time(dt);
+        // (name, cache part) of every pivot table, sheets in tab order.
+        let wb_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml")?;
+        let rels_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/_rels/workbook.xml.rels")?;
+        let mut pivots: Vec<(String, Option<String>)> = Vec::new();
+        for (_, sheet_part) in ooxml_util::sheet_part_paths(&wb_xml, &rels_xml)? {
+            for table_part in related_parts(&mut zip, &sheet_part, "pivotTable")? {
+                let Some(xml) = ooxml_util::zip_read_to_string_opt(&mut zip, &table_part)? else {
+                    continue;
+                };
+                let name = pivot::pivot_table_name(&xml)
+                    .map_err(|e| PyErr::new::<PyIOError, _>(format!("{table_part}: {e}")))?
+                    .unwrap_or_default();
+                let cache = related_parts(&mut zip, &table_part, "pivotCacheDefinition")?
+                    .into_iter()
+                    .next();
+                pivots.push((name, cache));
             }
-            if let Some(mode) = file.unix_mode() {
-                opts = opts.unix_permissions(mode);
+        }
+
+        let found = match pivot.extract::<usize>() {
+            Ok(index) => pivots.get(index).ok_or_else(|| {
+                PyErr::new::<PyValueError, _>(format!(
+                    "Pivot table index {index} is out of range ({} in workbook)",
+                    pivots.len()
+                ))
+            })?,
+            Err(_) => {
+                let name: String = pivot.extract()?;
+                pivots
+                    .iter()
+                    .find(|(n, _)| n.eq_ignore_ascii_case(&name))
+                    .ok_or_else(|| {
+                        let names: Vec<&str> = pivots.iter().map(|(n, _)| n.as_str()).collect();
+                        PyErr::new::<PyValueError, _>(format!(
+                            "No pivot table named '{name}' (available: {})",
+                            names.join(", ")
+                        ))
+                    })?
             }
+        };
+        found.1.clone().ok_or_else(|| {
+            PyErr::new::<PyValueError, _>(format!("Pivot table '{}' has no pivot cache", found.0))
+        })
+    }
 
-            if file.is_dir() {
-                out.add_directory(&name, opts)
-                    .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP write error: {e}")))?;
+    /// Add the queued media bytes, plus content-type overrides for parts
+    /// whose format changed.
+    fn patch_images(
+        &self,
+        zip: &mut ZipArchive<File>,
+        file_patches: &mut HashMap<String, Vec<u8>>,
+    ) -> PyResult<()> {
+        let mut types_xml: Option<String> = None;
+        for (part, patch) in &self.image_patches {
+            file_patches.insert(part.clone(), patch.data.clone());
+            let Some(content_type) = patch.content_type else {
                 continue;
+            };
+            let xml = match types_xml.take() {
+                Some(xml) => xml,
+                None => ooxml_util::zip_read_to_string(zip, "[Content_Types].xml")?,
+            };
+            types_xml = Some(
+                images::set_content_type_override(&xml, part, content_type)
+                    .map_err(PyErr::new::<PyIOError, _>)?,
+            );
+        }
+        if let Some(xml) = types_xml {
+            file_patches.insert("[Content_Types].xml".to_string(), xml.into_bytes());
+        }
+        Ok(())
+    }
+
+    /// Apply the queued conditional-format extensions on top of any patches
+    /// already in `file_patches`.
+    fn extend_cf_parts(
+        &self,
+        zip: &mut ZipArchive<File>,
+        file_patches: &mut HashMap<String, Vec<u8>>,
+    ) -> PyResult<()> {
+        for ext in &self.cf_extensions {
+            let part = &self.sheet_paths[&ext.sheet];
+            let xml = match file_patches.get(part) {
+                Some(bytes) => String::from_utf8(bytes.clone())
+                    .map_err(|e| PyErr::new::<PyIOError, _>(format!("UTF-8 error: {e}")))?,
+                None => ooxml_util::zip_read_to_string(zip, part)?,
+            };
+            let (patched, extended) =
+                conditional_format::extend_conditional_formats(&xml, &ext.source, &ext.target)
+                    .map_err(|e| PyErr::new::<PyIOError, _>(format!("Patch failed: {e}")))?;
+            if extended == 0 {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "No conditional formatting on '{}' covers {}",
+                    ext.sheet,
+                    ext.source.to_a1()
+                )));
             }
+            file_patches.insert(part.clone(), patched.into_bytes());
+        }
+        Ok(())
+    }
 
-            let data = if let Some(patched) = file_patches.get(&name) {
-                patched.clone()
-            } else {
-                let mut buf = Vec::new();
-                file.read_to_end(&mut buf)
-                    .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP read error: {e}")))?;
-                buf
+    /// Rewrite the formulas of every worksheet for the queued remaps, on top
+    /// of any cell patches already in `file_patches`.
+    fn remap_parts(
+        &self,
+        zip: &mut ZipArchive<File>,
+        file_patches: &mut HashMap<String, Vec<u8>>,
+    ) -> PyResult<()> {
+        let mut inputs = Vec::with_capacity(self.sheet_paths.len());
+        for (sheet, part) in &self.sheet_paths {
+            let xml = match file_patches.remove(part) {
+                Some(bytes) => String::from_utf8(bytes)
+                    .map_err(|e| PyErr::new::<PyIOError, _>(format!("UTF-8 error: {e}")))?,
+                None => ooxml_util::zip_read_to_string(zip, part)?,
             };
+            inputs.push((sheet, part, xml));
+        }
 
-            out.start_file(&name, opts)
-                .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP write error: {e}")))?;
-            out.write_all(&data)
+        let _span = profile::span("wolfxl.remap");
+        let remapped = inputs
+            .into_par_iter()
+            .map(|(sheet, part, mut xml)| {
+                for remap in &self.remaps {
+                    xml = remap::remap_worksheet(&xml, sheet, remap)
+                        .map_err(|e| format!("Remap failed for {part}: {e}"))?;
+                }
+                Ok((part.clone(), xml.into_bytes()))
+            })
+            .collect::<Result<Vec<_>, String>>()
+            .map_err(PyErr::new::<PyIOError, _>)?;
+        file_patches.extend(remapped);
+        Ok(())
+    }
+
+    /// Apply queued visibility/active-sheet changes to workbook.xml, and move
+    /// `tabSelected` between worksheets when the active tab changes.
+    fn patch_workbook_view(
+        &self,
+        zip: &mut ZipArchive<File>,
+        file_patches: &mut HashMap<String, Vec<u8>>,
+    ) -> PyResult<()> {
+        let wb_xml = ooxml_util::zip_read_to_string(zip, "xl/workbook.xml")?;
+        let sheets: Vec<(String, Option<String>)> =
+            ooxml_util::parts::parse_workbook_sheets(&wb_xml)
+                .map_err(PyErr::new::<PyIOError, _>)?
+                .into_iter()
+                .map(|s| (s.name, s.state))
+                .collect();
+        let current =
+            workbook_patcher::current_active_tab(&wb_xml).map_err(PyErr::new::<PyIOError, _>)?;
+        let plan = workbook_patcher::plan_view(
+            &sheets,
+            current,
+            &self.sheet_states,
+            self.active_sheet.as_deref(),
+        )
+        .map_err(PyErr::new::<PyValueError, _>)?;
+
+        let patched = workbook_patcher::patch_workbook_view(&wb_xml, &plan)
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("Patch failed: {e}")))?;
+        file_patches.insert("xl/workbook.xml".to_string(), patched.into_bytes());
+
+        if plan.active == plan.previous_active {
+            return Ok(());
+        }
+        for (index, selected) in [(plan.previous_active, false), (plan.active, true)] {
+            let Some(part) = sheets
+                .get(index)
+                .and_then(|(name, _)| self.sheet_paths.get(name))
+            else {
+                continue;
+            };
+            let xml = match file_patches.get(part) {
+                Some(bytes) => String::from_utf8(bytes.clone())
+                    .map_err(|e| PyErr::new::<PyIOError, _>(format!("UTF-8 error: {e}")))?,
+                None => ooxml_util::zip_read_to_string(zip, part)?,
+            };
+            let patched = workbook_patcher::set_tab_selected(&xml, selected)
+                .map_err(|e| PyErr::new::<PyIOError, _>(format!("Patch failed: {e}")))?;
+            file_patches.insert(part.clone(), patched.into_bytes());
+        }
+        Ok(())
+    }
+}
+
+/// Internal parts `part` links to with a relationship of type `rel_type`.
+fn related_parts(zip: &mut ZipArchive<File>, part: &str, rel_type: &str) -> PyResult<Vec<String>> {
+    let Some(rels_xml) =
+        ooxml_util::zip_read_to_string_opt(zip, &ooxml_util::part_rels_path(part))?
+    else {
+        return Ok(Vec::new());
+    };
+    let dir = match part.rfind('/') {
+        Some(i) => &part[..i + 1],
+        None => "",
+    };
+    Ok(ooxml_util::parts::parse_relationships(&rels_xml)
+        .map_err(PyErr::new::<PyIOError, _>)?
+        .into_iter()
+        .filter(|rel| rel.is_type(rel_type) && !rel.external)
+        .map(|rel| ooxml_util::join_and_normalize(dir, &rel.target))
+        .collect())
+}
+
+/// Patch each dirty worksheet part. The XML is read serially (the archive
+/// is single-cursor), then the independent rewrites run on the rayon pool.
+fn patch_parts(
+    zip: &mut ZipArchive<File>,
+    part_patches: &HashMap<String, Vec<CellPatch>>,
+    spans: RowSpans,
+) -> PyResult<HashMap<String, Vec<u8>>> {
+    let mut inputs = Vec::with_capacity(part_patches.len());
+    for (part, patches) in part_patches {
+        inputs.push((part, ooxml_util::zip_read_to_string(zip, part)?, patches));
+    }
+
+    let _span = profile::span("wolfxl.patch");
+    inputs
+        .into_par_iter()
+        .map(|(part, xml, patches)| {
+            let patched = sheet_patcher::patch_worksheet(&xml, patches, spans)
+                .map_err(|e| format!("Patch failed for {part}: {e}"))?;
+            Ok((part.clone(), patched.into_bytes()))
+        })
+        .collect::<Result<HashMap<_, _>, String>>()
+        .map_err(PyErr::new::<PyIOError, _>)
+}
+
+/// Copy the ZIP at `src_path` to `output_path`, substituting the entries in
+/// `file_patches` and raw-copying everything else.
+fn rewrite_zip(
+    src_path: &str,
+    output_path: &str,
+    file_patches: &HashMap<String, Vec<u8>>,
+) -> PyResult<()> {
+    let _span = profile::span("wolfxl.zip_write");
+    let src = File::open(src_path)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Cannot open '{src_path}': {e}")))?;
+    let mut zip = ZipArchive::new(src)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP read error: {e}")))?;
+
+    let dst = File::create(output_path)
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("Cannot create '{output_path}': {e}")))?;
+    let mut out = ZipWriter::new(dst);
+
+    for i in 0..zip.len() {
+        let mut file = zip
+            .by_index(i)
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP entry read error: {e}")))?;
+        let name = file.name().to_string();
+
+        let mut opts = SimpleFileOptions::default().compression_method(file.compression());
+        if let Some(dt) = file.last_modified() {
+            opts = opts.last_modified_time(dt);
+        }
+        if let Some(mode) = file.unix_mode() {
+            opts = opts.unix_permissions(mode);
+        }
+
+        if file.is_dir() {
+            out.add_directory(&name, opts)
                 .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP write error: {e}")))?;
+            continue;
         }
 
-        out.finish()
-            .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP finalize error: {e}")))?;
+        let data = if let Some(patched) = file_patches.get(&name) {
+            patched.clone()
+        } else {
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf)
+                .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP read error: {e}")))?;
+            buf
+        };
 
-        Ok(())
+        out.start_file(&name, opts)
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP write error: {e}")))?;
+        out.write_all(&data)
+            .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP write error: {e}")))?;
     }
+
+    out.finish()
+        .map_err(|e| PyErr::new::<PyIOError, _>(format!("ZIP finalize error: {e}")))?;
+
+    Ok(())
 }
 
 // ---------------------------------------------------------------------------
-// Dict → spec conversion helpers
+// Payload → spec conversion helpers
 // ---------------------------------------------------------------------------
 
-fn dict_to_format_spec(d: &Bound<'_, PyDict>) -> PyResult<FormatSpec> {
+/// The value a cell payload writes; formats and borders are queued separately.
+fn cell_value(payload: &Bound<'_, PyAny>) -> PyResult<CellValue> {
+    Ok(match payload::parse_cell_payload(payload)? {
+        CellPayload::Blank => CellValue::Blank,
+        CellPayload::String(s) => CellValue::String(s),
+        CellPayload::Number(n) => CellValue::Number(n),
+        CellPayload::Boolean(b) => CellValue::Boolean(b),
+        CellPayload::Formula { formula, .. } => CellValue::Formula(formula),
+        other => return Err(payload::unsupported_type(other.type_name())),
+    })
+}
+
+fn format_spec(fmt: &FormatPayload) -> PyResult<FormatSpec> {
     let mut spec = FormatSpec::default();
 
     // Font properties
-    let bold = extract_bool(d, "bold")?;
-    let italic = extract_bool(d, "italic")?;
-    let underline = extract_bool(d, "underline")?;
-    let strikethrough = extract_bool(d, "strikethrough")?;
-    let font_name = extract_str(d, "font_name")?;
-    let font_size = extract_u32(d, "font_size")?;
-    let font_color = extract_str(d, "font_color")?;
-
-    if bold.is_some()
-        || italic.is_some()
-        || underline.is_some()
-        || strikethrough.is_some()
-        || font_name.is_some()
-        || font_size.is_some()
-        || font_color.is_some()
+    if fmt.bold.is_some()
+        || fmt.italic.is_some()
+        || fmt.underline.is_some()
+        || fmt.strikethrough.is_some()
+        || fmt.font_name.is_some()
+        || fmt.font_size.is_some()
+        || fmt.font_color.is_some()
     {
         spec.font = Some(styles::FontSpec {
-            bold: bold.unwrap_or(false),
-            italic: italic.unwrap_or(false),
-            underline: underline.unwrap_or(false),
-            strikethrough: strikethrough.unwrap_or(false),
-            name: font_name,
-            size: font_size,
-            color_rgb: font_color.map(|c| normalize_color(&c)),
+            bold: fmt.bold.unwrap_or(false),
+            italic: fmt.italic.unwrap_or(false),
+            underline: fmt.underline.as_deref().is_some_and(|u| u != "none"),
+            strikethrough: fmt.strikethrough.unwrap_or(false),
+            name: fmt.font_name.clone(),
+            // styles.xml sizes are whole points here.
+            size: fmt.font_size.map(|sz| sz.round().max(0.0) as u32),
+            color_rgb: fmt.font_color.as_deref().map(normalize_color).transpose()?,
         });
     }
 
     // Fill properties
-    let bg_color = extract_str(d, "bg_color")?;
-    if let Some(color) = bg_color {
+    if let Some(color) = &fmt.bg_color {
         spec.fill = Some(styles::FillSpec {
             pattern_type: "solid".to_string(),
-            fg_color_rgb: Some(normalize_color(&color)),
+            fg_color_rgb: Some(normalize_color(color)?),
         });
     }
 
     // Number format
-    spec.number_format = extract_str(d, "number_format")?;
-
-    // Alignment — accept both openpyxl-style and wolfxl-style key names
-    let horizontal = extract_str(d, "horizontal")?.or(extract_str(d, "h_align")?);
-    let vertical = extract_str(d, "vertical")?.or(extract_str(d, "v_align")?);
-    let wrap_text = extract_bool(d, "wrap_text")?.or(extract_bool(d, "wrap")?);
-    let indent = extract_u32(d, "indent")?;
-    let text_rotation = extract_u32(d, "text_rotation")?.or(extract_u32(d, "rotation")?);
-
-    if horizontal.is_some()
-        || vertical.is_some()
-        || wrap_text.is_some()
-        || indent.is_some()
-        || text_rotation.is_some()
+    spec.number_format = fmt.number_format.clone();
+
+    // Alignment
+    if fmt.h_align.is_some()
+        || fmt.v_align.is_some()
+        || fmt.wrap.is_some()
+        || fmt.indent.is_some()
+        || fmt.rotation.is_some()
     {
         spec.alignment = Some(styles::AlignmentSpec {
-            horizontal,
-            vertical,
-            wrap_text: wrap_text.unwrap_or(false),
-            indent: indent.unwrap_or(0),
-            text_rotation: text_rotation.unwrap_or(0),
+            horizontal: fmt.h_align.clone(),
+            vertical: fmt.v_align.clone(),
+            wrap_text: fmt.wrap.unwrap_or(false),
+            indent: fmt.indent.map_or(0, |i| i.max(0) as u32),
+            text_rotation: fmt.rotation.map_or(0, |r| r.max(0) as u32),
         });
     }
 
     Ok(spec)
 }
 
-fn dict_to_border_spec(d: &Bound<'_, PyDict>) -> PyResult<styles::BorderSpec> {
-    fn extract_side(d: &Bound<'_, PyDict>, key: &str) -> PyResult<styles::BorderSideSpec> {
-        if let Some(side) = d.get_item(key)? {
-            if let Ok(sd) = side.downcast::<PyDict>() {
-                let style = extract_str(sd, "style")?;
-                let color = extract_str(sd, "color")?.map(|c| normalize_color(&c));
-                return Ok(styles::BorderSideSpec {
-                    style,
-                    color_rgb: color,
-                });
-            }
-        }
-        Ok(styles::BorderSideSpec::default())
-    }
-
+/// Diagonal edges are not patched; only the four sides are.
+fn border_spec(bdr: &BorderPayload) -> PyResult<styles::BorderSpec> {
+    let side = |edge: &Option<BorderEdge>| -> PyResult<styles::BorderSideSpec> {
+        Ok(match edge {
+            Some(e) => styles::BorderSideSpec {
+                style: e.style.clone(),
+                color_rgb: e.color.as_deref().map(normalize_color).transpose()?,
+            },
+            None => styles::BorderSideSpec::default(),
+        })
+    };
     Ok(styles::BorderSpec {
-        left: extract_side(d, "left")?,
-        right: extract_side(d, "right")?,
-        top: extract_side(d, "top")?,
-        bottom: extract_side(d, "bottom")?,
+        left: side(&bdr.left)?,
+        right: side(&bdr.right)?,
+        top: side(&bdr.top)?,
+        bottom: side(&bdr.bottom)?,
     })
 }
 
-fn extract_str(d: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<String>> {
-    d.get_item(key)?.map(|v| v.extract::<String>()).transpose()
-}
-
-fn extract_bool(d: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<bool>> {
-    d.get_item(key)?.map(|v| v.extract::<bool>()).transpose()
-}
-
-fn extract_u32(d: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<u32>> {
-    d.get_item(key)?.map(|v| v.extract::<u32>()).transpose()
-}
-
 /// Normalize "#RRGGBB" or "RRGGBB" to "FFRRGGBB" (OOXML ARGB format).
-fn normalize_color(color: &str) -> String {
+/// `theme:N[:tint]` and `indexed:N` colors are validated and kept as is;
+/// `styles` turns them into `theme`/`tint`/`indexed` attributes.
+fn normalize_color(color: &str) -> PyResult<String> {
+    if color.contains(':') {
+        ColorSpec::parse(color).map_err(PyErr::new::<PyValueError, _>)?;
+        return Ok(color.trim().to_ascii_lowercase());
+    }
     let hex = color.trim_start_matches('#');
-    if hex.len() == 6 {
+    Ok(if hex.len() == 6 {
         format!("FF{}", hex.to_uppercase())
     } else if hex.len() == 8 {
         hex.to_uppercase()
     } else {
         format!("FF{hex}")
-    }
+    })
 }
 
 fn minimal_styles_xml() -> String {
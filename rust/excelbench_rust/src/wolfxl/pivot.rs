@@ -0,0 +1,176 @@
+//! Pivot cache source repointing (`XlsxPatcher.repoint_pivot_source`).
+//!
+//! A pivot table reads its data through a pivot cache, and the cache's
+//! `<cacheSource><worksheetSource ref sheet/>` fixes the source range, so
+//! rows appended below it never reach the pivot. Repointing rewrites that
+//! element and sets `refreshOnLoad` on the cache definition, so Excel
+//! rebuilds the stale cached records the next time the workbook opens.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader as XmlReader;
+use quick_xml::Writer as XmlWriter;
+
+use crate::cell_ref::RangeRef;
+use crate::ooxml_util::parts::local_attr;
+
+/// `name` of a `<pivotTableDefinition>` part.
+pub(crate) fn pivot_table_name(xml: &str) -> Result<Option<String>, String> {
+    let mut reader = XmlReader::from_str(xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e))
+                if e.local_name().as_ref() == b"pivotTableDefinition" =>
+            {
+                return Ok(local_attr(&e, b"name"));
+            }
+            Ok(Event::Eof) => return Ok(None),
+            Err(e) => return Err(format!("XML parse error: {e}")),
+            _ => {}
+        }
+    }
+}
+
+/// Copy of `e` without the attributes in `drop`, plus `extra` appended.
+fn rebuilt(e: &BytesStart<'_>, drop: &[&[u8]], extra: &[(&str, &str)]) -> BytesStart<'static> {
+    let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+    let mut out = BytesStart::new(name);
+    for a in e.attributes().with_checks(false).flatten() {
+        if !drop.contains(&a.key.as_ref()) {
+            out.push_attribute(a);
+        }
+    }
+    for attr in extra {
+        out.push_attribute(*attr);
+    }
+    out
+}
+
+/// `<worksheetSource>` pointing at `range`. Without a sheet in `range` the
+/// current `sheet` is kept; a source given as a defined name (`name`) or in
+/// another workbook (`r:id`) can't be repointed that way.
+fn worksheet_source(e: &BytesStart<'_>, range: &RangeRef) -> Result<BytesStart<'static>, String> {
+    if local_attr(e, b"id").is_some() {
+        return Err("The pivot cache reads from another workbook".to_string());
+    }
+    let sheet = match (&range.sheet, local_attr(e, b"sheet")) {
+        (Some(sheet), _) => sheet.clone(),
+        (None, Some(sheet)) => sheet,
+        (None, None) => {
+            return Err(format!(
+                "The pivot cache reads the defined name '{}'; give the new range a sheet",
+                local_attr(e, b"name").unwrap_or_default()
+            ))
+        }
+    };
+    let range = range.to_a1();
+    Ok(rebuilt(
+        e,
+        &[b"ref", b"sheet", b"name"],
+        &[("ref", range.as_str()), ("sheet", sheet.as_str())],
+    ))
+}
+
+/// Only worksheet sources have a range; external and consolidation caches
+/// are left alone.
+fn check_source_type(e: &BytesStart<'_>) -> Result<(), String> {
+    match local_attr(e, b"type").as_deref() {
+        Some("worksheet") | None => Ok(()),
+        Some(kind) => Err(format!(
+            "The pivot cache source is of type '{kind}', not a worksheet range"
+        )),
+    }
+}
+
+/// Point the worksheet source of a `pivotCacheDefinition` part at `range`
+/// and mark the cache for refresh on load.
+pub(crate) fn repoint_cache_source(xml: &str, range: &RangeRef) -> Result<String, String> {
+    let mut reader = XmlReader::from_str(xml);
+    reader.config_mut().trim_text(false);
+    let mut writer = XmlWriter::new(Vec::new());
+    let mut repointed = false;
+
+    loop {
+        let event = match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"pivotCacheDefinition" => {
+                Event::Start(rebuilt(&e, &[b"refreshOnLoad"], &[("refreshOnLoad", "1")]))
+            }
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"cacheSource" => {
+                check_source_type(&e)?;
+                Event::Start(e)
+            }
+            Ok(Event::Empty(e)) if e.local_name().as_ref() == b"cacheSource" => {
+                check_source_type(&e)?;
+                Event::Empty(e)
+            }
+            Ok(Event::Empty(e)) if e.local_name().as_ref() == b"worksheetSource" => {
+                repointed = true;
+                Event::Empty(worksheet_source(&e, range)?)
+            }
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"worksheetSource" => {
+                repointed = true;
+                Event::Start(worksheet_source(&e, range)?)
+            }
+            Ok(e) => e,
+            Err(e) => return Err(format!("XML parse error: {e}")),
+        };
+        writer
+            .write_event(event)
+            .map_err(|e| format!("XML write error: {e}"))?;
+    }
+
+    if !repointed {
+        return Err("The pivot cache has no worksheet source".to_string());
+    }
+    String::from_utf8(writer.into_inner()).map_err(|e| format!("Pivot cache XML not UTF-8: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CACHE: &str = concat!(
+        r#"<pivotCacheDefinition xmlns="main" xmlns:r="rels" r:id="rId1" recordCount="3">"#,
+        r#"<cacheSource type="worksheet"><worksheetSource ref="A1:C4" sheet="Data"/>"#,
+        r#"</cacheSource><cacheFields count="1"><cacheField name="Region"/></cacheFields>"#,
+        r#"</pivotCacheDefinition>"#
+    );
+
+    fn range(s: &str) -> RangeRef {
+        RangeRef::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_pivot_table_name() {
+        let xml = r#"<pivotTableDefinition xmlns="main" name="Summary" cacheId="3"/>"#;
+        assert_eq!(pivot_table_name(xml).unwrap().as_deref(), Some("Summary"));
+        assert_eq!(pivot_table_name("<other/>").unwrap(), None);
+    }
+
+    #[test]
+    fn test_repoint_cache_source() {
+        let out = repoint_cache_source(CACHE, &range("$A$1:$C$20")).unwrap();
+        assert!(out.contains(r#"<worksheetSource ref="A1:C20" sheet="Data"/>"#));
+        assert!(out.contains(r#"r:id="rId1" recordCount="3" refreshOnLoad="1">"#));
+        assert!(out.contains(r#"<cacheField name="Region"/>"#));
+
+        let out = repoint_cache_source(CACHE, &range("'New Data'!B2:D9")).unwrap();
+        assert!(out.contains(r#"<worksheetSource ref="B2:D9" sheet="New Data"/>"#));
+
+        let named = CACHE.replace(r#"ref="A1:C4" sheet="Data""#, r#"name="SalesData""#);
+        assert!(repoint_cache_source(&named, &range("A1:C9")).is_err());
+        let out = repoint_cache_source(&named, &range("Data!A1:C9")).unwrap();
+        assert!(out.contains(r#"<worksheetSource ref="A1:C9" sheet="Data"/>"#));
+    }
+
+    #[test]
+    fn test_repoint_cache_source_rejects_other_sources() {
+        let external = CACHE.replace(r#"type="worksheet""#, r#"type="external""#);
+        assert!(repoint_cache_source(&external, &range("A1:C9")).is_err());
+        let other_book = CACHE.replace(r#"sheet="Data""#, r#"sheet="Data" r:id="rId9""#);
+        assert!(repoint_cache_source(&other_book, &range("A1:C9")).is_err());
+        let no_source =
+            r#"<pivotCacheDefinition><cacheSource type="worksheet"/></pivotCacheDefinition>"#;
+        assert!(repoint_cache_source(no_source, &range("A1:C9")).is_err());
+    }
+}
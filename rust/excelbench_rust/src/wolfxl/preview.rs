@@ -0,0 +1,339 @@
+//! Cheap previews for UIs (`sample()`, `extract_print_preview_metadata()`).
+//!
+//! `sample()` decodes only the first rows of a sheet and stops the stream
+//! there. The layout pass reads the worksheet parts from the ZIP but skips
+//! `<sheetData>` wholesale, so neither walks every cell of a large file.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader as XmlReader;
+use zip::ZipArchive;
+
+use crate::cell_ref::col_to_letters;
+use crate::errors;
+use crate::ooxml_util::{self, attr_value};
+
+use super::reader::{load_context, row_cells_to_py, scan_sheet};
+use super::CAPABILITIES;
+
+/// One `<col>` span; columns are 1-based as in the XML.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ColSpan {
+    pub min: u32,
+    pub max: u32,
+    pub width: Option<f64>,
+    pub hidden: bool,
+}
+
+/// Everything `extract_print_preview_metadata()` reports for one sheet.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SheetLayout {
+    /// `<dimension ref>`, as written (may be stale or absent).
+    pub dimension: Option<String>,
+    pub default_col_width: Option<f64>,
+    pub default_row_height: Option<f64>,
+    pub cols: Vec<ColSpan>,
+    pub merges: Vec<String>,
+    /// Top-left cell of the scrolling pane when panes are frozen.
+    pub freeze: Option<String>,
+    pub orientation: Option<String>,
+    pub paper_size: Option<u32>,
+    pub scale: Option<u32>,
+    pub fit_to_width: Option<u32>,
+    pub fit_to_height: Option<u32>,
+}
+
+fn parsed<T: std::str::FromStr>(e: &BytesStart<'_>, key: &[u8]) -> Option<T> {
+    attr_value(e, key).and_then(|v| v.parse().ok())
+}
+
+/// Read a worksheet's layout, skipping the cell data.
+pub(crate) fn read_sheet_layout<R: BufRead>(src: R) -> Result<SheetLayout, String> {
+    let mut reader = XmlReader::from_reader(src);
+    let mut buf = Vec::new();
+    let mut skip = Vec::new();
+    let mut out = SheetLayout::default();
+
+    loop {
+        let (e, empty) = match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => (e, false),
+            Ok(Event::Empty(e)) => (e, true),
+            Ok(Event::Eof) => return Ok(out),
+            Err(e) => return Err(format!("Failed to parse worksheet XML: {e}")),
+            _ => {
+                buf.clear();
+                continue;
+            }
+        };
+        match e.local_name().as_ref() {
+            b"sheetData" if !empty => {
+                let end = e.to_end().into_owned();
+                reader
+                    .read_to_end_into(end.name(), &mut skip)
+                    .map_err(|e| format!("Failed to parse worksheet XML: {e}"))?;
+                skip.clear();
+            }
+            b"dimension" => out.dimension = attr_value(&e, b"ref"),
+            b"sheetFormatPr" => {
+                out.default_col_width = parsed(&e, b"defaultColWidth");
+                out.default_row_height = parsed(&e, b"defaultRowHeight");
+            }
+            b"col" => {
+                if let (Some(min), Some(max)) = (parsed(&e, b"min"), parsed(&e, b"max")) {
+                    let hidden = attr_value(&e, b"hidden");
+                    out.cols.push(ColSpan {
+                        min,
+                        max,
+                        width: parsed(&e, b"width"),
+                        hidden: matches!(hidden.as_deref(), Some("1") | Some("true")),
+                    });
+                }
+            }
+            b"mergeCell" => {
+                if let Some(r) = attr_value(&e, b"ref") {
+                    out.merges.push(r);
+                }
+            }
+            b"pane" => {
+                let state = attr_value(&e, b"state");
+                if matches!(state.as_deref(), Some("frozen") | Some("frozenSplit")) {
+                    out.freeze = attr_value(&e, b"topLeftCell");
+                }
+            }
+            b"pageSetup" => {
+                out.orientation = attr_value(&e, b"orientation");
+                out.paper_size = parsed(&e, b"paperSize");
+                out.scale = parsed(&e, b"scale");
+                out.fit_to_width = parsed(&e, b"fitToWidth");
+                out.fit_to_height = parsed(&e, b"fitToHeight");
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// `_xlnm.Print_Area` / `_xlnm.Print_Titles` by sheet position.
+pub(crate) fn print_names(
+    workbook_xml: &str,
+) -> Result<HashMap<(usize, &'static str), String>, String> {
+    let mut reader = XmlReader::from_str(workbook_xml);
+    let mut out = HashMap::new();
+    let mut current: Option<(usize, &'static str)> = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"definedName" => {
+                let kind = match attr_value(&e, b"name").as_deref() {
+                    Some("_xlnm.Print_Area") => Some("print_area"),
+                    Some("_xlnm.Print_Titles") => Some("print_titles"),
+                    _ => None,
+                };
+                let sheet = parsed::<usize>(&e, b"localSheetId");
+                current = kind.zip(sheet).map(|(k, s)| (s, k));
+            }
+            Ok(Event::Text(t)) => {
+                if let Some(key) = current.take() {
+                    let text = t
+                        .unescape()
+                        .map_err(|e| format!("Failed to parse workbook.xml: {e}"))?;
+                    out.insert(key, text.into_owned());
+                }
+            }
+            Ok(Event::End(_)) => current = None,
+            Ok(Event::Eof) => return Ok(out),
+            Err(e) => return Err(format!("Failed to parse workbook.xml: {e}")),
+            _ => {}
+        }
+    }
+}
+
+pub(super) fn open_zip(path: &str) -> PyResult<ZipArchive<File>> {
+    let f = File::open(path).map_err(|e| {
+        errors::file_format(
+            CAPABILITIES.backend,
+            path,
+            format!("Cannot open '{path}': {e}"),
+        )
+    })?;
+    ZipArchive::new(f).map_err(|e| {
+        errors::file_format(CAPABILITIES.backend, path, format!("Not a valid ZIP: {e}"))
+    })
+}
+
+/// The first `n_rows` rows of `sheet` as `list[list[dict]]`.
+///
+/// Rows are dense from row 1 and column A, with the `read_cell_value`
+/// payload shape (cached values for formula cells); missing rows are empty
+/// lists and the list ends at the last non-empty row sampled. Decoding stops
+/// once `n_rows` rows have been read.
+#[pyfunction]
+#[pyo3(signature = (path, sheet, n_rows=50))]
+pub(crate) fn sample(py: Python<'_>, path: &str, sheet: &str, n_rows: u32) -> PyResult<PyObject> {
+    let mut zip = open_zip(path)?;
+    let wb_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml")?;
+    let rels_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/_rels/workbook.xml.rels")?;
+    let part = ooxml_util::sheet_part_paths(&wb_xml, &rels_xml)?
+        .into_iter()
+        .find(|(name, _)| name == sheet)
+        .map(|(_, part)| part)
+        .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
+    let ctx = load_context(&mut zip, &rels_xml)?;
+    drop(zip);
+    let date1904 = ooxml_util::workbook_is_date1904(&wb_xml);
+
+    let rows = py
+        .allow_threads(|| {
+            let mut rows = Vec::new();
+            scan_sheet(path, &part, &ctx, |row| {
+                if row.0 >= n_rows {
+                    return false;
+                }
+                rows.push(row);
+                true
+            })
+            .map(|_| rows)
+        })
+        .map_err(PyErr::new::<PyIOError, _>)?;
+
+    let out = PyList::empty(py);
+    for (row, cells) in &rows {
+        while (out.len() as u32) < *row {
+            out.append(PyList::empty(py))?;
+        }
+        out.append(row_cells_to_py(py, cells, date1904)?)?;
+    }
+    Ok(out.into())
+}
+
+/// Layout metadata for previews, per sheet in workbook order.
+///
+/// Each entry is `{"sheet", "dimension", "default_column_width",
+/// "default_row_height", "columns", "merged_ranges", "freeze_panes",
+/// "page_setup", "print_area", "print_titles"}`. `columns` lists the
+/// `<col>` spans as `{"first", "last", "width", "hidden"}` with column
+/// letters; `page_setup` has `orientation`, `paper_size`, `scale`,
+/// `fit_to_width` and `fit_to_height` (None when unset). Cell data is not
+/// decoded.
+#[pyfunction]
+pub(crate) fn extract_print_preview_metadata(py: Python<'_>, path: &str) -> PyResult<PyObject> {
+    let mut zip = open_zip(path)?;
+    let wb_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml")?;
+    let rels_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/_rels/workbook.xml.rels")?;
+    let sheets = ooxml_util::sheet_part_paths(&wb_xml, &rels_xml)?;
+    let names = print_names(&wb_xml).map_err(PyErr::new::<PyIOError, _>)?;
+
+    let out = PyList::empty(py);
+    for (idx, (sheet, part)) in sheets.iter().enumerate() {
+        let entry = zip.by_name(part).map_err(|e| {
+            PyErr::new::<PyIOError, _>(format!("Missing worksheet part {part}: {e}"))
+        })?;
+        let layout =
+            read_sheet_layout(BufReader::new(entry)).map_err(PyErr::new::<PyIOError, _>)?;
+
+        let d = PyDict::new(py);
+        d.set_item("sheet", sheet)?;
+        d.set_item("dimension", &layout.dimension)?;
+        d.set_item("default_column_width", layout.default_col_width)?;
+        d.set_item("default_row_height", layout.default_row_height)?;
+        let columns = PyList::empty(py);
+        for span in &layout.cols {
+            if span.min == 0 || span.max < span.min {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "Invalid <col> span {}-{} in {part}",
+                    span.min, span.max
+                )));
+            }
+            let c = PyDict::new(py);
+            c.set_item("first", col_to_letters(span.min - 1))?;
+            c.set_item("last", col_to_letters(span.max - 1))?;
+            c.set_item("width", span.width)?;
+            c.set_item("hidden", span.hidden)?;
+            columns.append(c)?;
+        }
+        d.set_item("columns", columns)?;
+        d.set_item("merged_ranges", &layout.merges)?;
+        d.set_item("freeze_panes", &layout.freeze)?;
+        let setup = PyDict::new(py);
+        setup.set_item("orientation", &layout.orientation)?;
+        setup.set_item("paper_size", layout.paper_size)?;
+        setup.set_item("scale", layout.scale)?;
+        setup.set_item("fit_to_width", layout.fit_to_width)?;
+        setup.set_item("fit_to_height", layout.fit_to_height)?;
+        d.set_item("page_setup", setup)?;
+        d.set_item("print_area", names.get(&(idx, "print_area")))?;
+        d.set_item("print_titles", names.get(&(idx, "print_titles")))?;
+        out.append(d)?;
+    }
+    Ok(out.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sheet_layout_skips_cells() {
+        let xml = concat!(
+            r#"<worksheet><dimension ref="A1:D40"/>"#,
+            r#"<sheetViews><sheetView><pane ySplit="1" topLeftCell="A2" state="frozen"/>"#,
+            r#"</sheetView></sheetViews><sheetFormatPr defaultRowHeight="15"/>"#,
+            r#"<cols><col min="1" max="1" width="20.5" customWidth="1"/>"#,
+            r#"<col min="2" max="4" width="9" hidden="1"/></cols>"#,
+            r#"<sheetData><row r="1"><c r="A1" t="inlineStr"><is><t>x</t></is></c></row>"#,
+            r#"<row r="2"><c r="A2"><v>1</v></c></row></sheetData>"#,
+            r#"<mergeCells count="1"><mergeCell ref="A1:D1"/></mergeCells>"#,
+            r#"<pageSetup orientation="landscape" paperSize="9" fitToHeight="0"/>"#,
+            r#"</worksheet>"#
+        );
+        let layout = read_sheet_layout(xml.as_bytes()).unwrap();
+        assert_eq!(layout.dimension.as_deref(), Some("A1:D40"));
+        assert_eq!(layout.default_row_height, Some(15.0));
+        assert_eq!(layout.default_col_width, None);
+        assert_eq!(
+            layout.cols,
+            vec![
+                ColSpan {
+                    min: 1,
+                    max: 1,
+                    width: Some(20.5),
+                    hidden: false
+                },
+                ColSpan {
+                    min: 2,
+                    max: 4,
+                    width: Some(9.0),
+                    hidden: true
+                },
+            ]
+        );
+        assert_eq!(layout.merges, vec!["A1:D1".to_string()]);
+        assert_eq!(layout.freeze.as_deref(), Some("A2"));
+        assert_eq!(layout.orientation.as_deref(), Some("landscape"));
+        assert_eq!(
+            (layout.paper_size, layout.fit_to_height),
+            (Some(9), Some(0))
+        );
+    }
+
+    #[test]
+    fn test_print_names_by_sheet() {
+        let xml = concat!(
+            r#"<workbook><definedNames>"#,
+            r#"<definedName name="_xlnm.Print_Area" localSheetId="1">"#,
+            r#"'B'!$A$1:$F$20</definedName>"#,
+            r#"<definedName name="_xlnm.Print_Titles" localSheetId="0">A!$1:$1</definedName>"#,
+            r#"<definedName name="Total">A!$B$2</definedName>"#,
+            r#"</definedNames></workbook>"#
+        );
+        let names = print_names(xml).unwrap();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[&(1, "print_area")], "'B'!$A$1:$F$20");
+        assert_eq!(names[&(0, "print_titles")], "A!$1:$1");
+    }
+}
@@ -0,0 +1,639 @@
+//! WolfXL read path ("wolfxl-read").
+//!
+//! A calamine-free xlsx reader: worksheets are decoded straight out of the ZIP
+//! by [`sheet_reader`](super::sheet_reader), with shared strings and cell
+//! styles parsed by the same modules the patcher uses. Payload dicts match
+//! `CalamineBook`, so the two readers can be scored side by side.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::Arc;
+use std::thread;
+
+use chrono::NaiveTime;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use zip::ZipArchive;
+
+use crate::backend::{self, pyclass_object, Backend, BackendEntry, ExcelReadBackend};
+use crate::capabilities::BackendCapabilities;
+use crate::cell_ref::RangeRef;
+use crate::errors;
+use crate::numfmt;
+use crate::ooxml_util;
+use crate::profile;
+use crate::util::{
+    a1_to_row_col, cell_blank, cell_with_value, excel_serial_to_datetime, parse_iso_date,
+    parse_iso_datetime,
+};
+
+use super::shared_strings::parse_shared_strings;
+use super::sheet_reader::{read_sheet_rows, ReadCell, ReadContext, ReadRow, ReadValue};
+use super::styles::{parse_cellxfs, parse_num_fmts};
+
+/// Rows per batch handed from the decoder thread to `XlsxRowIter`.
+const ROW_BATCH: usize = 256;
+/// Batches buffered ahead of the consumer before the decoder blocks.
+const ROW_CHANNEL_DEPTH: usize = 4;
+
+type RowBatch = Result<Vec<ReadRow>, String>;
+
+// ---------------------------------------------------------------------------
+// Payload conversion
+// ---------------------------------------------------------------------------
+
+fn datetime_payload(py: Python<'_>, serial: f64, date1904: bool) -> PyResult<PyObject> {
+    // Midnight surfaces as a DATE, anything else as a DATETIME.
+    match excel_serial_to_datetime(serial, date1904) {
+        Some(ndt) if ndt.time() == NaiveTime::MIN => {
+            cell_with_value(py, "date", ndt.date().format("%Y-%m-%d").to_string())
+        }
+        Some(ndt) => cell_with_value(py, "datetime", ndt.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        None => cell_with_value(py, "number", serial),
+    }
+}
+
+fn value_to_py(py: Python<'_>, value: &ReadValue, date1904: bool) -> PyResult<PyObject> {
+    match value {
+        ReadValue::Empty => cell_blank(py),
+        ReadValue::Number(n) => cell_with_value(py, "number", *n),
+        ReadValue::Date(serial) => datetime_payload(py, *serial, date1904),
+        ReadValue::DateIso(s) => {
+            let raw = s.trim_end_matches('Z');
+            if let Some(d) = parse_iso_date(raw) {
+                cell_with_value(py, "date", d.format("%Y-%m-%d").to_string())
+            } else if let Some(ndt) = parse_iso_datetime(raw) {
+                if ndt.time() == NaiveTime::MIN {
+                    cell_with_value(py, "date", ndt.date().format("%Y-%m-%d").to_string())
+                } else {
+                    cell_with_value(py, "datetime", ndt.format("%Y-%m-%dT%H:%M:%S").to_string())
+                }
+            } else {
+                cell_with_value(py, "datetime", s.clone())
+            }
+        }
+        ReadValue::String(s) => cell_with_value(py, "string", s.clone()),
+        ReadValue::Bool(b) => cell_with_value(py, "boolean", *b),
+        ReadValue::Error(e) => cell_with_value(py, "error", e.clone()),
+    }
+}
+
+/// The text Excel shows for a value through the format `code`.
+pub(super) fn display_text(
+    value: &ReadValue,
+    code: &str,
+    date1904: bool,
+    locale: &numfmt::Locale,
+) -> String {
+    use numfmt::CellValue;
+    let value = match value {
+        ReadValue::Empty => return String::new(),
+        ReadValue::Number(n) => CellValue::Number(*n),
+        ReadValue::Date(serial) => CellValue::Number(numfmt::serial_1900(*serial, date1904)),
+        ReadValue::DateIso(s) => match numfmt::iso_serial(s) {
+            Some(serial) => CellValue::Number(serial),
+            None => CellValue::Text(s),
+        },
+        ReadValue::String(s) => CellValue::Text(s),
+        ReadValue::Bool(b) => CellValue::Bool(*b),
+        ReadValue::Error(e) => CellValue::Error(e),
+    };
+    numfmt::format_value(value, code, locale)
+}
+
+/// Payload for one cell, formula-aware (same shape as `CalamineBook`).
+pub(super) fn cell_to_py(
+    py: Python<'_>,
+    cell: Option<&ReadCell>,
+    date1904: bool,
+) -> PyResult<PyObject> {
+    let Some(cell) = cell else {
+        return cell_blank(py);
+    };
+    let Some(formula) = cell.formula.as_deref() else {
+        return value_to_py(py, &cell.value, date1904);
+    };
+
+    let d = PyDict::new(py);
+    match &cell.value {
+        // Error results surface as error cells, but keep the formula.
+        ReadValue::Error(e) => {
+            d.set_item("type", "error")?;
+            d.set_item("value", e)?;
+            d.set_item("formula", formula)?;
+            d.set_item("cached", true)?;
+        }
+        ReadValue::Empty => {
+            d.set_item("type", "formula")?;
+            d.set_item("formula", formula)?;
+            d.set_item("value", formula)?;
+            d.set_item("cached", false)?;
+        }
+        cached => {
+            let inner = value_to_py(py, cached, date1904)?;
+            let inner = inner.downcast_bound::<PyDict>(py)?;
+            d.set_item("type", "formula")?;
+            d.set_item("formula", formula)?;
+            d.set_item("value", inner.get_item("value")?)?;
+            d.set_item("value_type", inner.get_item("type")?)?;
+            d.set_item("cached", true)?;
+        }
+    }
+    Ok(d.into())
+}
+
+// ---------------------------------------------------------------------------
+// Workbook loading
+// ---------------------------------------------------------------------------
+
+/// A fully decoded worksheet: non-empty rows in order, cells sorted by column.
+struct SheetData {
+    rows: Vec<ReadRow>,
+}
+
+impl SheetData {
+    fn cell(&self, row: u32, col: u32) -> Option<&ReadCell> {
+        let i = self.rows.binary_search_by_key(&row, |(r, _)| *r).ok()?;
+        let cells = &self.rows[i].1;
+        let j = cells.binary_search_by_key(&col, |c| c.col).ok()?;
+        Some(&cells[j])
+    }
+
+    /// Inclusive 0-based (r0, c0, r1, c1) of the used cells.
+    fn bounds(&self) -> Option<(u32, u32, u32, u32)> {
+        let r0 = self.rows.first()?.0;
+        let r1 = self.rows.last()?.0;
+        let c0 = self
+            .rows
+            .iter()
+            .filter_map(|(_, c)| c.first())
+            .map(|c| c.col)
+            .min()?;
+        let c1 = self
+            .rows
+            .iter()
+            .filter_map(|(_, c)| c.last())
+            .map(|c| c.col)
+            .max()?;
+        Some((r0, c0, r1, c1))
+    }
+}
+
+/// Workbook part path for a relationship type, falling back to the usual name.
+pub(super) fn workbook_part(rels_xml: &str, rel_type: &str, default: &str) -> PyResult<String> {
+    let target = ooxml_util::relationship_target_by_type(rels_xml, rel_type)?;
+    Ok(match target {
+        Some(target) => ooxml_util::join_and_normalize("xl/", &target),
+        None => default.to_string(),
+    })
+}
+
+/// Build the shared-string table and date-style lookup for a workbook.
+pub(super) fn load_context(zip: &mut ZipArchive<File>, rels_xml: &str) -> PyResult<ReadContext> {
+    let sst_path = workbook_part(rels_xml, "sharedStrings", "xl/sharedStrings.xml")?;
+    let styles_path = workbook_part(rels_xml, "styles", "xl/styles.xml")?;
+
+    let shared_strings = ooxml_util::zip_read_to_string_opt(zip, &sst_path)?
+        .map(|xml| parse_shared_strings(&xml))
+        .unwrap_or_default();
+
+    let (date_styles, num_formats) = match ooxml_util::zip_read_to_string_opt(zip, &styles_path)? {
+        Some(xml) => {
+            let custom = parse_num_fmts(&xml);
+            parse_cellxfs(&xml)
+                .iter()
+                .map(|xf| {
+                    let code = custom.get(&xf.num_fmt_id).map(String::as_str);
+                    (
+                        numfmt::is_date_format_id(xf.num_fmt_id, code),
+                        code.or_else(|| numfmt::builtin_format_code(xf.num_fmt_id))
+                            .unwrap_or("General")
+                            .to_string(),
+                    )
+                })
+                .unzip()
+        }
+        None => (Vec::new(), Vec::new()),
+    };
+
+    Ok(ReadContext {
+        shared_strings,
+        date_styles,
+        num_formats,
+    })
+}
+
+/// Decode one worksheet part, streaming rows into `on_row`.
+pub(super) fn scan_sheet(
+    file_path: &str,
+    part: &str,
+    ctx: &ReadContext,
+    on_row: impl FnMut(ReadRow) -> bool,
+) -> Result<(), String> {
+    let _span = profile::span("wolfxl_read.parse");
+    let f = File::open(file_path).map_err(|e| format!("Cannot open '{file_path}': {e}"))?;
+    let mut zip = ZipArchive::new(f).map_err(|e| format!("Not a valid ZIP: {e}"))?;
+    let entry = zip
+        .by_name(part)
+        .map_err(|e| format!("Missing worksheet part {part}: {e}"))?;
+    read_sheet_rows(BufReader::new(entry), ctx, on_row)
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 classes
+// ---------------------------------------------------------------------------
+
+pub(crate) const CAPABILITIES: BackendCapabilities = BackendCapabilities {
+    class: "XlsxReader",
+    backend: "wolfxl",
+    extensions: &[".xlsx", ".xlsm"],
+    read: &["cell_values", "formulas", "multiple_sheets"],
+    write: &[],
+    modify: false,
+};
+
+#[pyclass]
+pub struct XlsxReader {
+    file_path: String,
+    /// Sheet name → worksheet ZIP path, in workbook order.
+    sheet_paths: Vec<(String, String)>,
+    ctx: Arc<ReadContext>,
+    date1904: bool,
+    /// Decoded sheets kept for random access (`read_cell_value`, `read_range`).
+    cache: HashMap<String, SheetData>,
+    closed: bool,
+}
+
+pub(crate) const BACKEND: BackendEntry = BackendEntry {
+    name: "wolfxl-read",
+    open: |_py, path| {
+        let path = backend::require_path("wolfxl-read", path)?;
+        Ok(Box::new(XlsxReader::open(path)?))
+    },
+};
+
+impl Backend for XlsxReader {
+    fn into_py_object(self: Box<Self>, py: Python<'_>) -> PyResult<PyObject> {
+        pyclass_object(py, *self)
+    }
+
+    fn as_reader(&mut self) -> Option<&mut dyn ExcelReadBackend> {
+        Some(self)
+    }
+}
+
+impl ExcelReadBackend for XlsxReader {
+    fn sheet_names(&self) -> PyResult<Vec<String>> {
+        Ok(XlsxReader::sheet_names(self))
+    }
+
+    fn read_cell_value(&mut self, py: Python<'_>, sheet: &str, a1: &str) -> PyResult<PyObject> {
+        XlsxReader::read_cell_value(self, py, sheet, a1)
+    }
+
+    fn used_cells(&mut self, py: Python<'_>, sheet: &str) -> PyResult<Vec<(u32, u32)>> {
+        let data = self.cached_sheet(py, sheet)?;
+        Ok(data
+            .rows
+            .iter()
+            .flat_map(|(row, cells)| {
+                cells
+                    .iter()
+                    .filter(|c| c.formula.is_some() || !matches!(c.value, ReadValue::Empty))
+                    .map(move |c| (*row, c.col))
+            })
+            .collect())
+    }
+}
+
+#[pymethods]
+impl XlsxReader {
+    /// Open an xlsx file for reading.
+    ///
+    /// Only workbook-level parts (workbook, rels, shared strings, styles) are
+    /// parsed up front; worksheets are decoded on first access.
+    #[staticmethod]
+    pub fn open(path: &str) -> PyResult<Self> {
+        let _span = profile::span("wolfxl_read.parse");
+        let f = File::open(path).map_err(|e| {
+            errors::file_format(
+                CAPABILITIES.backend,
+                path,
+                format!("Cannot open '{path}': {e}"),
+            )
+        })?;
+        let mut zip = ZipArchive::new(f).map_err(|e| {
+            errors::file_format(CAPABILITIES.backend, path, format!("Not a valid ZIP: {e}"))
+        })?;
+
+        let wb_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml")?;
+        let rels_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/_rels/workbook.xml.rels")?;
+        let sheet_paths = ooxml_util::sheet_part_paths(&wb_xml, &rels_xml)?;
+        let ctx = load_context(&mut zip, &rels_xml)?;
+
+        Ok(XlsxReader {
+            file_path: path.to_string(),
+            sheet_paths,
+            ctx: Arc::new(ctx),
+            date1904: ooxml_util::workbook_is_date1904(&wb_xml),
+            cache: HashMap::new(),
+            closed: false,
+        })
+    }
+
+    pub fn sheet_names(&self) -> Vec<String> {
+        self.sheet_paths
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Whether the workbook uses the 1904 date system.
+    pub fn date1904(&self) -> bool {
+        self.date1904
+    }
+
+    pub fn read_cell_value(&mut self, py: Python<'_>, sheet: &str, a1: &str) -> PyResult<PyObject> {
+        let (row, col) =
+            a1_to_row_col(a1).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a1, msg))?;
+        let data = self.cached_sheet(py, sheet)?;
+        cell_to_py(py, data.cell(row, col), self.date1904)
+    }
+
+    /// The cell's value as Excel displays it through its number format
+    /// (`"1,234.50"`, `"(12.5%)"`, `"Mar 5, 2024"`); `""` for an empty cell or
+    /// a formula without a cached value. `locale` (`"de-DE"`, `"fr"`) sets the
+    /// decimal and thousands separators.
+    #[pyo3(signature = (sheet, a1, locale=None))]
+    pub fn format_cell_display(
+        &mut self,
+        py: Python<'_>,
+        sheet: &str,
+        a1: &str,
+        locale: Option<&str>,
+    ) -> PyResult<String> {
+        let locale = locale
+            .map(numfmt::Locale::from_tag)
+            .transpose()
+            .map_err(PyErr::new::<PyValueError, _>)?
+            .unwrap_or_default();
+        let (row, col) =
+            a1_to_row_col(a1).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a1, msg))?;
+        let (ctx, date1904) = (Arc::clone(&self.ctx), self.date1904);
+        let data = self.cached_sheet(py, sheet)?;
+        Ok(data.cell(row, col).map_or_else(String::new, |cell| {
+            let code = ctx
+                .num_formats
+                .get(cell.style)
+                .map_or("General", String::as_str);
+            display_text(&cell.value, code, date1904, &locale)
+        }))
+    }
+
+    /// Read an A1 range (e.g. `"A1:C10"`) as `list[list[dict]]`.
+    pub fn read_range(
+        &mut self,
+        py: Python<'_>,
+        sheet: &str,
+        a1_range: &str,
+    ) -> PyResult<PyObject> {
+        let bounds = RangeRef::parse(a1_range)
+            .map(|r| r.bounds())
+            .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, a1_range, msg))?;
+        let date1904 = self.date1904;
+        let data = self.cached_sheet(py, sheet)?;
+        rows_to_py(py, data, bounds, date1904)
+    }
+
+    /// Read the sheet's used range as `list[list[dict]]`.
+    pub fn read_sheet(&mut self, py: Python<'_>, sheet: &str) -> PyResult<PyObject> {
+        let date1904 = self.date1904;
+        let data = self.cached_sheet(py, sheet)?;
+        match data.bounds() {
+            Some(bounds) => rows_to_py(py, data, bounds, date1904),
+            None => Ok(PyList::empty(py).into()),
+        }
+    }
+
+    /// Stream the sheet row by row without caching it.
+    ///
+    /// Rows are decoded on a background thread and handed over in bounded
+    /// batches, with the GIL released while waiting. Each item is a
+    /// `list[dict]` starting at column A; rows start at row 1 and gaps are
+    /// yielded as empty lists. Formula cells yield their cached values.
+    pub fn iter_rows(&self, sheet: &str) -> PyResult<XlsxRowIter> {
+        let part = self.sheet_part(sheet)?.to_string();
+        let (tx, rx) = sync_channel(ROW_CHANNEL_DEPTH);
+        let file_path = self.file_path.clone();
+        let ctx = Arc::clone(&self.ctx);
+        thread::spawn(move || {
+            if let Err(e) = stream_rows(&file_path, &part, &ctx, &tx) {
+                let _ = tx.send(Err(e));
+            }
+        });
+        Ok(XlsxRowIter {
+            rx: Some(rx),
+            pending: VecDeque::new(),
+            next_row: 0,
+            done: false,
+            date1904: self.date1904,
+        })
+    }
+
+    /// Drop cached sheet data (all sheets when `sheet` is None).
+    #[pyo3(signature = (sheet=None))]
+    pub fn invalidate(&mut self, sheet: Option<&str>) {
+        match sheet {
+            Some(name) => {
+                self.cache.remove(name);
+            }
+            None => self.cache.clear(),
+        }
+    }
+
+    /// Drop decoded sheets and the shared-string/style context. The file is
+    /// only opened per read, so no handle outlives a call; later reads raise
+    /// ValueError.
+    pub fn close(&mut self) {
+        self.cache.clear();
+        self.ctx = Arc::new(ReadContext::default());
+        self.closed = true;
+    }
+
+    pub fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (_exc_type=None, _exc_value=None, _traceback=None))]
+    pub fn __exit__(
+        &mut self,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> bool {
+        self.close();
+        false
+    }
+}
+
+impl XlsxReader {
+    fn sheet_part(&self, sheet: &str) -> PyResult<&str> {
+        if self.closed {
+            return Err(errors::closed(CAPABILITIES.backend));
+        }
+        self.sheet_paths
+            .iter()
+            .find(|(name, _)| name == sheet)
+            .map(|(_, part)| part.as_str())
+            .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))
+    }
+
+    /// Decode (once) and return a sheet's cells; parsing runs without the GIL.
+    fn cached_sheet(&mut self, py: Python<'_>, sheet: &str) -> PyResult<&SheetData> {
+        if !self.cache.contains_key(sheet) {
+            let part = self.sheet_part(sheet)?;
+            let (file_path, ctx) = (self.file_path.as_str(), self.ctx.as_ref());
+            let rows = py
+                .allow_threads(|| {
+                    let mut rows = Vec::new();
+                    scan_sheet(file_path, part, ctx, |row| {
+                        rows.push(row);
+                        true
+                    })
+                    .map(|_| rows)
+                })
+                .map_err(PyErr::new::<PyIOError, _>)?;
+            self.cache.insert(sheet.to_string(), SheetData { rows });
+        }
+        Ok(&self.cache[sheet])
+    }
+}
+
+/// Convert an inclusive 0-based rectangle of a sheet into `list[list[dict]]`.
+fn rows_to_py(
+    py: Python<'_>,
+    data: &SheetData,
+    bounds: (u32, u32, u32, u32),
+    date1904: bool,
+) -> PyResult<PyObject> {
+    let _span = profile::span("wolfxl_read.convert");
+    let (r0, c0, r1, c1) = bounds;
+    let outer = PyList::empty(py);
+    for row in r0..=r1 {
+        let inner = PyList::empty(py);
+        for col in c0..=c1 {
+            inner.append(cell_to_py(py, data.cell(row, col), date1904)?)?;
+        }
+        outer.append(inner)?;
+    }
+    Ok(outer.into())
+}
+
+/// Decode a worksheet and push row batches into `tx`.
+///
+/// Returns early (without error) once the consumer hangs up.
+fn stream_rows(
+    file_path: &str,
+    part: &str,
+    ctx: &ReadContext,
+    tx: &SyncSender<RowBatch>,
+) -> Result<(), String> {
+    let mut batch: Vec<ReadRow> = Vec::with_capacity(ROW_BATCH);
+    let mut connected = true;
+    scan_sheet(file_path, part, ctx, |row| {
+        batch.push(row);
+        if batch.len() >= ROW_BATCH {
+            connected = tx.send(Ok(std::mem::take(&mut batch))).is_ok();
+        }
+        connected
+    })?;
+    if connected && !batch.is_empty() {
+        let _ = tx.send(Ok(batch));
+    }
+    Ok(())
+}
+
+/// Python iterator returned by `XlsxReader.iter_rows()`.
+#[pyclass(unsendable)]
+pub struct XlsxRowIter {
+    /// Taken while blocked in `recv()` so the wait can run without the GIL.
+    rx: Option<Receiver<RowBatch>>,
+    pending: VecDeque<ReadRow>,
+    /// 0-based index of the next row to yield (used to fill gaps).
+    next_row: u32,
+    done: bool,
+    date1904: bool,
+}
+
+#[pymethods]
+impl XlsxRowIter {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        loop {
+            if let Some((row, _)) = self.pending.front() {
+                if *row > self.next_row {
+                    self.next_row += 1;
+                    return Ok(Some(PyList::empty(py).into()));
+                }
+                let (_, cells) = self.pending.pop_front().unwrap();
+                self.next_row += 1;
+                return row_cells_to_py(py, &cells, self.date1904).map(Some);
+            }
+            if self.done {
+                return Ok(None);
+            }
+
+            let rx = match self.rx.take() {
+                Some(rx) => rx,
+                None => return Ok(None),
+            };
+            let (rx, msg) = py.allow_threads(move || {
+                let msg = rx.recv();
+                (rx, msg)
+            });
+            self.rx = Some(rx);
+
+            match msg {
+                Ok(Ok(rows)) => self.pending.extend(rows),
+                Ok(Err(e)) => {
+                    self.done = true;
+                    return Err(PyErr::new::<PyIOError, _>(e));
+                }
+                // Decoder finished and dropped its sender.
+                Err(_) => self.done = true,
+            }
+        }
+    }
+
+    /// Stop the background decoder and drop buffered rows.
+    fn close(&mut self) {
+        self.rx = None;
+        self.pending.clear();
+        self.done = true;
+    }
+}
+
+/// Convert a row's cells into a dense `list[dict]` starting at column A.
+pub(super) fn row_cells_to_py(
+    py: Python<'_>,
+    cells: &[ReadCell],
+    date1904: bool,
+) -> PyResult<PyObject> {
+    let out = PyList::empty(py);
+    let mut next_col = 0u32;
+    for cell in cells {
+        while next_col < cell.col {
+            out.append(cell_blank(py)?)?;
+            next_col += 1;
+        }
+        out.append(value_to_py(py, &cell.value, date1904)?)?;
+        next_col = cell.col + 1;
+    }
+    Ok(out.into())
+}
@@ -0,0 +1,369 @@
+//! Reference remapping for moved ranges (`XlsxPatcher.remap_references`).
+//!
+//! A [`Remap`] records that the cells of some source ranges on one sheet now
+//! live elsewhere on that sheet. Every `<f>` in the workbook is rewritten so
+//! references into a source follow the move: A1 and R1C1 forms, `$`-anchored
+//! or not, unqualified (from the moved sheet itself) or sheet-qualified from
+//! any sheet. A range reference moves only when it lies entirely inside one
+//! source, as Excel does for cut-and-paste. External-workbook and 3-D
+//! (`Sheet1:Sheet3!A1`) references are left alone.
+
+use std::collections::HashMap;
+
+use quick_xml::events::{BytesStart, BytesText, Event};
+use quick_xml::Reader as XmlReader;
+use quick_xml::Writer as XmlWriter;
+
+use crate::cell_ref::{CellRef, RangeKind, RangeRef};
+use crate::formula;
+use crate::ooxml_util::attr_value;
+
+use super::shared_formula::{self, SharedMaster};
+
+/// One moved block: cells inside `source` shift by (`rows`, `cols`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeMove {
+    pub source: RangeRef,
+    pub rows: i64,
+    pub cols: i64,
+}
+
+impl RangeMove {
+    /// Move `source` so its top-left corner lands on `dest`.
+    pub fn new(source: RangeRef, dest: CellRef) -> Self {
+        let rows = i64::from(dest.row) - i64::from(source.start.row);
+        let cols = i64::from(dest.col) - i64::from(source.start.col);
+        Self { source, rows, cols }
+    }
+}
+
+/// The moves recorded by one `remap_references` call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Remap {
+    /// Sheet the moved cells live on.
+    pub sheet: String,
+    /// Checked in order; the first source containing a reference wins.
+    pub moves: Vec<RangeMove>,
+}
+
+impl Remap {
+    /// New position of `cell`: None when no source contains it, `Some(None)`
+    /// when the move pushes it off the sheet.
+    fn cell(&self, cell: &CellRef) -> Option<Option<CellRef>> {
+        let m = self
+            .moves
+            .iter()
+            .find(|m| m.source.contains(cell.row, cell.col))?;
+        Some(cell.offset(m.rows, m.cols))
+    }
+
+    /// New position of `range`, under the same rules as [`Remap::cell`].
+    fn range(&self, range: &RangeRef) -> Option<Option<RangeRef>> {
+        let m = self.moves.iter().find(|m| {
+            m.source.contains(range.start.row, range.start.col)
+                && m.source.contains(range.end.row, range.end.col)
+        })?;
+        Some(range.offset(m.rows, m.cols))
+    }
+
+    fn applies_to(&self, sheet: &str) -> bool {
+        sheet.to_lowercase() == self.sheet.to_lowercase()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Formula text
+// ---------------------------------------------------------------------------
+
+/// Rewrite the references in `formula` (no leading `=`) that point into a
+/// moved source.
+///
+/// `home_sheet` is the sheet the formula lives on (the target of unqualified
+/// references) and `base` its 0-based (row, col), which R1C1 relative
+/// offsets are measured from. References moved off the sheet become `#REF!`.
+pub fn remap_formula(formula: &str, home_sheet: &str, base: (u32, u32), remap: &Remap) -> String {
+    formula::map_references(formula, |r, text| {
+        if r.is_external() || r.is_3d() {
+            return None;
+        }
+        if !remap.applies_to(r.sheet.as_deref().unwrap_or(home_sheet)) {
+            return None;
+        }
+        let target = if r.r1c1 {
+            remap_r1c1(&r.target, base, remap)?
+        } else {
+            remap_a1(&r.target, remap)?
+        };
+        Some(formula::replace_target(text, r, &target))
+    })
+}
+
+fn remap_a1(token: &str, remap: &Remap) -> Option<String> {
+    let range = RangeRef::parse(token).ok()?;
+    // Bare `A` or `1` would parse as a whole column/row; in a formula those
+    // are names or numbers.
+    if range.kind != RangeKind::Cells && !token.contains(':') {
+        return None;
+    }
+    if !token.contains(':') {
+        let moved = remap.cell(&range.start)?;
+        return Some(moved.map_or_else(|| "#REF!".to_string(), |c| c.to_string()));
+    }
+    let moved = remap.range(&range)?;
+    Some(match moved {
+        None => "#REF!".to_string(),
+        // Keep `A1:A1` spelled out rather than collapsing it to `A1`.
+        Some(r) if r.kind == RangeKind::Cells => format!("{}:{}", r.start, r.end),
+        Some(r) => r.to_string(),
+    })
+}
+
+fn remap_r1c1(token: &str, base: (u32, u32), remap: &Remap) -> Option<String> {
+    let render = |c: Option<CellRef>| c.map_or_else(|| "#REF!".to_string(), |c| c.to_r1c1(base));
+    match token.split_once(':') {
+        None => {
+            let cell = CellRef::from_r1c1(token, base).ok()?;
+            Some(render(remap.cell(&cell)?))
+        }
+        Some((a, b)) => {
+            let start = CellRef::from_r1c1(a, base).ok()?;
+            let end = CellRef::from_r1c1(b, base).ok()?;
+            let m = remap.moves.iter().find(|m| {
+                m.source.contains(start.row, start.col) && m.source.contains(end.row, end.col)
+            })?;
+            let moved = start.offset(m.rows, m.cols).zip(end.offset(m.rows, m.cols));
+            Some(match moved {
+                Some((s, e)) => format!("{}:{}", s.to_r1c1(base), e.to_r1c1(base)),
+                None => "#REF!".to_string(),
+            })
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Worksheet XML
+// ---------------------------------------------------------------------------
+
+/// Rewrite every `<f>` in a worksheet of `home_sheet`.
+///
+/// Shared-formula masters are rewritten in place. A dependent keeps its bare
+/// `<f t="shared" si=".."/>` when the rewritten master still derives the
+/// right formula for it, and is detached into an ordinary formula otherwise.
+pub fn remap_worksheet(xml: &str, home_sheet: &str, remap: &Remap) -> Result<String, String> {
+    let masters = shared_formula::scan_masters(xml)?;
+    let remapped_masters: HashMap<String, SharedMaster> = masters
+        .iter()
+        .map(|(si, m)| {
+            let base = (m.row.saturating_sub(1), m.col.saturating_sub(1));
+            let formula = remap_formula(&m.formula, home_sheet, base, remap);
+            (
+                si.clone(),
+                SharedMaster {
+                    formula,
+                    ..m.clone()
+                },
+            )
+        })
+        .collect();
+    // Formula for a shared dependent at `cell` when it can no longer derive
+    // from its master, or None to keep it shared.
+    let detached_formula = |e: &BytesStart<'_>, cell: (u32, u32)| -> Option<String> {
+        if attr_value(e, b"ref").is_some() {
+            return None;
+        }
+        let si = shared_formula::shared_si(e)?;
+        let (old, new) = (masters.get(&si)?, remapped_masters.get(&si)?);
+        let base = (cell.0.saturating_sub(1), cell.1.saturating_sub(1));
+        let expected = remap_formula(&old.formula_at(cell.0, cell.1), home_sheet, base, remap);
+        (expected != new.formula_at(cell.0, cell.1)).then_some(expected)
+    };
+
+    let mut reader = XmlReader::from_str(xml);
+    reader.config_mut().trim_text(false);
+    let mut writer = XmlWriter::new(Vec::new());
+    let mut buf: Vec<u8> = Vec::new();
+    let write_err = |e: std::io::Error| format!("XML write error: {e}");
+
+    // 1-based (row, col) of the open <c>.
+    let mut cell: (u32, u32) = (0, 0);
+    // Events inside the open <f> and its unescaped text.
+    let mut open_f: Option<(Vec<Event<'static>>, String)> = None;
+    // Dropping the original text of a detached dependent.
+    let mut skip_f = false;
+
+    loop {
+        let event = match reader.read_event_into(&mut buf) {
+            Ok(Event::Eof) => break,
+            Ok(e) => e.into_owned(),
+            Err(e) => return Err(format!("XML parse error: {e}")),
+        };
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) if e.local_name().as_ref() == b"c" => {
+                cell = shared_formula::cell_position(e);
+                writer.write_event(event).map_err(write_err)?;
+            }
+            Event::Start(ref e) if e.local_name().as_ref() == b"f" => {
+                if let Some(formula) = detached_formula(e, cell) {
+                    let start = shared_formula::detached_f(e);
+                    writer.write_event(Event::Start(start)).map_err(write_err)?;
+                    writer
+                        .write_event(Event::Text(BytesText::new(&formula)))
+                        .map_err(write_err)?;
+                    skip_f = true;
+                } else {
+                    writer.write_event(event).map_err(write_err)?;
+                    open_f = Some((Vec::new(), String::new()));
+                }
+            }
+            Event::Empty(ref e) if e.local_name().as_ref() == b"f" => {
+                if let Some(formula) = detached_formula(e, cell) {
+                    let end = e.to_end().into_owned();
+                    let start = shared_formula::detached_f(e);
+                    writer.write_event(Event::Start(start)).map_err(write_err)?;
+                    writer
+                        .write_event(Event::Text(BytesText::new(&formula)))
+                        .map_err(write_err)?;
+                    writer.write_event(Event::End(end)).map_err(write_err)?;
+                } else {
+                    writer.write_event(event).map_err(write_err)?;
+                }
+            }
+            Event::End(ref e) if e.local_name().as_ref() == b"f" => {
+                skip_f = false;
+                if let Some((events, text)) = open_f.take() {
+                    let base = (cell.0.saturating_sub(1), cell.1.saturating_sub(1));
+                    let remapped = remap_formula(&text, home_sheet, base, remap);
+                    if remapped == text {
+                        for ev in events {
+                            writer.write_event(ev).map_err(write_err)?;
+                        }
+                    } else {
+                        writer
+                            .write_event(Event::Text(BytesText::new(&remapped)))
+                            .map_err(write_err)?;
+                    }
+                }
+                writer.write_event(event).map_err(write_err)?;
+            }
+            _ if skip_f => {}
+            Event::Text(ref t) if open_f.is_some() => {
+                let unescaped = t.unescape().map_err(|e| format!("XML parse error: {e}"))?;
+                if let Some((events, text)) = open_f.as_mut() {
+                    text.push_str(&unescaped);
+                    events.push(event.clone());
+                }
+            }
+            Event::CData(ref t) if open_f.is_some() => {
+                let raw = String::from_utf8_lossy(t).into_owned();
+                if let Some((events, text)) = open_f.as_mut() {
+                    text.push_str(&raw);
+                    events.push(event.clone());
+                }
+            }
+            _ => writer.write_event(event).map_err(write_err)?,
+        }
+        buf.clear();
+    }
+
+    String::from_utf8(writer.into_inner()).map_err(|e| format!("UTF-8 error: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remap(moves: &[(&str, &str)]) -> Remap {
+        Remap {
+            sheet: "Data".to_string(),
+            moves: moves
+                .iter()
+                .map(|(src, dst)| {
+                    RangeMove::new(RangeRef::parse(src).unwrap(), CellRef::parse(dst).unwrap())
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_remap_a1_cells_and_ranges() {
+        let r = remap(&[("A1:B10", "D1")]);
+        assert_eq!(remap_formula("A1+$B$2", "Data", (0, 0), &r), "D1+$E$2");
+        assert_eq!(
+            remap_formula("SUM(A1:A10)", "Data", (0, 0), &r),
+            "SUM(D1:D10)"
+        );
+        // Straddles the source boundary: left alone.
+        assert_eq!(
+            remap_formula("SUM(A1:A11)", "Data", (0, 0), &r),
+            "SUM(A1:A11)"
+        );
+        assert_eq!(remap_formula("C1*2", "Data", (0, 0), &r), "C1*2");
+    }
+
+    #[test]
+    fn test_remap_respects_sheets() {
+        let r = remap(&[("A1", "C3")]);
+        assert_eq!(remap_formula("A1", "Other", (0, 0), &r), "A1");
+        assert_eq!(
+            remap_formula("Data!A1+'data'!A1+Other!A1", "Other", (0, 0), &r),
+            "Data!C3+'data'!C3+Other!A1"
+        );
+        assert_eq!(
+            remap_formula("[1]Data!A1", "Data", (0, 0), &r),
+            "[1]Data!A1"
+        );
+        assert_eq!(
+            remap_formula("SUM(Data:Other!A1)", "Data", (0, 0), &r),
+            "SUM(Data:Other!A1)"
+        );
+    }
+
+    #[test]
+    fn test_remap_skips_strings_names_and_functions() {
+        let r = remap(&[("A1:Z100", "A201")]);
+        assert_eq!(
+            remap_formula("\"A1\"&LOG10(A1)&Table1[A1]", "Data", (0, 0), &r),
+            "\"A1\"&LOG10(A201)&Table1[A1]"
+        );
+        assert_eq!(
+            remap_formula("IF(TRUE,#N/A,A1)", "Data", (0, 0), &r),
+            "IF(TRUE,#N/A,A201)"
+        );
+    }
+
+    #[test]
+    fn test_remap_off_sheet_is_ref_error() {
+        let r = remap(&[("A1", "A1")]);
+        let mut up = r.clone();
+        up.moves[0].rows = -1;
+        assert_eq!(remap_formula("A1+1", "Data", (0, 0), &up), "#REF!+1");
+    }
+
+    #[test]
+    fn test_remap_r1c1() {
+        let r = remap(&[("A1:B10", "D1")]);
+        assert_eq!(
+            remap_formula("R1C1+R[1]C", "Data", (0, 0), &r),
+            "R1C4+R[1]C[3]"
+        );
+        // `RC1` is an A1 cell (column RC), not R1C1.
+        assert_eq!(remap_formula("RC1", "Data", (0, 0), &r), "RC1");
+    }
+
+    #[test]
+    fn test_remap_worksheet_detaches_shared_dependents() {
+        let xml = r#"<worksheet><sheetData>
+<row r="1"><c r="C1"><f t="shared" ref="C1:C3" si="0">A1+$B$1</f><v>1</v></c></row>
+<row r="2"><c r="C2"><f t="shared" si="0"/><v>2</v></c></row>
+<row r="3"><c r="C3"><f t="shared" si="0"/><v>3</v></c></row>
+<row r="4"><c r="C4"><f>SUM(A1:A2)&amp;"x"</f><v>3</v></c></row>
+</sheetData></worksheet>"#;
+        // Only A2 moves, so C2's formula no longer follows the master.
+        let r = remap(&[("A2", "F2")]);
+        let out = remap_worksheet(xml, "Data", &r).unwrap();
+        assert!(out.contains(r#"<f t="shared" ref="C1:C3" si="0">A1+$B$1</f>"#));
+        assert!(out.contains(r#"<c r="C2"><f>F2+$B$1</f>"#));
+        assert!(out.contains(r#"<c r="C3"><f t="shared" si="0"/>"#));
+        assert!(out.contains("SUM(A1:A2)&amp;"));
+    }
+}
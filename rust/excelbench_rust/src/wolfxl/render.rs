@@ -0,0 +1,794 @@
+//! XLSX → HTML rendering (`render_html()`) for visual diffs.
+//!
+//! A sheet range becomes one `<table>` with inline CSS: fonts, fills,
+//! borders, alignment, merged cells (`rowspan`/`colspan`), column widths and
+//! row heights, and values shown through their number format. Two backends'
+//! outputs rendered this way can be compared side by side in a browser or
+//! diffed as text, without driving Excel for screenshots. Conditional
+//! formats, images and charts are not drawn.
+//!
+//! Cell values come from the same stream reader as `XlsxReader`; a second,
+//! light pass over the worksheet collects style indices (including styled
+//! empty cells) and row heights.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader};
+
+use pyo3::exceptions::PyIOError;
+use pyo3::prelude::*;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader as XmlReader;
+
+use crate::cell_ref::{CellRef, RangeKind, RangeRef};
+use crate::color::{ColorSpec, Palette};
+use crate::errors;
+use crate::numfmt::Locale;
+use crate::ooxml_util::fills::{read_fills, FillDef, StyleFills};
+use crate::ooxml_util::{self, attr_value};
+
+use super::preview::{open_zip, read_sheet_layout, SheetLayout};
+use super::reader::{display_text, load_context, scan_sheet, workbook_part};
+use super::sheet_reader::{ReadCell, ReadRow, ReadValue};
+use super::CAPABILITIES;
+
+/// Inclusive 0-based (first row, first col, last row, last col).
+type Bounds = (u32, u32, u32, u32);
+
+// ---------------------------------------------------------------------------
+// Styles → CSS
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct FontDef {
+    name: Option<String>,
+    size: Option<f64>,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strike: bool,
+    color: Option<ColorSpec>,
+}
+
+/// `style` and color of the left, right, top and bottom edges.
+type BorderDef = [(Option<String>, Option<ColorSpec>); 4];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct XfDef {
+    font: usize,
+    border: usize,
+    num_fmt: u32,
+    horizontal: Option<String>,
+    vertical: Option<String>,
+    wrap: bool,
+    indent: u32,
+}
+
+/// Per-cellXfs CSS and number formats of a workbook.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StyleTable {
+    /// `font-family`/`font-size` of font 0, set once on the table.
+    base_css: String,
+    /// Inline CSS per cellXfs index.
+    xf_css: Vec<String>,
+    /// Horizontal alignment per cellXfs index (None: General).
+    xf_align: Vec<Option<String>>,
+    xf_num_fmt: Vec<u32>,
+    num_fmts: HashMap<u32, String>,
+}
+
+/// True for `<b/>`, `<b val="1"/>`; false for `<b val="0"/>`.
+fn flag(e: &BytesStart<'_>) -> bool {
+    !matches!(attr_value(e, b"val").as_deref(), Some("0") | Some("false"))
+}
+
+fn parsed<T: std::str::FromStr>(e: &BytesStart<'_>, key: &[u8]) -> Option<T> {
+    attr_value(e, key).and_then(|v| v.parse().ok())
+}
+
+/// CSS for an Excel border style.
+fn border_css(style: &str) -> Option<&'static str> {
+    Some(match style {
+        "thin" => "1px solid",
+        "medium" => "2px solid",
+        "thick" => "3px solid",
+        "double" => "3px double",
+        "hair" | "dotted" => "1px dotted",
+        "dashed" | "dashDot" | "dashDotDot" => "1px dashed",
+        "mediumDashed" | "mediumDashDot" | "mediumDashDotDot" | "slantDashDot" => "2px dashed",
+        _ => return None,
+    })
+}
+
+fn fill_css(fill: &FillDef, palette: &Palette) -> Option<String> {
+    match fill {
+        FillDef::Pattern(p) if p.pattern_type != "none" => {
+            // Patterns other than solid are approximated by their foreground.
+            let color = p.fg.or(p.bg)?.resolve(palette)?;
+            Some(format!("background:{color}"))
+        }
+        FillDef::Gradient(g) => {
+            let stops: Vec<String> = g
+                .stops
+                .iter()
+                .filter_map(|s| {
+                    let color = s.color?.resolve(palette)?;
+                    Some(format!("{color} {}%", (s.position * 100.0).round()))
+                })
+                .collect();
+            if stops.is_empty() {
+                return None;
+            }
+            // Excel measures 0° left to right; CSS measures 90° that way.
+            let angle = if g.kind == "path" {
+                90.0
+            } else {
+                g.degree + 90.0
+            };
+            Some(format!(
+                "background:linear-gradient({angle}deg,{})",
+                stops.join(",")
+            ))
+        }
+        _ => None,
+    }
+}
+
+impl StyleTable {
+    /// Parse `xl/styles.xml` and resolve its colors against `palette`.
+    pub(crate) fn parse(styles_xml: &str, palette: &Palette) -> Result<Self, String> {
+        let mut fonts: Vec<FontDef> = Vec::new();
+        let mut borders: Vec<BorderDef> = Vec::new();
+        let mut xfs: Vec<XfDef> = Vec::new();
+        let mut num_fmts: HashMap<u32, String> = HashMap::new();
+        // Top-level table being read; `<dxfs>` repeats fonts and borders.
+        let mut section: Option<Vec<u8>> = None;
+        let mut edge: Option<usize> = None;
+
+        let mut reader = XmlReader::from_str(styles_xml);
+        loop {
+            let (e, empty) = match reader.read_event() {
+                Ok(Event::Start(e)) => (e, false),
+                Ok(Event::Empty(e)) => (e, true),
+                Ok(Event::End(e)) => {
+                    let name = e.local_name();
+                    if section.as_deref() == Some(name.as_ref()) {
+                        section = None;
+                    } else if matches!(name.as_ref(), b"left" | b"right" | b"top" | b"bottom") {
+                        edge = None;
+                    }
+                    continue;
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(format!("Failed to parse styles.xml: {e}")),
+                _ => continue,
+            };
+            let name = e.local_name().as_ref().to_vec();
+            let current = section.clone();
+            match (current.as_deref(), name.as_slice()) {
+                (None, b"fonts" | b"borders" | b"cellXfs" | b"dxfs" | b"cellStyleXfs")
+                    if !empty =>
+                {
+                    section = Some(name);
+                }
+                (None, b"numFmt") => {
+                    if let (Some(id), Some(code)) =
+                        (parsed(&e, b"numFmtId"), attr_value(&e, b"formatCode"))
+                    {
+                        num_fmts.insert(id, code);
+                    }
+                }
+                (Some(b"fonts"), b"font") => fonts.push(FontDef::default()),
+                (Some(b"fonts"), tag) => {
+                    let Some(font) = fonts.last_mut() else {
+                        continue;
+                    };
+                    match tag {
+                        b"name" | b"rFont" => font.name = attr_value(&e, b"val"),
+                        b"sz" => font.size = parsed(&e, b"val"),
+                        b"b" => font.bold = flag(&e),
+                        b"i" => font.italic = flag(&e),
+                        b"strike" => font.strike = flag(&e),
+                        b"u" => font.underline = attr_value(&e, b"val").as_deref() != Some("none"),
+                        b"color" => font.color = ColorSpec::from_attrs(|k| attr_value(&e, k)),
+                        _ => {}
+                    }
+                }
+                (Some(b"borders"), b"border") => borders.push(BorderDef::default()),
+                (Some(b"borders"), b"left" | b"right" | b"top" | b"bottom") => {
+                    let i = match name.as_slice() {
+                        b"left" => 0,
+                        b"right" => 1,
+                        b"top" => 2,
+                        _ => 3,
+                    };
+                    if let Some(border) = borders.last_mut() {
+                        border[i].0 = attr_value(&e, b"style");
+                    }
+                    edge = (!empty).then_some(i);
+                }
+                (Some(b"borders"), b"color") => {
+                    if let (Some(border), Some(i)) = (borders.last_mut(), edge) {
+                        border[i].1 = ColorSpec::from_attrs(|k| attr_value(&e, k));
+                    }
+                }
+                (Some(b"cellXfs"), b"xf") => xfs.push(XfDef {
+                    font: parsed(&e, b"fontId").unwrap_or(0),
+                    border: parsed(&e, b"borderId").unwrap_or(0),
+                    num_fmt: parsed(&e, b"numFmtId").unwrap_or(0),
+                    ..XfDef::default()
+                }),
+                (Some(b"cellXfs"), b"alignment") => {
+                    if let Some(xf) = xfs.last_mut() {
+                        xf.horizontal = attr_value(&e, b"horizontal");
+                        xf.vertical = attr_value(&e, b"vertical");
+                        xf.wrap = matches!(
+                            attr_value(&e, b"wrapText").as_deref(),
+                            Some("1") | Some("true")
+                        );
+                        xf.indent = parsed(&e, b"indent").unwrap_or(0);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let fills = read_fills(styles_xml)?;
+        let base = fonts.first().cloned().unwrap_or_default();
+        let mut table = Self {
+            base_css: format!(
+                "font-family:'{}';font-size:{}pt",
+                base.name.as_deref().unwrap_or("Calibri"),
+                base.size.unwrap_or(11.0)
+            ),
+            num_fmts,
+            ..Self::default()
+        };
+        for (i, xf) in xfs.iter().enumerate() {
+            let font = fonts.get(xf.font).cloned().unwrap_or_default();
+            let border = borders.get(xf.border).cloned().unwrap_or_default();
+            table
+                .xf_css
+                .push(xf_css(i, xf, &font, &base, &border, &fills, palette));
+            table
+                .xf_align
+                .push(xf.horizontal.clone().filter(|h| h != "general"));
+            table.xf_num_fmt.push(xf.num_fmt);
+        }
+        Ok(table)
+    }
+
+    fn css(&self, xf: usize) -> &str {
+        self.xf_css.get(xf).map_or("", String::as_str)
+    }
+
+    /// Format code of a cellXfs entry (custom or built-in).
+    fn num_fmt(&self, xf: usize) -> Option<&str> {
+        let id = *self.xf_num_fmt.get(xf)?;
+        self.num_fmts
+            .get(&id)
+            .map(String::as_str)
+            .or_else(|| crate::numfmt::builtin_format_code(id))
+    }
+}
+
+fn xf_css(
+    index: usize,
+    xf: &XfDef,
+    font: &FontDef,
+    base: &FontDef,
+    border: &BorderDef,
+    fills: &StyleFills,
+    palette: &Palette,
+) -> String {
+    let mut css: Vec<String> = Vec::new();
+    if font.name != base.name {
+        if let Some(name) = &font.name {
+            css.push(format!("font-family:'{name}'"));
+        }
+    }
+    if font.size != base.size {
+        if let Some(size) = font.size {
+            css.push(format!("font-size:{size}pt"));
+        }
+    }
+    if font.bold {
+        css.push("font-weight:bold".into());
+    }
+    if font.italic {
+        css.push("font-style:italic".into());
+    }
+    let decoration: Vec<&str> = [(font.underline, "underline"), (font.strike, "line-through")]
+        .iter()
+        .filter(|(on, _)| *on)
+        .map(|(_, v)| *v)
+        .collect();
+    if !decoration.is_empty() {
+        css.push(format!("text-decoration:{}", decoration.join(" ")));
+    }
+    if let Some(color) = font.color.and_then(|c| c.resolve(palette)) {
+        css.push(format!("color:{color}"));
+    }
+    if let Some(fill) = fills
+        .for_xf(index as u32)
+        .and_then(|f| fill_css(f, palette))
+    {
+        css.push(fill);
+    }
+    for (side, (style, color)) in ["left", "right", "top", "bottom"].iter().zip(border) {
+        if let Some(line) = style.as_deref().and_then(border_css) {
+            let color = color
+                .and_then(|c| c.resolve(palette))
+                .unwrap_or_else(|| "#000000".into());
+            css.push(format!("border-{side}:{line} {color}"));
+        }
+    }
+    match xf.vertical.as_deref() {
+        Some("top") => css.push("vertical-align:top".into()),
+        Some("center") => css.push("vertical-align:middle".into()),
+        _ => {}
+    }
+    if xf.wrap {
+        css.push("white-space:pre-wrap".into());
+    }
+    if xf.indent > 0 {
+        css.push(format!("padding-left:{}px", 3 + 9 * xf.indent));
+    }
+    css.join(";")
+}
+
+// ---------------------------------------------------------------------------
+// Worksheet formatting pass
+// ---------------------------------------------------------------------------
+
+/// Style indices and row properties of a worksheet.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SheetFormatting {
+    /// cellXfs index of every cell with a non-zero `s`, values or not.
+    pub cell_styles: HashMap<(u32, u32), usize>,
+    /// 0-based row → (`ht` in points, hidden).
+    pub rows: HashMap<u32, (Option<f64>, bool)>,
+}
+
+/// Collect cell style indices and row heights, skipping values.
+pub(crate) fn read_sheet_formatting<R: BufRead>(src: R) -> Result<SheetFormatting, String> {
+    let mut reader = XmlReader::from_reader(src);
+    let mut buf = Vec::new();
+    let mut out = SheetFormatting::default();
+    let (mut next_row, mut next_col, mut row) = (0u32, 0u32, 0u32);
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                b"row" => {
+                    row = parsed::<u32>(&e, b"r")
+                        .filter(|r| *r > 0)
+                        .map_or(next_row, |r| r - 1);
+                    next_row = row + 1;
+                    next_col = 0;
+                    let hidden = matches!(
+                        attr_value(&e, b"hidden").as_deref(),
+                        Some("1") | Some("true")
+                    );
+                    let height = parsed::<f64>(&e, b"ht");
+                    if hidden || height.is_some() {
+                        out.rows.insert(row, (height, hidden));
+                    }
+                }
+                b"c" => {
+                    let col = attr_value(&e, b"r")
+                        .and_then(|r| CellRef::parse(&r).ok())
+                        .map_or(next_col, |c| c.col);
+                    next_col = col + 1;
+                    if let Some(s) = parsed::<usize>(&e, b"s").filter(|s| *s > 0) {
+                        out.cell_styles.insert((row, col), s);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"sheetData" => break,
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Failed to parse worksheet XML: {e}")),
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Values
+// ---------------------------------------------------------------------------
+
+/// Excel's General alignment: numbers right, logicals and errors centered.
+fn general_align(value: &ReadValue) -> Option<&'static str> {
+    match value {
+        ReadValue::Number(_) | ReadValue::Date(_) | ReadValue::DateIso(_) => Some("right"),
+        ReadValue::Bool(_) | ReadValue::Error(_) => Some("center"),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HTML
+// ---------------------------------------------------------------------------
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Pixel width of a `<col width>` (character units, padding included).
+fn col_px(width: f64) -> u32 {
+    ((256.0 * width + (128.0_f64 / 7.0).trunc()) / 256.0 * 7.0).trunc() as u32
+}
+
+/// Everything `render_table` needs about one sheet.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SheetRender {
+    pub rows: Vec<ReadRow>,
+    pub formatting: SheetFormatting,
+    pub layout: SheetLayout,
+    pub styles: StyleTable,
+    pub date1904: bool,
+}
+
+impl SheetRender {
+    /// Cells with a value or formula, styled cells and merges.
+    fn used_bounds(&self) -> Option<Bounds> {
+        let values = self
+            .rows
+            .iter()
+            .flat_map(|(r, cells)| cells.iter().map(move |c| (*r, c.col)));
+        let styled = self.formatting.cell_styles.keys().copied();
+        let merges = self
+            .merges()
+            .into_iter()
+            .flat_map(|(r0, c0, r1, c1)| [(r0, c0), (r1, c1)]);
+        values
+            .chain(styled)
+            .chain(merges)
+            .fold(None, |acc, (r, c)| {
+                Some(match acc {
+                    Some((r0, c0, r1, c1)) => (r0.min(r), c0.min(c), r1.max(r), c1.max(c)),
+                    None => (r, c, r, c),
+                })
+            })
+    }
+
+    fn merges(&self) -> Vec<Bounds> {
+        self.layout
+            .merges
+            .iter()
+            .filter_map(|m| RangeRef::parse(m).ok())
+            .map(|r| r.bounds())
+            .collect()
+    }
+
+    fn col_width_px(&self, col: u32) -> (u32, bool) {
+        let span = self
+            .layout
+            .cols
+            .iter()
+            .find(|s| s.min <= col + 1 && col + 1 <= s.max);
+        let hidden = span.is_some_and(|s| s.hidden);
+        let width = span
+            .and_then(|s| s.width)
+            .or(self.layout.default_col_width)
+            .map_or(64, col_px);
+        (width, hidden)
+    }
+}
+
+/// Render `bounds` (or the used range) of a sheet as an HTML table.
+pub(crate) fn render_table(sheet: &str, data: &SheetRender, bounds: Option<Bounds>) -> String {
+    let mut html = String::new();
+    let bounds = bounds.or_else(|| data.used_bounds());
+    let range = bounds.map(|(r0, c0, r1, c1)| {
+        format!(
+            "{}:{}",
+            CellRef::new(r0, c0).to_a1(),
+            CellRef::new(r1, c1).to_a1()
+        )
+    });
+    let _ = write!(
+        html,
+        "<table data-sheet=\"{}\" data-range=\"{}\" style=\"border-collapse:collapse;\
+         table-layout:fixed;white-space:nowrap;{}\">\n",
+        escape(sheet),
+        range.as_deref().unwrap_or(""),
+        data.styles.base_css
+    );
+    let Some((r0, c0, r1, c1)) = bounds else {
+        html.push_str("</table>\n");
+        return html;
+    };
+
+    html.push_str("<colgroup>");
+    for col in c0..=c1 {
+        let (px, hidden) = data.col_width_px(col);
+        let collapse = if hidden { ";visibility:collapse" } else { "" };
+        let _ = write!(html, "<col style=\"width:{px}px{collapse}\">");
+    }
+    html.push_str("</colgroup>\n");
+
+    // Merges clipped to the rendered range: anchor → span, plus covered cells.
+    let mut spans: HashMap<(u32, u32), (u32, u32)> = HashMap::new();
+    let mut covered: HashSet<(u32, u32)> = HashSet::new();
+    for (mr0, mc0, mr1, mc1) in data.merges() {
+        let (top, left) = (mr0.max(r0), mc0.max(c0));
+        let (bottom, right) = (mr1.min(r1), mc1.min(c1));
+        if top > bottom || left > right {
+            continue;
+        }
+        spans.insert((top, left), (bottom - top + 1, right - left + 1));
+        for r in top..=bottom {
+            for c in left..=right {
+                if (r, c) != (top, left) {
+                    covered.insert((r, c));
+                }
+            }
+        }
+    }
+
+    let cells: HashMap<(u32, u32), &ReadCell> = data
+        .rows
+        .iter()
+        .filter(|(r, _)| (r0..=r1).contains(r))
+        .flat_map(|(r, cells)| cells.iter().map(move |c| ((*r, c.col), c)))
+        .collect();
+    let default_height = data.layout.default_row_height.unwrap_or(15.0);
+
+    for row in r0..=r1 {
+        let (height, hidden) = data
+            .formatting
+            .rows
+            .get(&row)
+            .copied()
+            .unwrap_or((None, false));
+        let px = (height.unwrap_or(default_height) * 96.0 / 72.0).round();
+        let display = if hidden { ";display:none" } else { "" };
+        let _ = write!(html, "<tr style=\"height:{px}px{display}\">");
+        for col in c0..=c1 {
+            if covered.contains(&(row, col)) {
+                continue;
+            }
+            let a1 = CellRef::new(row, col).to_a1();
+            let _ = write!(html, "<td data-cell=\"{a1}\"");
+            if let Some(&(rows, cols)) = spans.get(&(row, col)) {
+                if rows > 1 {
+                    let _ = write!(html, " rowspan=\"{rows}\"");
+                }
+                if cols > 1 {
+                    let _ = write!(html, " colspan=\"{cols}\"");
+                }
+            }
+
+            let xf = data
+                .formatting
+                .cell_styles
+                .get(&(row, col))
+                .copied()
+                .unwrap_or(0);
+            let value = cells.get(&(row, col)).map(|c| &c.value);
+            let mut css = data.styles.css(xf).to_string();
+            let align = match data.styles.xf_align.get(xf).cloned().flatten() {
+                Some(h) => Some(match h.as_str() {
+                    "centerContinuous" => "center".to_string(),
+                    "distributed" => "justify".to_string(),
+                    "fill" => "left".to_string(),
+                    _ => h,
+                }),
+                None => value.and_then(general_align).map(str::to_string),
+            };
+            if let Some(align) = align {
+                if !css.is_empty() {
+                    css.push(';');
+                }
+                let _ = write!(css, "text-align:{align}");
+            }
+            if !css.is_empty() {
+                let _ = write!(html, " style=\"{}\"", escape(&css));
+            }
+
+            let text = value
+                .map(|v| {
+                    let code = data.styles.num_fmt(xf).unwrap_or("General");
+                    display_text(v, code, data.date1904, &Locale::default())
+                })
+                .unwrap_or_default();
+            let _ = write!(html, ">{}</td>", escape(&text));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+/// Render a sheet range as an HTML `<table>` with inline styles.
+///
+/// `range` is an A1 range such as `"A1:F20"`; without it the used range is
+/// rendered (cells with values, styled cells and merges). Each `<td>` carries
+/// its address in `data-cell`; values are shown as formatted text.
+#[pyfunction]
+#[pyo3(signature = (path, sheet, range=None))]
+pub(crate) fn render_html(
+    py: Python<'_>,
+    path: &str,
+    sheet: &str,
+    range: Option<&str>,
+) -> PyResult<String> {
+    let bounds = match range {
+        Some(r) => {
+            let parsed =
+                RangeRef::parse(r).map_err(|msg| errors::cell_ref(CAPABILITIES.backend, r, msg))?;
+            if parsed.kind != RangeKind::Cells {
+                return Err(errors::cell_ref(
+                    CAPABILITIES.backend,
+                    r,
+                    "render_html needs a cell range such as A1:F20".to_string(),
+                ));
+            }
+            Some(parsed.bounds())
+        }
+        None => None,
+    };
+
+    let mut zip = open_zip(path)?;
+    let wb_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml")?;
+    let rels_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/_rels/workbook.xml.rels")?;
+    let part = ooxml_util::sheet_part_paths(&wb_xml, &rels_xml)?
+        .into_iter()
+        .find(|(name, _)| name == sheet)
+        .map(|(_, part)| part)
+        .ok_or_else(|| errors::sheet_not_found(CAPABILITIES.backend, sheet))?;
+    let ctx = load_context(&mut zip, &rels_xml)?;
+
+    let styles_path = workbook_part(&rels_xml, "styles", "xl/styles.xml")?;
+    let theme_path = workbook_part(&rels_xml, "theme", "xl/theme/theme1.xml")?;
+    let styles_xml = ooxml_util::zip_read_to_string_opt(&mut zip, &styles_path)?;
+    let theme_xml = ooxml_util::zip_read_to_string_opt(&mut zip, &theme_path)?;
+    let palette = Palette::from_parts(theme_xml.as_deref(), styles_xml.as_deref());
+    let styles = match &styles_xml {
+        Some(xml) => StyleTable::parse(xml, &palette).map_err(PyErr::new::<PyIOError, _>)?,
+        None => StyleTable::default(),
+    };
+
+    let missing = |e: zip::result::ZipError| {
+        PyErr::new::<PyIOError, _>(format!("Missing worksheet part {part}: {e}"))
+    };
+    let entry = zip.by_name(&part).map_err(missing)?;
+    let layout = read_sheet_layout(BufReader::new(entry)).map_err(PyErr::new::<PyIOError, _>)?;
+    let entry = zip.by_name(&part).map_err(missing)?;
+    let formatting =
+        read_sheet_formatting(BufReader::new(entry)).map_err(PyErr::new::<PyIOError, _>)?;
+    drop(zip);
+
+    let data = SheetRender {
+        rows: Vec::new(),
+        formatting,
+        layout,
+        styles,
+        date1904: ooxml_util::workbook_is_date1904(&wb_xml),
+    };
+    py.allow_threads(move || {
+        let mut data = data;
+        let last_row = bounds.map(|(_, _, r1, _)| r1);
+        scan_sheet(path, &part, &ctx, |row| {
+            if last_row.is_some_and(|last| row.0 > last) {
+                return false;
+            }
+            data.rows.push(row);
+            true
+        })?;
+        Ok(render_table(sheet, &data, bounds))
+    })
+    .map_err(PyErr::new::<PyIOError, _>)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STYLES: &str = concat!(
+        r#"<styleSheet><numFmts count="1">"#,
+        r##"<numFmt numFmtId="164" formatCode="#,##0.00;[Red](#,##0.00)"/></numFmts>"##,
+        r#"<fonts count="2"><font><sz val="11"/><name val="Calibri"/></font>"#,
+        r#"<font><b/><i val="0"/><u/><sz val="14"/><color rgb="FFFF0000"/>"#,
+        r#"<name val="Calibri"/></font></fonts><fills count="3">"#,
+        r#"<fill><patternFill patternType="none"/></fill>"#,
+        r#"<fill><patternFill patternType="gray125"/></fill><fill>"#,
+        r#"<patternFill patternType="solid"><fgColor rgb="FFFFFF00"/></patternFill></fill>"#,
+        r#"</fills><borders count="2"><border/><border>"#,
+        r#"<left style="thin"><color rgb="FF0000FF"/></left>"#,
+        r#"<right/><top style="double"/><bottom/></border></borders>"#,
+        r#"<cellStyleXfs count="1"><xf fontId="1"/></cellStyleXfs>"#,
+        r#"<cellXfs count="3"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/>"#,
+        r#"<xf numFmtId="164" fontId="1" fillId="2" borderId="1">"#,
+        r#"<alignment horizontal="center" wrapText="1"/></xf>"#,
+        r#"<xf numFmtId="10" fontId="0" fillId="0" borderId="0"/></cellXfs>"#,
+        r#"<dxfs count="1"><dxf><font><b/></font></dxf></dxfs></styleSheet>"#
+    );
+
+    #[test]
+    fn test_style_table_css() {
+        let table = StyleTable::parse(STYLES, &Palette::default()).unwrap();
+        assert_eq!(table.base_css, "font-family:'Calibri';font-size:11pt");
+        assert_eq!(table.css(0), "");
+        assert_eq!(
+            table.css(1),
+            "font-size:14pt;font-weight:bold;text-decoration:underline;color:#FF0000;\
+             background:#FFFF00;border-left:1px solid #0000FF;border-top:3px double #000000;\
+             white-space:pre-wrap"
+        );
+        assert_eq!(table.xf_align[1].as_deref(), Some("center"));
+        assert_eq!(table.num_fmt(1), Some("#,##0.00;[Red](#,##0.00)"));
+        assert_eq!(table.num_fmt(2), Some("0.00%"));
+    }
+
+    #[test]
+    fn test_sheet_formatting_pass() {
+        let xml = concat!(
+            r#"<worksheet><sheetData><row r="2" ht="30" customHeight="1">"#,
+            r#"<c r="B2" s="1"><v>1</v></c><c s="2"/></row><row r="4" hidden="1"/>"#,
+            r#"</sheetData></worksheet>"#
+        );
+        let f = read_sheet_formatting(xml.as_bytes()).unwrap();
+        assert_eq!(f.cell_styles.get(&(1, 1)), Some(&1));
+        assert_eq!(f.cell_styles.get(&(1, 2)), Some(&2));
+        assert_eq!(f.rows.get(&1), Some(&(Some(30.0), false)));
+        assert_eq!(f.rows.get(&3), Some(&(None, true)));
+    }
+
+    #[test]
+    fn test_render_table_merges_values_and_styles() {
+        let mut data = SheetRender {
+            styles: StyleTable::parse(STYLES, &Palette::default()).unwrap(),
+            ..SheetRender::default()
+        };
+        data.rows = vec![
+            (
+                0,
+                vec![ReadCell {
+                    col: 0,
+                    value: ReadValue::String("Q1 <total>".into()),
+                    formula: None,
+                    style: 0,
+                }],
+            ),
+            (
+                1,
+                vec![ReadCell {
+                    col: 1,
+                    value: ReadValue::Number(-1234.5),
+                    formula: None,
+                    style: 1,
+                }],
+            ),
+        ];
+        data.formatting.cell_styles.insert((1, 1), 1);
+        data.layout.merges = vec!["A1:B1".into()];
+
+        let html = render_table("Q&A", &data, None);
+        assert!(html.starts_with(r#"<table data-sheet="Q&amp;A" data-range="A1:B2""#));
+        assert!(html.contains(r#"<td data-cell="A1" colspan="2">Q1 &lt;total&gt;</td></tr>"#));
+        assert!(!html.contains(r#"data-cell="B1""#));
+        assert!(html.contains(r#"<td data-cell="A2"></td>"#));
+        assert!(html.contains(">(1,234.50)</td>"));
+        assert!(html.contains("text-align:center"));
+        assert_eq!(html.matches("<col style=\"width:64px\">").count(), 2);
+
+        let window = render_table("S", &data, Some((1, 1, 1, 1)));
+        assert!(window.contains(r#"data-range="B2:B2""#));
+        assert_eq!(window.matches("<td").count(), 1);
+    }
+}
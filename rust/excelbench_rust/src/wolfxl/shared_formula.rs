@@ -0,0 +1,176 @@
+//! Shared-formula groups (`<f t="shared" ref=".." si="..">`).
+//!
+//! Only the master cell of a group stores the formula text; dependents carry
+//! a bare `<f t="shared" si="N"/>` and Excel derives their formula by
+//! shifting the master's relative references. When the patcher replaces a
+//! master's value, the group loses its definition, so the dependents are
+//! rewritten as ordinary formulas with the shift applied.
+
+use std::collections::HashMap;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader as XmlReader;
+
+use crate::cell_ref::CellRef;
+use crate::formula;
+use crate::ooxml_util::attr_value;
+
+/// The master cell of a shared-formula group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedMaster {
+    /// 1-based row of the master cell.
+    pub row: u32,
+    /// 1-based column of the master cell.
+    pub col: u32,
+    /// Formula text without the leading `=`.
+    pub formula: String,
+}
+
+impl SharedMaster {
+    /// Formula for the dependent at (`row`, `col`), both 1-based.
+    pub fn formula_at(&self, row: u32, col: u32) -> String {
+        shift_formula(
+            &self.formula,
+            i64::from(row) - i64::from(self.row),
+            i64::from(col) - i64::from(self.col),
+        )
+    }
+}
+
+/// Find the shared-formula masters in a worksheet, keyed by `si`.
+pub fn scan_masters(xml: &str) -> Result<HashMap<String, SharedMaster>, String> {
+    let mut masters = HashMap::new();
+    if !xml.contains("shared") {
+        return Ok(masters);
+    }
+
+    let mut reader = XmlReader::from_str(xml);
+    let mut buf = Vec::new();
+    let mut cell: (u32, u32) = (0, 0);
+    // si of the master `<f>` whose text is being read.
+    let mut open: Option<String> = None;
+    let mut text = String::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"c" => cell = cell_position(e),
+                b"f" => {
+                    open = master_si(e);
+                    text.clear();
+                }
+                _ => {}
+            },
+            Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"c" => {
+                cell = cell_position(e);
+            }
+            Ok(Event::Text(ref t)) if open.is_some() => {
+                let t = t.unescape().map_err(|e| format!("XML parse error: {e}"))?;
+                text.push_str(&t);
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"f" => {
+                if let Some(si) = open.take() {
+                    masters.insert(
+                        si,
+                        SharedMaster {
+                            row: cell.0,
+                            col: cell.1,
+                            formula: text.clone(),
+                        },
+                    );
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("XML parse error: {e}")),
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(masters)
+}
+
+/// `si` of a shared `<f>`, for dependents and masters alike.
+pub fn shared_si(e: &BytesStart<'_>) -> Option<String> {
+    if attr_value(e, b"t").as_deref() != Some("shared") {
+        return None;
+    }
+    attr_value(e, b"si")
+}
+
+/// `si` of a master `<f>` (a shared `<f>` that carries the group's `ref`).
+fn master_si(e: &BytesStart<'_>) -> Option<String> {
+    attr_value(e, b"ref")?;
+    shared_si(e)
+}
+
+/// 1-based (row, col) of a `<c r="..">`; (0, 0) when unparseable.
+pub fn cell_position(e: &BytesStart<'_>) -> (u32, u32) {
+    attr_value(e, b"r")
+        .and_then(|r| CellRef::parse(&r).ok())
+        .map(|c| (c.row + 1, c.col + 1))
+        .unwrap_or((0, 0))
+}
+
+/// Copy of a shared `<f>` tag as an ordinary formula (no `t`/`si`/`ref`).
+pub fn detached_f(e: &BytesStart<'_>) -> BytesStart<'static> {
+    let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+    let mut out = BytesStart::new(name);
+    for a in e.attributes().with_checks(false).flatten() {
+        if !matches!(a.key.local_name().as_ref(), b"t" | b"si" | b"ref") {
+            out.push_attribute(a);
+        }
+    }
+    out
+}
+
+/// Shift the relative A1 references in `formula` by `rows`/`cols`; see
+/// [`formula::shift_references`].
+pub fn shift_formula(formula: &str, rows: i64, cols: i64) -> String {
+    formula::shift_references(formula, rows, cols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shift_relative_and_absolute() {
+        assert_eq!(shift_formula("A1*2", 3, 0), "A4*2");
+        assert_eq!(shift_formula("$A1+A$1+$A$1", 2, 1), "$A3+B$1+$A$1");
+        assert_eq!(shift_formula("SUM(B2:B10)/C2", 1, 1), "SUM(C3:C11)/D3");
+    }
+
+    #[test]
+    fn test_shift_skips_functions_strings_and_sheets() {
+        assert_eq!(shift_formula("LOG10(A1)", 1, 0), "LOG10(A2)");
+        assert_eq!(shift_formula("\"A1\"&A1", 1, 0), "\"A1\"&A2");
+        assert_eq!(
+            shift_formula("Sheet2!A1+'My A1'!B1", 1, 0),
+            "Sheet2!A2+'My A1'!B2"
+        );
+        assert_eq!(
+            shift_formula("IF(TRUE,1.5E3,A1)", 0, 1),
+            "IF(TRUE,1.5E3,B1)"
+        );
+        assert_eq!(shift_formula("Table1[Col1]", 1, 1), "Table1[Col1]");
+    }
+
+    #[test]
+    fn test_shift_off_sheet_is_ref_error() {
+        assert_eq!(shift_formula("A1", -1, 0), "#REF!");
+    }
+
+    #[test]
+    fn test_scan_masters() {
+        let xml = r#"<worksheet><sheetData>
+<row r="1"><c r="B1"><f t="shared" ref="B1:B3" si="0">A1&amp;"x"</f><v>1</v></c></row>
+<row r="2"><c r="B2"><f t="shared" si="0"/><v>2</v></c></row>
+<row r="3"><c r="B3"><f>A3</f><v>3</v></c></row>
+</sheetData></worksheet>"#;
+        let masters = scan_masters(xml).unwrap();
+        assert_eq!(masters.len(), 1);
+        let m = &masters["0"];
+        assert_eq!((m.row, m.col), (1, 2));
+        assert_eq!(m.formula, "A1&\"x\"");
+        assert_eq!(m.formula_at(3, 2), "A3&\"x\"");
+    }
+}
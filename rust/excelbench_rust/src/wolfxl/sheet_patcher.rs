@@ -2,20 +2,37 @@
 //!
 //! Takes a worksheet XML string and a set of cell patches, produces a new XML
 //! string with those cells replaced or inserted.  Uses quick-xml's streaming
-//! reader+writer to avoid building a full DOM.
+//! reader+writer to avoid building a full DOM.  Elements are matched by local
+//! name, so prefixed documents (`<x:sheetData>`, `<x:c>`) patch the same way;
+//! new rows and cells are written in the document's prefix.
+//!
+//! Only `<sheetData>` is rewritten. Everything after it streams through
+//! untouched, in particular `<legacyDrawing r:id>`, the link from the sheet to
+//! the VML shapes of its cell notes.
+//!
+//! A new cell without a patch style takes its row's default style
+//! (`<row s customFormat="1">`), else its column's (`<col style>`), as Excel
+//! does when typing into a formatted band.
+//!
+//! Rows that gain or change cells get their optional `spans` hint (the
+//! `first:last` columns holding cells) recomputed, or dropped, per
+//! [`RowSpans`]; untouched rows keep theirs.
 //!
 //! WolfXL uses **inline strings** (`t="str"`) for all new string values.  This
 //! avoids modifying the shared string table for the common case.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::io::Write;
 
 use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
 use quick_xml::Reader as XmlReader;
 use quick_xml::Writer as XmlWriter;
 
+use crate::cell_ref::CellRef;
 use crate::ooxml_util::attr_value;
 
+use super::shared_formula::{self, SharedMaster};
+
 // ---------------------------------------------------------------------------
 // Cell patch types
 // ---------------------------------------------------------------------------
@@ -48,6 +65,26 @@ pub struct CellPatch {
     pub style_index: Option<u32>,
 }
 
+/// What to do with the `spans` attribute of rows the patch touches.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum RowSpans {
+    /// Rewrite it to cover the row's cells after patching.
+    #[default]
+    Recompute,
+    /// Remove it; readers treat it as optional.
+    Strip,
+}
+
+impl RowSpans {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "recompute" => Some(Self::Recompute),
+            "strip" => Some(Self::Strip),
+            _ => None,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
@@ -55,12 +92,17 @@ pub struct CellPatch {
 /// Patch a worksheet XML string with the given cell modifications.
 ///
 /// Cells are replaced if they already exist, or inserted at the correct
-/// sorted position if they don't.  Rows are created as needed.
+/// sorted position if they don't.  Rows are created as needed; inserted cells
+/// inherit row and column default styles.
 ///
 /// The `shared_strings` table is used only to resolve existing shared string
 /// values in cells that aren't being patched (for context — we don't modify
 /// them).
-pub fn patch_worksheet(xml: &str, patches: &[CellPatch]) -> Result<String, String> {
+pub fn patch_worksheet(
+    xml: &str,
+    patches: &[CellPatch],
+    spans: RowSpans,
+) -> Result<String, String> {
     if patches.is_empty() {
         return Ok(xml.to_string());
     }
@@ -72,6 +114,33 @@ pub fn patch_worksheet(xml: &str, patches: &[CellPatch]) -> Result<String, Strin
         row_patches.entry(p.row).or_default().insert(p.col, p);
     }
 
+    // Shared-formula groups whose master is being overwritten; their
+    // dependents are rewritten as standalone formulas as they stream past.
+    let detached: HashMap<String, SharedMaster> = shared_formula::scan_masters(xml)?
+        .into_iter()
+        .filter(|(_, m)| {
+            row_patches
+                .get(&m.row)
+                .and_then(|r| r.get(&m.col))
+                .is_some_and(|p| p.value.is_some())
+        })
+        .collect();
+    let detached_master =
+        |e: &BytesStart<'_>| shared_formula::shared_si(e).and_then(|si| detached.get(&si));
+
+    // Columns of the cells already in patched rows, for their new `spans`.
+    let existing_extents = match spans {
+        RowSpans::Recompute => row_extents(xml, &row_patches)?,
+        RowSpans::Strip => HashMap::new(),
+    };
+    let patched_extent = |row: u32, cells: &BTreeMap<u32, &CellPatch>| {
+        let mut extent = patch_extent(cells);
+        if let (Some((lo, hi)), Some(&(a, b))) = (extent, existing_extents.get(&row)) {
+            extent = Some((lo.min(a), hi.max(b)));
+        }
+        extent.or_else(|| existing_extents.get(&row).copied())
+    };
+
     let mut reader = XmlReader::from_str(xml);
     reader.config_mut().trim_text(false);
     let mut writer = XmlWriter::new(Vec::new());
@@ -80,17 +149,27 @@ pub fn patch_worksheet(xml: &str, patches: &[CellPatch]) -> Result<String, Strin
     // State tracking
     let mut in_sheet_data = false;
     let mut current_row: Option<u32> = None;
+    let mut current_row_style: Option<u32> = None;
+    // `<cols>` precedes `<sheetData>`, so it is complete before any insert.
+    let mut col_styles = ColumnStyles::default();
     let mut current_row_cols_seen: Vec<u32> = Vec::new(); // cols we've seen in current row
     let mut rows_seen: Vec<u32> = Vec::new();
     let mut skip_until_cell_end = false; // skip children of a cell being replaced
+    let mut current_cell: (u32, u32) = (0, 0); // 1-based (row, col) of the open <c>
+    let mut in_detached_f = false; // replacing the text of a detached shared <f>
+
+    // Namespace prefix of <sheetData> (`"x:"` for `<x:sheetData>`), reused for
+    // every element we write so new rows/cells match the document.
+    let mut prefix = String::new();
 
     loop {
         match reader.read_event_into(&mut buf) {
             Ok(Event::Start(ref e)) => {
-                let tag = e.name().as_ref().to_vec();
+                let tag = e.local_name().as_ref().to_vec();
 
                 if tag == b"sheetData" {
                     in_sheet_data = true;
+                    prefix = name_prefix(e);
                     write_event(&mut writer, Event::Start(e.to_owned()))?;
                 } else if tag == b"row" && in_sheet_data {
                     let row_num = attr_value(e, b"r")
@@ -98,20 +177,28 @@ pub fn patch_worksheet(xml: &str, patches: &[CellPatch]) -> Result<String, Strin
                         .unwrap_or(0);
 
                     // Insert any missing rows that should come before this one
-                    for &pr in row_patches.keys() {
+                    for (&pr, cells) in &row_patches {
                         if pr < row_num && !rows_seen.contains(&pr) {
-                            write_new_row(&mut writer, pr, row_patches.get(&pr).unwrap())?;
+                            write_new_row(&mut writer, &prefix, pr, cells, &col_styles, spans)?;
                             rows_seen.push(pr);
                         }
                     }
 
                     current_row = Some(row_num);
+                    current_row_style = row_style(e);
                     current_row_cols_seen.clear();
                     rows_seen.push(row_num);
-                    write_event(&mut writer, Event::Start(e.to_owned()))?;
+                    match row_patches.get(&row_num) {
+                        Some(cells) => {
+                            let extent = patched_extent(row_num, cells);
+                            write_event(&mut writer, Event::Start(with_spans(e, spans, extent)?))?;
+                        }
+                        None => write_event(&mut writer, Event::Start(e.to_owned()))?,
+                    }
                 } else if tag == b"c" && in_sheet_data {
                     let cell_ref = attr_value(e, b"r").unwrap_or_default();
-                    let (_, col) = parse_cell_ref(&cell_ref);
+                    current_cell = parse_cell_ref(&cell_ref);
+                    let (_, col) = current_cell;
 
                     current_row_cols_seen.push(col);
 
@@ -120,12 +207,13 @@ pub fn patch_worksheet(xml: &str, patches: &[CellPatch]) -> Result<String, Strin
                             // This cell is being patched.
                             // If it's style-only (no value change), preserve the original
                             // children (<v>, <f>, etc.) and only rewrite the <c ...> attrs.
-                            if patch.value.is_none() && patch.style_index.is_some() {
-                                write_style_only_cell_start(&mut writer, &cell_ref, e, patch)?;
+                            if patch.value.is_none() {
+                                let start = style_only_cell(&cell_ref, e, patch)?;
+                                write_event(&mut writer, Event::Start(start))?;
                                 // Do NOT skip children.
                             } else {
                                 // Value patch: replace the entire cell element.
-                                write_patched_cell(&mut writer, &cell_ref, e, patch)?;
+                                write_patched_cell(&mut writer, &prefix, &cell_ref, e, patch)?;
                                 skip_until_cell_end = true;
                             }
                         } else {
@@ -135,14 +223,19 @@ pub fn patch_worksheet(xml: &str, patches: &[CellPatch]) -> Result<String, Strin
                     } else {
                         write_event(&mut writer, Event::Start(e.to_owned()))?;
                     }
+                } else if skip_until_cell_end {
+                    // Child of a replaced cell — dropped.
+                } else if let Some(master) = detached_master(e).filter(|_| tag == b"f") {
+                    let formula = master.formula_at(current_cell.0, current_cell.1);
+                    write_event(&mut writer, Event::Start(shared_formula::detached_f(e)))?;
+                    write_event(&mut writer, Event::Text(BytesText::new(&formula)))?;
+                    in_detached_f = true;
                 } else {
-                    if !skip_until_cell_end {
-                        write_event(&mut writer, Event::Start(e.to_owned()))?;
-                    }
+                    write_event(&mut writer, Event::Start(e.to_owned()))?;
                 }
             }
             Ok(Event::Empty(ref e)) => {
-                let tag = e.name().as_ref().to_vec();
+                let tag = e.local_name().as_ref().to_vec();
 
                 if tag == b"row" && in_sheet_data {
                     // Self-closing empty row — handle insertions
@@ -150,17 +243,25 @@ pub fn patch_worksheet(xml: &str, patches: &[CellPatch]) -> Result<String, Strin
                         .and_then(|s| s.parse::<u32>().ok())
                         .unwrap_or(0);
 
-                    for &pr in row_patches.keys() {
+                    for (&pr, cells) in &row_patches {
                         if pr < row_num && !rows_seen.contains(&pr) {
-                            write_new_row(&mut writer, pr, row_patches.get(&pr).unwrap())?;
+                            write_new_row(&mut writer, &prefix, pr, cells, &col_styles, spans)?;
                             rows_seen.push(pr);
                         }
                     }
                     rows_seen.push(row_num);
 
-                    // If this empty row has patches, expand it
+                    // If this empty row has patches, expand it, keeping its
+                    // attributes (height, default style).
                     if let Some(row_map) = row_patches.get(&row_num) {
-                        write_new_row(&mut writer, row_num, row_map)?;
+                        let start = with_spans(e, spans, patch_extent(row_map))?;
+                        write_event(&mut writer, Event::Start(start))?;
+                        for (&col, patch) in row_map {
+                            let default = row_style(e).or_else(|| col_styles.get(col));
+                            let cell_ref = col_row_to_a1(col, row_num);
+                            write_new_cell(&mut writer, &prefix, &cell_ref, patch, default)?;
+                        }
+                        write_event(&mut writer, Event::End(e.to_end().into_owned()))?;
                     } else {
                         write_event(&mut writer, Event::Empty(e.to_owned()))?;
                     }
@@ -173,30 +274,45 @@ pub fn patch_worksheet(xml: &str, patches: &[CellPatch]) -> Result<String, Strin
 
                     if let Some(row_map) = current_row.and_then(|r| row_patches.get(&r)) {
                         if let Some(patch) = row_map.get(&col) {
-                            write_patched_cell(&mut writer, &cell_ref, e, patch)?;
+                            if patch.value.is_none() {
+                                let cell = style_only_cell(&cell_ref, e, patch)?;
+                                write_event(&mut writer, Event::Empty(cell))?;
+                            } else {
+                                write_patched_cell(&mut writer, &prefix, &cell_ref, e, patch)?;
+                            }
                         } else {
                             write_event(&mut writer, Event::Empty(e.to_owned()))?;
                         }
                     } else {
                         write_event(&mut writer, Event::Empty(e.to_owned()))?;
                     }
+                } else if tag == b"col" && !in_sheet_data {
+                    col_styles.add(e);
+                    write_event(&mut writer, Event::Empty(e.to_owned()))?;
                 } else if tag == b"sheetData" {
                     // Empty <sheetData/> — need to insert all rows
-                    let start = BytesStart::new("sheetData");
+                    prefix = name_prefix(e);
+                    let start = BytesStart::new(tag_name(&prefix, "sheetData"));
                     write_event(&mut writer, Event::Start(start))?;
                     for (&row_num, row_map) in &row_patches {
-                        write_new_row(&mut writer, row_num, row_map)?;
+                        write_new_row(&mut writer, &prefix, row_num, row_map, &col_styles, spans)?;
                         rows_seen.push(row_num);
                     }
-                    write_event(&mut writer, Event::End(BytesEnd::new("sheetData")))?;
+                    let end = BytesEnd::new(tag_name(&prefix, "sheetData"));
+                    write_event(&mut writer, Event::End(end))?;
+                } else if skip_until_cell_end {
+                    // Child of a replaced cell — dropped.
+                } else if let Some(master) = detached_master(e).filter(|_| tag == b"f") {
+                    let formula = master.formula_at(current_cell.0, current_cell.1);
+                    write_event(&mut writer, Event::Start(shared_formula::detached_f(e)))?;
+                    write_event(&mut writer, Event::Text(BytesText::new(&formula)))?;
+                    write_event(&mut writer, Event::End(e.to_end().into_owned()))?;
                 } else {
-                    if !skip_until_cell_end {
-                        write_event(&mut writer, Event::Empty(e.to_owned()))?;
-                    }
+                    write_event(&mut writer, Event::Empty(e.to_owned()))?;
                 }
             }
             Ok(Event::End(ref e)) => {
-                let tag = e.name().as_ref().to_vec();
+                let tag = e.local_name().as_ref().to_vec();
 
                 if tag == b"c" && skip_until_cell_end {
                     skip_until_cell_end = false;
@@ -207,24 +323,42 @@ pub fn patch_worksheet(xml: &str, patches: &[CellPatch]) -> Result<String, Strin
                         if let Some(row_map) = row_patches.get(&r) {
                             for (&col, patch) in row_map.iter() {
                                 if !current_row_cols_seen.contains(&col) {
+                                    let default = current_row_style.or_else(|| col_styles.get(col));
                                     let cell_ref = col_row_to_a1(col, r);
-                                    write_new_cell(&mut writer, &cell_ref, patch)?;
+                                    write_new_cell(
+                                        &mut writer,
+                                        &prefix,
+                                        &cell_ref,
+                                        patch,
+                                        default,
+                                    )?;
                                 }
                             }
                         }
                     }
                     current_row = None;
+                    current_row_style = None;
                     write_event(&mut writer, Event::End(e.to_owned()))?;
                 } else if tag == b"sheetData" {
                     // Before closing sheetData, insert any remaining rows
                     for (&row_num, row_map) in &row_patches {
                         if !rows_seen.contains(&row_num) {
-                            write_new_row(&mut writer, row_num, row_map)?;
+                            write_new_row(
+                                &mut writer,
+                                &prefix,
+                                row_num,
+                                row_map,
+                                &col_styles,
+                                spans,
+                            )?;
                         }
                     }
                     in_sheet_data = false;
                     write_event(&mut writer, Event::End(e.to_owned()))?;
                 } else if !skip_until_cell_end {
+                    if tag == b"f" {
+                        in_detached_f = false;
+                    }
                     write_event(&mut writer, Event::End(e.to_owned()))?;
                 }
             }
@@ -234,7 +368,7 @@ pub fn patch_worksheet(xml: &str, patches: &[CellPatch]) -> Result<String, Strin
             | Ok(event @ Event::Decl(_))
             | Ok(event @ Event::PI(_))
             | Ok(event @ Event::DocType(_)) => {
-                if !skip_until_cell_end {
+                if !skip_until_cell_end && !in_detached_f {
                     write_event(&mut writer, event.into_owned())?;
                 }
             }
@@ -244,28 +378,41 @@ pub fn patch_worksheet(xml: &str, patches: &[CellPatch]) -> Result<String, Strin
         buf.clear();
     }
 
-    let out = writer.into_inner();
-    String::from_utf8(out).map_err(|e| format!("Output not UTF-8: {e}"))
+    String::from_utf8(writer.into_inner()).map_err(|e| format!("Output not UTF-8: {e}"))
 }
 
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
 
+/// `"x:"` for `<x:sheetData>`, `""` for the default namespace.
+pub(super) fn name_prefix(e: &BytesStart<'_>) -> String {
+    match e.name().prefix() {
+        Some(p) => format!("{}:", String::from_utf8_lossy(p.as_ref())),
+        None => String::new(),
+    }
+}
+
+/// Qualified element name in the document's prefix.
+fn tag_name(prefix: &str, local: &str) -> String {
+    format!("{prefix}{local}")
+}
+
 fn write_event<W: Write>(writer: &mut XmlWriter<W>, event: Event<'_>) -> Result<(), String> {
     writer
         .write_event(event)
         .map_err(|e| format!("XML write error: {e}"))
 }
 
-/// Write a patched `<c ...>` start tag for a style-only patch, preserving original children.
-fn write_style_only_cell_start<W: Write>(
-    writer: &mut XmlWriter<W>,
+/// Patched `<c ...>` tag for a style-only patch: original attributes (including
+/// `t`) with the new `s`. The caller writes it as a start tag and streams the
+/// original children through, or as an empty tag for a self-closing cell.
+fn style_only_cell(
     cell_ref: &str,
     original: &BytesStart<'_>,
     patch: &CellPatch,
-) -> Result<(), String> {
-    let mut elem = BytesStart::new("c");
+) -> Result<BytesStart<'static>, String> {
+    let mut elem = BytesStart::new(String::from_utf8_lossy(original.name().as_ref()).into_owned());
 
     // Copy all original attributes except r/s. We'll re-add r and (patched) s.
     for a in original.attributes() {
@@ -293,17 +440,18 @@ fn write_style_only_cell_start<W: Write>(
         }
     }
 
-    write_event(writer, Event::Start(elem))
+    Ok(elem)
 }
 
 /// Write a complete patched cell element.
 fn write_patched_cell<W: Write>(
     writer: &mut XmlWriter<W>,
+    prefix: &str,
     cell_ref: &str,
     original: &BytesStart<'_>,
     patch: &CellPatch,
 ) -> Result<(), String> {
-    let mut elem = BytesStart::new("c");
+    let mut elem = BytesStart::new(tag_name(prefix, "c"));
     elem.push_attribute(("r", cell_ref));
 
     // Style index: use patch value if set, otherwise preserve original
@@ -319,34 +467,19 @@ fn write_patched_cell<W: Write>(
     }
 
     match &patch.value {
+        // Explicit blank, or a style-only insert of a brand-new cell (existing
+        // cells with a value-less patch go through `style_only_cell`).
         Some(CellValue::Blank) | None => {
-            if patch.value.is_some() {
-                // Explicit blank — write empty cell
-                writer
-                    .write_event(Event::Empty(elem))
-                    .map_err(|e| format!("XML write error: {e}"))?;
-            } else {
-                // No value change — need to preserve original value
-                // For simplicity, write the cell with original type
-                // This path means only style changed, copy original attributes
-                let orig_type = attr_value(original, b"t");
-                if let Some(t) = &orig_type {
-                    elem.push_attribute(("t", t.as_str()));
-                }
-                // Write as start tag, original children will follow via skip logic...
-                // Actually, since skip_until_cell_end skips children, we need to
-                // read and replay them.  For now, write empty if no value patch.
-                writer
-                    .write_event(Event::Empty(elem))
-                    .map_err(|e| format!("XML write error: {e}"))?;
-            }
+            writer
+                .write_event(Event::Empty(elem))
+                .map_err(|e| format!("XML write error: {e}"))?;
         }
         Some(CellValue::Number(n)) => {
             writer
                 .write_event(Event::Start(elem))
                 .map_err(|e| format!("XML write error: {e}"))?;
             // <v>number</v>
-            let v_start = BytesStart::new("v");
+            let v_start = BytesStart::new(tag_name(prefix, "v"));
             writer
                 .write_event(Event::Start(v_start))
                 .map_err(|e| format!("XML write error: {e}"))?;
@@ -359,10 +492,10 @@ fn write_patched_cell<W: Write>(
                 .write_event(Event::Text(BytesText::new(&text)))
                 .map_err(|e| format!("XML write error: {e}"))?;
             writer
-                .write_event(Event::End(BytesEnd::new("v")))
+                .write_event(Event::End(BytesEnd::new(tag_name(prefix, "v"))))
                 .map_err(|e| format!("XML write error: {e}"))?;
             writer
-                .write_event(Event::End(BytesEnd::new("c")))
+                .write_event(Event::End(BytesEnd::new(tag_name(prefix, "c"))))
                 .map_err(|e| format!("XML write error: {e}"))?;
         }
         Some(CellValue::String(s)) => {
@@ -370,7 +503,7 @@ fn write_patched_cell<W: Write>(
             writer
                 .write_event(Event::Start(elem))
                 .map_err(|e| format!("XML write error: {e}"))?;
-            let v_start = BytesStart::new("v");
+            let v_start = BytesStart::new(tag_name(prefix, "v"));
             writer
                 .write_event(Event::Start(v_start))
                 .map_err(|e| format!("XML write error: {e}"))?;
@@ -378,10 +511,10 @@ fn write_patched_cell<W: Write>(
                 .write_event(Event::Text(BytesText::new(s)))
                 .map_err(|e| format!("XML write error: {e}"))?;
             writer
-                .write_event(Event::End(BytesEnd::new("v")))
+                .write_event(Event::End(BytesEnd::new(tag_name(prefix, "v"))))
                 .map_err(|e| format!("XML write error: {e}"))?;
             writer
-                .write_event(Event::End(BytesEnd::new("c")))
+                .write_event(Event::End(BytesEnd::new(tag_name(prefix, "c"))))
                 .map_err(|e| format!("XML write error: {e}"))?;
         }
         Some(CellValue::Boolean(b)) => {
@@ -389,7 +522,7 @@ fn write_patched_cell<W: Write>(
             writer
                 .write_event(Event::Start(elem))
                 .map_err(|e| format!("XML write error: {e}"))?;
-            let v_start = BytesStart::new("v");
+            let v_start = BytesStart::new(tag_name(prefix, "v"));
             writer
                 .write_event(Event::Start(v_start))
                 .map_err(|e| format!("XML write error: {e}"))?;
@@ -398,10 +531,10 @@ fn write_patched_cell<W: Write>(
                 .write_event(Event::Text(BytesText::new(val)))
                 .map_err(|e| format!("XML write error: {e}"))?;
             writer
-                .write_event(Event::End(BytesEnd::new("v")))
+                .write_event(Event::End(BytesEnd::new(tag_name(prefix, "v"))))
                 .map_err(|e| format!("XML write error: {e}"))?;
             writer
-                .write_event(Event::End(BytesEnd::new("c")))
+                .write_event(Event::End(BytesEnd::new(tag_name(prefix, "c"))))
                 .map_err(|e| format!("XML write error: {e}"))?;
         }
         Some(CellValue::Formula(f)) => {
@@ -409,7 +542,7 @@ fn write_patched_cell<W: Write>(
                 .write_event(Event::Start(elem))
                 .map_err(|e| format!("XML write error: {e}"))?;
             // <f>formula</f> — no <v> (force recalc)
-            let f_start = BytesStart::new("f");
+            let f_start = BytesStart::new(tag_name(prefix, "f"));
             writer
                 .write_event(Event::Start(f_start))
                 .map_err(|e| format!("XML write error: {e}"))?;
@@ -417,10 +550,10 @@ fn write_patched_cell<W: Write>(
                 .write_event(Event::Text(BytesText::new(f)))
                 .map_err(|e| format!("XML write error: {e}"))?;
             writer
-                .write_event(Event::End(BytesEnd::new("f")))
+                .write_event(Event::End(BytesEnd::new(tag_name(prefix, "f"))))
                 .map_err(|e| format!("XML write error: {e}"))?;
             writer
-                .write_event(Event::End(BytesEnd::new("c")))
+                .write_event(Event::End(BytesEnd::new(tag_name(prefix, "c"))))
                 .map_err(|e| format!("XML write error: {e}"))?;
         }
     }
@@ -428,24 +561,34 @@ fn write_patched_cell<W: Write>(
     Ok(())
 }
 
-/// Write a brand-new cell element (insertion, not replacement).
+/// Write a brand-new cell element (insertion, not replacement). `default`
+/// is the row or column style it takes when the patch sets none.
 fn write_new_cell<W: Write>(
     writer: &mut XmlWriter<W>,
+    prefix: &str,
     cell_ref: &str,
     patch: &CellPatch,
+    default: Option<u32>,
 ) -> Result<(), String> {
-    let dummy = BytesStart::new("c");
-    write_patched_cell(writer, cell_ref, &dummy, patch)
+    let mut dummy = BytesStart::new(tag_name(prefix, "c"));
+    if let Some(s) = default {
+        dummy.push_attribute(("s", s.to_string().as_str()));
+    }
+    write_patched_cell(writer, prefix, cell_ref, &dummy, patch)
 }
 
 /// Write a brand-new `<row>` element containing patched cells.
 fn write_new_row<W: Write>(
     writer: &mut XmlWriter<W>,
+    prefix: &str,
     row_num: u32,
     cells: &BTreeMap<u32, &CellPatch>,
+    col_styles: &ColumnStyles,
+    spans: RowSpans,
 ) -> Result<(), String> {
-    let mut row_elem = BytesStart::new("row");
+    let mut row_elem = BytesStart::new(tag_name(prefix, "row"));
     row_elem.push_attribute(("r", row_num.to_string().as_str()));
+    let row_elem = with_spans(&row_elem, spans, patch_extent(cells))?;
 
     writer
         .write_event(Event::Start(row_elem))
@@ -453,43 +596,112 @@ fn write_new_row<W: Write>(
 
     for (&col, patch) in cells {
         let cell_ref = col_row_to_a1(col, row_num);
-        write_new_cell(writer, &cell_ref, patch)?;
+        write_new_cell(writer, prefix, &cell_ref, patch, col_styles.get(col))?;
     }
 
     writer
-        .write_event(Event::End(BytesEnd::new("row")))
+        .write_event(Event::End(BytesEnd::new(tag_name(prefix, "row"))))
         .map_err(|e| format!("XML write error: {e}"))?;
 
     Ok(())
 }
 
-/// Parse a cell reference like "B3" into (row=3, col=2) — both 1-based.
-fn parse_cell_ref(cell_ref: &str) -> (u32, u32) {
-    let mut col: u32 = 0;
-    let mut row_str = String::new();
-
-    for ch in cell_ref.chars() {
-        if ch.is_ascii_alphabetic() {
-            col = col * 26 + (ch.to_ascii_uppercase() as u32 - b'A' as u32 + 1);
-        } else if ch.is_ascii_digit() {
-            row_str.push(ch);
+/// First and last column patched in a row.
+fn patch_extent(cells: &BTreeMap<u32, &CellPatch>) -> Option<(u32, u32)> {
+    Some((*cells.keys().next()?, *cells.keys().next_back()?))
+}
+
+/// First and last column of the existing cells in each row of `rows`.
+fn row_extents<T>(xml: &str, rows: &BTreeMap<u32, T>) -> Result<HashMap<u32, (u32, u32)>, String> {
+    let mut reader = XmlReader::from_str(xml);
+    let mut extents: HashMap<u32, (u32, u32)> = HashMap::new();
+    let mut row: Option<u32> = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"row" => {
+                row = attr_value(&e, b"r")
+                    .and_then(|r| r.parse().ok())
+                    .filter(|r| rows.contains_key(r));
+            }
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"row" => row = None,
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.local_name().as_ref() == b"c" => {
+                let Some(row) = row else { continue };
+                let (_, col) = parse_cell_ref(&attr_value(&e, b"r").unwrap_or_default());
+                if col == 0 {
+                    continue;
+                }
+                let extent = extents.entry(row).or_insert((col, col));
+                *extent = (extent.0.min(col), extent.1.max(col));
+            }
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"sheetData" => break,
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(format!("XML parse error: {e}")),
         }
     }
+    Ok(extents)
+}
 
-    let row = row_str.parse::<u32>().unwrap_or(0);
-    (row, col)
+/// `row` with its `spans` set to `extent` (Recompute) or removed (Strip).
+fn with_spans(
+    row: &BytesStart<'_>,
+    spans: RowSpans,
+    extent: Option<(u32, u32)>,
+) -> Result<BytesStart<'static>, String> {
+    let mut elem = BytesStart::new(String::from_utf8_lossy(row.name().as_ref()).into_owned());
+    for a in row.attributes() {
+        let a = a.map_err(|e| format!("XML attr error: {e}"))?;
+        if a.key.as_ref() != b"spans" {
+            elem.push_attribute((a.key.as_ref(), a.value.as_ref()));
+        }
+    }
+    if let (RowSpans::Recompute, Some((first, last))) = (spans, extent) {
+        elem.push_attribute(("spans", format!("{first}:{last}").as_str()));
+    }
+    Ok(elem)
+}
+
+/// Default styles of `<col>` spans as (first, last, style), 1-based.
+#[derive(Default)]
+struct ColumnStyles(Vec<(u32, u32, u32)>);
+
+impl ColumnStyles {
+    fn add(&mut self, col: &BytesStart<'_>) {
+        let num = |key: &[u8]| attr_value(col, key).and_then(|v| v.parse::<u32>().ok());
+        if let (Some(min), Some(max), Some(style)) = (num(b"min"), num(b"max"), num(b"style")) {
+            if style > 0 {
+                self.0.push((min, max, style));
+            }
+        }
+    }
+
+    fn get(&self, col: u32) -> Option<u32> {
+        self.0
+            .iter()
+            .find(|&&(min, max, _)| (min..=max).contains(&col))
+            .map(|&(_, _, style)| style)
+    }
+}
+
+/// Default style of a `<row>`; `s` only applies with `customFormat` set.
+fn row_style(row: &BytesStart<'_>) -> Option<u32> {
+    let custom = attr_value(row, b"customFormat").is_some_and(|v| v == "1" || v == "true");
+    attr_value(row, b"s")
+        .and_then(|v| v.parse().ok())
+        .filter(|&s| custom && s > 0)
+}
+
+/// Parse a cell reference like "B3" into (row=3, col=2) — both 1-based.
+/// Unparseable references yield (0, 0).
+fn parse_cell_ref(cell_ref: &str) -> (u32, u32) {
+    CellRef::parse(cell_ref)
+        .map(|c| (c.row + 1, c.col + 1))
+        .unwrap_or((0, 0))
 }
 
 /// Convert 1-based (col, row) to A1-style reference.
 fn col_row_to_a1(col: u32, row: u32) -> String {
-    let mut letters = String::new();
-    let mut c = col;
-    while c > 0 {
-        c -= 1;
-        letters.insert(0, (b'A' + (c % 26) as u8) as char);
-        c /= 26;
-    }
-    format!("{letters}{row}")
+    CellRef::new(row - 1, col - 1).to_a1()
 }
 
 #[cfg(test)]
@@ -526,7 +738,7 @@ mod tests {
             style_index: None,
         }];
 
-        let result = patch_worksheet(xml, &patches).unwrap();
+        let result = patch_worksheet(xml, &patches, RowSpans::Recompute).unwrap();
         assert!(result.contains("<v>99</v>"));
         // A1 should be unchanged (though type=s is preserved)
         assert!(result.contains("r=\"A1\""));
@@ -545,7 +757,7 @@ mod tests {
             style_index: None,
         }];
 
-        let result = patch_worksheet(xml, &patches).unwrap();
+        let result = patch_worksheet(xml, &patches, RowSpans::Recompute).unwrap();
         assert!(result.contains("r=\"C1\""));
         assert!(result.contains("t=\"str\""));
         assert!(result.contains("<v>new</v>"));
@@ -565,7 +777,7 @@ mod tests {
             style_index: None,
         }];
 
-        let result = patch_worksheet(xml, &patches).unwrap();
+        let result = patch_worksheet(xml, &patches, RowSpans::Recompute).unwrap();
         assert!(result.contains("r=\"A2\""));
         assert!(result.contains("<v>inserted</v>"));
         // Verify row ordering: row 1 before row 2 before row 3
@@ -589,7 +801,7 @@ mod tests {
             style_index: None,
         }];
 
-        let result = patch_worksheet(xml, &patches).unwrap();
+        let result = patch_worksheet(xml, &patches, RowSpans::Recompute).unwrap();
         assert!(result.contains("<f>SUM(B1:B10)</f>"));
         // No <v> — forces recalculation
         assert!(!result.contains("<v>10</v>"));
@@ -608,10 +820,173 @@ mod tests {
             style_index: Some(5),
         }];
 
-        let result = patch_worksheet(xml, &patches).unwrap();
+        let result = patch_worksheet(xml, &patches, RowSpans::Recompute).unwrap();
         assert!(result.contains("s=\"5\""));
     }
 
+    #[test]
+    fn test_style_only_patch_keeps_formula() {
+        let xml = r#"<worksheet><sheetData>
+<row r="1"><c r="A1" t="str" s="2"><f>UPPER(B1)</f><v>HI</v></c><c r="B1" t="s"/></row>
+</sheetData></worksheet>"#;
+
+        let patches = vec![
+            CellPatch {
+                row: 1,
+                col: 1,
+                value: None,
+                style_index: Some(7),
+            },
+            CellPatch {
+                row: 1,
+                col: 2,
+                value: None,
+                style_index: Some(3),
+            },
+        ];
+
+        let result = patch_worksheet(xml, &patches, RowSpans::Recompute).unwrap();
+        assert!(result.contains(r#"<c t="str" r="A1" s="7"><f>UPPER(B1)</f><v>HI</v></c>"#));
+        assert!(result.contains(r#"<c t="s" r="B1" s="3"/>"#));
+    }
+
+    #[test]
+    fn test_valueless_patch_without_style_keeps_cell() {
+        let xml = r#"<worksheet><sheetData>
+<row r="1"><c r="A1" s="4"><f>1+1</f><v>2</v></c></row>
+</sheetData></worksheet>"#;
+
+        let patches = vec![CellPatch {
+            row: 1,
+            col: 1,
+            value: None,
+            style_index: None,
+        }];
+
+        let result = patch_worksheet(xml, &patches, RowSpans::Recompute).unwrap();
+        assert!(result.contains(r#"<c r="A1" s="4"><f>1+1</f><v>2</v></c>"#));
+    }
+
+    /// Open XML SDK output: everything in the `x:` prefix.
+    const PREFIXED: &str = r#"<x:worksheet xmlns:x="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><x:sheetData>
+<x:row r="2"><x:c r="A2" t="s"><x:v>0</x:v></x:c><x:c r="B2"><x:v>42</x:v></x:c></x:row>
+</x:sheetData></x:worksheet>"#;
+
+    #[test]
+    fn test_patch_prefixed_replace_value() {
+        let patches = vec![CellPatch {
+            row: 2,
+            col: 2,
+            value: Some(CellValue::Number(99.0)),
+            style_index: None,
+        }];
+
+        let result = patch_worksheet(PREFIXED, &patches, RowSpans::Recompute).unwrap();
+        assert!(result.contains(r#"<x:c r="B2"><x:v>99</x:v></x:c>"#));
+        assert!(!result.contains("42"));
+        assert!(!result.contains("<c "));
+    }
+
+    #[test]
+    fn test_patch_prefixed_inserts_rows_and_cells() {
+        let patches = vec![
+            CellPatch {
+                row: 1,
+                col: 1,
+                value: Some(CellValue::String("head".to_string())),
+                style_index: None,
+            },
+            CellPatch {
+                row: 2,
+                col: 3,
+                value: Some(CellValue::Formula("B2*2".to_string())),
+                style_index: None,
+            },
+            CellPatch {
+                row: 5,
+                col: 1,
+                value: Some(CellValue::Boolean(true)),
+                style_index: None,
+            },
+        ];
+
+        let result = patch_worksheet(PREFIXED, &patches, RowSpans::Strip).unwrap();
+        assert!(
+            result.contains(r#"<x:row r="1"><x:c r="A1" t="str"><x:v>head</x:v></x:c></x:row>"#)
+        );
+        assert!(result.contains(r#"<x:c r="C2"><x:f>B2*2</x:f></x:c></x:row>"#));
+        assert!(result.contains(r#"<x:row r="5"><x:c r="A5" t="b"><x:v>1</x:v></x:c></x:row>"#));
+        assert!(!result.contains("<row") && !result.contains("<c "));
+    }
+
+    #[test]
+    fn test_patch_prefixed_empty_sheet_data() {
+        let xml = r#"<x:worksheet xmlns:x="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><x:sheetData/></x:worksheet>"#;
+
+        let patches = vec![CellPatch {
+            row: 1,
+            col: 1,
+            value: Some(CellValue::Number(1.0)),
+            style_index: None,
+        }];
+
+        let result = patch_worksheet(xml, &patches, RowSpans::Strip).unwrap();
+        assert!(result.contains(
+            r#"<x:sheetData><x:row r="1"><x:c r="A1"><x:v>1</x:v></x:c></x:row></x:sheetData>"#
+        ));
+    }
+
+    const SHARED: &str = r#"<worksheet><sheetData>
+<row r="1"><c r="B1"><f t="shared" ref="B1:B3" si="0">A1*2</f><v>2</v></c></row>
+<row r="2"><c r="B2"><f t="shared" si="0"/><v>4</v></c></row>
+<row r="3"><c r="B3"><f t="shared" si="0"></f><v>6</v></c></row>
+</sheetData></worksheet>"#;
+
+    #[test]
+    fn test_patch_shared_master_detaches_dependents() {
+        let patches = vec![CellPatch {
+            row: 1,
+            col: 2,
+            value: Some(CellValue::Number(0.0)),
+            style_index: None,
+        }];
+
+        let result = patch_worksheet(SHARED, &patches, RowSpans::Recompute).unwrap();
+        assert!(result.contains(r#"<c r="B1"><v>0</v></c>"#));
+        assert!(result.contains(r#"<c r="B2"><f>A2*2</f><v>4</v></c>"#));
+        assert!(result.contains(r#"<c r="B3"><f>A3*2</f><v>6</v></c>"#));
+        assert!(!result.contains("shared"));
+    }
+
+    #[test]
+    fn test_patch_shared_dependent_keeps_group() {
+        let patches = vec![CellPatch {
+            row: 2,
+            col: 2,
+            value: Some(CellValue::Formula("A2*3".to_string())),
+            style_index: None,
+        }];
+
+        let result = patch_worksheet(SHARED, &patches, RowSpans::Recompute).unwrap();
+        assert!(result.contains(r#"<f t="shared" ref="B1:B3" si="0">A1*2</f>"#));
+        assert!(result.contains(r#"<c r="B2"><f>A2*3</f></c>"#));
+        assert!(result.contains(r#"<c r="B3"><f t="shared" si="0"></f>"#));
+    }
+
+    #[test]
+    fn test_patch_shared_master_style_only_keeps_group() {
+        let patches = vec![CellPatch {
+            row: 1,
+            col: 2,
+            value: None,
+            style_index: Some(4),
+        }];
+
+        let result = patch_worksheet(SHARED, &patches, RowSpans::Recompute).unwrap();
+        assert!(result.contains(r#"<c r="B1" s="4"><f t="shared" ref="B1:B3" si="0">A1*2</f>"#));
+        assert!(result.contains(r#"<c r="B2"><f t="shared" si="0"/>"#));
+    }
+
     #[test]
     fn test_patch_boolean() {
         let xml = r#"<worksheet><sheetData>
@@ -625,7 +1000,7 @@ mod tests {
             style_index: None,
         }];
 
-        let result = patch_worksheet(xml, &patches).unwrap();
+        let result = patch_worksheet(xml, &patches, RowSpans::Recompute).unwrap();
         assert!(result.contains("t=\"b\""));
         assert!(result.contains("<v>1</v>"));
     }
@@ -641,18 +1016,129 @@ mod tests {
             style_index: None,
         }];
 
-        let result = patch_worksheet(xml, &patches).unwrap();
+        let result = patch_worksheet(xml, &patches, RowSpans::Recompute).unwrap();
         assert!(result.contains("r=\"A1\""));
         assert!(result.contains("<v>hello</v>"));
     }
 
+    #[test]
+    fn test_patch_new_cells_inherit_row_and_column_styles() {
+        let xml = concat!(
+            r#"<worksheet><cols><col min="2" max="3" width="12" style="4" customWidth="1"/>"#,
+            r#"</cols><sheetData>"#,
+            r#"<row r="1" s="7" customFormat="1"><c r="A1" s="7"><v>1</v></c></row>"#,
+            r#"<row r="2" s="9"><c r="A2"><v>2</v></c></row>"#,
+            r#"<row r="4" s="7" customFormat="1" ht="30" customHeight="1"/>"#,
+            r#"</sheetData></worksheet>"#
+        );
+        let patch = |row, col, style_index| CellPatch {
+            row,
+            col,
+            value: Some(CellValue::Number(5.0)),
+            style_index,
+        };
+        let patches = vec![
+            patch(1, 3, None),
+            patch(2, 2, None),
+            patch(2, 4, None),
+            patch(3, 3, None),
+            patch(4, 1, None),
+            patch(4, 2, Some(11)),
+        ];
+
+        let result = patch_worksheet(xml, &patches, RowSpans::Recompute).unwrap();
+        // Row style wins over the column's; `s` without customFormat is ignored.
+        assert!(result.contains(r#"<c r="C1" s="7"><v>5</v></c>"#));
+        assert!(result.contains(r#"<c r="B2" s="4"><v>5</v></c>"#));
+        assert!(result.contains(r#"<c r="D2"><v>5</v></c>"#));
+        // A brand-new row only has the column default.
+        assert!(result.contains(r#"<row r="3" spans="3:3"><c r="C3" s="4"><v>5</v></c></row>"#));
+        // An expanded empty row keeps its attributes; a patch style wins.
+        assert!(result.contains(concat!(
+            r#"<row r="4" s="7" customFormat="1" ht="30" customHeight="1" spans="1:2">"#,
+            r#"<c r="A4" s="7"><v>5</v></c><c r="B4" s="11"><v>5</v></c></row>"#
+        )));
+    }
+
+    #[test]
+    fn test_patch_row_spans_recompute_and_strip() {
+        let xml = concat!(
+            r#"<worksheet><sheetData>"#,
+            r#"<row r="1" spans="2:3"><c r="B1"><v>1</v></c><c r="C1"><v>2</v></c></row>"#,
+            r#"<row r="2" spans="1:1" ht="20"><c r="A2"><v>3</v></c></row>"#,
+            r#"<row r="3" spans="2:2"><c r="B3"><v>4</v></c></row>"#,
+            r#"</sheetData></worksheet>"#
+        );
+        let patch = |row, col| CellPatch {
+            row,
+            col,
+            value: Some(CellValue::Number(9.0)),
+            style_index: None,
+        };
+        let patches = vec![patch(1, 5), patch(2, 1), patch(4, 2), patch(4, 6)];
+
+        let result = patch_worksheet(xml, &patches, RowSpans::Recompute).unwrap();
+        // Grown by an insert; rewritten in place; untouched; brand new.
+        assert!(result.contains(r#"<row r="1" spans="2:5">"#));
+        assert!(result.contains(r#"<row r="2" ht="20" spans="1:1">"#));
+        assert!(result.contains(r#"<row r="3" spans="2:2">"#));
+        assert!(result.contains(r#"<row r="4" spans="2:6">"#));
+
+        let result = patch_worksheet(xml, &patches, RowSpans::Strip).unwrap();
+        assert!(result.contains(r#"<row r="1">"#));
+        assert!(result.contains(r#"<row r="2" ht="20">"#));
+        assert!(result.contains(r#"<row r="3" spans="2:2">"#));
+        assert!(result.contains(r#"<row r="4">"#));
+        assert_eq!(RowSpans::parse("strip"), Some(RowSpans::Strip));
+        assert_eq!(RowSpans::parse("bogus"), None);
+    }
+
+    /// rust_xlsxwriter output for a sheet with notes on A1 and B2.
+    const COMMENTED: &str = concat!(
+        r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+        r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+        r#"<dimension ref="A1:B2"/><sheetData><row r="1"><c r="A1" t="s"><v>0</v></c></row>"#,
+        r#"<row r="2"><c r="B2"><f>A1&amp;"!"</f><v>0</v></c></row></sheetData>"#,
+        r#"<pageMargins left="0.7" right="0.7" top="0.75" bottom="0.75" header="0.3" "#,
+        r#"footer="0.3"/><legacyDrawing r:id="rId1"/></worksheet>"#
+    );
+
+    #[test]
+    fn test_patch_commented_cells_keeps_legacy_drawing() {
+        let patches = vec![
+            CellPatch {
+                row: 1,
+                col: 1,
+                value: Some(CellValue::String("note here".to_string())),
+                style_index: Some(2),
+            },
+            CellPatch {
+                row: 2,
+                col: 2,
+                value: Some(CellValue::Blank),
+                style_index: None,
+            },
+            CellPatch {
+                row: 3,
+                col: 3,
+                value: Some(CellValue::Number(7.0)),
+                style_index: None,
+            },
+        ];
+
+        let result = patch_worksheet(COMMENTED, &patches, RowSpans::Recompute).unwrap();
+        assert!(result.contains(r#"<c r="A1" s="2" t="str"><v>note here</v></c>"#));
+        assert!(result.contains(r#"<c r="B2"/>"#));
+        assert!(result.ends_with(r#"<legacyDrawing r:id="rId1"/></worksheet>"#));
+    }
+
     #[test]
     fn test_no_patches_returns_unchanged() {
         let xml = r#"<worksheet><sheetData>
 <row r="1"><c r="A1"><v>42</v></c></row>
 </sheetData></worksheet>"#;
 
-        let result = patch_worksheet(xml, &[]).unwrap();
+        let result = patch_worksheet(xml, &[], RowSpans::Recompute).unwrap();
         assert_eq!(result, xml);
     }
 }
@@ -0,0 +1,372 @@
+//! Worksheet XML stream-reader.
+//!
+//! The read-side counterpart of `sheet_patcher`: walks `<sheetData>` with
+//! quick-xml and hands back one row of decoded cells at a time, so callers
+//! decide whether to materialize the sheet or stream it.  Shared strings and
+//! date styles are resolved through a [`ReadContext`] built once per workbook.
+
+use std::io::BufRead;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader as XmlReader;
+
+use crate::cell_ref::CellRef;
+use crate::formula;
+use crate::ooxml_util::attr_value;
+
+// ---------------------------------------------------------------------------
+// Decoded cell types
+// ---------------------------------------------------------------------------
+
+/// A decoded cell value (the cached result, for formula cells).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadValue {
+    Empty,
+    Number(f64),
+    /// Numeric serial in a date-formatted cell.
+    Date(f64),
+    /// `t="d"` ISO-8601 cell.
+    DateIso(String),
+    String(String),
+    Bool(bool),
+    /// Error token as stored (`#DIV/0!`, `#N/A`, ...).
+    Error(String),
+}
+
+/// One non-empty cell of a row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadCell {
+    /// 0-based column.
+    pub col: u32,
+    pub value: ReadValue,
+    /// Formula text with a leading `=`, when the cell carries one.
+    pub formula: Option<String>,
+    /// cellXfs index (0 when the cell has no `s`).
+    pub style: usize,
+}
+
+/// 0-based row index plus its cells in column order.
+pub type ReadRow = (u32, Vec<ReadCell>);
+
+/// Workbook-level tables needed to decode cells.
+#[derive(Debug, Clone, Default)]
+pub struct ReadContext {
+    pub shared_strings: Vec<String>,
+    /// Per cellXfs index: does the style's number format render a date?
+    pub date_styles: Vec<bool>,
+    /// Per cellXfs index: the number format code (`"General"` by default).
+    pub num_formats: Vec<String>,
+}
+
+impl ReadContext {
+    fn is_date_style(&self, style: Option<usize>) -> bool {
+        style.and_then(|s| self.date_styles.get(s).copied()) == Some(true)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+/// Which text node we're collecting.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TextTarget {
+    None,
+    Value,
+    Formula,
+    InlineText,
+}
+
+/// Attributes and collected text for the `<c>` currently being read.
+#[derive(Default)]
+struct PendingCell {
+    col: u32,
+    cell_type: Option<String>,
+    style: Option<usize>,
+    value: String,
+    formula: String,
+    inline: String,
+    has_formula: bool,
+}
+
+impl PendingCell {
+    fn start(e: &BytesStart<'_>, next_col: u32) -> Self {
+        let col = attr_value(e, b"r")
+            .and_then(|r| CellRef::parse(&r).ok())
+            .map(|c| c.col)
+            .unwrap_or(next_col);
+        Self {
+            col,
+            cell_type: attr_value(e, b"t"),
+            style: attr_value(e, b"s").and_then(|s| s.parse().ok()),
+            ..Self::default()
+        }
+    }
+
+    fn finish(self, ctx: &ReadContext) -> Option<ReadCell> {
+        let value = match self.cell_type.as_deref() {
+            Some("s") => self
+                .value
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| ctx.shared_strings.get(i).cloned())
+                .map(ReadValue::String)
+                .unwrap_or(ReadValue::Empty),
+            Some("inlineStr") => ReadValue::String(self.inline),
+            Some("str") => ReadValue::String(self.value),
+            Some("b") => ReadValue::Bool(self.value.trim() == "1"),
+            Some("e") => ReadValue::Error(self.value.trim().to_string()),
+            Some("d") if !self.value.is_empty() => ReadValue::DateIso(self.value),
+            _ => match self.value.trim().parse::<f64>() {
+                Ok(n) if ctx.is_date_style(self.style) => ReadValue::Date(n),
+                Ok(n) => ReadValue::Number(n),
+                Err(_) => ReadValue::Empty,
+            },
+        };
+        // Shared-formula children (`<f t="shared" si="0"/>`) carry no text.
+        let formula = (self.has_formula && !self.formula.is_empty())
+            .then(|| formula::with_equals(&self.formula));
+        if value == ReadValue::Empty && formula.is_none() {
+            return None;
+        }
+        Some(ReadCell {
+            col: self.col,
+            value,
+            formula,
+            style: self.style.unwrap_or(0),
+        })
+    }
+}
+
+/// Stream `<sheetData>` rows to `on_row` in document order.
+///
+/// Rows without cells are skipped. Returning `false` from `on_row` stops the
+/// scan early (e.g. when a consumer hangs up).
+pub fn read_sheet_rows<R: BufRead>(
+    source: R,
+    ctx: &ReadContext,
+    mut on_row: impl FnMut(ReadRow) -> bool,
+) -> Result<(), String> {
+    let mut reader = XmlReader::from_reader(source);
+    reader.config_mut().trim_text(false);
+    let mut buf: Vec<u8> = Vec::new();
+
+    let mut next_row: u32 = 0;
+    let mut next_col: u32 = 0;
+    let mut row: Option<ReadRow> = None;
+    let mut cell: Option<PendingCell> = None;
+    let mut target = TextTarget::None;
+    let mut in_phonetic_run = false;
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| format!("Failed to parse worksheet XML: {e}"))?;
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let empty = matches!(event, Event::Empty(_));
+                match e.local_name().as_ref() {
+                    b"row" => {
+                        let index = attr_value(e, b"r")
+                            .and_then(|r| r.parse::<u32>().ok())
+                            .filter(|r| *r > 0)
+                            .map(|r| r - 1)
+                            .unwrap_or(next_row);
+                        next_row = index + 1;
+                        next_col = 0;
+                        if !empty {
+                            row = Some((index, Vec::new()));
+                        }
+                    }
+                    b"c" => {
+                        let pending = PendingCell::start(e, next_col);
+                        next_col = pending.col + 1;
+                        // `<c r="A1" s="3"/>` is styled but empty; nothing to report.
+                        if !empty {
+                            cell = Some(pending);
+                        }
+                    }
+                    b"v" if !empty => target = TextTarget::Value,
+                    b"f" => {
+                        if let Some(c) = cell.as_mut() {
+                            c.has_formula = true;
+                        }
+                        if !empty {
+                            target = TextTarget::Formula;
+                        }
+                    }
+                    b"rPh" if !empty => in_phonetic_run = true,
+                    b"t" if !empty && !in_phonetic_run => target = TextTarget::InlineText,
+                    _ => {}
+                }
+            }
+            Event::Text(ref t) if target != TextTarget::None => {
+                let text = t
+                    .unescape()
+                    .map_err(|e| format!("Failed to decode worksheet text: {e}"))?;
+                push_text(cell.as_mut(), target, &text);
+            }
+            Event::CData(ref t) if target != TextTarget::None => {
+                let text = String::from_utf8_lossy(t).into_owned();
+                push_text(cell.as_mut(), target, &text);
+            }
+            Event::End(ref e) => match e.local_name().as_ref() {
+                b"v" | b"f" | b"t" => target = TextTarget::None,
+                b"rPh" => in_phonetic_run = false,
+                b"c" => {
+                    if let (Some(pending), Some((_, cells))) = (cell.take(), row.as_mut()) {
+                        if let Some(done) = pending.finish(ctx) {
+                            cells.push(done);
+                        }
+                    }
+                }
+                b"row" => {
+                    if let Some(done) = row.take() {
+                        if !done.1.is_empty() && !on_row(done) {
+                            return Ok(());
+                        }
+                    }
+                }
+                b"sheetData" => return Ok(()),
+                _ => {}
+            },
+            Event::Eof => return Ok(()),
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+fn push_text(cell: Option<&mut PendingCell>, target: TextTarget, text: &str) {
+    let Some(c) = cell else {
+        return;
+    };
+    match target {
+        TextTarget::Value => c.value.push_str(text),
+        TextTarget::Formula => c.formula.push_str(text),
+        TextTarget::InlineText => c.inline.push_str(text),
+        TextTarget::None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(xml: &str, ctx: &ReadContext) -> Vec<ReadRow> {
+        let mut rows = Vec::new();
+        read_sheet_rows(xml.as_bytes(), ctx, |r| {
+            rows.push(r);
+            true
+        })
+        .unwrap();
+        rows
+    }
+
+    fn ctx() -> ReadContext {
+        ReadContext {
+            shared_strings: vec!["Hello".into(), "World".into()],
+            // xf 0 = General, xf 1 = a date format.
+            date_styles: vec![false, true],
+            num_formats: vec!["General".into(), "yyyy-mm-dd".into()],
+        }
+    }
+
+    #[test]
+    fn test_basic_cell_types() {
+        let xml = r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>
+<row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1"><v>42.5</v></c><c r="C1" t="b"><v>1</v></c></row>
+<row r="3"><c r="B3" t="e"><v>#N/A</v></c><c r="C3" t="inlineStr"><is><t>inline &amp; text</t></is></c></row>
+</sheetData></worksheet>"#;
+        let rows = read_all(xml, &ctx());
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].0, 0);
+        assert_eq!(rows[0].1[0].value, ReadValue::String("Hello".into()));
+        assert_eq!(rows[0].1[1].value, ReadValue::Number(42.5));
+        assert_eq!(rows[0].1[2].value, ReadValue::Bool(true));
+        assert_eq!(rows[1].0, 2);
+        assert_eq!(rows[1].1[0].col, 1);
+        assert_eq!(rows[1].1[0].value, ReadValue::Error("#N/A".into()));
+        assert_eq!(
+            rows[1].1[1].value,
+            ReadValue::String("inline & text".into())
+        );
+    }
+
+    #[test]
+    fn test_date_styles_and_iso_dates() {
+        let xml = r#"<worksheet><sheetData><row r="1">
+<c r="A1" s="1"><v>45000</v></c><c r="B1" s="0"><v>45000</v></c><c r="C1" t="d"><v>2024-01-02T03:04:05</v></c>
+</row></sheetData></worksheet>"#;
+        let rows = read_all(xml, &ctx());
+        assert_eq!(rows[0].1[0].value, ReadValue::Date(45000.0));
+        assert_eq!((rows[0].1[0].style, rows[0].1[1].style), (1, 0));
+        assert_eq!(rows[0].1[1].value, ReadValue::Number(45000.0));
+        assert_eq!(
+            rows[0].1[2].value,
+            ReadValue::DateIso("2024-01-02T03:04:05".into())
+        );
+    }
+
+    #[test]
+    fn test_formulas_keep_cached_values() {
+        let xml = r#"<worksheet><sheetData><row r="2">
+<c r="A2"><f>SUM(B2:C2)</f><v>3</v></c><c r="B2" t="str"><f>"a"&amp;"b"</f><v>ab</v></c>
+<c r="C2"><f t="shared" si="0"/><v>7</v></c><c r="D2"><f>NOW()</f></c>
+</row></sheetData></worksheet>"#;
+        let rows = read_all(xml, &ctx());
+        let cells = &rows[0].1;
+        assert_eq!(cells[0].formula.as_deref(), Some("=SUM(B2:C2)"));
+        assert_eq!(cells[0].value, ReadValue::Number(3.0));
+        assert_eq!(cells[1].formula.as_deref(), Some("=\"a\"&\"b\""));
+        assert_eq!(cells[1].value, ReadValue::String("ab".into()));
+        // Shared-formula followers have no text of their own.
+        assert_eq!(cells[2].formula, None);
+        assert_eq!(cells[2].value, ReadValue::Number(7.0));
+        assert_eq!(cells[3].formula.as_deref(), Some("=NOW()"));
+        assert_eq!(cells[3].value, ReadValue::Empty);
+    }
+
+    #[test]
+    fn test_missing_refs_and_empty_cells() {
+        let xml = r#"<worksheet><sheetData>
+<row><c><v>1</v></c><c s="1"/><c><v>2</v></c></row>
+<row spans="1:2"/>
+<row><c r="B3"><v>3</v></c><c><v>4</v></c></row>
+</sheetData></worksheet>"#;
+        let rows = read_all(xml, &ctx());
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].0, 0);
+        assert_eq!(
+            rows[0].1.iter().map(|c| c.col).collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        assert_eq!(rows[1].0, 2);
+        assert_eq!(
+            rows[1].1.iter().map(|c| c.col).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn test_rich_text_runs_skip_phonetics() {
+        let xml = r#"<worksheet><sheetData><row r="1"><c r="A1" t="inlineStr"><is>
+<r><rPr><b/></rPr><t>Bold</t></r><r><t xml:space="preserve"> plain</t></r><rPh sb="0" eb="1"><t>ruby</t></rPh>
+</is></c></row></sheetData></worksheet>"#;
+        let rows = read_all(xml, &ctx());
+        assert_eq!(rows[0].1[0].value, ReadValue::String("Bold plain".into()));
+    }
+
+    #[test]
+    fn test_early_stop() {
+        let xml = r#"<worksheet><sheetData><row r="1"><c><v>1</v></c></row><row r="2"><c><v>2</v></c></row></sheetData></worksheet>"#;
+        let mut seen = 0;
+        read_sheet_rows(xml.as_bytes(), &ctx(), |_| {
+            seen += 1;
+            false
+        })
+        .unwrap();
+        assert_eq!(seen, 1);
+    }
+}
@@ -12,9 +12,12 @@
 //! For patching, WolfXL appends new component entries and a new `<xf>`,
 //! then sets the cell's `s` attribute to the new xf index.
 
+use std::collections::HashMap;
+
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader as XmlReader;
 
+use crate::color::{ColorBase, ColorSpec};
 use crate::ooxml_util::attr_value;
 
 // ---------------------------------------------------------------------------
@@ -39,21 +42,21 @@ pub struct FontSpec {
     pub strikethrough: bool,
     pub name: Option<String>,
     pub size: Option<u32>,         // stored as integer points (e.g. 11)
-    pub color_rgb: Option<String>, // "FFRRGGBB"
+    pub color_rgb: Option<String>, // "FFRRGGBB" or "theme:N[:tint]" / "indexed:N"
 }
 
 /// Fill specification for creating a new `<fill>` element.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct FillSpec {
     pub pattern_type: String,         // "solid", "none", etc.
-    pub fg_color_rgb: Option<String>, // "FFRRGGBB"
+    pub fg_color_rgb: Option<String>, // "FFRRGGBB" or "theme:N[:tint]" / "indexed:N"
 }
 
 /// Border side.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct BorderSideSpec {
     pub style: Option<String>,     // "thin", "medium", "thick", etc.
-    pub color_rgb: Option<String>, // "FFRRGGBB"
+    pub color_rgb: Option<String>, // "FFRRGGBB" or "theme:N[:tint]" / "indexed:N"
 }
 
 /// Border specification for creating a new `<border>` element.
@@ -144,6 +147,35 @@ fn parse_xf_entry(e: &BytesStart<'_>) -> XfEntry {
     }
 }
 
+/// Parse custom `<numFmts>` entries: numFmtId → formatCode.
+pub fn parse_num_fmts(xml: &str) -> HashMap<u32, String> {
+    let mut reader = XmlReader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut buf: Vec<u8> = Vec::new();
+    let mut out: HashMap<u32, String> = HashMap::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                if e.name().as_ref() == b"numFmt" {
+                    let id = attr_value(e, b"numFmtId").and_then(|s| s.parse::<u32>().ok());
+                    let code = attr_value(e, b"formatCode");
+                    if let (Some(id), Some(code)) = (id, code) {
+                        out.insert(id, code);
+                    }
+                }
+            }
+            Ok(Event::End(ref e)) if e.name().as_ref() == b"numFmts" => break,
+            Ok(Event::Eof) => break,
+            Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    out
+}
+
 // ---------------------------------------------------------------------------
 // XML generation helpers
 // ---------------------------------------------------------------------------
@@ -211,6 +243,17 @@ pub fn count_section_elements(xml: &str, section_tag: &str) -> (u32, u64) {
     (count, end_offset)
 }
 
+/// `<{tag} .../>` for a spec color: ARGB hex is written as `rgb`, theme and
+/// indexed colors as `theme`/`tint` or `indexed`.
+fn color_element(tag: &str, color: &str) -> String {
+    match ColorSpec::parse(color) {
+        Ok(spec) if !matches!(spec.base, ColorBase::Rgb(_)) => {
+            format!("<{tag} {}/>", spec.xml_attrs())
+        }
+        _ => format!("<{tag} rgb=\"{color}\"/>"),
+    }
+}
+
 /// Generate `<font>` XML element from a FontSpec.
 pub fn font_to_xml(spec: &FontSpec) -> String {
     let mut parts: Vec<String> = Vec::new();
@@ -230,7 +273,7 @@ pub fn font_to_xml(spec: &FontSpec) -> String {
         parts.push(format!("<sz val=\"{sz}\"/>"));
     }
     if let Some(ref rgb) = spec.color_rgb {
-        parts.push(format!("<color rgb=\"{rgb}\"/>"));
+        parts.push(color_element("color", rgb));
     }
     if let Some(ref name) = spec.name {
         parts.push(format!("<name val=\"{name}\"/>"));
@@ -242,7 +285,7 @@ pub fn font_to_xml(spec: &FontSpec) -> String {
 pub fn fill_to_xml(spec: &FillSpec) -> String {
     let mut inner = format!("<patternFill patternType=\"{}\"", spec.pattern_type);
     if let Some(ref rgb) = spec.fg_color_rgb {
-        inner.push_str(&format!("><fgColor rgb=\"{rgb}\"/></patternFill>"));
+        inner.push_str(&format!(">{}</patternFill>", color_element("fgColor", rgb)));
     } else {
         inner.push_str("/>");
     }
@@ -254,7 +297,8 @@ pub fn border_to_xml(spec: &BorderSpec) -> String {
     fn side_xml(tag: &str, side: &BorderSideSpec) -> String {
         match (&side.style, &side.color_rgb) {
             (Some(style), Some(rgb)) => {
-                format!("<{tag} style=\"{style}\"><color rgb=\"{rgb}\"/></{tag}>")
+                let color = color_element("color", rgb);
+                format!("<{tag} style=\"{style}\">{color}</{tag}>")
             }
             (Some(style), None) => format!("<{tag} style=\"{style}\"/>"),
             _ => format!("<{tag}/>"),
@@ -597,6 +641,20 @@ mod tests {
         assert!(updated.contains("FFFF0000"));
     }
 
+    #[test]
+    fn test_theme_and_indexed_colors() {
+        let font = FontSpec {
+            color_rgb: Some("theme:4:-0.25".to_string()),
+            ..Default::default()
+        };
+        assert!(font_to_xml(&font).contains(r#"<color theme="4" tint="-0.25"/>"#));
+        let fill = FillSpec {
+            pattern_type: "solid".to_string(),
+            fg_color_rgb: Some("indexed:10".to_string()),
+        };
+        assert!(fill_to_xml(&fill).contains(r#"<fgColor indexed="10"/>"#));
+    }
+
     #[test]
     fn test_apply_format_spec_full() {
         let spec = FormatSpec {
@@ -632,6 +690,14 @@ mod tests {
         assert!(updated.contains("<numFmts count=\"1\">"));
     }
 
+    #[test]
+    fn test_parse_num_fmts_round_trip() {
+        let (updated, id) = find_or_create_num_fmt(MINIMAL_STYLES, "yyyy-mm-dd");
+        let fmts = parse_num_fmts(&updated);
+        assert_eq!(fmts.get(&id).map(String::as_str), Some("yyyy-mm-dd"));
+        assert!(parse_num_fmts(MINIMAL_STYLES).is_empty());
+    }
+
     #[test]
     fn test_xf_with_alignment() {
         let align = AlignmentSpec {
@@ -0,0 +1,178 @@
+//! Streaming copy-with-transform (`transform()`).
+//!
+//! Each worksheet is decoded row by row with [`sheet_reader`](super::sheet_reader),
+//! Python callbacks decide which cells change, and the changes are applied
+//! with the same stream-patcher and raw ZIP copy as `XlsxPatcher.save()`.
+//! Styles and every untouched part are carried over byte for byte.
+
+use std::collections::HashMap;
+use std::fs::File;
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use zip::ZipArchive;
+
+use crate::cell_ref::col_to_letters;
+use crate::errors;
+use crate::ooxml_util;
+
+use super::reader::{cell_to_py, load_context, scan_sheet};
+use super::sheet_patcher::{CellPatch, RowSpans};
+use super::sheet_reader::ReadCell;
+use super::{cell_value, patch_parts, rewrite_zip, CAPABILITIES};
+
+/// Copy `src` to `dst`, letting Python callbacks rewrite cell values.
+///
+/// `callbacks` is a dict with either or both of:
+///   - `"row"`: `fn(sheet, row, cells) -> dict | None`, called once per
+///     non-empty row with the 1-based row number and `{a1: payload}`;
+///     returns the payloads to replace (same keys), or None to keep the row.
+///   - `"cell"`: `fn(sheet, a1, payload) -> payload | None`, called for each
+///     non-empty cell (after the row callback); None keeps the cell.
+///
+/// Payloads passed in use the `read_cell_value` shape; returned payloads use
+/// the write shape (`string`, `number`, `boolean`, `formula`, `blank`). Cell
+/// styles are kept. Returns `{"sheets", "cells"}`: sheets scanned, cells
+/// rewritten.
+#[pyfunction]
+pub(crate) fn transform(
+    py: Python<'_>,
+    src: &str,
+    dst: &str,
+    callbacks: &Bound<'_, PyDict>,
+) -> PyResult<PyObject> {
+    for key in callbacks.keys() {
+        let key: String = key.extract()?;
+        if key != "row" && key != "cell" {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "Unknown transform callback '{key}' (expected 'row' or 'cell')"
+            )));
+        }
+    }
+    let on_row = callbacks.get_item("row")?.filter(|f| !f.is_none());
+    let on_cell = callbacks.get_item("cell")?.filter(|f| !f.is_none());
+    if on_row.is_none() && on_cell.is_none() {
+        return Err(PyErr::new::<PyValueError, _>(
+            "transform() needs a 'row' or 'cell' callback",
+        ));
+    }
+
+    let f = File::open(src).map_err(|e| {
+        errors::file_format(
+            CAPABILITIES.backend,
+            src,
+            format!("Cannot open '{src}': {e}"),
+        )
+    })?;
+    let mut zip = ZipArchive::new(f).map_err(|e| {
+        errors::file_format(CAPABILITIES.backend, src, format!("Not a valid ZIP: {e}"))
+    })?;
+    let wb_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/workbook.xml")?;
+    let rels_xml = ooxml_util::zip_read_to_string(&mut zip, "xl/_rels/workbook.xml.rels")?;
+    let sheet_paths = ooxml_util::sheet_part_paths(&wb_xml, &rels_xml)?;
+    let ctx = load_context(&mut zip, &rels_xml)?;
+    let date1904 = ooxml_util::workbook_is_date1904(&wb_xml);
+
+    let mut part_patches: HashMap<String, Vec<CellPatch>> = HashMap::new();
+    let mut cells = 0usize;
+    for (sheet, part) in &sheet_paths {
+        let mut patches: Vec<CellPatch> = Vec::new();
+        let mut failed: Option<PyErr> = None;
+        scan_sheet(src, part, &ctx, |(row, row_cells)| {
+            let cb = Callbacks {
+                row: on_row.as_ref(),
+                cell: on_cell.as_ref(),
+            };
+            match transform_row(py, sheet, row, &row_cells, date1904, cb) {
+                Ok(mut p) => {
+                    patches.append(&mut p);
+                    true
+                }
+                Err(e) => {
+                    failed = Some(e);
+                    false
+                }
+            }
+        })
+        .map_err(|e| PyErr::new::<PyIOError, _>(e))?;
+        if let Some(e) = failed {
+            return Err(e);
+        }
+        if !patches.is_empty() {
+            cells += patches.len();
+            part_patches.insert(part.clone(), patches);
+        }
+    }
+    let file_patches =
+        py.allow_threads(|| patch_parts(&mut zip, &part_patches, RowSpans::default()))?;
+    drop(zip);
+
+    rewrite_zip(src, dst, &file_patches)?;
+
+    let out = PyDict::new(py);
+    out.set_item("sheets", sheet_paths.len())?;
+    out.set_item("cells", cells)?;
+    Ok(out.into())
+}
+
+#[derive(Clone, Copy)]
+struct Callbacks<'a, 'py> {
+    row: Option<&'a Bound<'py, PyAny>>,
+    cell: Option<&'a Bound<'py, PyAny>>,
+}
+
+/// Run the callbacks over one decoded row and return the resulting patches.
+fn transform_row(
+    py: Python<'_>,
+    sheet: &str,
+    row: u32,
+    row_cells: &[ReadCell],
+    date1904: bool,
+    cb: Callbacks<'_, '_>,
+) -> PyResult<Vec<CellPatch>> {
+    let payloads = PyDict::new(py);
+    for cell in row_cells {
+        let a1 = format!("{}{}", col_to_letters(cell.col), row + 1);
+        payloads.set_item(a1, cell_to_py(py, Some(cell), date1904)?)?;
+    }
+    // a1 → replacement payload for this row.
+    let replaced = PyDict::new(py);
+    if let Some(f) = cb.row {
+        let out = f.call1((sheet, row + 1, payloads.clone()))?;
+        if !out.is_none() {
+            for (a1, payload) in out.downcast::<PyDict>()?.iter() {
+                if !payloads.contains(&a1)? {
+                    return Err(PyErr::new::<PyValueError, _>(format!(
+                        "row callback returned {a1}, which is not in row {}",
+                        row + 1
+                    )));
+                }
+                replaced.set_item(&a1, &payload)?;
+                payloads.set_item(a1, payload)?;
+            }
+        }
+    }
+    if let Some(f) = cb.cell {
+        for (a1, payload) in payloads.iter() {
+            let out = f.call1((sheet, &a1, payload))?;
+            if !out.is_none() {
+                replaced.set_item(a1, out)?;
+            }
+        }
+    }
+
+    let mut patches = Vec::with_capacity(replaced.len());
+    for (a1, payload) in replaced.iter() {
+        let a1: String = a1.extract()?;
+        let (r, c) = crate::util::a1_to_row_col(&a1)
+            .map_err(|msg| errors::cell_ref(CAPABILITIES.backend, &a1, msg))?;
+        patches.push(CellPatch {
+            row: r + 1,
+            col: c + 1,
+            value: Some(cell_value(&payload)?),
+            style_index: None,
+        });
+    }
+    Ok(patches)
+}
@@ -0,0 +1,368 @@
+//! workbook.xml stream-patcher for sheet visibility and the active tab.
+//!
+//! Visibility and activation are planned together for the whole workbook
+//! ([`plan_view`]) so a batch of patches is validated as one unit: at least
+//! one sheet stays visible and the active tab never points at a hidden sheet.
+//! The plan is then applied to workbook.xml (`<sheet state>`,
+//! `<workbookView activeTab/firstSheet>`) and to the `tabSelected` flag of
+//! the worksheets whose selection changes.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use quick_xml::Reader as XmlReader;
+use quick_xml::Writer as XmlWriter;
+
+use super::sheet_patcher::name_prefix;
+use crate::ooxml_util::parts::local_attr;
+
+/// `<sheet state>` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetState {
+    Visible,
+    Hidden,
+    /// Only unhideable through VBA.
+    VeryHidden,
+}
+
+impl SheetState {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "visible" => Some(SheetState::Visible),
+            "hidden" => Some(SheetState::Hidden),
+            "veryHidden" => Some(SheetState::VeryHidden),
+            _ => None,
+        }
+    }
+
+    /// The `state` attribute to write (visible sheets omit it).
+    fn attr(self) -> Option<&'static str> {
+        match self {
+            SheetState::Visible => None,
+            SheetState::Hidden => Some("hidden"),
+            SheetState::VeryHidden => Some("veryHidden"),
+        }
+    }
+}
+
+/// Final sheet states and active tab after applying a batch of view patches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewPlan {
+    /// One state per sheet, in workbook order.
+    pub states: Vec<SheetState>,
+    /// 0-based index of the active tab after patching.
+    pub active: usize,
+    /// 0-based index of the active tab before patching.
+    pub previous_active: usize,
+}
+
+/// Combine the current workbook view with queued patches and validate it.
+///
+/// `sheets` is `(name, state attribute)` in workbook order. Without an
+/// explicit `active` sheet, an active tab that ends up hidden moves to the
+/// first visible sheet.
+pub fn plan_view(
+    sheets: &[(String, Option<String>)],
+    current_active: usize,
+    state_patches: &HashMap<String, SheetState>,
+    active: Option<&str>,
+) -> Result<ViewPlan, String> {
+    let index_of = |name: &str| {
+        sheets
+            .iter()
+            .position(|(n, _)| n == name)
+            .ok_or_else(|| format!("Unknown sheet: {name}"))
+    };
+    for name in state_patches.keys() {
+        index_of(name)?;
+    }
+
+    let states: Vec<SheetState> = sheets
+        .iter()
+        .map(|(name, state)| match state_patches.get(name) {
+            Some(s) => *s,
+            None => state
+                .as_deref()
+                .and_then(SheetState::parse)
+                .unwrap_or(SheetState::Visible),
+        })
+        .collect();
+    let Some(first_visible) = states.iter().position(|s| *s == SheetState::Visible) else {
+        return Err(format!(
+            "At least one sheet must remain visible; these changes would hide all {} sheets",
+            sheets.len()
+        ));
+    };
+
+    let previous_active = current_active.min(sheets.len().saturating_sub(1));
+    let active = match active {
+        Some(name) => {
+            let i = index_of(name)?;
+            if states[i] != SheetState::Visible {
+                return Err(format!(
+                    "Cannot make hidden sheet '{name}' the active sheet"
+                ));
+            }
+            i
+        }
+        None if states[previous_active] == SheetState::Visible => previous_active,
+        None => first_visible,
+    };
+
+    Ok(ViewPlan {
+        states,
+        active,
+        previous_active,
+    })
+}
+
+/// The `activeTab` of the first `<workbookView>` (0 when absent).
+pub fn current_active_tab(xml: &str) -> Result<usize, String> {
+    let mut reader = XmlReader::from_str(xml);
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e))
+                if e.local_name().as_ref() == b"workbookView" =>
+            {
+                return Ok(local_attr(&e, b"activeTab")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0));
+            }
+            Ok(Event::Eof) => return Ok(0),
+            Err(e) => return Err(format!("Failed to parse workbook.xml: {e}")),
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Rewrite workbook.xml so `<sheet state>` and the first `<workbookView>`
+/// match `plan`. A missing `<bookViews>` is inserted before `<sheets>` when
+/// the active tab is not the first sheet.
+pub fn patch_workbook_view(xml: &str, plan: &ViewPlan) -> Result<String, String> {
+    let mut reader = XmlReader::from_str(xml);
+    reader.config_mut().trim_text(false);
+    let mut writer = XmlWriter::new(Vec::new());
+    let mut buf: Vec<u8> = Vec::new();
+
+    let mut sheet_index = 0usize;
+    let mut view_patched = false;
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| format!("Failed to parse workbook.xml: {e}"))?;
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let empty = matches!(event, Event::Empty(_));
+                let out = match e.local_name().as_ref() {
+                    b"sheets" if !view_patched && plan.active != 0 => {
+                        let prefix = name_prefix(e);
+                        let mut view = BytesStart::new(format!("{prefix}workbookView"));
+                        view.push_attribute(("activeTab", plan.active.to_string().as_str()));
+                        write_event(
+                            &mut writer,
+                            Event::Start(BytesStart::new(format!("{prefix}bookViews"))),
+                        )?;
+                        write_event(&mut writer, Event::Empty(view))?;
+                        write_event(
+                            &mut writer,
+                            Event::End(BytesEnd::new(format!("{prefix}bookViews"))),
+                        )?;
+                        view_patched = true;
+                        e.to_owned()
+                    }
+                    b"workbookView" if !view_patched => {
+                        view_patched = true;
+                        workbook_view_start(e, plan.active)
+                    }
+                    b"sheet" => {
+                        let state = plan.states.get(sheet_index).copied();
+                        sheet_index += 1;
+                        match state {
+                            Some(state) => with_attr(e, b"state", state.attr()),
+                            None => e.to_owned(),
+                        }
+                    }
+                    _ => e.to_owned(),
+                };
+                if empty {
+                    write_event(&mut writer, Event::Empty(out))?;
+                } else {
+                    write_event(&mut writer, Event::Start(out))?;
+                }
+            }
+            Event::Eof => break,
+            other => write_event(&mut writer, other)?,
+        }
+        buf.clear();
+    }
+
+    String::from_utf8(writer.into_inner()).map_err(|e| format!("UTF-8 error: {e}"))
+}
+
+/// Set or clear `tabSelected` on a worksheet's first `<sheetView>`.
+///
+/// Worksheets without `<sheetViews>` are returned unchanged; Excel derives
+/// the selection from `activeTab` for them.
+pub fn set_tab_selected(xml: &str, selected: bool) -> Result<String, String> {
+    let mut reader = XmlReader::from_str(xml);
+    reader.config_mut().trim_text(false);
+    let mut writer = XmlWriter::new(Vec::new());
+    let mut buf: Vec<u8> = Vec::new();
+    let mut done = false;
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| format!("Failed to parse worksheet XML: {e}"))?;
+        match event {
+            Event::Start(ref e) if !done && e.local_name().as_ref() == b"sheetView" => {
+                done = true;
+                let out = with_attr(e, b"tabSelected", selected.then_some("1"));
+                write_event(&mut writer, Event::Start(out))?;
+            }
+            Event::Empty(ref e) if !done && e.local_name().as_ref() == b"sheetView" => {
+                done = true;
+                let out = with_attr(e, b"tabSelected", selected.then_some("1"));
+                write_event(&mut writer, Event::Empty(out))?;
+            }
+            Event::Eof => break,
+            other => write_event(&mut writer, other)?,
+        }
+        buf.clear();
+    }
+
+    String::from_utf8(writer.into_inner()).map_err(|e| format!("UTF-8 error: {e}"))
+}
+
+// ---------------------------------------------------------------------------
+// Element helpers
+// ---------------------------------------------------------------------------
+
+/// Copy of `e` with the attribute `local` replaced by `value` (or removed).
+fn with_attr(e: &BytesStart<'_>, local: &[u8], value: Option<&str>) -> BytesStart<'static> {
+    let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+    let mut out = BytesStart::new(name);
+    for a in e.attributes().with_checks(false).flatten() {
+        if a.key.local_name().as_ref() != local {
+            out.push_attribute(a);
+        }
+    }
+    if let Some(v) = value {
+        let key = String::from_utf8_lossy(local).into_owned();
+        out.push_attribute((key.as_str(), v));
+    }
+    out
+}
+
+/// `<workbookView>` with `activeTab` set and `firstSheet` pulled back so the
+/// active tab is scrolled into view.
+fn workbook_view_start(e: &BytesStart<'_>, active: usize) -> BytesStart<'static> {
+    let active_str = active.to_string();
+    let mut out = with_attr(
+        e,
+        b"activeTab",
+        (active != 0).then_some(active_str.as_str()),
+    );
+    let first = local_attr(e, b"firstSheet").and_then(|v| v.parse::<usize>().ok());
+    if first.is_some_and(|f| f > active) {
+        out = with_attr(&out, b"firstSheet", Some(active_str.as_str()));
+    }
+    out
+}
+
+fn write_event<W: Write>(writer: &mut XmlWriter<W>, event: Event<'_>) -> Result<(), String> {
+    writer
+        .write_event(event)
+        .map_err(|e| format!("XML write error: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheets(states: &[(&str, Option<&str>)]) -> Vec<(String, Option<String>)> {
+        states
+            .iter()
+            .map(|(n, s)| (n.to_string(), s.map(str::to_string)))
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_view_rejects_all_hidden() {
+        let wb = sheets(&[("A", None), ("B", Some("hidden"))]);
+        let patches = HashMap::from([("A".to_string(), SheetState::Hidden)]);
+        let err = plan_view(&wb, 0, &patches, None).unwrap_err();
+        assert!(
+            err.contains("At least one sheet must remain visible"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_plan_view_moves_active_off_hidden_sheet() {
+        let wb = sheets(&[("A", None), ("B", None), ("C", None)]);
+        let patches = HashMap::from([("A".to_string(), SheetState::VeryHidden)]);
+        let plan = plan_view(&wb, 0, &patches, None).unwrap();
+        assert_eq!(plan.active, 1);
+        assert_eq!(plan.previous_active, 0);
+
+        let err = plan_view(&wb, 0, &patches, Some("A")).unwrap_err();
+        assert!(err.contains("Cannot make hidden sheet 'A'"), "{err}");
+        assert_eq!(plan_view(&wb, 0, &patches, Some("C")).unwrap().active, 2);
+        assert!(plan_view(&wb, 0, &HashMap::new(), Some("Z")).is_err());
+    }
+
+    #[test]
+    fn test_patch_workbook_view() {
+        let xml = r#"<workbook><bookViews><workbookView firstSheet="2" activeTab="2"/></bookViews><sheets><sheet name="A" sheetId="1" r:id="rId1"/><sheet name="B" sheetId="2" state="hidden" r:id="rId2"/><sheet name="C" sheetId="3" r:id="rId3"/></sheets></workbook>"#;
+        let plan = ViewPlan {
+            states: vec![SheetState::Visible, SheetState::Visible, SheetState::Hidden],
+            active: 1,
+            previous_active: 2,
+        };
+        let out = patch_workbook_view(xml, &plan).unwrap();
+        assert!(
+            out.contains(r#"<workbookView activeTab="1" firstSheet="1"/>"#),
+            "{out}"
+        );
+        assert!(
+            out.contains(r#"<sheet name="B" sheetId="2" r:id="rId2"/>"#),
+            "{out}"
+        );
+        assert!(
+            out.contains(r#"<sheet name="C" sheetId="3" r:id="rId3" state="hidden"/>"#),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn test_patch_workbook_view_inserts_book_views() {
+        let xml = r#"<x:workbook><x:sheets><x:sheet name="A" r:id="rId1"/><x:sheet name="B" r:id="rId2"/></x:sheets></x:workbook>"#;
+        let plan = ViewPlan {
+            states: vec![SheetState::Visible, SheetState::Visible],
+            active: 1,
+            previous_active: 0,
+        };
+        let out = patch_workbook_view(xml, &plan).unwrap();
+        assert!(
+            out.contains(r#"<x:bookViews><x:workbookView activeTab="1"/></x:bookViews><x:sheets>"#),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn test_set_tab_selected() {
+        let xml = r#"<worksheet><sheetViews><sheetView tabSelected="1" workbookViewId="0"/></sheetViews></worksheet>"#;
+        let out = set_tab_selected(xml, false).unwrap();
+        assert!(out.contains(r#"<sheetView workbookViewId="0"/>"#), "{out}");
+        let out = set_tab_selected(&out, true).unwrap();
+        assert!(
+            out.contains(r#"<sheetView workbookViewId="0" tabSelected="1"/>"#),
+            "{out}"
+        );
+    }
+}
@@ -0,0 +1,377 @@
+#!/usr/bin/env python3
+"""Generate password-protected xlsx fixtures for the calamine decryption tests.
+
+Writes `fixtures/encrypted/standard.xlsx` (ECMA-376 Standard encryption,
+AES-128/SHA-1), `fixtures/encrypted/agile.xlsx` (Agile encryption,
+AES-256/SHA-512) and `fixtures/encrypted/rc4.xlsx` (RC4 CryptoAPI, which
+readers must reject rather than treat as Standard), all with the password in
+`PASSWORD`. The plaintext is a one-sheet workbook: sheet "Secret" with
+A1 = "classified" and B1 = 42.
+
+The OLE container holds only the `EncryptionInfo` and `EncryptedPackage`
+streams; Excel also writes `\\x06DataSpaces`, which readers do not need to
+decrypt the package.
+
+Requires `cryptography`.
+"""
+
+from __future__ import annotations
+
+import argparse
+import hashlib
+import hmac
+import io
+import os
+import struct
+import zipfile
+from base64 import b64encode
+from pathlib import Path
+
+from cryptography.hazmat.primitives.ciphers import Cipher, algorithms, modes
+
+PASSWORD = "excelbench"
+
+_SECTOR = 512
+_MINI_SECTOR = 64
+_MINI_CUTOFF = 4096
+_END_OF_CHAIN = 0xFFFFFFFE
+_FREE_SECT = 0xFFFFFFFF
+_FAT_SECT = 0xFFFFFFFD
+_NO_STREAM = 0xFFFFFFFF
+
+# Agile block keys (MS-OFFCRYPTO 2.3.4.13).
+_BLOCK_VERIFIER_INPUT = bytes.fromhex("fea7d2763b4b9e79")
+_BLOCK_VERIFIER_VALUE = bytes.fromhex("d7aa0f6d3061344e")
+_BLOCK_KEY_VALUE = bytes.fromhex("146e0be7abacd0d6")
+_BLOCK_INTEGRITY_KEY = bytes.fromhex("5fb2ad010cb9e1f6")
+_BLOCK_INTEGRITY_VALUE = bytes.fromhex("a0677f02b22c8433")
+
+
+def _plain_xlsx() -> bytes:
+    parts = {
+        "[Content_Types].xml": (
+            '<?xml version="1.0" encoding="UTF-8"?>'
+            '<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">'
+            '<Default Extension="rels" '
+            'ContentType="application/vnd.openxmlformats-package.relationships+xml"/>'
+            '<Default Extension="xml" ContentType="application/xml"/>'
+            '<Override PartName="/xl/workbook.xml" ContentType="application/'
+            'vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>'
+            '<Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/'
+            'vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>'
+            "</Types>"
+        ),
+        "_rels/.rels": (
+            '<?xml version="1.0" encoding="UTF-8"?>'
+            '<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">'
+            '<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/'
+            '2006/relationships/officeDocument" Target="xl/workbook.xml"/>'
+            "</Relationships>"
+        ),
+        "xl/workbook.xml": (
+            '<?xml version="1.0" encoding="UTF-8"?>'
+            '<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" '
+            'xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">'
+            '<sheets><sheet name="Secret" sheetId="1" r:id="rId1"/></sheets></workbook>'
+        ),
+        "xl/_rels/workbook.xml.rels": (
+            '<?xml version="1.0" encoding="UTF-8"?>'
+            '<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">'
+            '<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/'
+            '2006/relationships/worksheet" Target="worksheets/sheet1.xml"/>'
+            "</Relationships>"
+        ),
+        "xl/worksheets/sheet1.xml": (
+            '<?xml version="1.0" encoding="UTF-8"?>'
+            '<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">'
+            '<dimension ref="A1:B1"/><sheetData><row r="1">'
+            '<c r="A1" t="inlineStr"><is><t>classified</t></is></c>'
+            '<c r="B1"><v>42</v></c></row></sheetData></worksheet>'
+        ),
+    }
+    buf = io.BytesIO()
+    with zipfile.ZipFile(buf, "w", zipfile.ZIP_DEFLATED) as zf:
+        for name, xml in parts.items():
+            # Fixed timestamps keep the output reproducible.
+            zf.writestr(zipfile.ZipInfo(name, (2024, 1, 1, 0, 0, 0)), xml)
+    return buf.getvalue()
+
+
+# =========================================================================
+# OLE compound file (CFB v3, 512-byte sectors)
+# =========================================================================
+
+
+def _dir_entry(
+    name: str, kind: int, start: int, size: int, left: int, child: int, red: bool = False
+) -> bytes:
+    encoded = (name + "\0").encode("utf-16-le") if name else b""
+    return struct.pack(
+        "<64sHBBIII16sIQQIQ",
+        encoded,
+        len(encoded),
+        kind,
+        0 if red else 1,
+        left,
+        _NO_STREAM,
+        child,
+        b"",
+        0,
+        0,
+        0,
+        start,
+        size,
+    )
+
+
+def _compound_file(streams: dict[str, bytes]) -> bytes:
+    """An OLE container whose root storage holds `streams` (one or two)."""
+    names = list(streams)
+    mini = bytearray()
+    minifat: list[int] = []
+    big: list[tuple[str, bytes]] = []
+    starts: dict[str, int] = {}
+    for name in names:
+        data = streams[name]
+        if len(data) < _MINI_CUTOFF:
+            starts[name] = len(mini) // _MINI_SECTOR
+            count = -(-len(data) // _MINI_SECTOR)
+            minifat += list(range(starts[name] + 1, starts[name] + count)) + [_END_OF_CHAIN]
+            mini += data.ljust(count * _MINI_SECTOR, b"\0")
+        else:
+            big.append((name, data))
+
+    # Sector runs in file order: directory, mini FAT, mini stream, big streams.
+    runs: list[tuple[str, bytes]] = [("<dir>", b"")]
+    if minifat:
+        runs.append(("<minifat>", b"".join(struct.pack("<I", n) for n in minifat)))
+        runs.append(("<root>", bytes(mini)))
+    runs += big
+    counts = [max(1, -(-len(data) // _SECTOR)) for _, data in runs]
+    fat_count = 1
+    while fat_count * (_SECTOR // 4) < fat_count + sum(counts):
+        fat_count += 1
+
+    fat = [_FAT_SECT] * fat_count
+    for (name, _), count in zip(runs, counts):
+        starts[name] = len(fat)
+        fat += list(range(len(fat) + 1, len(fat) + count)) + [_END_OF_CHAIN]
+
+    # Red-black tree of the root's children, ordered by name length and then
+    # upper-cased text: the last name is the black tree root and the first
+    # (if any) its red left child.
+    ordered = sorted(names, key=lambda n: (len(n), n.upper()))
+    ids = {name: i + 1 for i, name in enumerate(ordered)}
+    root_start = starts.get("<root>", _END_OF_CHAIN)
+    entries = [_dir_entry("Root Entry", 5, root_start, len(mini), _NO_STREAM, ids[ordered[-1]])]
+    for name in ordered:
+        is_top = name == ordered[-1]
+        left = ids[ordered[0]] if is_top and len(ordered) > 1 else _NO_STREAM
+        size = len(streams[name])
+        entries.append(_dir_entry(name, 2, starts[name], size, left, _NO_STREAM, not is_top))
+    while len(entries) % (_SECTOR // 128):
+        entries.append(_dir_entry("", 0, 0, 0, _NO_STREAM, _NO_STREAM))
+    runs[0] = ("<dir>", b"".join(entries))
+    minifat_count = counts[1] if minifat else 0
+
+    difat = list(range(fat_count)) + [_FREE_SECT] * (109 - fat_count)
+    header = struct.pack(
+        "<8s16sHHHHH6sIIIIIIIII109I",
+        bytes.fromhex("d0cf11e0a1b11ae1"),
+        b"",
+        0x3E,
+        3,
+        0xFFFE,
+        9,
+        6,
+        b"",
+        0,
+        fat_count,
+        starts["<dir>"],
+        0,
+        _MINI_CUTOFF,
+        starts.get("<minifat>", _END_OF_CHAIN),
+        minifat_count,
+        _END_OF_CHAIN,
+        0,
+        *difat,
+    )
+    fat += [_FREE_SECT] * (-len(fat) % (_SECTOR // 4))
+    body = b"".join(struct.pack("<I", n) for n in fat)
+    for (name, data), count in zip(runs, counts):
+        body += data.ljust(count * _SECTOR, b"\xff" if name == "<minifat>" else b"\0")
+    return header + body
+
+
+# =========================================================================
+# Encryption
+# =========================================================================
+
+
+def _aes(key: bytes, mode: modes.Mode, data: bytes) -> bytes:
+    enc = Cipher(algorithms.AES(key), mode).encryptor()
+    return enc.update(data) + enc.finalize()
+
+
+def _pad(data: bytes, block: int = 16) -> bytes:
+    return data + b"\0" * (-len(data) % block)
+
+
+def _fit(data: bytes, size: int) -> bytes:
+    return data[:size].ljust(size, b"\x36")
+
+
+def _spin(hash_name: str, salt: bytes, password: bytes, spin: int) -> bytes:
+    h = hashlib.new(hash_name, salt + password).digest()
+    for i in range(spin):
+        h = hashlib.new(hash_name, struct.pack("<I", i) + h).digest()
+    return h
+
+
+def encrypt_standard(plain: bytes, password: str) -> bytes:
+    pw = password.encode("utf-16-le")
+    salt = os.urandom(16)
+    h = hashlib.sha1(_spin("sha1", salt, pw, 50_000) + struct.pack("<I", 0)).digest()
+    inner = bytes(b ^ 0x36 for b in h.ljust(64, b"\0"))
+    outer = bytes(b ^ 0x5C for b in h.ljust(64, b"\0"))
+    key = (hashlib.sha1(inner).digest() + hashlib.sha1(outer).digest())[:16]
+
+    verifier = os.urandom(16)
+    verifier_hash = hashlib.sha1(verifier).digest()
+    csp = "Microsoft Enhanced RSA and AES Cryptographic Provider\0".encode("utf-16-le")
+    # flags, sizeExtra, AES-128, SHA-1, keySize, providerType, reserved x2
+    header = struct.pack("<IIIIIIII", 0x24, 0, 0x660E, 0x8004, 128, 0x18, 0, 0) + csp
+    info = (
+        struct.pack("<HHI", 3, 2, 0x24)
+        + struct.pack("<I", len(header))
+        + header
+        + struct.pack("<I", 16)
+        + salt
+        + _aes(key, modes.ECB(), verifier)
+        + struct.pack("<I", 20)
+        + _aes(key, modes.ECB(), verifier_hash.ljust(32, b"\0"))
+    )
+    package = struct.pack("<Q", len(plain)) + _aes(key, modes.ECB(), _pad(plain))
+    return _compound_file({"EncryptionInfo": info, "EncryptedPackage": package})
+
+
+def _rc4(key: bytes, data: bytes) -> bytes:
+    s = list(range(256))
+    j = 0
+    for i in range(256):
+        j = (j + s[i] + key[i % len(key)]) % 256
+        s[i], s[j] = s[j], s[i]
+    out = bytearray()
+    i = j = 0
+    for byte in data:
+        i = (i + 1) % 256
+        j = (j + s[i]) % 256
+        s[i], s[j] = s[j], s[i]
+        out.append(byte ^ s[(s[i] + s[j]) % 256])
+    return bytes(out)
+
+
+def encrypt_rc4(plain: bytes, password: str) -> bytes:
+    """RC4 CryptoAPI: Standard's header layout without fAES, re-keyed every
+    512 bytes from SHA-1(H0 + block number)."""
+    salt = os.urandom(16)
+    h0 = hashlib.sha1(salt + password.encode("utf-16-le")).digest()
+
+    def block_key(block: int) -> bytes:
+        return hashlib.sha1(h0 + struct.pack("<I", block)).digest()[:16]
+
+    verifier = os.urandom(16)
+    # The verifier and its hash are one RC4 stream under the block 0 key.
+    enc_verifier = _rc4(block_key(0), verifier + hashlib.sha1(verifier).digest())
+    csp = "Microsoft Enhanced Cryptographic Provider v1.0\0".encode("utf-16-le")
+    # flags (fCryptoAPI only), sizeExtra, RC4, SHA-1, keySize, providerType, reserved x2
+    header = struct.pack("<IIIIIIII", 0x04, 0, 0x6801, 0x8004, 128, 0x01, 0, 0) + csp
+    info = (
+        struct.pack("<HHI", 4, 2, 0x04)
+        + struct.pack("<I", len(header))
+        + header
+        + struct.pack("<I", 16)
+        + salt
+        + enc_verifier[:16]
+        + struct.pack("<I", 20)
+        + enc_verifier[16:]
+    )
+    body = b"".join(
+        _rc4(block_key(n), plain[at : at + 512]) for n, at in enumerate(range(0, len(plain), 512))
+    )
+    package = struct.pack("<Q", len(plain)) + body
+    return _compound_file({"EncryptionInfo": info, "EncryptedPackage": package})
+
+
+def encrypt_agile(plain: bytes, password: str, spin: int = 100_000) -> bytes:
+    hash_name, hash_size, key_len = "sha512", 64, 32
+    pw = password.encode("utf-16-le")
+    key_salt, pw_salt = os.urandom(16), os.urandom(16)
+    secret = os.urandom(key_len)
+
+    h = _spin(hash_name, pw_salt, pw, spin)
+
+    def wrap(block_key: bytes, data: bytes) -> bytes:
+        key = _fit(hashlib.new(hash_name, h + block_key).digest(), key_len)
+        return _aes(key, modes.CBC(pw_salt), _pad(data))
+
+    verifier = os.urandom(16)
+    enc_verifier_input = wrap(_BLOCK_VERIFIER_INPUT, verifier)
+    enc_verifier_value = wrap(_BLOCK_VERIFIER_VALUE, hashlib.new(hash_name, verifier).digest())
+    enc_key_value = wrap(_BLOCK_KEY_VALUE, secret)
+
+    def segment_iv(block_key: bytes) -> bytes:
+        return _fit(hashlib.new(hash_name, key_salt + block_key).digest(), 16)
+
+    body = b"".join(
+        _aes(secret, modes.CBC(segment_iv(struct.pack("<I", i))), _pad(plain[at : at + 4096]))
+        for i, at in enumerate(range(0, len(plain), 4096))
+    )
+    package = struct.pack("<Q", len(plain)) + body
+
+    hmac_key = os.urandom(hash_size)
+    hmac_value = hmac.new(hmac_key, package, hash_name).digest()
+    enc_hmac_key = _aes(secret, modes.CBC(segment_iv(_BLOCK_INTEGRITY_KEY)), hmac_key)
+    enc_hmac_value = _aes(secret, modes.CBC(segment_iv(_BLOCK_INTEGRITY_VALUE)), hmac_value)
+
+    def b64(data: bytes) -> str:
+        return b64encode(data).decode()
+
+    params = (
+        f'saltSize="16" blockSize="16" keyBits="{key_len * 8}" hashSize="{hash_size}" '
+        'cipherAlgorithm="AES" cipherChaining="ChainingModeCBC" hashAlgorithm="SHA512"'
+    )
+    xml = (
+        '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\r\n'
+        '<encryption xmlns="http://schemas.microsoft.com/office/2006/encryption" '
+        'xmlns:p="http://schemas.microsoft.com/office/2006/keyEncryptor/password">'
+        f'<keyData {params} saltValue="{b64(key_salt)}"/>'
+        f'<dataIntegrity encryptedHmacKey="{b64(enc_hmac_key)}" '
+        f'encryptedHmacValue="{b64(enc_hmac_value)}"/>'
+        '<keyEncryptors><keyEncryptor '
+        'uri="http://schemas.microsoft.com/office/2006/keyEncryptor/password">'
+        f'<p:encryptedKey spinCount="{spin}" {params} saltValue="{b64(pw_salt)}" '
+        f'encryptedVerifierHashInput="{b64(enc_verifier_input)}" '
+        f'encryptedVerifierHashValue="{b64(enc_verifier_value)}" '
+        f'encryptedKeyValue="{b64(enc_key_value)}"/>'
+        "</keyEncryptor></keyEncryptors></encryption>"
+    )
+    info = struct.pack("<HHI", 4, 4, 0x40) + xml.encode()
+    return _compound_file({"EncryptionInfo": info, "EncryptedPackage": package})
+
+
+def main() -> None:
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("--output", type=Path, default=Path("fixtures/encrypted"))
+    args = parser.parse_args()
+
+    args.output.mkdir(parents=True, exist_ok=True)
+    plain = _plain_xlsx()
+    (args.output / "standard.xlsx").write_bytes(encrypt_standard(plain, PASSWORD))
+    (args.output / "agile.xlsx").write_bytes(encrypt_agile(plain, PASSWORD))
+    (args.output / "rc4.xlsx").write_bytes(encrypt_rc4(plain, PASSWORD))
+    print(f"Wrote standard.xlsx, agile.xlsx and rc4.xlsx to {args.output}")
+
+
+if __name__ == "__main__":
+    main()
@@ -65,6 +65,15 @@ except ImportError:
 else:
     WolfxlAdapter = _WolfxlAdapter
 
+try:
+    from excelbench.harness.adapters.rust_wolfxl_read_adapter import (
+        RustWolfxlReadAdapter as _RustWolfxlReadAdapter,
+    )
+except ImportError:
+    RustWolfxlReadAdapter: AdapterClass | None = None
+else:
+    RustWolfxlReadAdapter = _RustWolfxlReadAdapter
+
 try:
     from excelbench.harness.adapters.pyumya_adapter import PyumyaAdapter as _PyumyaAdapter
 except ImportError:
@@ -163,6 +172,8 @@ if UmyaAdapter is not None:
     __all__.append("UmyaAdapter")
 if WolfxlAdapter is not None:
     __all__.append("WolfxlAdapter")
+if RustWolfxlReadAdapter is not None:
+    __all__.append("RustWolfxlReadAdapter")
 if PyumyaAdapter is not None:
     __all__.append("PyumyaAdapter")
 if PylightxlAdapter is not None:
@@ -202,6 +213,8 @@ def get_all_adapters() -> list[ExcelAdapter]:
         adapters.append(UmyaAdapter())
     if WolfxlAdapter is not None:
         adapters.append(WolfxlAdapter())
+    if RustWolfxlReadAdapter is not None:
+        adapters.append(RustWolfxlReadAdapter())
     if PyumyaAdapter is not None:
         adapters.append(PyumyaAdapter())
     if PylightxlAdapter is not None:
@@ -18,8 +18,21 @@ from excelbench.models import (
 
 JSONDict = dict[str, Any]
 
+# Structured exceptions raised by wolfxl._rust, matched by class name so this
+# module does not need the extension installed.
+_RUST_ERROR_CATEGORIES = {
+    "FileFormatError": DiagnosticCategory.PARSE,
+    "SheetNotFound": DiagnosticCategory.INVALID_INPUT,
+    "CellRefError": DiagnosticCategory.INVALID_INPUT,
+    "UnsupportedFeature": DiagnosticCategory.UNSUPPORTED_FEATURE,
+}
+
 
 def _infer_diagnostic_category(exc: Exception) -> DiagnosticCategory:
+    for cls in type(exc).__mro__:
+        category = _RUST_ERROR_CATEGORIES.get(cls.__name__)
+        if category is not None:
+            return category
     name = type(exc).__name__.lower()
     message = str(exc).lower()
     if isinstance(exc, (FileNotFoundError, PermissionError, IsADirectoryError, OSError)):
@@ -97,6 +110,9 @@ class ExcelAdapter(ABC):
     ) -> Diagnostic:
         """Normalize adapter/runtime exceptions into a typed diagnostic."""
         category = _infer_diagnostic_category(exc)
+        # Rust backend errors carry their own location context.
+        sheet = sheet or getattr(exc, "sheet", None)
+        cell = cell or getattr(exc, "cell", None)
         severity = (
             DiagnosticSeverity.WARNING
             if category == DiagnosticCategory.UNSUPPORTED_FEATURE
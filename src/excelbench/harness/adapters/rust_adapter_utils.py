@@ -6,7 +6,7 @@ and the Python harness.
 
 from __future__ import annotations
 
-from datetime import date, datetime
+from datetime import date, datetime, time
 from typing import Any
 
 from excelbench.models import BorderEdge, BorderInfo, BorderStyle, CellFormat, CellType, CellValue
@@ -30,6 +30,36 @@ def get_rust_backend_version(backend_key: str) -> str:
         return "unknown"
 
 
+def get_rust_capabilities(class_name: str) -> dict[str, Any] | None:
+    """Return the capability matrix entry for a ``wolfxl._rust`` class.
+
+    The entry has ``backend``, ``extensions``, ``read``/``write`` (feature name
+    -> bool) and ``modify`` keys. Returns None when the extension is missing,
+    predates ``capabilities()``, or was built without the class.
+    """
+
+    try:
+        import wolfxl._rust as rust
+
+        rust_mod: Any = rust
+        matrix = rust_mod.capabilities()
+    except Exception:
+        return None
+    entry = matrix.get(class_name) if isinstance(matrix, dict) else None
+    return entry if isinstance(entry, dict) else None
+
+
+def close_rust_book(workbook: Any) -> None:
+    """Release a ``wolfxl._rust`` book's file handles and caches.
+
+    Builds that predate ``close()`` hold nothing beyond the Python reference.
+    """
+
+    close = getattr(workbook, "close", None)
+    if callable(close):
+        close()
+
+
 def payload_from_cell_value(value: CellValue) -> dict[str, Any]:
     if value.type == CellType.BLANK:
         return {"type": "blank"}
@@ -87,6 +117,20 @@ def cell_value_from_payload(payload: dict[str, Any]) -> CellValue:
         if isinstance(value, str):
             return CellValue(type=CellType.DATETIME, value=datetime.fromisoformat(value))
         return CellValue(type=CellType.DATETIME, value=value)
+    if type_str == "time":
+        # No TIME cell type: anchor to today, as the python-calamine adapter does.
+        if isinstance(value, str):
+            value = time.fromisoformat(value)
+        if isinstance(value, time):
+            return CellValue(type=CellType.DATETIME, value=datetime.combine(date.today(), value))
+        return CellValue(type=CellType.DATETIME, value=value)
+    if type_str == "duration":
+        # "[-]h:mm:ss" elapsed time -> the day count Excel stores.
+        if isinstance(value, str):
+            sign = -1 if value.startswith("-") else 1
+            h, m, sec = (int(part) for part in value.lstrip("-").split(":"))
+            return CellValue(type=CellType.NUMBER, value=sign * (h * 3600 + m * 60 + sec) / 86400)
+        return CellValue(type=CellType.NUMBER, value=value)
 
     return CellValue(type=CellType.STRING, value=str(value) if value is not None else None)
 
@@ -10,6 +10,7 @@ from typing import Any
 from excelbench.harness.adapters.base import ReadOnlyAdapter
 from excelbench.harness.adapters.rust_adapter_utils import (
     cell_value_from_payload,
+    close_rust_book,
     get_rust_backend_version,
 )
 from excelbench.models import (
@@ -48,7 +49,7 @@ class RustCalamineAdapter(ReadOnlyAdapter):
 
     @property
     def supported_read_extensions(self) -> set[str]:
-        return {".xlsx", ".xls"}
+        return {".xlsx", ".xls", ".xlsb", ".ods"}
 
     def open_workbook(self, path: Path) -> Any:
         import wolfxl._rust as rust
@@ -58,8 +59,7 @@ class RustCalamineAdapter(ReadOnlyAdapter):
         return book_cls.open(str(path))
 
     def close_workbook(self, workbook: Any) -> None:
-        # No explicit close needed for the current binding.
-        return
+        close_rust_book(workbook)
 
     def get_sheet_names(self, workbook: Any) -> list[str]:
         return [str(name) for name in workbook.sheet_names()]
@@ -70,6 +70,37 @@ class RustCalamineAdapter(ReadOnlyAdapter):
             return CellValue(type=CellType.STRING, value=str(payload))
         return cell_value_from_payload(payload)
 
+    def read_sheet_values(
+        self,
+        workbook: Any,
+        sheet: str,
+        cell_range: str | None = None,
+    ) -> list[list[CellValue]]:
+        """Bulk read all values from a sheet via CalamineBook.read_sheet()/read_range()."""
+        raw = self.read_sheet_values_raw(workbook, sheet, cell_range)
+        return [
+            [
+                cell_value_from_payload(v)
+                if isinstance(v, dict)
+                else CellValue(type=CellType.BLANK)
+                for v in row
+            ]
+            for row in raw
+        ]
+
+    def read_sheet_values_raw(
+        self,
+        workbook: Any,
+        sheet: str,
+        cell_range: str | None = None,
+    ) -> list[list[Any]]:
+        """Return raw Rust FFI output without cell_value_from_payload() wrapping."""
+        if cell_range:
+            result: list[list[Any]] = workbook.read_range(sheet, cell_range)
+        else:
+            result = workbook.read_sheet(sheet)
+        return result
+
     def read_cell_format(self, workbook: Any, sheet: str, cell: str) -> CellFormat:
         return CellFormat()
 
@@ -99,13 +130,32 @@ class RustCalamineAdapter(ReadOnlyAdapter):
         return []
 
     def read_images(self, workbook: Any, sheet: str) -> list[JSONDict]:
+        result = workbook.read_images(sheet)
+        if isinstance(result, list):
+            return [dict(x) for x in result if isinstance(x, dict)]
         return []
 
     def read_pivot_tables(self, workbook: Any, sheet: str) -> list[JSONDict]:
         return []
 
     def read_comments(self, workbook: Any, sheet: str) -> list[JSONDict]:
+        result = workbook.read_comments(sheet)
+        if isinstance(result, list):
+            return [dict(x) for x in result if isinstance(x, dict)]
         return []
 
     def read_freeze_panes(self, workbook: Any, sheet: str) -> JSONDict:
-        return {}
+        result = workbook.read_freeze_panes(sheet)
+        return dict(result) if isinstance(result, dict) else {}
+
+    def read_named_ranges(self, workbook: Any, sheet: str) -> list[JSONDict]:
+        result = workbook.read_defined_names()
+        if not isinstance(result, list):
+            return []
+        return [dict(x) for x in result if isinstance(x, dict) and x.get("sheet") in (None, sheet)]
+
+    def read_tables(self, workbook: Any, sheet: str) -> list[JSONDict]:
+        result = workbook.read_tables(sheet)
+        if isinstance(result, list):
+            return [dict(x) for x in result if isinstance(x, dict)]
+        return []
@@ -12,6 +12,7 @@ from typing import Any
 from excelbench.harness.adapters.base import ReadOnlyAdapter
 from excelbench.harness.adapters.rust_adapter_utils import (
     cell_value_from_payload,
+    close_rust_book,
     dict_to_border,
     dict_to_format,
     get_rust_backend_version,
@@ -65,6 +66,7 @@ class RustCalamineStyledAdapter(ReadOnlyAdapter):
     def close_workbook(self, workbook: Any) -> None:
         wb_id = id(workbook)
         self._cell_cache = {k: v for k, v in self._cell_cache.items() if k[0] != wb_id}
+        close_rust_book(workbook)
 
     def get_sheet_names(self, workbook: Any) -> list[str]:
         return [str(name) for name in workbook.sheet_names()]
@@ -0,0 +1,141 @@
+"""Adapter for wolfxl-read, the pure-Rust xlsx reader in our PyO3 extension.
+
+Worksheets are parsed straight from the ZIP without calamine, so this adapter
+benchmarks the wolfxl read path against the calamine-backed ones. It is
+read-only and xlsx-only.
+"""
+
+from pathlib import Path
+from typing import Any
+
+from excelbench.harness.adapters.base import ReadOnlyAdapter
+from excelbench.harness.adapters.rust_adapter_utils import (
+    cell_value_from_payload,
+    close_rust_book,
+    get_rust_backend_version,
+)
+from excelbench.models import (
+    BorderInfo,
+    CellFormat,
+    CellType,
+    CellValue,
+    LibraryInfo,
+)
+
+JSONDict = dict[str, Any]
+
+
+try:
+    import wolfxl._rust as _excelbench_rust
+except ImportError as e:  # pragma: no cover
+    raise ImportError("wolfxl._rust wolfxl-read backend unavailable") from e
+
+if getattr(_excelbench_rust, "XlsxReader", None) is None:  # pragma: no cover
+    raise ImportError("wolfxl._rust built without wolfxl backend")
+
+
+class RustWolfxlReadAdapter(ReadOnlyAdapter):
+    """Adapter for the wolfxl-read streaming xlsx reader."""
+
+    @property
+    def info(self) -> LibraryInfo:
+        return LibraryInfo(
+            name="wolfxl-read",
+            version=get_rust_backend_version("wolfxl"),
+            language="rust",
+            capabilities={"read"},
+        )
+
+    @property
+    def supported_read_extensions(self) -> set[str]:
+        return {".xlsx"}
+
+    def open_workbook(self, path: Path) -> Any:
+        import wolfxl._rust as rust
+
+        m: Any = rust
+        book_cls = getattr(m, "XlsxReader")
+        return book_cls.open(str(path))
+
+    def close_workbook(self, workbook: Any) -> None:
+        close_rust_book(workbook)
+
+    def get_sheet_names(self, workbook: Any) -> list[str]:
+        return [str(name) for name in workbook.sheet_names()]
+
+    def read_cell_value(self, workbook: Any, sheet: str, cell: str) -> CellValue:
+        payload = workbook.read_cell_value(sheet, cell)
+        if not isinstance(payload, dict):
+            return CellValue(type=CellType.STRING, value=str(payload))
+        return cell_value_from_payload(payload)
+
+    def read_sheet_values(
+        self,
+        workbook: Any,
+        sheet: str,
+        cell_range: str | None = None,
+    ) -> list[list[CellValue]]:
+        """Bulk read all values from a sheet via XlsxReader.read_sheet()/read_range()."""
+        raw = self.read_sheet_values_raw(workbook, sheet, cell_range)
+        return [
+            [
+                cell_value_from_payload(v)
+                if isinstance(v, dict)
+                else CellValue(type=CellType.BLANK)
+                for v in row
+            ]
+            for row in raw
+        ]
+
+    def read_sheet_values_raw(
+        self,
+        workbook: Any,
+        sheet: str,
+        cell_range: str | None = None,
+    ) -> list[list[Any]]:
+        """Return raw Rust FFI output without cell_value_from_payload() wrapping."""
+        if cell_range:
+            result: list[list[Any]] = workbook.read_range(sheet, cell_range)
+        else:
+            result = workbook.read_sheet(sheet)
+        return result
+
+    def read_cell_format(self, workbook: Any, sheet: str, cell: str) -> CellFormat:
+        return CellFormat()
+
+    def read_cell_border(self, workbook: Any, sheet: str, cell: str) -> BorderInfo:
+        return BorderInfo()
+
+    def read_row_height(self, workbook: Any, sheet: str, row: int) -> float | None:
+        return None
+
+    def read_column_width(self, workbook: Any, sheet: str, column: str) -> float | None:
+        return None
+
+    # =========================================================================
+    # Tier 2 Read Operations
+    # =========================================================================
+
+    def read_merged_ranges(self, workbook: Any, sheet: str) -> list[str]:
+        return []
+
+    def read_conditional_formats(self, workbook: Any, sheet: str) -> list[JSONDict]:
+        return []
+
+    def read_data_validations(self, workbook: Any, sheet: str) -> list[JSONDict]:
+        return []
+
+    def read_hyperlinks(self, workbook: Any, sheet: str) -> list[JSONDict]:
+        return []
+
+    def read_images(self, workbook: Any, sheet: str) -> list[JSONDict]:
+        return []
+
+    def read_pivot_tables(self, workbook: Any, sheet: str) -> list[JSONDict]:
+        return []
+
+    def read_comments(self, workbook: Any, sheet: str) -> list[JSONDict]:
+        return []
+
+    def read_freeze_panes(self, workbook: Any, sheet: str) -> JSONDict:
+        return {}
@@ -14,6 +14,7 @@ from excelbench.harness.adapters.base import ExcelAdapter
 from excelbench.harness.adapters.rust_adapter_utils import (
     border_to_dict,
     cell_value_from_payload,
+    close_rust_book,
     dict_to_border,
     dict_to_format,
     format_to_dict,
@@ -66,7 +67,7 @@ class UmyaAdapter(ExcelAdapter):
         return cls.open(str(path))
 
     def close_workbook(self, workbook: Any) -> None:
-        return
+        close_rust_book(workbook)
 
     def get_sheet_names(self, workbook: Any) -> list[str]:
         return [str(n) for n in workbook.sheet_names()]
@@ -104,7 +105,12 @@ class UmyaAdapter(ExcelAdapter):
         return list(workbook.read_conditional_formats(sheet))
 
     def read_data_validations(self, workbook: Any, sheet: str) -> list[JSONDict]:
-        return list(workbook.read_data_validations(sheet))
+        validations: list[JSONDict] = list(workbook.read_data_validations(sheet))
+        for entry in validations:
+            # Same default as OpenpyxlAdapter: "between" when the operator is omitted
+            if entry.get("operator") is None and entry.get("formula2"):
+                entry["operator"] = "between"
+        return validations
 
     def read_hyperlinks(self, workbook: Any, sheet: str) -> list[JSONDict]:
         return list(workbook.read_hyperlinks(sheet))
@@ -11,6 +11,7 @@ from excelbench.harness.adapters.base import ExcelAdapter
 from excelbench.harness.adapters.rust_adapter_utils import (
     border_to_dict,
     cell_value_from_payload,
+    close_rust_book,
     dict_to_border,
     dict_to_format,
     format_to_dict,
@@ -75,6 +76,7 @@ class WolfxlAdapter(ExcelAdapter):
         # Evict cached cells for this workbook.
         wb_id = id(workbook)
         self._cell_cache = {k: v for k, v in self._cell_cache.items() if k[0] != wb_id}
+        close_rust_book(workbook)
 
     def get_sheet_names(self, workbook: Any) -> list[str]:
         return [str(name) for name in workbook.sheet_names()]
@@ -321,6 +321,33 @@ def test_map_error_to_diagnostic() -> None:
     assert diag.location.feature == "pivot_tables"
 
 
+def test_map_error_to_diagnostic_uses_rust_error_types() -> None:
+    class ExcelBenchError(Exception):
+        pass
+
+    class SheetNotFound(ExcelBenchError, ValueError):
+        pass
+
+    class FileFormatError(ExcelBenchError, OSError):
+        pass
+
+    adapter = ConcreteReadOnly()
+    exc = SheetNotFound("Unknown sheet: Missing")
+    exc.sheet = "Missing"  # type: ignore[attr-defined]
+    exc.cell = None  # type: ignore[attr-defined]
+    diag = adapter.map_error_to_diagnostic(
+        exc=exc, feature="cell_values", operation=OperationType.READ
+    )
+    assert diag.category == DiagnosticCategory.INVALID_INPUT
+    assert diag.location.sheet == "Missing"
+
+    # OSError subclass, but classified as a parse failure without any "zip" text.
+    diag = adapter.map_error_to_diagnostic(
+        exc=FileFormatError("bad header"), feature="cell_values", operation=OperationType.READ
+    )
+    assert diag.category == DiagnosticCategory.PARSE
+
+
 def test_build_mismatch_diagnostic() -> None:
     adapter = ConcreteReadOnly()
     diag = adapter.build_mismatch_diagnostic(
@@ -10,7 +10,7 @@ Targets remaining uncovered lines in:
 
 from __future__ import annotations
 
-from datetime import date, datetime
+from datetime import date, datetime, time
 from pathlib import Path
 from typing import Any
 from unittest.mock import MagicMock
@@ -1148,6 +1148,20 @@ class TestRustPayloadConversion:
         assert cv.type == CellType.DATETIME
         assert cv.value == 45000.5
 
+    def test_cell_value_from_time_string(self) -> None:
+        """Time payload should become a DATETIME anchored to today."""
+        cv = cell_value_from_payload({"type": "time", "value": "08:15:30"})
+        assert cv.type == CellType.DATETIME
+        assert cv.value == datetime.combine(date.today(), time(8, 15, 30))
+
+    def test_cell_value_from_duration_string(self) -> None:
+        """Duration payload should become a NUMBER of days."""
+        cv = cell_value_from_payload({"type": "duration", "value": "30:00:00"})
+        assert cv.type == CellType.NUMBER
+        assert cv.value == 1.25
+        cv = cell_value_from_payload({"type": "duration", "value": "-0:36:00"})
+        assert cv.value == -0.025
+
     def test_cell_value_from_unknown_type(self) -> None:
         """Unknown type should fallback to STRING."""
         cv = cell_value_from_payload({"type": "custom", "value": "foo"})
@@ -7,8 +7,11 @@ module is not built/installed.
 from __future__ import annotations
 
 import importlib.util
+import json
+import re
 import tempfile
-from datetime import date, datetime
+import zipfile
+from datetime import date, datetime, time, timedelta
 from pathlib import Path
 from typing import Any
 
@@ -43,6 +46,4069 @@ def test_registry_works_without_wolfxl_rust() -> None:
     assert "umya-spreadsheet" not in names
 
 
+def test_capabilities_cover_exported_classes() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(rust, "capabilities", None) is None:
+        pytest.skip("wolfxl._rust predates capabilities()")
+
+    from excelbench.harness.adapters.rust_adapter_utils import get_rust_capabilities
+
+    matrix = rust.capabilities()
+    enabled = _enabled_backends(rust)
+    features = None
+    for class_name, entry in matrix.items():
+        assert getattr(rust, class_name, None) is not None, class_name
+        assert entry["backend"] in enabled
+        assert entry["read"] or entry["write"]
+        # Every class reports the same feature keys.
+        keys = set(entry["read"]) | set(entry["write"])
+        features = features or keys
+        assert keys == features
+        assert get_rust_capabilities(class_name) == entry
+
+    if "calamine" in enabled:
+        assert matrix["CalamineBook"]["read"]["cell_values"] is True
+        assert not any(matrix["CalamineBook"]["write"].values())
+    assert get_rust_capabilities("NoSuchBook") is None
+
+
+def test_open_backend_dispatches_by_adapter_name() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(rust, "open_backend", None) is None:
+        pytest.skip("wolfxl._rust predates open_backend()")
+
+    with pytest.raises(ValueError, match="Unknown backend"):
+        rust.open_backend("no-such-backend")
+
+    enabled = _enabled_backends(rust)
+    if "umya-spreadsheet" not in enabled:
+        pytest.skip("wolfxl._rust compiled without umya backend")
+
+    f = tempfile.NamedTemporaryFile(suffix=".xlsx", delete=False)
+    path = Path(f.name)
+    f.close()
+    try:
+        book = rust.open_backend("umya-spreadsheet")
+        assert isinstance(book, rust.UmyaBook)
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "number", "value": 7})
+        book.save(str(path))
+
+        reopened = rust.open_backend("umya-spreadsheet", str(path))
+        assert reopened.read_cell_value("S", "A1") == {"type": "number", "value": 7}
+
+        if "calamine" in enabled:
+            with pytest.raises(ValueError, match="requires a path"):
+                rust.open_backend("calamine")
+            calamine = rust.open_backend("calamine", str(path))
+            assert isinstance(calamine, rust.CalamineBook)
+            assert calamine.sheet_names() == ["S"]
+        if "rust_xlsxwriter" in enabled:
+            with pytest.raises(ValueError, match="only creates new workbooks"):
+                rust.open_backend("rust_xlsxwriter", str(path))
+    finally:
+        path.unlink(missing_ok=True)
+
+
+def test_profiling_records_backend_phases() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(rust, "enable_profiling", None) is None:
+        pytest.skip("wolfxl._rust predates enable_profiling()")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without rust_xlsxwriter backend")
+
+    f = tempfile.NamedTemporaryFile(suffix=".xlsx", delete=False)
+    path = Path(f.name)
+    f.close()
+    rust.get_profile(reset=True)
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "number", "value": 1})
+        book.save(str(path))
+        assert rust.get_profile() == {}
+
+        rust.enable_profiling(True)
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "number", "value": 1})
+        book.write_cell_format("S", "A1", {"bold": True})
+        book.save(str(path))
+
+        profile = rust.get_profile(reset=True)
+        assert profile["rust_xlsxwriter.zip_write"]["calls"] == 1
+        assert profile["rust_xlsxwriter.zip_write"]["seconds"] > 0
+        assert profile["rust_xlsxwriter.style_build"]["calls"] >= 1
+        assert rust.get_profile() == {}
+    finally:
+        rust.enable_profiling(False)
+        path.unlink(missing_ok=True)
+
+
+def test_rust_degradation_warnings_reach_python_logging(
+    caplog: pytest.LogCaptureFixture,
+) -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without rust_xlsxwriter backend")
+    if getattr(rust, "flush_logs", None) is None:
+        pytest.skip("wolfxl._rust predates log forwarding")
+
+    import logging
+
+    f = tempfile.NamedTemporaryFile(suffix=".xlsx", delete=False)
+    path = Path(f.name)
+    f.close()
+    try:
+        with caplog.at_level(logging.WARNING, logger="excelbench_rust"):
+            book = rust.RustXlsxWriterBook()
+            book.add_sheet("S")
+            book.write_cell_value("S", "A1", {"type": "number", "value": 1})
+            book.write_cell_border("S", "A1", {"top": {"style": "wavy"}})
+            book.add_conditional_format("S", {"range": "A1:A5", "rule_type": "iconSet"})
+            book.save(str(path))
+            rust.flush_logs()
+
+        records = [r for r in caplog.records if r.name.startswith("excelbench_rust.")]
+        messages = [r.getMessage() for r in records]
+        assert all(r.levelno == logging.WARNING for r in records)
+        assert any("Border style 'wavy'" in m for m in messages)
+        assert any("'iconSet'" in m and "skipped" in m for m in messages)
+    finally:
+        path.unlink(missing_ok=True)
+
+
+def test_rust_strict_books_raise_on_dropped_features() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"rust_xlsxwriter", "umya-spreadsheet"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without rust_xlsxwriter/umya backends")
+
+    lenient = rust.RustXlsxWriterBook()
+    lenient.add_sheet("S")
+    lenient.write_cell_format("S", "A1", {"underline": "squiggly"})
+    lenient.add_conditional_format("S", {"range": "A1:A5", "rule_type": "iconSet"})
+
+    book = rust.RustXlsxWriterBook(strict=True)
+    book.add_sheet("S")
+    book.write_cell_format("S", "A1", {"underline": "double", "h_align": "center"})
+    with pytest.raises(rust.UnsupportedFeature, match="squiggly") as info:
+        book.write_cell_format("S", "A2", {"underline": "squiggly"})
+    assert info.value.backend == "rust_xlsxwriter"
+    assert isinstance(info.value, NotImplementedError)
+    with pytest.raises(rust.UnsupportedFeature, match="wavy"):
+        book.write_cell_border("S", "A1", {"left": {"style": "wavy"}})
+    with pytest.raises(rust.UnsupportedFeature, match="iconSet"):
+        book.add_conditional_format("S", {"range": "A1:A5", "rule_type": "iconSet"})
+    with pytest.raises(rust.UnsupportedFeature, match="needs an operator"):
+        book.add_conditional_format("S", {"range": "A1:A5", "rule_type": "cellIs"})
+    with pytest.raises(rust.UnsupportedFeature, match="Pane mode"):
+        book.set_freeze_panes("S", {"mode": "tiled"})
+
+    umya = rust.UmyaBook(strict=True)
+    umya.add_sheet("S")
+    with pytest.raises(rust.UnsupportedFeature, match="sparkle"):
+        umya.add_conditional_format("S", {"range": "A1:A5", "rule_type": "sparkle"})
+    with pytest.raises(rust.UnsupportedFeature, match="roughly"):
+        umya.add_data_validation(
+            "S", {"range": "B1", "validation_type": "whole", "operator": "roughly"}
+        )
+
+
+def test_transcode_copies_values_between_backends() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(rust, "transcode", None) is None:
+        pytest.skip("wolfxl._rust predates transcode()")
+    enabled = _enabled_backends(rust)
+    if not {"umya-spreadsheet", "rust_xlsxwriter"} <= enabled:
+        pytest.skip("wolfxl._rust compiled without umya/rust_xlsxwriter backends")
+
+    tmp = Path(tempfile.mkdtemp())
+    src, dst, flat = tmp / "src.xlsx", tmp / "dst.xlsx", tmp / "flat.xlsx"
+    try:
+        book = rust.UmyaBook()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "string", "value": "hi"})
+        book.write_cell_value("S", "B2", {"type": "number", "value": 2.5})
+        book.write_cell_value("S", "C3", {"type": "formula", "formula": "=B2*2"})
+        book.write_cell_format("S", "A1", {"bold": True, "bg_color": "#FF0000"})
+        book.save(str(src))
+
+        with pytest.raises(ValueError, match="Unknown transcode feature"):
+            rust.transcode(str(src), str(dst), features=["sparkles"])
+
+        stats = rust.transcode(
+            str(src),
+            str(dst),
+            reader="umya-spreadsheet",
+            features=["cell_values", "formulas", "text_formatting"],
+        )
+        assert stats == {"sheets": 1, "cells": 3, "formats": 1, "borders": 0}
+
+        out = rust.UmyaBook.open(str(dst))
+        assert out.read_cell_value("S", "A1") == {"type": "string", "value": "hi"}
+        assert out.read_cell_value("S", "B2") == {"type": "number", "value": 2.5}
+        assert out.read_cell_value("S", "C3")["formula"] == "=B2*2"
+        fmt = out.read_cell_format("S", "A1")
+        assert fmt.get("bold") is True
+        assert fmt.get("bg_color") is None
+
+        # Without formulas, uncached formula cells are dropped.
+        stats = rust.transcode(
+            str(src), str(flat), reader="umya-spreadsheet", features=["cell_values"]
+        )
+        assert stats["cells"] == 2
+    finally:
+        for p in (src, dst, flat):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_roundtrip_reports_field_diffs() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(rust, "roundtrip", None) is None:
+        pytest.skip("wolfxl._rust predates roundtrip()")
+    enabled = _enabled_backends(rust)
+    if not {"umya-spreadsheet", "rust_xlsxwriter"} <= enabled:
+        pytest.skip("wolfxl._rust compiled without umya/rust_xlsxwriter backends")
+
+    case = {
+        "sheet": "S",
+        "cells": [
+            {"cell": "A1", "value": {"type": "string", "value": "hi"}, "format": {"bold": True}},
+            {"cell": "B2", "value": {"type": "number", "value": 2.5}},
+            {"cell": "A1", "sheet": "T", "value": {"type": "boolean", "value": True}},
+        ],
+    }
+    verdict = rust.roundtrip("rust_xlsxwriter", "umya-spreadsheet", case)
+    assert verdict == {"passed": True, "diffs": [], "error": None}
+
+    single = {
+        "cell": "C3",
+        "value": {"type": "number", "value": 1},
+        "format": {"italic": True},
+        "expected": {"value": {"type": "number", "value": 2}, "format": {"italic": None}},
+    }
+    verdict = rust.roundtrip("rust_xlsxwriter", "umya-spreadsheet", single)
+    assert verdict["passed"] is False
+    assert [(d["sheet"], d["cell"], d["field"]) for d in verdict["diffs"]] == [
+        ("Sheet1", "C3", "value.value"),
+    ]
+    assert (verdict["diffs"][0]["expected"], verdict["diffs"][0]["actual"]) == (2, 1)
+
+    # Backend failures fail the case instead of raising.
+    verdict = rust.roundtrip("rust_xlsxwriter", "no-such-backend", single)
+    assert verdict["passed"] is False
+    assert "Unknown backend" in verdict["error"]
+    with pytest.raises(ValueError):
+        rust.roundtrip("rust_xlsxwriter", "umya-spreadsheet", {"cells": [{"value": {}}]})
+
+
+@pytest.mark.parametrize("cls_name", ["RustXlsxWriterBook", "UmyaBook"])
+def test_load_csv_infers_types(cls_name: str) -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    cls = getattr(rust, cls_name, None)
+    if cls is None or getattr(cls, "load_csv", None) is None:
+        pytest.skip(f"wolfxl._rust has no {cls_name}.load_csv")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    data = 'id;name;score;ok;when\n007;"caf\xe9";1.5;TRUE;2024-06-15\n1;"a;b";;false;x\n'
+    f = tempfile.NamedTemporaryFile(suffix=".xlsx", delete=False)
+    path = Path(f.name)
+    f.close()
+    try:
+        book = cls()
+        with pytest.raises(ValueError, match="Unknown CSV option"):
+            book.load_csv(b"a", "S", {"sep": ";"})
+        rows = book.load_csv(
+            data.encode("latin-1"), "S", {"delimiter": ";", "encoding": "latin-1"}
+        )
+        assert rows == 3
+        book.save(str(path))
+
+        ws = openpyxl.load_workbook(path)["S"]
+        assert [c.value for c in ws[2]] == ["007", "caf\xe9", 1.5, True, datetime(2024, 6, 15)]
+        assert [c.value for c in ws[3]] == [1, "a;b", None, False, "x"]
+    finally:
+        path.unlink(missing_ok=True)
+
+
+def test_export_csv_from_read_backends() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(getattr(rust, "UmyaBook", None), "export_csv", None) is None:
+        pytest.skip("wolfxl._rust has no UmyaBook.export_csv")
+
+    tmp = Path(tempfile.mkdtemp())
+    xlsx, out = tmp / "src.xlsx", tmp / "out.csv"
+    try:
+        book = rust.UmyaBook()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "string", "value": "a,b"})
+        book.write_cell_value("S", "B1", {"type": "number", "value": 3})
+        book.write_cell_value("S", "C2", {"type": "date", "value": "2024-06-15"})
+        book.write_cell_value("S", "A2", {"type": "boolean", "value": True})
+        book.save(str(xlsx))
+
+        expected = '"a,b",3,\nTRUE,,15/06/2024\n'
+        readers = [rust.UmyaBook.open(str(xlsx))]
+        if "calamine" in _enabled_backends(rust):
+            readers.append(rust.CalamineBook.open(str(xlsx)))
+        for reader in readers:
+            with pytest.raises(ValueError, match="Unknown CSV quoting"):
+                reader.export_csv("S", str(out), {"quoting": "sometimes"})
+            rows = reader.export_csv("S", str(out), {"date_format": "%d/%m/%Y"})
+            assert rows == 2
+            assert out.read_text() == expected
+    finally:
+        for p in (xlsx, out):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_export_json_records_and_rows() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(getattr(rust, "UmyaBook", None), "export_json", None) is None:
+        pytest.skip("wolfxl._rust has no UmyaBook.export_json")
+
+    tmp = Path(tempfile.mkdtemp())
+    xlsx, out = tmp / "src.xlsx", tmp / "out.json"
+    try:
+        book = rust.UmyaBook()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "string", "value": "name"})
+        book.write_cell_value("S", "A2", {"type": "string", "value": 'say "hi"'})
+        book.write_cell_value("S", "B2", {"type": "number", "value": 1.5})
+        book.write_cell_value("S", "B3", {"type": "date", "value": "2024-06-15"})
+        book.save(str(xlsx))
+
+        readers = [rust.UmyaBook.open(str(xlsx))]
+        if "calamine" in _enabled_backends(rust):
+            readers.append(rust.CalamineBook.open(str(xlsx)))
+        for reader in readers:
+            with pytest.raises(ValueError, match="orient must be"):
+                reader.export_json("S", str(out), orient="columns")
+
+            assert reader.export_json("S", str(out)) == 2
+            records = [json.loads(line) for line in out.read_text().splitlines()]
+            assert records == [
+                {
+                    "name": {"type": "string", "value": 'say "hi"'},
+                    "B": {"type": "number", "value": 1.5},
+                },
+                {
+                    "name": {"type": "blank"},
+                    "B": {"type": "date", "value": "2024-06-15"},
+                },
+            ]
+
+            assert reader.export_json("S", str(out), orient="rows") == 3
+            rows = json.loads(out.read_text())
+            assert rows[2][1] == reader.read_cell_value("S", "B3")
+    finally:
+        for p in (xlsx, out):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_calamine_export_parquet_infers_column_types() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    calamine = getattr(rust, "CalamineBook", None)
+    if getattr(calamine, "export_parquet", None) is None:
+        pytest.skip("wolfxl._rust compiled without the parquet feature")
+    pq = pytest.importorskip("pyarrow.parquet")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    xlsx, out = tmp / "src.xlsx", tmp / "out.parquet"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws.append(["id", "score", "name"])
+        ws.append([1, 1.5, "a"])
+        ws.append([2, 2, 3])
+        wb.save(xlsx)
+
+        book = calamine.open(str(xlsx))
+        assert book.export_parquet("S", str(out)) == 2
+        table = pq.read_table(out)
+        assert table.column_names == ["id", "score", "name"]
+        assert str(table.schema.field("score").type) == "double"
+        assert str(table.schema.field("name").type) == "string"
+        assert table.to_pydict() == {"id": [1, 2], "score": [1.5, 2.0], "name": ["a", "3"]}
+
+        assert book.export_parquet("S", str(out), header_row=None) == 3
+        assert pq.read_table(out).column_names == ["A", "B", "C"]
+
+        # A title row above the header: everything up to header_row is skipped,
+        # repeated names are suffixed and blank header cells fall back to letters.
+        ws = wb.create_sheet("T")
+        ws.append(["Quarterly report"])
+        ws.append(["id", "id", None])
+        ws.append([1, 2.5, "x"])
+        ws.append([2, 3.5, None])
+        wb.save(xlsx)
+
+        book = calamine.open(str(xlsx))
+        assert book.export_parquet("T", str(out), header_row=1) == 2
+        table = pq.read_table(out)
+        assert table.column_names == ["id", "id.1", "C"]
+        assert table.to_pydict() == {"id": [1, 2], "id.1": [2.5, 3.5], "C": ["x", None]}
+        # header_row=0 takes the title row as the header and keeps row 2 as data.
+        assert book.export_parquet("T", str(out), header_row=0) == 3
+        assert pq.read_table(out).column_names == ["Quarterly report", "B", "C"]
+    finally:
+        for p in (xlsx, out):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_transform_redacts_values_and_keeps_styles() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(rust, "transform", None) is None:
+        pytest.skip("wolfxl._rust predates transform()")
+    if not {"wolfxl", "rust_xlsxwriter", "calamine"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without wolfxl/rust_xlsxwriter/calamine backends")
+
+    tmp = Path(tempfile.mkdtemp())
+    src, dst = tmp / "src.xlsx", tmp / "dst.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "string", "value": "alice@example.com"})
+        book.write_cell_format("S", "A1", {"bold": True})
+        book.write_cell_value("S", "B1", {"type": "number", "value": 42})
+        book.write_cell_value("S", "A2", {"type": "string", "value": "bob"})
+        book.save(str(src))
+
+        with pytest.raises(ValueError, match="Unknown transform callback"):
+            rust.transform(str(src), str(dst), {"sheet": print})
+
+        def redact(sheet: str, a1: str, payload: dict[str, Any]) -> dict[str, Any] | None:
+            if payload["type"] == "string":
+                return {"type": "string", "value": "***"}
+            return None
+
+        def double_row_2(sheet: str, row: int, cells: dict[str, Any]) -> dict[str, Any] | None:
+            return {"A2": {"type": "number", "value": 2}} if row == 2 else None
+
+        stats = rust.transform(str(src), str(dst), {"cell": redact, "row": double_row_2})
+        assert stats == {"sheets": 1, "cells": 2}
+
+        out = rust.CalamineStyledBook.open(str(dst))
+        assert out.read_cell_value("S", "A1") == {"type": "string", "value": "***"}
+        assert out.read_cell_value("S", "B1") == {"type": "number", "value": 42.0}
+        assert out.read_cell_value("S", "A2") == {"type": "number", "value": 2.0}
+        assert out.read_cell_format("S", "A1").get("bold") is True
+    finally:
+        for p in (src, dst):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_patches_many_sheets() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"wolfxl", "rust_xlsxwriter", "calamine"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without wolfxl/rust_xlsxwriter/calamine backends")
+
+    tmp = Path(tempfile.mkdtemp())
+    src, dst = tmp / "src.xlsx", tmp / "dst.xlsx"
+    sheets = [f"S{i}" for i in range(12)]
+    try:
+        book = rust.RustXlsxWriterBook()
+        for name in sheets:
+            book.add_sheet(name)
+            book.write_cell_value(name, "A1", {"type": "string", "value": "old"})
+        book.save(str(src))
+
+        patcher = rust.XlsxPatcher.open(str(src))
+        for i, name in enumerate(sheets):
+            patcher.queue_value(name, "A1", {"type": "number", "value": i})
+            patcher.queue_value(name, "B2", {"type": "string", "value": name})
+        patcher.save(str(dst))
+
+        out = rust.CalamineBook.open(str(dst))
+        for i, name in enumerate(sheets):
+            assert out.read_cell_value(name, "A1") == {"type": "number", "value": float(i)}
+            assert out.read_cell_value(name, "B2") == {"type": "string", "value": name}
+    finally:
+        for p in (src, dst):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_xlsxwriter_writes_sheets_independently() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"rust_xlsxwriter", "calamine"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without rust_xlsxwriter/calamine backends")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "sheets.xlsx"
+    sheets = [f"Report{i}" for i in range(8)]
+    try:
+        book = rust.RustXlsxWriterBook()
+        for name in sheets:
+            book.add_sheet(name)
+        # Interleave sheets so per-sheet grouping has to reorder the queue.
+        for row in range(1, 51):
+            for name in sheets:
+                book.write_cell_value(name, f"A{row}", {"type": "number", "value": row})
+        for name in sheets:
+            book.write_cell_format(name, "A1", {"bold": True})
+            book.write_cell_border(name, "C3", {"top": {"style": "thin", "color": "#000000"}})
+        book.save(str(path))
+
+        out = rust.CalamineStyledBook.open(str(path))
+        for name in sheets:
+            assert out.read_cell_value(name, "A50") == {"type": "number", "value": 50.0}
+            assert out.read_cell_format(name, "A1").get("bold") is True
+            assert out.read_cell_border(name, "C3")["top"]["style"] == "thin"
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+@pytest.mark.parametrize("cls_name", ["RustXlsxWriterBook", "UmyaBook"])
+def test_autofit_columns(cls_name: str) -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    cls = getattr(rust, cls_name, None)
+    if cls is None or getattr(cls, "autofit_columns", None) is None:
+        pytest.skip(f"wolfxl._rust has no {cls_name}.autofit_columns")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without umya backend")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "fit.xlsx"
+    try:
+        book = cls()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "string", "value": "x"})
+        book.write_cell_value("S", "B1", {"type": "string", "value": "a fairly long heading"})
+        book.write_cell_value("S", "C1", {"type": "string", "value": "W" * 200})
+        with pytest.raises(ValueError, match="exceeds max_width"):
+            book.autofit_columns("S", min_width=10, max_width=5)
+        book.autofit_columns("S", min_width=6, max_width=60)
+        book.save(str(path))
+
+        out = rust.UmyaBook.open(str(path))
+        a = out.read_column_width("S", "A")
+        b = out.read_column_width("S", "B")
+        c = out.read_column_width("S", "C")
+        assert a is not None and b is not None and c is not None
+        assert a == pytest.approx(6, abs=0.5)
+        assert 15 < b < 30
+        assert c == pytest.approx(60, abs=1)
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_xlsxwriter_comment_size_and_visibility() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without rust_xlsxwriter backend")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "notes.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S")
+        book.add_comment(
+            "S",
+            {
+                "cell": "B2",
+                "text": "shown",
+                "visible": True,
+                "width": 300,
+                "height": 120,
+                "bg_color": "#CCFFCC",
+            },
+        )
+        book.add_comment("S", {"cell": "D4", "text": "hover"})
+        with pytest.raises(ValueError, match="must be a non-negative int"):
+            book.add_comment("S", {"cell": "E5", "text": "x", "width": "wide"})
+        book.save(str(path))
+
+        with zipfile.ZipFile(path) as zf:
+            vml_name = next(n for n in zf.namelist() if n.endswith(".vml"))
+            vml = zf.read(vml_name).decode("utf-8")
+        shapes = vml.split("<v:shape ")[1:]
+        assert len(shapes) == 2
+        assert "visibility:visible" in shapes[0]
+        assert "width:300px" in shapes[0] or "width:225pt" in shapes[0]
+        assert 'fillcolor="#ccffcc"' in shapes[0].lower()
+        assert "visibility:hidden" in shapes[1]
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_sheet_visibility_and_active_sheet() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"wolfxl", "rust_xlsxwriter"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without wolfxl/rust_xlsxwriter backends")
+    if getattr(rust.XlsxPatcher, "set_active_sheet", None) is None:
+        pytest.skip("wolfxl._rust predates XlsxPatcher.set_active_sheet")
+
+    tmp = Path(tempfile.mkdtemp())
+    src, dst = tmp / "src.xlsx", tmp / "dst.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook()
+        for name in ("A", "B", "C"):
+            book.add_sheet(name)
+            book.write_cell_value(name, "A1", {"type": "string", "value": name})
+        book.save(str(src))
+
+        patcher = rust.XlsxPatcher.open(str(src))
+        with pytest.raises(ValueError, match="Invalid sheet state"):
+            patcher.set_sheet_visibility("A", "invisible")
+        with pytest.raises(ValueError, match="Unknown sheet"):
+            patcher.set_active_sheet("Z")
+        for name in ("A", "B", "C"):
+            patcher.set_sheet_visibility(name, "hidden")
+        with pytest.raises(ValueError, match="At least one sheet must remain visible"):
+            patcher.save(str(dst))
+
+        patcher.set_sheet_visibility("C", "visible")
+        patcher.set_active_sheet("B")
+        with pytest.raises(ValueError, match="Cannot make hidden sheet 'B'"):
+            patcher.save(str(dst))
+
+        # Without an explicit active sheet, the hidden active tab moves to C.
+        patcher = rust.XlsxPatcher.open(str(src))
+        patcher.set_sheet_visibility("A", "hidden")
+        patcher.set_sheet_visibility("B", "veryHidden")
+        patcher.save(str(dst))
+        with zipfile.ZipFile(dst) as zf:
+            wb_xml = zf.read("xl/workbook.xml").decode("utf-8")
+            sheet1 = zf.read("xl/worksheets/sheet1.xml").decode("utf-8")
+            sheet3 = zf.read("xl/worksheets/sheet3.xml").decode("utf-8")
+        assert 'activeTab="2"' in wb_xml
+        assert wb_xml.count('state="hidden"') == 1
+        assert 'state="veryHidden"' in wb_xml
+        assert 'tabSelected="1"' not in sheet1
+        assert 'tabSelected="1"' in sheet3
+    finally:
+        for p in (src, dst):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_calamine_open_lenient_repairs_package() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    calamine = getattr(rust, "CalamineBook", None)
+    if getattr(calamine, "open_lenient", None) is None:
+        pytest.skip("wolfxl._rust predates CalamineBook.open_lenient")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    good, bad = tmp / "good.xlsx", tmp / "bad.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws["A1"] = "hello"
+        ws["B2"] = 42
+        wb.save(good)
+
+        book, anomalies = calamine.open_lenient(str(good))
+        assert anomalies == []
+        assert book.read_cell_value("S", "A1") == {"type": "string", "value": "hello"}
+
+        # Drop the content types, use Windows separators, append junk.
+        with zipfile.ZipFile(good) as zin, zipfile.ZipFile(bad, "w") as zout:
+            for info in zin.infolist():
+                if info.filename == "[Content_Types].xml":
+                    continue
+                name = info.filename
+                if name == "xl/worksheets/sheet1.xml":
+                    name = name.replace("/", "\\")
+                zout.writestr(name, zin.read(info))
+        with bad.open("ab") as f:
+            f.write(b"trailing garbage")
+
+        book, anomalies = calamine.open_lenient(str(bad))
+        codes = {a["code"] for a in anomalies}
+        assert {"zip.trailing_data", "part.nonstandard_name", "content_types.missing"} <= codes
+        renamed = [a for a in anomalies if a["code"] == "part.nonstandard_name"]
+        assert renamed[0]["part"] == "xl/worksheets/sheet1.xml"
+        assert book.sheet_names() == ["S"]
+        assert book.read_cell_value("S", "A1") == {"type": "string", "value": "hello"}
+        assert book.read_cell_value("S", "B2") == {"type": "number", "value": 42}
+    finally:
+        for p in (good, bad):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_umya_conditional_format_icon_set_top10_above_average() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without the umya backend")
+
+    tmp = Path(tempfile.mkdtemp())
+    out = tmp / "cf.xlsx"
+    try:
+        book = rust.UmyaBook()
+        book.add_sheet("S")
+        for i in range(1, 11):
+            book.write_cell_value("S", f"A{i}", {"type": "number", "value": i})
+        book.add_conditional_format(
+            "S",
+            {
+                "range": "A1:A10",
+                "rule_type": "iconSet",
+                "priority": 1,
+                "thresholds": [
+                    {"type": "percent", "value": 0},
+                    {"type": "num", "value": 4},
+                    {"type": "percentile", "value": 80},
+                ],
+            },
+        )
+        book.add_conditional_format(
+            "S",
+            {"range": "A1:A10", "rule_type": "top10", "priority": 2, "rank": 3, "bottom": True},
+        )
+        book.add_conditional_format(
+            "S",
+            {
+                "range": "A1:A10",
+                "rule_type": "aboveAverage",
+                "priority": 3,
+                "above_average": False,
+                "std_dev": 1,
+            },
+        )
+        with pytest.raises(ValueError, match="icon set"):
+            book.add_conditional_format(
+                "S", {"range": "A1:A10", "rule_type": "iconSet", "icon_style": "5Arrows"}
+            )
+        with pytest.raises(ValueError, match="threshold type"):
+            book.add_conditional_format(
+                "S",
+                {"range": "A1", "rule_type": "iconSet", "thresholds": [{"type": "median"}]},
+            )
+        book.save(str(out))
+
+        with zipfile.ZipFile(out) as zf:
+            xml = zf.read("xl/worksheets/sheet1.xml").decode()
+        assert '<cfvo type="num" val="4"' in xml
+        assert '<cfvo type="percentile" val="80"' in xml
+        assert 'type="top10"' in xml and 'rank="3"' in xml
+        assert 'bottom="1"' in xml or 'bottom="true"' in xml
+        assert 'type="aboveAverage"' in xml and 'stdDev="1"' in xml
+
+        rules = rust.UmyaBook.open(str(out)).read_conditional_formats("S")
+        assert [r["rule_type"] for r in rules] == ["iconSet", "top10", "aboveAverage"]
+    finally:
+        out.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_umya_conditional_format_data_bar_and_color_scale_params() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without the umya backend")
+
+    tmp = Path(tempfile.mkdtemp())
+    out = tmp / "cf.xlsx"
+    try:
+        book = rust.UmyaBook()
+        book.add_sheet("S")
+        bar = {
+            "range": "A1:A10",
+            "rule_type": "dataBar",
+            "min": {"type": "num", "value": 2},
+            "max": {"type": "percent", "value": 90},
+            "colors": ["#112233"],
+        }
+        scale = {
+            "range": "B1:B10",
+            "rule_type": "colorScale",
+            "min": {"type": "min", "value": None},
+            "mid": {"type": "percentile", "value": 40},
+            "max": {"type": "max", "value": None},
+            "colors": ["#FF0000", "#FFFF00", "#00FF00"],
+        }
+        book.add_conditional_format("S", bar)
+        book.add_conditional_format("S", scale)
+        book.add_conditional_format(
+            "S", {"range": "C1:C10", "rule_type": "colorScale", "colors": ["#000000", "#FFFFFF"]}
+        )
+        with pytest.raises(ValueError, match="colorScale takes"):
+            book.add_conditional_format(
+                "S", {"range": "D1", "rule_type": "colorScale", "colors": ["#000000"]}
+            )
+        book.save(str(out))
+
+        rules = rust.UmyaBook.open(str(out)).read_conditional_formats("S")
+        assert rules[0]["min"] == {"type": "num", "value": 2}
+        assert rules[0]["max"] == {"type": "percent", "value": 90}
+        assert rules[0]["colors"] == ["#112233"]
+        assert rules[1]["mid"] == {"type": "percentile", "value": 40}
+        assert rules[1]["colors"] == ["#FF0000", "#FFFF00", "#00FF00"]
+        assert "mid" not in rules[2]
+        assert rules[2]["colors"] == ["#000000", "#FFFFFF"]
+    finally:
+        out.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_xlsxwriter_merged_top_left_keeps_type() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"rust_xlsxwriter", "calamine"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without rust_xlsxwriter/calamine backends")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "merged.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "number", "value": 2024})
+        book.write_cell_value("S", "A3", {"type": "formula", "formula": "=A1+1", "value": 2025})
+        book.write_cell_value("S", "A5", {"type": "string", "value": "Header"})
+        book.write_cell_format("S", "A5", {"bold": True})
+        for rng in ("A1:C1", "A3:C3", "A5:C5"):
+            book.merge_cells("S", rng)
+        book.save(str(path))
+
+        out = rust.CalamineStyledBook.open(str(path))
+        assert sorted(out.read_merged_ranges("S")) == ["A1:C1", "A3:C3", "A5:C5"]
+        assert out.read_cell_value("S", "A1") == {"type": "number", "value": 2024.0}
+        assert out.read_cell_value("S", "A3")["formula"] == "=A1+1"
+        assert out.read_cell_value("S", "A5") == {"type": "string", "value": "Header"}
+        assert out.read_cell_format("S", "A5").get("bold") is True
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_structured_exceptions() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not hasattr(rust, "ExcelBenchError"):
+        pytest.skip("structured exceptions not available in this build")
+
+    assert issubclass(rust.SheetNotFound, rust.ExcelBenchError)
+    assert issubclass(rust.SheetNotFound, ValueError)
+    assert issubclass(rust.CellRefError, ValueError)
+    assert issubclass(rust.FileFormatError, OSError)
+    assert issubclass(rust.UnsupportedFeature, NotImplementedError)
+
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("rust_xlsxwriter backend not enabled in this build")
+    book = rust.RustXlsxWriterBook()
+    book.add_sheet("S1")
+    with pytest.raises(rust.SheetNotFound, match="Unknown sheet") as info:
+        book.write_cell_value("Missing", "A1", {"type": "string", "value": "x"})
+    assert info.value.sheet == "Missing"
+    assert info.value.backend == "rust_xlsxwriter"
+    with pytest.raises(rust.CellRefError) as info:
+        book.write_cell_value("S1", "not-a-cell", {"type": "string", "value": "x"})
+    assert info.value.cell == "not-a-cell"
+
+    if "wolfxl" in _enabled_backends(rust):
+        tmp = Path(tempfile.mkdtemp())
+        try:
+            bad = tmp / "bad.xlsx"
+            bad.write_bytes(b"not a zip")
+            with pytest.raises(rust.FileFormatError) as info:
+                rust.XlsxPatcher.open(str(bad))
+            assert info.value.path == str(bad)
+        finally:
+            bad.unlink(missing_ok=True)
+            tmp.rmdir()
+
+
+def test_rust_writer_books_usable_from_worker_threads() -> None:
+    from concurrent.futures import ThreadPoolExecutor
+
+    rust = pytest.importorskip("wolfxl._rust")
+    enabled = _enabled_backends(rust)
+    factories = []
+    if "rust_xlsxwriter" in enabled:
+        factories.append(rust.RustXlsxWriterBook)
+    if "umya-spreadsheet" in enabled:
+        factories.append(rust.UmyaBook)
+    if not factories:
+        pytest.skip("no writer backends enabled in this build")
+
+    def fill(book: Any, sheet: str) -> None:
+        book.add_sheet(sheet)
+        book.write_cell_value(sheet, "A1", {"type": "string", "value": sheet})
+
+    tmp = Path(tempfile.mkdtemp())
+    paths = []
+    try:
+        with ThreadPoolExecutor(max_workers=2) as pool:
+            for i, factory in enumerate(factories):
+                # Created on this thread, mutated and saved on a worker.
+                book = factory()
+                pool.submit(fill, book, "S1").result()
+                path = tmp / f"book{i}.xlsx"
+                paths.append(path)
+                pool.submit(book.save, str(path)).result()
+        for path in paths:
+            with zipfile.ZipFile(path) as zf:
+                assert "xl/workbook.xml" in zf.namelist()
+    finally:
+        for path in paths:
+            path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_books_support_context_manager() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    enabled = _enabled_backends(rust)
+    if "rust_xlsxwriter" not in enabled:
+        pytest.skip("rust_xlsxwriter backend not enabled in this build")
+    if not hasattr(rust.RustXlsxWriterBook, "__exit__"):
+        pytest.skip("wolfxl._rust predates the context-manager protocol")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "ctx.xlsx"
+    try:
+        # Saved on a clean exit ...
+        with rust.RustXlsxWriterBook(str(path)) as book:
+            book.add_sheet("S1")
+            book.write_cell_value("S1", "A1", {"type": "string", "value": "hi"})
+        assert path.exists()
+
+        # ... but not when the block raises.
+        other = tmp / "aborted.xlsx"
+        with pytest.raises(RuntimeError, match="boom"):
+            with rust.RustXlsxWriterBook(str(other)) as book:
+                book.add_sheet("S1")
+                raise RuntimeError("boom")
+        assert not other.exists()
+
+        readers = []
+        if "calamine" in enabled:
+            readers += [rust.CalamineBook, rust.CalamineStyledBook]
+        if "wolfxl" in enabled:
+            readers.append(rust.XlsxReader)
+        if "umya-spreadsheet" in enabled:
+            readers.append(rust.UmyaBook)
+        for cls in readers:
+            with cls.open(str(path)) as book:
+                assert book.read_cell_value("S1", "A1")["value"] == "hi"
+            if cls is not rust.UmyaBook:
+                with pytest.raises(ValueError, match="closed"):
+                    book.read_cell_value("S1", "A1")
+            book.close()  # idempotent
+    finally:
+        for p in tmp.iterdir():
+            p.unlink()
+        tmp.rmdir()
+
+
+def test_rust_xlsxwriter_ignore_errors() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("rust_xlsxwriter backend not enabled in this build")
+    if not hasattr(rust.RustXlsxWriterBook, "ignore_errors"):
+        pytest.skip("wolfxl._rust predates RustXlsxWriterBook.ignore_errors")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "ignore.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S1")
+        book.write_cell_value("S1", "A2", {"type": "string", "value": "00123"})
+        book.ignore_errors("S1", {"number_stored_as_text": "A2:A10 C1", "formula": "B1"})
+        with pytest.raises(ValueError, match="Unknown ignore_errors key"):
+            book.ignore_errors("S1", {"green_triangles": "A1"})
+        book.save(str(path))
+
+        with zipfile.ZipFile(path) as zf:
+            sheet_xml = zf.read("xl/worksheets/sheet1.xml").decode("utf-8")
+        assert "<ignoredErrors>" in sheet_xml
+        assert 'sqref="A2:A10"' in sheet_xml or "A2:A10 C1" in sheet_xml
+        assert 'numberStoredAsText="1"' in sheet_xml
+        assert 'formula="1"' in sheet_xml
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_xlsxwriter_calc_mode_and_full_calc_on_load() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("rust_xlsxwriter backend not enabled in this build")
+    if not hasattr(rust.RustXlsxWriterBook, "set_calc_mode"):
+        pytest.skip("wolfxl._rust predates RustXlsxWriterBook.set_calc_mode")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "calc.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S1")
+        book.write_cell_value("S1", "A1", {"type": "formula", "formula": "=1+1"})
+        with pytest.raises(ValueError, match="Unknown calc mode"):
+            book.set_calc_mode("sometimes")
+        book.set_calc_mode("manual")
+        book.set_full_calc_on_load(True)
+        book.save(str(path))
+
+        with zipfile.ZipFile(path) as zf:
+            wb_xml = zf.read("xl/workbook.xml").decode("utf-8")
+        assert wb_xml.count("<calcPr") == 1
+        assert 'calcMode="manual"' in wb_xml
+        assert 'fullCalcOnLoad="1"' in wb_xml
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_umya_calc_properties_survive_resave() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("umya backend not enabled in this build")
+    if not hasattr(rust.UmyaBook, "set_calc_properties"):
+        pytest.skip("wolfxl._rust predates UmyaBook.set_calc_properties")
+
+    tmp = Path(tempfile.mkdtemp())
+    first = tmp / "calc.xlsx"
+    second = tmp / "calc_resaved.xlsx"
+    try:
+        book = rust.UmyaBook()
+        book.add_sheet("S1")
+        with pytest.raises(ValueError, match="Unknown calc property"):
+            book.set_calc_properties({"recalc": True})
+        book.set_calc_properties(
+            {"calc_mode": "manual", "iterative": True, "max_iterations": 500}
+        )
+        book.save(str(first))
+
+        with zipfile.ZipFile(first) as zf:
+            wb_xml = zf.read("xl/workbook.xml").decode("utf-8")
+        assert wb_xml.count("<calcPr") == 1
+        assert 'calcMode="manual"' in wb_xml
+        assert 'iterate="1"' in wb_xml
+        assert 'iterateCount="500"' in wb_xml
+
+        # A plain open + save keeps the properties umya itself would drop.
+        reopened = rust.UmyaBook.open(str(first))
+        props = reopened.read_calc_properties()
+        assert props["calc_mode"] == "manual"
+        assert props["iterative"] is True
+        assert props["max_iterations"] == 500
+        reopened.save(str(second))
+
+        with zipfile.ZipFile(second) as zf:
+            wb_xml = zf.read("xl/workbook.xml").decode("utf-8")
+        assert 'iterate="1"' in wb_xml
+        assert 'iterateCount="500"' in wb_xml
+    finally:
+        first.unlink(missing_ok=True)
+        second.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_remap_references() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"wolfxl", "rust_xlsxwriter"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without wolfxl/rust_xlsxwriter backends")
+    if getattr(rust.XlsxPatcher, "remap_references", None) is None:
+        pytest.skip("wolfxl._rust predates XlsxPatcher.remap_references")
+
+    tmp = Path(tempfile.mkdtemp())
+    src, dst = tmp / "src.xlsx", tmp / "dst.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("Data")
+        book.add_sheet("Summary")
+        book.write_cell_value("Data", "C1", {"type": "formula", "formula": "=A1*2+$C$9"})
+        book.write_cell_value(
+            "Summary",
+            "A1",
+            {"type": "formula", "formula": '=SUM(Data!A1:A3)+Data!$B$1&"A1"'},
+        )
+        book.write_cell_value("Summary", "A2", {"type": "formula", "formula": "=SUM(A1:A3)"})
+        book.save(str(src))
+
+        patcher = rust.XlsxPatcher.open(str(src))
+        with pytest.raises(ValueError, match="not the same size"):
+            patcher.remap_references("Data", {"A1:B3": "E1:E2"})
+        patcher.remap_references("Data", {"A1:B3": "E1"})
+        patcher.save(str(dst))
+
+        with zipfile.ZipFile(dst) as zf:
+            data_xml = zf.read("xl/worksheets/sheet1.xml").decode("utf-8")
+            summary_xml = zf.read("xl/worksheets/sheet2.xml").decode("utf-8")
+        assert "E1*2+$C$9" in data_xml
+        assert "SUM(Data!E1:E3)+Data!$F$1&amp;&quot;A1&quot;" in summary_xml or (
+            'SUM(Data!E1:E3)+Data!$F$1&amp;"A1"' in summary_xml
+        )
+        # Unqualified references on other sheets point at those sheets.
+        assert "SUM(A1:A3)" in summary_xml
+    finally:
+        for p in (src, dst):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_analyze_formulas() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    if getattr(rust.CalamineBook, "analyze_formulas", None) is None:
+        pytest.skip("wolfxl._rust predates CalamineBook.analyze_formulas")
+    openpyxl = pytest.importorskip("openpyxl")
+    from openpyxl.worksheet.formula import ArrayFormula
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "formulas.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        for row in range(1, 4):
+            ws.cell(row=row, column=1, value=row)
+        ws["B1"] = "=NOW()+RAND()"
+        ws["B2"] = "=[1]Sheet1!A1*2"
+        ws["B3"] = '=SUM(A1:A3)&"NOW()"'
+        ws["C1"] = ArrayFormula("C1:C3", "=A1:A3*2")
+        wb.save(path)
+
+        report = rust.CalamineBook.open(str(path)).analyze_formulas("S")
+        assert report["formula_cells"] == 4
+        assert report["volatile"]["count"] == 1
+        assert report["volatile"]["functions"] == {"NOW": 1, "RAND": 1}
+        assert report["volatile"]["cells"] == [{"cell": "B1", "functions": ["NOW", "RAND"]}]
+        assert report["external"]["cells"] == [{"cell": "B2", "references": ["[1]Sheet1!A1"]}]
+        assert report["array"] == {"count": 1, "cells": [{"cell": "C1", "ref": "C1:C3"}]}
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_umya_read_auto_filter() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without umya backend")
+    if getattr(rust.UmyaBook, "read_auto_filter", None) is None:
+        pytest.skip("wolfxl._rust predates UmyaBook.read_auto_filter")
+    openpyxl = pytest.importorskip("openpyxl")
+    from openpyxl.worksheet.filters import CustomFilter, CustomFilters, FilterColumn
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "filter.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws.append(["region", "amount"])
+        ws.append(["East", 3])
+        ws.append(["West", 7])
+        ws.auto_filter.ref = "A1:B3"
+        ws.auto_filter.add_filter_column(0, ["East", "West"], blank=True)
+        custom = CustomFilters(customFilter=[CustomFilter(operator="greaterThan", val="5")])
+        ws.auto_filter.filterColumn.append(FilterColumn(colId=1, customFilters=custom))
+        wb.save(path)
+
+        book = rust.UmyaBook.open(str(path))
+        assert book.read_auto_filter("S") == {
+            "range": "A1:B3",
+            "columns": [
+                {
+                    "type": "values",
+                    "values": ["East", "West"],
+                    "dates": [],
+                    "blank": True,
+                    "col_id": 0,
+                },
+                {
+                    "type": "custom",
+                    "and": False,
+                    "conditions": [{"operator": "greaterThan", "value": "5"}],
+                    "col_id": 1,
+                },
+            ],
+        }
+
+        # A different range no longer matches the criteria read from the file.
+        book.set_auto_filter("S", "A1:B10")
+        assert book.read_auto_filter("S") == {"range": "A1:B10", "columns": []}
+        book.remove_auto_filter("S")
+        assert book.read_auto_filter("S") is None
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_xlsxwriter_native_datetime_payloads() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without rust_xlsxwriter backend")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "dates.xlsx"
+    stamp = datetime(2024, 6, 15, 10, 30, 5, 250000)
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "date", "value": date(2024, 6, 15)})
+        book.write_cell_value("S", "A2", {"type": "datetime", "value": stamp})
+        book.write_cell_value("S", "A3", {"type": "duration", "value": timedelta(hours=30)})
+        book.write_cell_value("S", "A4", {"type": "duration", "value": "1:02:03.5"})
+        with pytest.raises(ValueError, match="Invalid duration"):
+            book.write_cell_value("S", "A5", {"type": "duration", "value": "1:99:00"})
+        book.save(str(path))
+
+        ws = openpyxl.load_workbook(path)["S"]
+        assert ws["A1"].value.date() == date(2024, 6, 15)
+        assert abs(ws["A2"].value - stamp) < timedelta(milliseconds=1)
+        assert ws["A3"].number_format == "[h]:mm:ss"
+        assert ws["A3"].value == timedelta(hours=30)
+        assert abs(ws["A4"].value - timedelta(hours=1, minutes=2, seconds=3.5)) < timedelta(
+            milliseconds=1
+        )
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_umya_native_datetime_payloads() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without umya backend")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "dates.xlsx"
+    stamp = datetime(2024, 6, 15, 10, 30, 5, 123456)
+    # Before 1900-03-01, where Excel's phantom 1900-02-29 shifts serials.
+    early = datetime(1900, 1, 1, 12, 0)
+    try:
+        book = rust.UmyaBook()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "date", "value": date(2024, 6, 15)})
+        book.write_cell_value("S", "A2", {"type": "datetime", "value": stamp})
+        book.write_cell_value("S", "A3", {"type": "time", "value": time(8, 15, 30)})
+        book.write_cell_value("S", "A4", {"type": "time", "value": "17:45:00"})
+        book.write_cell_value("S", "A5", {"type": "datetime", "value": early})
+        expected = {"type": "datetime", "value": "2024-06-15T10:30:05.123456"}
+        assert book.read_cell_value("S", "A2") == expected
+        book.save(str(path))
+
+        reopened = rust.UmyaBook.open(str(path))
+        assert reopened.read_cell_value("S", "A2") == expected
+        assert reopened.read_cell_value("S", "A5") == {
+            "type": "datetime",
+            "value": "1900-01-01T12:00:00",
+        }
+
+        ws = openpyxl.load_workbook(path)["S"]
+        assert ws["A1"].value.date() == date(2024, 6, 15)
+        assert abs(ws["A2"].value - stamp) < timedelta(milliseconds=1)
+        assert ws["A3"].number_format == "hh:mm:ss"
+        assert ws["A3"].value == time(8, 15, 30)
+        assert ws["A4"].value == time(17, 45)
+        assert ws["A5"].value == early
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_time_and_duration_cells() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "times.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws["A1"] = time(8, 15, 30)
+        ws["A1"].number_format = "hh:mm:ss"
+        ws["A2"] = 1.25
+        ws["A2"].number_format = "[h]:mm:ss"
+        ws["A3"] = datetime(2024, 6, 15, 10, 30)
+        wb.save(path)
+
+        book = rust.CalamineBook.open(str(path))
+        assert book.read_cell_value("S", "A1") == {"type": "time", "value": "08:15:30"}
+        assert book.read_cell_value("S", "A2") == {"type": "duration", "value": "30:00:00"}
+        assert book.read_cell_value("S", "A3")["type"] == "datetime"
+        raw = rust.CalamineBook.open(str(path), raw_dates=True)
+        assert raw.read_cell_value("S", "A2")["type"] == "number"
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_patch_hyperlink_attrs() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "wolfxl" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without wolfxl backend")
+    if getattr(rust, "patch_hyperlink_attrs", None) is None:
+        pytest.skip("patch_hyperlink_attrs not available")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    src = tmp / "links.xlsx"
+    dst = tmp / "patched.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws["A1"] = "Home"
+        ws["A1"].hyperlink = "https://example.com"
+        ws["A1"].hyperlink.tooltip = "Old"
+        ws["B2"] = "Jump"
+        ws["B2"].hyperlink = "#S!C3"
+        ws["C3"] = "plain"
+        wb.save(src)
+
+        attrs = {"S": {"A1": {"tooltip": None}, "B2": {"tooltip": "Tip", "display": "Go"}}}
+        assert rust.patch_hyperlink_attrs(str(src), attrs, str(dst)) == 2
+        links = {c: openpyxl.load_workbook(dst)["S"][c].hyperlink for c in ("A1", "B2")}
+        assert links["A1"].tooltip is None
+        assert links["A1"].target == "https://example.com"
+        assert (links["B2"].tooltip, links["B2"].display) == ("Tip", "Go")
+
+        with pytest.raises(ValueError, match="No hyperlink"):
+            rust.patch_hyperlink_attrs(str(src), {"S": {"C3": {"tooltip": "x"}}})
+        with pytest.raises(rust.SheetNotFound):
+            rust.patch_hyperlink_attrs(str(src), {"Nope": {"A1": {"tooltip": "x"}}})
+
+        # In place
+        assert rust.patch_hyperlink_attrs(str(src), {"S": {"A1": {"tooltip": "New"}}}) == 1
+        assert openpyxl.load_workbook(src)["S"]["A1"].hyperlink.tooltip == "New"
+    finally:
+        for p in (src, dst):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_umya_hyperlink_tooltip_written_on_save() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without umya backend")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "tooltips.xlsx"
+    try:
+        book = rust.UmyaBook()
+        book.add_sheet("S")
+        book.add_hyperlink(
+            "S",
+            {"cell": "A1", "target": "https://example.com", "display": "Docs", "tooltip": "Tip"},
+        )
+        book.save(str(path))
+
+        with zipfile.ZipFile(path) as zf:
+            sheet_xml = zf.read("xl/worksheets/sheet1.xml").decode()
+        assert 'tooltip="Tip"' in sheet_xml
+        links = rust.UmyaBook.open(str(path)).read_hyperlinks("S")
+        assert links[0]["tooltip"] == "Tip"
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_theme_and_indexed_colors() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    enabled = _enabled_backends(rust)
+    openpyxl = pytest.importorskip("openpyxl")
+    from openpyxl.styles import Border, Color, Font, PatternFill, Side
+
+    tmp = Path(tempfile.mkdtemp())
+    src, dst = tmp / "colors.xlsx", tmp / "patched.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws["A1"] = "accent1 darker 25%"
+        ws["A1"].font = Font(color=Color(theme=4, tint=-0.25))
+        ws["A2"] = "indexed red"
+        ws["A2"].fill = PatternFill("solid", fgColor=Color(indexed=10))
+        ws["A3"] = "diagonal"
+        ws["A3"].border = Border(
+            diagonal=Side(style="thin", color=Color(theme=5)), diagonalUp=True
+        )
+        wb.save(src)
+
+        if "umya-spreadsheet" in enabled:
+            book = rust.UmyaBook.open(str(src))
+            assert book.read_cell_format("S", "A1")["font_color"] == "#2F5597"
+            assert book.read_cell_format("S", "A2")["bg_color"] == "#FF0000"
+        if "calamine" in enabled:
+            styled = rust.CalamineStyledBook.open(str(src))
+            diagonal = styled.read_cell_border("S", "A3")["diagonal_up"]
+            assert diagonal["color"] == "#ED7D31"
+        if "wolfxl" in enabled:
+            patcher = rust.XlsxPatcher.open(str(src))
+            with pytest.raises(ValueError, match="out of range"):
+                patcher.queue_format("S", "B1", {"font_color": "theme:12"})
+            patcher.queue_format(
+                "S", "B1", {"font_color": "theme:4:-0.25", "bg_color": "indexed:10"}
+            )
+            patcher.save(str(dst))
+            cell = openpyxl.load_workbook(dst)["S"]["B1"]
+            assert (cell.font.color.theme, cell.font.color.tint) == (4, -0.25)
+            assert cell.fill.fgColor.indexed == 10
+    finally:
+        for p in (src, dst):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_styled_pattern_and_gradient_fills() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    openpyxl = pytest.importorskip("openpyxl")
+    from openpyxl.styles import GradientFill, PatternFill
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "fills.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws["A1"].fill = PatternFill("solid", fgColor="FFFF00")
+        ws["A2"].fill = PatternFill("darkGrid", fgColor="FF0000", bgColor="0000FF")
+        ws["A3"].fill = PatternFill("gray125")
+        ws["A4"].fill = GradientFill(degree=90, stop=("FFFFFF", "4472C4"))
+        ws["A5"] = "plain"
+        wb.save(path)
+
+        book = rust.CalamineStyledBook.open(str(path))
+        solid = book.read_cell_format("S", "A1")
+        assert solid["pattern_type"] == "solid"
+        assert solid["bg_color"] == solid["fg_color"] == "#FFFF00"
+        grid = book.read_cell_format("S", "A2")
+        assert (grid["pattern_type"], grid["fg_color"], grid["bg_color"]) == (
+            "darkGrid",
+            "#FF0000",
+            "#0000FF",
+        )
+        assert book.read_cell_format("S", "A3")["pattern_type"] == "gray125"
+        gradient = book.read_cell_format("S", "A4")["gradient"]
+        assert (gradient["type"], gradient["degree"]) == ("linear", 90.0)
+        assert [(s["position"], s["color"]) for s in gradient["stops"]] == [
+            (0.0, "#FFFFFF"),
+            (1.0, "#4472C4"),
+        ]
+        assert "pattern_type" not in book.read_cell_format("S", "A5")
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_xlsxwriter_pattern_and_gradient_fills() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without rust_xlsxwriter backend")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "fills.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S")
+        book.write_cell_format("S", "A1", {"bg_color": "#FFFF00"})
+        book.write_cell_format(
+            "S", "A2", {"pattern": "darkGrid", "fg_color": "#FF0000", "bg_color": "#0000FF"}
+        )
+        book.write_cell_format("S", "A3", {"pattern_type": "gray125"})
+        gradient = {"degree": 90, "stops": ["#FFFFFF", "#4472C4"]}
+        book.write_cell_format("S", "A4", {"gradient": gradient, "bold": True})
+        book.write_cell_format("S", "A5", {"gradient": gradient})
+        path_gradient = {
+            "type": "path",
+            "left": 0.5,
+            "right": 0.5,
+            "top": 0.5,
+            "bottom": 0.5,
+            "stops": [{"position": 0, "color": "#000000"}, {"position": 1, "color": "#FFFFFF"}],
+        }
+        book.write_cell_format("S", "A6", {"gradient": path_gradient})
+        with pytest.raises(ValueError):
+            book.write_cell_format("S", "B1", {"pattern": "plaid"})
+        with pytest.raises(ValueError):
+            book.write_cell_format("S", "B1", {"gradient": {"stops": ["#FFFFFF"]}})
+        book.save(str(path))
+
+        ws = openpyxl.load_workbook(path)["S"]
+        assert ws["A1"].fill.patternType == "solid"
+        assert ws["A1"].fill.fgColor.rgb == "FFFFFF00"
+        grid = ws["A2"].fill
+        assert (grid.patternType, grid.fgColor.rgb, grid.bgColor.rgb) == (
+            "darkGrid",
+            "FFFF0000",
+            "FF0000FF",
+        )
+        assert ws["A3"].fill.patternType == "gray125"
+        for cell in ("A4", "A5"):
+            fill = ws[cell].fill
+            assert (fill.type, fill.degree) == ("linear", 90)
+            assert [(s.position, s.color.rgb) for s in fill.stop] == [
+                (0, "FFFFFFFF"),
+                (1, "FF4472C4"),
+            ]
+        assert ws["A4"].font.b
+        radial = ws["A6"].fill
+        assert (radial.type, radial.left, radial.top) == ("path", 0.5, 0.5)
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_umya_pattern_and_gradient_fills() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without umya backend")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "fills.xlsx"
+    try:
+        book = rust.UmyaBook()
+        book.add_sheet("S")
+        book.write_cell_format("S", "A1", {"bg_color": "indexed:5"})
+        book.write_cell_format(
+            "S", "A2", {"pattern": "DARKGRID", "fg_color": "#FF0000", "bg_color": "theme:4"}
+        )
+        book.write_cell_format("S", "A3", {"pattern_type": "gray125"})
+        gradient = {"degree": 90, "stops": ["#FFFFFF", "#4472C4"]}
+        book.write_cell_format("S", "A4", {"gradient": gradient})
+        with pytest.raises(ValueError):
+            book.write_cell_format("S", "B1", {"pattern": "plaid"})
+        with pytest.raises(NotImplementedError):
+            path_gradient = {"type": "path", "stops": gradient["stops"]}
+            book.write_cell_format("S", "B1", {"gradient": path_gradient})
+        book.save(str(path))
+
+        ws = openpyxl.load_workbook(path)["S"]
+        assert (ws["A1"].fill.patternType, ws["A1"].fill.fgColor.indexed) == ("solid", 5)
+        grid = ws["A2"].fill
+        assert (grid.patternType, grid.fgColor.rgb, grid.bgColor.theme) == (
+            "darkGrid",
+            "FFFF0000",
+            4,
+        )
+        assert ws["A3"].fill.patternType == "gray125"
+        fill = ws["A4"].fill
+        assert (fill.type, fill.degree) == ("linear", 90)
+        assert [(s.position, s.color.rgb) for s in fill.stop] == [
+            (0, "FFFFFFFF"),
+            (1, "FF4472C4"),
+        ]
+
+        reread = rust.UmyaBook.open(str(path))
+        assert reread.read_cell_format("S", "A1")["bg_color"] == "#FFFF00"
+        fmt = reread.read_cell_format("S", "A2")
+        assert (fmt["pattern_type"], fmt["fg_color"]) == ("darkGrid", "#FF0000")
+        assert fmt["bg_color"].startswith("#")
+        stops = reread.read_cell_format("S", "A4")["gradient"]["stops"]
+        assert [s["color"] for s in stops] == ["#FFFFFF", "#4472C4"]
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_anonymize_redacts_strings() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(rust, "anonymize", None) is None:
+        pytest.skip("wolfxl._rust predates anonymize()")
+    openpyxl = pytest.importorskip("openpyxl")
+    from openpyxl.styles import Font
+
+    tmp = Path(tempfile.mkdtemp())
+    src, dst = tmp / "src.xlsx", tmp / "dst.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws["A1"] = "Contact alice@example.com"
+        ws["A2"] = "Acct 12345678"
+        ws["A3"] = "Acct 12345678"
+        ws["B1"] = 42
+        ws["B2"] = '=A1&"!"'
+        ws["A1"].font = Font(bold=True)
+        wb.save(src)
+
+        rules = [
+            {"pattern": r"[\w.]+@[\w.]+", "replace": "<email>"},
+            {"pattern": r"\d{8}", "hash": True, "prefix": "ACCT-"},
+        ]
+        with pytest.raises(ValueError):
+            rust.anonymize(str(src), str(dst), [{"pattern": "(", "replace": ""}])
+        stats = rust.anonymize(str(src), str(dst), rules, salt="corpus-1")
+        assert stats["replacements"] == 2
+        assert stats["parts"] == 1
+
+        ws = openpyxl.load_workbook(dst)["S"]
+        assert ws["A1"].value == "Contact <email>"
+        assert ws["A1"].font.b
+        assert ws["A2"].value.startswith("Acct ACCT-")
+        assert ws["A2"].value == ws["A3"].value
+        assert ws["B1"].value == 42
+        assert ws["B2"].value == '=A1&"!"'
+    finally:
+        for p in (src, dst):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_sample_and_preview_metadata() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(rust, "sample", None) is None:
+        pytest.skip("wolfxl._rust predates sample()")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "preview.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "Data"
+        ws["A1"] = "Report"
+        ws.merge_cells("A1:C1")
+        for row in range(3, 200):
+            ws.cell(row=row, column=1, value=row)
+            ws.cell(row=row, column=2, value=f"r{row}")
+        ws.column_dimensions["B"].width = 30
+        ws.freeze_panes = "A3"
+        ws.page_setup.orientation = "landscape"
+        ws.print_area = "A1:C50"
+        wb.create_sheet("Empty")
+        wb.save(path)
+
+        rows = rust.sample(str(path), "Data", n_rows=4)
+        assert len(rows) == 4
+        assert rows[0][0] == {"type": "string", "value": "Report"}
+        assert rows[1] == []
+        assert rows[3][:2] == [
+            {"type": "number", "value": 4.0},
+            {"type": "string", "value": "r4"},
+        ]
+        assert len(rust.sample(str(path), "Data")) == 50
+        assert rust.sample(str(path), "Empty") == []
+        with pytest.raises(ValueError):
+            rust.sample(str(path), "Nope")
+
+        meta = rust.extract_print_preview_metadata(str(path))
+        assert [m["sheet"] for m in meta] == ["Data", "Empty"]
+        data = meta[0]
+        assert data["merged_ranges"] == ["A1:C1"]
+        assert data["freeze_panes"] == "A3"
+        assert {"first": "B", "last": "B", "width": 30.0, "hidden": False} in data["columns"]
+        assert data["page_setup"]["orientation"] == "landscape"
+        assert data["print_area"].endswith("$A$1:$C$50")
+        assert meta[1]["merged_ranges"] == []
+        assert meta[1]["print_area"] is None
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_xlsxwriter_background_and_watermark() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("rust_xlsxwriter backend not enabled in this build")
+    if not hasattr(rust.RustXlsxWriterBook, "set_watermark"):
+        pytest.skip("wolfxl._rust predates set_watermark()")
+
+    import base64
+    import zipfile
+
+    png = base64.b64decode(
+        "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR4nGNgYGD4DwAB"
+        "BAEAwS2OUAAAAABJRU5ErkJggg=="
+    )
+    tmp = Path(tempfile.mkdtemp())
+    img = tmp / "bg.png"
+    out = tmp / "out.xlsx"
+    try:
+        img.write_bytes(png)
+        wb = rust.RustXlsxWriterBook()
+        wb.add_sheet("Back")
+        wb.add_sheet("Mark")
+        wb.write_cell_value("Back", "A1", {"type": "string", "value": "x"})
+        wb.write_cell_value("Mark", "A1", {"type": "string", "value": "x"})
+        wb.set_background("Back", str(img))
+        wb.set_watermark("Mark", str(img))
+        with pytest.raises((OSError, ValueError)):
+            wb.set_background("Back", str(tmp / "missing.png"))
+        wb.save(str(out))
+
+        with zipfile.ZipFile(out) as zf:
+            names = zf.namelist()
+            back = zf.read("xl/worksheets/sheet1.xml").decode("utf-8")
+            mark = zf.read("xl/worksheets/sheet2.xml").decode("utf-8")
+        assert any(n.startswith("xl/media/") for n in names)
+        assert "<picture r:id=" in back
+        assert "&amp;C&amp;[Picture]" in mark
+        assert any(n.startswith("xl/drawings/vmlDrawing") for n in names)
+    finally:
+        out.unlink(missing_ok=True)
+        img.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_umya_image_offset_size_and_alt_text() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("umya backend not enabled in this build")
+
+    import base64
+
+    png = base64.b64decode(
+        "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR4nGNgYGD4DwAB"
+        "BAEAwS2OUAAAAABJRU5ErkJggg=="
+    )
+    tmp = Path(tempfile.mkdtemp())
+    img = tmp / "dot.png"
+    out = tmp / "out.xlsx"
+    try:
+        img.write_bytes(png)
+        wb = rust.UmyaBook()
+        wb.add_sheet("S")
+        wb.add_image(
+            "S",
+            {
+                "image": {
+                    "cell": "B2",
+                    "path": str(img),
+                    "offset": [9525, 19050],
+                    "width": 40,
+                    "height": 30,
+                    "alt_text": "A dot",
+                }
+            },
+        )
+        wb.save(str(out))
+
+        images = rust.UmyaBook.open(str(out)).read_images("S")
+        assert len(images) == 1
+        got = images[0]
+        assert got["cell"] == "B2"
+        assert got["offset"] == [9525, 19050]
+        assert (got["width"], got["height"]) == (40, 30)
+        assert got["alt_text"] == "A dot"
+    finally:
+        out.unlink(missing_ok=True)
+        img.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_read_images_with_bytes() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"calamine", "umya-spreadsheet"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without umya/calamine backends")
+    if getattr(rust.CalamineBook, "read_images", None) is None:
+        pytest.skip("wolfxl._rust predates CalamineBook.read_images")
+
+    import base64
+
+    png = base64.b64decode(
+        "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR4nGNgYGD4DwAB"
+        "BAEAwS2OUAAAAABJRU5ErkJggg=="
+    )
+    tmp = Path(tempfile.mkdtemp())
+    img = tmp / "dot.png"
+    out = tmp / "out.xlsx"
+    try:
+        img.write_bytes(png)
+        wb = rust.UmyaBook()
+        wb.add_sheet("S")
+        wb.add_sheet("Empty")
+        wb.add_image("S", {"image": {"cell": "C4", "path": str(img), "alt_text": "Dot"}})
+        wb.save(str(out))
+
+        book = rust.CalamineBook.open(str(out))
+        assert book.read_images("Empty") == []
+        (meta,) = book.read_images("S")
+        assert meta["cell"] == "C4"
+        assert meta["path"].startswith("xl/media/")
+        assert (meta["width"], meta["height"]) == (1, 1)
+        assert meta["alt_text"] == "Dot"
+        assert "data" not in meta
+
+        (full,) = book.read_images("S", include_bytes=True)
+        assert full["data"] == png
+        assert full["content_type"] == "image/png"
+    finally:
+        out.unlink(missing_ok=True)
+        img.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_replace_image_swaps_media_bytes() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"wolfxl", "umya-spreadsheet"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without wolfxl/umya backends")
+    if getattr(rust.XlsxPatcher, "replace_image", None) is None:
+        pytest.skip("wolfxl._rust predates XlsxPatcher.replace_image")
+
+    import base64
+    import zipfile
+
+    png = base64.b64decode(
+        "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR4nGNgYGD4DwAB"
+        "BAEAwS2OUAAAAABJRU5ErkJggg=="
+    )
+    tmp = Path(tempfile.mkdtemp())
+    old = tmp / "old.png"
+    new_png = tmp / "new.png"
+    new_jpg = tmp / "new.jpg"
+    src = tmp / "src.xlsx"
+    by_cell = tmp / "by_cell.xlsx"
+    by_name = tmp / "by_name.xlsx"
+    try:
+        old.write_bytes(png)
+        new_png.write_bytes(png + b"\0")
+        new_jpg.write_bytes(b"\xff\xd8\xff\xe0not-really-a-jpeg")
+        wb = rust.UmyaBook()
+        wb.add_sheet("S")
+        wb.add_image("S", {"image": {"cell": "B2", "path": str(old)}})
+        wb.save(str(src))
+        with zipfile.ZipFile(src) as zf:
+            (media,) = [n for n in zf.namelist() if n.startswith("xl/media/")]
+            drawings = {n: zf.read(n) for n in zf.namelist() if n.startswith("xl/drawings/")}
+
+        patcher = rust.XlsxPatcher.open(str(src))
+        with pytest.raises(ValueError):
+            patcher.replace_image("S!H8", str(new_png))
+        with pytest.raises(ValueError):
+            patcher.replace_image("nope.png", str(new_png))
+        patcher.replace_image("S!B2", str(new_png))
+        patcher.save(str(by_cell))
+        with zipfile.ZipFile(by_cell) as zf:
+            assert zf.read(media) == png + b"\0"
+            assert {n: zf.read(n) for n in drawings} == drawings
+
+        patcher = rust.XlsxPatcher.open(str(src))
+        patcher.replace_image(media.rsplit("/", 1)[1], str(new_jpg))
+        patcher.save(str(by_name))
+        with zipfile.ZipFile(by_name) as zf:
+            assert zf.read(media).startswith(b"\xff\xd8")
+            types = zf.read("[Content_Types].xml").decode("utf-8")
+        assert f'PartName="/{media}" ContentType="image/jpeg"' in types
+    finally:
+        for p in (old, new_png, new_jpg, src, by_cell, by_name):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_xlsxwriter_sheet_defaults_and_outline() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("rust_xlsxwriter backend not enabled in this build")
+    if not hasattr(rust.RustXlsxWriterBook, "set_default_column_width"):
+        pytest.skip("wolfxl._rust predates RustXlsxWriterBook.set_default_column_width")
+
+    import re
+    import zipfile
+
+    tmp = Path(tempfile.mkdtemp())
+    out = tmp / "out.xlsx"
+    try:
+        wb = rust.RustXlsxWriterBook()
+        wb.add_sheet("S")
+        wb.write_cell_value("S", "A1", {"type": "number", "value": 1})
+        wb.set_default_row_height("S", 20)
+        wb.set_default_column_width("S", 12.5)
+        wb.hide_unused_rows("S")
+        wb.set_outline_settings("S", summary_below=False, summary_right=False)
+        with pytest.raises(ValueError):
+            wb.set_default_row_height("S", 0)
+        with pytest.raises(ValueError):
+            wb.set_default_column_width("S", 300)
+        wb.save(str(out))
+
+        with zipfile.ZipFile(out) as zf:
+            xml = zf.read("xl/worksheets/sheet1.xml").decode("utf-8")
+        fmt = re.search(r"<sheetFormatPr[^>]*>", xml)
+        assert fmt is not None
+        assert 'defaultRowHeight="20"' in fmt.group(0)
+        assert 'zeroHeight="1"' in fmt.group(0)
+        assert 'defaultColWidth="12.5"' in fmt.group(0)
+        outline = re.search(r"<outlinePr[^>]*>", xml)
+        assert outline is not None
+        assert 'summaryBelow="0"' in outline.group(0)
+        assert 'summaryRight="0"' in outline.group(0)
+    finally:
+        out.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_umya_sheet_format_properties() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("umya backend not enabled in this build")
+    if not hasattr(rust.UmyaBook, "set_sheet_format_properties"):
+        pytest.skip("wolfxl._rust predates UmyaBook.set_sheet_format_properties")
+
+    import zipfile
+
+    tmp = Path(tempfile.mkdtemp())
+    first = tmp / "first.xlsx"
+    second = tmp / "second.xlsx"
+    try:
+        wb = rust.UmyaBook()
+        wb.add_sheet("Hidden")
+        wb.add_sheet("Plain")
+        wb.set_sheet_format_properties(
+            "Hidden",
+            {"default_row_height": 18, "default_col_width": 11, "zero_height": True},
+        )
+        with pytest.raises(ValueError):
+            wb.set_sheet_format_properties("Plain", {"row_height": 18})
+        with pytest.raises(ValueError):
+            wb.set_sheet_format_properties("Plain", {"default_row_height": -1})
+        wb.save(str(first))
+        with zipfile.ZipFile(first) as zf:
+            assert 'zeroHeight="1"' in zf.read("xl/worksheets/sheet1.xml").decode("utf-8")
+            assert "zeroHeight" not in zf.read("xl/worksheets/sheet2.xml").decode("utf-8")
+
+        # A plain open/save keeps the flag umya itself drops.
+        reopened = rust.UmyaBook.open(str(first))
+        props = reopened.read_sheet_format_properties("Hidden")
+        assert props == {"default_row_height": 18, "default_col_width": 11, "zero_height": True}
+        assert reopened.read_sheet_format_properties("Plain")["zero_height"] is False
+        reopened.save(str(second))
+        with zipfile.ZipFile(second) as zf:
+            assert 'zeroHeight="1"' in zf.read("xl/worksheets/sheet1.xml").decode("utf-8")
+    finally:
+        first.unlink(missing_ok=True)
+        second.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_read_freeze_panes_and_sheet_view() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"calamine", "rust_xlsxwriter"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without rust_xlsxwriter/calamine backends")
+    if getattr(rust.CalamineBook, "read_sheet_view", None) is None:
+        pytest.skip("wolfxl._rust predates CalamineBook.read_sheet_view")
+
+    tmp = Path(tempfile.mkdtemp())
+    out = tmp / "panes.xlsx"
+    try:
+        wb = rust.RustXlsxWriterBook()
+        wb.add_sheet("Frozen")
+        wb.add_sheet("Plain")
+        wb.write_cell_value("Frozen", "A1", {"type": "string", "value": "header"})
+        wb.set_freeze_panes("Frozen", {"mode": "freeze", "top_left_cell": "B3"})
+        wb.save(str(out))
+
+        book = rust.CalamineBook.open(str(out))
+        panes = book.read_freeze_panes("Frozen")
+        assert panes == {"mode": "freeze", "top_left_cell": "B3"}
+        assert book.read_freeze_panes("Plain") == {}
+
+        view = book.read_sheet_view("Frozen")
+        assert view["zoom"] == 100
+        assert view["show_gridlines"] is True
+        assert view["tab_selected"] is True
+        assert any(sel["pane"] == "bottomRight" for sel in view["selection"])
+        assert book.read_sheet_view("Plain")["tab_selected"] is False
+        with pytest.raises(rust.SheetNotFound):
+            book.read_sheet_view("Missing")
+    finally:
+        out.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_extend_conditional_format_to_appended_rows() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"wolfxl", "umya-spreadsheet"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without wolfxl/umya backends")
+    if getattr(rust.XlsxPatcher, "extend_conditional_format", None) is None:
+        pytest.skip("wolfxl._rust predates XlsxPatcher.extend_conditional_format")
+
+    import re
+    import zipfile
+
+    tmp = Path(tempfile.mkdtemp())
+    src = tmp / "template.xlsx"
+    out = tmp / "report.xlsx"
+    try:
+        book = rust.UmyaBook()
+        book.add_sheet("S")
+        for i in range(2, 6):
+            book.write_cell_value("S", f"B{i}", {"type": "number", "value": i})
+        book.add_conditional_format(
+            "S", {"range": "B2:B5", "rule_type": "dataBar", "colors": ["#638EC6"]}
+        )
+        book.add_conditional_format(
+            "S", {"range": "C2:C5", "rule_type": "colorScale", "colors": ["#000000", "#FFFFFF"]}
+        )
+        book.save(str(src))
+
+        patcher = rust.XlsxPatcher.open(str(src))
+        with pytest.raises(rust.CellRefError):
+            patcher.extend_conditional_format("S", "B2:B5", "not a range")
+        patcher.extend_conditional_format("S", "B2:B5", "B6:B9")
+        patcher.extend_conditional_format("S", "C3", "E2:E5")
+        patcher.save(str(out))
+        with zipfile.ZipFile(out) as zf:
+            xml = zf.read("xl/worksheets/sheet1.xml").decode("utf-8")
+        sqrefs = re.findall(r'<conditionalFormatting[^>]*sqref="([^"]*)"', xml)
+        assert sorted(sqrefs) == ["B2:B9", "C2:C5 E2:E5"]
+
+        patcher = rust.XlsxPatcher.open(str(src))
+        patcher.extend_conditional_format("S", "F1:F3", "F4")
+        with pytest.raises(ValueError, match="No conditional formatting"):
+            patcher.save(str(out))
+    finally:
+        for p in (src, out):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_xlsxwriter_write_records() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without rust_xlsxwriter backend")
+    if getattr(rust.RustXlsxWriterBook, "write_records", None) is None:
+        pytest.skip("wolfxl._rust predates RustXlsxWriterBook.write_records")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    from dataclasses import dataclass
+
+    @dataclass
+    class Sale:
+        region: str
+        units: int
+        shipped: date
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "records.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("Dicts")
+        book.add_sheet("Classes")
+        book.write_records(
+            "Dicts",
+            [
+                {"name": "a", "qty": 3, "ok": True},
+                {"name": "b", "price": 2.5, "qty": None},
+            ],
+        )
+        book.write_records(
+            "Classes",
+            [Sale("North", 7, date(2024, 6, 15)), Sale("South", 2, date(2024, 7, 1))],
+            header=False,
+        )
+        with pytest.raises(ValueError, match="dicts or dataclass"):
+            book.write_records("Dicts", [("not", "a", "record")])
+        book.save(str(path))
+
+        wb = openpyxl.load_workbook(path)
+        rows = list(wb["Dicts"].iter_rows(values_only=True))
+        assert rows == [
+            ("name", "qty", "ok", "price"),
+            ("a", 3, True, None),
+            ("b", None, None, 2.5),
+        ]
+        ws = wb["Classes"]
+        assert [ws["A1"].value, ws["B1"].value] == ["North", 7]
+        assert ws["C2"].value.date() == date(2024, 7, 1)
+        assert ws.max_row == 2
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_umya_update_and_remove_comment() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"umya-spreadsheet", "rust_xlsxwriter"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without umya/rust_xlsxwriter backends")
+    if getattr(rust.UmyaBook, "update_comment", None) is None:
+        pytest.skip("wolfxl._rust predates UmyaBook.update_comment")
+
+    tmp = Path(tempfile.mkdtemp())
+    src, dst = tmp / "src.xlsx", tmp / "dst.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S")
+        book.add_comment(
+            "S", {"cell": "B2", "text": "shown", "visible": True, "width": 300, "height": 120}
+        )
+        book.add_comment("S", {"cell": "D4", "text": "hover", "author": "bot"})
+        book.save(str(src))
+
+        umya = rust.UmyaBook.open(str(src))
+        notes = {c["cell"]: c for c in umya.read_comments("S")}
+        assert notes["B2"]["visible"] is True
+        assert notes["D4"]["visible"] is False
+        assert notes["B2"]["anchor"]["cols"] > notes["D4"]["anchor"]["cols"] > 0
+        assert notes["D4"]["anchor"]["rows"] > 0
+
+        umya.update_comment("S", "$d$4", "edited")
+        umya.remove_comment("S", "B2")
+        with pytest.raises(ValueError, match="No comment at S!B2"):
+            umya.remove_comment("S", "B2")
+        with pytest.raises(ValueError, match="No comment at S!Z9"):
+            umya.update_comment("S", "Z9", "x", "me")
+        with pytest.raises(rust.SheetNotFound):
+            umya.remove_comment("Missing", "A1")
+        umya.save(str(dst))
+
+        notes = rust.UmyaBook.open(str(dst)).read_comments("S")
+        assert [(c["cell"], c["text"], c["author"]) for c in notes] == [("D4", "edited", "bot")]
+    finally:
+        for p in (src, dst):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_value_patch_keeps_cell_comments() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    openpyxl = pytest.importorskip("openpyxl")
+    if not {"wolfxl", "rust_xlsxwriter"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without wolfxl/rust_xlsxwriter backends")
+
+    tmp = Path(tempfile.mkdtemp())
+    src, dst = tmp / "src.xlsx", tmp / "dst.xlsx"
+    notes = {"A1": "header note", "B2": "formula note", "C3": "empty-cell note"}
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "string", "value": "old"})
+        book.write_cell_value("S", "B2", {"type": "formula", "formula": "=1+1"})
+        for cell, text in notes.items():
+            book.add_comment("S", {"cell": cell, "text": text, "author": "qa"})
+        book.save(str(src))
+
+        patcher = rust.XlsxPatcher.open(str(src))
+        patcher.queue_value("S", "A1", {"type": "string", "value": "new"})
+        patcher.queue_format("S", "A1", {"bold": True})
+        patcher.queue_value("S", "B2", {"type": "number", "value": 3})
+        patcher.queue_value("S", "C3", {"type": "boolean", "value": True})
+        patcher.save(str(dst))
+
+        with zipfile.ZipFile(src) as zf:
+            src_parts = sorted(n for n in zf.namelist() if "comments" in n or n.endswith(".vml"))
+        with zipfile.ZipFile(dst) as zf:
+            sheet_xml = zf.read("xl/worksheets/sheet1.xml").decode("utf-8")
+            rels = zf.read("xl/worksheets/_rels/sheet1.xml.rels").decode("utf-8")
+            dst_parts = sorted(n for n in zf.namelist() if "comments" in n or n.endswith(".vml"))
+        assert "<legacyDrawing" in sheet_xml
+        assert "vmlDrawing" in rels and "comments" in rels
+        assert dst_parts == src_parts
+
+        ws = openpyxl.load_workbook(dst)["S"]
+        assert (ws["A1"].value, ws["B2"].value, ws["C3"].value) == ("new", 3, True)
+        assert ws["A1"].font.b
+        for cell, text in notes.items():
+            assert ws[cell].comment is not None
+            assert (ws[cell].comment.text, ws[cell].comment.author) == (text, "qa")
+    finally:
+        for p in (src, dst):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_sheet_stats() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"calamine", "rust_xlsxwriter"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine/rust_xlsxwriter backends")
+    if getattr(rust.CalamineBook, "sheet_stats", None) is None:
+        pytest.skip("wolfxl._rust predates CalamineBook.sheet_stats")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "stats.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S")
+        book.add_sheet("Empty")
+        book.write_cell_value("S", "B2", {"type": "string", "value": "name"})
+        book.write_cell_value("S", "C2", {"type": "number", "value": 1.5})
+        book.write_cell_value("S", "B4", {"type": "date", "value": "2024-06-15"})
+        book.write_cell_value("S", "C4", {"type": "boolean", "value": True})
+        book.write_cell_value("S", "E7", {"type": "formula", "formula": "=C2*2", "value": 3})
+        book.write_cell_format("S", "D5", {"bold": True})
+        book.save(str(path))
+
+        stats = rust.CalamineBook.open(str(path)).sheet_stats("S")
+        assert stats == {
+            "used_range": "B2:E7",
+            "cells": 5,
+            "formulas": 1,
+            "strings": 1,
+            "numbers": 2,
+            "dates": 1,
+            "booleans": 1,
+            "errors": 0,
+            "max_row": 7,
+            "max_col": 5,
+        }
+        empty = rust.CalamineBook.open(str(path)).sheet_stats("Empty")
+        assert (empty["used_range"], empty["cells"], empty["max_row"]) == (None, 0, 0)
+        with pytest.raises(rust.SheetNotFound):
+            rust.CalamineBook.open(str(path)).sheet_stats("Missing")
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_render_html() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(rust, "render_html", None) is None:
+        pytest.skip("wolfxl._rust predates render_html()")
+    openpyxl = pytest.importorskip("openpyxl")
+    from openpyxl.styles import Border, Font, PatternFill, Side
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "render.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "Report"
+        ws["A1"] = "Q1 <total>"
+        ws["A1"].font = Font(bold=True, color="FFFF0000")
+        ws.merge_cells("A1:C1")
+        ws["A2"] = 1234.5
+        ws["A2"].number_format = "#,##0.00"
+        ws["A2"].fill = PatternFill("solid", fgColor="FFFFFF00")
+        ws["B2"] = 0.125
+        ws["B2"].number_format = "0.0%"
+        ws["B2"].border = Border(bottom=Side(style="medium", color="FF0000FF"))
+        ws["C3"] = True
+        ws.column_dimensions["B"].width = 20
+        wb.save(path)
+
+        html = rust.render_html(str(path), "Report")
+        assert html.startswith('<table data-sheet="Report" data-range="A1:C3"')
+        assert 'data-cell="A1" colspan="3"' in html
+        assert 'data-cell="B1"' not in html
+        assert "Q1 &lt;total&gt;</td>" in html
+        assert "font-weight:bold" in html and "color:#FF0000" in html
+        assert "background:#FFFF00" in html and ">1,234.50</td>" in html
+        assert "border-bottom:2px solid #0000FF" in html and ">12.5%</td>" in html
+        assert ">TRUE</td>" in html
+        assert "<col style=\"width:140px\">" in html
+
+        window = rust.render_html(str(path), "Report", "B2:B2")
+        assert 'data-range="B2:B2"' in window
+        assert window.count("<td") == 1
+        with pytest.raises(ValueError):
+            rust.render_html(str(path), "Nope")
+        with pytest.raises(ValueError):
+            rust.render_html(str(path), "Report", "not a range")
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_format_cell_display() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    enabled = _enabled_backends(rust)
+    readers = []
+    if "calamine" in enabled:
+        readers.append(rust.CalamineStyledBook)
+    if "wolfxl" in enabled:
+        readers.append(rust.XlsxReader)
+    readers = [cls for cls in readers if hasattr(cls, "format_cell_display")]
+    if not readers:
+        pytest.skip("wolfxl._rust predates format_cell_display()")
+    openpyxl = pytest.importorskip("openpyxl")
+    import datetime as dt
+
+    cells = {
+        "A1": (1234.5, "#,##0.00;[Red](#,##0.00)", "1,234.50"),
+        "A2": (-1234.5, "#,##0.00;[Red](#,##0.00)", "(1,234.50)"),
+        "A3": (0.125, "0.0%", "12.5%"),
+        "A4": (12345.678, "0.00E+00", "1.23E+04"),
+        "A5": (1.25, "# ?/?", "1 1/4"),
+        "A6": (dt.datetime(2024, 3, 5, 14, 7, 9), "mmm d, yyyy h:mm AM/PM", "Mar 5, 2024 2:07 PM"),
+        "A7": (1 / 3, "General", "0.333333333"),
+        "A8": ("abc", '0;-0;0;"<"@">"', "<abc>"),
+        "A9": (True, "General", "TRUE"),
+    }
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "display.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        for a1, (value, code, _) in cells.items():
+            ws[a1] = value
+            ws[a1].number_format = code
+        wb.save(path)
+
+        for cls in readers:
+            book = cls.open(str(path))
+            for a1, (_, _, shown) in cells.items():
+                assert book.format_cell_display("S", a1) == shown, (cls, a1)
+            assert book.format_cell_display("S", "B1") == ""
+            assert book.format_cell_display("S", "A1", locale="de-DE") == "1.234,50"
+            with pytest.raises(ValueError):
+                book.format_cell_display("S", "A1", locale="xx")
+            book.close()
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_xlsxwriter_data_bar_and_color_scale_custom_colors() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without the rust_xlsxwriter backend")
+
+    import re
+    import zipfile
+
+    tmp = Path(tempfile.mkdtemp())
+    out = tmp / "cf.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook(strict=True)
+        book.add_sheet("S")
+        book.add_conditional_format(
+            "S",
+            {
+                "range": "A1:A10",
+                "rule_type": "dataBar",
+                "min_type": "num",
+                "min_value": 2,
+                "max": {"type": "percent", "value": 90},
+                "colors": ["#112233"],
+                "bar_border_color": "#445566",
+                "negative_fill": "FFAA0000",
+            },
+        )
+        book.add_conditional_format(
+            "S",
+            {
+                "range": "B1:B10",
+                "rule_type": "colorScale",
+                "mid": {"type": "percentile", "value": 40},
+                "colors": ["#FF0000", "#FFFF00", "#00FF00"],
+            },
+        )
+        book.add_conditional_format(
+            "S", {"range": "C1:C10", "rule_type": "colorScale", "colors": ["#000000", "#FFFFFF"]}
+        )
+        with pytest.raises(rust.UnsupportedFeature, match="takes 2 colors"):
+            book.add_conditional_format(
+                "S", {"range": "D1", "rule_type": "colorScale", "colors": ["#000000"]}
+            )
+        with pytest.raises(rust.UnsupportedFeature, match="'bogus'"):
+            book.add_conditional_format(
+                "S", {"range": "D1", "rule_type": "dataBar", "min_type": "bogus"}
+            )
+        book.save(str(out))
+
+        with zipfile.ZipFile(out) as zf:
+            xml = zf.read("xl/worksheets/sheet1.xml").decode("utf-8")
+        rules = re.findall(r"<cfRule .*?</cfRule>", xml, re.S)
+        bar, three, two = rules[:3]
+        assert re.findall(r'<cfvo type="(\w+)" val="([^"]*)"', bar) == [
+            ("num", "2"),
+            ("percent", "90"),
+        ]
+        assert '<color rgb="FF112233"' in bar
+        assert '<cfvo type="percentile" val="40"' in three
+        assert re.findall(r'<color rgb="(\w+)"', three) == ["FFFF0000", "FFFFFF00", "FF00FF00"]
+        assert re.findall(r'<color rgb="(\w+)"', two) == ["FF000000", "FFFFFFFF"]
+        assert 'borderColor rgb="FF445566"' in xml
+        assert 'negativeFillColor rgb="FFAA0000"' in xml
+    finally:
+        out.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_umya_cell_protection_round_trip() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without the umya backend")
+
+    import openpyxl
+
+    tmp = Path(tempfile.mkdtemp())
+    out = tmp / "protect.xlsx"
+    try:
+        book = rust.UmyaBook()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "string", "value": "input"})
+        book.write_cell_format("S", "A1", {"locked": False})
+        book.write_cell_value("S", "B1", {"type": "formula", "formula": "=1+1"})
+        book.write_cell_format("S", "B1", {"bold": True, "hidden": True})
+        with pytest.raises(ValueError, match="locked"):
+            book.write_cell_format("S", "C1", {"locked": "no"})
+        book.save(str(out))
+
+        ws = openpyxl.load_workbook(out)["S"]
+        assert (ws["A1"].protection.locked, ws["A1"].protection.hidden) == (False, False)
+        assert (ws["B1"].protection.locked, ws["B1"].protection.hidden) == (True, True)
+
+        back = rust.UmyaBook.open(str(out))
+        assert back.read_cell_format("S", "A1") == {"locked": False, "hidden": False}
+        b1 = back.read_cell_format("S", "B1")
+        assert (b1["bold"], b1["locked"], b1["hidden"]) == (True, True, True)
+    finally:
+        out.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_xlsxwriter_cell_protection_flags() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without the rust_xlsxwriter backend")
+
+    import openpyxl
+
+    tmp = Path(tempfile.mkdtemp())
+    out = tmp / "protect.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "string", "value": "input"})
+        book.write_cell_format("S", "A1", {"locked": False})
+        book.write_cell_value("S", "B1", {"type": "formula", "formula": "=1+1"})
+        book.write_cell_format("S", "B1", {"bold": True, "hidden": True})
+        book.write_cell_value("S", "C1", {"type": "number", "value": 3})
+        book.write_cell_format("S", "C1", {"locked": True, "hidden": False})
+        book.save(str(out))
+
+        ws = openpyxl.load_workbook(out)["S"]
+        assert (ws["A1"].protection.locked, ws["A1"].protection.hidden) == (False, False)
+        assert (ws["B1"].protection.locked, ws["B1"].protection.hidden) == (True, True)
+        assert ws["B1"].font.bold is True
+        assert (ws["C1"].protection.locked, ws["C1"].protection.hidden) == (True, False)
+    finally:
+        out.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_calamine_styled_reads_cell_protection() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without the calamine backend")
+
+    import openpyxl
+    from openpyxl.styles import Font, Protection
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "protect.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws["A1"] = "input"
+        ws["A1"].protection = Protection(locked=False)
+        ws["B1"] = "=1+1"
+        ws["B1"].protection = Protection(locked=True, hidden=True)
+        ws["C1"] = 3
+        ws["C1"].font = Font(bold=True)
+        wb.save(path)
+
+        book = rust.CalamineStyledBook.open(str(path))
+        a1 = book.read_cell_format("S", "A1")
+        assert (a1["locked"], a1["hidden"]) == (False, False)
+        b1 = book.read_cell_format("S", "B1")
+        assert (b1["locked"], b1["hidden"]) == (True, True)
+        c1 = book.read_cell_format("S", "C1")
+        assert c1["bold"] is True
+        assert "locked" not in c1
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_new_cells_inherit_row_and_column_styles() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "wolfxl" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without the wolfxl backend")
+
+    import openpyxl
+    from openpyxl.styles import Font, PatternFill
+
+    tmp = Path(tempfile.mkdtemp())
+    src = tmp / "bands.xlsx"
+    out = tmp / "patched.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws["A1"] = "header"
+        ws.row_dimensions[1].font = Font(bold=True)
+        ws.column_dimensions["C"].fill = PatternFill("solid", fgColor="FFFF00")
+        ws["A5"] = 1
+        wb.save(src)
+
+        patcher = rust.XlsxPatcher.open(str(src))
+        patcher.queue_value("S", "B1", {"type": "string", "value": "new"})
+        patcher.queue_value("S", "C3", {"type": "number", "value": 3})
+        patcher.queue_value("S", "D3", {"type": "number", "value": 4})
+        patcher.save(str(out))
+
+        ws = openpyxl.load_workbook(out)["S"]
+        assert ws["B1"].value == "new"
+        assert ws["B1"].font.bold is True
+        assert ws["C3"].fill.fgColor.rgb == "00FFFF00"
+        assert ws["D3"].fill.fill_type is None
+    finally:
+        for p in (src, out):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_row_spans_recompute_and_strip() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"wolfxl", "rust_xlsxwriter"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without wolfxl/rust_xlsxwriter backends")
+    if getattr(rust.XlsxPatcher, "set_row_spans", None) is None:
+        pytest.skip("wolfxl._rust predates XlsxPatcher.set_row_spans")
+
+    import re
+    import zipfile
+
+    tmp = Path(tempfile.mkdtemp())
+    src = tmp / "spans.xlsx"
+    out = tmp / "patched.xlsx"
+    try:
+        book = rust.RustXlsxWriterBook()
+        book.add_sheet("S")
+        for a1 in ("A1", "B1", "A2"):
+            book.write_cell_value("S", a1, {"type": "number", "value": 1})
+        book.save(str(src))
+
+        def row_tags(path: Path) -> dict[str, str]:
+            with zipfile.ZipFile(path) as zf:
+                xml = zf.read("xl/worksheets/sheet1.xml").decode("utf-8")
+            return {m.group(1): m.group(0) for m in re.finditer(r'<row r="(\d+)"[^>]*>', xml)}
+
+        patcher = rust.XlsxPatcher.open(str(src))
+        patcher.queue_value("S", "D1", {"type": "number", "value": 4})
+        patcher.queue_value("S", "C5", {"type": "number", "value": 5})
+        patcher.save(str(out))
+        before, rows = row_tags(src), row_tags(out)
+        assert 'spans="1:4"' in rows["1"]
+        assert rows["2"] == before["2"]
+        assert 'spans="3:3"' in rows["5"]
+
+        patcher = rust.XlsxPatcher.open(str(src))
+        with pytest.raises(ValueError, match="row spans mode"):
+            patcher.set_row_spans("auto")
+        patcher.set_row_spans("strip")
+        patcher.queue_value("S", "D1", {"type": "number", "value": 4})
+        patcher.save(str(out))
+        rows = row_tags(out)
+        assert "spans" not in rows["1"]
+        assert rows["2"] == before["2"]
+    finally:
+        for p in (src, out):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_load_backend_registers_lazily() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(rust, "load_backend", None) is None:
+        pytest.skip("wolfxl._rust predates load_backend")
+    enabled = _enabled_backends(rust)
+    if "umya-spreadsheet" not in enabled:
+        pytest.skip("wolfxl._rust compiled without the umya backend")
+
+    import subprocess
+    import sys
+
+    # A fresh interpreter: the class is listed before and found on lookup.
+    script = (
+        "import wolfxl._rust as r\n"
+        "assert 'UmyaBook' in dir(r)\n"
+        "assert r.UmyaBook.__name__ == 'UmyaBook'\n"
+        "assert 'UmyaBook' in vars(r)\n"
+    )
+    subprocess.run([sys.executable, "-c", script], check=True)
+
+    names = rust.load_backend("umya-spreadsheet")
+    assert "UmyaBook" in names
+    assert rust.load_backend("umya-spreadsheet") == names
+    assert rust.UmyaBook is vars(rust)["UmyaBook"]
+    with pytest.raises(ValueError, match="Unknown backend 'nope'"):
+        rust.load_backend("nope")
+    with pytest.raises(AttributeError):
+        rust.NoSuchClass  # noqa: B018
+
+
+def test_rust_xlsxwriter_vba_project_xlsm() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without the rust_xlsxwriter backend")
+    if not hasattr(rust.RustXlsxWriterBook, "add_vba_project"):
+        pytest.skip("wolfxl._rust predates add_vba_project")
+
+    import zipfile
+
+    tmp = Path(tempfile.mkdtemp())
+    vba = tmp / "vbaProject.bin"
+    out = tmp / "macros.xlsm"
+    try:
+        vba.write_bytes(b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1" + b"\x00" * 504)
+
+        book = rust.RustXlsxWriterBook()
+        with pytest.raises(OSError):
+            book.add_vba_project(str(tmp / "missing.bin"))
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "string", "value": "macro"})
+        book.add_vba_project(str(vba))
+        book.set_vba_name("MyBook")
+        book.set_vba_name("MySheet", "S")
+        with pytest.raises(ValueError):
+            book.set_vba_name("Other", "Missing")
+        with pytest.raises(ValueError):
+            book.save(str(tmp / "macros.xlsx"))
+        book.save(str(out))
+
+        with zipfile.ZipFile(out) as zf:
+            assert zf.read("xl/vbaProject.bin") == vba.read_bytes()
+            content_types = zf.read("[Content_Types].xml").decode()
+            workbook = zf.read("xl/workbook.xml").decode()
+            sheet = zf.read("xl/worksheets/sheet1.xml").decode()
+        assert "macroEnabled" in content_types
+        assert 'codeName="MyBook"' in workbook
+        assert 'codeName="MySheet"' in sheet
+    finally:
+        for p in (vba, out, tmp / "macros.xlsx"):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_umya_read_named_ranges_reports_builtin_names() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without the umya-spreadsheet backend")
+
+    import openpyxl
+    from openpyxl.workbook.defined_name import DefinedName
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "names.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws_a = wb.active
+        ws_a.title = "A"
+        ws_b = wb.create_sheet("B")
+        for ws in (ws_a, ws_b):
+            ws.append(["h1", "h2"])
+            ws.append([1, 2])
+        ws_a.auto_filter.ref = "A1:B2"
+        ws_b.print_area = "A1:B2"
+        ws_b.print_title_rows = "1:1"
+        wb.defined_names["Total"] = DefinedName("Total", attr_text="B!$B$2")
+        wb.save(path)
+
+        book = rust.UmyaBook.open(str(path))
+        names_a = {nr["name"]: nr for nr in book.read_named_ranges("A")}
+        names_b = {nr["name"]: nr for nr in book.read_named_ranges("B")}
+
+        assert names_b["Total"]["builtin"] is False
+        assert names_b["Total"]["scope"] == "workbook"
+        area = names_b["_xlnm.Print_Area"]
+        assert (area["builtin"], area["scope"], area["sheet"]) == (True, "sheet", "B")
+        assert area["refers_to"] == "B!A1:B2"
+        assert names_b["_xlnm.Print_Titles"]["builtin"] is True
+        assert "_xlnm._FilterDatabase" not in names_b
+
+        assert "_xlnm.Print_Area" not in names_a
+        flt = names_a["_xlnm._FilterDatabase"]
+        assert (flt["builtin"], flt["sheet"], flt["refers_to"]) == (True, "A", "A!A1:B2")
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_defined_names_and_tables() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without the calamine backend")
+    if not hasattr(rust.CalamineBook, "read_tables"):
+        pytest.skip("wolfxl._rust predates CalamineBook.read_tables")
+
+    import openpyxl
+    from openpyxl.workbook.defined_name import DefinedName
+    from openpyxl.worksheet.table import Table, TableStyleInfo
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "tables.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "Data"
+        other = wb.create_sheet("Other")
+        for row in (["Region", "Q1", "Q2"], ["East", 1, 2], ["West", 3, 4]):
+            ws.append(row)
+        ws["E1"] = "=SUM(Sales[Q1])"
+        table = Table(displayName="Sales", ref="A1:C3")
+        table.tableStyleInfo = TableStyleInfo(name="TableStyleMedium9", showRowStripes=True)
+        ws.add_table(table)
+        ws.print_area = "A1:C3"
+        wb.defined_names["Rate"] = DefinedName("Rate", attr_text="Data!$B$2")
+        other.defined_names["Local"] = DefinedName("Local", attr_text="Other!$A$1")
+        wb.save(path)
+
+        book = rust.CalamineBook.open(str(path))
+        names = {nr["name"]: nr for nr in book.read_defined_names()}
+        assert set(names) == {"Rate", "Local", "_xlnm.Print_Area"}
+        assert names["Rate"] == {
+            "name": "Rate",
+            "scope": "workbook",
+            "sheet": None,
+            "refers_to": "Data!$B$2",
+            "builtin": False,
+        }
+        assert (names["Local"]["scope"], names["Local"]["sheet"]) == ("sheet", "Other")
+        area = names["_xlnm.Print_Area"]
+        assert (area["builtin"], area["scope"], area["sheet"]) == (True, "sheet", "Data")
+
+        tables = book.read_tables("Data")
+        assert len(tables) == 1
+        t = tables[0]
+        assert (t["name"], t["display_name"], t["ref"]) == ("Sales", "Sales", "A1:C3")
+        assert (t["header_row"], t["totals_row"]) == (True, False)
+        assert t["columns"] == ["Region", "Q1", "Q2"]
+        assert t["style"] == "TableStyleMedium9"
+        assert book.read_tables("Other") == []
+        with pytest.raises(ValueError):
+            book.read_tables("Missing")
+        book.close()
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_resolve_structured_ref() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not hasattr(rust, "resolve_structured_ref"):
+        pytest.skip("wolfxl._rust predates resolve_structured_ref")
+
+    import openpyxl
+    from openpyxl.worksheet.table import Table
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "structured.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "Data"
+        for row in (["Region", "Q1", "Q2"], ["East", 1, 2], ["West", 3, 4]):
+            ws.append(row)
+        ws["D2"] = "=Sales[[#This Row],[Q1]]*2"
+        ws.add_table(Table(displayName="Sales", ref="A1:C3"))
+        wb.save(path)
+
+        resolve = rust.resolve_structured_ref
+        assert resolve(str(path), "Sales[Q1]") == "Data!B2:B3"
+        assert resolve(str(path), "Sales[#All]") == "Data!A1:C3"
+        assert resolve(str(path), "Sales[[#Headers],[Region]:[Q2]]") == "Data!A1:C1"
+        assert resolve(str(path), "Sales[[#This Row],[Q1]]", "Data!D2") == "Data!B2"
+        assert resolve(str(path), "[@Q2]", "Data!B3") == "Data!C3"
+        for bad in ("Sales[Q9]", "Nope[Q1]", "Sales[#Totals]", "Sales[@Q1]"):
+            with pytest.raises(ValueError):
+                resolve(str(path), bad)
+        with pytest.raises(OSError):
+            resolve(str(tmp / "missing.xlsx"), "Sales[Q1]")
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_wolfxl_repoint_pivot_source() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "wolfxl" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without wolfxl backend")
+    if getattr(rust.XlsxPatcher, "repoint_pivot_source", None) is None:
+        pytest.skip("wolfxl._rust predates XlsxPatcher.repoint_pivot_source")
+
+    import zipfile
+
+    import openpyxl
+
+    rel_ns = "http://schemas.openxmlformats.org/package/2006/relationships"
+    rel_type = "http://schemas.openxmlformats.org/officeDocument/2006/relationships"
+    main_ns = "http://schemas.openxmlformats.org/spreadsheetml/2006/main"
+    cache_part = "xl/pivotCache/pivotCacheDefinition1.xml"
+    parts = {
+        "xl/worksheets/_rels/sheet2.xml.rels": (
+            f'<Relationships xmlns="{rel_ns}"><Relationship Id="rId1" '
+            f'Type="{rel_type}/pivotTable" Target="../pivotTables/pivotTable1.xml"/>'
+            "</Relationships>"
+        ),
+        "xl/pivotTables/pivotTable1.xml": (
+            f'<pivotTableDefinition xmlns="{main_ns}" name="Summary" cacheId="1"/>'
+        ),
+        "xl/pivotTables/_rels/pivotTable1.xml.rels": (
+            f'<Relationships xmlns="{rel_ns}"><Relationship Id="rId1" '
+            f'Type="{rel_type}/pivotCacheDefinition" '
+            'Target="../pivotCache/pivotCacheDefinition1.xml"/></Relationships>'
+        ),
+        cache_part: (
+            f'<pivotCacheDefinition xmlns="{main_ns}" recordCount="3">'
+            '<cacheSource type="worksheet"><worksheetSource ref="A1:B4" sheet="Data"/>'
+            "</cacheSource></pivotCacheDefinition>"
+        ),
+    }
+
+    tmp = Path(tempfile.mkdtemp())
+    plain = tmp / "plain.xlsx"
+    src = tmp / "pivot.xlsx"
+    out = tmp / "patched.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        data = wb.active
+        data.title = "Data"
+        for row in (("Region", "Sales"), ("N", 1), ("S", 2), ("E", 3), ("W", 4)):
+            data.append(row)
+        wb.create_sheet("Report")
+        wb.save(plain)
+        with zipfile.ZipFile(plain) as zin, zipfile.ZipFile(src, "w") as zout:
+            for item in zin.infolist():
+                if item.filename not in parts:
+                    zout.writestr(item, zin.read(item.filename))
+            for name, xml in parts.items():
+                zout.writestr(name, xml)
+
+        patcher = rust.XlsxPatcher.open(str(src))
+        with pytest.raises(ValueError, match="No pivot table named"):
+            patcher.repoint_pivot_source("Missing", "A1:B5")
+        with pytest.raises(ValueError, match="out of range"):
+            patcher.repoint_pivot_source(1, "A1:B5")
+        patcher.repoint_pivot_source("summary", "$A$1:$B$5")
+        patcher.save(str(out))
+
+        with zipfile.ZipFile(out) as zf:
+            xml = zf.read(cache_part).decode("utf-8")
+        assert '<worksheetSource ref="A1:B5" sheet="Data"/>' in xml
+        assert 'refreshOnLoad="1"' in xml
+
+        patcher = rust.XlsxPatcher.open(str(src))
+        patcher.repoint_pivot_source(0, "Report!C1:D9")
+        patcher.save(str(out))
+        with zipfile.ZipFile(out) as zf:
+            xml = zf.read(cache_part).decode("utf-8")
+        assert '<worksheetSource ref="C1:D9" sheet="Report"/>' in xml
+    finally:
+        for p in (plain, src, out):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_umya_cell_payloads_round_trip() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without umya backend")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    payloads = {
+        "A1": {"type": "string", "value": "text"},
+        "A2": {"type": "number", "value": 1.5},
+        "A3": {"type": "boolean", "value": True},
+        "A4": {"type": "date", "value": "2024-06-15"},
+        "A5": {"type": "error", "value": "#DIV/0!"},
+        "A6": {"type": "error", "value": "#REF!"},
+        "B1": {"type": "formula", "value": "=A2*2", "result": 3},
+        "B2": {"type": "formula", "value": "=A3", "result": True},
+        "B3": {"type": "formula", "value": '=A1&"!"', "result": "text!"},
+        "B4": {"type": "formula", "value": "=A2+1"},
+    }
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "payloads.xlsx"
+    try:
+        book = rust.UmyaBook()
+        book.add_sheet("S")
+        for a1, payload in payloads.items():
+            book.write_cell_value("S", a1, payload)
+        book.save(str(path))
+
+        reopened = rust.UmyaBook.open(str(path))
+        for a1 in ("A1", "A2", "A3", "A4", "A5", "A6"):
+            assert reopened.read_cell_value("S", a1) == payloads[a1], a1
+        for a1 in ("B1", "B2", "B3", "B4"):
+            formula = payloads[a1]["value"]
+            expected = {"type": "formula", "value": formula, "formula": formula}
+            assert reopened.read_cell_value("S", a1) == expected, a1
+        assert reopened.read_cell_value("S", "C1") == {"type": "blank"}
+
+        # The cached results are what a non-recalculating reader sees.
+        ws = openpyxl.load_workbook(path, data_only=True)["S"]
+        assert (ws["B1"].value, ws["B2"].value, ws["B3"].value) == (3, True, "text!")
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_umya_data_validation_operator_as_written() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without umya backend")
+    openpyxl = pytest.importorskip("openpyxl")
+    from openpyxl.worksheet.datavalidation import DataValidation
+
+    from excelbench.harness.adapters.umya_adapter import UmyaAdapter
+
+    tmp = Path(tempfile.mkdtemp())
+    src, out = tmp / "dv.xlsx", tmp / "resaved.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        rules = [
+            ("A1", DataValidation(type="whole", formula1="5")),
+            ("B1", DataValidation(type="whole", operator="lessThan", formula1="1", formula2="9")),
+            ("C1", DataValidation(type="list", formula1='"a,b"')),
+            ("D1", DataValidation(type="decimal", formula1="1", formula2="9")),
+        ]
+        for cell, dv in rules:
+            dv.add(cell)
+            ws.add_data_validation(dv)
+        wb.save(src)
+
+        def operators(book: Any) -> dict[str, Any]:
+            return {d["range"]: d["operator"] for d in book.read_data_validations("S")}
+
+        expected = {"A1": None, "B1": "lessThan", "C1": None, "D1": None}
+        book = rust.UmyaBook.open(str(src))
+        assert operators(book) == expected
+        # The adapter applies the same "between" default as OpenpyxlAdapter.
+        adapted = UmyaAdapter().read_data_validations(book, "S")
+        assert {d["range"]: d["operator"] for d in adapted}["D1"] == "between"
+
+        book.add_data_validation("S", {"range": "E1", "validation_type": "whole", "formula1": "3"})
+        book.add_data_validation(
+            "S",
+            {
+                "range": "F1",
+                "validation_type": "whole",
+                "operator": "lessThan",
+                "formula1": "1",
+                "formula2": "9",
+            },
+        )
+        expected.update({"E1": None, "F1": "lessThan"})
+        assert operators(book) == expected
+        book.save(str(out))
+        assert operators(rust.UmyaBook.open(str(out))) == expected
+    finally:
+        for p in (src, out):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_formula_payload_carries_cached_value() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not {"calamine", "umya-spreadsheet"} <= _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine/umya backends")
+
+    from excelbench.harness.adapters.rust_calamine_adapter import RustCalamineAdapter
+    from excelbench.models import CellType
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "formulas.xlsx"
+    try:
+        book = rust.UmyaBook()
+        book.add_sheet("S")
+        book.write_cell_value("S", "A1", {"type": "number", "value": 2})
+        book.write_cell_value("S", "B1", {"type": "formula", "value": "=A1*2", "result": 4})
+        book.save(str(path))
+
+        reader = rust.CalamineBook.open(str(path))
+        expected = {
+            "type": "formula",
+            "value": 4.0,
+            "formula": "=A1*2",
+            "value_type": "number",
+            "cached": True,
+        }
+        assert reader.read_cell_value("S", "B1") == expected
+        assert reader.read_cell_formula("S", "B1") == expected
+        assert reader.read_cell_formula("S", "A1") is None
+
+        adapter = RustCalamineAdapter()
+        value = adapter.read_cell_value(adapter.open_workbook(path), "S", "B1")
+        assert value.type == CellType.FORMULA
+        assert (value.formula, value.value) == ("=A1*2", 4.0)
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_caches_ranges_until_invalidate() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    if getattr(rust.CalamineBook, "invalidate", None) is None:
+        pytest.skip("wolfxl._rust predates CalamineBook.invalidate")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "cache.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws.append([1, 2])
+        ws.append(["=A1+B1", "x"])
+        wb.save(path)
+
+        book = rust.CalamineBook.open(str(path))
+        rust.enable_profiling(True)
+        rust.get_profile(reset=True)
+
+        def parses() -> int:
+            return int(rust.get_profile().get("calamine.parse", {}).get("calls", 0))
+
+        # Values and formulas of a sheet are parsed once, then served from the cache.
+        assert book.read_cell_value("S", "A1") == {"type": "number", "value": 1.0}
+        assert book.read_cell_formula("S", "A2")["formula"] == "=A1+B1"
+        assert len(book.read_sheet("S")) == 2
+        assert parses() == 1
+
+        book.invalidate("S")
+        assert book.read_cell_value("S", "B2") == {"type": "string", "value": "x"}
+        assert parses() == 2
+        book.invalidate()
+        assert book.read_range("S", "A1:B1")[0][1] == {"type": "number", "value": 2.0}
+        assert parses() == 3
+    finally:
+        rust.enable_profiling(False)
+        rust.get_profile(reset=True)
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_iter_rows_streams_batches() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    if getattr(rust.CalamineBook, "iter_rows", None) is None:
+        pytest.skip("wolfxl._rust predates CalamineBook.iter_rows")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    import os
+    from time import monotonic, sleep
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "stream.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws.append([1, "h"])
+        for i in range(2, 3001):
+            ws.append([i])
+        ws["B3005"] = "end"
+        wb.save(path)
+        book = rust.CalamineBook.open(str(path))
+
+        rows = list(book.iter_rows("S"))
+        assert len(rows) == 3005
+        assert rows[0] == [{"type": "number", "value": 1.0}, {"type": "string", "value": "h"}]
+        # Rows 256/257 straddle the first batch the decoder hands over.
+        assert [rows[i][0]["value"] for i in (254, 255, 256, 257)] == [255, 256, 257, 258]
+        assert rows[3000:3004] == [[], [], [], []]
+        assert rows[3004] == [{"type": "blank"}, {"type": "string", "value": "end"}]
+
+        # Dropping the iterator early stops the decoder even though it is blocked
+        # on a full channel.
+        tasks = Path("/proc/self/task")
+        baseline = len(os.listdir(tasks)) if tasks.exists() else None
+        it = book.iter_rows("S")
+        assert next(it)[0]["value"] == 1.0
+        it.close()
+        with pytest.raises(StopIteration):
+            next(it)
+        del it
+        if baseline is not None:
+            deadline = monotonic() + 10
+            while len(os.listdir(tasks)) > baseline and monotonic() < deadline:
+                sleep(0.05)
+            assert len(os.listdir(tasks)) <= baseline
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_arrow_and_numpy_exports() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    calamine = getattr(rust, "CalamineBook", None)
+    if getattr(calamine, "read_sheet_arrow", None) is None:
+        pytest.skip("wolfxl._rust compiled without the arrow feature")
+    pytest.importorskip("pyarrow")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "arrow.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws.append(["title"])
+        ws.append(["id", "score"])
+        ws.append([1, 1.5])
+        ws.append([2, None])
+        wb.save(path)
+        book = calamine.open(str(path))
+
+        # header_row is 0-based and defaults to the first row, as in read_sheet_columns.
+        batch = book.read_sheet_arrow("S")
+        assert batch.schema.names == ["title", "B"]
+        batch = book.read_sheet_arrow("S", header_row=1)
+        assert batch.schema.names == ["id", "score"]
+        assert batch.to_pydict() == {"id": [1.0, 2.0], "score": [1.5, None]}
+        assert book.read_sheet_arrow("S", header_row=None).num_rows == 4
+
+        if getattr(calamine, "read_sheet_numpy", None) is not None:
+            arr = book.read_sheet_numpy("S", "A3:B4")
+            assert arr.shape == (2, 2)
+            assert arr[0].tolist() == [1.0, 1.5]
+            assert arr[1, 0] == 2.0 and arr[1, 1] != arr[1, 1]
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def _write_minimal_ods(path: Path, sheet: str, rows_xml: str) -> None:
+    """Write a one-sheet ODS package whose table rows are `rows_xml`."""
+    ns = {
+        "office": "urn:oasis:names:tc:opendocument:xmlns:office:1.0",
+        "table": "urn:oasis:names:tc:opendocument:xmlns:table:1.0",
+        "text": "urn:oasis:names:tc:opendocument:xmlns:text:1.0",
+        "of": "urn:oasis:names:tc:opendocument:xmlns:of:1.2",
+    }
+    xmlns = " ".join(f'xmlns:{prefix}="{uri}"' for prefix, uri in ns.items())
+    content = (
+        f'<?xml version="1.0" encoding="UTF-8"?><office:document-content {xmlns} '
+        'office:version="1.2"><office:body><office:spreadsheet>'
+        f'<table:table table:name="{sheet}">{rows_xml}</table:table>'
+        "</office:spreadsheet></office:body></office:document-content>"
+    )
+    manifest = (
+        '<?xml version="1.0" encoding="UTF-8"?><manifest:manifest '
+        'xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" '
+        'manifest:version="1.2"><manifest:file-entry manifest:full-path="/" '
+        'manifest:media-type="application/vnd.oasis.opendocument.spreadsheet"/>'
+        '<manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>'
+        "</manifest:manifest>"
+    )
+    with zipfile.ZipFile(path, "w") as zf:
+        zf.writestr(
+            "mimetype",
+            "application/vnd.oasis.opendocument.spreadsheet",
+            compress_type=zipfile.ZIP_STORED,
+        )
+        zf.writestr("content.xml", content)
+        zf.writestr("META-INF/manifest.xml", manifest)
+
+
+def test_rust_calamine_detects_format_from_content() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    if getattr(rust.CalamineBook, "format", None) is None:
+        pytest.skip("wolfxl._rust predates CalamineBook.format")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    import shutil
+
+    xls_fixture = Path(__file__).parent.parent / "fixtures/excel_xls/tier1/09_multiple_sheets.xls"
+    tmp = Path(tempfile.mkdtemp())
+    xls_as_xlsx = tmp / "legacy.xlsx"
+    xlsx_as_bin = tmp / "modern.bin"
+    ods_as_xls = tmp / "open.xls"
+    junk = tmp / "junk.xlsx"
+    try:
+        shutil.copy(xls_fixture, xls_as_xlsx)
+        book = rust.CalamineBook.open(str(xls_as_xlsx))
+        assert book.format() == "xls"
+        assert book.sheet_names() == rust.CalamineBook.open(str(xls_fixture)).sheet_names()
+
+        wb = openpyxl.Workbook()
+        wb.active.title = "S"
+        wb.active["A1"] = 5
+        wb.save(xlsx_as_bin)
+        book = rust.CalamineBook.open(str(xlsx_as_bin))
+        assert book.format() == "xlsx"
+        assert book.read_cell_value("S", "A1") == {"type": "number", "value": 5.0}
+
+        _write_minimal_ods(
+            ods_as_xls,
+            "Data",
+            '<table:table-row><table:table-cell office:value-type="float" office:value="7">'
+            "<text:p>7</text:p></table:table-cell></table:table-row>",
+        )
+        book = rust.CalamineBook.open(str(ods_as_xls))
+        assert book.format() == "ods"
+        assert book.read_cell_value("Data", "A1") == {"type": "number", "value": 7.0}
+
+        junk.write_bytes(b"not a workbook at all")
+        with pytest.raises(rust.FileFormatError):
+            rust.CalamineBook.open(str(junk))
+    finally:
+        for p in (xls_as_xlsx, xlsx_as_bin, ods_as_xls, junk):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_ods_formulas_use_excel_syntax() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+
+    from excelbench.harness.adapters.rust_calamine_adapter import RustCalamineAdapter
+
+    def cell(value: str, formula: str | None = None, kind: str = "float") -> str:
+        attrs = f' table:formula="{formula}"' if formula else ""
+        if kind == "float":
+            attrs += f' office:value-type="float" office:value="{value}"'
+        else:
+            attrs += ' office:value-type="string"'
+        return f"<table:table-cell{attrs}><text:p>{value}</text:p></table:table-cell>"
+
+    rows = [
+        cell("1"),
+        cell("2"),
+        cell("5", "of:=SUM([.A1:.A2];2)"),
+        cell("x;", "of:=[$Other.B1]&amp;&quot;;&quot;", kind="string"),
+    ]
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "formulas.ods"
+    try:
+        _write_minimal_ods(
+            path, "S", "".join(f"<table:table-row>{c}</table:table-row>" for c in rows)
+        )
+        assert ".ods" in RustCalamineAdapter().supported_read_extensions
+
+        book = rust.CalamineBook.open(str(path))
+        assert book.read_cell_formula("S", "A3") == {
+            "type": "formula",
+            "value": 5.0,
+            "formula": "=SUM(A1:A2,2)",
+            "value_type": "number",
+            "cached": True,
+        }
+        # Separators inside string literals are left alone.
+        assert book.read_cell_value("S", "A4")["formula"] == '=Other!B1&";"'
+        assert book.read_cell_value("S", "A4")["value"] == "x;"
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_styled_reports_builtin_names() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    if not hasattr(rust.CalamineStyledBook, "read_defined_names"):
+        pytest.skip("wolfxl._rust predates CalamineStyledBook.read_defined_names")
+
+    import openpyxl
+    from openpyxl.workbook.defined_name import DefinedName
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "names.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws_a = wb.active
+        ws_a.title = "A"
+        ws_b = wb.create_sheet("B")
+        for ws in (ws_a, ws_b):
+            ws.append(["h1", "h2"])
+            ws.append([1, 2])
+        ws_b.print_area = "A1:B2"
+        wb.defined_names["Total"] = DefinedName("Total", attr_text="B!$B$2")
+        wb.save(path)
+
+        book = rust.CalamineStyledBook.open(str(path))
+        names = {nr["name"]: nr for nr in book.read_defined_names()}
+        assert set(names) == {"Total", "_xlnm.Print_Area"}
+        assert (names["Total"]["builtin"], names["Total"]["sheet"]) == (False, None)
+        area = names["_xlnm.Print_Area"]
+        assert (area["builtin"], area["scope"], area["sheet"]) == (True, "sheet", "B")
+
+        names_b = {nr["name"]: nr for nr in book.read_named_ranges("B")}
+        assert names_b["_xlnm.Print_Area"]["builtin"] is True
+        assert names_b["Total"]["builtin"] is False
+        names_a = {nr["name"] for nr in book.read_named_ranges("A")}
+        assert names_a == {"Total"}
+
+        if "umya-spreadsheet" in _enabled_backends(rust):
+            umya = rust.UmyaBook.open(str(path))
+            flags = {nr["name"]: nr["builtin"] for nr in umya.read_named_ranges("B")}
+            assert flags == {n: nr["builtin"] for n, nr in names_b.items()}
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_sheet_info_uses_dimension_ref() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    src = tmp / "src.xlsx"
+    path = tmp / "info.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "Ref"
+        ws["B2"] = 1
+        ws["D5"] = "x"
+        other = wb.create_sheet("NoRef")
+        other["A1"] = 1
+        other["C3"] = 2
+        wb.save(src)
+
+        # Drop the <dimension> of the second sheet to force the fallback.
+        with zipfile.ZipFile(src) as zin, zipfile.ZipFile(path, "w") as zout:
+            for item in zin.infolist():
+                data = zin.read(item.filename)
+                if item.filename == "xl/worksheets/sheet2.xml":
+                    data = re.sub(rb"<dimension [^>]*/>", b"", data)
+                zout.writestr(item, data)
+
+        book = rust.CalamineBook.open(str(path))
+        rust.enable_profiling(True)
+        rust.get_profile(reset=True)
+        info = {d["name"]: d for d in book.sheet_info()}
+        assert (info["Ref"]["dimensions"], info["Ref"]["rows"], info["Ref"]["cols"]) == (
+            "B2:D5",
+            4,
+            3,
+        )
+        assert (info["NoRef"]["dimensions"], info["NoRef"]["rows"]) == ("A1:C3", 3)
+        # Only the sheet without a dimension ref was parsed.
+        assert rust.get_profile().get("calamine.parse", {}).get("calls") == 1
+    finally:
+        rust.enable_profiling(False)
+        rust.get_profile(reset=True)
+        for p in (src, path):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_probe_catalogs_each_format() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    xls = Path(__file__).parent.parent / "fixtures/excel_xls/tier1/09_multiple_sheets.xls"
+    tmp = Path(tempfile.mkdtemp())
+    xlsx = tmp / "probe.xlsx"
+    ods = tmp / "probe.ods"
+    try:
+        wb = openpyxl.Workbook()
+        wb.active.title = "First"
+        wb.active.append(["a"])
+        wb.active["C4"] = 1
+        wb.create_sheet("Second")["A1"] = 2
+        wb.save(xlsx)
+        _write_minimal_ods(ods, "S", "<table:table-row><table:table-cell/></table:table-row>")
+
+        info = rust.CalamineBook.probe(str(xlsx))
+        assert info["format"] == "xlsx"
+        assert info["sheet_names"] == ["First", "Second"]
+        assert [s["dimensions"] for s in info["sheets"]] == ["A1:C4", "A1:A1"]
+        assert info["date1904"] is False
+
+        for path, fmt, date1904 in ((xls, "xls", False), (ods, "ods", None)):
+            info = rust.CalamineBook.probe(str(path))
+            assert info["format"] == fmt
+            assert info["sheet_names"] == rust.CalamineBook.open(str(path)).sheet_names()
+            assert all(s["dimensions"] is None for s in info["sheets"])
+            assert info["date1904"] is date1904
+    finally:
+        for p in (xlsx, ods):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_styled_read_formats_range_matches_cells() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    if not hasattr(rust.CalamineStyledBook, "read_formats_range"):
+        pytest.skip("wolfxl._rust predates CalamineStyledBook.read_formats_range")
+    openpyxl = pytest.importorskip("openpyxl")
+    from openpyxl.styles import Font, PatternFill
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "formats.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws["A1"] = "bold"
+        ws["A1"].font = Font(bold=True)
+        ws["B1"] = 1.5
+        ws["B1"].number_format = "0.00%"
+        ws["A2"] = "fill"
+        ws["A2"].fill = PatternFill("solid", fgColor="FFFF00")
+        ws["B3"] = "plain"
+        wb.save(path)
+
+        book = rust.CalamineStyledBook.open(str(path))
+        grid = book.read_formats_range("S", "$b$3:A1")
+        assert [len(row) for row in grid] == [2, 2, 2]
+        for r, row in enumerate(grid, start=1):
+            for c, fmt in enumerate(row):
+                assert fmt == book.read_cell_format("S", f"{'AB'[c]}{r}")
+        assert grid[0][0]["bold"] is True
+        assert grid[0][1]["number_format"] == "0.00%"
+        assert grid[1][0]["bg_color"] == "#FFFF00"
+
+        assert book.read_formats_range("S", "B1") == [[grid[0][1]]]
+        with pytest.raises(ValueError):
+            book.read_formats_range("S", "A1:??")
+        with pytest.raises(ValueError):
+            book.read_formats_range("Missing", "A1:B2")
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_read_sheet_columns_infers_dtypes() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    if not hasattr(rust.CalamineBook, "read_sheet_columns"):
+        pytest.skip("wolfxl._rust predates CalamineBook.read_sheet_columns")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "columns.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws.append(["score", "name", "flag", "day", "ts", "blank", "score"])
+        ws.append([1, "a", True, date(2024, 1, 2), datetime(2024, 1, 2, 3, 4, 5), None, 1])
+        ws.append([2.5, 3, None, date(2024, 2, 3), date(2024, 2, 3), None, 2])
+        wb.save(path)
+
+        book = rust.CalamineBook.open(str(path))
+        out = book.read_sheet_columns("S")
+        assert out["dtypes"] == {
+            "score": "float",
+            "name": "string",
+            "flag": "bool",
+            "day": "date",
+            "ts": "datetime",
+            "blank": "empty",
+            "score.1": "float",
+        }
+        cols = out["columns"]
+        assert cols["score"] == [1.0, 2.5]
+        assert cols["name"] == ["a", "3"]
+        assert cols["flag"] == [True, None]
+        assert cols["day"] == [date(2024, 1, 2), date(2024, 2, 3)]
+        assert cols["ts"] == [datetime(2024, 1, 2, 3, 4, 5), datetime(2024, 2, 3)]
+        assert cols["blank"] == [None, None]
+
+        by_letter = book.read_sheet_columns("S", header_row=None)
+        assert list(by_letter["columns"]) == ["A", "B", "C", "D", "E", "F", "G"]
+        assert by_letter["dtypes"]["A"] == "string"
+        assert len(by_letter["columns"]["A"]) == 3
+
+        if getattr(rust.CalamineBook, "read_sheet_arrow", None) is not None:
+            pytest.importorskip("pyarrow")
+            batch = book.read_sheet_arrow("S")
+            assert batch.schema.names == list(cols)
+            arrow = batch.to_pydict()
+            for name in ("score", "name", "flag", "ts", "blank", "score.1"):
+                assert arrow[name] == cols[name]
+            assert [d.date() for d in arrow["day"]] == cols["day"]
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_formula_cached_values_and_error_tokens() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    cells = [
+        '<c r="A1" t="e"><f>1/0</f><v>#DIV/0!</v></c>',
+        '<c r="A2"><f>SUM(1,2)</f><v>3</v></c>',
+        '<c r="A3" t="str"><f>"a"&amp;"b"</f><v>ab</v></c>',
+        '<c r="A4"><f>B1+1</f></c>',
+        '<c r="A5"><f>NA()</f></c>',
+        '<c r="A6" t="e"><f>WEBSERVICE("x")</f><v>#GETTING_DATA</v></c>',
+    ]
+    rows = "".join(f'<row r="{i}">{c}</row>' for i, c in enumerate(cells, start=1))
+    sheet_xml = (
+        '<?xml version="1.0" encoding="UTF-8"?>'
+        '<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">'
+        f"<sheetData>{rows}</sheetData></worksheet>"
+    )
+
+    tmp = Path(tempfile.mkdtemp())
+    src = tmp / "src.xlsx"
+    path = tmp / "cached.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        wb.active.title = "S"
+        wb.save(src)
+        with zipfile.ZipFile(src) as zin, zipfile.ZipFile(path, "w") as zout:
+            for item in zin.infolist():
+                data = zin.read(item.filename)
+                if item.filename == "xl/worksheets/sheet1.xml":
+                    data = sheet_xml.encode()
+                zout.writestr(item, data)
+
+        book = rust.CalamineBook.open(str(path))
+        assert book.read_cell_value("S", "A1") == {
+            "type": "error",
+            "value": "#DIV/0!",
+            "formula": "=1/0",
+            "cached": True,
+        }
+        assert book.read_cell_value("S", "A2") == {
+            "type": "formula",
+            "value": 3.0,
+            "formula": "=SUM(1,2)",
+            "value_type": "number",
+            "cached": True,
+        }
+        a3 = book.read_cell_value("S", "A3")
+        assert (a3["value"], a3["value_type"], a3["cached"]) == ("ab", "string", True)
+        # Without a cached result the formula text stands in for the value.
+        assert book.read_cell_value("S", "A4") == {
+            "type": "formula",
+            "value": "=B1+1",
+            "formula": "=B1+1",
+            "cached": False,
+        }
+        a5 = book.read_cell_value("S", "A5")
+        assert (a5["type"], a5["value"], a5["cached"]) == ("error", "#N/A", False)
+        a6 = book.read_cell_value("S", "A6")
+        assert (a6["type"], a6["value"]) == ("error", "#GETTING_DATA")
+        assert book.read_cell_formula("S", "A2")["value"] == 3.0
+    finally:
+        for p in (src, path):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_reads_1904_date_system() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    openpyxl = pytest.importorskip("openpyxl")
+    from openpyxl.utils.datetime import CALENDAR_MAC_1904
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "mac1904.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        wb.epoch = CALENDAR_MAC_1904
+        ws = wb.active
+        ws.title = "S"
+        ws["A1"] = date(2024, 3, 15)
+        ws["A2"] = datetime(2024, 3, 15, 13, 45, 30)
+        wb.save(path)
+        with zipfile.ZipFile(path) as zf:
+            assert 'date1904="1"' in zf.read("xl/workbook.xml").decode()
+
+        book = rust.CalamineBook.open(str(path))
+        assert book.date1904() is True
+        assert rust.CalamineBook.probe(str(path))["date1904"] is True
+        assert book.read_cell_value("S", "A1") == {"type": "date", "value": "2024-03-15"}
+        assert book.read_cell_value("S", "A2") == {
+            "type": "datetime",
+            "value": "2024-03-15T13:45:30",
+        }
+
+        raw = rust.CalamineBook.open(str(path), raw_dates=True)
+        serial = (date(2024, 3, 15) - date(1904, 1, 1)).days
+        assert raw.read_cell_value("S", "A1") == {"type": "number", "value": float(serial)}
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_reads_1904_date_system_xls() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    xlwt = pytest.importorskip("xlwt")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "mac1904.xls"
+    out = tmp / "mac1904.csv"
+    try:
+        wb = xlwt.Workbook()
+        wb.dates_1904 = True
+        ws = wb.add_sheet("S")
+        ws.write(0, 0, date(2024, 3, 15), xlwt.easyxf(num_format_str="yyyy-mm-dd"))
+        ws.write(
+            1,
+            0,
+            datetime(2024, 3, 15, 13, 45, 30),
+            xlwt.easyxf(num_format_str="yyyy-mm-dd hh:mm:ss"),
+        )
+        wb.save(str(path))
+
+        book = rust.CalamineBook.open(str(path))
+        assert book.format() == "xls"
+        assert book.date1904() is True
+        assert rust.CalamineBook.probe(str(path))["date1904"] is True
+        assert book.read_cell_value("S", "A1") == {"type": "date", "value": "2024-03-15"}
+        assert book.read_cell_value("S", "A2") == {
+            "type": "datetime",
+            "value": "2024-03-15T13:45:30",
+        }
+
+        book.export_csv("S", str(out))
+        assert out.read_text().splitlines() == ["2024-03-15", "2024-03-15 13:45:30"]
+    finally:
+        for p in (path, out):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_read_comments_legacy_and_threaded() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    if not hasattr(rust.CalamineBook, "read_comments"):
+        pytest.skip("wolfxl._rust predates CalamineBook.read_comments")
+    openpyxl = pytest.importorskip("openpyxl")
+    from openpyxl.comments import Comment
+
+    rel = "http://schemas.microsoft.com/office/2017/10/relationships"
+    threaded_xml = (
+        '<?xml version="1.0" encoding="UTF-8"?><ThreadedComments '
+        'xmlns="http://schemas.microsoft.com/office/spreadsheetml/2018/threadedcomments">'
+        '<threadedComment ref="B2" id="{1}" personId="{P1}"><text>Looks right</text>'
+        '</threadedComment><threadedComment ref="B2" id="{2}" personId="{P1}" '
+        'parentId="{1}"><text>Reply</text></threadedComment></ThreadedComments>'
+    )
+    persons_xml = (
+        '<?xml version="1.0" encoding="UTF-8"?><personList '
+        'xmlns="http://schemas.microsoft.com/office/spreadsheetml/2018/threadedcomments">'
+        '<person displayName="Bea" id="{P1}"/></personList>'
+    )
+
+    tmp = Path(tempfile.mkdtemp())
+    src = tmp / "src.xlsx"
+    path = tmp / "comments.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws["A1"].comment = Comment("note", "Ann")
+        ws["B2"].comment = Comment("[Threaded comment] Looks right", "tc={1}")
+        wb.create_sheet("Empty")
+        wb.save(src)
+
+        def add_rel(xml: bytes, rel_type: str, target: str) -> bytes:
+            entry = f'<Relationship Id="rIdT" Type="{rel}/{rel_type}" Target="{target}"/>'
+            return xml.replace(b"</Relationships>", entry.encode() + b"</Relationships>")
+
+        with zipfile.ZipFile(src) as zin, zipfile.ZipFile(path, "w") as zout:
+            for item in zin.infolist():
+                data = zin.read(item.filename)
+                if item.filename == "xl/worksheets/_rels/sheet1.xml.rels":
+                    data = add_rel(data, "threadedComment", "../threadedComments/tc1.xml")
+                elif item.filename == "xl/_rels/workbook.xml.rels":
+                    data = add_rel(data, "person", "persons/person.xml")
+                zout.writestr(item, data)
+            zout.writestr("xl/threadedComments/tc1.xml", threaded_xml)
+            zout.writestr("xl/persons/person.xml", persons_xml)
+
+        book = rust.CalamineBook.open(str(path))
+        assert book.read_comments("S") == [
+            {"cell": "A1", "text": "note", "author": "Ann", "threaded": False},
+            # The thread root replaces Excel's legacy placeholder; replies are dropped.
+            {"cell": "B2", "text": "Looks right", "author": "Bea", "threaded": True},
+        ]
+        assert book.read_comments("Empty") == []
+        with pytest.raises(ValueError):
+            book.read_comments("Missing")
+
+        xls = Path(__file__).parent.parent / "fixtures/excel_xls/tier1/09_multiple_sheets.xls"
+        xls_book = rust.CalamineBook.open(str(xls))
+        assert xls_book.read_comments(xls_book.sheet_names()[0]) == []
+    finally:
+        for p in (src, path):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+@pytest.mark.parametrize("scheme", ["standard", "agile"])
+def test_rust_calamine_opens_encrypted_workbooks(scheme: str) -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    if not hasattr(rust, "FileFormatError"):
+        pytest.skip("structured exceptions not available in this build")
+
+    # Generated by scripts/generate_encrypted_fixtures.py.
+    path = Path(__file__).parent.parent / "fixtures" / "encrypted" / f"{scheme}.xlsx"
+
+    # Without a password the OLE container must not be mistaken for an xls.
+    with pytest.raises(rust.FileFormatError, match="password is required") as info:
+        rust.CalamineBook.open(str(path))
+    assert info.value.path == str(path)
+
+    try:
+        book = rust.CalamineBook.open(str(path), password="excelbench")
+    except rust.UnsupportedFeature:
+        pytest.skip("wolfxl._rust compiled without the encryption feature")
+    assert book.format() == "xlsx"
+    assert book.sheet_names() == ["Secret"]
+    assert book.read_cell_value("Secret", "A1") == {"type": "string", "value": "classified"}
+    assert book.read_cell_value("Secret", "B1") == {"type": "number", "value": 42.0}
+
+    with pytest.raises(rust.FileFormatError, match="Incorrect password") as info:
+        rust.CalamineBook.open(str(path), password="wrong")
+    assert (info.value.backend, info.value.path) == ("calamine", str(path))
+
+
+def test_rust_calamine_rejects_rc4_encryption() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    if not hasattr(rust, "FileFormatError"):
+        pytest.skip("structured exceptions not available in this build")
+
+    # RC4 CryptoAPI shares Standard's version numbers; only fAES tells them apart.
+    path = Path(__file__).parent.parent / "fixtures" / "encrypted" / "rc4.xlsx"
+    with pytest.raises((rust.FileFormatError, rust.UnsupportedFeature)) as info:
+        rust.CalamineBook.open(str(path), password="excelbench")
+    if isinstance(info.value, rust.UnsupportedFeature):
+        pytest.skip("wolfxl._rust compiled without the encryption feature")
+    assert "Unsupported RC4 encryption" in str(info.value)
+
+
+def test_rust_calamine_read_all_sheets_parallel_only_for_xlsx() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    xls = Path(__file__).parent.parent / "fixtures/excel_xls/tier1/09_multiple_sheets.xls"
+    tmp = Path(tempfile.mkdtemp())
+    xlsx = tmp / "sheets.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        wb.active.title = "One"
+        wb.active.append([1, "a"])
+        wb.create_sheet("Two").append(["=1+1"])
+        wb.create_sheet("Three")
+        wb.save(xlsx)
+
+        def parses() -> int:
+            return int(rust.get_profile().get("calamine.parse", {}).get("calls", 0))
+
+        rust.enable_profiling(True)
+        for path, on_workers in ((xlsx, True), (xls, False)):
+            expected = rust.CalamineBook.open(str(path)).read_all_sheets()
+            book = rust.CalamineBook.open(str(path))
+            rust.get_profile(reset=True)
+            assert book.read_all_sheets(parallel=True) == expected
+            # xlsx sheets are parsed on rayon workers, whose counters are their
+            # own; other formats parse every sheet on the calling thread.
+            assert parses() == (0 if on_workers else len(expected))
+    finally:
+        rust.enable_profiling(False)
+        rust.get_profile(reset=True)
+        xlsx.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_read_failures_raise_file_format_error() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    if not hasattr(rust, "FileFormatError"):
+        pytest.skip("structured exceptions not available in this build")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    good = tmp / "good.xlsx"
+    bad_sheet = tmp / "bad_sheet.xlsx"
+    junk = tmp / "junk.xlsx"
+    try:
+        junk.write_bytes(b"not a zip")
+        with pytest.raises(rust.FileFormatError) as info:
+            rust.CalamineBook.open(str(junk), mmap=True)
+        assert info.value.path == str(junk)
+        with pytest.raises(rust.FileFormatError) as info:
+            rust.CalamineBook.probe(str(junk))
+        assert info.value.path == str(junk)
+
+        wb = openpyxl.Workbook()
+        wb.active.title = "S"
+        wb.active["A1"] = 1
+        wb.save(good)
+        # Mismatched end tag: the workbook opens, the sheet part does not parse.
+        with zipfile.ZipFile(good) as src, zipfile.ZipFile(bad_sheet, "w") as dst:
+            for item in src.infolist():
+                data = src.read(item.filename)
+                if item.filename == "xl/worksheets/sheet1.xml":
+                    data = data.replace(b"</row>", b"</rows>")
+                dst.writestr(item, data)
+
+        book = rust.CalamineBook.open(str(bad_sheet))
+        for read in (
+            lambda: book.read_sheet("S"),
+            lambda: book.sheet_stats("S"),
+            lambda: list(book.iter_rows("S")),
+        ):
+            with pytest.raises(rust.FileFormatError) as info:
+                read()
+            assert info.value.path == str(bad_sheet)
+            assert info.value.backend == "calamine"
+    finally:
+        for p in (good, bad_sheet, junk):
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_calamine_read_window_clips_to_used_range() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "grid.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        for r in range(1, 5):
+            for c in range(1, 6):
+                ws.cell(row=r, column=c, value=r * 10 + c)
+        ws["B2"] = "=A1+1"
+        wb.save(path)
+
+        book = rust.CalamineBook.open(str(path))
+        # 0-based, end-exclusive: rows 2-3, columns C:D.
+        window = book.read_window("S", rows=(1, 3), cols=(2, 4))
+        assert window == book.read_range("S", "C2:D3")
+        assert [[c["value"] for c in row] for row in window] == [[23, 24], [33, 34]]
+        # Formula cells carry the same payload as read_range.
+        assert book.read_window("S", rows=(1, 2), cols=(1, 2)) == book.read_range("S", "B2:B2")
+
+        assert book.read_window("S") == book.read_range("S", "A1:E4")
+        assert book.read_window("S", cols=(3, 5)) == book.read_range("S", "D1:E4")
+        # Windows are truncated at the end of the used range...
+        assert book.read_window("S", rows=(2, 1000), cols=(4, 1000)) == book.read_range(
+            "S", "E3:E4"
+        )
+        # ...and are empty when they start beyond it or have zero extent.
+        assert book.read_window("S", rows=(10, 20)) == []
+        assert book.read_window("S", cols=(5, 9)) == []
+        assert book.read_window("S", rows=(1, 1)) == []
+        with pytest.raises(ValueError, match="Invalid rows window"):
+            book.read_window("S", rows=(3, 1))
+        with pytest.raises(ValueError, match="Unknown sheet"):
+            book.read_window("Missing", rows=(0, 1))
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_build_info_reports_locked_backend_versions() -> None:
+    import tomllib
+
+    rust = pytest.importorskip("wolfxl._rust")
+    info = rust.build_info()
+    if "backend_sources" not in info:
+        pytest.skip("wolfxl._rust predates build-time dependency versions")
+
+    lock_path = Path(__file__).parent.parent / "rust/excelbench_rust/Cargo.lock"
+    locked = {
+        pkg["name"]: pkg
+        for pkg in tomllib.loads(lock_path.read_text())["package"]
+        if pkg["name"] in {"calamine", "rust_xlsxwriter", "umya-spreadsheet"}
+    }
+    enabled = _enabled_backends(rust)
+    versions = info["backend_versions"]
+    sources = info["backend_sources"]
+    assert set(versions) == set(sources) == set(locked)
+    for key, pkg in locked.items():
+        if key not in enabled:
+            assert versions[key] is None
+            assert sources[key] is None
+            continue
+        assert versions[key] == pkg["version"]
+        source = pkg.get("source", "")
+        if source.startswith("registry+"):
+            assert sources[key] == "crates.io"
+        elif source.startswith("git+"):
+            repo, commit = source.split("#")
+            assert sources[key] == f"{repo.split('?')[0]}#{commit[:12]}"
+
+    from excelbench.harness.adapters.rust_adapter_utils import get_rust_backend_version
+
+    for key in enabled & set(locked):
+        assert get_rust_backend_version(key) == locked[key]["version"]
+
+
+def test_rust_calamine_time_cells_follow_number_format() -> None:
+    from openpyxl.utils.datetime import CALENDAR_MAC_1904
+
+    rust = pytest.importorskip("wolfxl._rust")
+    if "calamine" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without calamine backend")
+    openpyxl = pytest.importorskip("openpyxl")
+
+    tmp = Path(tempfile.mkdtemp())
+    paths = [tmp / "times1900.xlsx", tmp / "times1904.xlsx", tmp / "out.json", tmp / "out.csv"]
+    try:
+        for path in paths[:2]:
+            wb = openpyxl.Workbook()
+            if path.stem.endswith("1904"):
+                wb.epoch = CALENDAR_MAC_1904
+            ws = wb.active
+            ws.title = "S"
+            # A full datetime shown through a time-only format is a time.
+            ws["A1"] = datetime(2024, 6, 15, 10, 30)
+            ws["A1"].number_format = "h:mm"
+            ws["A2"] = time(6, 0)
+            ws["A2"].number_format = "hh:mm:ss AM/PM"
+            # A day fraction under a date format stays a datetime.
+            ws["A3"] = 0.5
+            ws["A3"].number_format = "yyyy-mm-dd hh:mm"
+            ws["A4"] = datetime(2024, 6, 15)
+            ws["A4"].number_format = "yyyy-mm-dd"
+            wb.save(path)
+
+            book = rust.CalamineBook.open(str(path))
+            assert book.read_cell_value("S", "A1") == {"type": "time", "value": "10:30:00"}
+            assert book.read_cell_value("S", "A2") == {"type": "time", "value": "06:00:00"}
+            assert book.read_cell_value("S", "A3")["type"] == "datetime"
+            assert book.read_cell_value("S", "A4") == {"type": "date", "value": "2024-06-15"}
+
+            column = [book.read_cell_value("S", f"A{r}") for r in range(1, 5)]
+            assert [row[0] for row in book.read_sheet("S")] == column
+            assert [row[0] for row in book.read_range("S", "A1:A4")] == column
+            assert [row[0] for row in book.iter_rows("S")] == column
+            assert book.export_json("S", str(paths[2]), orient="rows") == 4
+            assert [row[0] for row in json.loads(paths[2].read_text())] == column
+            assert book.export_csv("S", str(paths[3])) == 4
+            assert paths[3].read_text().splitlines()[:2] == ["10:30:00", "06:00:00"]
+
+            # Streaming before any other read scans the time cells itself.
+            fresh = rust.CalamineBook.open(str(path))
+            assert [row[0] for row in fresh.iter_rows("S")] == column
+    finally:
+        for p in paths:
+            p.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_umya_column_letters_bounded_to_xfd() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "umya-spreadsheet" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without umya backend")
+
+    book = rust.UmyaBook()
+    book.add_sheet("S")
+    book.set_column_width("S", "xfd", 12.0)
+    assert book.read_column_width("S", "XFD") == 12.0
+    for letters in ("XFE", "AAAA", "ZZZZZZZZ", "A1", ""):
+        with pytest.raises(ValueError, match="Invalid column"):
+            book.set_column_width("S", letters, 12.0)
+        with pytest.raises(ValueError, match="Invalid column"):
+            book.read_column_width("S", letters)
+
+
+def test_rust_xlsxwriter_failed_save_can_be_retried() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("wolfxl._rust compiled without rust_xlsxwriter backend")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "retry.xlsx"
+    try:
+        wb = rust.RustXlsxWriterBook()
+        wb.add_sheet("S")
+        wb.write_cell_value("S", "A1", {"type": "number", "value": 1})
+        with pytest.raises(OSError):
+            wb.save(str(tmp / "missing" / "retry.xlsx"))
+        wb.save(str(path))
+        assert path.exists()
+        with pytest.raises(ValueError, match="already saved"):
+            wb.save(str(path))
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
+def test_rust_bad_ranges_and_columns_raise_cell_ref_error() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if not hasattr(rust, "CellRefError"):
+        pytest.skip("structured exceptions not available in this build")
+
+    fixture = Path(__file__).parent.parent / "fixtures/excel/tier1/01_cell_values.xlsx"
+    enabled = _enabled_backends(rust)
+    cases: list[tuple[str, Any, str]] = []
+    if "calamine" in enabled:
+        book = rust.CalamineBook.open(str(fixture))
+        first = book.sheet_names()[0]
+        cases.append(("calamine", lambda: book.read_range(first, "A1:nope"), "A1:nope"))
+        styled = rust.CalamineStyledBook.open(str(fixture))
+        cases.append(("calamine", lambda: styled.read_column_width(first, "A1"), "A1"))
+    if "wolfxl" in enabled:
+        reader = rust.XlsxReader.open(str(fixture))
+        name = reader.sheet_names()[0]
+        cases.append(("wolfxl", lambda: reader.read_range(name, "A1:nope"), "A1:nope"))
+    if "umya-spreadsheet" in enabled:
+        umya = rust.UmyaBook()
+        umya.add_sheet("S")
+        cases.append(("umya-spreadsheet", lambda: umya.read_column_width("S", "XFE"), "XFE"))
+        cases.append(("umya-spreadsheet", lambda: umya.set_column_width("S", "1", 9.0), "1"))
+    if not cases:
+        pytest.skip("no reader backends enabled in this build")
+
+    for backend, call, cell in cases:
+        with pytest.raises(rust.CellRefError) as info:
+            call()
+        assert (info.value.backend, info.value.cell) == (backend, cell)
+
+
+def test_rust_xlsxwriter_post_save_patches_share_one_rewrite() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if "rust_xlsxwriter" not in _enabled_backends(rust):
+        pytest.skip("rust_xlsxwriter backend not enabled in this build")
+
+    tmp = Path(tempfile.mkdtemp())
+    path = tmp / "patched.xlsx"
+    try:
+        wb = rust.RustXlsxWriterBook()
+        wb.add_sheet("S")
+        wb.write_cell_value("S", "A1", {"type": "number", "value": 1})
+        # Split panes and zeroHeight both edit sheet1.xml.
+        wb.set_freeze_panes("S", {"mode": "split", "x_split": 2000, "y_split": 1000})
+        wb.hide_unused_rows("S")
+        wb.set_calc_mode("manual")
+        wb.save(str(path))
+
+        with zipfile.ZipFile(path) as zf:
+            sheet_xml = zf.read("xl/worksheets/sheet1.xml").decode("utf-8")
+            wb_xml = zf.read("xl/workbook.xml").decode("utf-8")
+            assert not any(name.endswith(".tmp") for name in zf.namelist())
+        assert 'state="split"' in sheet_xml
+        assert 'zeroHeight="1"' in sheet_xml
+        assert 'calcMode="manual"' in wb_xml
+        assert not (tmp / "patched.xlsx.tmp").exists()
+    finally:
+        path.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
 def test_rust_calamine_datetime_semantics() -> None:
     rust = pytest.importorskip("wolfxl._rust")
     enabled = _enabled_backends(rust)
@@ -81,6 +4147,155 @@ def test_rust_calamine_datetime_semantics() -> None:
         path.unlink(missing_ok=True)
 
 
+def test_wolfxl_read_matches_openpyxl_values() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(rust, "XlsxReader", None) is None:
+        pytest.skip("wolfxl._rust compiled without wolfxl backend")
+
+    import openpyxl
+
+    from excelbench.harness.adapters.rust_wolfxl_read_adapter import RustWolfxlReadAdapter
+    from excelbench.models import CellType
+
+    f = tempfile.NamedTemporaryFile(suffix=".xlsx", delete=False)
+    path = Path(f.name)
+    f.close()
+    try:
+        wb = openpyxl.Workbook()
+        ws = wb.active
+        ws.title = "S"
+        ws["A1"] = "hello"
+        ws["B1"] = 42.5
+        ws["C1"] = True
+        ws["A2"] = date(2024, 6, 15)
+        ws["B2"] = datetime(2024, 6, 15, 10, 30, 0)
+        ws["A4"] = "=B1*2"
+        wb.save(path)
+
+        adapter = RustWolfxlReadAdapter()
+        book = adapter.open_workbook(path)
+        assert adapter.get_sheet_names(book) == ["S"]
+        assert adapter.read_cell_value(book, "S", "A1").value == "hello"
+        assert adapter.read_cell_value(book, "S", "B1").value == 42.5
+        assert adapter.read_cell_value(book, "S", "C1").type == CellType.BOOLEAN
+        assert adapter.read_cell_value(book, "S", "A2").type == CellType.DATE
+        assert adapter.read_cell_value(book, "S", "B2").type == CellType.DATETIME
+        assert adapter.read_cell_value(book, "S", "A4").type == CellType.FORMULA
+        assert adapter.read_cell_value(book, "S", "Z9").type == CellType.BLANK
+
+        rows = list(book.iter_rows("S"))
+        assert len(rows) == 4
+        assert rows[2] == []
+        assert [c["type"] for c in rows[0]] == ["string", "number", "boolean"]
+        assert len(book.read_range("S", "A1:C2")) == 2
+    finally:
+        path.unlink(missing_ok=True)
+
+
+def test_fixture_builder_manifest_matches_workbook() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(rust, "FixtureBuilder", None) is None:
+        pytest.skip("wolfxl._rust compiled without rust_xlsxwriter backend")
+
+    import openpyxl
+
+    f = tempfile.NamedTemporaryFile(suffix=".xlsx", delete=False)
+    path = Path(f.name)
+    f.close()
+    try:
+        builder = rust.FixtureBuilder(
+            rows=21,
+            cols=4,
+            sheets=2,
+            string_ratio=0.3,
+            formula_ratio=0.2,
+            style_density=0.5,
+            merge_pattern="block",
+            merge_every=5,
+            seed=7,
+        )
+        manifest = builder.build(str(path))
+        assert manifest["sheet_names"] == ["Sheet1", "Sheet2"]
+        assert manifest["merges"] == 8
+        assert manifest["bytes"] == path.stat().st_size
+
+        wb = openpyxl.load_workbook(str(path))
+        assert wb.sheetnames == ["Sheet1", "Sheet2"]
+        ws = wb["Sheet1"]
+        assert (ws.max_row, ws.max_column) == (21, 4)
+        assert ws["A1"].value == "col_A"
+        assert len(ws.merged_cells.ranges) == 4
+        formulas = sum(
+            1
+            for sheet in wb.worksheets
+            for row in sheet.iter_rows()
+            for cell in row
+            if isinstance(cell.value, str) and cell.value.startswith("=")
+        )
+        assert formulas == manifest["formulas"]
+
+        # Same seed, same workbook.
+        again = rust.FixtureBuilder(
+            rows=21,
+            cols=4,
+            sheets=2,
+            string_ratio=0.3,
+            formula_ratio=0.2,
+            style_density=0.5,
+            merge_pattern="block",
+            merge_every=5,
+            seed=7,
+        ).build(str(path))
+        assert {k: v for k, v in again.items() if k != "bytes"} == {
+            k: v for k, v in manifest.items() if k != "bytes"
+        }
+
+        with pytest.raises(ValueError):
+            rust.FixtureBuilder(string_ratio=0.8, formula_ratio=0.5)
+    finally:
+        path.unlink(missing_ok=True)
+
+
+def test_validate_xlsx_reports_broken_packages() -> None:
+    rust = pytest.importorskip("wolfxl._rust")
+    if getattr(rust, "validate_xlsx", None) is None:
+        pytest.skip("wolfxl._rust compiled without OOXML support")
+
+    import zipfile
+
+    import openpyxl
+
+    tmp = Path(tempfile.mkdtemp())
+    good = tmp / "good.xlsx"
+    broken = tmp / "broken.xlsx"
+    try:
+        wb = openpyxl.Workbook()
+        wb.active["A1"] = "hello"
+        wb.active["B2"] = 3
+        wb.save(good)
+
+        report = rust.validate_xlsx(str(good))
+        assert report["valid"] is True
+        assert report["errors"] == 0
+
+        # Drop the shared-string table but keep everything that references it.
+        with zipfile.ZipFile(good) as src, zipfile.ZipFile(broken, "w") as dst:
+            for item in src.infolist():
+                if item.filename != "xl/sharedStrings.xml":
+                    dst.writestr(item, src.read(item.filename))
+
+        report = rust.validate_xlsx(str(broken))
+        assert report["valid"] is False
+        codes = {f["code"] for f in report["findings"]}
+        assert "rels.missing_target" in codes
+        assert "sheet.sst_missing" in codes
+        assert all(f["severity"] in {"error", "warning"} for f in report["findings"])
+    finally:
+        good.unlink(missing_ok=True)
+        broken.unlink(missing_ok=True)
+        tmp.rmdir()
+
+
 def test_rust_xlsxwriter_preserves_sheet_insertion_order() -> None:
     rust = pytest.importorskip("wolfxl._rust")
     enabled = _enabled_backends(rust)
@@ -417,6 +4632,55 @@ def test_rust_xlsxwriter_writes_alignment() -> None:
         path.unlink(missing_ok=True)
 
 
+def test_write_backends_share_payload_keys_and_errors() -> None:
+    """Alias keys and malformed dicts behave the same on every write backend."""
+    rust = pytest.importorskip("wolfxl._rust")
+    enabled = _enabled_backends(rust)
+    books = []
+    if "rust_xlsxwriter" in enabled:
+        books.append(rust.RustXlsxWriterBook())
+    if "umya-spreadsheet" in enabled:
+        books.append(rust.UmyaBook())
+    if not books:
+        pytest.skip("wolfxl._rust compiled without a write backend")
+
+    import openpyxl
+
+    for book in books:
+        book.add_sheet("S")
+        with pytest.raises(ValueError, match="payload missing 'type'"):
+            book.write_cell_value("S", "A1", {"value": 1})
+        with pytest.raises(ValueError, match="number payload missing 'value'"):
+            book.write_cell_value("S", "A1", {"type": "number"})
+        with pytest.raises(ValueError, match="Unsupported cell type: money"):
+            book.write_cell_value("S", "A1", {"type": "money", "value": 1})
+        with pytest.raises(ValueError, match="Invalid ISO date"):
+            book.write_cell_value("S", "A1", {"type": "date", "value": "yesterday"})
+        with pytest.raises(ValueError, match="format key 'bold' must be a bool"):
+            book.write_cell_format("S", "A1", {"bold": "very"})
+        with pytest.raises(ValueError, match="border key 'top' must be a dict"):
+            book.write_cell_border("S", "A1", {"top": "thin"})
+
+        book.write_cell_value("S", "A1", {"type": "str", "value": "x"})
+        book.write_cell_format(
+            "S", "A1", {"horizontal": "center", "wrap_text": True, "bold": None}
+        )
+
+        f = tempfile.NamedTemporaryFile(suffix=".xlsx", delete=False)
+        path = Path(f.name)
+        f.close()
+        try:
+            book.save(str(path))
+            wb = openpyxl.load_workbook(str(path))
+            cell = wb["S"]["A1"]
+            assert cell.value == "x"
+            assert cell.alignment.horizontal == "center"
+            assert cell.alignment.wrapText is True
+            wb.close()
+        finally:
+            path.unlink(missing_ok=True)
+
+
 def test_rust_xlsxwriter_writes_row_height_and_col_width() -> None:
     """Write row height + col width via rust_xlsxwriter, verify with openpyxl."""
     _skip_unless_rust_xlsxwriter()